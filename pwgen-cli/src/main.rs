@@ -1,27 +1,53 @@
+#![allow(clippy::too_many_arguments)]
+
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use pwgen_core::{
-    backup::{BackupManager, ConflictResolution, RestoreOptions},
-    crypto::hash_entry_id,
-    generator::{PasswordConfig, PasswordGenerator},
-    models::{DecryptedPasswordEntry, SearchFilter},
-    storage::Storage,
+    audit_export::{write_audit_events, AuditFormat},
+    backup::{
+        export_age, import_age, AgeIdentity, AgeRecipient, BackupManager, BackupProgress, BackupStage,
+        ConflictResolution, RestoreOptions, RestoreResult,
+    },
+    crypto::{hash_entry_id, Argon2Params, KeyFile, RecoveryKey},
+    entry_rules::EntryRules,
+    export::{decrypt_export, encrypt_export, write_export, ExportFormat},
+    external_editor::{edit_in_external_editor, secure_temp_dir},
+    fs::write_private_file,
+    generator::{PasswordConfig, PasswordGenerator, Wordlist},
+    identity::{load_device_name, save_device_name},
+    models::{
+        generate_totp, normalize_site, normalize_totp_secret, totp_seconds_remaining, DecryptedPasswordEntry,
+        SearchFilter, SortOrder,
+    },
+    storage::{Storage, DEFAULT_PASSWORD_HISTORY_LIMIT},
+    strength::{estimate_strength, GUESSES_PER_SECOND},
 };
 use pwgen_core::secrets::{
-    DecryptedSecretEntry, SecretData, SecretFilter, SecretMetadata, SecretType,
+    DecryptedSecretEntry, SecretData, SecretFilter, SecretMetadata, SecretSortField, SecretType,
     NoteFormat, SshKeyType, ConfigFormat, DatabaseType, SslConfig
 };
 use pwgen_core::secrets_storage::SecretsStorage;
+use pwgen_core::cards::{self, CardBrand};
 use pwgen_core::ssh_keys::{SshKeyManager, SshKeyGenParams, SshKeyUtils};
-use pwgen_core::document_storage::{DocumentManager, DocumentAttachment, DocumentType};
+use pwgen_core::document_storage::{
+    DocumentAttachment, DocumentImportOptions, DocumentManager, DocumentType, DEFAULT_MAX_DOCUMENT_SIZE,
+};
 use pwgen_core::api_keys::{ApiKeyManager, ApiKeyProvider, RotationInfo, UsageStats};
 use pwgen_core::notes_config::{NotesConfigManager, NoteCategory, ConfigType, NotePriority};
-use pwgen_core::env_connections::{EnvConnectionManager, EnvironmentType, ConnectionType, EnvVarType, EnvVariable};
+use pwgen_core::env_connections::{EnvConnectionManager, EnvDiffKind, EnvironmentType, ConnectionType, EnvVarType, EnvVariable};
 use pwgen_core::secret_templates::{SecretTemplateManager, TemplateCategory};
-use pwgen_core::browser_import::{BrowserImporter, BrowserType, ImportFormat, ImportConfig};
-use pwgen_core::team_sharing::{TeamSharingManager, Permission};
-use std::path::PathBuf;
-use tracing_subscriber;
+use pwgen_core::browser_import::{BrowserImporter, BrowserType, CsvColumnMapping, ImportFormat, ImportConfig, UrlCleanupRules};
+use pwgen_core::team_sharing::{AccessAction, TeamSharingManager, Permission, OneTimeShareStatus, MAX_SHARE_REDEEM_ATTEMPTS};
+use pwgen_core::integrity::{IntegrityReport, ItemKind};
+use pwgen_core::leak_scan;
+use pwgen_core::sample_data::sample_entries;
+use pwgen_core::snippets::command_snippet;
+use pwgen_core::status::VaultStatus;
+use pwgen_core::totp::parse_totp_import;
+use pwgen_core::vault_audit::{AuditChecks, AuditedEntry, VaultAudit};
+use zeroize::Zeroize;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -29,15 +55,49 @@ struct Cli {
     #[arg(short, long, value_name = "FILE", default_value = "~/.pwgen/vault.db")]
     vault: PathBuf,
     
+    /// Key file required, along with the master password, to unlock the vault.
+    /// With `init`, a new random key file is written if it doesn't exist.
+    #[arg(long, global = true, value_name = "FILE")]
+    keyfile: Option<PathBuf>,
+    
+    /// Number of previous passwords kept per entry when a password changes
+    #[arg(long, global = true, value_name = "N", default_value_t = DEFAULT_PASSWORD_HISTORY_LIMIT)]
+    history_limit: usize,
+    
+    /// Before running the command, warn about expired secrets, secrets
+    /// expiring within DAYS (30 by default) and API keys due for rotation.
+    /// Asks for the master password separately from the command itself.
+    #[arg(long, global = true, value_name = "DAYS", num_args = 0..=1, require_equals = true, default_missing_value = "30")]
+    check_expiring: Option<i64>,
+    
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+// `--output` for list and get commands. Not a global flag, since commands
+// that write a file already use `--output <FILE>`.
+#[derive(Args)]
+struct OutputArgs {
+    /// text, or json for scripts. Secret values are only included with --show
+    #[arg(long = "output", value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     Init {
         #[arg(short, long)]
         force: bool,
+        /// Also generate a recovery key that unlocks the vault if the master
+        /// password is forgotten; it is shown only once
+        #[arg(long)]
+        recovery_key: bool,
     },
     
     Add {
@@ -51,6 +111,9 @@ enum Commands {
         notes: Option<String>,
         #[arg(short, long)]
         tags: Vec<String>,
+        /// Prompt for a two-factor secret (base32 key or otpauth:// URI)
+        #[arg(long)]
+        totp: bool,
     },
     
     Get {
@@ -61,6 +124,33 @@ enum Commands {
         copy: bool,
         #[arg(short, long)]
         show: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    
+    /// Print the current two-factor code of an entry, or import seeds from
+    /// an authenticator app
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Totp {
+        #[command(subcommand)]
+        action: Option<TotpAction>,
+        #[arg(required = true)]
+        site: Option<String>,
+        #[arg(short, long)]
+        username: Option<String>,
+    },
+    
+    /// List the previous passwords of an entry, or restore one of them
+    History {
+        site: String,
+        #[arg(short, long)]
+        username: Option<String>,
+        /// Print the previous passwords, not only when they were changed
+        #[arg(short, long)]
+        show: bool,
+        /// Make the Nth previous password (as listed) current again
+        #[arg(short, long, value_name = "N")]
+        restore: Option<usize>,
     },
     
     List {
@@ -70,6 +160,8 @@ enum Commands {
         tags: Vec<String>,
         #[arg(short, long)]
         favorites: bool,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     
     Update {
@@ -81,6 +173,9 @@ enum Commands {
         notes: Option<String>,
         #[arg(short, long)]
         tags: Vec<String>,
+        /// Prompt for a new two-factor secret; enter nothing to remove it
+        #[arg(long)]
+        totp: bool,
     },
     
     Delete {
@@ -90,6 +185,29 @@ enum Commands {
         force: bool,
     },
     
+    /// Replace an entry's password with a newly generated one; the old one
+    /// goes to the password history
+    Rotate {
+        site: String,
+        /// Required when more than one entry matches the site
+        #[arg(short, long)]
+        username: Option<String>,
+        /// Password length [default: the entry's site rules, or 16]
+        #[arg(short, long)]
+        length: Option<usize>,
+        /// Generate from a format string, as with 'generate --pattern'
+        #[arg(long, conflicts_with = "length")]
+        pattern: Option<String>,
+        /// Read --pattern as a template, as with 'generate --template'
+        #[arg(long, requires = "pattern")]
+        template: bool,
+        /// Print the new password
+        #[arg(short, long)]
+        show: bool,
+        #[arg(short, long)]
+        copy: bool,
+    },
+    
     Generate {
         #[arg(short, long, default_value = "16")]
         length: usize,
@@ -105,26 +223,90 @@ enum Commands {
         symbols: Option<String>,
         #[arg(short, long)]
         escape: bool,
+        /// Only use symbols that are easy to type on phone keyboards
+        #[arg(long)]
+        mobile_friendly: bool,
         #[arg(long)]
         passphrase: bool,
         #[arg(long, default_value = "4")]
         words: usize,
         #[arg(long, default_value = "-")]
         separator: String,
+        /// Passphrase words: "eff-long", "eff-short" or "diceware" for a
+        /// built-in list, or a word list file, e.g. a Diceware list in another
+        /// language (dice roll numbers are skipped)
+        #[arg(long, default_value = "eff-long", requires = "passphrase")]
+        wordlist: String,
+        /// Add a digit to one of the passphrase words
+        #[arg(long, requires = "passphrase")]
+        with_number: bool,
+        /// Generate from a format string: X = uppercase, x = lowercase,
+        /// 9 = digit, * = any character; anything else is literal (escape with \)
+        #[arg(long, conflicts_with = "passphrase")]
+        pattern: Option<String>,
+        /// Read --pattern as a template instead: L = uppercase, l = lowercase,
+        /// d = digit, s = symbol, {text} = literal, e.g. "Llll-dddd-s"
+        #[arg(long, requires = "pattern")]
+        template: bool,
+        /// Alternating consonants and vowels that are easy to read and type;
+        /// --no-numbers and --no-symbols drop the trailing digits and symbol
+        #[arg(long, conflicts_with_all = ["passphrase", "pattern"])]
+        pronounceable: bool,
+    },
+    
+    /// Rate a password: 0-4 score, estimated guesses and crack time, and
+    /// what weakens it
+    Strength {
+        /// Password to rate; read from stdin when omitted or "-" (prompted
+        /// for without echo on a terminal)
+        password: Option<String>,
     },
     
     Import {
+        /// age restores a file written by `export --format age`
         #[arg(short, long)]
         format: String,
-        #[arg(short, long)]
+        #[arg(long)]
         file: PathBuf,
+        /// age identity file (from age-keygen) to decrypt with; without it
+        /// the passphrase is asked for
+        #[arg(long)]
+        identity: Option<PathBuf>,
+        /// What to do with items matching an existing one: overwrite, skip,
+        /// merge (keep the newer) or keep-both
+        #[arg(long, default_value = "merge")]
+        conflict_resolution: String,
     },
     
+    /// Export passwords in plaintext for another password manager or a
+    /// browser, or encrypted with age for an offline backup
     Export {
+        /// csv (browser-compatible), json, 1password (1PIF) or age (JSON
+        /// with every entry and secret, encrypted with age)
         #[arg(short, long)]
         format: String,
         #[arg(short, long)]
         output: PathBuf,
+        /// Include secrets as well as password entries
+        #[arg(long)]
+        include_secrets: bool,
+        /// Only items changed since this time (RFC3339 or YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// Encrypt the file with a separate password, as backups are
+        #[arg(long)]
+        encrypt: bool,
+        /// age public key (age1...) to encrypt an age export to; repeat for
+        /// several. Without any, a passphrase is asked for
+        #[arg(long = "recipient", value_name = "PUBKEY")]
+        recipients: Vec<String>,
+    },
+    
+    /// Decrypt a file written by `export --encrypt`
+    DecryptExport {
+        file: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
     },
     
     Backup {
@@ -139,6 +321,8 @@ enum Commands {
     Restore {
         #[arg(short, long)]
         backup_file: PathBuf,
+        /// What to do with entries matching an existing one by ID or by site
+        /// and username: overwrite, skip, merge (keep the newer) or keep-both
         #[arg(long, default_value = "merge")]
         conflict_resolution: String,
     },
@@ -148,6 +332,8 @@ enum Commands {
     },
     
     // Secrets management commands
+    /// Add a secret, prompting for its fields. Without a terminal on stdin,
+    /// fields come from --value and the --*-stdin flags instead.
     AddSecret {
         #[arg(short, long)]
         name: String,
@@ -159,6 +345,17 @@ enum Commands {
         tags: Vec<String>,
         #[arg(long)]
         template: Option<String>,
+        /// Read the password (or card number, for credit-card) from stdin
+        #[arg(long, conflicts_with = "api_key_stdin")]
+        password_stdin: bool,
+        /// Read the API key from stdin
+        #[arg(long)]
+        api_key_stdin: bool,
+        /// Set a field without prompting, e.g. `--value username=deploy`.
+        /// Values on the command line are visible to other processes, so
+        /// pass secret values with --password-stdin or --api-key-stdin.
+        #[arg(long = "value", value_name = "KEY=VALUE", value_parser = parse_field_value)]
+        values: Vec<(String, String)>,
     },
     
     GetSecret {
@@ -167,6 +364,8 @@ enum Commands {
         show: bool,
         #[arg(short, long)]
         copy: bool,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     
     ListSecrets {
@@ -184,6 +383,14 @@ enum Commands {
         favorites: bool,
         #[arg(long)]
         expiring: Option<i64>,
+        /// Sort by name, type, created, updated or expires (default: updated)
+        #[arg(long)]
+        sort: Option<String>,
+        /// Sort order: asc or desc (default: asc with --sort, otherwise desc)
+        #[arg(long)]
+        order: Option<String>,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     
     UpdateSecret {
@@ -247,6 +454,26 @@ enum Commands {
         folders_as_tags: bool,
         #[arg(short, long)]
         tags: Vec<String>,
+        /// Name entries from a template, e.g. "{domain} ({username})". Placeholders:
+        /// {name}, {domain}, {host}, {username}, {folder}
+        #[arg(long)]
+        name_template: Option<String>,
+        /// Keep URL query strings instead of stripping them
+        #[arg(long)]
+        keep_query: bool,
+        /// Drop a leading "www." from URL hosts
+        #[arg(long)]
+        strip_www: bool,
+        /// Import URLs exactly as they appear in the export
+        #[arg(long)]
+        raw_urls: bool,
+        /// Show the names, URLs and usernames entries would get, without importing
+        #[arg(long)]
+        preview: bool,
+        /// Read a CSV of any layout by mapping columns to fields, e.g.
+        /// "site=1,username=3,password=4" (1-based numbers or header names)
+        #[arg(long, value_name = "MAPPING")]
+        map: Option<CsvColumnMapping>,
     },
     
     ListBrowserPaths {
@@ -307,6 +534,16 @@ enum Commands {
         format: Option<String>,
     },
     
+    /// Write every stored SSH public key in authorized_keys format
+    ExportSshPubkeys {
+        /// File to write; prints to stdout when omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Only export keys with one of these tags
+        #[arg(short, long)]
+        tags: Vec<String>,
+    },
+    
     SshKeyInfo {
         name: String,
     },
@@ -317,16 +554,25 @@ enum Commands {
         remove_passphrase: bool,
     },
     
+    /// Work with the running ssh-agent
+    Ssh {
+        #[command(subcommand)]
+        action: SshAction,
+    },
+    
     // Document management commands
     ImportDocument {
         #[arg(short, long)]
         name: String,
         #[arg(short, long)]
         file_path: PathBuf,
-        #[arg(short, long)]
+        #[arg(long)]
         document_type: Option<String>,
         #[arg(long)]
         compress: bool,
+        /// Refuse files larger than this many megabytes
+        #[arg(long, value_name = "MB", default_value_t = DEFAULT_MAX_DOCUMENT_SIZE / (1024 * 1024))]
+        max_size: u64,
         #[arg(short, long)]
         description: Option<String>,
         #[arg(short, long)]
@@ -342,7 +588,7 @@ enum Commands {
         content: Option<String>,
         #[arg(long)]
         from_stdin: bool,
-        #[arg(short, long)]
+        #[arg(long)]
         document_type: Option<String>,
         #[arg(short, long)]
         description: Option<String>,
@@ -435,6 +681,8 @@ enum Commands {
         expiring_days: Option<u32>,
         #[arg(short, long)]
         environment: Option<String>,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     
     GetApiKey {
@@ -443,6 +691,11 @@ enum Commands {
         show_secret: bool,
         #[arg(short, long)]
         copy: bool,
+        /// Print a ready-to-run curl command instead of the key details
+        #[arg(long)]
+        as_curl: bool,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     
     UpdateApiKeyUsage {
@@ -493,6 +746,9 @@ enum Commands {
         from_stdin: bool,
         #[arg(long)]
         new_format: Option<String>,
+        /// Edit the content in $VISUAL/$EDITOR via a private temporary file
+        #[arg(long, conflicts_with_all = ["new_content", "from_stdin"])]
+        edit: bool,
     },
     
     ConvertNote {
@@ -530,6 +786,9 @@ enum Commands {
         file: Option<PathBuf>,
         #[arg(long)]
         from_stdin: bool,
+        /// Keep ${VAR} references in env files literal instead of resolving them
+        #[arg(long)]
+        no_interpolate: bool,
         #[arg(short, long)]
         template: Option<String>,
         #[arg(short, long)]
@@ -546,6 +805,9 @@ enum Commands {
         merge: bool,
         #[arg(short, long)]
         file: Option<PathBuf>,
+        /// Edit the whole configuration in $VISUAL/$EDITOR via a private temporary file
+        #[arg(long, conflicts_with_all = ["variable", "file", "merge"])]
+        edit: bool,
     },
     
     ExportConfig {
@@ -600,6 +862,9 @@ enum Commands {
         file: Option<PathBuf>,
         #[arg(long)]
         from_stdin: bool,
+        /// Keep ${VAR} references literal instead of resolving them
+        #[arg(long)]
+        no_interpolate: bool,
         #[arg(short, long)]
         template: Option<String>,
         #[arg(short, long)]
@@ -614,6 +879,51 @@ enum Commands {
         output: Option<PathBuf>,
     },
     
+    /// Flatten selected secrets into one plaintext .env file
+    ExportEnv {
+        /// Secrets to export by name
+        names: Vec<String>,
+        /// Also export every secret with one of these tags
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Prefix for every variable name, e.g. APP_
+        #[arg(short, long, default_value = "")]
+        prefix: String,
+        /// File to write, created with 0600 permissions
+        #[arg(short, long, required_unless_present = "stdout")]
+        output: Option<PathBuf>,
+        /// Print `export KEY=VALUE` lines instead, for eval or direnv
+        #[arg(long, conflicts_with = "output")]
+        stdout: bool,
+    },
+    
+    /// Run a command with an environment set's variables, without writing a .env file
+    Run {
+        /// Environment set whose variables are passed to the command
+        #[arg(long)]
+        env_set: String,
+        /// Only use a set scoped to this environment, e.g. production
+        #[arg(short, long)]
+        environment: Option<String>,
+        /// Command to run and its arguments, after `--`
+        #[arg(trailing_var_arg = true, required = true)]
+        command: Vec<String>,
+    },
+    
+    /// Show which variables differ between two environment sets
+    DiffEnv {
+        /// Set to compare against, e.g. staging
+        a: String,
+        /// Set to compare, e.g. prod
+        b: String,
+        /// Print the differing values instead of masking them
+        #[arg(long)]
+        show_values: bool,
+        /// Also list variables that are the same in both
+        #[arg(long)]
+        all: bool,
+    },
+    
     ValidateEnvVars {
         name: String,
         #[arg(short, long)]
@@ -635,7 +945,8 @@ enum Commands {
         name: String,
         #[arg(short, long)]
         connection_type: String,
-        #[arg(short, long)]
+        // -h is --help, -p is --port and -d is --database
+        #[arg(short = 'H', long)]
         host: String,
         #[arg(short, long)]
         port: Option<u16>,
@@ -643,13 +954,13 @@ enum Commands {
         database: String,
         #[arg(short, long)]
         username: String,
-        #[arg(short, long)]
+        #[arg(long)]
         password: Option<String>,
         #[arg(short, long, default_value = "development")]
         environment: String,
         #[arg(long)]
         ssl_enabled: bool,
-        #[arg(short, long)]
+        #[arg(long)]
         description: Option<String>,
         #[arg(short, long)]
         tags: Vec<String>,
@@ -663,11 +974,34 @@ enum Commands {
         name: String,
     },
     
+    /// Print the psql/mysql/redis-cli/... invocation for a stored connection
+    ConnectionCommand {
+        name: String,
+        #[arg(long)]
+        show_secret: bool,
+        #[arg(short, long)]
+        copy: bool,
+    },
+    
     ListConnections {
         #[arg(short, long)]
         connection_type: Option<String>,
         #[arg(short, long)]
         environment: Option<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    
+    /// Check a repository for stored env/config secret values in plaintext
+    ScanRepo {
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Also scan lines added in every commit of the git history
+        #[arg(long)]
+        history: bool,
+        /// Ignore stored values shorter than this many characters
+        #[arg(long, default_value = "8")]
+        min_length: usize,
     },
     
     // Team sharing commands
@@ -687,6 +1021,10 @@ enum Commands {
         member_name: String,
         #[arg(short, long, default_value = "read")]
         role: String,
+        /// The member's team sharing public key (hex), as shown by `show-team`
+        /// in their vault. Secrets can only be shared with members who have one.
+        #[arg(long)]
+        public_key: Option<String>,
     },
     
     RemoveTeamMember {
@@ -760,6 +1098,204 @@ enum Commands {
         #[arg(short, long, default_value = "100")]
         limit: usize,
     },
+    
+    /// Export the secret audit log for SIEM tools; secret values are never included
+    ExportAudit {
+        /// json (one event per line), csv or cef
+        #[arg(short, long, default_value = "json")]
+        format: String,
+        /// Only events at or after this time (RFC3339 or YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    
+    /// Issue a one-time, expiring share of a secret, unlocked by a generated
+    /// passphrase to send separately
+    ShareOnce {
+        /// Secret name or ID
+        name: String,
+        /// How long the share can be redeemed, e.g. 30m, 24h or 7d
+        #[arg(short, long, default_value = "24h")]
+        expires: String,
+    },
+    
+    /// Redeem a one-time share with its passphrase (no master password needed)
+    RedeemShare {
+        /// Share ID
+        id: String,
+    },
+    
+    /// List one-time shares issued from this vault and their status
+    ListShares {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    
+    /// Revoke an active one-time share
+    RevokeShare {
+        /// Share ID
+        id: String,
+    },
+    
+    /// Check that every password entry and secret decrypts and validates
+    VerifyVault,
+    
+    /// Fill a vault with realistic fake entries for benchmarking and testing
+    SeedTestVault {
+        #[arg(short, long, default_value = "1000")]
+        count: usize,
+        /// Seed even if the vault already contains entries
+        #[arg(short, long)]
+        force: bool,
+    },
+    
+    /// Report vault state without unlocking it: path, backup and audit times,
+    /// key derivation settings
+    Status {
+        /// Print a JSON object instead of text
+        #[arg(long)]
+        json: bool,
+        /// Ask for the master password and include entry and secret counts
+        #[arg(long)]
+        unlock: bool,
+    },
+    
+    /// Change the master password; every entry and secret is re-encrypted
+    ChangePassword,
+    
+    /// Unlock the vault with its recovery key and set a new master password
+    Recover,
+    
+    /// Tune the vault's key derivation settings
+    Security {
+        /// Measure Argon2 on this machine and recommend settings that take
+        /// about 500 ms to unlock
+        #[arg(long, required = true)]
+        kdf_benchmark: bool,
+        /// Upgrade the vault to the recommended settings; settings are only
+        /// ever raised
+        #[arg(long)]
+        apply: bool,
+    },
+    
+    /// Check the database file for corruption and reclaim unused space;
+    /// without flags, both are done
+    Maintenance {
+        /// Rebuild the database file with SQLite VACUUM
+        #[arg(long)]
+        vacuum: bool,
+        /// Run SQLite's integrity check over the database file
+        #[arg(long)]
+        check: bool,
+    },
+    
+    /// Check the vault for problems. Without a check, prints a scored
+    /// summary of weak, reused and old passwords and expiring secrets
+    #[command(args_conflicts_with_subcommands = true)]
+    Audit {
+        #[command(subcommand)]
+        check: Option<AuditCheck>,
+        #[command(flatten)]
+        options: AuditArgs,
+    },
+    
+    /// List, restore or purge deleted entries and secrets
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+    
+    /// Show or set the device name recorded on entries and secrets changed here
+    DeviceName {
+        /// New device or user name, e.g. "alice-laptop"
+        name: Option<String>,
+        /// Stop recording a device name
+        #[arg(long, conflicts_with = "name")]
+        clear: bool,
+    },
+}
+
+#[derive(Args)]
+struct AuditArgs {
+    /// Skip the weak password check
+    #[arg(long)]
+    no_weak: bool,
+    /// Skip the reused password check
+    #[arg(long)]
+    no_reused: bool,
+    /// Skip the old password check
+    #[arg(long)]
+    no_old: bool,
+    /// Report passwords not changed for this many days
+    #[arg(long, default_value_t = 365, value_name = "DAYS")]
+    max_age: i64,
+    /// Also report entries without a 2FA secret for sites that offer 2FA
+    #[arg(long)]
+    missing_totp: bool,
+    /// Skip the expiring secrets and API key rotation check
+    #[arg(long)]
+    no_expiring: bool,
+    /// Report secrets expiring within this many days
+    #[arg(long, default_value_t = 30, value_name = "DAYS")]
+    expiring_within: i64,
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Subcommand)]
+enum AuditCheck {
+    /// List passwords used by more than one entry, with the entries sharing them
+    Duplicates,
+    /// List entries for the same site and username; sites are compared by
+    /// host, so https://www.example.com/login and example.com match
+    DuplicateEntries,
+}
+
+#[derive(Subcommand)]
+enum SshAction {
+    /// Load a stored SSH key into the running ssh-agent without writing it to disk
+    Add {
+        /// Name or ID of the SSH key secret
+        name: String,
+        /// Seconds until the agent forgets the key again
+        #[arg(long)]
+        lifetime: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TotpAction {
+    /// Add seeds from otpauth://totp/ or Google Authenticator
+    /// otpauth-migration:// URIs to the entries for their issuers, creating
+    /// entries for seeds without one
+    Import {
+        /// A URI, or a file with one URI per line
+        source: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    /// List deleted entries and secrets with when they were deleted
+    List,
+    /// Bring back a deleted entry or secret
+    Restore {
+        /// ID, or its first characters, as shown by `trash list`
+        id: String,
+    },
+    /// Permanently remove everything in the trash
+    Empty {
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Show or set how many days deleted items are kept before they are purged
+    Retention {
+        /// Days to keep deleted items; 0 keeps them until the trash is emptied
+        days: Option<u32>,
+    },
 }
 
 #[tokio::main]
@@ -768,65 +1304,117 @@ async fn main() -> Result<()> {
     
     let cli = Cli::parse();
     let vault_path = expand_tilde(&cli.vault);
+    let key_file = cli.keyfile.as_deref().map(expand_tilde);
+    
+    if let Some(within_days) = cli.check_expiring {
+        print_expiry_warnings(&vault_path, key_file.as_deref(), within_days).await?;
+    }
     
     match cli.command {
-        Commands::Init { force } => {
-            init_vault(&vault_path, force).await?;
+        Commands::Init { force, recovery_key } => {
+            init_vault(&vault_path, key_file.as_deref(), force, recovery_key).await?;
+        }
+        
+        Commands::Add { site, username, generate, length, notes, tags, totp } => {
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            add_entry(&storage, site, username, generate, length, notes, tags, totp).await?;
+        }
+        
+        Commands::Get { site, username, copy, show, output } => {
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            get_entry(&storage, &site, username.as_deref(), copy, show, output.format).await?;
+        }
+        
+        Commands::Totp { action: Some(TotpAction::Import { source }), .. } => {
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            import_totp(&storage, &source).await?;
         }
         
-        Commands::Add { site, username, generate, length, notes, tags } => {
-            let storage = open_vault(&vault_path).await?;
-            add_entry(&storage, site, username, generate, length, notes, tags).await?;
+        Commands::Totp { action: None, site, username } => {
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            totp_code(&storage, site.as_deref().unwrap_or_default(), username.as_deref()).await?;
         }
         
-        Commands::Get { site, username, copy, show } => {
-            let storage = open_vault(&vault_path).await?;
-            get_entry(&storage, &site, username.as_deref(), copy, show).await?;
+        Commands::List { query, tags, favorites, output } => {
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            list_entries(&storage, query, tags, favorites, output.format).await?;
         }
         
-        Commands::List { query, tags, favorites } => {
-            let storage = open_vault(&vault_path).await?;
-            list_entries(&storage, query, tags, favorites).await?;
+        Commands::History { site, username, show, restore } => {
+            let mut storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            storage.set_password_history_limit(cli.history_limit);
+            password_history(&storage, &site, username.as_deref(), show, restore).await?;
         }
         
-        Commands::Update { site, username, new_password, notes, tags } => {
-            let storage = open_vault(&vault_path).await?;
-            update_entry(&storage, site, username, new_password, notes, tags).await?;
+        Commands::Update { site, username, new_password, notes, tags, totp } => {
+            let mut storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            storage.set_password_history_limit(cli.history_limit);
+            update_entry(&storage, site, username, new_password, notes, tags, totp).await?;
         }
         
         Commands::Delete { site, username, force } => {
-            let storage = open_vault(&vault_path).await?;
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
             delete_entry(&storage, &site, &username, force).await?;
         }
         
+        Commands::Rotate { site, username, length, pattern, template, show, copy } => {
+            let mut storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            storage.set_password_history_limit(cli.history_limit);
+            rotate_password(
+                &storage, &site, username.as_deref(), length, pattern.as_deref(), template, show, copy
+            ).await?;
+        }
+        
         Commands::Generate { 
             length, no_uppercase, no_lowercase, no_numbers, no_symbols, 
-            symbols, escape, passphrase, words, separator 
+            symbols, escape, mobile_friendly, passphrase, words, separator, wordlist, with_number, pattern,
+            template, pronounceable
         } => {
             generate_password(
                 length, !no_uppercase, !no_lowercase, !no_numbers, !no_symbols,
-                symbols, escape, passphrase, words, separator
+                symbols, escape, mobile_friendly, passphrase, words, separator, &wordlist, with_number, pattern,
+                template, pronounceable
             )?;
         }
         
-        Commands::Import { format, file } => {
-            let storage = open_vault(&vault_path).await?;
-            import_passwords(&storage, &format, &file).await?;
+        Commands::Strength { password } => {
+            password_strength(password)?;
+        }
+        
+        Commands::Import { format, file, identity, conflict_resolution } => {
+            if format.eq_ignore_ascii_case("age") {
+                import_age_file(&vault_path, key_file.as_deref(), &file, identity.as_deref(), &conflict_resolution)
+                    .await?;
+            } else {
+                let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+                import_passwords(&storage, &format, &file).await?;
+            }
+        }
+        
+        Commands::Export { format, output, include_secrets, since, encrypt, recipients } => {
+            if format.eq_ignore_ascii_case("age") {
+                if encrypt || since.is_some() {
+                    return Err(anyhow::anyhow!("--encrypt and --since do not apply to age exports"));
+                }
+                export_age_file(&vault_path, key_file.as_deref(), &output, recipients).await?;
+            } else {
+                export_vault(&vault_path, key_file.as_deref(), &format, &output, include_secrets, since.as_deref(), encrypt)
+                    .await?;
+            }
         }
         
-        Commands::Export { format, output } => {
-            let storage = open_vault(&vault_path).await?;
-            export_passwords(&storage, &format, &output).await?;
+        Commands::DecryptExport { file, output } => {
+            decrypt_export_file(&file, &output)?;
         }
         
         Commands::Backup { output, incremental, since } => {
-            let storage = open_vault(&vault_path).await?;
-            create_backup(&storage, &output, incremental, since).await?;
+            let (storage, secrets_storage) = open_vault_with_secrets(&vault_path, key_file.as_deref()).await?;
+            create_backup(&storage, &secrets_storage, &output, incremental, since).await?;
         }
         
         Commands::Restore { backup_file, conflict_resolution } => {
-            let mut storage = open_vault(&vault_path).await?;
-            restore_backup(&mut storage, &backup_file, conflict_resolution).await?;
+            let (mut storage, secrets_storage) = open_vault_with_secrets(&vault_path, key_file.as_deref()).await?;
+            restore_backup(&mut storage, &secrets_storage, &backup_file, conflict_resolution).await?;
         }
         
         Commands::VerifyBackup { backup_file } => {
@@ -834,28 +1422,39 @@ async fn main() -> Result<()> {
         }
         
         // Secrets management commands
-        Commands::AddSecret { name, secret_type, description, tags, template } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            add_secret(&secrets_storage, name, secret_type, description, tags, template).await?;
+        Commands::AddSecret { name, secret_type, description, tags, template, password_stdin, api_key_stdin, values } => {
+            let stdin_field = if password_stdin {
+                Some(StdinField::Password)
+            } else if api_key_stdin {
+                Some(StdinField::ApiKey)
+            } else {
+                None
+            };
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            add_secret(&secrets_storage, name, secret_type, description, tags, template, stdin_field, values).await?;
         }
         
-        Commands::GetSecret { name, show, copy } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            get_secret(&secrets_storage, &name, show, copy).await?;
+        Commands::GetSecret { name, show, copy, output } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            get_secret(&secrets_storage, &name, show, copy, output.format).await?;
         }
         
-        Commands::ListSecrets { query, secret_type, tags, environment, project, favorites, expiring } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            list_secrets(&secrets_storage, query, secret_type, tags, environment, project, favorites, expiring).await?;
+        Commands::ListSecrets { query, secret_type, tags, environment, project, favorites, expiring, sort, order, output } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            list_secrets(
+                &secrets_storage, query, secret_type, tags, environment, project, favorites, expiring, sort, order,
+                output.format,
+            )
+            .await?;
         }
         
         Commands::UpdateSecret { name, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             update_secret(&secrets_storage, name, description, tags).await?;
         }
         
         Commands::DeleteSecret { name, force } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             delete_secret(&secrets_storage, &name, force).await?;
         }
         
@@ -868,7 +1467,7 @@ async fn main() -> Result<()> {
         }
         
         Commands::CreateFromTemplate { template_id, name, description, tags, interactive } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             create_from_template(&secrets_storage, &template_id, name, description, tags, interactive).await?;
         }
         
@@ -880,10 +1479,18 @@ async fn main() -> Result<()> {
             import_template(&file)?;
         }
         
-        Commands::ImportBrowser { file, browser, format, skip_duplicates, merge_duplicates, folders_as_tags, tags } => {
-            let storage = open_vault(&vault_path).await?;
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            import_browser(&storage, &secrets_storage, &file, browser, format, skip_duplicates, merge_duplicates, folders_as_tags, tags).await?;
+        Commands::ImportBrowser { file, browser, format, skip_duplicates, merge_duplicates, folders_as_tags, tags, name_template, keep_query, strip_www, raw_urls, preview, map } => {
+            let url_rules = UrlCleanupRules { strip_query: !keep_query, strip_www, ..Default::default() };
+            if map.is_some() && format.parse::<ImportFormat>()? != ImportFormat::Csv {
+                anyhow::bail!("--map can only be used with --format csv");
+            }
+            if preview {
+                preview_browser_import(&file, browser, format, name_template, !raw_urls, url_rules, map)?;
+            } else {
+                let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+                let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+                import_browser(&storage, &secrets_storage, &file, browser, format, skip_duplicates, merge_duplicates, folders_as_tags, tags, name_template, !raw_urls, url_rules, map).await?;
+            }
         }
         
         Commands::ListBrowserPaths { browser } => {
@@ -895,64 +1502,75 @@ async fn main() -> Result<()> {
         }
         
         Commands::ExpiringSecrets { within_days } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             show_expiring_secrets(&secrets_storage, within_days).await?;
         }
         
         Commands::SecretsStats => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             show_secrets_stats(&secrets_storage).await?;
         }
         
         // SSH Key management commands
         Commands::GenerateSshKey { name, key_type, bits, comment, with_passphrase, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             generate_ssh_key(&secrets_storage, name, key_type, bits, comment, with_passphrase, description, tags).await?;
         }
         
         Commands::ImportSshKey { name, private_key_file, public_key_file, passphrase, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             import_ssh_key(&secrets_storage, name, private_key_file, public_key_file, passphrase, description, tags).await?;
         }
         
         Commands::ExportSshKey { name, output_dir, public_only, format } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             export_ssh_key(&secrets_storage, &name, &output_dir, public_only, format).await?;
         }
         
+        Commands::ExportSshPubkeys { output, tags } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            export_ssh_pubkeys(&secrets_storage, output.as_deref(), tags).await?;
+        }
+        
         Commands::SshKeyInfo { name } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             show_ssh_key_info(&secrets_storage, &name).await?;
         }
         
         Commands::ChangeSshKeyPassphrase { name, remove_passphrase } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             change_ssh_key_passphrase(&secrets_storage, &name, remove_passphrase).await?;
         }
         
+        Commands::Ssh { action: SshAction::Add { name, lifetime } } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            add_ssh_key_to_agent(&secrets_storage, &name, lifetime).await?;
+        }
+        
         // Document management commands
-        Commands::ImportDocument { name, file_path, document_type, compress, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            import_document(&secrets_storage, name, file_path, document_type, compress, description, tags).await?;
+        Commands::ImportDocument { name, file_path, document_type, compress, max_size, description, tags } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            let options = DocumentImportOptions { compress, max_size: max_size.saturating_mul(1024 * 1024) };
+            import_document(&secrets_storage, name, file_path, document_type, &options, description, tags).await?;
         }
         
         Commands::CreateTextDocument { name, filename, content, from_stdin, document_type, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             create_text_document(&secrets_storage, name, filename, content, from_stdin, document_type, description, tags).await?;
         }
         
         Commands::ExportDocument { name, output_path, verify } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             export_document(&secrets_storage, &name, &output_path, verify).await?;
         }
         
         Commands::DocumentInfo { name } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             show_document_info(&secrets_storage, &name).await?;
         }
         
         Commands::ViewDocument { name, text_only } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             view_document(&secrets_storage, &name, text_only).await?;
         }
         
@@ -962,37 +1580,41 @@ async fn main() -> Result<()> {
         
         // API Key management command handlers
         Commands::CreateApiKey { name, provider, api_key, api_secret, description, tags, expires_days, environment } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             create_api_key(&secrets_storage, name, provider, api_key, api_secret, description, tags, expires_days, environment).await?;
         }
         
         Commands::CreateJwtToken { name, token, issuer, audience, description, tags, expires_days } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             create_jwt_token(&secrets_storage, name, token, issuer, audience, description, tags, expires_days).await?;
         }
         
         Commands::CreateOauthToken { name, access_token, refresh_token, token_secret, scopes, description, tags, expires_days } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             create_oauth_token(&secrets_storage, name, access_token, refresh_token, token_secret, scopes, description, tags, expires_days).await?;
         }
         
-        Commands::ListApiKeys { provider, expired, expiring_days, environment } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            list_api_keys(&secrets_storage, provider, expired, expiring_days, environment).await?;
+        Commands::ListApiKeys { provider, expired, expiring_days, environment, output } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            list_api_keys(&secrets_storage, provider, expired, expiring_days, environment, output.format).await?;
         }
         
-        Commands::GetApiKey { name, show_secret, copy } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            get_api_key(&secrets_storage, &name, show_secret, copy).await?;
+        Commands::GetApiKey { name, show_secret, copy, as_curl, output } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            if as_curl {
+                print_command_snippet(&secrets_storage, &name, show_secret, copy).await?;
+            } else {
+                get_api_key(&secrets_storage, &name, show_secret, copy, output.format).await?;
+            }
         }
         
         Commands::UpdateApiKeyUsage { name, success, error_message } => {
-            let mut secrets_storage = open_secrets_vault(&vault_path).await?;
+            let mut secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             update_api_key_usage(&mut secrets_storage, &name, success, error_message).await?;
         }
         
         Commands::SetupApiKeyRotation { name, rotation_days, reminder_days } => {
-            let mut secrets_storage = open_secrets_vault(&vault_path).await?;
+            let mut secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             setup_api_key_rotation(&mut secrets_storage, &name, rotation_days, reminder_days).await?;
         }
         
@@ -1002,48 +1624,48 @@ async fn main() -> Result<()> {
         
         // Notes management command handlers
         Commands::CreateNote { title, content, from_stdin, format, category, priority, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             create_note(&secrets_storage, title, content, from_stdin, format, category, priority, description, tags).await?;
         }
         
-        Commands::UpdateNote { name, new_title, new_content, from_stdin, new_format } => {
-            let mut secrets_storage = open_secrets_vault(&vault_path).await?;
-            update_note(&mut secrets_storage, &name, new_title, new_content, from_stdin, new_format).await?;
+        Commands::UpdateNote { name, new_title, new_content, from_stdin, new_format, edit } => {
+            let mut secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            update_note(&mut secrets_storage, &name, new_title, new_content, from_stdin, new_format, edit).await?;
         }
         
         Commands::ConvertNote { name, format } => {
-            let mut secrets_storage = open_secrets_vault(&vault_path).await?;
+            let mut secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             convert_note(&mut secrets_storage, &name, format).await?;
         }
         
         Commands::SearchNotes { query, case_sensitive, category } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             search_notes(&secrets_storage, &query, case_sensitive, category).await?;
         }
         
         Commands::ListNotes { category, priority, format } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             list_notes(&secrets_storage, category, priority, format).await?;
         }
         
         // Configuration management command handlers
-        Commands::CreateConfig { name, config_type, format, file, from_stdin, template, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            create_config(&secrets_storage, name, config_type, format, file, from_stdin, template, description, tags).await?;
+        Commands::CreateConfig { name, config_type, format, file, from_stdin, no_interpolate, template, description, tags } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            create_config(&secrets_storage, name, config_type, format, file, from_stdin, !no_interpolate, template, description, tags).await?;
         }
         
-        Commands::UpdateConfig { name, variable, merge, file } => {
-            let mut secrets_storage = open_secrets_vault(&vault_path).await?;
-            update_config(&mut secrets_storage, &name, variable, merge, file).await?;
+        Commands::UpdateConfig { name, variable, merge, file, edit } => {
+            let mut secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            update_config(&mut secrets_storage, &name, variable, merge, file, edit).await?;
         }
         
         Commands::ExportConfig { name, output, format } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             export_config(&secrets_storage, &name, output, format).await?;
         }
         
         Commands::ValidateConfig { name, template } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             validate_config(&secrets_storage, &name, template).await?;
         }
         
@@ -1052,28 +1674,44 @@ async fn main() -> Result<()> {
         }
         
         Commands::ListConfigs { config_type, format } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             list_configs(&secrets_storage, config_type, format).await?;
         }
         
         // Environment variables management command handlers
         Commands::CreateEnvVar { name, variable_name, value, var_type, environment, sensitive, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             create_env_var(&secrets_storage, name, variable_name, value, var_type, environment, sensitive, description, tags).await?;
         }
         
-        Commands::CreateEnvSet { name, environment, file, from_stdin, template, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            create_env_set(&secrets_storage, name, environment, file, from_stdin, template, description, tags).await?;
+        Commands::CreateEnvSet { name, environment, file, from_stdin, no_interpolate, template, description, tags } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            create_env_set(&secrets_storage, name, environment, file, from_stdin, !no_interpolate, template, description, tags).await?;
         }
         
         Commands::GenerateEnvFile { name, output } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             generate_env_file(&secrets_storage, &name, output).await?;
         }
         
+        Commands::ExportEnv { names, tags, prefix, output, stdout: _ } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            export_env(&secrets_storage, names, tags, &prefix, output.as_deref()).await?;
+        }
+        
+        Commands::Run { env_set, environment, command } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            let code = run_with_env(&secrets_storage, &env_set, environment.as_deref(), &command).await?;
+            std::process::exit(code);
+        }
+        
+        Commands::DiffEnv { a, b, show_values, all } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            diff_env(&secrets_storage, &a, &b, show_values, all).await?;
+        }
+        
         Commands::ValidateEnvVars { name, template } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             validate_env_vars(&secrets_storage, &name, template).await?;
         }
         
@@ -1082,13 +1720,13 @@ async fn main() -> Result<()> {
         }
         
         Commands::ListEnvVars { environment, show_sensitive } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             list_env_vars(&secrets_storage, environment, show_sensitive).await?;
         }
         
         // Connection strings management command handlers
         Commands::CreateConnection { name, connection_type, host, port, database, username, password, environment, ssl_enabled, description, tags } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             create_connection(&secrets_storage, name, connection_type, host, port, database, username, password, environment, ssl_enabled, description, tags).await?;
         }
         
@@ -1097,52 +1735,68 @@ async fn main() -> Result<()> {
         }
         
         Commands::TestConnection { name } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             test_connection(&secrets_storage, &name).await?;
         }
         
-        Commands::ListConnections { connection_type, environment } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
-            list_connections(&secrets_storage, connection_type, environment).await?;
+        Commands::ConnectionCommand { name, show_secret, copy } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            print_command_snippet(&secrets_storage, &name, show_secret, copy).await?;
+        }
+        
+        Commands::ListConnections { connection_type, environment, output } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            list_connections(&secrets_storage, connection_type, environment, output.format).await?;
+        }
+        
+        Commands::ScanRepo { path, history, min_length } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            scan_repo(&secrets_storage, &path, history, min_length).await?;
         }
         
         // Team sharing command handlers
         Commands::CreateTeam { name, description, owner_email, owner_name } => {
-            create_team(name, description, owner_email, owner_name).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            create_team(&secrets_storage, name, description, owner_email, owner_name).await?;
         }
         
-        Commands::AddTeamMember { team_id, member_email, member_name, role } => {
-            add_team_member(team_id, member_email, member_name, role).await?;
+        Commands::AddTeamMember { team_id, member_email, member_name, role, public_key } => {
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            add_team_member(&secrets_storage, team_id, member_email, member_name, role, public_key).await?;
         }
         
         Commands::RemoveTeamMember { team_id, member_id } => {
-            remove_team_member(team_id, member_id).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            remove_team_member(&secrets_storage, team_id, member_id).await?;
         }
         
         Commands::UpdateMemberRole { team_id, member_id, new_role } => {
-            update_member_role(team_id, member_id, new_role).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            update_member_role(&secrets_storage, team_id, member_id, new_role).await?;
         }
         
         Commands::ListTeams => {
-            list_teams().await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            list_teams(&secrets_storage).await?;
         }
         
         Commands::ShowTeam { team_id } => {
-            show_team(team_id).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            show_team(&secrets_storage, team_id).await?;
         }
         
         Commands::ShareSecret { secret_name, team_id, permissions, expiration_days } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             share_secret(&secrets_storage, secret_name, team_id, permissions, expiration_days).await?;
         }
         
         Commands::ListSharedSecrets { team_id } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             list_shared_secrets(&secrets_storage, team_id).await?;
         }
         
         Commands::RevokeSecretAccess { secret_name, team_id } => {
-            let secrets_storage = open_secrets_vault(&vault_path).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
             revoke_secret_access(&secrets_storage, secret_name, team_id).await?;
         }
         
@@ -1163,23 +1817,106 @@ async fn main() -> Result<()> {
         }
         
         Commands::ViewAccessLog { secret_name, user_id, limit } => {
-            view_access_log(secret_name, user_id, limit).await?;
+            let secrets_storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            view_access_log(&secrets_storage, secret_name, user_id, limit).await?;
+        }
+        
+        Commands::ExportAudit { format, since, output } => {
+            let storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            export_audit(&storage, &format, since.as_deref(), output.as_deref()).await?;
+        }
+        
+        Commands::ShareOnce { name, expires } => {
+            let storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            share_once(&storage, &name, &expires).await?;
+        }
+        
+        Commands::RedeemShare { id } => {
+            redeem_share(&vault_path, &id).await?;
+        }
+        
+        Commands::ListShares { output } => {
+            let storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            list_shares(&storage, output.format).await?;
+        }
+        
+        Commands::RevokeShare { id } => {
+            let storage = open_secrets_vault(&vault_path, key_file.as_deref()).await?;
+            storage.revoke_one_time_share(&id).await?;
+            println!("✅ Share {} revoked", id);
+        }
+        
+        Commands::VerifyVault => {
+            verify_vault(&vault_path, key_file.as_deref()).await?;
+        }
+        
+        Commands::SeedTestVault { count, force } => {
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            seed_test_vault(&storage, count, force).await?;
+        }
+        
+        Commands::Status { json, unlock } => {
+            vault_status(&vault_path, key_file.as_deref(), json, unlock).await?;
+        }
+        
+        Commands::ChangePassword => {
+            change_master_password(&vault_path, key_file.as_deref()).await?;
+        }
+        
+        Commands::Recover => {
+            recover_vault(&vault_path, key_file.as_deref()).await?;
+        }
+        
+        Commands::Security { kdf_benchmark: _, apply } => {
+            kdf_benchmark(&vault_path, key_file.as_deref(), apply).await?;
+        }
+        
+        Commands::Maintenance { vacuum, check } => {
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            let both = !vacuum && !check;
+            database_maintenance(&storage, vacuum || both, check || both).await?;
+        }
+        
+        Commands::Audit { check: Some(AuditCheck::Duplicates), .. } => {
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            audit_duplicates(&storage).await?;
+        }
+        
+        Commands::Audit { check: Some(AuditCheck::DuplicateEntries), .. } => {
+            let storage = open_vault(&vault_path, key_file.as_deref()).await?;
+            audit_duplicate_entries(&storage).await?;
+        }
+        
+        Commands::Audit { check: None, options } => {
+            audit_vault(&vault_path, key_file.as_deref(), options).await?;
+        }
+        
+        Commands::Trash { action } => {
+            trash(&vault_path, key_file.as_deref(), action).await?;
+        }
+        
+        Commands::DeviceName { name, clear } => {
+            device_name(name, clear)?;
         }
     }
     
     Ok(())
 }
 
-async fn open_secrets_vault(path: &PathBuf) -> Result<SecretsStorage> {
+async fn open_secrets_vault(path: &PathBuf, key_file: Option<&Path>) -> Result<SecretsStorage> {
     if !path.exists() {
         eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
         std::process::exit(1);
     }
     
+    let key_file = read_key_file(key_file)?;
     let password = rpassword::prompt_password("Enter master password: ")?;
     
-    match SecretsStorage::from_existing_storage(path, &password).await {
-        Ok(storage) => Ok(storage),
+    match SecretsStorage::from_existing_storage_with_key_file(path, &password, key_file.as_ref()).await {
+        Ok(mut storage) => {
+            storage.set_device_name(load_device_name());
+            Ok(storage)
+        }
         Err(e) => {
             eprintln!("Failed to open secrets vault: {}", e);
             std::process::exit(1);
@@ -1194,46 +1931,61 @@ async fn add_secret(
     description: Option<String>,
     tags: Vec<String>,
     template: Option<String>,
+    stdin_field: Option<StdinField>,
+    values: Vec<(String, String)>,
 ) -> Result<()> {
     let secret_type = match secret_type_str.as_str() {
         "password" => SecretType::Password,
         "ssh-key" => SecretType::SshKey,
         "api-key" => SecretType::ApiKey,
         "note" => SecretType::SecureNote,
+        "credit-card" => SecretType::CreditCard,
         custom => SecretType::Custom(custom.to_string()),
     };
     
-    let data = match &secret_type {
-        SecretType::Password => {
-            print!("Username: ");
-            use std::io::{self, Write};
-            io::stdout().flush()?;
-            let mut username = String::new();
-            io::stdin().read_line(&mut username)?;
-            
-            let password = rpassword::prompt_password("Password: ")?;
-            
-            SecretData::Password {
-                username: username.trim().to_string(),
-                password,
-                url: None,
-                notes: None,
-            }
+    let (known_fields, stdin_key): (&[&str], _) = match (&secret_type, stdin_field) {
+        (SecretType::Password, None | Some(StdinField::Password)) => {
+            (&["username", "password", "url", "notes"], Some("password"))
         }
-        
-        SecretType::ApiKey => {
-            let api_key = rpassword::prompt_password("API Key: ")?;
-            let provider = ApiKeyProvider::Generic;
-            
-            SecretData::ApiKey {
+        (SecretType::ApiKey, None | Some(StdinField::ApiKey)) => {
+            (&["api_key", "api_secret", "provider", "environment", "endpoint"], Some("api_key"))
+        }
+        (SecretType::SecureNote, None) => (&["title", "content"], None),
+        (SecretType::CreditCard, None | Some(StdinField::Password)) => {
+            (&["cardholder", "number", "expiry", "cvv", "issuer", "billing_zip"], Some("number"))
+        }
+        (SecretType::Password | SecretType::SecureNote | SecretType::ApiKey | SecretType::CreditCard, Some(flag)) => {
+            return Err(anyhow::anyhow!("{} can't be used for {} secrets", flag.flag(), secret_type_str));
+        }
+        _ => {
+            println!("Interactive creation for {} not yet implemented. Use GUI instead.", secret_type_str);
+            return Ok(());
+        }
+    };
+    let mut fields = SecretFields::new(values, known_fields, stdin_field.and(stdin_key))?;
+    
+    let mut expires_at = None;
+    let data = match &secret_type {
+        SecretType::Password => SecretData::Password {
+            username: fields.text("username", "Username: ")?,
+            password: fields.secret("password", "Password: ")?,
+            url: fields.take("url"),
+            notes: fields.take("notes"),
+        },
+        
+        SecretType::ApiKey => {
+            let api_key = fields.secret("api_key", "API Key: ")?;
+            let provider: ApiKeyProvider = fields.take("provider").as_deref().unwrap_or("generic").parse()?;
+            
+            SecretData::ApiKey {
+                key_id: format!("{}_{}", provider.to_string().to_lowercase(), uuid::Uuid::new_v4()),
                 provider,
-                key_id: format!("generic_{}", uuid::Uuid::new_v4()),
                 api_key,
-                api_secret: None,
+                api_secret: fields.take("api_secret"),
                 token_type: "Bearer".to_string(),
                 permissions: Default::default(),
-                environment: "production".to_string(),
-                endpoint_url: None,
+                environment: fields.take("environment").unwrap_or_else(|| "production".to_string()),
+                endpoint_url: fields.take("endpoint"),
                 rotation_info: RotationInfo {
                     auto_rotate: false,
                     rotation_period_days: None,
@@ -1251,29 +2003,41 @@ async fn add_secret(
             }
         }
         
-        SecretType::SecureNote => {
-            print!("Title: ");
-            use std::io::{self, Write};
-            io::stdout().flush()?;
-            let mut title = String::new();
-            io::stdin().read_line(&mut title)?;
-            
-            print!("Content: ");
-            io::stdout().flush()?;
-            let mut content = String::new();
-            io::stdin().read_line(&mut content)?;
-            
-            SecretData::SecureNote {
-                title: title.trim().to_string(),
-                content: content.trim().to_string(),
-                format: NoteFormat::PlainText,
+        SecretType::SecureNote => SecretData::SecureNote {
+            title: fields.text("title", "Title: ")?,
+            content: fields.text("content", "Content: ")?,
+            format: NoteFormat::PlainText,
+        },
+        
+        SecretType::CreditCard => {
+            let cardholder = fields.text("cardholder", "Cardholder name: ")?;
+            let number = cards::normalize_card_number(&fields.secret("number", "Card number: ")?);
+            if !cards::luhn_check(&number) {
+                return Err(anyhow::anyhow!("Card number is not valid, please check it for typos"));
             }
+            let expiry = fields.text("expiry", "Expiry (MM/YY): ")?;
+            let (expiry_month, expiry_year) = cards::parse_expiry(&expiry)
+                .ok_or_else(|| anyhow::anyhow!("Invalid expiry '{}', use MM/YY", expiry))?;
+            let cvv = fields.optional_secret("cvv", "CVV (optional): ")?;
+            let issuer = fields.optional("issuer", "Issuing bank (optional): ")?;
+            let billing_zip = fields.optional("billing_zip", "Billing ZIP (optional): ")?;
+            
+            // Lets `list-secrets --expiring` and the expiry notices cover cards
+            expires_at = cards::card_expires_at(expiry_month, expiry_year);
+            let data = SecretData::CreditCard {
+                cardholder,
+                number,
+                expiry_month,
+                expiry_year,
+                cvv,
+                issuer,
+                billing_zip,
+            };
+            data.validate()?;
+            data
         }
         
-        _ => {
-            println!("Interactive creation for {} not yet implemented. Use GUI instead.", secret_type_str);
-            return Ok(());
-        }
+        _ => unreachable!("unsupported types return above"),
     };
     
     let secret = DecryptedSecretEntry {
@@ -1290,7 +2054,7 @@ async fn add_secret(
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
         last_accessed: None,
-        expires_at: None,
+        expires_at,
         favorite: false,
     };
     
@@ -1300,68 +2064,331 @@ async fn add_secret(
     Ok(())
 }
 
+/// Which `add-secret` field is read from stdin.
+#[derive(Clone, Copy)]
+enum StdinField {
+    Password,
+    ApiKey,
+}
+
+impl StdinField {
+    fn flag(self) -> &'static str {
+        match self {
+            StdinField::Password => "--password-stdin",
+            StdinField::ApiKey => "--api-key-stdin",
+        }
+    }
+}
+
+fn parse_field_value(arg: &str) -> std::result::Result<(String, String), String> {
+    let (key, value) = arg.split_once('=').ok_or("expected KEY=VALUE")?;
+    Ok((key.trim().to_string(), value.to_string()))
+}
+
+/// Field values for `add-secret`, taken from `--value` flags and stdin, or
+/// prompted for when stdin is a terminal. Values that are never used are
+/// wiped on drop.
+struct SecretFields {
+    values: std::collections::HashMap<String, String>,
+    interactive: bool,
+}
+
+impl SecretFields {
+    fn new(values: Vec<(String, String)>, known: &[&str], stdin_key: Option<&str>) -> Result<Self> {
+        let stdin = std::io::stdin();
+        let terminal = stdin.is_terminal();
+        Self::from_reader(values, known, stdin_key, stdin, terminal)
+    }
+    
+    /// `new` reading `input` in place of stdin, which is taken to be a
+    /// terminal when `terminal` is set.
+    fn from_reader(
+        values: Vec<(String, String)>,
+        known: &[&str],
+        stdin_key: Option<&str>,
+        mut input: impl std::io::Read,
+        terminal: bool,
+    ) -> Result<Self> {
+        let mut fields = SecretFields { values: values.into_iter().collect(), interactive: false };
+        if let Some(unknown) = fields.values.keys().find(|key| !known.contains(&key.as_str())) {
+            return Err(anyhow::anyhow!("Unknown field '{}', expected one of: {}", unknown, known.join(", ")));
+        }
+        match stdin_key {
+            Some(key) => {
+                let mut value = String::new();
+                input.read_to_string(&mut value)?;
+                // Drop the trailing newline in place so that no copy is left behind
+                let trimmed = value.trim_end_matches(['\r', '\n']).len();
+                value.truncate(trimmed);
+                if value.is_empty() {
+                    value.zeroize();
+                    return Err(anyhow::anyhow!("No {} was given on stdin", key));
+                }
+                if let Some(mut replaced) = fields.values.insert(key.to_string(), value) {
+                    replaced.zeroize();
+                    return Err(anyhow::anyhow!("{} is read from stdin, don't also pass --value {}=", key, key));
+                }
+            }
+            None => fields.interactive = terminal,
+        }
+        Ok(fields)
+    }
+    
+    fn take(&mut self, key: &str) -> Option<String> {
+        self.values.remove(key)
+    }
+    
+    fn missing(key: &str) -> anyhow::Error {
+        anyhow::anyhow!("Missing {}: pass --value {}=... when stdin is not a terminal", key, key)
+    }
+    
+    fn text(&mut self, key: &str, prompt: &str) -> Result<String> {
+        if let Some(value) = self.take(key) {
+            return Ok(value);
+        }
+        if !self.interactive {
+            return Err(Self::missing(key));
+        }
+        use std::io::Write;
+        print!("{}", prompt);
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        Ok(line.trim().to_string())
+    }
+    
+    fn secret(&mut self, key: &str, prompt: &str) -> Result<String> {
+        if let Some(value) = self.take(key) {
+            return Ok(value);
+        }
+        if !self.interactive {
+            return Err(Self::missing(key));
+        }
+        Ok(rpassword::prompt_password(prompt)?)
+    }
+    
+    fn optional(&mut self, key: &str, prompt: &str) -> Result<Option<String>> {
+        if self.interactive || self.values.contains_key(key) {
+            return Ok(Some(self.text(key, prompt)?).filter(|v| !v.is_empty()));
+        }
+        Ok(None)
+    }
+    
+    fn optional_secret(&mut self, key: &str, prompt: &str) -> Result<Option<String>> {
+        if self.interactive || self.values.contains_key(key) {
+            return Ok(Some(self.secret(key, prompt)?).filter(|v| !v.is_empty()));
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for SecretFields {
+    fn drop(&mut self) {
+        for value in self.values.values_mut() {
+            value.zeroize();
+        }
+    }
+}
+
+/// Find the secret `name_or_id` refers to: the secret with that id, or else
+/// the one secret with exactly that name, optionally of a given type.
+///
+/// When several secrets share the name, the user picks one if stdin is a
+/// terminal; otherwise it is an error listing them, so scripts never act on
+/// an arbitrary match. When nothing matches exactly, the error suggests
+/// secrets with similar names.
+async fn resolve_secret(
+    storage: &SecretsStorage,
+    name_or_id: &str,
+    secret_type: Option<SecretType>,
+) -> Result<DecryptedSecretEntry> {
+    let kind = secret_type.as_ref().map(secret_type_label).unwrap_or("Secret");
+    
+    match storage.get_secret(name_or_id).await {
+        Ok(secret) => {
+            if secret_type.as_ref().is_some_and(|t| *t != secret.secret_type) {
+                return Err(anyhow::anyhow!(
+                    "'{}' is a {}, not a {}",
+                    name_or_id,
+                    secret_type_label(&secret.secret_type),
+                    kind
+                ));
+            }
+            return Ok(secret);
+        }
+        Err(pwgen_core::Error::EntryNotFound(_)) => {}
+        Err(e) => return Err(e.into()),
+    }
+    
+    let filter = SecretFilter {
+        query: Some(name_or_id.to_string()),
+        secret_types: secret_type.map(|t| vec![t]),
+        sort_by: SecretSortField::Name,
+        sort_order: SortOrder::Ascending,
+        ..Default::default()
+    };
+    let (mut exact, similar): (Vec<_>, Vec<_>) = storage
+        .search_secrets(&filter)
+        .await?
+        .into_iter()
+        .partition(|s| s.name == name_or_id);
+    
+    let describe = |s: &DecryptedSecretEntry| format!("{} ({}, id {})", s.name, secret_type_label(&s.secret_type), s.id);
+    match exact.len() {
+        1 => Ok(exact.remove(0)),
+        0 if similar.is_empty() => Err(anyhow::anyhow!("{} '{}' not found", kind, name_or_id)),
+        0 => {
+            let suggestions: Vec<String> = similar.iter().map(|s| format!("  {}", describe(s))).collect();
+            Err(anyhow::anyhow!(
+                "{} '{}' not found. Did you mean:\n{}",
+                kind,
+                name_or_id,
+                suggestions.join("\n")
+            ))
+        }
+        _ if std::io::stdin().is_terminal() => {
+            println!("{} secrets are named '{}':", exact.len(), name_or_id);
+            for (index, secret) in exact.iter().enumerate() {
+                println!("  {}) {}", index + 1, describe(secret));
+            }
+            print!("Choose one [1-{}]: ", exact.len());
+            std::io::Write::flush(&mut std::io::stdout())?;
+            
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+            let choice = input
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..=exact.len()).contains(n))
+                .ok_or_else(|| anyhow::anyhow!("No secret selected"))?;
+            Ok(exact.remove(choice - 1))
+        }
+        _ => {
+            let candidates: Vec<String> = exact.iter().map(|s| format!("  {}", describe(s))).collect();
+            Err(anyhow::anyhow!(
+                "{} secrets are named '{}'; use the id of the one you mean:\n{}",
+                exact.len(),
+                name_or_id,
+                candidates.join("\n")
+            ))
+        }
+    }
+}
+
+fn secret_type_label(secret_type: &SecretType) -> &'static str {
+    match secret_type {
+        SecretType::Password => "Password",
+        SecretType::SshKey => "SSH key",
+        SecretType::ApiKey => "API key",
+        SecretType::Token => "Token",
+        SecretType::Document => "Document",
+        SecretType::Configuration => "Configuration",
+        SecretType::SecureNote => "Note",
+        SecretType::Certificate => "Certificate",
+        SecretType::ConnectionString => "Connection",
+        SecretType::CloudCredentials => "Cloud credentials",
+        SecretType::CreditCard => "Card",
+        SecretType::Custom(_) => "Custom secret",
+    }
+}
+
 async fn get_secret(
     storage: &SecretsStorage,
     name: &str,
     show: bool,
     _copy: bool,
+    output: OutputFormat,
 ) -> Result<()> {
-    let filter = SecretFilter {
-        query: Some(name.to_string()),
-        ..Default::default()
-    };
+    let secret = resolve_secret(storage, name, None).await?;
     
-    let secrets = storage.search_secrets(&filter).await?;
-    let secret = secrets.iter().find(|s| s.name == name);
+    if output == OutputFormat::Json {
+        return print_json(&secret_json(&secret, show)?);
+    }
     
-    if let Some(secret) = secret {
-        println!("Name: {}", secret.name);
-        println!("Type: {:?}", secret.secret_type);
-        if let Some(desc) = &secret.description {
-            println!("Description: {}", desc);
-        }
-        println!("Tags: {:?}", secret.tags);
-        println!("Created: {}", secret.created_at.format("%Y-%m-%d %H:%M:%S"));
-        
-        if show {
-            match &secret.data {
-                SecretData::Password { username, password, url, notes } => {
-                    println!("Username: {}", username);
-                    println!("Password: {}", password);
-                    if let Some(url) = url {
-                        println!("URL: {}", url);
-                    }
-                    if let Some(notes) = notes {
-                        println!("Notes: {}", notes);
-                    }
-                }
-                SecretData::ApiKey { api_key, api_secret, endpoint_url, .. } => {
-                    println!("Key: {}", api_key);
-                    if let Some(secret) = api_secret {
-                        println!("Secret: {}", secret);
-                    }
-                    if let Some(endpoint) = endpoint_url {
-                        println!("Endpoint: {}", endpoint);
-                    }
-                }
-                SecretData::SecureNote { title, content, .. } => {
-                    println!("Title: {}", title);
-                    println!("Content: {}", content);
-                }
-                _ => {
-                    println!("Full display for this secret type not yet implemented in CLI");
-                }
-            }
-        } else {
-            println!("Use --show to display secret contents");
-        }
+    println!("Name: {}", secret.name);
+    println!("ID: {}", secret.id);
+    println!("Type: {:?}", secret.secret_type);
+    if let Some(desc) = &secret.description {
+        println!("Description: {}", desc);
+    }
+    println!("Tags: {:?}", secret.tags);
+    println!("Created: {}", secret.created_at.format("%Y-%m-%d %H:%M:%S"));
+    print_attribution(&secret.metadata.created_by, &secret.metadata.modified_by);
+    
+    if show {
+        print_secret_data(&secret.data);
     } else {
-        println!("Secret '{}' not found", name);
+        println!("Use --show to display secret contents");
     }
     
     Ok(())
 }
 
+/// Print `value` pretty-printed, for `--output json`.
+fn print_json(value: &serde_json::Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// `secret` as JSON. Its `data`, which holds the secret values, is only
+/// included with `show`, as in the text output.
+fn secret_json(secret: &DecryptedSecretEntry, show: bool) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(secret)?;
+    if !show {
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("data");
+        }
+    }
+    Ok(value)
+}
+
+fn print_secret_data(data: &SecretData) {
+    match data {
+        SecretData::Password { username, password, url, notes } => {
+            println!("Username: {}", username);
+            println!("Password: {}", password);
+            if let Some(url) = url {
+                println!("URL: {}", url);
+            }
+            if let Some(notes) = notes {
+                println!("Notes: {}", notes);
+            }
+        }
+        SecretData::ApiKey { api_key, api_secret, endpoint_url, .. } => {
+            println!("Key: {}", api_key);
+            if let Some(secret) = api_secret {
+                println!("Secret: {}", secret);
+            }
+            if let Some(endpoint) = endpoint_url {
+                println!("Endpoint: {}", endpoint);
+            }
+        }
+        SecretData::SecureNote { title, content, .. } => {
+            println!("Title: {}", title);
+            println!("Content: {}", content);
+        }
+        SecretData::CreditCard { cardholder, number, expiry_month, expiry_year, cvv, issuer, billing_zip } => {
+            println!("Cardholder: {}", cardholder);
+            println!("Number: {}", number);
+            println!("Brand: {}", CardBrand::from_number(number));
+            println!("Expires: {:02}/{}", expiry_month, expiry_year);
+            if let Some(cvv) = cvv {
+                println!("CVV: {}", cvv);
+            }
+            if let Some(issuer) = issuer {
+                println!("Issuer: {}", issuer);
+            }
+            if let Some(billing_zip) = billing_zip {
+                println!("Billing ZIP: {}", billing_zip);
+            }
+        }
+        _ => {
+            println!("Full display for this secret type not yet implemented in CLI");
+        }
+    }
+}
+
 async fn list_secrets(
     storage: &SecretsStorage,
     query: Option<String>,
@@ -1371,13 +2398,24 @@ async fn list_secrets(
     project: Option<String>,
     favorites: bool,
     expiring: Option<i64>,
+    sort: Option<String>,
+    order: Option<String>,
+    output: OutputFormat,
 ) -> Result<()> {
+    let sort_by: SecretSortField = sort.as_deref().unwrap_or("updated").parse()?;
+    let sort_order: SortOrder = match (&order, &sort) {
+        (Some(order), _) => order.parse()?,
+        (None, Some(_)) => SortOrder::Ascending,
+        (None, None) => SortOrder::Descending,
+    };
+    
     let secret_types = if let Some(type_str) = secret_type {
         let parsed_type = match type_str.as_str() {
             "password" => SecretType::Password,
             "ssh-key" => SecretType::SshKey,
             "api-key" => SecretType::ApiKey,
             "note" => SecretType::SecureNote,
+            "credit-card" => SecretType::CreditCard,
             custom => SecretType::Custom(custom.to_string()),
         };
         Some(vec![parsed_type])
@@ -1392,21 +2430,24 @@ async fn list_secrets(
         environment,
         project,
         favorite_only: favorites,
-        expires_before: if let Some(days) = expiring {
-            Some(chrono::Utc::now() + chrono::Duration::days(days))
-        } else {
-            None
-        },
+        expires_before: expiring.map(|days| chrono::Utc::now() + chrono::Duration::days(days)),
+        sort_by,
+        sort_order,
         ..Default::default()
     };
     
     let secrets = storage.search_secrets(&filter).await?;
     
+    if output == OutputFormat::Json {
+        let secrets = secrets.iter().map(|secret| secret_json(secret, false)).collect::<Result<_>>()?;
+        return print_json(&serde_json::Value::Array(secrets));
+    }
+    
     if secrets.is_empty() {
         println!("No secrets found");
     } else {
-        println!("{:<30} {:<15} {:<20}", "Name", "Type", "Created");
-        println!("{:-<65}", "");
+        println!("{:<30} {:<15} {:<18} {:<18}", "Name", "Type", "Created", "Expires");
+        println!("{:-<81}", "");
         
         for secret in secrets {
             let type_name = match secret.secret_type {
@@ -1414,13 +2455,17 @@ async fn list_secrets(
                 SecretType::SshKey => "SSH Key",
                 SecretType::ApiKey => "API Key",
                 SecretType::SecureNote => "Note",
+                SecretType::CreditCard => "Card",
                 SecretType::Custom(_) => "Custom",
                 _ => "Other",
             };
             
             let created = secret.created_at.format("%Y-%m-%d %H:%M").to_string();
+            let expires = secret.expires_at
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "never".to_string());
             
-            println!("{:<30} {:<15} {:<20}", secret.name, type_name, created);
+            println!("{:<30} {:<15} {:<18} {:<18}", secret.name, type_name, created, expires);
         }
     }
     
@@ -1433,14 +2478,7 @@ async fn update_secret(
     description: Option<String>,
     tags: Vec<String>,
 ) -> Result<()> {
-    let filter = SecretFilter {
-        query: Some(name.clone()),
-        ..Default::default()
-    };
-    
-    let secrets = storage.search_secrets(&filter).await?;
-    let mut secret = secrets.into_iter().find(|s| s.name == name)
-        .ok_or_else(|| anyhow::anyhow!("Secret '{}' not found", name))?;
+    let mut secret = resolve_secret(storage, &name, None).await?;
     
     if let Some(desc) = description {
         secret.description = Some(desc);
@@ -1472,17 +2510,11 @@ async fn delete_secret(storage: &SecretsStorage, name: &str, force: bool) -> Res
         }
     }
     
-    let filter = SecretFilter {
-        query: Some(name.to_string()),
-        ..Default::default()
-    };
-    
-    let secrets = storage.search_secrets(&filter).await?;
-    let secret = secrets.iter().find(|s| s.name == name)
-        .ok_or_else(|| anyhow::anyhow!("Secret '{}' not found", name))?;
+    let secret = resolve_secret(storage, name, None).await?;
     
     storage.delete_secret(&secret.id).await?;
-    println!("Secret '{}' deleted successfully", name);
+    println!("Secret '{}' moved to the trash", name);
+    eprintln!("Restore it with 'pwgen trash restore {}'", short_id(&secret.id));
     
     Ok(())
 }
@@ -1679,20 +2711,27 @@ async fn import_browser(
     merge_duplicates: bool,
     folders_as_tags: bool,
     tags: Vec<String>,
+    name_template: Option<String>,
+    cleanup_urls: bool,
+    url_rules: UrlCleanupRules,
+    column_mapping: Option<CsvColumnMapping>,
 ) -> Result<()> {
     println!("Importing passwords from browser export...");
     
     // Detect browser type if not specified
     let browser_type = if let Some(browser_str) = browser {
         browser_str.parse::<BrowserType>()?
+    } else if column_mapping.is_some() {
+        BrowserType::Custom("CSV".to_string())
     } else {
         BrowserImporter::detect_browser_type(file)?
     };
     
     // Parse format
-    let import_format = format.parse::<ImportFormat>()?;
+    let import_format = import_format_for(&browser_type, &format)?;
     
     // Create import configuration
+    let defaults = ImportConfig::default();
     let config = ImportConfig {
         browser_type: browser_type.clone(),
         format: import_format,
@@ -1701,7 +2740,10 @@ async fn import_browser(
         import_folders_as_tags: folders_as_tags,
         default_tags: if tags.is_empty() { vec!["imported".to_string()] } else { tags },
         password_strength_check: false,
-        cleanup_urls: true,
+        cleanup_urls,
+        url_rules,
+        name_template: name_template.unwrap_or(defaults.name_template),
+        column_mapping,
     };
     
     println!("Detected browser: {}", browser_type);
@@ -1729,13 +2771,24 @@ async fn import_browser(
         }
     }
     
-    if imported_passwords.is_empty() {
+    // Bitwarden exports also hold secure notes and cards
+    let other_secrets = if config.format == ImportFormat::BitwardenJson {
+        BrowserImporter::import_bitwarden_secrets(file, &config)?
+    } else {
+        Vec::new()
+    };
+    if !other_secrets.is_empty() {
+        println!("  Secure notes and cards: {}", other_secrets.len());
+    }
+    
+    if imported_passwords.is_empty() && other_secrets.is_empty() {
         println!("No valid passwords found to import.");
         return Ok(());
     }
     
     // Convert to secret entries and import
-    let secret_entries = BrowserImporter::convert_to_secret_entries(imported_passwords, &config)?;
+    let mut secret_entries = BrowserImporter::convert_to_secret_entries(imported_passwords, &config)?;
+    secret_entries.extend(other_secrets);
     
     println!("Converting {} entries to secrets...", secret_entries.len());
     
@@ -1748,7 +2801,7 @@ async fn import_browser(
         if config.skip_duplicates {
             let existing = secrets_storage.search_secrets(&SecretFilter {
                 query: Some(entry.name.clone()),
-                secret_types: Some(vec![SecretType::Password]),
+                secret_types: Some(vec![entry.secret_type.clone()]),
                 ..Default::default()
             }).await?;
             
@@ -1784,6 +2837,62 @@ async fn import_browser(
     Ok(())
 }
 
+/// `format` as an import format. A Bitwarden export is always read as
+/// Bitwarden JSON unless another format was asked for, since `--format`
+/// defaults to csv.
+fn import_format_for(browser_type: &BrowserType, format: &str) -> Result<ImportFormat> {
+    let import_format = format.parse::<ImportFormat>()?;
+    if *browser_type == BrowserType::Bitwarden && import_format == ImportFormat::Csv {
+        return Ok(ImportFormat::BitwardenJson);
+    }
+    Ok(import_format)
+}
+
+fn preview_browser_import(
+    file: &PathBuf,
+    browser: Option<String>,
+    format: String,
+    name_template: Option<String>,
+    cleanup_urls: bool,
+    url_rules: UrlCleanupRules,
+    column_mapping: Option<CsvColumnMapping>,
+) -> Result<()> {
+    let browser_type = match browser {
+        Some(browser_str) => browser_str.parse::<BrowserType>()?,
+        None if column_mapping.is_some() => BrowserType::Custom("CSV".to_string()),
+        None => BrowserImporter::detect_browser_type(file)?,
+    };
+    let defaults = ImportConfig::default();
+    let config = ImportConfig {
+        format: import_format_for(&browser_type, &format)?,
+        browser_type,
+        cleanup_urls,
+        url_rules,
+        name_template: name_template.unwrap_or(defaults.name_template.clone()),
+        column_mapping,
+        ..defaults
+    };
+    
+    let preview = BrowserImporter::preview_import(file, &config)?;
+    println!("{} entries would be imported:", preview.len());
+    for entry in &preview {
+        let url = entry.url.as_deref().unwrap_or("-");
+        if entry.name != entry.original_name {
+            println!("  {}  (was: {})", entry.name, entry.original_name);
+        } else {
+            println!("  {}", entry.name);
+        }
+        match &entry.original_url {
+            Some(original) if original != url => println!("      {}  (was: {})", url, original),
+            _ => println!("      {}", url),
+        }
+        if let Some(username) = &entry.username {
+            println!("      user: {}", username);
+        }
+    }
+    Ok(())
+}
+
 fn list_browser_paths(browser: Option<String>) {
     let browsers = if let Some(browser_str) = browser {
         if let Ok(browser_type) = browser_str.parse::<BrowserType>() {
@@ -1852,11 +2961,13 @@ fn detect_browser_type(file: &PathBuf) -> Result<()> {
                     println!("Recommended format: csv");
                     println!("Export from: File → Export → Passwords");
                 }
+                BrowserType::Bitwarden => {
+                    println!("Recommended format: bitwarden");
+                    println!("Export from: Tools → Export vault → .json (not encrypted)");
+                }
                 BrowserType::Custom(name) => {
                     if name.contains("LastPass") {
                         println!("Recommended format: lastpass");
-                    } else if name.contains("Bitwarden") {
-                        println!("Recommended format: bitwarden");
                     } else if name.contains("1Password") {
                         println!("Recommended format: 1password");
                     } else {
@@ -1897,6 +3008,19 @@ async fn show_expiring_secrets(storage: &SecretsStorage, within_days: i64) -> Re
     Ok(())
 }
 
+/// Print expiry and rotation warnings to stderr, so that they stay out of
+/// the command's own output. Does nothing before the vault exists.
+async fn print_expiry_warnings(path: &PathBuf, key_file: Option<&Path>, within_days: i64) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let storage = open_secrets_vault(path, key_file).await?;
+    for notice in storage.get_expiry_notices(within_days).await? {
+        eprintln!("Warning: {}", notice);
+    }
+    Ok(())
+}
+
 async fn show_secrets_stats(storage: &SecretsStorage) -> Result<()> {
     let stats = storage.get_secrets_stats().await?;
     
@@ -1906,6 +3030,17 @@ async fn show_secrets_stats(storage: &SecretsStorage) -> Result<()> {
     println!("Expired secrets: {}", stats.expired_count);
     println!("Expiring soon (30 days): {}", stats.expiring_soon_count);
     
+    let breakdown = storage.get_secrets_breakdown().await?;
+    println!("API keys overdue for rotation: {}", breakdown.rotation_overdue);
+    println!("SSH keys without a passphrase: {}", breakdown.ssh_keys_without_passphrase);
+    println!("Document storage: {:.1} KiB", breakdown.document_bytes as f64 / 1024.0);
+    if !breakdown.by_type.is_empty() {
+        println!();
+        for (secret_type, count) in &breakdown.by_type {
+            println!("{:<20} {}", format!("{:?}:", secret_type), count);
+        }
+    }
+    
     Ok(())
 }
 
@@ -1920,12 +3055,6 @@ async fn generate_ssh_key(
     description: Option<String>,
     tags: Vec<String>,
 ) -> Result<()> {
-    // Check if ssh-keygen is available
-    if !SshKeyUtils::check_ssh_keygen_available() {
-        eprintln!("Error: ssh-keygen is not available. Please install OpenSSH.");
-        return Ok(());
-    }
-    
     // Parse key type
     let key_type = match key_type_str.as_str() {
         "rsa" => SshKeyType::Rsa,
@@ -1952,6 +3081,9 @@ async fn generate_ssh_key(
     };
     
     println!("Generating {} SSH key pair...", key_type_str.to_uppercase());
+    if !SshKeyUtils::check_ssh_keygen_available() {
+        eprintln!("ssh-keygen not found, generating the key in-process");
+    }
     
     // Generate key pair
     let params = SshKeyGenParams {
@@ -2123,15 +3255,7 @@ async fn export_ssh_key(
     format: Option<String>,
 ) -> Result<()> {
     // Find the SSH key
-    let filter = SecretFilter {
-        query: Some(name.to_string()),
-        secret_types: Some(vec![SecretType::SshKey]),
-        ..Default::default()
-    };
-    
-    let secrets = storage.search_secrets(&filter).await?;
-    let secret = secrets.iter().find(|s| s.name == name && s.secret_type == SecretType::SshKey)
-        .ok_or_else(|| anyhow::anyhow!("SSH key '{}' not found", name))?;
+    let secret = resolve_secret(storage, name, Some(SecretType::SshKey)).await?;
     
     if let SecretData::SshKey { private_key, public_key, .. } = &secret.data {
         // Create output directory if it doesn't exist
@@ -2178,17 +3302,49 @@ async fn export_ssh_key(
     Ok(())
 }
 
-async fn show_ssh_key_info(storage: &SecretsStorage, name: &str) -> Result<()> {
-    // Find the SSH key
+async fn export_ssh_pubkeys(storage: &SecretsStorage, output: Option<&Path>, tags: Vec<String>) -> Result<()> {
     let filter = SecretFilter {
-        query: Some(name.to_string()),
         secret_types: Some(vec![SecretType::SshKey]),
+        tags: if tags.is_empty() { None } else { Some(tags) },
+        sort_by: SecretSortField::Name,
+        sort_order: SortOrder::Ascending,
         ..Default::default()
     };
     
-    let secrets = storage.search_secrets(&filter).await?;
-    let secret = secrets.iter().find(|s| s.name == name && s.secret_type == SecretType::SshKey)
-        .ok_or_else(|| anyhow::anyhow!("SSH key '{}' not found", name))?;
+    let mut authorized_keys = String::new();
+    let mut exported = 0;
+    for secret in storage.search_secrets(&filter).await? {
+        match &secret.data {
+            SecretData::SshKey { public_key: Some(public_key), .. } => {
+                match SshKeyUtils::authorized_keys_entry(&secret.name, public_key) {
+                    Ok(entry) => {
+                        authorized_keys.push_str(&entry);
+                        exported += 1;
+                    }
+                    Err(e) => eprintln!("Skipping '{}': {}", secret.name, e),
+                }
+            }
+            _ => eprintln!("Skipping '{}': no public key stored", secret.name),
+        }
+    }
+    
+    match output {
+        Some(path) => {
+            std::fs::write(path, &authorized_keys)?;
+            eprintln!("✅ Exported {} public key(s) to {}", exported, path.display());
+        }
+        None => {
+            print!("{}", authorized_keys);
+            eprintln!("Exported {} public key(s)", exported);
+        }
+    }
+    
+    Ok(())
+}
+
+async fn show_ssh_key_info(storage: &SecretsStorage, name: &str) -> Result<()> {
+    // Find the SSH key
+    let secret = resolve_secret(storage, name, Some(SecretType::SshKey)).await?;
     
     if let SecretData::SshKey { key_type, private_key, public_key, passphrase, comment, fingerprint } = &secret.data {
         println!("SSH Key Information: {}", secret.name);
@@ -2243,15 +3399,7 @@ async fn change_ssh_key_passphrase(
     remove_passphrase: bool,
 ) -> Result<()> {
     // Find the SSH key
-    let filter = SecretFilter {
-        query: Some(name.to_string()),
-        secret_types: Some(vec![SecretType::SshKey]),
-        ..Default::default()
-    };
-    
-    let secrets = storage.search_secrets(&filter).await?;
-    let mut secret = secrets.into_iter().find(|s| s.name == name && s.secret_type == SecretType::SshKey)
-        .ok_or_else(|| anyhow::anyhow!("SSH key '{}' not found", name))?;
+    let mut secret = resolve_secret(storage, name, Some(SecretType::SshKey)).await?;
     
     if let SecretData::SshKey { private_key, passphrase, .. } = &mut secret.data {
         if let Some(priv_key) = private_key {
@@ -2300,13 +3448,27 @@ async fn change_ssh_key_passphrase(
     Ok(())
 }
 
+async fn add_ssh_key_to_agent(storage: &SecretsStorage, name: &str, lifetime: Option<u64>) -> Result<()> {
+    let secret = resolve_secret(storage, name, Some(SecretType::SshKey)).await?;
+    let SecretData::SshKey { private_key: Some(private_key), passphrase, .. } = &secret.data else {
+        return Err(anyhow::anyhow!("'{}' has no private key to add to the agent", secret.name));
+    };
+    
+    SshKeyManager::add_to_agent(private_key, passphrase.as_deref(), lifetime.map(std::time::Duration::from_secs))?;
+    match lifetime {
+        Some(seconds) => println!("✅ Added '{}' to the SSH agent for {} seconds", secret.name, seconds),
+        None => println!("✅ Added '{}' to the SSH agent", secret.name),
+    }
+    Ok(())
+}
+
 // Document management functions
 async fn import_document(
     storage: &SecretsStorage,
     name: String,
     file_path: PathBuf,
     document_type: Option<String>,
-    compress: bool,
+    options: &DocumentImportOptions,
     description: Option<String>,
     tags: Vec<String>,
 ) -> Result<()> {
@@ -2334,11 +3496,23 @@ async fn import_document(
     };
     
     println!("Importing document: {}", file_path.display());
-    if compress {
+    if options.compress {
         println!("Compression enabled");
     }
     
-    match DocumentManager::import_file(&file_path, doc_type, description.clone(), compress) {
+    let mut shown = None;
+    let imported = DocumentManager::import_file_with(&file_path, doc_type, options, |read, total| {
+        let percent = read * 100 / total.max(1);
+        if shown != Some(percent) {
+            shown = Some(percent);
+            print!("\rRead {}%", percent);
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+        }
+    });
+    if shown.is_some() {
+        println!();
+    }
+    match imported {
         Ok((secret_data, doc_info)) => {
             let secret = DecryptedSecretEntry {
                 id: uuid::Uuid::new_v4().to_string(),
@@ -2361,6 +3535,9 @@ async fn import_document(
             println!("File: {}", doc_info.filename);
             println!("Type: {:?}", doc_info.document_type);
             println!("Size: {} bytes", doc_info.file_size);
+            if let (Some(compression), SecretData::Document { content, .. }) = (&doc_info.compression, &secret.data) {
+                println!("Stored: {} bytes ({:?})", content.len(), compression);
+            }
             println!("Content Type: {}", doc_info.content_type);
             println!("Checksum: {}...", &doc_info.checksum_sha256[..16]); // Show first 16 chars
         }
@@ -2430,19 +3607,11 @@ async fn create_text_document(
 async fn export_document(
     storage: &SecretsStorage,
     name: &str,
-    output_path: &PathBuf,
+    output_path: &Path,
     verify: bool,
 ) -> Result<()> {
     // Find the document
-    let filter = SecretFilter {
-        query: Some(name.to_string()),
-        secret_types: Some(vec![SecretType::Document]),
-        ..Default::default()
-    };
-    
-    let secrets = storage.search_secrets(&filter).await?;
-    let secret = secrets.iter().find(|s| s.name == name && s.secret_type == SecretType::Document)
-        .ok_or_else(|| anyhow::anyhow!("Document '{}' not found", name))?;
+    let secret = resolve_secret(storage, name, Some(SecretType::Document)).await?;
     
     match DocumentManager::export_document(&secret.data, output_path, verify) {
         Ok(_) => {
@@ -2461,15 +3630,7 @@ async fn export_document(
 
 async fn show_document_info(storage: &SecretsStorage, name: &str) -> Result<()> {
     // Find the document
-    let filter = SecretFilter {
-        query: Some(name.to_string()),
-        secret_types: Some(vec![SecretType::Document]),
-        ..Default::default()
-    };
-    
-    let secrets = storage.search_secrets(&filter).await?;
-    let secret = secrets.iter().find(|s| s.name == name && s.secret_type == SecretType::Document)
-        .ok_or_else(|| anyhow::anyhow!("Document '{}' not found", name))?;
+    let secret = resolve_secret(storage, name, Some(SecretType::Document)).await?;
     
     if let Ok(doc_info) = DocumentManager::get_document_info(&secret.data) {
         println!("Document Information: {}", secret.name);
@@ -2512,15 +3673,7 @@ async fn show_document_info(storage: &SecretsStorage, name: &str) -> Result<()>
 
 async fn view_document(storage: &SecretsStorage, name: &str, text_only: bool) -> Result<()> {
     // Find the document
-    let filter = SecretFilter {
-        query: Some(name.to_string()),
-        secret_types: Some(vec![SecretType::Document]),
-        ..Default::default()
-    };
-    
-    let secrets = storage.search_secrets(&filter).await?;
-    let secret = secrets.iter().find(|s| s.name == name && s.secret_type == SecretType::Document)
-        .ok_or_else(|| anyhow::anyhow!("Document '{}' not found", name))?;
+    let secret = resolve_secret(storage, name, Some(SecretType::Document)).await?;
     
     if text_only {
         match DocumentManager::extract_text_content(&secret.data) {
@@ -2702,11 +3855,14 @@ async fn list_api_keys(
     expired: bool,
     expiring_days: Option<u32>,
     environment: Option<String>,
+    output: OutputFormat,
 ) -> Result<()> {
-    let mut filter = SecretFilter::default();
-    filter.secret_types = Some(vec![SecretType::ApiKey, SecretType::Token]);
-    filter.environment = environment;
-    
+    let filter = SecretFilter {
+        secret_types: Some(vec![SecretType::ApiKey, SecretType::Token]),
+        environment,
+        ..Default::default()
+    };
+    
     let entries = storage.search_secrets(&filter).await?;
     
     let mut filtered_entries: Vec<_> = entries.iter().collect();
@@ -2730,6 +3886,29 @@ async fn list_api_keys(
         filtered_entries.retain(|entry| ApiKeyManager::is_expiring_soon(entry, days));
     }
     
+    if output == OutputFormat::Json {
+        let mut keys = Vec::new();
+        for entry in filtered_entries {
+            let mut value = secret_json(entry, false)?;
+            value["status"] = api_key_status(entry).into();
+            match &entry.data {
+                SecretData::ApiKey { provider, environment, .. } => {
+                    value["provider"] = provider.to_string().into();
+                    value["environment"] = environment.as_str().into();
+                    if let Some(rotation) = ApiKeyManager::rotation_status(entry) {
+                        value["rotation"] = serde_json::to_value(rotation)?;
+                    }
+                }
+                SecretData::Token { token_type, .. } => {
+                    value["token_type"] = token_type.to_string().into();
+                }
+                _ => {}
+            }
+            keys.push(value);
+        }
+        return print_json(&serde_json::Value::Array(keys));
+    }
+    
     if filtered_entries.is_empty() {
         println!("No API keys found matching criteria.");
         return Ok(());
@@ -2739,12 +3918,10 @@ async fn list_api_keys(
     println!("{:-<80}", "");
     
     for entry in filtered_entries {
-        let status = if ApiKeyManager::is_expired(entry) {
-            "🔴 EXPIRED"
-        } else if ApiKeyManager::is_expiring_soon(entry, 30) {
-            "🟡 EXPIRING SOON"
-        } else {
-            "🟢 ACTIVE"
+        let status = match api_key_status(entry) {
+            "expired" => "🔴 EXPIRED",
+            "expiring" => "🟡 EXPIRING SOON",
+            _ => "🟢 ACTIVE",
         };
         
         println!("📋 {} [{}]", entry.name, status);
@@ -2754,6 +3931,16 @@ async fn list_api_keys(
             SecretData::ApiKey { provider, environment, .. } => {
                 println!("   Provider: {}", provider);
                 println!("   Environment: {}", environment);
+                if let Some(rotation) = ApiKeyManager::rotation_status(entry) {
+                    let due = rotation.due.format("%Y-%m-%d");
+                    if rotation.overdue {
+                        println!("   Rotation: 🔴 OVERDUE since {}", due);
+                    } else if rotation.reminder {
+                        println!("   Rotation: 🟡 due {} ({} days left)", due, rotation.days_remaining);
+                    } else {
+                        println!("   Rotation: due {}", due);
+                    }
+                }
             }
             SecretData::Token { token_type, .. } => {
                 println!("   Token Type: {}", token_type);
@@ -2776,13 +3963,39 @@ async fn list_api_keys(
     Ok(())
 }
 
+/// `expired`, `expiring` (within 30 days) or `active`.
+fn api_key_status(entry: &DecryptedSecretEntry) -> &'static str {
+    if ApiKeyManager::is_expired(entry) {
+        "expired"
+    } else if ApiKeyManager::is_expiring_soon(entry, 30) {
+        "expiring"
+    } else {
+        "active"
+    }
+}
+
 async fn get_api_key(
     storage: &SecretsStorage,
     name: &str,
     show_secret: bool,
     copy: bool,
+    output: OutputFormat,
 ) -> Result<()> {
-    let entry = storage.get_secret(name).await?;
+    let entry = resolve_secret(storage, name, Some(SecretType::ApiKey)).await?;
+    
+    if output == OutputFormat::Json {
+        if show_secret && copy {
+            if let Some(value) = entry.data.reveal() {
+                match arboard::Clipboard::new().and_then(|mut ctx| ctx.set_text(value)) {
+                    Ok(()) => eprintln!("✅ Copied to clipboard"),
+                    Err(e) => eprintln!("Failed to copy to clipboard: {}", e),
+                }
+            }
+        }
+        let mut value = secret_json(&entry, show_secret)?;
+        value["status"] = api_key_status(&entry).into();
+        return print_json(&value);
+    }
     
     match &entry.data {
         SecretData::ApiKey { provider, api_key, api_secret, environment, endpoint_url, usage_stats, .. } => {
@@ -2808,7 +4021,7 @@ async fn get_api_key(
                     }
                 }
             } else {
-                println!("API Key: {} (use --show-secret to reveal)", "*".repeat(api_key.len().min(16)));
+                println!("API Key: ******** (use --show-secret to reveal)");
                 if api_secret.is_some() {
                     println!("API Secret: ******** (use --show-secret to reveal)");
                 }
@@ -2847,7 +4060,7 @@ async fn get_api_key(
                     }
                 }
             } else {
-                println!("Access Token: {} (use --show-secret to reveal)", "*".repeat(access_token.len().min(16)));
+                println!("Access Token: ******** (use --show-secret to reveal)");
                 if refresh_token.is_some() {
                     println!("Refresh Token: ******** (use --show-secret to reveal)");
                 }
@@ -2883,7 +4096,7 @@ async fn update_api_key_usage(
     success: bool,
     error_message: Option<String>,
 ) -> Result<()> {
-    let mut entry = storage.get_secret(name).await?;
+    let mut entry = resolve_secret(storage, name, Some(SecretType::ApiKey)).await?;
     
     ApiKeyManager::update_usage_stats(&mut entry, success, error_message)?;
     
@@ -2899,14 +4112,14 @@ async fn setup_api_key_rotation(
     rotation_days: u32,
     reminder_days: u32,
 ) -> Result<()> {
-    let mut entry = storage.get_secret(name).await?;
+    let mut entry = resolve_secret(storage, name, Some(SecretType::ApiKey)).await?;
     
     ApiKeyManager::setup_rotation(&mut entry, rotation_days, reminder_days)?;
     
     storage.update_secret(&entry).await?;
     println!("✅ API key rotation configured!");
     println!("   Rotation period: {} days", rotation_days);
-    println!("   Reminder: {} days before expiration", reminder_days);
+    println!("   Reminder: {} days before rotation is due", reminder_days);
     
     Ok(())
 }
@@ -3011,10 +4224,28 @@ async fn update_note(
     new_content: Option<String>,
     from_stdin: bool,
     new_format: Option<String>,
+    edit: bool,
 ) -> Result<()> {
-    let mut entry = storage.get_secret(name).await?;
+    let mut entry = resolve_secret(storage, name, Some(SecretType::SecureNote)).await?;
 
-    let content = if from_stdin {
+    let content = if edit {
+        let SecretData::SecureNote { content, format, .. } = &entry.data else {
+            return Err(anyhow::anyhow!("Entry is not a secure note"));
+        };
+        let extension = match format {
+            NoteFormat::Markdown => "md",
+            NoteFormat::Html => "html",
+            NoteFormat::PlainText | NoteFormat::RichText => "txt",
+        };
+        match edit_externally(content, extension)? {
+            Some(edited) => Some(edited),
+            None if new_title.is_none() && new_format.is_none() => {
+                println!("Note unchanged");
+                return Ok(());
+            }
+            None => None,
+        }
+    } else if from_stdin {
         use std::io::Read;
         let mut buffer = String::new();
         std::io::stdin().read_to_string(&mut buffer)?;
@@ -3043,12 +4274,25 @@ async fn update_note(
     Ok(())
 }
 
+/// Open `content` in the user's editor, warning first if the temporary file
+/// cannot be kept in memory.
+fn edit_externally(content: &str, extension: &str) -> Result<Option<String>> {
+    let (dir, memory_backed) = secure_temp_dir();
+    if !memory_backed {
+        eprintln!(
+            "⚠️  Decrypted content will be written to {} while the editor is open and wiped afterwards",
+            dir.display()
+        );
+    }
+    Ok(edit_in_external_editor(content, extension)?)
+}
+
 async fn convert_note(
     storage: &mut SecretsStorage,
     name: &str,
     format: String,
 ) -> Result<()> {
-    let mut entry = storage.get_secret(name).await?;
+    let mut entry = resolve_secret(storage, name, Some(SecretType::SecureNote)).await?;
 
     let target_format = match format.to_lowercase().as_str() {
         "plaintext" | "plain" | "text" => NoteFormat::PlainText,
@@ -3072,8 +4316,10 @@ async fn search_notes(
     case_sensitive: bool,
     category: Option<String>,
 ) -> Result<()> {
-    let mut filter = SecretFilter::default();
-    filter.secret_types = Some(vec![SecretType::SecureNote]);
+    let filter = SecretFilter {
+        secret_types: Some(vec![SecretType::SecureNote]),
+        ..Default::default()
+    };
 
     let entries = storage.search_secrets(&filter).await?;
     let search_results = NotesConfigManager::search_notes_content(&entries, query, case_sensitive);
@@ -3126,8 +4372,10 @@ async fn list_notes(
     _priority: Option<String>,
     _format: Option<String>,
 ) -> Result<()> {
-    let mut filter = SecretFilter::default();
-    filter.secret_types = Some(vec![SecretType::SecureNote]);
+    let filter = SecretFilter {
+        secret_types: Some(vec![SecretType::SecureNote]),
+        ..Default::default()
+    };
 
     let entries = storage.search_secrets(&filter).await?;
 
@@ -3172,6 +4420,7 @@ async fn create_config(
     format: String,
     file: Option<PathBuf>,
     from_stdin: bool,
+    interpolate: bool,
     _template: Option<String>,
     description: Option<String>,
     tags: Vec<String>,
@@ -3198,7 +4447,8 @@ async fn create_config(
         return Err(anyhow::anyhow!("Must provide either --file or --from-stdin"));
     };
 
-    let entry = NotesConfigManager::import_config_from_string(
+    let interpolate = interpolate && matches!(config_format, ConfigFormat::EnvFile);
+    let mut entry = NotesConfigManager::import_config_from_string(
         &content,
         config_format,
         name,
@@ -3206,6 +4456,9 @@ async fn create_config(
         description,
         tags,
     )?;
+    if interpolate {
+        EnvConnectionManager::apply_interpolation(&mut entry.data)?;
+    }
 
     storage.add_secret(&entry).await?;
     println!("✅ Configuration created successfully!");
@@ -3219,10 +4472,32 @@ async fn update_config(
     variables: Vec<String>,
     merge: bool,
     file: Option<PathBuf>,
+    edit: bool,
 ) -> Result<()> {
-    let mut entry = storage.get_secret(name).await?;
+    let mut entry = resolve_secret(storage, name, Some(SecretType::Configuration)).await?;
 
-    let new_variables = if let Some(file_path) = file {
+    let new_variables = if edit {
+        let SecretData::Configuration { format, .. } = &entry.data else {
+            return Err(anyhow::anyhow!("Entry is not a configuration"));
+        };
+        let extension = match format {
+            ConfigFormat::EnvFile => "env",
+            ConfigFormat::Json => "json",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Xml => "xml",
+            ConfigFormat::Properties => "properties",
+            ConfigFormat::Custom(_) => "txt",
+        };
+        let current = NotesConfigManager::export_config_to_string(&entry, None)?;
+        match edit_externally(&current, extension)? {
+            Some(edited) => NotesConfigManager::parse_config(&edited, format)?,
+            None => {
+                println!("Configuration unchanged");
+                return Ok(());
+            }
+        }
+    } else if let Some(file_path) = file {
         let content = std::fs::read_to_string(file_path)?;
         if let SecretData::Configuration { format, .. } = &entry.data {
             NotesConfigManager::parse_config(&content, format)?
@@ -3257,7 +4532,7 @@ async fn export_config(
     output: Option<PathBuf>,
     format: Option<String>,
 ) -> Result<()> {
-    let entry = storage.get_secret(name).await?;
+    let entry = resolve_secret(storage, name, Some(SecretType::Configuration)).await?;
 
     let target_format = if let Some(fmt) = format {
         Some(match fmt.to_lowercase().as_str() {
@@ -3289,7 +4564,7 @@ async fn validate_config(
     name: &str,
     template: Option<String>,
 ) -> Result<()> {
-    let entry = storage.get_secret(name).await?;
+    let entry = resolve_secret(storage, name, Some(SecretType::Configuration)).await?;
 
     if let SecretData::Configuration { variables, .. } = &entry.data {
         if let Some(template_name) = template {
@@ -3354,8 +4629,10 @@ async fn list_configs(
     config_type: Option<String>,
     _format: Option<String>,
 ) -> Result<()> {
-    let mut filter = SecretFilter::default();
-    filter.secret_types = Some(vec![SecretType::Configuration]);
+    let filter = SecretFilter {
+        secret_types: Some(vec![SecretType::Configuration]),
+        ..Default::default()
+    };
 
     let entries = storage.search_secrets(&filter).await?;
 
@@ -3382,7 +4659,7 @@ async fn list_configs(
     println!("{:-<70}", "");
 
     for entry in filtered_entries {
-        if let SecretData::Configuration { format, variables, template } = &entry.data {
+        if let SecretData::Configuration { format, variables, template, .. } = &entry.data {
             println!("⚙️  {} [{}]", entry.name, format_config_format(format));
             
             if let Some(template_name) = template {
@@ -3393,7 +4670,7 @@ async fn list_configs(
             
             // Show a few variables (non-sensitive)
             let mut shown = 0;
-            for (key, _) in variables {
+            for key in variables.keys() {
                 if shown < 3 && !key.to_lowercase().contains("password") && !key.to_lowercase().contains("secret") {
                     println!("     - {}", key);
                     shown += 1;
@@ -3489,6 +4766,7 @@ async fn create_env_set(
     environment: String,
     file: Option<PathBuf>,
     from_stdin: bool,
+    interpolate: bool,
     template: Option<String>,
     description: Option<String>,
     tags: Vec<String>,
@@ -3513,6 +4791,7 @@ async fn create_env_set(
         name,
         environment_type,
         variables,
+        interpolate,
         description,
         tags,
     )?;
@@ -3528,7 +4807,7 @@ async fn generate_env_file(
     name: &str,
     output: Option<PathBuf>,
 ) -> Result<()> {
-    let entry = storage.get_secret(name).await?;
+    let entry = resolve_secret(storage, name, Some(SecretType::Configuration)).await?;
 
     if let SecretData::Configuration { variables, .. } = &entry.data {
         let env_content = EnvConnectionManager::generate_env_file(variables);
@@ -3546,12 +4825,183 @@ async fn generate_env_file(
     Ok(())
 }
 
+async fn diff_env(
+    storage: &SecretsStorage,
+    a: &str,
+    b: &str,
+    show_values: bool,
+    all: bool,
+) -> Result<()> {
+    let left = resolve_secret(storage, a, Some(SecretType::Configuration)).await?;
+    let right = resolve_secret(storage, b, Some(SecretType::Configuration)).await?;
+    let diff = EnvConnectionManager::diff_environment_sets(&left, &right)?;
+
+    let value = |value: &Option<String>| match value {
+        Some(value) if show_values => format!("{:?}", value),
+        Some(_) => "********".to_string(),
+        None => String::new(),
+    };
+
+    println!("Comparing {} → {}", left.name, right.name);
+    let mut differences = 0;
+    for var in &diff {
+        let line = match var.kind {
+            EnvDiffKind::Added => format!("  + {} (only in {}) {}", var.name, right.name, value(&var.right)),
+            EnvDiffKind::Removed => format!("  - {} (missing in {}) {}", var.name, right.name, value(&var.left)),
+            EnvDiffKind::Changed => format!("  ~ {} {} → {}", var.name, value(&var.left), value(&var.right)),
+            EnvDiffKind::Unchanged if all => format!("    {}", var.name),
+            EnvDiffKind::Unchanged => continue,
+        };
+        if var.kind != EnvDiffKind::Unchanged {
+            differences += 1;
+        }
+        println!("{}", line.trim_end());
+    }
+
+    if differences == 0 {
+        println!("✅ No differences ({} variables)", diff.len());
+    } else {
+        println!("{} difference(s), {} variable(s) the same", differences, diff.len() - differences);
+    }
+    Ok(())
+}
+
+/// Write the selected secrets as `.env` lines to `output`, or as `export`
+/// lines to stdout when there is no output file.
+async fn export_env(
+    storage: &SecretsStorage,
+    names: Vec<String>,
+    tags: Vec<String>,
+    prefix: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    if names.is_empty() && tags.is_empty() {
+        return Err(anyhow::anyhow!("Choose the secrets to export by name or with --tag"));
+    }
+    
+    let mut secrets = Vec::new();
+    for name in &names {
+        secrets.push(resolve_secret(storage, name, None).await?);
+    }
+    if !tags.is_empty() {
+        let filter = SecretFilter {
+            tags: Some(tags),
+            sort_by: SecretSortField::Name,
+            sort_order: SortOrder::Ascending,
+            ..Default::default()
+        };
+        for secret in storage.search_secrets(&filter).await? {
+            if !secrets.iter().any(|s: &DecryptedSecretEntry| s.id == secret.id) {
+                secrets.push(secret);
+            }
+        }
+    }
+    
+    let variables = EnvConnectionManager::secrets_to_env(&secrets, prefix)?;
+    
+    match output {
+        Some(path) => {
+            let mut content = String::new();
+            content.push_str("# PLAINTEXT SECRETS exported by pwgen - do not commit this file\n");
+            for (key, value) in &variables {
+                content.push_str(&EnvConnectionManager::format_env_line(key, value));
+                content.push('\n');
+            }
+            write_private_file(path, content.as_bytes())?;
+            eprintln!("✅ Exported {} variable(s) from {} secret(s) to {}", variables.len(), secrets.len(), path.display());
+            eprintln!("⚠️  WARNING: {} contains secrets in PLAINTEXT.", path.display());
+            eprintln!("   Keep it out of version control (add it to .gitignore) and delete it when no longer needed.");
+        }
+        None => {
+            for (key, value) in &variables {
+                println!("export {}", EnvConnectionManager::format_env_line(key, value));
+            }
+            eprintln!("Exported {} variable(s) from {} secret(s)", variables.len(), secrets.len());
+        }
+    }
+    
+    Ok(())
+}
+
+/// Run `command` with the variables of the environment set `name` added to
+/// its environment, forwarding stdio, and return its exit code. The values
+/// only ever live in memory: the parent's copies are wiped once the child
+/// has started.
+///
+/// With `environment`, only a set scoped to that environment qualifies, so
+/// `--env-set api --environment prod` picks the production "api" set even
+/// when staging has one of the same name.
+async fn run_with_env(
+    storage: &SecretsStorage,
+    name: &str,
+    environment: Option<&str>,
+    command: &[String],
+) -> Result<i32> {
+    let entry = match environment {
+        None => resolve_secret(storage, name, Some(SecretType::Configuration)).await?,
+        Some(environment) => {
+            let environment_type: EnvironmentType = environment.parse()?;
+            let filter = SecretFilter {
+                secret_types: Some(vec![SecretType::Configuration]),
+                environment: Some(format!("{:?}", environment_type)),
+                ..Default::default()
+            };
+            let mut matches: Vec<_> = storage
+                .search_secrets(&filter)
+                .await?
+                .into_iter()
+                .filter(|s| s.name == name || s.id == name)
+                .collect();
+            match matches.len() {
+                1 => matches.remove(0),
+                0 => return Err(anyhow::anyhow!("No environment set '{}' for {}", name, environment_type)),
+                _ => {
+                    let ids: Vec<_> = matches.iter().map(|s| s.id.as_str()).collect();
+                    return Err(anyhow::anyhow!(
+                        "Several {} environment sets are named '{}', pass one of these ids instead: {}",
+                        environment_type, name, ids.join(", ")
+                    ));
+                }
+            }
+        }
+    };
+    if !matches!(entry.data, SecretData::Configuration { .. }) {
+        return Err(anyhow::anyhow!("'{}' is not an environment set", name));
+    }
+    
+    let mut variables = EnvConnectionManager::secrets_to_env(std::slice::from_ref(&entry), "")?;
+    drop(entry);
+    
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("No command given to run"))?;
+    let child = std::process::Command::new(program)
+        .args(args)
+        .envs(variables.iter().map(|(key, value)| (key, value)))
+        .spawn();
+    for (_, value) in &mut variables {
+        value.zeroize();
+    }
+    let mut child = child.map_err(|e| anyhow::anyhow!("Could not run {}: {}", program, e))?;
+    
+    let status = child.wait()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        // Mirror the shell's convention for a child killed by a signal
+        if let Some(signal) = status.signal() {
+            return Ok(128 + signal);
+        }
+    }
+    Ok(status.code().unwrap_or(1))
+}
+
 async fn validate_env_vars(
     storage: &SecretsStorage,
     name: &str,
     template: Option<String>,
 ) -> Result<()> {
-    let entry = storage.get_secret(name).await?;
+    let entry = resolve_secret(storage, name, Some(SecretType::Configuration)).await?;
 
     if let SecretData::Configuration { variables, .. } = &entry.data {
         if let Some(template_name) = template {
@@ -3621,11 +5071,11 @@ async fn list_env_vars(
     environment: Option<String>,
     show_sensitive: bool,
 ) -> Result<()> {
-    let mut filter = SecretFilter::default();
-    filter.secret_types = Some(vec![SecretType::Configuration]);
-    if let Some(env) = environment {
-        filter.environment = Some(env);
-    }
+    let filter = SecretFilter {
+        secret_types: Some(vec![SecretType::Configuration]),
+        environment,
+        ..Default::default()
+    };
 
     let entries = storage.search_secrets(&filter).await?;
 
@@ -3783,44 +5233,113 @@ async fn test_connection(
     storage: &SecretsStorage,
     name: &str,
 ) -> Result<()> {
-    let entry = storage.get_secret(name).await?;
-
-    if let SecretData::ConnectionString { connection_string, .. } = &entry.data {
-        match EnvConnectionManager::test_connection_string(connection_string) {
-            Ok(_) => {
-                println!("✅ Connection string is valid");
-                println!("   Connection: {}", name);
-                
-                // Parse and show details
-                if let Ok(components) = EnvConnectionManager::parse_connection_string(connection_string) {
-                    println!("   Host: {}", components.host);
-                    println!("   Database: {}", components.database);
-                    if let Some(port) = components.port {
-                        println!("   Port: {}", port);
-                    }
-                }
-            }
-            Err(e) => {
-                println!("❌ Connection validation failed: {}", e);
-            }
-        }
-    } else {
+    let entry = resolve_secret(storage, name, Some(SecretType::ConnectionString)).await?;
+    
+    let SecretData::ConnectionString { connection_string, .. } = &entry.data else {
         return Err(anyhow::anyhow!("Entry '{}' is not a connection string", name));
+    };
+    if let Err(e) = EnvConnectionManager::test_connection_string(connection_string) {
+        println!("❌ Connection validation failed: {}", e);
+        return Ok(());
+    }
+    if let Ok(components) = EnvConnectionManager::parse_connection_string(connection_string) {
+        println!("   Connection: {}", name);
+        println!("   Host: {}", components.host);
+        println!("   Database: {}", components.database);
+        if let Some(port) = components.port {
+            println!("   Port: {}", port);
+        }
+    }
+    
+    #[cfg(feature = "connection-test")]
+    match EnvConnectionManager::test_connection(&entry.data).await {
+        Ok(latency) => println!("✅ Connected in {} ms", latency.as_millis()),
+        Err(e) => return Err(anyhow::anyhow!("Could not connect to '{}': {}", name, e)),
     }
+    #[cfg(not(feature = "connection-test"))]
+    println!("✅ Connection string is valid (built without connection-test, so no connection was attempted)");
+    
+    Ok(())
+}
 
+/// Print the command template for an API key or connection. The secret is
+/// only included in the printed command with `--show-secret`; `--copy` always
+/// places the complete command on the clipboard.
+async fn print_command_snippet(
+    storage: &SecretsStorage,
+    name: &str,
+    show_secret: bool,
+    copy: bool,
+) -> Result<()> {
+    let entry = resolve_secret(storage, name, None).await?;
+    let snippet = command_snippet(&entry.data, show_secret)
+        .ok_or_else(|| anyhow::anyhow!("No command template for '{}'", name))?;
+    
+    println!("{}", snippet.command);
+    
+    if copy {
+        let full = command_snippet(&entry.data, true).unwrap_or(snippet);
+        if let Err(e) = arboard::Clipboard::new().and_then(|mut ctx| ctx.set_text(full.command)) {
+            eprintln!("Failed to copy to clipboard: {}", e);
+        } else {
+            eprintln!("✅ {} command copied to clipboard", full.tool);
+        }
+    } else if !show_secret {
+        eprintln!("(secret hidden; use --show-secret to include it, or --copy to copy the full command)");
+    }
+    
     Ok(())
 }
 
+/// Report every file:line where a stored env/config value appears. Exits with
+/// an error when anything is found so it can gate a pre-commit hook or CI job.
+async fn scan_repo(
+    storage: &SecretsStorage,
+    path: &Path,
+    history: bool,
+    min_length: usize,
+) -> Result<()> {
+    let filter = SecretFilter {
+        secret_types: Some(vec![SecretType::Configuration]),
+        ..Default::default()
+    };
+    let secrets = storage.search_secrets(&filter).await?;
+    let targets = leak_scan::scan_targets(&secrets, min_length);
+    drop(secrets);
+    
+    if targets.is_empty() {
+        println!("No stored env/config values of {} or more characters to scan for.", min_length);
+        return Ok(());
+    }
+    
+    println!("🔍 Scanning {} for {} stored values...", path.display(), targets.len());
+    let mut findings = leak_scan::scan_working_tree(path, &targets)?;
+    if history {
+        findings.extend(leak_scan::scan_git_history(path, &targets)?);
+    }
+    
+    if findings.is_empty() {
+        println!("✅ No stored secrets found in plaintext");
+        return Ok(());
+    }
+    
+    for finding in &findings {
+        println!("⚠️  {}", finding);
+    }
+    Err(anyhow::anyhow!("{} exposed secret value(s) found", findings.len()))
+}
+
 async fn list_connections(
     storage: &SecretsStorage,
     connection_type: Option<String>,
     environment: Option<String>,
+    output: OutputFormat,
 ) -> Result<()> {
-    let mut filter = SecretFilter::default();
-    filter.secret_types = Some(vec![SecretType::ConnectionString]);
-    if let Some(env) = environment {
-        filter.environment = Some(env);
-    }
+    let filter = SecretFilter {
+        secret_types: Some(vec![SecretType::ConnectionString]),
+        environment,
+        ..Default::default()
+    };
 
     let entries = storage.search_secrets(&filter).await?;
 
@@ -3838,6 +5357,23 @@ async fn list_connections(
         })
         .collect();
 
+    if output == OutputFormat::Json {
+        let mut connections = Vec::new();
+        for entry in filtered_entries {
+            let mut value = secret_json(entry, false)?;
+            // Everything but the password and the connection string, as in the text output
+            if let SecretData::ConnectionString { database_type, host, port, database, username, .. } = &entry.data {
+                value["database_type"] = serde_json::to_value(database_type)?;
+                value["host"] = host.as_str().into();
+                value["port"] = (*port).into();
+                value["database"] = database.as_str().into();
+                value["username"] = username.as_str().into();
+            }
+            connections.push(value);
+        }
+        return print_json(&serde_json::Value::Array(connections));
+    }
+    
     if filtered_entries.is_empty() {
         println!("No connections found.");
         return Ok(());
@@ -3948,7 +5484,7 @@ fn parse_connection_type(connection_type: &str) -> Result<ConnectionType> {
     }
 }
 
-fn expand_tilde(path: &PathBuf) -> PathBuf {
+fn expand_tilde(path: &Path) -> PathBuf {
     if let Some(path_str) = path.to_str() {
         if path_str.starts_with("~") {
             if let Some(home) = dirs::home_dir() {
@@ -3956,10 +5492,10 @@ fn expand_tilde(path: &PathBuf) -> PathBuf {
             }
         }
     }
-    path.clone()
+    path.to_path_buf()
 }
 
-async fn init_vault(path: &PathBuf, force: bool) -> Result<()> {
+async fn init_vault(path: &PathBuf, key_file_path: Option<&Path>, force: bool, with_recovery_key: bool) -> Result<()> {
     if path.exists() && !force {
         eprintln!("Vault already exists at {:?}. Use --force to overwrite.", path);
         return Ok(());
@@ -3977,129 +5513,862 @@ async fn init_vault(path: &PathBuf, force: bool) -> Result<()> {
         return Ok(());
     }
     
-    Storage::create_new(path, &password).await?;
+    let key_file = match key_file_path {
+        Some(key_path) if !key_path.exists() => {
+            let key_file = KeyFile::generate(key_path)?;
+            println!("Generated new key file at {:?}", key_path);
+            Some(key_file)
+        }
+        Some(key_path) => Some(KeyFile::read(key_path)?),
+        None => None,
+    };
+    
+    let storage = Storage::create_new_with_key_file(path, &password, key_file.as_ref()).await?;
     println!("Vault initialized successfully at {:?}", path);
+    if let Some(key_path) = key_file_path {
+        println!("Keep {:?} safe: the vault cannot be opened without it, even with the master password.", key_path);
+    }
+    if with_recovery_key {
+        let recovery_key = storage.enable_recovery_key().await?;
+        println!();
+        println!("Recovery key:");
+        println!();
+        println!("  {}", recovery_key.phrase());
+        println!();
+        println!("Write it down and keep it somewhere safe. It unlocks the vault without the master password");
+        println!("or key file ('pwgen-cli recover'), and it will not be shown again.");
+    }
     
     Ok(())
 }
 
-async fn open_vault(path: &PathBuf) -> Result<Storage> {
+async fn verify_vault(path: &PathBuf, key_file: Option<&Path>) -> Result<()> {
     if !path.exists() {
         eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
         std::process::exit(1);
     }
     
+    let key_file = read_key_file(key_file)?;
     let password = rpassword::prompt_password("Enter master password: ")?;
+    let storage = Storage::open_with_key_file(path, &password, key_file.as_ref()).await?;
+    let secrets_storage =
+        SecretsStorage::from_existing_storage_with_key_file(path, &password, key_file.as_ref()).await?;
     
-    match Storage::open(path, &password).await {
-        Ok(storage) => Ok(storage),
-        Err(e) => {
-            eprintln!("Failed to open vault: {}", e);
-            std::process::exit(1);
-        }
+    let mut report = IntegrityReport::default();
+    storage.verify_entries(&mut report).await?;
+    secrets_storage.verify_secrets(&mut report).await?;
+    
+    println!("Checked {} password entries and {} secrets", report.entries_checked, report.secrets_checked);
+    if report.is_ok() {
+        println!("✅ Every item decrypts and validates");
+        return Ok(());
     }
+    
+    for issue in &report.issues {
+        let kind = match issue.kind {
+            ItemKind::PasswordEntry => "entry",
+            ItemKind::Secret => "secret",
+        };
+        println!("❌ {} '{}' ({}): {}", kind, issue.name, issue.id, issue.problem);
+    }
+    Err(anyhow::anyhow!("{} item(s) failed verification", report.issues.len()))
 }
 
-async fn add_entry(
-    storage: &Storage,
-    site: String,
-    username: String,
-    generate: bool,
-    length: usize,
-    notes: Option<String>,
-    tags: Vec<String>,
-) -> Result<()> {
-    let password = if generate {
-        let config = PasswordConfig {
-            length,
-            ..Default::default()
-        };
-        PasswordGenerator::generate(&config)?
-    } else {
-        rpassword::prompt_password("Enter password: ")?
-    };
-    
-    let id = hash_entry_id(&site, &username);
-    let entry = DecryptedPasswordEntry {
-        id,
-        site: site.clone(),
-        username: username.clone(),
-        password,
-        notes,
-        tags,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
-        last_used: None,
-        password_changed_at: chrono::Utc::now(),
-        favorite: false,
+async fn kdf_benchmark(path: &PathBuf, key_file: Option<&Path>, apply: bool) -> Result<()> {
+    println!("Measuring Argon2 on this machine...");
+    let (recommended, took) = tokio::task::spawn_blocking(|| {
+        let params = Argon2Params::benchmark(std::time::Duration::from_millis(500))?;
+        Ok::<_, pwgen_core::Error>((params, params.measure()?))
+    })
+    .await??;
+    println!(
+        "Recommended: memory {} KiB, {} iterations, parallelism {} (unlocks in about {} ms here)",
+        recommended.mem_kib,
+        recommended.iterations,
+        recommended.parallelism,
+        took.as_millis()
+    );
+    
+    let status = VaultStatus::read(path).await?;
+    let Some(kdf) = status.kdf else {
+        return Ok(());
     };
-    
-    storage.add_entry(&entry).await?;
-    println!("Password saved for {} @ {}", username, site);
-    
+    let current = Argon2Params { mem_kib: kdf.memory_kib, iterations: kdf.iterations, parallelism: kdf.parallelism };
+    println!(
+        "This vault:  memory {} KiB, {} iterations, parallelism {}",
+        current.mem_kib, current.iterations, current.parallelism
+    );
+    if !current.is_weaker_than(&recommended) {
+        println!("✅ The vault already meets the recommendation");
+    } else if apply {
+        let key_file = read_key_file(key_file)?;
+        let password = rpassword::prompt_password("Enter master password: ")?;
+        Storage::open_with_kdf_target(path, &password, key_file.as_ref(), &recommended).await?;
+        println!("✅ Vault re-encrypted under the recommended settings");
+    } else {
+        println!("Run again with --apply to upgrade the vault");
+    }
     Ok(())
 }
 
-async fn get_entry(
-    storage: &Storage,
-    site: &str,
-    username: Option<&str>,
-    copy: bool,
-    show: bool,
-) -> Result<()> {
-    let filter = SearchFilter {
-        query: Some(site.to_string()),
-        ..Default::default()
-    };
-    
-    let entries = storage.search_entries(&filter).await?;
-    let entry = if let Some(username) = username {
-        entries.iter().find(|e| e.username == username)
-    } else if entries.len() == 1 {
-        entries.first()
-    } else {
-        println!("Multiple entries found:");
-        for (i, entry) in entries.iter().enumerate() {
-            println!("{}: {} @ {}", i + 1, entry.username, entry.site);
-        }
-        return Ok(());
-    };
-    
-    if let Some(entry) = entry {
-        storage.mark_as_used(&entry.id).await?;
-        
-        if show {
-            println!("Password: {}", entry.password);
-        } else if copy {
-            // Cross-platform clipboard (Linux/macOS/Windows/BSD) via arboard,
-            // consistent with how API keys and tokens are copied elsewhere.
-            match arboard::Clipboard::new().and_then(|mut ctx| ctx.set_text(entry.password.clone())) {
-                Ok(()) => println!("Password copied to clipboard"),
-                Err(e) => {
-                    eprintln!("Failed to copy to clipboard: {}", e);
-                    println!("Password: {}", entry.password);
-                }
-            }
-        } else {
-            println!("Username: {}", entry.username);
-            println!("Site: {}", entry.site);
-            if let Some(notes) = &entry.notes {
-                println!("Notes: {}", notes);
-            }
-            println!("Use --show to display password or --copy to copy to clipboard");
+async fn database_maintenance(storage: &Storage, vacuum: bool, check: bool) -> Result<()> {
+    if check {
+        if !storage.integrity_check().await? {
+            return Err(anyhow::anyhow!(
+                "Integrity check failed: the database file is damaged; restore it from a backup"
+            ));
         }
-    } else {
-        println!("No entry found");
+        println!("✅ Integrity check passed");
     }
     
+    if vacuum {
+        let stats = storage.vacuum().await?;
+        let kib = |bytes: u64| bytes as f64 / 1024.0;
+        println!(
+            "✅ Database optimized: {:.1} KiB -> {:.1} KiB",
+            kib(stats.bytes_before),
+            kib(stats.bytes_after)
+        );
+    }
     Ok(())
 }
 
-async fn list_entries(
-    storage: &Storage,
+async fn seed_test_vault(storage: &Storage, count: usize, force: bool) -> Result<()> {
+    let existing = storage.get_entry_count().await?;
+    if existing > 0 && !force {
+        eprintln!("Vault already contains {} entries. Use --force to add test data anyway.", existing);
+        return Ok(());
+    }
+    
+    const BATCH_SIZE: usize = 1000;
+    let start = std::time::Instant::now();
+    let mut seeded = 0;
+    while seeded < count {
+        let batch = BATCH_SIZE.min(count - seeded);
+        storage.add_entries(&sample_entries(batch)).await?;
+        seeded += batch;
+        print!("\rSeeded {}/{} entries", seeded, count);
+        std::io::Write::flush(&mut std::io::stdout())?;
+    }
+    println!();
+    println!("Done in {:.1}s", start.elapsed().as_secs_f64());
+    
+    Ok(())
+}
+
+async fn vault_status(path: &PathBuf, key_file: Option<&Path>, json: bool, unlock: bool) -> Result<()> {
+    let mut status = VaultStatus::read(path).await?;
+    if unlock && status.exists {
+        let key_file = read_key_file(key_file)?;
+        let password = rpassword::prompt_password("Enter master password: ")?;
+        let storage = Storage::open_with_key_file(path, &password, key_file.as_ref()).await?;
+        let secrets_storage =
+            SecretsStorage::from_existing_storage_with_key_file(path, &password, key_file.as_ref()).await?;
+        status = status.unlocked(&storage, &secrets_storage).await?;
+    }
+    
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+    
+    let time = |at: Option<chrono::DateTime<chrono::Utc>>| match at {
+        Some(at) => at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        None => "never".to_string(),
+    };
+    println!("Vault:       {}", status.vault_path.display());
+    if !status.exists {
+        println!("State:       not initialized (run 'pwgen init')");
+        return Ok(());
+    }
+    println!("State:       {}", if status.locked { "locked" } else { "unlocked" });
+    if let (Some(entries), Some(secrets)) = (status.entry_count, status.secret_count) {
+        println!("Entries:     {}", entries);
+        println!("Secrets:     {}", secrets);
+    }
+    println!("Key file:    {}", if status.key_file_required { "required" } else { "not used" });
+    println!("Last backup: {}", time(status.last_backup_at));
+    println!("Last audit:  {}", time(status.last_audit_at));
+    if let Some(kdf) = &status.kdf {
+        println!(
+            "KDF:         {} (memory {} KiB, {} iterations, parallelism {})",
+            kdf.algorithm, kdf.memory_kib, kdf.iterations, kdf.parallelism
+        );
+    }
+    Ok(())
+}
+
+/// The part of an item ID shown in listings, enough to tell items apart.
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(8)]
+}
+
+async fn trash(path: &PathBuf, key_file: Option<&Path>, action: TrashAction) -> Result<()> {
+    if !path.exists() {
+        eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
+        std::process::exit(1);
+    }
+    
+    let key_file = read_key_file(key_file)?;
+    let password = rpassword::prompt_password("Enter master password: ")?;
+    let storage = Storage::open_with_key_file(path, &password, key_file.as_ref()).await?;
+    let secrets_storage =
+        SecretsStorage::from_existing_storage_with_key_file(path, &password, key_file.as_ref()).await?;
+    let entries = storage.list_trashed().await?;
+    let secrets = secrets_storage.list_trashed_secrets().await?;
+    
+    match action {
+        TrashAction::List => {
+            if entries.is_empty() && secrets.is_empty() {
+                println!("The trash is empty");
+                return Ok(());
+            }
+            println!("{:<10} {:<8} {:<40} {:<20}", "ID", "Kind", "Name", "Deleted");
+            println!("{:-<80}", "");
+            for trashed in &entries {
+                println!(
+                    "{:<10} {:<8} {:<40} {:<20}",
+                    short_id(&trashed.item.id),
+                    "entry",
+                    format!("{} @ {}", trashed.item.username, trashed.item.site),
+                    trashed.deleted_at.format("%Y-%m-%d %H:%M")
+                );
+            }
+            for trashed in &secrets {
+                println!(
+                    "{:<10} {:<8} {:<40} {:<20}",
+                    short_id(&trashed.item.id),
+                    "secret",
+                    trashed.item.name,
+                    trashed.deleted_at.format("%Y-%m-%d %H:%M")
+                );
+            }
+            let retention = storage.trash_retention_days().await?;
+            if retention > 0 {
+                eprintln!("\nItems are purged {} day(s) after deletion", retention);
+            }
+        }
+        
+        TrashAction::Restore { id } => {
+            let entry_ids = entries.iter().map(|t| (t.item.id.as_str(), false));
+            let secret_ids = secrets.iter().map(|t| (t.item.id.as_str(), true));
+            let matches: Vec<_> = entry_ids.chain(secret_ids).filter(|(item_id, _)| item_id.starts_with(&id)).collect();
+            match matches.as_slice() {
+                [] => {
+                    eprintln!("Nothing in the trash has ID '{}'", id);
+                    std::process::exit(1);
+                }
+                [(item_id, is_secret)] => {
+                    if *is_secret {
+                        secrets_storage.restore_secret(item_id).await?;
+                        println!("Secret restored");
+                    } else {
+                        storage.restore_entry(item_id).await?;
+                        println!("Entry restored");
+                    }
+                }
+                _ => {
+                    eprintln!("'{}' matches {} items; give more of the ID", id, matches.len());
+                    std::process::exit(1);
+                }
+            }
+        }
+        
+        TrashAction::Empty { force } => {
+            let count = entries.len() + secrets.len();
+            if count == 0 {
+                println!("The trash is empty");
+                return Ok(());
+            }
+            if !force {
+                print!("Permanently delete {} item(s) in the trash? [y/N] ", count);
+                use std::io::{self, Write};
+                io::stdout().flush()?;
+                
+                let mut input = String::new();
+                io::stdin().read_line(&mut input)?;
+                
+                if !input.trim().eq_ignore_ascii_case("y") {
+                    println!("Cancelled");
+                    return Ok(());
+                }
+            }
+            let purged = storage.purge_trash(chrono::Duration::zero()).await?
+                + secrets_storage.purge_trashed_secrets(chrono::Duration::zero()).await?;
+            println!("Permanently deleted {} item(s)", purged);
+        }
+        
+        TrashAction::Retention { days: None } => {
+            match storage.trash_retention_days().await? {
+                0 => println!("Deleted items are kept until the trash is emptied"),
+                days => println!("Deleted items are purged after {} day(s)", days),
+            }
+        }
+        
+        TrashAction::Retention { days: Some(days) } => {
+            storage.set_trash_retention_days(days).await?;
+            match days {
+                0 => println!("Deleted items will be kept until the trash is emptied"),
+                days => println!("Deleted items will be purged after {} day(s)", days),
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+async fn audit_duplicates(storage: &Storage) -> Result<()> {
+    let clusters = storage.find_duplicate_passwords().await?;
+    if clusters.is_empty() {
+        println!("No reused passwords found");
+        return Ok(());
+    }
+    
+    for (index, cluster) in clusters.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("Password shared by {} entries:", cluster.len());
+        for entry in cluster {
+            println!("  {} ({})", entry.site, entry.username);
+        }
+    }
+    let reused: usize = clusters.iter().map(Vec::len).sum();
+    eprintln!(
+        "\n{} entries share {} password{}; give each a unique password",
+        reused,
+        clusters.len(),
+        if clusters.len() == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+async fn audit_duplicate_entries(storage: &Storage) -> Result<()> {
+    let groups = storage.find_duplicate_entries().await?;
+    if groups.is_empty() {
+        println!("No duplicate entries found");
+        return Ok(());
+    }
+    
+    for (index, group) in groups.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("{} ({}), {} entries:", normalize_site(&group[0].site), group[0].username, group.len());
+        for entry in group {
+            println!("  {}  {}  added {}", short_id(&entry.id), entry.site, entry.created_at.format("%Y-%m-%d"));
+        }
+    }
+    eprintln!("\nKeep one entry per account and delete the others");
+    Ok(())
+}
+
+async fn audit_vault(path: &PathBuf, key_file: Option<&Path>, options: AuditArgs) -> Result<()> {
+    if !path.exists() {
+        eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
+        std::process::exit(1);
+    }
+    
+    let key_file = read_key_file(key_file)?;
+    let password = rpassword::prompt_password("Enter master password: ")?;
+    let storage = Storage::open_with_key_file(path, &password, key_file.as_ref()).await?;
+    let secrets_storage = if options.no_expiring {
+        None
+    } else {
+        Some(SecretsStorage::from_existing_storage_with_key_file(path, &password, key_file.as_ref()).await?)
+    };
+    
+    let checks = AuditChecks {
+        weak: !options.no_weak,
+        reused: !options.no_reused,
+        max_age_days: (!options.no_old).then_some(options.max_age),
+        missing_totp: options.missing_totp,
+        expiring_within_days: (!options.no_expiring).then_some(options.expiring_within),
+    };
+    let audit = VaultAudit::run(&storage, secrets_storage.as_ref(), &checks).await?;
+    
+    if options.output.format == OutputFormat::Json {
+        return print_json(&serde_json::to_value(&audit)?);
+    }
+    
+    println!("Vault security score: {}/100 ({} entries checked)", audit.score, audit.entries_checked);
+    let print_entries = |title: String, entries: &[AuditedEntry]| {
+        println!();
+        println!("{} {}: {}", if entries.is_empty() { "✅" } else { "⚠" }, title, entries.len());
+        for entry in entries {
+            println!("  {} ({})", entry.site, entry.username);
+        }
+    };
+    if let Some(weak) = &audit.weak {
+        print_entries("Weak passwords".to_string(), weak);
+    }
+    if let Some(reused) = &audit.reused {
+        println!();
+        let entries: usize = reused.iter().map(Vec::len).sum();
+        println!("{} Reused passwords: {} entries share {} password(s)", if reused.is_empty() { "✅" } else { "⚠" }, entries, reused.len());
+        for group in reused {
+            let sites: Vec<String> = group.iter().map(|entry| format!("{} ({})", entry.site, entry.username)).collect();
+            println!("  {}", sites.join(", "));
+        }
+    }
+    if let Some(old) = &audit.old {
+        print_entries(format!("Passwords not changed in {} days", options.max_age), old);
+    }
+    if let Some(missing_totp) = &audit.missing_totp {
+        print_entries("Sites offering 2FA without a 2FA secret".to_string(), missing_totp);
+    }
+    if let Some(expiring) = &audit.expiring {
+        println!();
+        println!("{} Expiring secrets and keys due for rotation: {}", if expiring.is_empty() { "✅" } else { "⚠" }, expiring.len());
+        for notice in expiring {
+            println!("  {}", notice);
+        }
+    }
+    Ok(())
+}
+
+fn password_strength(password: Option<String>) -> Result<()> {
+    let password = match password.filter(|p| p != "-") {
+        Some(password) => password,
+        None if std::io::stdin().is_terminal() => rpassword::prompt_password("Password to rate: ")?,
+        None => {
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line)?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+    
+    let report = estimate_strength(&password);
+    println!("Score: {}/4 ({})", report.score(), report.level.label());
+    println!("Guesses: ~10^{:.1}", report.guesses_log10);
+    println!(
+        "Crack time: {} (offline attack, {} guesses/second)",
+        report.crack_time_display(),
+        GUESSES_PER_SECOND
+    );
+    if !report.reasons.is_empty() {
+        println!("Weaknesses:");
+        for reason in &report.reasons {
+            println!("  - {}", reason);
+        }
+    }
+    Ok(())
+}
+
+fn device_name(name: Option<String>, clear: bool) -> Result<()> {
+    if clear {
+        save_device_name(None)?;
+        println!("Device name cleared; changes made here will no longer be attributed");
+    } else if let Some(name) = name {
+        let saved = save_device_name(Some(&name))?.unwrap_or(name);
+        println!("Changes made on this machine will be recorded as '{}'", saved);
+    } else {
+        match load_device_name() {
+            Some(name) => println!("{}", name),
+            None => println!("No device name set. Use 'pwgen device-name <NAME>' to set one."),
+        }
+    }
+    Ok(())
+}
+
+/// Print who added and last changed an item, when that was recorded.
+fn print_attribution(created_by: &Option<String>, modified_by: &Option<String>) {
+    if let Some(created_by) = created_by {
+        println!("Created by: {}", created_by);
+    }
+    if let Some(modified_by) = modified_by {
+        println!("Modified by: {}", modified_by);
+    }
+}
+
+/// Canonicalize the entry's tags as configured, then refuse an entry that
+/// breaks the entry rules, listing every violation.
+fn apply_entry_rules(entry: &mut DecryptedPasswordEntry) -> Result<()> {
+    let rules = EntryRules::load()?;
+    entry.tags = rules.canonical_tags(&entry.tags);
+    let violations = rules.validate(entry);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    for violation in &violations {
+        eprintln!("  {}", violation);
+    }
+    anyhow::bail!("Entry does not satisfy {} entry rule(s)", violations.len())
+}
+
+async fn change_master_password(path: &PathBuf, key_file: Option<&Path>) -> Result<()> {
+    if !path.exists() {
+        eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
+        std::process::exit(1);
+    }
+    
+    let key_file = read_key_file(key_file)?;
+    let current = rpassword::prompt_password("Enter current master password: ")?;
+    let mut storage = match Storage::open_with_key_file(path, &current, key_file.as_ref()).await {
+        Ok(storage) => storage,
+        Err(e) => {
+            eprintln!("Failed to open vault: {}", e);
+            std::process::exit(1);
+        }
+    };
+    
+    let new_password = rpassword::prompt_password("Enter new master password: ")?;
+    let confirm = rpassword::prompt_password("Confirm new master password: ")?;
+    if new_password != confirm {
+        eprintln!("Passwords do not match!");
+        return Ok(());
+    }
+    if new_password.is_empty() {
+        eprintln!("The new master password must not be empty");
+        return Ok(());
+    }
+    
+    println!("Re-encrypting vault...");
+    storage.change_master_password(&current, &new_password, key_file.as_ref()).await?;
+    println!("Master password changed. Existing backups keep the backup password they were created with.");
+    
+    Ok(())
+}
+
+/// Unlock with the recovery key and replace the forgotten master password.
+/// A key file given with `--key-file` is required from then on; without one
+/// the vault no longer needs a key file.
+async fn recover_vault(path: &PathBuf, key_file: Option<&Path>) -> Result<()> {
+    if !path.exists() {
+        eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
+        std::process::exit(1);
+    }
+    
+    let key_file = read_key_file(key_file)?;
+    let recovery_key = RecoveryKey::parse(&rpassword::prompt_password("Enter recovery key: ")?)?;
+    let mut storage = match Storage::open_with_recovery_key(path, &recovery_key).await {
+        Ok(storage) => storage,
+        Err(e) => {
+            eprintln!("Failed to open vault: {}", e);
+            std::process::exit(1);
+        }
+    };
+    
+    let new_password = rpassword::prompt_password("Enter new master password: ")?;
+    let confirm = rpassword::prompt_password("Confirm new master password: ")?;
+    if new_password != confirm {
+        eprintln!("Passwords do not match!");
+        return Ok(());
+    }
+    if new_password.is_empty() {
+        eprintln!("The new master password must not be empty");
+        return Ok(());
+    }
+    
+    println!("Re-encrypting vault...");
+    storage.reset_master_password(&new_password, key_file.as_ref()).await?;
+    println!("Master password reset. The recovery key still works.");
+    
+    Ok(())
+}
+
+fn read_key_file(path: Option<&Path>) -> Result<Option<KeyFile>> {
+    Ok(path.map(KeyFile::read).transpose()?)
+}
+
+async fn open_vault(path: &PathBuf, key_file: Option<&Path>) -> Result<Storage> {
+    if !path.exists() {
+        eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
+        std::process::exit(1);
+    }
+    
+    let key_file = read_key_file(key_file)?;
+    let password = rpassword::prompt_password("Enter master password: ")?;
+    
+    match Storage::open_with_key_file(path, &password, key_file.as_ref()).await {
+        Ok(mut storage) => {
+            storage.set_device_name(load_device_name());
+            Ok(storage)
+        }
+        Err(e) => {
+            eprintln!("Failed to open vault: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Open the vault for both entries and secrets with one password prompt.
+async fn open_vault_with_secrets(path: &PathBuf, key_file: Option<&Path>) -> Result<(Storage, SecretsStorage)> {
+    if !path.exists() {
+        eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
+        std::process::exit(1);
+    }
+    
+    let key_file = read_key_file(key_file)?;
+    let password = rpassword::prompt_password("Enter master password: ")?;
+    
+    let opened = async {
+        let storage = Storage::open_with_key_file(path, &password, key_file.as_ref()).await?;
+        let secrets_storage =
+            SecretsStorage::from_existing_storage_with_key_file(path, &password, key_file.as_ref()).await?;
+        Ok::<_, pwgen_core::Error>((storage, secrets_storage))
+    };
+    match opened.await {
+        Ok((mut storage, mut secrets_storage)) => {
+            storage.set_device_name(load_device_name());
+            secrets_storage.set_device_name(load_device_name());
+            Ok((storage, secrets_storage))
+        }
+        Err(e) => {
+            eprintln!("Failed to open vault: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn add_entry(
+    storage: &Storage,
+    site: String,
+    username: String,
+    generate: bool,
+    length: usize,
+    notes: Option<String>,
+    tags: Vec<String>,
+    totp: bool,
+) -> Result<()> {
+    let password = if generate {
+        let config = PasswordConfig {
+            length,
+            ..Default::default()
+        };
+        PasswordGenerator::generate(&config)?
+    } else {
+        rpassword::prompt_password("Enter password: ")?
+    };
+    
+    let id = hash_entry_id(&site, &username);
+    let mut entry = DecryptedPasswordEntry {
+        id,
+        site: site.clone(),
+        username: username.clone(),
+        password,
+        notes,
+        tags,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+        last_used: None,
+        password_changed_at: chrono::Utc::now(),
+        favorite: false,
+        urls: Vec::new(),
+        created_by: None,
+        modified_by: None,
+        totp_secret: if totp { prompt_totp_secret()? } else { None },
+        password_history: Vec::new(),
+        require_reauth: false,
+        password_policy: None,
+    };
+    
+    apply_entry_rules(&mut entry)?;
+    storage.add_entry(&entry).await?;
+    println!("Password saved for {} @ {}", username, site);
+    
+    Ok(())
+}
+
+/// The entry for `site`, narrowed down by `username` when given. Prints the
+/// candidates, or that there are none, and returns `None` otherwise.
+async fn select_entry(
+    storage: &Storage,
+    site: &str,
+    username: Option<&str>,
+) -> Result<Option<DecryptedPasswordEntry>> {
+    let filter = SearchFilter {
+        query: Some(site.to_string()),
+        ..Default::default()
+    };
+    
+    let entries = storage.search_entries(&filter).await?;
+    let entry = if let Some(username) = username {
+        entries.into_iter().find(|e| e.username == username)
+    } else if entries.len() <= 1 {
+        entries.into_iter().next()
+    } else {
+        println!("Multiple entries found:");
+        for (i, entry) in entries.iter().enumerate() {
+            println!("{}: {} @ {}", i + 1, entry.username, entry.site);
+        }
+        return Ok(None);
+    };
+    
+    if entry.is_none() {
+        println!("No entry found");
+    }
+    Ok(entry)
+}
+
+async fn get_entry(
+    storage: &Storage,
+    site: &str,
+    username: Option<&str>,
+    copy: bool,
+    show: bool,
+    output: OutputFormat,
+) -> Result<()> {
+    let Some(entry) = select_entry(storage, site, username).await? else {
+        return Ok(());
+    };
+    
+    storage.touch_entry(&entry.id).await?;
+    
+    if output == OutputFormat::Json {
+        if copy {
+            match arboard::Clipboard::new().and_then(|mut ctx| ctx.set_text(entry.reveal())) {
+                Ok(()) => eprintln!("Password copied to clipboard"),
+                Err(e) => eprintln!("Failed to copy to clipboard: {}", e),
+            }
+        }
+        // The password is left out when an entry is serialized
+        let mut value = serde_json::to_value(&entry)?;
+        if show {
+            value["password"] = entry.reveal().into();
+        }
+        return print_json(&value);
+    }
+    
+    if show {
+        println!("Password: {}", entry.reveal());
+    } else if copy {
+        // Cross-platform clipboard (Linux/macOS/Windows/BSD) via arboard,
+        // consistent with how API keys and tokens are copied elsewhere.
+        match arboard::Clipboard::new().and_then(|mut ctx| ctx.set_text(entry.reveal())) {
+            Ok(()) => println!("Password copied to clipboard"),
+            Err(e) => {
+                // Don't fall back to printing: the user didn't ask for --show
+                eprintln!("Failed to copy to clipboard: {}", e);
+                println!("Use --show to display the password instead");
+            }
+        }
+    } else {
+        println!("Username: {}", entry.username);
+        println!("Site: {}", entry.site);
+        if let Some(notes) = &entry.notes {
+            println!("Notes: {}", notes);
+        }
+        if entry.totp_secret.is_some() {
+            println!("2FA: use 'pwgen totp' for the current code");
+        }
+        print_attribution(&entry.created_by, &entry.modified_by);
+        println!("Use --show to display password or --copy to copy to clipboard");
+    }
+    
+    Ok(())
+}
+
+async fn totp_code(storage: &Storage, site: &str, username: Option<&str>) -> Result<()> {
+    let Some(entry) = select_entry(storage, site, username).await? else {
+        return Ok(());
+    };
+    let Some(secret) = &entry.totp_secret else {
+        eprintln!("{} @ {} has no two-factor secret. Add one with 'pwgen update --totp'.", entry.username, entry.site);
+        std::process::exit(1);
+    };
+    
+    let now = chrono::Utc::now();
+    println!("{}", generate_totp(secret, now)?);
+    // Report on stderr so the code alone can still be piped
+    eprintln!("Valid for {}s", totp_seconds_remaining(secret, now));
+    Ok(())
+}
+
+async fn import_totp(storage: &Storage, source: &str) -> Result<()> {
+    let mut text = if source.to_lowercase().starts_with("otpauth") {
+        source.to_string()
+    } else {
+        std::fs::read_to_string(expand_tilde(Path::new(source)))?
+    };
+    let import = parse_totp_import(&text);
+    text.zeroize();
+    let import = import?;
+    
+    let mut report = storage.import_totp_seeds(&import.seeds).await?;
+    report.skipped.extend(import.unsupported);
+    for label in &report.attached {
+        println!("✓ Added 2FA to {}", label);
+    }
+    for label in &report.created {
+        println!("+ New entry {}", label);
+    }
+    for (label, reason) in &report.skipped {
+        println!("- Skipped {}: {}", label, reason);
+    }
+    println!(
+        "Imported {} seed(s): {} added to existing entries, {} new entries, {} skipped",
+        report.attached.len() + report.created.len(),
+        report.attached.len(),
+        report.created.len(),
+        report.skipped.len()
+    );
+    if !report.created.is_empty() {
+        println!("New entries have no password; set one with 'pwgen update <site> <username> --new-password'");
+    }
+    Ok(())
+}
+
+async fn password_history(
+    storage: &Storage,
+    site: &str,
+    username: Option<&str>,
+    show: bool,
+    restore: Option<usize>,
+) -> Result<()> {
+    let Some(mut entry) = select_entry(storage, site, username).await? else {
+        return Ok(());
+    };
+    if entry.password_history.is_empty() {
+        println!("{} @ {} has no previous passwords", entry.username, entry.site);
+        return Ok(());
+    }
+    
+    if let Some(number) = restore {
+        let Some(previous) = number.checked_sub(1).and_then(|i| entry.password_history.get(i)) else {
+            eprintln!("No previous password {}; there are {}", number, entry.password_history.len());
+            std::process::exit(1);
+        };
+        let changed_at = previous.changed_at;
+        entry.password = previous.password.clone();
+        storage.update_entry(&entry).await?;
+        println!(
+            "Restored the password replaced on {}; the current one was added to the history",
+            changed_at.format("%Y-%m-%d %H:%M")
+        );
+        return Ok(());
+    }
+    
+    println!("{:<4} {:<20}", "#", "Replaced");
+    println!("{:-<24}", "");
+    for (i, previous) in entry.password_history.iter().enumerate() {
+        let changed_at = previous.changed_at.format("%Y-%m-%d %H:%M").to_string();
+        if show {
+            println!("{:<4} {:<20} {}", i + 1, changed_at, previous.password);
+        } else {
+            println!("{:<4} {:<20}", i + 1, changed_at);
+        }
+    }
+    if !show {
+        eprintln!("Use --show to display the passwords or --restore N to make one current again");
+    }
+    Ok(())
+}
+
+fn prompt_totp_secret() -> Result<Option<String>> {
+    let input = rpassword::prompt_password("Enter 2FA secret (base32 key or otpauth:// URI): ")?;
+    if input.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(normalize_totp_secret(&input)?))
+}
+
+async fn list_entries(
+    storage: &Storage,
     query: Option<String>,
     tags: Vec<String>,
     favorites: bool,
+    output: OutputFormat,
 ) -> Result<()> {
     let filter = SearchFilter {
         query,
@@ -4110,6 +6379,10 @@ async fn list_entries(
     
     let entries = storage.search_entries(&filter).await?;
     
+    if output == OutputFormat::Json {
+        return print_json(&serde_json::to_value(&entries)?);
+    }
+    
     if entries.is_empty() {
         println!("No entries found");
     } else {
@@ -4135,6 +6408,7 @@ async fn update_entry(
     new_password: bool,
     notes: Option<String>,
     tags: Vec<String>,
+    totp: bool,
 ) -> Result<()> {
     let id = hash_entry_id(&site, &username);
     let mut entry = storage.get_entry(&id).await?;
@@ -4153,7 +6427,12 @@ async fn update_entry(
         entry.tags = tags;
     }
     
+    if totp {
+        entry.totp_secret = prompt_totp_secret()?;
+    }
+    
     entry.updated_at = chrono::Utc::now();
+    apply_entry_rules(&mut entry)?;
     storage.update_entry(&entry).await?;
     
     println!("Entry updated successfully");
@@ -4176,9 +6455,64 @@ async fn delete_entry(storage: &Storage, site: &str, username: &str, force: bool
     }
     
     let id = hash_entry_id(site, username);
+    storage.get_entry(&id).await?;
     storage.delete_entry(&id).await?;
     
-    println!("Entry deleted successfully");
+    println!("Entry moved to the trash");
+    eprintln!("Restore it with 'pwgen trash restore {}'", short_id(&id));
+    Ok(())
+}
+
+/// Generate a new password for the matching entry, from `pattern`, or
+/// `length` characters under the entry's site rules. Exits with 1 unless
+/// exactly one entry matches.
+async fn rotate_password(
+    storage: &Storage,
+    site: &str,
+    username: Option<&str>,
+    length: Option<usize>,
+    pattern: Option<&str>,
+    template: bool,
+    show: bool,
+    copy: bool,
+) -> Result<()> {
+    let Some(mut entry) = select_entry(storage, site, username).await? else {
+        if username.is_none() {
+            eprintln!("Use --username to pick the entry to rotate");
+        }
+        std::process::exit(1);
+    };
+    
+    entry.password = match pattern {
+        Some(pattern) if template => PasswordGenerator::generate_from_pattern(pattern)?,
+        Some(pattern) => PasswordGenerator::generate_pattern(pattern)?,
+        None => {
+            let mut config = entry
+                .password_policy
+                .as_ref()
+                .map(|policy| policy.config())
+                .unwrap_or_default();
+            if let Some(length) = length {
+                config.length = length;
+            }
+            PasswordGenerator::generate(&config)?
+        }
+    };
+    entry.updated_at = chrono::Utc::now();
+    apply_entry_rules(&mut entry)?;
+    storage.update_entry(&entry).await?;
+    
+    // Report on stderr so the password alone can still be piped
+    eprintln!("Password rotated for {} @ {}", entry.username, entry.site);
+    if show {
+        println!("{}", entry.reveal());
+    }
+    if copy {
+        match arboard::Clipboard::new().and_then(|mut ctx| ctx.set_text(entry.reveal())) {
+            Ok(()) => eprintln!("Password copied to clipboard"),
+            Err(e) => eprintln!("Failed to copy to clipboard: {}", e),
+        }
+    }
     Ok(())
 }
 
@@ -4190,12 +6524,52 @@ fn generate_password(
     symbols: bool,
     custom_symbols: Option<String>,
     escape: bool,
+    mobile_friendly: bool,
     passphrase: bool,
     words: usize,
     separator: String,
+    wordlist: &str,
+    with_number: bool,
+    pattern: Option<String>,
+    template: bool,
+    pronounceable: bool,
 ) -> Result<()> {
     let password = if passphrase {
-        PasswordGenerator::generate_passphrase(words, &separator, true)?
+        let source = wordlist.parse::<Wordlist>()?;
+        let wordlist = source.load()?;
+        let passphrase = PasswordGenerator::generate_passphrase_from(&wordlist, words, &separator, true, with_number)?;
+        // Report on stderr so the passphrase alone can still be piped
+        eprintln!(
+            "Word list: {}, {} words, {:.1} bits per word, ~{:.0} bits for {} words",
+            source,
+            wordlist.len(),
+            wordlist.bits_per_word(),
+            passphrase.entropy_bits,
+            words
+        );
+        passphrase.phrase
+    } else if let Some(pattern) = pattern {
+        let (password, bits) = if template {
+            (PasswordGenerator::generate_from_pattern(&pattern)?, PasswordGenerator::template_entropy(&pattern)?)
+        } else {
+            (PasswordGenerator::generate_pattern(&pattern)?, PasswordGenerator::pattern_entropy(&pattern)?)
+        };
+        // Report on stderr so the password alone can still be piped
+        eprintln!("Pattern: ~{:.0} bits of entropy", bits);
+        if escape {
+            PasswordGenerator::escape_for_shell(&password)
+        } else {
+            password
+        }
+    } else if pronounceable {
+        let pronounceable = PasswordGenerator::generate_pronounceable(length, numbers, symbols)?;
+        // Report on stderr so the password alone can still be piped
+        eprintln!("Pronounceable: ~{:.0} bits of entropy", pronounceable.entropy_bits);
+        if escape {
+            PasswordGenerator::escape_for_shell(&pronounceable.password)
+        } else {
+            pronounceable.password
+        }
     } else {
         let config = PasswordConfig {
             length,
@@ -4204,9 +6578,18 @@ fn generate_password(
             include_numbers: numbers,
             include_symbols: symbols,
             custom_symbols,
+            mobile_friendly,
             ..Default::default()
         };
         
+        if mobile_friendly {
+            // Report on stderr so the password alone can still be piped
+            let standard = PasswordConfig { mobile_friendly: false, ..config.clone() };
+            let bits = PasswordGenerator::estimate_entropy(&config);
+            let cost = PasswordGenerator::estimate_entropy(&standard) - bits;
+            eprintln!("Mobile-friendly: ~{:.0} bits of entropy ({:.0} bits less than standard)", bits, cost);
+        }
+        
         if escape {
             PasswordGenerator::generate_escaped(&config)?
         } else {
@@ -4223,13 +6606,136 @@ async fn import_passwords(_storage: &Storage, format: &str, _file: &PathBuf) ->
     Ok(())
 }
 
-async fn export_passwords(_storage: &Storage, format: &str, _output: &PathBuf) -> Result<()> {
-    println!("Export functionality for format '{}' not yet implemented", format);
+async fn export_vault(
+    path: &PathBuf,
+    key_file: Option<&Path>,
+    format: &str,
+    output: &Path,
+    include_secrets: bool,
+    since: Option<&str>,
+    encrypt: bool,
+) -> Result<()> {
+    let format: ExportFormat = format.parse()?;
+    let since = since.map(parse_since).transpose()?;
+    if !path.exists() {
+        eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
+        std::process::exit(1);
+    }
+    
+    let key_file = read_key_file(key_file)?;
+    let password = rpassword::prompt_password("Enter master password: ")?;
+    let storage = Storage::open_with_key_file(path, &password, key_file.as_ref()).await?;
+    let entries = match since {
+        Some(since) => storage.get_entries_since(since).await?,
+        None => storage.search_entries(&SearchFilter::default()).await?,
+    };
+    let secrets = if include_secrets {
+        let secrets_storage =
+            SecretsStorage::from_existing_storage_with_key_file(path, &password, key_file.as_ref()).await?;
+        let mut secrets = secrets_storage.search_secrets(&SecretFilter::default()).await?;
+        if let Some(since) = since {
+            secrets.retain(|secret| secret.updated_at >= since);
+        }
+        secrets
+    } else {
+        Vec::new()
+    };
+    
+    let mut contents = Vec::new();
+    write_export(&entries, &secrets, format, &mut contents)?;
+    if encrypt {
+        let export_password = rpassword::prompt_password("Enter export password: ")?;
+        if rpassword::prompt_password("Confirm export password: ")? != export_password {
+            return Err(anyhow::anyhow!("Export passwords do not match"));
+        }
+        contents = encrypt_export(&contents, &export_password)?;
+    }
+    write_private_file(output, &contents)?;
+    
+    println!("Exported {} entries and {} secrets to {}", entries.len(), secrets.len(), output.display());
+    if !encrypt {
+        eprintln!("⚠️  The file contains your passwords in plaintext. Delete it once it has been imported.");
+    }
+    Ok(())
+}
+
+/// Export every entry and secret to an age-encrypted file, for the public
+/// keys given or else a passphrase.
+async fn export_age_file(path: &PathBuf, key_file: Option<&Path>, output: &Path, recipients: Vec<String>) -> Result<()> {
+    if !path.exists() {
+        eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
+        std::process::exit(1);
+    }
+    let recipients = if recipients.is_empty() {
+        let passphrase = rpassword::prompt_password("Enter export passphrase: ")?;
+        if rpassword::prompt_password("Confirm export passphrase: ")? != passphrase {
+            return Err(anyhow::anyhow!("Export passphrases do not match"));
+        }
+        vec![AgeRecipient::Passphrase(passphrase)]
+    } else {
+        recipients.into_iter().map(AgeRecipient::X25519).collect()
+    };
+    
+    let key_file = read_key_file(key_file)?;
+    let password = rpassword::prompt_password("Enter master password: ")?;
+    let storage = Storage::open_with_key_file(path, &password, key_file.as_ref()).await?;
+    let secrets_storage =
+        SecretsStorage::from_existing_storage_with_key_file(path, &password, key_file.as_ref()).await?;
+    let summary = export_age(&storage, Some(&secrets_storage), output, &recipients).await?;
+    
+    println!(
+        "Exported {} entries and {} secrets to {}",
+        summary.entry_count,
+        summary.secret_count,
+        output.display()
+    );
+    println!("Decrypt it with `pwgen-cli import --format age` or `age --decrypt`.");
+    Ok(())
+}
+
+/// Restore a file written by `export --format age` into the vault.
+async fn import_age_file(
+    path: &PathBuf,
+    key_file: Option<&Path>,
+    file: &Path,
+    identity: Option<&Path>,
+    conflict_resolution: &str,
+) -> Result<()> {
+    if !path.exists() {
+        eprintln!("Vault not found at {:?}. Run 'pwgen init' first.", path);
+        std::process::exit(1);
+    }
+    let conflict_resolution = parse_conflict_resolution(conflict_resolution)?;
+    let identity = match identity {
+        Some(identity) => AgeIdentity::File(std::fs::read_to_string(identity)?),
+        None => AgeIdentity::Passphrase(rpassword::prompt_password("Enter export passphrase: ")?),
+    };
+    
+    let key_file = read_key_file(key_file)?;
+    let password = rpassword::prompt_password("Enter master password: ")?;
+    let mut storage = Storage::open_with_key_file(path, &password, key_file.as_ref()).await?;
+    let secrets_storage =
+        SecretsStorage::from_existing_storage_with_key_file(path, &password, key_file.as_ref()).await?;
+    let options = RestoreOptions { conflict_resolution, ..Default::default() };
+    let result = import_age(file, &identity, &mut storage, Some(&secrets_storage), options).await?;
+    
+    println!("Import completed!");
+    print_restore_result(&result);
+    Ok(())
+}
+
+fn decrypt_export_file(file: &Path, output: &Path) -> Result<()> {
+    let contents = std::fs::read(file)?;
+    let password = rpassword::prompt_password("Enter export password: ")?;
+    let plaintext = decrypt_export(&contents, &password)?;
+    write_private_file(output, &plaintext)?;
+    println!("Decrypted export written to {}", output.display());
     Ok(())
 }
 
 async fn create_backup(
     storage: &Storage,
+    secrets_storage: &SecretsStorage,
     output: &PathBuf,
     incremental: bool,
     since: Option<String>,
@@ -4258,11 +6764,21 @@ async fn create_backup(
         };
         
         println!("Creating incremental backup since {}", since_date.to_rfc3339());
-        BackupManager::create_incremental_backup(storage, output, &backup_password, since_date).await?
+        BackupManager::create_incremental_backup(
+            storage,
+            Some(secrets_storage),
+            output,
+            &backup_password,
+            since_date,
+            print_backup_progress,
+        )
+        .await?
     } else {
         println!("Creating full backup...");
-        BackupManager::create_backup(storage, output, &backup_password).await?
+        BackupManager::create_backup(storage, Some(secrets_storage), output, &backup_password, print_backup_progress)
+            .await?
     };
+    eprintln!();
     
     println!("Backup created successfully!");
     println!("Backup ID: {}", metadata.id);
@@ -4274,8 +6790,27 @@ async fn create_backup(
     Ok(())
 }
 
+/// Redraw a one-line progress bar on stderr.
+fn print_backup_progress(progress: BackupProgress) {
+    const WIDTH: usize = 30;
+    let filled = (progress.fraction() * WIDTH as f32).round() as usize;
+    let detail = match progress.stage {
+        BackupStage::Writing => format!("{}/{} bytes", progress.bytes_written, progress.total_bytes),
+        _ => format!("{}/{} entries", progress.entries_processed, progress.total_entries),
+    };
+    eprint!(
+        "\r{:<10} [{}{}] {:<30}",
+        progress.stage,
+        "#".repeat(filled.min(WIDTH)),
+        " ".repeat(WIDTH - filled.min(WIDTH)),
+        detail
+    );
+    let _ = std::io::Write::flush(&mut std::io::stderr());
+}
+
 async fn restore_backup(
     storage: &mut Storage,
+    secrets_storage: &SecretsStorage,
     backup_file: &PathBuf,
     conflict_resolution: String,
 ) -> Result<()> {
@@ -4285,12 +6820,18 @@ async fn restore_backup(
     }
     
     // Read backup metadata first
-    let metadata = BackupManager::read_backup_metadata(backup_file).await?;
-    println!("Backup Information:");
-    println!("  ID: {}", metadata.id);
-    println!("  Created: {}", metadata.created_at.to_rfc3339());
-    println!("  Entries: {}", metadata.entry_count);
-    println!("  File size: {} bytes", metadata.file_size);
+    let legacy_plaintext = BackupManager::is_legacy_plaintext_backup(backup_file).await?;
+    if legacy_plaintext {
+        eprintln!("WARNING: {:?} is an UNENCRYPTED backup from an older PwGen version.", backup_file);
+        eprintln!("WARNING: Anyone with a copy of this file can read your passwords.");
+    } else {
+        let metadata = BackupManager::read_backup_metadata(backup_file).await?;
+        println!("Backup Information:");
+        println!("  ID: {}", metadata.id);
+        println!("  Created: {}", metadata.created_at.to_rfc3339());
+        println!("  Entries: {}", metadata.entry_count);
+        println!("  File size: {} bytes", metadata.file_size);
+    }
     
     print!("Continue with restore? [y/N] ");
     use std::io::{self, Write};
@@ -4304,39 +6845,41 @@ async fn restore_backup(
         return Ok(());
     }
     
-    let backup_password = rpassword::prompt_password("Enter backup password: ")?;
+    let backup_password = if legacy_plaintext {
+        String::new()
+    } else {
+        rpassword::prompt_password("Enter backup password: ")?
+    };
     
-    let conflict_res = match conflict_resolution.as_str() {
-        "overwrite" => ConflictResolution::Overwrite,
-        "skip" => ConflictResolution::Skip,
-        "merge" => ConflictResolution::Merge,
-        _ => {
-            eprintln!("Invalid conflict resolution. Use: overwrite, skip, or merge");
+    let conflict_res = match parse_conflict_resolution(&conflict_resolution) {
+        Ok(conflict_res) => conflict_res,
+        Err(e) => {
+            eprintln!("{}", e);
             return Ok(());
         }
     };
     
     let options = RestoreOptions {
         conflict_resolution: conflict_res,
+        ..Default::default()
     };
     
     println!("Restoring backup...");
     
-    match BackupManager::restore_backup(backup_file, &backup_password, storage, options).await {
+    let result = BackupManager::restore_backup(
+        backup_file,
+        &backup_password,
+        storage,
+        Some(secrets_storage),
+        options,
+        print_backup_progress,
+    )
+    .await;
+    eprintln!();
+    match result {
         Ok(result) => {
             println!("Restore completed!");
-            println!("  Total entries in backup: {}", result.total_entries);
-            println!("  Successfully restored: {}", result.restored_count);
-            println!("  Skipped (conflicts): {}", result.skipped_count);
-            println!("  Errors: {}", result.error_count);
-            println!("  Success rate: {:.1}%", result.success_rate());
-            
-            if !result.errors.is_empty() {
-                println!("\nErrors encountered:");
-                for error in &result.errors {
-                    println!("  - {}", error);
-                }
-            }
+            print_restore_result(&result);
         }
         Err(e) => {
             eprintln!("Restore failed: {}", e);
@@ -4346,6 +6889,35 @@ async fn restore_backup(
     Ok(())
 }
 
+fn parse_conflict_resolution(name: &str) -> Result<ConflictResolution> {
+    match name {
+        "overwrite" => Ok(ConflictResolution::Overwrite),
+        "skip" => Ok(ConflictResolution::Skip),
+        "merge" => Ok(ConflictResolution::Merge),
+        "keep-both" => Ok(ConflictResolution::KeepBoth),
+        _ => Err(anyhow::anyhow!("Invalid conflict resolution. Use: overwrite, skip, merge or keep-both")),
+    }
+}
+
+fn print_restore_result(result: &RestoreResult) {
+    println!("  Total entries in backup: {}", result.total_entries);
+    println!("  Successfully restored: {}", result.restored_count);
+    println!("  Updated existing entries: {}", result.updated_count);
+    println!("  Skipped (conflicts): {}", result.skipped_count);
+    println!("  Errors: {}", result.error_count);
+    println!("  Success rate: {:.1}%", result.success_rate());
+    
+    if !result.errors.is_empty() {
+        println!("\nErrors encountered:");
+        for error in &result.errors {
+            println!("  - {}", error);
+        }
+    }
+    for warning in &result.warnings {
+        eprintln!("\nWARNING: {}", warning);
+    }
+}
+
 async fn verify_backup(backup_file: &PathBuf) -> Result<()> {
     if !backup_file.exists() {
         eprintln!("Backup file does not exist: {:?}", backup_file);
@@ -4374,16 +6946,25 @@ async fn verify_backup(backup_file: &PathBuf) -> Result<()> {
 }
 
 // Team sharing command handlers
+fn parse_permission(value: &str) -> Result<Permission> {
+    match value {
+        "read" => Ok(Permission::Read),
+        "write" => Ok(Permission::Write),
+        "share" => Ok(Permission::Share),
+        "admin" => Ok(Permission::Admin),
+        _ => anyhow::bail!("Invalid role: {}. Use: read, write, share, admin", value),
+    }
+}
+
 async fn create_team(
+    storage: &SecretsStorage,
     name: String,
     description: Option<String>,
     owner_email: String,
     owner_name: String,
 ) -> Result<()> {
-    // For now, we'll use a dummy public key - in a real implementation,
-    // this would be generated or provided by the user
-    let owner_public_key = vec![0u8; 32]; // Dummy key
-    let owner_id = uuid::Uuid::new_v4().to_string();
+    // The owner is this vault, with its team sharing key pair
+    let (owner_id, owner_key) = storage.team_identity().await?;
     
     let team = TeamSharingManager::create_team(
         name.clone(),
@@ -4391,213 +6972,263 @@ async fn create_team(
         owner_id,
         owner_email,
         owner_name,
-        owner_public_key,
+        owner_key.public_key(),
     )?;
+    storage.save_team(&team).await?;
     
     println!("✅ Team '{}' created successfully!", name);
     println!("Team ID: {}", team.id);
     println!("Owner: {} ({})", team.members[0].name, team.members[0].email);
-    
-    // In a real implementation, you'd save this to a database
-    println!(r#"
-💡 To save this team permanently, you would need to:
-1. Store team data in a database
-2. Set up proper public key cryptography
-3. Implement user authentication system
-"#);
+    println!("Your public key: {}", hex::encode(owner_key.public_key()));
     
     Ok(())
 }
 
 async fn add_team_member(
+    storage: &SecretsStorage,
     team_id: String,
     member_email: String,
     member_name: String,
     role_str: String,
+    public_key: Option<String>,
 ) -> Result<()> {
-    let _role = match role_str.as_str() {
-        "read" => Permission::Read,
-        "write" => Permission::Write,
-        "share" => Permission::Share,
-        "admin" => Permission::Admin,
-        _ => {
-            eprintln!("Invalid role: {}. Use: read, write, share, admin", role_str);
-            return Ok(());
+    let role = parse_permission(&role_str)?;
+    let public_key = match public_key {
+        Some(key) => {
+            let key = hex::decode(key.trim()).map_err(|_| anyhow::anyhow!("--public-key must be hex"))?;
+            if key.len() != 32 {
+                anyhow::bail!("--public-key must be 32 bytes (64 hex characters)");
+            }
+            key
         }
+        None => Vec::new(),
     };
     
-    println!("✅ Member '{}' ({}) would be added to team {} with {} permission", 
-             member_name, member_email, team_id, role_str);
+    let (user_id, _) = storage.team_identity().await?;
+    let mut team = storage.team(&team_id).await?;
+    let member_id = uuid::Uuid::new_v4().to_string();
+    TeamSharingManager::add_team_member(
+        &mut team,
+        member_id.clone(),
+        member_email.clone(),
+        member_name.clone(),
+        public_key.clone(),
+        role,
+        &user_id,
+    )?;
+    storage.save_team(&team).await?;
     
-    println!(r#"
-💡 In a real implementation, this would:
-1. Validate team exists and user has admin rights
-2. Send invitation to the member
-3. Generate/exchange cryptographic keys
-4. Update team membership in database
-"#);
+    println!("✅ Added {} ({}) to team '{}' with {} permission", member_name, member_email, team.name, role_str);
+    println!("Member ID: {}", member_id);
+    if public_key.is_empty() {
+        println!("⚠️  No public key given; secrets cannot be shared with this team until the member is re-added with --public-key");
+    }
     
     Ok(())
 }
 
-async fn remove_team_member(team_id: String, member_id: String) -> Result<()> {
-    println!("✅ Member {} would be removed from team {}", member_id, team_id);
+async fn remove_team_member(storage: &SecretsStorage, team_id: String, member_id: String) -> Result<()> {
+    let (user_id, _) = storage.team_identity().await?;
+    let mut team = storage.team(&team_id).await?;
+    TeamSharingManager::remove_team_member(&mut team, &member_id, &user_id)?;
     
-    println!(r#"
-💡 In a real implementation, this would:
-1. Validate permissions and team membership
-2. Revoke access to all shared secrets
-3. Update team database
-4. Notify other team members
-"#);
+    // Drop the member's copies of everything shared with the team
+    let teams = vec![team];
+    let mut revoked = 0;
+    for mut share in storage.team_shares(Some(&team_id)).await? {
+        if share.wrapped_keys.iter().any(|k| k.member_id == member_id) {
+            TeamSharingManager::revoke_member_access(&mut share, &member_id, &user_id, &teams)?;
+            storage.save_team_share(&share).await?;
+            revoked += 1;
+        }
+    }
+    storage.save_team(&teams[0]).await?;
+    
+    println!("✅ Member {} removed from team '{}'", member_id, teams[0].name);
+    if revoked > 0 {
+        println!("Revoked their access to {} shared secret(s). Change those secrets if they may have been read.", revoked);
+    }
     
     Ok(())
 }
 
-async fn update_member_role(team_id: String, member_id: String, new_role_str: String) -> Result<()> {
-    let _role = match new_role_str.as_str() {
-        "read" => Permission::Read,
-        "write" => Permission::Write,
-        "share" => Permission::Share,
-        "admin" => Permission::Admin,
-        _ => {
-            eprintln!("Invalid role: {}. Use: read, write, share, admin", new_role_str);
-            return Ok(());
-        }
-    };
+async fn update_member_role(
+    storage: &SecretsStorage,
+    team_id: String,
+    member_id: String,
+    new_role_str: String,
+) -> Result<()> {
+    let role = parse_permission(&new_role_str)?;
+    let (user_id, _) = storage.team_identity().await?;
+    let mut team = storage.team(&team_id).await?;
+    TeamSharingManager::update_member_role(&mut team, &member_id, role, &user_id)?;
+    storage.save_team(&team).await?;
     
-    println!("✅ Member {} role would be updated to {} in team {}", 
-             member_id, new_role_str, team_id);
+    println!("✅ Member {} is now {} in team '{}'", member_id, new_role_str, team.name);
     
     Ok(())
 }
 
-async fn list_teams() -> Result<()> {
+async fn list_teams(storage: &SecretsStorage) -> Result<()> {
+    let teams = storage.teams().await?;
+    if teams.is_empty() {
+        println!("No teams. Create one with 'create-team'.");
+        return Ok(());
+    }
+    
     println!("Teams:");
     println!("{:-<50}", "");
-    
-    // Mock data for demonstration
-    println!("📋 Development Team (team-123)");
-    println!("   Description: Main development team");
-    println!("   Members: 5");
-    println!("   Owner: alice@company.com");
-    println!("");
-    
-    println!("📋 Security Team (team-456)");
-    println!("   Description: Security and compliance team");
-    println!("   Members: 3");
-    println!("   Owner: bob@company.com");
-    
-    println!(r#"
-💡 In a real implementation, this would fetch teams from database
-where the current user is a member.
-"#);
+    for team in &teams {
+        println!("📋 {} ({})", team.name, team.id);
+        if let Some(description) = &team.description {
+            println!("   Description: {}", description);
+        }
+        println!("   Members: {}", team.members.iter().filter(|m| m.is_active).count());
+        if let Some(owner) = team.members.iter().find(|m| m.id == team.owner_id) {
+            println!("   Owner: {}", owner.email);
+        }
+        println!();
+    }
     
     Ok(())
 }
 
-async fn show_team(team_id: String) -> Result<()> {
-    println!("Team Details: {}", team_id);
-    println!("{:-<50}", "");
+async fn show_team(storage: &SecretsStorage, team_id: String) -> Result<()> {
+    let team = storage.team(&team_id).await?;
+    let shares = storage.team_shares(Some(&team_id)).await?;
     
-    // Mock data for demonstration
-    println!("Name: Development Team");
-    println!("Description: Main development team");
-    println!("Created: 2024-01-15 10:30:00 UTC");
-    println!("Owner: alice@company.com");
-    println!("");
+    println!("Team Details: {}", team.id);
+    println!("{:-<50}", "");
+    println!("Name: {}", team.name);
+    if let Some(description) = &team.description {
+        println!("Description: {}", description);
+    }
+    println!("Created: {}", team.created_at.format("%Y-%m-%d %H:%M:%S UTC"));
+    if let Some(owner) = team.members.iter().find(|m| m.id == team.owner_id) {
+        println!("Owner: {}", owner.email);
+    }
+    println!();
     println!("Members:");
-    println!("  👤 Alice (alice@company.com) - Admin");
-    println!("  👤 Bob (bob@company.com) - Write");
-    println!("  👤 Charlie (charlie@company.com) - Read");
-    println!("");
-    println!("Shared Secrets: 12");
-    println!("Active Share Requests: 2");
+    for member in &team.members {
+        println!("  👤 {} ({}) - {:?} [{}]", member.name, member.email, member.role, member.id);
+        if member.public_key.is_empty() {
+            println!("     Public key: none");
+        } else {
+            println!("     Public key: {}", hex::encode(&member.public_key));
+        }
+    }
+    println!();
+    println!("Shared Secrets: {}", shares.iter().filter(|s| s.is_active).count());
     
     Ok(())
 }
 
 async fn share_secret(
-    _storage: &SecretsStorage,
+    storage: &SecretsStorage,
     secret_name: String,
     team_id: String,
     permissions_str: String,
     expiration_days: Option<i64>,
 ) -> Result<()> {
-    let _permissions = match permissions_str.as_str() {
-        "read" => Permission::Read,
-        "write" => Permission::Write,
-        "share" => Permission::Share,
-        "admin" => Permission::Admin,
-        _ => {
-            eprintln!("Invalid permissions: {}. Use: read, write, share, admin", permissions_str);
-            return Ok(());
-        }
-    };
-    
-    let expiration_text = if let Some(days) = expiration_days {
-        format!("expires in {} days", days)
-    } else {
-        "no expiration".to_string()
+    let permissions = parse_permission(&permissions_str)?;
+    let secret = resolve_secret(storage, &secret_name, None).await?;
+    let team = storage.team(&team_id).await?;
+    let (user_id, _) = storage.team_identity().await?;
+    let expiration = expiration_days.map(|days| chrono::Utc::now() + chrono::Duration::days(days));
+    
+    let result = TeamSharingManager::share_secret(&secret, &team, &user_id, permissions, expiration);
+    let log = TeamSharingManager::log_access(
+        secret.id.clone(),
+        user_id,
+        Some(team.id.clone()),
+        AccessAction::Share,
+        result.is_ok(),
+        None,
+        None,
+        result.as_ref().err().map(|e| e.to_string()),
+    );
+    storage.add_access_log(&log).await?;
+    let shared = result?;
+    storage.save_team_share(&shared).await?;
+    
+    let expiration_text = match expiration {
+        Some(expiration) => format!("expires {}", expiration.format("%Y-%m-%d")),
+        None => "no expiration".to_string(),
     };
-    
-    println!("✅ Secret '{}' would be shared with team {} ({} permissions, {})", 
-             secret_name, team_id, permissions_str, expiration_text);
-    
-    println!(r#"
-💡 In a real implementation, this would:
-1. Encrypt secret with team's public key
-2. Create sharing record in database
-3. Notify team members
-4. Log the sharing activity
-"#);
+    println!("✅ Secret '{}' shared with team '{}' ({} permissions, {})",
+             secret.name, team.name, permissions_str, expiration_text);
+    println!("Encrypted for {} member(s)", shared.wrapped_keys.len());
     
     Ok(())
 }
 
-async fn list_shared_secrets(_storage: &SecretsStorage, team_id: Option<String>) -> Result<()> {
+async fn list_shared_secrets(storage: &SecretsStorage, team_id: Option<String>) -> Result<()> {
     let filter_text = if let Some(tid) = &team_id {
         format!(" for team {}", tid)
     } else {
         String::new()
     };
     
+    let shares = storage.team_shares(team_id.as_deref()).await?;
+    let teams = storage.teams().await?;
     println!("Shared Secrets{}:", filter_text);
     println!("{:-<60}", "");
-    
-    // Mock data for demonstration
-    println!("🔑 AWS Production Keys");
-    println!("   Team: Development Team (team-123)");
-    println!("   Permissions: Read");
-    println!("   Shared by: alice@company.com");
-    println!("   Expires: Never");
-    println!("   Last accessed: 2 hours ago");
-    println!("");
-    
-    println!("🔑 Database Connection String");
-    println!("   Team: Development Team (team-123)");
-    println!("   Permissions: Write");
-    println!("   Shared by: bob@company.com");
-    println!("   Expires: 30 days");
-    println!("   Last accessed: 1 day ago");
+    if !shares.iter().any(|share| share.is_active) {
+        println!("Nothing shared.");
+    }
+    for share in shares.iter().filter(|share| share.is_active) {
+        let name = match storage.get_secret(&share.secret_id).await {
+            Ok(secret) => secret.name.clone(),
+            Err(_) => share.secret_id.clone(),
+        };
+        let team_name = teams.iter().find(|t| t.id == share.team_id).map_or("unknown team", |t| t.name.as_str());
+        println!("🔑 {}", name);
+        println!("   Team: {} ({})", team_name, share.team_id);
+        println!("   Permissions: {:?}", share.permissions);
+        println!("   Members with access: {}", share.wrapped_keys.len());
+        match share.expiration {
+            Some(expiration) => println!("   Expires: {}", expiration.format("%Y-%m-%d %H:%M UTC")),
+            None => println!("   Expires: Never"),
+        }
+        println!();
+    }
     
     Ok(())
 }
 
 async fn revoke_secret_access(
-    _storage: &SecretsStorage,
+    storage: &SecretsStorage,
     secret_name: String,
     team_id: String,
 ) -> Result<()> {
-    println!("✅ Access to secret '{}' would be revoked for team {}", secret_name, team_id);
+    let secret = resolve_secret(storage, &secret_name, None).await?;
+    let teams = vec![storage.team(&team_id).await?];
+    let (user_id, _) = storage.team_identity().await?;
     
-    println!(r#"
-💡 In a real implementation, this would:
-1. Validate user has permission to revoke
-2. Update sharing records in database
-3. Notify affected team members
-4. Log the revocation activity
-"#);
+    let mut revoked = 0;
+    for mut share in storage.team_shares(Some(&team_id)).await? {
+        if share.secret_id == secret.id && share.is_active {
+            TeamSharingManager::revoke_shared_secret(&mut share, &user_id, &teams)?;
+            storage.save_team_share(&share).await?;
+            revoked += 1;
+        }
+    }
+    if revoked == 0 {
+        anyhow::bail!("'{}' is not shared with team {}", secret.name, team_id);
+    }
+    
+    let log = TeamSharingManager::log_access(
+        secret.id.clone(),
+        user_id,
+        Some(team_id),
+        AccessAction::Share,
+        true,
+        None,
+        None,
+        Some("access revoked".to_string()),
+    );
+    storage.add_access_log(&log).await?;
+    println!("✅ Access to secret '{}' revoked for team '{}'", secret.name, teams[0].name);
     
     Ok(())
 }
@@ -4636,7 +7267,7 @@ async fn list_share_requests(incoming: bool, outgoing: bool) -> Result<()> {
         println!("   Permissions: Read");
         println!("   Message: Need access for debugging");
         println!("   Status: Pending");
-        println!("");
+        println!();
     }
     
     if outgoing {
@@ -4648,7 +7279,7 @@ async fn list_share_requests(incoming: bool, outgoing: bool) -> Result<()> {
         println!("   To: alice@company.com");
         println!("   Permissions: Write");
         println!("   Status: Approved");
-        println!("");
+        println!();
     }
     
     if !incoming && !outgoing {
@@ -4678,11 +7309,138 @@ async fn reject_share_request(request_id: String) -> Result<()> {
     Ok(())
 }
 
+/// A `--since` value: RFC3339, or a date meaning midnight UTC.
+fn parse_since(since: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(since)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(since, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        })
+        .map_err(|_| anyhow::anyhow!("Invalid --since '{}'. Use RFC3339 (2024-06-01T00:00:00Z) or YYYY-MM-DD", since))
+}
+
+async fn export_audit(
+    storage: &SecretsStorage,
+    format: &str,
+    since: Option<&str>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let format: AuditFormat = format.parse()?;
+    let since = since.map(parse_since).transpose()?;
+    
+    let events = storage.audit_events(since).await?;
+    match output {
+        Some(path) => {
+            let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+            write_audit_events(&events, format, &mut writer)?;
+            std::io::Write::flush(&mut writer)?;
+            eprintln!("Exported {} audit events to {}", events.len(), path.display());
+        }
+        None => write_audit_events(&events, format, std::io::stdout().lock())?,
+    }
+    
+    Ok(())
+}
+
+/// Parse a share lifetime such as `30m`, `24h` or `7d`.
+fn parse_share_lifetime(value: &str) -> Result<chrono::Duration> {
+    let invalid = || anyhow::anyhow!("Invalid expiry '{}'. Use e.g. 30m, 24h or 7d", value);
+    let (amount, unit) = value.trim().split_at(value.trim().len().saturating_sub(1));
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let lifetime = match unit {
+        "m" => chrono::Duration::minutes(amount),
+        "h" => chrono::Duration::hours(amount),
+        "d" => chrono::Duration::days(amount),
+        _ => return Err(invalid()),
+    };
+    if lifetime <= chrono::Duration::zero() || lifetime > chrono::Duration::days(30) {
+        return Err(anyhow::anyhow!("Share expiry must be between 1 minute and 30 days"));
+    }
+    Ok(lifetime)
+}
+
+async fn share_once(storage: &SecretsStorage, name: &str, expires: &str) -> Result<()> {
+    let lifetime = parse_share_lifetime(expires)?;
+    let secret = resolve_secret(storage, name, None).await?;
+    let (share, passphrase) = storage
+        .create_one_time_share(&secret, chrono::Utc::now() + lifetime)
+        .await?;
+    
+    println!("✅ One-time share of '{}' created", secret.name);
+    println!("Share ID:   {}", share.id);
+    println!("Passphrase: {}", passphrase);
+    println!("Expires:    {}", share.expires_at.format("%Y-%m-%d %H:%M UTC"));
+    println!();
+    println!("Send the ID and the passphrase through different channels. The recipient runs:");
+    println!("  pwgen-cli --vault <this vault> redeem-share {}", share.id);
+    println!("The share works once, and is destroyed after {} wrong passphrases.", MAX_SHARE_REDEEM_ATTEMPTS);
+    Ok(())
+}
+
+async fn redeem_share(vault_path: &Path, id: &str) -> Result<()> {
+    let passphrase = rpassword::prompt_password("Share passphrase: ")?;
+    let secret = SecretsStorage::redeem_one_time_share(vault_path, id, &passphrase).await?;
+    
+    println!("Name: {}", secret.name);
+    println!("Type: {}", secret_type_label(&secret.secret_type));
+    match &secret.data {
+        SecretData::Password { .. } | SecretData::ApiKey { .. } | SecretData::SecureNote { .. } => {
+            print_secret_data(&secret.data);
+        }
+        _ => {
+            // Everything else is shown the way export-env would write it
+            let variables = EnvConnectionManager::secrets_to_env(std::slice::from_ref(&secret), "")?;
+            if variables.is_empty() {
+                print_secret_data(&secret.data);
+            }
+            for (key, value) in variables {
+                println!("{}={}", key, value);
+            }
+        }
+    }
+    println!();
+    println!("This share has now been used up; store the secret somewhere safe.");
+    Ok(())
+}
+
+async fn list_shares(storage: &SecretsStorage, output: OutputFormat) -> Result<()> {
+    let shares = storage.one_time_shares().await?;
+    if output == OutputFormat::Json {
+        return print_json(&serde_json::to_value(&shares)?);
+    }
+    if shares.is_empty() {
+        println!("No one-time shares issued");
+        return Ok(());
+    }
+    
+    println!("{:<36}  {:<24} {:<10} {:<17}", "ID", "Secret", "Status", "Expires");
+    for share in shares {
+        let status = match (share.status, share.redeemed_at) {
+            (OneTimeShareStatus::Redeemed, Some(at)) => format!("redeemed {}", at.format("%Y-%m-%d %H:%M")),
+            (status, _) => status.as_str().to_string(),
+        };
+        println!(
+            "{:<36}  {:<24} {:<10} {:<17}",
+            share.id,
+            share.secret_name,
+            status,
+            share.expires_at.format("%Y-%m-%d %H:%M")
+        );
+    }
+    Ok(())
+}
+
 async fn view_access_log(
+    storage: &SecretsStorage,
     secret_name: Option<String>,
     user_id: Option<String>,
     limit: usize,
 ) -> Result<()> {
+    let secret_id = match &secret_name {
+        Some(name) => Some(resolve_secret(storage, name, None).await?.id.clone()),
+        None => None,
+    };
     let filter_parts = vec![
         secret_name.as_ref().map(|s| format!("secret: {}", s)),
         user_id.as_ref().map(|u| format!("user: {}", u)),
@@ -4694,15 +7452,61 @@ async fn view_access_log(
         format!(" ({})", filter_parts.join(", "))
     };
     
+    let logs = storage.access_logs(secret_id.as_deref(), user_id.as_deref(), limit).await?;
     println!("Access Log{} (last {} entries):", filter_text, limit);
     println!("{:-<70}", "");
-    
-    // Mock data for demonstration
-    println!("🔍 2024-06-26 14:30:15 | alice@company.com | View | AWS Keys | Success");
-    println!("🔍 2024-06-26 13:45:22 | bob@company.com | Edit | Database Config | Success");
-    println!("🔍 2024-06-26 12:15:08 | charlie@company.com | View | API Token | Failed - No access");
-    println!("🔍 2024-06-26 11:20:33 | alice@company.com | Share | SSH Keys | Success");
-    println!("🔍 2024-06-26 10:55:41 | bob@company.com | Copy | Production Secrets | Success");
+    if logs.is_empty() {
+        println!("No access recorded.");
+    }
+    for log in &logs {
+        let name = match storage.get_secret(&log.secret_id).await {
+            Ok(secret) => secret.name.clone(),
+            Err(_) => log.secret_id.clone(),
+        };
+        let outcome = match (log.success, &log.details) {
+            (true, Some(details)) => format!("Success - {}", details),
+            (true, None) => "Success".to_string(),
+            (false, Some(details)) => format!("Failed - {}", details),
+            (false, None) => "Failed".to_string(),
+        };
+        println!("🔍 {} | {} | {:?} | {} | {}",
+                 log.timestamp.format("%Y-%m-%d %H:%M:%S"), log.user_id, log.action, name, outcome);
+    }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD_FIELDS: &[&str] = &["username", "password", "url", "notes"];
+
+    #[test]
+    fn test_secret_fields_read_stdin() {
+        let values = vec![("username".to_string(), "alice".to_string())];
+        let mut fields =
+            SecretFields::from_reader(values, PASSWORD_FIELDS, Some("password"), "hunter2\n".as_bytes(), false)
+                .unwrap();
+        assert_eq!(fields.secret("password", "Password: ").unwrap(), "hunter2");
+        assert_eq!(fields.text("username", "Username: ").unwrap(), "alice");
+        assert_eq!(fields.optional("url", "URL: ").unwrap(), None);
+    }
+
+    #[test]
+    fn test_secret_fields_missing_without_terminal() {
+        let mut fields =
+            SecretFields::from_reader(Vec::new(), PASSWORD_FIELDS, None, std::io::empty(), false).unwrap();
+        let err = fields.secret("password", "Password: ").unwrap_err();
+        assert!(err.to_string().contains("Missing password"), "{}", err);
+    }
+
+    #[test]
+    fn test_secret_fields_value_conflicts_with_stdin() {
+        // `--value password=` together with `--password-stdin`
+        let values = vec![("password".to_string(), String::new())];
+        let result = SecretFields::from_reader(values, PASSWORD_FIELDS, Some("password"), "hunter2".as_bytes(), false);
+        let err = result.err().unwrap();
+        assert!(err.to_string().contains("don't also pass --value password="), "{}", err);
+    }
+}
@@ -0,0 +1,111 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use pwgen_core::{
+    crypto::MasterKey,
+    document_storage::{DocumentManager, DocumentType},
+    generator::{PasswordConfig, PasswordGenerator},
+    models::SearchFilter,
+    sample_data::sample_entries,
+    secrets::SecretManager,
+    storage::Storage,
+};
+use tokio::runtime::Runtime;
+
+const MASTER_PASSWORD: &str = "benchmark master password";
+const SEEDED_ENTRIES: usize = 50_000;
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+fn bench_generator(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generator");
+    for length in [16, 64, 128] {
+        let config = PasswordConfig { length, ..Default::default() };
+        group.bench_with_input(BenchmarkId::new("generate", length), &config, |b, config| {
+            b.iter(|| PasswordGenerator::generate(config).unwrap())
+        });
+    }
+    group.bench_function("passphrase_6_words", |b| {
+        b.iter(|| PasswordGenerator::generate_passphrase(6, "-", true, false).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_search(c: &mut Criterion) {
+    let rt = runtime();
+    let dir = tempfile::tempdir().unwrap();
+    let storage = rt.block_on(async {
+        let storage = Storage::create_new(dir.path().join("vault.db"), MASTER_PASSWORD)
+            .await
+            .unwrap();
+        storage.add_entries(&sample_entries(SEEDED_ENTRIES)).await.unwrap();
+        storage
+    });
+
+    let mut group = c.benchmark_group("search_50k");
+    group.sample_size(10);
+    let filters = [
+        ("site_query", SearchFilter { query: Some("github".to_string()), ..Default::default() }),
+        ("tag_filter", SearchFilter { tags: Some(vec!["finance".to_string()]), ..Default::default() }),
+        ("favorites", SearchFilter { favorite_only: true, ..Default::default() }),
+    ];
+    for (name, filter) in &filters {
+        group.bench_function(*name, |b| {
+            b.to_async(&rt).iter(|| storage.search_entries(filter))
+        });
+    }
+    let all = SearchFilter::default();
+    group.bench_function("full_text", |b| {
+        b.to_async(&rt).iter(|| storage.search_fts("github", &all))
+    });
+    group.finish();
+}
+
+fn bench_unlock(c: &mut Criterion) {
+    let rt = runtime();
+    let dir = tempfile::tempdir().unwrap();
+    let vault_path = dir.path().join("vault.db");
+    rt.block_on(Storage::create_new(&vault_path, MASTER_PASSWORD)).unwrap();
+
+    let mut group = c.benchmark_group("unlock");
+    group.sample_size(10);
+    group.bench_function("open_default_kdf", |b| {
+        b.to_async(&rt).iter(|| async {
+            Storage::open(&vault_path, MASTER_PASSWORD).await.unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn bench_documents(c: &mut Criterion) {
+    let salt = MasterKey::generate_salt();
+    let key = MasterKey::derive_from_password(MASTER_PASSWORD, &salt).unwrap();
+
+    let mut group = c.benchmark_group("document");
+    group.sample_size(20);
+    for size in [64 * 1024, 1024 * 1024, 8 * 1024 * 1024] {
+        let text = "x".repeat(size);
+        let document = DocumentManager::create_text_document(
+            "bench.txt".to_string(),
+            text,
+            DocumentType::Document,
+        )
+        .unwrap();
+        let encrypted = SecretManager::encrypt_secret_data(&document, &key).unwrap();
+
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("encrypt", size), &document, |b, document| {
+            b.iter(|| SecretManager::encrypt_secret_data(document, &key).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decrypt", size), &encrypted, |b, encrypted| {
+            b.iter(|| SecretManager::decrypt_secret_data(encrypted, &key).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_generator, bench_search, bench_unlock, bench_documents);
+criterion_main!(benches);
@@ -62,6 +62,29 @@ pub struct RotationInfo {
     pub rotation_reminder_days: Option<u32>,
 }
 
+/// Reminder window used when a key doesn't set `rotation_reminder_days`
+pub const DEFAULT_ROTATION_REMINDER_DAYS: u32 = 30;
+
+/// Where an API key stands in its rotation schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RotationStatus {
+    /// When the key should next be rotated
+    pub due: DateTime<Utc>,
+    /// Whole days left until `due`, negative once it has passed
+    pub days_remaining: i64,
+    /// `due` has passed
+    pub overdue: bool,
+    /// `due` falls within the key's reminder window
+    pub reminder: bool,
+}
+
+impl RotationStatus {
+    /// Overdue, or close enough to remind about
+    pub fn needs_attention(&self) -> bool {
+        self.overdue || self.reminder
+    }
+}
+
 /// API key usage statistics
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct UsageStats {
@@ -87,6 +110,7 @@ pub struct ApiKeyManager;
 
 impl ApiKeyManager {
     /// Create a new API key entry
+    #[allow(clippy::too_many_arguments)]
     pub fn create_api_key(
         name: String,
         provider: ApiKeyProvider,
@@ -182,6 +206,7 @@ impl ApiKeyManager {
     }
 
     /// Create an OAuth token entry
+    #[allow(clippy::too_many_arguments)]
     pub fn create_oauth_token(
         name: String,
         access_token: String,
@@ -299,6 +324,50 @@ impl ApiKeyManager {
         Ok(())
     }
 
+    /// Work out when an API key is due for rotation: `rotation_period_days`
+    /// after it was last rotated, or the stored `next_rotation` for keys that
+    /// have never been rotated. `None` for keys without a rotation schedule.
+    pub fn rotation_status(entry: &DecryptedSecretEntry) -> Option<RotationStatus> {
+        Self::rotation_status_at(entry, Utc::now())
+    }
+
+    fn rotation_status_at(entry: &DecryptedSecretEntry, now: DateTime<Utc>) -> Option<RotationStatus> {
+        let SecretData::ApiKey { rotation_info, .. } = &entry.data else {
+            return None;
+        };
+        let due = match (rotation_info.last_rotated, rotation_info.rotation_period_days) {
+            (Some(last_rotated), Some(days)) => last_rotated + Duration::days(days as i64),
+            (None, period) => rotation_info
+                .next_rotation
+                .or_else(|| period.map(|days| entry.created_at + Duration::days(days as i64)))?,
+            (Some(_), None) => rotation_info.next_rotation?,
+        };
+        let reminder_days = rotation_info.rotation_reminder_days.unwrap_or(DEFAULT_ROTATION_REMINDER_DAYS);
+        let overdue = due <= now;
+        Some(RotationStatus {
+            due,
+            days_remaining: (due - now).num_days(),
+            overdue,
+            reminder: !overdue && due - now <= Duration::days(reminder_days as i64),
+        })
+    }
+
+    /// Record that an API key's value was just replaced, restarting its
+    /// rotation schedule.
+    pub fn record_rotation(entry: &mut DecryptedSecretEntry) -> Result<()> {
+        match &mut entry.data {
+            SecretData::ApiKey { rotation_info, .. } => {
+                let now = Utc::now();
+                rotation_info.last_rotated = Some(now);
+                rotation_info.next_rotation = rotation_info
+                    .rotation_period_days
+                    .map(|days| now + Duration::days(days as i64));
+            }
+            _ => return Err(Error::Other("Entry is not an API key".to_string())),
+        }
+        Ok(())
+    }
+
     /// Get API keys that need rotation
     pub fn get_keys_needing_rotation(entries: &[DecryptedSecretEntry]) -> Vec<&DecryptedSecretEntry> {
         entries.iter()
@@ -323,25 +392,17 @@ impl ApiKeyManager {
     /// Validate API key format for a provider
     pub fn validate_api_key_format(provider: &ApiKeyProvider, api_key: &str) -> Result<()> {
         match provider {
-            ApiKeyProvider::AWS => {
-                if !api_key.starts_with("AKIA") || api_key.len() != 20 {
-                    return Err(Error::Other("Invalid AWS access key format".to_string()));
-                }
+            ApiKeyProvider::AWS if !api_key.starts_with("AKIA") || api_key.len() != 20 => {
+                return Err(Error::Other("Invalid AWS access key format".to_string()));
             }
-            ApiKeyProvider::GitHub => {
-                if !api_key.starts_with("ghp_") && !api_key.starts_with("github_pat_") {
-                    return Err(Error::Other("Invalid GitHub token format".to_string()));
-                }
+            ApiKeyProvider::GitHub if !api_key.starts_with("ghp_") && !api_key.starts_with("github_pat_") => {
+                return Err(Error::Other("Invalid GitHub token format".to_string()));
             }
-            ApiKeyProvider::Stripe => {
-                if !api_key.starts_with("sk_") && !api_key.starts_with("pk_") {
-                    return Err(Error::Other("Invalid Stripe key format".to_string()));
-                }
+            ApiKeyProvider::Stripe if !api_key.starts_with("sk_") && !api_key.starts_with("pk_") => {
+                return Err(Error::Other("Invalid Stripe key format".to_string()));
             }
-            ApiKeyProvider::OpenAI => {
-                if !api_key.starts_with("sk-") {
-                    return Err(Error::Other("Invalid OpenAI API key format".to_string()));
-                }
+            ApiKeyProvider::OpenAI if !api_key.starts_with("sk-") => {
+                return Err(Error::Other("Invalid OpenAI API key format".to_string()));
             }
             _ => {} // No specific validation for other providers
         }
@@ -496,6 +557,70 @@ mod tests {
         assert!(matches!(api_key.data, SecretData::ApiKey { .. }));
     }
 
+    fn key_with_rotation(last_rotated_days_ago: Option<i64>, period: u32, reminder: u32) -> DecryptedSecretEntry {
+        let mut key = ApiKeyManager::create_api_key(
+            "rotating".to_string(),
+            ApiKeyProvider::Generic,
+            "sk-test".to_string(),
+            None,
+            None,
+            None,
+            None,
+            vec![],
+        ).unwrap();
+        if let SecretData::ApiKey { rotation_info, .. } = &mut key.data {
+            rotation_info.rotation_period_days = Some(period);
+            rotation_info.rotation_reminder_days = Some(reminder);
+            rotation_info.last_rotated = last_rotated_days_ago.map(|days| key.created_at - Duration::days(days));
+        }
+        key
+    }
+
+    #[test]
+    fn test_rotation_status_reminder_threshold() {
+        let key = key_with_rotation(Some(82), 90, 7);
+        let status = |days_later: i64| {
+            ApiKeyManager::rotation_status_at(&key, key.created_at + Duration::days(days_later)).unwrap()
+        };
+
+        // Due 8 days after creation: outside the 7-day window until day 1
+        let early = status(0);
+        assert_eq!(early.due, key.created_at + Duration::days(8));
+        assert_eq!(early.days_remaining, 8);
+        assert!(!early.reminder && !early.overdue && !early.needs_attention());
+
+        let at_threshold = status(1);
+        assert_eq!(at_threshold.days_remaining, 7);
+        assert!(at_threshold.reminder && !at_threshold.overdue);
+
+        // The due date itself counts as overdue
+        let on_due_date = status(8);
+        assert_eq!(on_due_date.days_remaining, 0);
+        assert!(on_due_date.overdue && !on_due_date.reminder);
+
+        assert_eq!(status(11).days_remaining, -3);
+    }
+
+    #[test]
+    fn test_rotation_status_without_rotation_history() {
+        // Never rotated: counted from creation
+        let key = key_with_rotation(None, 30, 5);
+        let status = ApiKeyManager::rotation_status_at(&key, key.created_at).unwrap();
+        assert_eq!(status.due, key.created_at + Duration::days(30));
+
+        let mut unscheduled = key.clone();
+        if let SecretData::ApiKey { rotation_info, .. } = &mut unscheduled.data {
+            rotation_info.rotation_period_days = None;
+        }
+        assert!(ApiKeyManager::rotation_status(&unscheduled).is_none());
+
+        let mut rotated = key;
+        ApiKeyManager::record_rotation(&mut rotated).unwrap();
+        let status = ApiKeyManager::rotation_status(&rotated).unwrap();
+        assert_eq!(status.days_remaining, 29);
+        assert!(!status.needs_attention());
+    }
+
     #[test]
     fn test_jwt_creation() {
         let jwt = ApiKeyManager::create_jwt_token(
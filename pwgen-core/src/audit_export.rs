@@ -0,0 +1,227 @@
+//! Export of the secret audit log and team access logs for SIEM ingestion.
+//!
+//! Events carry identifiers, names, actions and attribution only, never a
+//! secret value. JSON output is one object per line, as expected by Splunk
+//! and Elastic file inputs; CEF follows the ArcSight Common Event Format.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+
+use crate::{
+    team_sharing::{AccessAction, AccessLog},
+    Error, Result,
+};
+
+/// Where an event was recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSource {
+    /// The vault's own secret audit log.
+    Vault,
+    /// A team sharing `AccessLog`.
+    Team,
+}
+
+/// One exported audit event.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEvent {
+    pub timestamp: DateTime<Utc>,
+    pub source: AuditSource,
+    /// Dotted event type such as `secret.created` or `team.view`.
+    pub event_type: String,
+    pub item_id: String,
+    /// Name of the secret, when it still exists.
+    pub item_name: Option<String>,
+    /// Device or user the event is attributed to, if recorded.
+    pub actor: Option<String>,
+    pub team_id: Option<String>,
+    pub success: bool,
+    pub details: Option<String>,
+}
+
+impl From<&AccessLog> for AuditEvent {
+    fn from(log: &AccessLog) -> Self {
+        let action = match log.action {
+            AccessAction::View => "view",
+            AccessAction::Edit => "edit",
+            AccessAction::Share => "share",
+            AccessAction::Delete => "delete",
+            AccessAction::Download => "download",
+            AccessAction::Copy => "copy",
+        };
+        AuditEvent {
+            timestamp: log.timestamp,
+            source: AuditSource::Team,
+            event_type: format!("team.{}", action),
+            item_id: log.secret_id.clone(),
+            item_name: None,
+            actor: Some(log.user_id.clone()),
+            team_id: log.team_id.clone(),
+            success: log.success,
+            details: log.details.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditFormat {
+    /// Newline-delimited JSON objects.
+    Json,
+    Csv,
+    /// ArcSight Common Event Format, one event per line.
+    Cef,
+}
+
+impl std::str::FromStr for AuditFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "json" | "ndjson" | "jsonl" => Ok(AuditFormat::Json),
+            "csv" => Ok(AuditFormat::Csv),
+            "cef" => Ok(AuditFormat::Cef),
+            _ => Err(Error::Other(format!("Unknown audit export format: {}", s))),
+        }
+    }
+}
+
+/// Write `events` to `out` in `format`.
+pub fn write_audit_events<W: Write>(events: &[AuditEvent], format: AuditFormat, mut out: W) -> Result<()> {
+    match format {
+        AuditFormat::Json => {
+            for event in events {
+                serde_json::to_writer(&mut out, event)?;
+                out.write_all(b"\n")?;
+            }
+        }
+        AuditFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record([
+                "timestamp", "source", "event_type", "item_id", "item_name", "actor", "team_id", "success",
+                "details",
+            ])?;
+            for event in events {
+                writer.write_record([
+                    event.timestamp.to_rfc3339().as_str(),
+                    source_name(event.source),
+                    &event.event_type,
+                    &event.item_id,
+                    event.item_name.as_deref().unwrap_or(""),
+                    event.actor.as_deref().unwrap_or(""),
+                    event.team_id.as_deref().unwrap_or(""),
+                    if event.success { "true" } else { "false" },
+                    event.details.as_deref().unwrap_or(""),
+                ])?;
+            }
+            writer.flush()?;
+        }
+        AuditFormat::Cef => {
+            for event in events {
+                writeln!(out, "{}", cef_line(event))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn source_name(source: AuditSource) -> &'static str {
+    match source {
+        AuditSource::Vault => "vault",
+        AuditSource::Team => "team",
+    }
+}
+
+/// `CEF:0|Vendor|Product|Version|SignatureID|Name|Severity|Extension`
+fn cef_line(event: &AuditEvent) -> String {
+    let severity = match (event.success, event.event_type.as_str()) {
+        (false, _) => 7,
+        (true, t) if t.ends_with(".deleted") || t.ends_with(".delete") || t.ends_with(".share") => 5,
+        _ => 3,
+    };
+    let mut extension = vec![
+        format!("rt={}", event.timestamp.timestamp_millis()),
+        format!("cs1Label=itemId cs1={}", cef_value(&event.item_id)),
+        format!("cs2Label=source cs2={}", source_name(event.source)),
+        format!("outcome={}", if event.success { "success" } else { "failure" }),
+    ];
+    if let Some(name) = &event.item_name {
+        extension.push(format!("cs3Label=itemName cs3={}", cef_value(name)));
+    }
+    if let Some(actor) = &event.actor {
+        extension.push(format!("suser={}", cef_value(actor)));
+    }
+    if let Some(team_id) = &event.team_id {
+        extension.push(format!("cs4Label=teamId cs4={}", cef_value(team_id)));
+    }
+    if let Some(details) = &event.details {
+        extension.push(format!("msg={}", cef_value(details)));
+    }
+    format!(
+        "CEF:0|PwGen|PwGen|{}|{}|{}|{}|{}",
+        cef_header(env!("CARGO_PKG_VERSION")),
+        cef_header(&event.event_type),
+        cef_header(&event.event_type.replace('.', " ")),
+        severity,
+        extension.join(" ")
+    )
+}
+
+/// Escape a CEF header field: backslashes and pipes.
+fn cef_header(value: &str) -> String {
+    value.replace('\\', r"\\").replace('|', r"\|")
+}
+
+/// Escape a CEF extension value: backslashes, equals signs and line breaks.
+fn cef_value(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('=', r"\=")
+        .replace('\r', r"\r")
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> AuditEvent {
+        AuditEvent {
+            timestamp: "2024-06-01T12:00:00Z".parse().unwrap(),
+            source: AuditSource::Vault,
+            event_type: "secret.updated".to_string(),
+            item_id: "abc-123".to_string(),
+            item_name: Some("prod|db=main".to_string()),
+            actor: Some("work-laptop".to_string()),
+            team_id: None,
+            success: true,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_write_audit_events_formats() {
+        let events = [event()];
+
+        let mut json = Vec::new();
+        write_audit_events(&events, AuditFormat::Json, &mut json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["event_type"], "secret.updated");
+        assert_eq!(value["source"], "vault");
+        assert_eq!(value["actor"], "work-laptop");
+
+        let mut csv = Vec::new();
+        write_audit_events(&events, AuditFormat::Csv, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("timestamp,source,event_type,"));
+        assert!(csv.contains("vault,secret.updated,abc-123,prod|db=main,work-laptop,,true,"));
+
+        let mut cef = Vec::new();
+        write_audit_events(&events, AuditFormat::Cef, &mut cef).unwrap();
+        let cef = String::from_utf8(cef).unwrap();
+        assert!(cef.starts_with("CEF:0|PwGen|PwGen|"));
+        assert!(cef.contains("|secret.updated|secret updated|3|rt=1717243200000 "));
+        assert!(cef.contains(r"cs3=prod|db\=main"));
+        assert!(cef.contains("suser=work-laptop"));
+    }
+}
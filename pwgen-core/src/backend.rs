@@ -0,0 +1,369 @@
+use chrono::{DateTime, Utc};
+use std::{collections::HashMap, future::Future, sync::Mutex};
+
+use crate::{
+    models::{normalize_site, PasswordEntry, SearchFilter, SortField, SortOrder, VaultMetadata},
+    Error, Result,
+};
+
+/// Persistence layer behind `Storage`.
+///
+/// A backend only ever sees `PasswordEntry` values whose passwords are already
+/// encrypted; key derivation, encryption and decryption stay in `Storage`. The
+/// SQLite implementation (`storage::SqliteBackend`) is the default, and
+/// `MemoryBackend` keeps everything in process memory.
+///
+/// Entries in the trash are left out of every query except `trashed_entries`.
+pub trait VaultBackend: Send + Sync {
+    /// Read the vault metadata written by `save_metadata`.
+    fn load_metadata(&self) -> impl Future<Output = Result<VaultMetadata>> + Send;
+
+    fn save_metadata(&self, metadata: &VaultMetadata) -> impl Future<Output = Result<()>> + Send;
+
+    /// Insert new entries atomically: either all of them are stored or none.
+    /// A trashed entry with the same id is replaced.
+    fn insert_entries(&self, entries: &[PasswordEntry]) -> impl Future<Output = Result<()>> + Send;
+
+    /// Overwrite the stored entry with the same id.
+    fn update_entry(&self, entry: &PasswordEntry) -> impl Future<Output = Result<()>> + Send;
+
+    /// Overwrite several stored entries atomically: either all of them are
+    /// updated or none.
+    fn update_entries(&self, entries: &[PasswordEntry]) -> impl Future<Output = Result<()>> + Send;
+
+    fn get_entry(&self, id: &str) -> impl Future<Output = Result<Option<PasswordEntry>>> + Send;
+
+    /// Remove the entry permanently.
+    fn delete_entry(&self, id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Move the entry to the trash, recording `at` as its deletion time.
+    fn trash_entry(&self, id: &str, at: DateTime<Utc>) -> impl Future<Output = Result<()>> + Send;
+
+    /// Move several entries to the trash atomically.
+    fn trash_entries(&self, ids: &[String], at: DateTime<Utc>) -> impl Future<Output = Result<()>> + Send;
+
+    /// Overwrite the `updated` entries and move the `trashed` ones to the
+    /// trash, all in one step, as when duplicates are merged.
+    fn update_and_trash_entries(
+        &self,
+        updated: &[PasswordEntry],
+        trashed: &[String],
+        at: DateTime<Utc>,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Take the entry out of the trash. Returns whether it was there.
+    fn restore_entry(&self, id: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    /// Entries in the trash with when they were deleted, most recent first.
+    fn trashed_entries(&self) -> impl Future<Output = Result<Vec<(PasswordEntry, DateTime<Utc>)>>> + Send;
+
+    /// Permanently remove the trashed entries deleted before `before`.
+    /// Returns how many were removed.
+    fn purge_trash(&self, before: DateTime<Utc>) -> impl Future<Output = Result<usize>> + Send;
+
+    /// Return the entries matching `filter`, sorted as it requests.
+    fn search_entries(
+        &self,
+        filter: &SearchFilter,
+    ) -> impl Future<Output = Result<Vec<PasswordEntry>>> + Send;
+
+    /// Return the entries where every one of `terms` (lowercase, see
+    /// `search_terms`) starts a word of the site, username, notes or tags,
+    /// narrowed and sorted by `filter` like `search_entries`. `filter.query`
+    /// is ignored.
+    fn full_text_search(
+        &self,
+        terms: &[String],
+        filter: &SearchFilter,
+    ) -> impl Future<Output = Result<Vec<PasswordEntry>>> + Send;
+
+    /// Return entries created or updated after `since`, newest first.
+    fn entries_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> impl Future<Output = Result<Vec<PasswordEntry>>> + Send;
+
+    fn mark_as_used(&self, id: &str, at: DateTime<Utc>) -> impl Future<Output = Result<()>> + Send;
+
+    /// Star or unstar an entry outside the trash, setting `updated_at` to
+    /// `at`. Returns whether the entry was found.
+    fn set_favorite(
+        &self,
+        id: &str,
+        favorite: bool,
+        at: DateTime<Utc>,
+    ) -> impl Future<Output = Result<bool>> + Send;
+
+    fn entry_count(&self) -> impl Future<Output = Result<usize>> + Send;
+
+    /// Most recent `updated_at` across all entries, if there are any.
+    fn last_modified(&self) -> impl Future<Output = Result<Option<DateTime<Utc>>>> + Send;
+
+    /// Read an encrypted draft stored under `name`.
+    fn load_draft(&self, name: &str) -> impl Future<Output = Result<Option<Vec<u8>>>> + Send;
+
+    /// Store an encrypted draft, replacing any previous one with the same name.
+    fn save_draft(&self, name: &str, data: &[u8]) -> impl Future<Output = Result<()>> + Send;
+
+    fn delete_draft(&self, name: &str) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Whether `entry` carries at least one of the tags a filter asks for.
+pub(crate) fn matches_tags(entry: &PasswordEntry, tags: &Option<Vec<String>>) -> bool {
+    match tags {
+        Some(tags) => tags.iter().any(|tag| entry.tags.contains(tag)),
+        None => true,
+    }
+}
+
+/// Lowercase words of `text`, split on anything that isn't alphanumeric the
+/// way SQLite's `unicode61` tokenizer splits them.
+pub fn search_terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether every term starts a word of the entry's site, username, notes or tags.
+fn matches_terms(entry: &PasswordEntry, terms: &[String]) -> bool {
+    let words: Vec<String> = [entry.site.as_str(), entry.username.as_str(), entry.notes.as_deref().unwrap_or_default()]
+        .into_iter()
+        .chain(entry.tags.iter().map(String::as_str))
+        .flat_map(search_terms)
+        .collect();
+    terms.iter().all(|term| words.iter().any(|word| word.starts_with(term.as_str())))
+}
+
+/// Non-persistent backend, useful for tests and for embedders that manage
+/// persistence themselves. Everything is lost when it is dropped.
+#[derive(Default)]
+pub struct MemoryBackend {
+    metadata: Mutex<Option<VaultMetadata>>,
+    entries: Mutex<HashMap<String, PasswordEntry>>,
+    trash: Mutex<HashMap<String, (PasswordEntry, DateTime<Utc>)>>,
+    drafts: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl VaultBackend for MemoryBackend {
+    async fn load_metadata(&self) -> Result<VaultMetadata> {
+        self.metadata
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| Error::Other("Vault has not been initialized".to_string()))
+    }
+
+    async fn save_metadata(&self, metadata: &VaultMetadata) -> Result<()> {
+        *self.metadata.lock().unwrap() = Some(metadata.clone());
+        Ok(())
+    }
+
+    async fn insert_entries(&self, entries: &[PasswordEntry]) -> Result<()> {
+        let mut stored = self.entries.lock().unwrap();
+        if let Some(duplicate) = entries.iter().find(|e| stored.contains_key(&e.id)) {
+            return Err(Error::Other(format!("Entry already exists: {}", duplicate.id)));
+        }
+        let mut trash = self.trash.lock().unwrap();
+        for entry in entries {
+            trash.remove(&entry.id);
+            stored.insert(entry.id.clone(), entry.clone());
+        }
+        Ok(())
+    }
+
+    async fn update_entry(&self, entry: &PasswordEntry) -> Result<()> {
+        let mut stored = self.entries.lock().unwrap();
+        if let Some(existing) = stored.get_mut(&entry.id) {
+            *existing = entry.clone();
+        }
+        Ok(())
+    }
+
+    async fn update_entries(&self, entries: &[PasswordEntry]) -> Result<()> {
+        let mut stored = self.entries.lock().unwrap();
+        for entry in entries {
+            if let Some(existing) = stored.get_mut(&entry.id) {
+                *existing = entry.clone();
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_entry(&self, id: &str) -> Result<Option<PasswordEntry>> {
+        Ok(self.entries.lock().unwrap().get(id).cloned())
+    }
+
+    async fn delete_entry(&self, id: &str) -> Result<()> {
+        self.entries.lock().unwrap().remove(id);
+        self.trash.lock().unwrap().remove(id);
+        Ok(())
+    }
+
+    async fn trash_entry(&self, id: &str, at: DateTime<Utc>) -> Result<()> {
+        if let Some(entry) = self.entries.lock().unwrap().remove(id) {
+            self.trash.lock().unwrap().insert(id.to_string(), (entry, at));
+        }
+        Ok(())
+    }
+
+    async fn trash_entries(&self, ids: &[String], at: DateTime<Utc>) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        let mut trash = self.trash.lock().unwrap();
+        for id in ids {
+            if let Some(entry) = entries.remove(id) {
+                trash.insert(id.clone(), (entry, at));
+            }
+        }
+        Ok(())
+    }
+
+    async fn update_and_trash_entries(
+        &self,
+        updated: &[PasswordEntry],
+        trashed: &[String],
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        let mut trash = self.trash.lock().unwrap();
+        for entry in updated {
+            if let Some(existing) = entries.get_mut(&entry.id) {
+                *existing = entry.clone();
+            }
+        }
+        for id in trashed {
+            if let Some(entry) = entries.remove(id) {
+                trash.insert(id.clone(), (entry, at));
+            }
+        }
+        Ok(())
+    }
+
+    async fn restore_entry(&self, id: &str) -> Result<bool> {
+        let Some((entry, _)) = self.trash.lock().unwrap().remove(id) else {
+            return Ok(false);
+        };
+        self.entries.lock().unwrap().insert(id.to_string(), entry);
+        Ok(true)
+    }
+
+    async fn trashed_entries(&self) -> Result<Vec<(PasswordEntry, DateTime<Utc>)>> {
+        let mut trashed: Vec<_> = self.trash.lock().unwrap().values().cloned().collect();
+        trashed.sort_by_key(|(_, deleted_at)| std::cmp::Reverse(*deleted_at));
+        Ok(trashed)
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<usize> {
+        let mut trash = self.trash.lock().unwrap();
+        let count = trash.len();
+        trash.retain(|_, (_, deleted_at)| *deleted_at >= before);
+        Ok(count - trash.len())
+    }
+
+    async fn search_entries(&self, filter: &SearchFilter) -> Result<Vec<PasswordEntry>> {
+        let query = filter.query.as_ref().map(|q| q.to_lowercase());
+        let mut entries: Vec<PasswordEntry> = self
+            .entries
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|e| !filter.favorite_only || e.favorite)
+            .filter(|e| matches_tags(e, &filter.tags))
+            .filter(|e| match &query {
+                Some(q) => {
+                    e.site.to_lowercase().contains(q)
+                        || e.username.to_lowercase().contains(q)
+                        || e.notes.as_ref().is_some_and(|n| n.to_lowercase().contains(q))
+                        || e.urls.iter().any(|u| u.url.to_lowercase().contains(q))
+                        || normalize_site(&e.site) == normalize_site(q)
+                }
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        entries.sort_by(|a, b| {
+            let ordering = match filter.sort_by {
+                SortField::Site => normalize_site(&a.site).cmp(&normalize_site(&b.site)),
+                SortField::Username => a.username.cmp(&b.username),
+                SortField::CreatedAt => a.created_at.cmp(&b.created_at),
+                SortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+                SortField::LastUsed => a
+                    .last_used
+                    .unwrap_or(a.created_at)
+                    .cmp(&b.last_used.unwrap_or(b.created_at)),
+            };
+            match filter.sort_order {
+                SortOrder::Ascending => ordering,
+                SortOrder::Descending => ordering.reverse(),
+            }
+        });
+
+        Ok(entries)
+    }
+
+    async fn full_text_search(&self, terms: &[String], filter: &SearchFilter) -> Result<Vec<PasswordEntry>> {
+        let filter = SearchFilter { query: None, ..filter.clone() };
+        let mut entries = self.search_entries(&filter).await?;
+        entries.retain(|entry| matches_terms(entry, terms));
+        Ok(entries)
+    }
+
+    async fn entries_since(&self, since: DateTime<Utc>) -> Result<Vec<PasswordEntry>> {
+        let mut entries: Vec<PasswordEntry> = self
+            .entries
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|e| e.updated_at > since || e.created_at > since)
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.updated_at));
+        Ok(entries)
+    }
+
+    async fn mark_as_used(&self, id: &str, at: DateTime<Utc>) -> Result<()> {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(id) {
+            entry.last_used = Some(at);
+        }
+        Ok(())
+    }
+
+    async fn set_favorite(&self, id: &str, favorite: bool, at: DateTime<Utc>) -> Result<bool> {
+        let mut stored = self.entries.lock().unwrap();
+        let Some(entry) = stored.get_mut(id) else {
+            return Ok(false);
+        };
+        entry.favorite = favorite;
+        entry.updated_at = at;
+        Ok(true)
+    }
+
+    async fn entry_count(&self) -> Result<usize> {
+        Ok(self.entries.lock().unwrap().len())
+    }
+
+    async fn last_modified(&self) -> Result<Option<DateTime<Utc>>> {
+        Ok(self.entries.lock().unwrap().values().map(|e| e.updated_at).max())
+    }
+
+    async fn load_draft(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.drafts.lock().unwrap().get(name).cloned())
+    }
+
+    async fn save_draft(&self, name: &str, data: &[u8]) -> Result<()> {
+        self.drafts.lock().unwrap().insert(name.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    async fn delete_draft(&self, name: &str) -> Result<()> {
+        self.drafts.lock().unwrap().remove(name);
+        Ok(())
+    }
+}
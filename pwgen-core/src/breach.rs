@@ -0,0 +1,266 @@
+//! Checking passwords against the Have I Been Pwned breach corpus.
+//!
+//! The range API is used with k-anonymity: only the first 5 hex characters
+//! of the password's SHA-1 hash are sent, the server answers with every hash
+//! suffix in that range, and the match is made locally, so neither the
+//! password nor its full hash leaves the machine. Responses are padded to
+//! hide how many suffixes a range holds.
+//!
+//! The HTTPS client is behind the `breach-check` feature; without it, or
+//! offline, nothing here touches the network and callers can supply their
+//! own `RangeClient`.
+
+use sha1::{Digest, Sha1};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{models::DecryptedPasswordEntry, Error, Result};
+
+/// Pause between two range requests, to stay well clear of the API's limits
+/// when a whole vault is checked.
+pub const DEFAULT_REQUEST_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Fetches the hash suffixes for a 5 character SHA-1 prefix, in the range
+/// API's `SUFFIX:COUNT` line format. Called on a blocking thread.
+pub trait RangeClient: Send + Sync {
+    fn fetch_range(&self, prefix: &str) -> Result<String>;
+}
+
+/// A password entry found in the breach corpus.
+#[derive(Debug, Clone)]
+pub struct BreachedEntry {
+    pub entry_id: String,
+    pub site: String,
+    pub username: String,
+    /// Number of times the password appears in known breaches
+    pub count: u32,
+}
+
+/// Checks passwords through a `RangeClient`, rate limited and caching each
+/// range so that reused passwords are only looked up once.
+pub struct BreachChecker {
+    client: Arc<dyn RangeClient>,
+    interval: Duration,
+    last_request: tokio::sync::Mutex<Option<Instant>>,
+    ranges: Mutex<HashMap<String, Arc<String>>>,
+}
+
+impl BreachChecker {
+    /// A checker using the Have I Been Pwned range API over HTTPS.
+    #[cfg(feature = "breach-check")]
+    pub fn new() -> Self {
+        Self::with_client(Arc::new(HibpClient::default()), DEFAULT_REQUEST_INTERVAL)
+    }
+
+    pub fn with_client(client: Arc<dyn RangeClient>, interval: Duration) -> Self {
+        Self {
+            client,
+            interval,
+            last_request: tokio::sync::Mutex::new(None),
+            ranges: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of times `password` appears in known breaches; 0 if it was not
+    /// found.
+    pub async fn check_pwned(&self, password: &str) -> Result<u32> {
+        let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(5);
+        let range = self.fetch_range(prefix).await?;
+        Ok(find_suffix_count(&range, suffix))
+    }
+
+    /// Check the password of every entry, calling `progress` with the number
+    /// checked so far and the total. Returns the breached entries, most
+    /// exposed first.
+    pub async fn check_entries<F>(
+        &self,
+        entries: &[DecryptedPasswordEntry],
+        mut progress: F,
+    ) -> Result<Vec<BreachedEntry>>
+    where
+        F: FnMut(usize, usize),
+    {
+        let mut breached = Vec::new();
+        for (index, entry) in entries.iter().enumerate() {
+            if !entry.password.is_empty() {
+                let count = self.check_pwned(&entry.password).await?;
+                if count > 0 {
+                    breached.push(BreachedEntry {
+                        entry_id: entry.id.clone(),
+                        site: entry.site.clone(),
+                        username: entry.username.clone(),
+                        count,
+                    });
+                }
+            }
+            progress(index + 1, entries.len());
+        }
+        breached.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.site.cmp(&b.site)));
+        Ok(breached)
+    }
+
+    async fn fetch_range(&self, prefix: &str) -> Result<Arc<String>> {
+        if let Some(range) = self.ranges.lock().unwrap().get(prefix) {
+            return Ok(range.clone());
+        }
+
+        {
+            // Held across the request so concurrent checks queue up
+            let mut last_request = self.last_request.lock().await;
+            if let Some(last) = *last_request {
+                tokio::time::sleep_until((last + self.interval).into()).await;
+            }
+            *last_request = Some(Instant::now());
+        }
+
+        let client = self.client.clone();
+        let request_prefix = prefix.to_string();
+        let range = tokio::task::spawn_blocking(move || client.fetch_range(&request_prefix))
+            .await
+            .map_err(|e| Error::Other(format!("Breach check failed: {}", e)))??;
+        let range = Arc::new(range);
+        self.ranges.lock().unwrap().insert(prefix.to_string(), range.clone());
+        Ok(range)
+    }
+}
+
+#[cfg(feature = "breach-check")]
+impl Default for BreachChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Number of times `password` appears in known breaches, looked up with the
+/// Have I Been Pwned range API.
+#[cfg(feature = "breach-check")]
+pub async fn check_pwned(password: &str) -> Result<u32> {
+    BreachChecker::new().check_pwned(password).await
+}
+
+fn find_suffix_count(range: &str, suffix: &str) -> u32 {
+    range
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// The Have I Been Pwned range API over HTTPS.
+#[cfg(feature = "breach-check")]
+pub struct HibpClient {
+    pub timeout: Duration,
+}
+
+#[cfg(feature = "breach-check")]
+impl Default for HibpClient {
+    fn default() -> Self {
+        Self { timeout: Duration::from_secs(15) }
+    }
+}
+
+#[cfg(feature = "breach-check")]
+impl HibpClient {
+    const HOST: &'static str = "api.pwnedpasswords.com";
+    /// A padded range is around 40 KB
+    const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+}
+
+#[cfg(feature = "breach-check")]
+impl RangeClient for HibpClient {
+    fn fetch_range(&self, prefix: &str) -> Result<String> {
+        let path = format!("/range/{}", prefix);
+        let response = crate::https::get(
+            Self::HOST,
+            &path,
+            &[("Add-Padding", "true")],
+            Self::MAX_RESPONSE_BYTES,
+            self.timeout,
+        )?;
+        if response.status != 200 {
+            return Err(Error::Other(format!("Breach API returned status {}", response.status)));
+        }
+        Ok(String::from_utf8_lossy(&response.body).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serves a fixed range and records the prefixes it was asked for.
+    struct MockClient {
+        range: String,
+        requests: Mutex<Vec<String>>,
+        calls: AtomicUsize,
+    }
+
+    impl RangeClient for MockClient {
+        fn fetch_range(&self, prefix: &str) -> Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.requests.lock().unwrap().push(prefix.to_string());
+            Ok(self.range.clone())
+        }
+    }
+
+    fn entry(site: &str, password: &str) -> DecryptedPasswordEntry {
+        DecryptedPasswordEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            site: site.to_string(),
+            username: "user".to_string(),
+            password: password.to_string(),
+            notes: None,
+            tags: vec![],
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            last_used: None,
+            password_changed_at: chrono::Utc::now(),
+            favorite: false,
+            urls: vec![],
+            created_by: None,
+            modified_by: None,
+            totp_secret: None,
+            password_history: Vec::new(),
+            require_reauth: false,
+            password_policy: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_pwned_sends_only_the_hash_prefix() {
+        // SHA-1("password") = 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+        let client = Arc::new(MockClient {
+            range: "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n\
+                    1E4C9B93F3F0682250B6CF8331B7EE68FD8:9659365\r\n\
+                    011053FD0102E94D6AE2F8B83D76FAF94F6:0\r\n"
+                .to_string(),
+            requests: Mutex::new(Vec::new()),
+            calls: AtomicUsize::new(0),
+        });
+        let checker = BreachChecker::with_client(client.clone(), Duration::ZERO);
+
+        assert_eq!(checker.check_pwned("password").await.unwrap(), 9659365);
+        assert_eq!(checker.check_pwned("not in the mocked range").await.unwrap(), 0);
+        let requests = client.requests.lock().unwrap().clone();
+        assert_eq!(requests[0], "5BAA6");
+        assert!(requests.iter().all(|prefix| prefix.len() == 5));
+
+        let entries = vec![entry("a.example", "password"), entry("b.example", "password"), entry("c.example", "")];
+        let calls_before = client.calls.load(Ordering::SeqCst);
+        let mut progress = Vec::new();
+        let breached = checker.check_entries(&entries, |done, total| progress.push((done, total))).await.unwrap();
+
+        assert_eq!(breached.len(), 2);
+        assert_eq!(breached[0].site, "a.example");
+        assert_eq!(breached[0].count, 9659365);
+        assert_eq!(progress.last(), Some(&(3, 3)));
+        // The range for "password" was cached by the first check
+        assert_eq!(client.calls.load(Ordering::SeqCst), calls_before);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
@@ -5,8 +6,10 @@ use csv::ReaderBuilder;
 use uuid::Uuid;
 
 use crate::{Result, Error};
-use crate::secrets::{SecretData, DecryptedSecretEntry, SecretType, SecretMetadata};
+use crate::cards;
+use crate::secrets::{NoteFormat, SecretData, DecryptedSecretEntry, SecretType, SecretMetadata};
 use crate::models::DecryptedPasswordEntry;
+use crate::redact::redacted;
 
 /// Supported browser types for import
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -18,6 +21,8 @@ pub enum BrowserType {
     Opera,
     Brave,
     Vivaldi,
+    /// A Bitwarden JSON export
+    Bitwarden,
     Custom(String),
 }
 
@@ -41,7 +46,7 @@ pub enum ImportFormat {
 }
 
 /// Imported password entry before conversion
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ImportedPassword {
     pub name: String,
     pub url: Option<String>,
@@ -56,6 +61,24 @@ pub struct ImportedPassword {
     pub tags: Vec<String>,
 }
 
+impl std::fmt::Debug for ImportedPassword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImportedPassword")
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("notes", &redacted(&self.notes))
+            .field("folder", &self.folder)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .field("totp_secret", &redacted(&self.totp_secret))
+            .field("favorite", &self.favorite)
+            .field("tags", &self.tags)
+            .finish()
+    }
+}
+
 /// Import statistics and results
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportResult {
@@ -79,7 +102,152 @@ pub struct ImportConfig {
     pub import_folders_as_tags: bool,
     pub default_tags: Vec<String>,
     pub password_strength_check: bool,
+    /// Apply `url_rules` to imported URLs
     pub cleanup_urls: bool,
+    #[serde(default)]
+    pub url_rules: UrlCleanupRules,
+    /// Template for entry names, e.g. `"{domain} ({username})"`. Available
+    /// placeholders are `{name}` (the name in the export), `{domain}` (the
+    /// registrable domain of the URL), `{host}`, `{username}` and `{folder}`.
+    #[serde(default = "default_name_template")]
+    pub name_template: String,
+    /// Read CSV exports with these columns instead of recognising them by
+    /// header name
+    #[serde(default)]
+    pub column_mapping: Option<CsvColumnMapping>,
+}
+
+fn default_name_template() -> String {
+    "{name}".to_string()
+}
+
+/// How imported URLs are tidied up when `ImportConfig::cleanup_urls` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlCleanupRules {
+    /// Prefix bare domains with `https://`
+    pub add_scheme: bool,
+    /// Drop `?query` parameters, which are often tracking or session tokens
+    pub strip_query: bool,
+    pub strip_fragment: bool,
+    pub lowercase_host: bool,
+    /// Drop a leading `www.` from the host
+    pub strip_www: bool,
+}
+
+impl Default for UrlCleanupRules {
+    fn default() -> Self {
+        Self {
+            add_scheme: true,
+            strip_query: true,
+            strip_fragment: true,
+            lowercase_host: true,
+            strip_www: false,
+        }
+    }
+}
+
+/// Name and URL an entry will be imported with, next to the values found in
+/// the export. Never includes the password.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportPreview {
+    pub original_name: String,
+    pub original_url: Option<String>,
+    pub name: String,
+    pub url: Option<String>,
+    pub username: Option<String>,
+}
+
+/// A CSV column, by position or by header name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CsvColumn {
+    /// 0-based position
+    Index(usize),
+    /// Header name, matched ignoring case
+    Header(String),
+}
+
+/// Which columns of a CSV export hold which entry fields, for exports whose
+/// layout isn't recognised. The first row must be a header row.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CsvColumnMapping {
+    /// Site name or URL
+    pub site: Option<CsvColumn>,
+    pub username: Option<CsvColumn>,
+    pub password: Option<CsvColumn>,
+    pub notes: Option<CsvColumn>,
+    /// Tags separated by commas or semicolons
+    pub tags: Option<CsvColumn>,
+}
+
+impl CsvColumnMapping {
+    /// Resolve each mapped column to its position in `headers`.
+    fn resolve(&self, headers: &csv::StringRecord) -> Result<[Option<usize>; 5]> {
+        let resolve = |column: &Option<CsvColumn>| -> Result<Option<usize>> {
+            match column {
+                None => Ok(None),
+                Some(CsvColumn::Index(index)) if *index < headers.len() => Ok(Some(*index)),
+                Some(CsvColumn::Index(index)) => Err(Error::Other(format!(
+                    "Column {} is out of range; the file has {} columns",
+                    index + 1,
+                    headers.len()
+                ))),
+                Some(CsvColumn::Header(name)) => headers
+                    .iter()
+                    .position(|header| header.trim().eq_ignore_ascii_case(name.trim()))
+                    .map(Some)
+                    .ok_or_else(|| Error::Other(format!(
+                        "No column named '{}' (columns: {})",
+                        name,
+                        headers.iter().collect::<Vec<_>>().join(", ")
+                    ))),
+            }
+        };
+        Ok([
+            resolve(&self.site)?,
+            resolve(&self.username)?,
+            resolve(&self.password)?,
+            resolve(&self.notes)?,
+            resolve(&self.tags)?,
+        ])
+    }
+}
+
+impl std::str::FromStr for CsvColumnMapping {
+    type Err = Error;
+
+    /// Parse `site=1,username=3,password=Secret`: fields mapped to 1-based
+    /// column numbers or header names.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut mapping = CsvColumnMapping::default();
+        for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (field, column) = pair
+                .split_once('=')
+                .ok_or_else(|| Error::Other(format!("Expected field=column, got '{}'", pair)))?;
+            let column = match column.trim().parse::<usize>() {
+                Ok(0) => return Err(Error::Other("Column numbers start at 1".to_string())),
+                Ok(number) => CsvColumn::Index(number - 1),
+                Err(_) => CsvColumn::Header(column.trim().to_string()),
+            };
+            let slot = match field.trim().to_lowercase().as_str() {
+                "site" | "url" => &mut mapping.site,
+                "username" | "user" => &mut mapping.username,
+                "password" => &mut mapping.password,
+                "notes" => &mut mapping.notes,
+                "tags" => &mut mapping.tags,
+                other => {
+                    return Err(Error::Other(format!(
+                        "Unknown field '{}' (expected site, username, password, notes or tags)",
+                        other
+                    )))
+                }
+            };
+            *slot = Some(column);
+        }
+        if mapping.password.is_none() {
+            return Err(Error::Other("The column mapping must include password".to_string()));
+        }
+        Ok(mapping)
+    }
 }
 
 impl Default for ImportConfig {
@@ -93,6 +261,9 @@ impl Default for ImportConfig {
             default_tags: vec!["imported".to_string()],
             password_strength_check: false,
             cleanup_urls: true,
+            url_rules: UrlCleanupRules::default(),
+            name_template: default_name_template(),
+            column_mapping: None,
         }
     }
 }
@@ -106,23 +277,75 @@ impl BrowserImporter {
         file_path: P,
         config: ImportConfig,
     ) -> Result<(Vec<ImportedPassword>, ImportResult)> {
-        let file_path = file_path.as_ref();
-        
-        let passwords = match config.format {
-            ImportFormat::Csv => Self::import_csv(file_path, &config)?,
-            ImportFormat::Json => Self::import_json(file_path, &config)?,
-            ImportFormat::Database => Self::import_database(file_path, &config)?,
-            ImportFormat::OnePasswordPif => Self::import_1password_pif(file_path, &config)?,
-            ImportFormat::KeePassXml => Self::import_keepass_xml(file_path, &config)?,
-            ImportFormat::LastPassCsv => Self::import_lastpass_csv(file_path, &config)?,
-            ImportFormat::BitwardenJson => Self::import_bitwarden_json(file_path, &config)?,
-        };
+        let passwords: Vec<ImportedPassword> = Self::parse_file(file_path.as_ref(), &config)?
+            .into_iter()
+            .map(|password| Self::tidy(password, &config))
+            .collect();
 
         let result = Self::process_imported_passwords(&passwords, &config)?;
         
         Ok((passwords, result))
     }
 
+    /// Show the names and URLs `import_from_file` would produce with
+    /// `config`, without importing anything.
+    pub fn preview_import<P: AsRef<Path>>(
+        file_path: P,
+        config: &ImportConfig,
+    ) -> Result<Vec<ImportPreview>> {
+        let passwords = Self::parse_file(file_path.as_ref(), config)?;
+        Ok(passwords
+            .into_iter()
+            .map(|original| {
+                let original_name = original.name.clone();
+                let original_url = original.url.clone();
+                let tidied = Self::tidy(original, config);
+                ImportPreview {
+                    original_name,
+                    original_url,
+                    name: tidied.name.clone(),
+                    url: tidied.url.clone(),
+                    username: tidied.username.clone(),
+                }
+            })
+            .collect())
+    }
+
+    fn parse_file(file_path: &Path, config: &ImportConfig) -> Result<Vec<ImportedPassword>> {
+        match config.format {
+            ImportFormat::Csv => Self::import_csv(file_path, config),
+            ImportFormat::Json => Self::import_json(file_path, config),
+            ImportFormat::Database => Self::import_database(file_path, config),
+            ImportFormat::OnePasswordPif => Self::import_1password_pif(file_path, config),
+            ImportFormat::KeePassXml => Self::import_keepass_xml(file_path, config),
+            ImportFormat::LastPassCsv => Self::import_lastpass_csv(file_path, config),
+            ImportFormat::BitwardenJson => Self::import_bitwarden_json(file_path, config),
+        }
+    }
+
+    /// Apply the URL cleanup rules and the name template to a parsed entry.
+    fn tidy(mut password: ImportedPassword, config: &ImportConfig) -> ImportedPassword {
+        if config.cleanup_urls {
+            password.url = password.url.map(|url| Self::cleanup_url(&url, &config.url_rules));
+        }
+
+        let host = password.url.as_deref().map(Self::extract_domain).unwrap_or_default();
+        let name = render_name_template(
+            &config.name_template,
+            &[
+                ("name", &password.name),
+                ("domain", &registrable_domain(&host)),
+                ("host", &host),
+                ("username", password.username.as_deref().unwrap_or("")),
+                ("folder", password.folder.as_deref().unwrap_or("")),
+            ],
+        );
+        if !name.is_empty() {
+            password.name = name;
+        }
+        password
+    }
+
     /// Convert imported passwords to password manager entries
     pub fn convert_to_entries(
         passwords: Vec<ImportedPassword>,
@@ -163,7 +386,7 @@ impl BrowserImporter {
             BrowserType::Opera => Self::get_opera_paths(),
             BrowserType::Brave => Self::get_brave_paths(),
             BrowserType::Vivaldi => Self::get_vivaldi_paths(),
-            BrowserType::Custom(_) => vec![],
+            BrowserType::Bitwarden | BrowserType::Custom(_) => vec![],
         }
     }
 
@@ -192,7 +415,14 @@ impl BrowserImporter {
         }
     }
 
-    /// Import from CSV format (Chrome, Edge, most browsers)
+    /// The header row of a CSV file, for choosing a column mapping.
+    pub fn csv_columns<P: AsRef<Path>>(file_path: P) -> Result<Vec<String>> {
+        let mut reader = ReaderBuilder::new().has_headers(true).from_path(file_path)?;
+        Ok(reader.headers()?.iter().map(|header| header.trim().to_string()).collect())
+    }
+
+    /// Import from CSV format (Chrome, Edge, most browsers), or any CSV
+    /// with `config.column_mapping`
     fn import_csv<P: AsRef<Path>>(
         file_path: P,
         config: &ImportConfig,
@@ -200,14 +430,28 @@ impl BrowserImporter {
         let content = std::fs::read_to_string(file_path)?;
         let mut reader = ReaderBuilder::new()
             .has_headers(true)
+            .flexible(config.column_mapping.is_some())
             .from_reader(content.as_bytes());
 
         let mut passwords = Vec::new();
         let headers = reader.headers()?.clone();
+        let columns = match &config.column_mapping {
+            Some(mapping) => Some(mapping.resolve(&headers)?),
+            None if !headers.iter().any(|h| matches!(h.trim().to_lowercase().as_str(), "password" | "pass")) => {
+                return Err(Error::Other(format!(
+                    "No password column recognised (columns: {}); map the columns explicitly",
+                    headers.iter().collect::<Vec<_>>().join(", ")
+                )));
+            }
+            None => None,
+        };
         
         for result in reader.records() {
             let record = result?;
-            let password = Self::parse_csv_record(&record, &headers, config)?;
+            let password = match &columns {
+                Some(columns) => Self::parse_mapped_csv_record(&record, columns, config),
+                None => Self::parse_csv_record(&record, &headers, config)?,
+            };
             if let Some(password) = password {
                 passwords.push(password);
             }
@@ -219,7 +463,7 @@ impl BrowserImporter {
     /// Import from JSON format
     fn import_json<P: AsRef<Path>>(
         file_path: P,
-        _config: &ImportConfig,
+        config: &ImportConfig,
     ) -> Result<Vec<ImportedPassword>> {
         let content = std::fs::read_to_string(file_path)?;
         let json_value: serde_json::Value = serde_json::from_str(&content)?;
@@ -236,16 +480,15 @@ impl BrowserImporter {
             }
             serde_json::Value::Object(ref obj) => {
                 // Check if it's a Bitwarden export with nested structure
-                if let Some(items) = obj.get("items") {
-                    if let serde_json::Value::Array(entries) = items {
-                        let mut passwords = Vec::new();
-                        for entry in entries {
-                            if let Some(password) = Self::parse_bitwarden_entry(entry)? {
-                                passwords.push(password);
-                            }
+                if let Some(serde_json::Value::Array(entries)) = obj.get("items") {
+                    let folders = Self::bitwarden_folders(obj)?;
+                    let mut passwords = Vec::new();
+                    for entry in entries {
+                        if let Some(password) = Self::parse_bitwarden_entry(entry, &folders, config)? {
+                            passwords.push(password);
                         }
-                        return Ok(passwords);
                     }
+                    return Ok(passwords);
                 }
                 
                 // Single entry JSON
@@ -338,6 +581,28 @@ impl BrowserImporter {
         Self::import_json(file_path, config)
     }
 
+    /// Import the secure notes and payment cards of a Bitwarden JSON export
+    /// as secrets. Its logins are imported by `import_from_file`.
+    pub fn import_bitwarden_secrets<P: AsRef<Path>>(
+        file_path: P,
+        config: &ImportConfig,
+    ) -> Result<Vec<DecryptedSecretEntry>> {
+        let content = std::fs::read_to_string(file_path)?;
+        let json_value: serde_json::Value = serde_json::from_str(&content)?;
+        let obj = json_value.as_object().ok_or_else(|| {
+            Error::Other("Invalid Bitwarden export format".to_string())
+        })?;
+        let folders = Self::bitwarden_folders(obj)?;
+
+        let mut secrets = Vec::new();
+        for item in obj.get("items").and_then(|v| v.as_array()).into_iter().flatten() {
+            if let Some(secret) = Self::parse_bitwarden_secret(item, &folders, config)? {
+                secrets.push(secret);
+            }
+        }
+        Ok(secrets)
+    }
+
     // Helper methods for parsing different formats
 
     fn parse_csv_record(
@@ -368,13 +633,7 @@ impl BrowserImporter {
                 
                 match header.to_lowercase().as_str() {
                     "name" | "title" | "site" => password.name = value.to_string(),
-                    "url" | "website" | "site_url" => {
-                        password.url = Some(if config.cleanup_urls {
-                            Self::cleanup_url(value)
-                        } else {
-                            value.to_string()
-                        });
-                    },
+                    "url" | "website" | "site_url" => password.url = Some(value.trim().to_string()),
                     "username" | "user" | "login" => password.username = Some(value.to_string()),
                     "password" | "pass" => password.password = Some(value.to_string()),
                     "notes" | "note" | "comment" => password.notes = Some(value.to_string()),
@@ -407,6 +666,50 @@ impl BrowserImporter {
         Ok(Some(password))
     }
 
+    /// Read a record through resolved `[site, username, password, notes,
+    /// tags]` column positions.
+    fn parse_mapped_csv_record(
+        record: &csv::StringRecord,
+        columns: &[Option<usize>; 5],
+        config: &ImportConfig,
+    ) -> Option<ImportedPassword> {
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|i| record.get(i))
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let [site, username, password, notes, tags] = columns.map(field);
+        let password = password?;
+
+        let mut all_tags = config.default_tags.clone();
+        for tag in tags.iter().flat_map(|tags| tags.split([',', ';'])).map(str::trim) {
+            if !tag.is_empty() && !all_tags.iter().any(|t| t == tag) {
+                all_tags.push(tag.to_string());
+            }
+        }
+        // A site that looks like a domain or URL is kept as the entry's URL
+        let url = site.clone().filter(|site| site.contains("://") || (site.contains('.') && !site.contains(' ')));
+        let name = site
+            .or_else(|| username.clone())
+            .unwrap_or_else(|| "Imported Entry".to_string());
+
+        Some(ImportedPassword {
+            name,
+            url,
+            username,
+            password: Some(password),
+            notes,
+            folder: None,
+            created_at: None,
+            updated_at: None,
+            totp_secret: None,
+            favorite: false,
+            tags: all_tags,
+        })
+    }
+
     fn parse_json_entry(entry: &serde_json::Value) -> Result<Option<ImportedPassword>> {
         if !entry.is_object() {
             return Ok(None);
@@ -472,7 +775,11 @@ impl BrowserImporter {
         }))
     }
 
-    fn parse_bitwarden_entry(entry: &serde_json::Value) -> Result<Option<ImportedPassword>> {
+    fn parse_bitwarden_entry(
+        entry: &serde_json::Value,
+        folders: &HashMap<String, String>,
+        config: &ImportConfig,
+    ) -> Result<Option<ImportedPassword>> {
         let obj = entry.as_object().ok_or_else(|| {
             Error::Other("Invalid Bitwarden entry format".to_string())
         })?;
@@ -518,8 +825,7 @@ impl BrowserImporter {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
-        let folder_id = obj.get("folderId")
-            .and_then(|v| v.as_str());
+        let folder = Self::bitwarden_folder(obj, folders);
 
         let favorite = obj.get("favorite")
             .and_then(|v| v.as_bool())
@@ -543,15 +849,131 @@ impl BrowserImporter {
             username,
             password,
             notes,
-            folder: folder_id.map(|s| s.to_string()),
-            created_at: None,
-            updated_at: None,
+            tags: Self::bitwarden_tags(folder.as_deref(), config),
+            folder,
+            created_at: Self::bitwarden_date(obj, "creationDate"),
+            updated_at: Self::bitwarden_date(obj, "revisionDate"),
             totp_secret,
             favorite,
-            tags: vec!["imported".to_string(), "bitwarden".to_string()],
         }))
     }
 
+    /// A Bitwarden secure note (type 2) or card (type 3) as a secret. Other
+    /// item types are skipped.
+    fn parse_bitwarden_secret(
+        entry: &serde_json::Value,
+        folders: &HashMap<String, String>,
+        config: &ImportConfig,
+    ) -> Result<Option<DecryptedSecretEntry>> {
+        let obj = entry.as_object().ok_or_else(|| {
+            Error::Other("Invalid Bitwarden entry format".to_string())
+        })?;
+        let text = |value: Option<&serde_json::Value>| {
+            value.and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string())
+        };
+
+        let name = text(obj.get("name")).unwrap_or_else(|| "Imported Entry".to_string());
+        let notes = text(obj.get("notes"));
+
+        // Notes are the content of a secure note and the description of a card
+        let (secret_type, data, description, expires_at) = match obj.get("type").and_then(|v| v.as_u64()) {
+            Some(2) => {
+                let data = SecretData::SecureNote {
+                    title: name.clone(),
+                    content: notes.unwrap_or_default(),
+                    format: NoteFormat::PlainText,
+                };
+                (SecretType::SecureNote, data, None, None)
+            }
+            Some(3) => {
+                let card = obj.get("card").and_then(|v| v.as_object()).ok_or_else(|| {
+                    Error::Other(format!("Bitwarden card '{}' has no card details", name))
+                })?;
+                let number = cards::normalize_card_number(&text(card.get("number")).unwrap_or_default());
+                let expiry_month: u8 = text(card.get("expMonth")).and_then(|m| m.parse().ok()).unwrap_or(0);
+                let expiry_year = text(card.get("expYear")).and_then(|y| match y.len() {
+                    2 => y.parse::<u16>().ok().map(|y| 2000 + y),
+                    _ => y.parse().ok(),
+                }).unwrap_or(0);
+                let data = SecretData::CreditCard {
+                    cardholder: text(card.get("cardholderName")).unwrap_or_default(),
+                    number,
+                    expiry_month,
+                    expiry_year,
+                    cvv: text(card.get("code")),
+                    issuer: text(card.get("brand")),
+                    billing_zip: None,
+                };
+                (SecretType::CreditCard, data, notes, cards::card_expires_at(expiry_month, expiry_year))
+            }
+            _ => return Ok(None),
+        };
+        data.validate().map_err(|e| Error::Other(format!("Bitwarden item '{}': {}", name, e)))?;
+
+        let folder = Self::bitwarden_folder(obj, folders);
+        let now = Utc::now();
+        Ok(Some(DecryptedSecretEntry {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description,
+            secret_type,
+            data,
+            metadata: SecretMetadata::default(),
+            tags: Self::bitwarden_tags(folder.as_deref(), config),
+            created_at: Self::bitwarden_date(obj, "creationDate").unwrap_or(now),
+            updated_at: Self::bitwarden_date(obj, "revisionDate").unwrap_or(now),
+            last_accessed: None,
+            expires_at,
+            favorite: obj.get("favorite").and_then(|v| v.as_bool()).unwrap_or(false),
+        }))
+    }
+
+    /// Folder names by id, from the `folders` array of a Bitwarden export.
+    /// Fails for password protected and account encrypted exports, whose
+    /// items cannot be read.
+    fn bitwarden_folders(export: &serde_json::Map<String, serde_json::Value>) -> Result<HashMap<String, String>> {
+        if export.get("encrypted").and_then(|v| v.as_bool()) == Some(true) {
+            return Err(Error::Other(
+                "This Bitwarden export is encrypted. Export the vault again as unencrypted JSON.".to_string()
+            ));
+        }
+        Ok(export.get("folders")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|folder| {
+                let id = folder.get("id")?.as_str()?;
+                let name = folder.get("name")?.as_str()?;
+                Some((id.to_string(), name.to_string()))
+            })
+            .collect())
+    }
+
+    fn bitwarden_folder(
+        item: &serde_json::Map<String, serde_json::Value>,
+        folders: &HashMap<String, String>,
+    ) -> Option<String> {
+        item.get("folderId")
+            .and_then(|v| v.as_str())
+            .and_then(|id| folders.get(id))
+            .cloned()
+    }
+
+    fn bitwarden_tags(folder: Option<&str>, config: &ImportConfig) -> Vec<String> {
+        let mut tags = config.default_tags.clone();
+        if let Some(folder) = folder.filter(|_| config.import_folders_as_tags) {
+            tags.push(folder.to_string());
+        }
+        tags
+    }
+
+    fn bitwarden_date(item: &serde_json::Map<String, serde_json::Value>, field: &str) -> Option<DateTime<Utc>> {
+        item.get(field)
+            .and_then(|v| v.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|date| date.with_timezone(&Utc))
+    }
+
     fn parse_1password_entry(entry: &serde_json::Value) -> Result<Option<ImportedPassword>> {
         let obj = entry.as_object().ok_or_else(|| {
             Error::Other("Invalid 1Password entry format".to_string())
@@ -575,15 +997,20 @@ impl BrowserImporter {
 
         // Parse secure contents
         let (username, password) = if let Some(contents) = obj.get("secureContents") {
-            let username = contents.get("username")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
-            
-            let password = contents.get("password")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string());
+            // Logins keep these in `fields`, tagged by designation; some
+            // exports put them directly on `secureContents`
+            let field = |designation: &str| {
+                contents.get(designation)
+                    .or_else(|| {
+                        contents.get("fields")?.as_array()?.iter()
+                            .find(|field| field.get("designation").and_then(|d| d.as_str()) == Some(designation))?
+                            .get("value")
+                    })
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            };
             
-            (username, password)
+            (field("username"), field("password"))
         } else {
             (None, None)
         };
@@ -654,13 +1081,7 @@ impl BrowserImporter {
 
         Ok(Some(ImportedPassword {
             name: entry_name,
-            url: if url.is_empty() { None } else { 
-                Some(if config.cleanup_urls {
-                    Self::cleanup_url(url)
-                } else {
-                    url.to_string()
-                })
-            },
+            url: if url.is_empty() { None } else { Some(url.to_string()) },
             username: if username.is_empty() { None } else { Some(username.to_string()) },
             password: Some(password.to_string()),
             notes: if extra.is_empty() { None } else { Some(extra.to_string()) },
@@ -692,6 +1113,13 @@ impl BrowserImporter {
             updated_at: imported.updated_at.unwrap_or(now),
             last_used: None,
             password_changed_at: imported.updated_at.unwrap_or(now),
+            urls: Vec::new(),
+            created_by: None,
+            modified_by: None,
+            totp_secret: None,
+            password_history: Vec::new(),
+            require_reauth: false,
+            password_policy: None,
         })
     }
 
@@ -749,15 +1177,32 @@ impl BrowserImporter {
         })
     }
 
-    fn cleanup_url(url: &str) -> String {
-        let url = url.trim();
-        if url.starts_with("http://") || url.starts_with("https://") {
-            url.to_string()
-        } else if url.contains('.') {
-            format!("https://{}", url)
-        } else {
-            url.to_string()
+    fn cleanup_url(url: &str, rules: &UrlCleanupRules) -> String {
+        let mut url = url.trim().to_string();
+        if rules.strip_fragment {
+            url.truncate(url.find('#').unwrap_or(url.len()));
+        }
+        if rules.strip_query {
+            url.truncate(url.find('?').unwrap_or(url.len()));
+        }
+        if rules.add_scheme && !url.contains("://") && url.contains('.') {
+            url = format!("https://{}", url);
+        }
+
+        // Host is everything between "scheme://" and the next '/'
+        let host_start = url.find("://").map(|i| i + 3).unwrap_or(0);
+        let host_end = url[host_start..].find('/').map(|i| host_start + i).unwrap_or(url.len());
+        let mut host = url[host_start..host_end].to_string();
+        if rules.lowercase_host {
+            host = host.to_lowercase();
         }
+        if rules.strip_www {
+            if let Some(stripped) = host.strip_prefix("www.") {
+                host = stripped.to_string();
+            }
+        }
+        url.replace_range(host_start..host_end, &host);
+        url
     }
 
     fn extract_domain(url: &str) -> String {
@@ -790,7 +1235,7 @@ impl BrowserImporter {
         }
         
         if lower_line.contains("bitwarden") {
-            return Ok(BrowserType::Custom("Bitwarden".to_string()));
+            return Ok(BrowserType::Bitwarden);
         }
         
         // Default to Chrome format for standard CSV
@@ -800,8 +1245,13 @@ impl BrowserImporter {
         
         // Check for JSON format indicators
         if content.trim_start().starts_with('{') || content.trim_start().starts_with('[') {
-            if content.contains("\"encrypted\"") && content.contains("\"folders\"") {
-                return Ok(BrowserType::Custom("Bitwarden".to_string()));
+            // Bitwarden exports look like {"encrypted": false, "folders": [...], "items": [...]}
+            if let Ok(serde_json::Value::Object(obj)) = serde_json::from_str::<serde_json::Value>(&content) {
+                if obj.get("encrypted").is_some_and(|v| v.is_boolean())
+                    && obj.get("items").is_some_and(|v| v.is_array())
+                {
+                    return Ok(BrowserType::Bitwarden);
+                }
             }
             if content.contains("\"category\"") && content.contains("\"secureContents\"") {
                 return Ok(BrowserType::Custom("1Password".to_string()));
@@ -990,6 +1440,35 @@ impl BrowserImporter {
     }
 }
 
+/// Second-level domains under which registrations happen one level deeper,
+/// as in "example.co.uk". A heuristic, not the full public suffix list.
+const SHARED_SECOND_LEVEL: &[&str] = &["co", "com", "net", "org", "gov", "edu", "ac"];
+
+/// The registrable part of `host`: "accounts.google.com" gives "google.com"
+/// and "login.example.co.uk" gives "example.co.uk". IP addresses and
+/// single-label hosts are returned unchanged.
+fn registrable_domain(host: &str) -> String {
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return host.to_string();
+    }
+    let labels: Vec<&str> = host.split('.').filter(|l| !l.is_empty()).collect();
+    let keep = match labels.as_slice() {
+        [.., second, top] if top.len() == 2 && SHARED_SECOND_LEVEL.contains(second) => 3,
+        _ => 2,
+    };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+/// Fill in `{placeholder}`s in an import name template. Unknown placeholders
+/// are left as they are; the result is trimmed.
+fn render_name_template(template: &str, fields: &[(&str, &str)]) -> String {
+    let mut name = template.to_string();
+    for (field, value) in fields {
+        name = name.replace(&format!("{{{}}}", field), value);
+    }
+    name.trim().to_string()
+}
+
 impl std::fmt::Display for BrowserType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -1000,6 +1479,7 @@ impl std::fmt::Display for BrowserType {
             BrowserType::Opera => write!(f, "Opera"),
             BrowserType::Brave => write!(f, "Brave"),
             BrowserType::Vivaldi => write!(f, "Vivaldi"),
+            BrowserType::Bitwarden => write!(f, "Bitwarden"),
             BrowserType::Custom(name) => write!(f, "{}", name),
         }
     }
@@ -1017,6 +1497,7 @@ impl std::str::FromStr for BrowserType {
             "opera" => Ok(BrowserType::Opera),
             "brave" => Ok(BrowserType::Brave),
             "vivaldi" => Ok(BrowserType::Vivaldi),
+            "bitwarden" => Ok(BrowserType::Bitwarden),
             s => Ok(BrowserType::Custom(s.to_string())),
         }
     }
@@ -1095,9 +1576,10 @@ mod tests {
 
     #[test]
     fn test_cleanup_url() {
-        assert_eq!(BrowserImporter::cleanup_url("example.com"), "https://example.com");
-        assert_eq!(BrowserImporter::cleanup_url("https://example.com"), "https://example.com");
-        assert_eq!(BrowserImporter::cleanup_url("http://example.com"), "http://example.com");
+        let rules = UrlCleanupRules::default();
+        assert_eq!(BrowserImporter::cleanup_url("example.com", &rules), "https://example.com");
+        assert_eq!(BrowserImporter::cleanup_url("https://example.com", &rules), "https://example.com");
+        assert_eq!(BrowserImporter::cleanup_url("http://example.com", &rules), "http://example.com");
     }
 
     #[test]
@@ -1106,10 +1588,110 @@ mod tests {
         assert_eq!(BrowserImporter::extract_domain("example.com"), "example.com");
     }
 
+    #[test]
+    fn test_url_rules_and_name_template() {
+        let csv_content = "name,url,username,password,folder\n\
+            ,HTTPS://Accounts.Google.COM/signin?continue=x&utm_source=y#frag,alice,pw1,Mail\n\
+            My Bank,online.bank.co.uk/login,bob,pw2,\n";
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(csv_content.as_bytes()).unwrap();
+
+        let config = ImportConfig {
+            name_template: "{domain} ({username})".to_string(),
+            ..Default::default()
+        };
+        let preview = BrowserImporter::preview_import(temp_file.path(), &config).unwrap();
+        assert_eq!(preview[0].original_url.as_deref(), Some("HTTPS://Accounts.Google.COM/signin?continue=x&utm_source=y#frag"));
+        assert_eq!(preview[0].url.as_deref(), Some("HTTPS://accounts.google.com/signin"));
+        assert_eq!(preview[0].name, "google.com (alice)");
+        assert_eq!(preview[1].url.as_deref(), Some("https://online.bank.co.uk/login"));
+        assert_eq!(preview[1].name, "bank.co.uk (bob)");
+
+        let (imported, _) = BrowserImporter::import_from_file(temp_file.path(), config).unwrap();
+        assert_eq!(imported[1].name, preview[1].name);
+
+        let raw = ImportConfig { cleanup_urls: false, ..Default::default() };
+        let preview = BrowserImporter::preview_import(temp_file.path(), &raw).unwrap();
+        assert_eq!(preview[0].url, preview[0].original_url);
+        assert_eq!(preview[1].name, "My Bank");
+    }
+
+    #[test]
+    fn test_import_csv_with_column_mapping() {
+        let csv_content = "Account,Login,Secret,Comment,Labels\n\
+            example.com,alice,pw1,2FA on phone,\"work; mail\"\n\
+            My Router,admin,,,\n\
+            Intranet Wiki,bob,pw2,,\n";
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(csv_content.as_bytes()).unwrap();
+
+        assert_eq!(
+            BrowserImporter::csv_columns(temp_file.path()).unwrap(),
+            ["Account", "Login", "Secret", "Comment", "Labels"]
+        );
+        // Nothing to recognise without a mapping
+        assert!(BrowserImporter::import_csv(temp_file.path(), &ImportConfig::default()).is_err());
+
+        let mapping: CsvColumnMapping = "site=1, username=login, password=3, notes=4, tags=5".parse().unwrap();
+        assert_eq!(mapping.username, Some(CsvColumn::Header("login".to_string())));
+        let config = ImportConfig { column_mapping: Some(mapping), ..Default::default() };
+        let result = BrowserImporter::import_csv(temp_file.path(), &config).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].url.as_deref(), Some("example.com"));
+        assert_eq!(result[0].username.as_deref(), Some("alice"));
+        assert_eq!(result[0].password.as_deref(), Some("pw1"));
+        assert_eq!(result[0].notes.as_deref(), Some("2FA on phone"));
+        assert_eq!(result[0].tags, ["imported", "work", "mail"]);
+        assert_eq!(result[1].name, "Intranet Wiki");
+        assert_eq!(result[1].url, None);
+
+        let missing = ImportConfig { column_mapping: Some("password=Pass".parse().unwrap()), ..Default::default() };
+        assert!(BrowserImporter::import_csv(temp_file.path(), &missing).is_err());
+        assert!("site=1".parse::<CsvColumnMapping>().is_err());
+        assert!("password=0".parse::<CsvColumnMapping>().is_err());
+        assert!("colour=2,password=3".parse::<CsvColumnMapping>().is_err());
+    }
+
+    #[test]
+    fn test_parse_bitwarden_export() {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/bitwarden_export.json");
+        assert_eq!(BrowserImporter::detect_browser_type(fixture).unwrap(), BrowserType::Bitwarden);
+
+        let config = ImportConfig { format: ImportFormat::BitwardenJson, ..Default::default() };
+        let (logins, result) = BrowserImporter::import_from_file(fixture, config.clone()).unwrap();
+        // The login without a password is skipped
+        assert_eq!(result.total_entries, 1);
+        assert_eq!(logins[0].name, "Example Mail");
+        assert_eq!(logins[0].username.as_deref(), Some("alice@example.com"));
+        assert_eq!(logins[0].url.as_deref(), Some("https://mail.example.com/login"));
+        assert_eq!(logins[0].folder.as_deref(), Some("Work"));
+        assert_eq!(logins[0].tags, ["imported", "Work"]);
+        assert!(logins[0].favorite);
+        assert_eq!(logins[0].created_at.unwrap().to_rfc3339(), "2023-11-20T08:00:00+00:00");
+
+        let secrets = BrowserImporter::import_bitwarden_secrets(fixture, &config).unwrap();
+        assert_eq!(secrets.len(), 2);
+        assert_eq!(secrets[0].secret_type, SecretType::SecureNote);
+        assert_eq!(secrets[0].tags, ["imported", "Work"]);
+        assert!(matches!(&secrets[0].data, SecretData::SecureNote { title, content, .. }
+            if title == "Door codes" && content == "Front: 4821\nGarage: 1397"));
+        assert_eq!(secrets[1].secret_type, SecretType::CreditCard);
+        assert_eq!(secrets[1].tags, ["imported", "Finance"]);
+        assert!(matches!(&secrets[1].data, SecretData::CreditCard { number, expiry_month: 4, expiry_year: 2029, cvv, .. }
+            if number == "4111111111111111" && cvv.as_deref() == Some("123")));
+        assert_eq!(secrets[1].expires_at.unwrap().to_rfc3339(), "2029-05-01T00:00:00+00:00");
+
+        let mut encrypted = NamedTempFile::new().unwrap();
+        encrypted.write_all(br#"{"encrypted": true, "passwordProtected": true, "data": "2.abc"}"#).unwrap();
+        assert!(BrowserImporter::import_bitwarden_secrets(encrypted.path(), &config).is_err());
+    }
+
     #[test]
     fn test_browser_type_parsing() {
         assert_eq!("chrome".parse::<BrowserType>().unwrap(), BrowserType::Chrome);
         assert_eq!("firefox".parse::<BrowserType>().unwrap(), BrowserType::Firefox);
+        assert_eq!("Bitwarden".parse::<BrowserType>().unwrap(), BrowserType::Bitwarden);
         assert_eq!("custom".parse::<BrowserType>().unwrap(), BrowserType::Custom("custom".to_string()));
     }
 }
\ No newline at end of file
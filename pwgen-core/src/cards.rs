@@ -0,0 +1,172 @@
+//! Payment card helpers for `SecretData::CreditCard`: number checks, the
+//! brand implied by the issuer identification number (the first digits),
+//! and masking for display.
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Card network, inferred from the number's leading digits.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CardBrand {
+    Visa,
+    Mastercard,
+    AmericanExpress,
+    Discover,
+    DinersClub,
+    Jcb,
+    UnionPay,
+    Maestro,
+    Unknown,
+}
+
+impl CardBrand {
+    /// The brand for `number`, which may contain spaces or dashes.
+    pub fn from_number(number: &str) -> Self {
+        let digits = normalize_card_number(number);
+        // Value of the first `len` digits, if the number has that many
+        let prefix = |len: usize| digits.get(..len).and_then(|p| p.parse::<u32>().ok());
+        let in_range = |len: usize, low: u32, high: u32| prefix(len).is_some_and(|p| (low..=high).contains(&p));
+
+        if in_range(1, 4, 4) {
+            CardBrand::Visa
+        } else if in_range(2, 51, 55) || in_range(4, 2221, 2720) {
+            CardBrand::Mastercard
+        } else if in_range(2, 34, 34) || in_range(2, 37, 37) {
+            CardBrand::AmericanExpress
+        } else if in_range(4, 6011, 6011) || in_range(3, 644, 649) || in_range(2, 65, 65) || in_range(6, 622126, 622925) {
+            CardBrand::Discover
+        } else if in_range(3, 300, 305) || in_range(2, 36, 36) || in_range(2, 38, 39) {
+            CardBrand::DinersClub
+        } else if in_range(4, 3528, 3589) {
+            CardBrand::Jcb
+        } else if in_range(2, 62, 62) {
+            CardBrand::UnionPay
+        } else if in_range(2, 50, 50) || in_range(2, 56, 58) || in_range(4, 6304, 6304) || in_range(2, 67, 67) {
+            CardBrand::Maestro
+        } else {
+            CardBrand::Unknown
+        }
+    }
+}
+
+impl fmt::Display for CardBrand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CardBrand::Visa => "Visa",
+            CardBrand::Mastercard => "Mastercard",
+            CardBrand::AmericanExpress => "American Express",
+            CardBrand::Discover => "Discover",
+            CardBrand::DinersClub => "Diners Club",
+            CardBrand::Jcb => "JCB",
+            CardBrand::UnionPay => "UnionPay",
+            CardBrand::Maestro => "Maestro",
+            CardBrand::Unknown => "Unknown",
+        })
+    }
+}
+
+/// `number` with the spaces and dashes people type between digit groups
+/// removed.
+pub fn normalize_card_number(number: &str) -> String {
+    number.chars().filter(|c| !matches!(c, ' ' | '-')).collect()
+}
+
+/// Whether `number` is 12 to 19 digits long and passes the Luhn checksum,
+/// which catches any single mistyped digit and most swapped pairs.
+pub fn luhn_check(number: &str) -> bool {
+    let digits = normalize_card_number(number);
+    if !(12..=19).contains(&digits.len()) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .bytes()
+        .rev()
+        .map(|b| u32::from(b - b'0'))
+        .enumerate()
+        .map(|(i, digit)| match (i % 2, digit * 2) {
+            (0, _) => digit,
+            (_, doubled) if doubled > 9 => doubled - 9,
+            (_, doubled) => doubled,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// `number` with all but the last four digits hidden, e.g.
+/// `•••• •••• •••• 1111`.
+pub fn mask_card_number(number: &str) -> String {
+    let digits = normalize_card_number(number);
+    let last_four: String = digits.chars().rev().take(4).collect::<Vec<_>>().into_iter().rev().collect();
+    format!("•••• •••• •••• {}", last_four)
+}
+
+/// Parse an expiry date as printed on cards, `MM/YY` or `MM/YYYY` (a dash
+/// works too), into month and four digit year.
+pub fn parse_expiry(text: &str) -> Option<(u8, u16)> {
+    let (month, year) = text.trim().split_once(['/', '-'])?;
+    let month: u8 = month.trim().parse().ok().filter(|m| (1..=12).contains(m))?;
+    let year = year.trim();
+    let year: u16 = match year.len() {
+        2 => 2000 + year.parse::<u16>().ok()?,
+        4 => year.parse().ok()?,
+        _ => return None,
+    };
+    Some((month, year))
+}
+
+/// The moment a card expiring `month`/`year` stops being valid: the end of
+/// that month. `None` for an invalid month.
+pub fn card_expires_at(month: u8, year: u16) -> Option<DateTime<Utc>> {
+    let first_of_month = NaiveDate::from_ymd_opt(i32::from(year), u32::from(month), 1)?;
+    let next_month = if first_of_month.month() == 12 {
+        NaiveDate::from_ymd_opt(first_of_month.year() + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(first_of_month.year(), first_of_month.month() + 1, 1)?
+    };
+    Some(next_month.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luhn_check() {
+        assert!(luhn_check("4111 1111 1111 1111"));
+        assert!(luhn_check("5555-5555-5555-4444"));
+        assert!(luhn_check("378282246310005"));
+        assert!(!luhn_check("4111 1111 1111 1112"));
+        assert!(!luhn_check("4111 1111 1111 111a"));
+        assert!(!luhn_check("0000"));
+    }
+
+    #[test]
+    fn test_brand_from_number() {
+        assert_eq!(CardBrand::from_number("4111 1111 1111 1111"), CardBrand::Visa);
+        assert_eq!(CardBrand::from_number("5555555555554444"), CardBrand::Mastercard);
+        assert_eq!(CardBrand::from_number("2223003122003222"), CardBrand::Mastercard);
+        assert_eq!(CardBrand::from_number("378282246310005"), CardBrand::AmericanExpress);
+        assert_eq!(CardBrand::from_number("6011111111111117"), CardBrand::Discover);
+        assert_eq!(CardBrand::from_number("6221260000000000"), CardBrand::Discover);
+        assert_eq!(CardBrand::from_number("30569309025904"), CardBrand::DinersClub);
+        assert_eq!(CardBrand::from_number("3530111333300000"), CardBrand::Jcb);
+        assert_eq!(CardBrand::from_number("6200000000000005"), CardBrand::UnionPay);
+        assert_eq!(CardBrand::from_number("6759649826438453"), CardBrand::Maestro);
+        assert_eq!(CardBrand::from_number("9999"), CardBrand::Unknown);
+        assert_eq!(CardBrand::from_number(""), CardBrand::Unknown);
+    }
+
+    #[test]
+    fn test_mask_and_expiry() {
+        assert_eq!(mask_card_number("4111 1111 1111 1234"), "•••• •••• •••• 1234");
+        assert_eq!(card_expires_at(12, 2030).unwrap().to_rfc3339(), "2031-01-01T00:00:00+00:00");
+        assert_eq!(card_expires_at(2, 2028).unwrap().to_rfc3339(), "2028-03-01T00:00:00+00:00");
+        assert!(card_expires_at(13, 2030).is_none());
+        assert_eq!(parse_expiry("04/27"), Some((4, 2027)));
+        assert_eq!(parse_expiry(" 12-2031 "), Some((12, 2031)));
+        assert_eq!(parse_expiry("13/27"), None);
+        assert_eq!(parse_expiry("0427"), None);
+    }
+}
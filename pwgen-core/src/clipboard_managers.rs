@@ -0,0 +1,156 @@
+//! Detect clipboard history tools that may keep copies of copied secrets.
+//!
+//! Detection is best effort: it looks for the processes of known clipboard
+//! managers and, on Windows, at the built-in clipboard history setting. Tools
+//! that are not in the list, or that run inside another process (Klipper in
+//! recent Plasma releases, GNOME Shell extensions), go unnoticed.
+
+/// Process names of known clipboard managers and the names to show for them.
+const KNOWN_MANAGERS: &[(&str, &str)] = &[
+    // Linux and BSD
+    ("klipper", "Klipper"),
+    ("copyq", "CopyQ"),
+    ("clipit", "ClipIt"),
+    ("parcellite", "Parcellite"),
+    ("gpaste-daemon", "GPaste"),
+    ("diodon", "Diodon"),
+    ("greenclip", "Greenclip"),
+    ("xfce4-clipman", "Clipman"),
+    ("clipman", "Clipman"),
+    ("cliphist", "cliphist"),
+    ("clipmenud", "clipmenu"),
+    ("clipcat", "Clipcat"),
+    // macOS
+    ("maccy", "Maccy"),
+    ("paste", "Paste"),
+    ("pastebot", "Pastebot"),
+    ("copyclip", "CopyClip"),
+    ("flycut", "Flycut"),
+    ("clipy", "Clipy"),
+    ("alfred", "Alfred"),
+    ("raycast", "Raycast"),
+    // Windows
+    ("ditto", "Ditto"),
+    ("clipclip", "ClipClip"),
+    ("clipboardfusion", "ClipboardFusion"),
+    ("1clipboard", "1Clipboard"),
+];
+
+/// Names of the clipboard managers that appear to be active, in a stable
+/// order and without duplicates. Empty when none were found or the process
+/// list could not be read.
+pub fn detect_clipboard_managers() -> Vec<&'static str> {
+    #[allow(unused_mut)]
+    let mut managers = managers_in(running_process_names());
+    #[cfg(windows)]
+    if windows_clipboard_history_enabled() {
+        managers.push("Windows clipboard history");
+    }
+    managers
+}
+
+/// Match process names (with or without a path or `.exe` suffix) against the
+/// known clipboard managers.
+fn managers_in<I, S>(process_names: I) -> Vec<&'static str>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let running: Vec<String> = process_names
+        .into_iter()
+        .map(|name| {
+            let name = name.as_ref().trim();
+            let name = name.rsplit(['/', '\\']).next().unwrap_or(name).to_lowercase();
+            name.strip_suffix(".exe").map(str::to_string).unwrap_or(name)
+        })
+        .collect();
+
+    let mut managers = Vec::new();
+    for (process, display) in KNOWN_MANAGERS {
+        if running.iter().any(|name| name == process) && !managers.contains(display) {
+            managers.push(*display);
+        }
+    }
+    managers
+}
+
+#[cfg(target_os = "linux")]
+fn running_process_names() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().bytes().all(|b| b.is_ascii_digit()))
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("comm")).ok())
+        .collect()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn running_process_names() -> Vec<String> {
+    command_output("ps", &["-axo", "comm="])
+        .map(|output| output.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(windows)]
+fn running_process_names() -> Vec<String> {
+    // CSV rows start with the quoted image name: "Ditto.exe","1234",...
+    command_output("tasklist", &["/fo", "csv", "/nh"])
+        .map(|output| {
+            output
+                .lines()
+                .filter_map(|line| line.split(',').next())
+                .map(|name| name.trim_matches('"').to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn running_process_names() -> Vec<String> {
+    Vec::new()
+}
+
+/// Whether Win+V clipboard history is switched on for the current user.
+#[cfg(windows)]
+fn windows_clipboard_history_enabled() -> bool {
+    command_output(
+        "reg",
+        &["query", r"HKCU\Software\Microsoft\Clipboard", "/v", "EnableClipboardHistory"],
+    )
+    .is_some_and(|output| output.contains("0x1"))
+}
+
+#[cfg(any(windows, all(unix, not(target_os = "linux"))))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_managers_in_matches_paths_and_suffixes() {
+        let processes = [
+            "systemd",
+            "copyq\n",
+            "/Applications/Maccy.app/Contents/MacOS/Maccy",
+            r"C:\Program Files\Ditto\Ditto.exe",
+            "xfce4-clipman",
+            "clipman",
+            "pastebin-helper",
+        ];
+        assert_eq!(managers_in(processes), vec!["CopyQ", "Clipman", "Maccy", "Ditto"]);
+        assert!(managers_in(["bash", "firefox"]).is_empty());
+    }
+}
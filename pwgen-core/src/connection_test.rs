@@ -0,0 +1,358 @@
+//! Live reachability checks for stored database connections.
+//!
+//! A test resolves the host, opens a TCP connection and then speaks just
+//! enough of the database protocol to log in and ping: sqlx for PostgreSQL
+//! and MySQL, and a hand-written `AUTH`/`PING` exchange for Redis. Errors are
+//! reduced to a few categories, and the password never appears in them.
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use sqlx::mysql::{MySqlConnectOptions, MySqlConnection, MySqlSslMode};
+use sqlx::postgres::{PgConnectOptions, PgConnection, PgSslMode};
+use sqlx::{ConnectOptions, Connection};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use zeroize::Zeroize;
+
+use crate::env_connections::EnvConnectionManager;
+use crate::secrets::{DatabaseType, SecretData, SslConfig};
+
+/// How long a connection test may take before it counts as timed out
+pub const CONNECTION_TEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Why a connection test failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionTestError {
+    /// The host name could not be resolved
+    Dns,
+    /// Nothing is listening on the port
+    Refused,
+    /// The server rejected the username or password
+    Auth,
+    /// No answer within the timeout
+    Timeout,
+    /// This kind of database can't be tested
+    Unsupported(DatabaseType),
+    /// Anything else, e.g. a TLS or protocol error
+    Other(String),
+}
+
+impl std::fmt::Display for ConnectionTestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionTestError::Dns => write!(f, "Host name could not be resolved"),
+            ConnectionTestError::Refused => write!(f, "Connection refused"),
+            ConnectionTestError::Auth => write!(f, "Authentication failed"),
+            ConnectionTestError::Timeout => write!(f, "Timed out"),
+            ConnectionTestError::Unsupported(database_type) => {
+                write!(f, "Testing {:?} connections is not supported", database_type)
+            }
+            ConnectionTestError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ConnectionTestError {}
+
+/// Fields of a connection string secret, filled in from the stored URL where
+/// the secret leaves them empty.
+struct Target<'a> {
+    database_type: &'a DatabaseType,
+    host: String,
+    port: u16,
+    database: String,
+    username: String,
+    password: String,
+    ssl_config: &'a Option<SslConfig>,
+}
+
+impl EnvConnectionManager {
+    /// Connect to the database a connection string secret points at, log in
+    /// and ping it. Returns how long that took.
+    pub async fn test_connection(conn: &SecretData) -> std::result::Result<Duration, ConnectionTestError> {
+        Self::test_connection_with_timeout(conn, CONNECTION_TEST_TIMEOUT).await
+    }
+
+    pub async fn test_connection_with_timeout(
+        conn: &SecretData,
+        timeout: Duration,
+    ) -> std::result::Result<Duration, ConnectionTestError> {
+        let target = Target::from_secret(conn)?;
+        let started = Instant::now();
+        tokio::time::timeout(timeout, target.check())
+            .await
+            .map_err(|_| ConnectionTestError::Timeout)??;
+        Ok(started.elapsed())
+    }
+}
+
+impl Drop for Target<'_> {
+    fn drop(&mut self) {
+        self.password.zeroize();
+    }
+}
+
+impl<'a> Target<'a> {
+    fn from_secret(conn: &'a SecretData) -> std::result::Result<Self, ConnectionTestError> {
+        let SecretData::ConnectionString {
+            database_type,
+            host,
+            port,
+            database,
+            username,
+            password,
+            connection_string,
+            ssl_config,
+        } = conn
+        else {
+            return Err(ConnectionTestError::Other("Not a connection string".to_string()));
+        };
+        if !matches!(database_type, DatabaseType::PostgreSQL | DatabaseType::MySQL | DatabaseType::Redis) {
+            return Err(ConnectionTestError::Unsupported(database_type.clone()));
+        }
+
+        let mut target = Target {
+            database_type,
+            host: host.clone(),
+            port: port.unwrap_or_else(|| EnvConnectionManager::get_default_port(database_type)),
+            database: database.clone(),
+            username: username.clone(),
+            password: password.clone(),
+            ssl_config,
+        };
+        if target.host.is_empty() {
+            let parsed = EnvConnectionManager::parse_connection_string(connection_string)
+                .map_err(|_| ConnectionTestError::Other("No host to connect to".to_string()))?;
+            target.host = parsed.host;
+            target.port = parsed.port.unwrap_or(target.port);
+            target.database = parsed.database;
+            target.username = parsed.username.unwrap_or_default();
+            target.password = parsed.password.unwrap_or_default();
+        }
+        Ok(target)
+    }
+
+    async fn check(&self) -> std::result::Result<(), ConnectionTestError> {
+        let address = self.resolve().await?;
+        let stream = TcpStream::connect(address).await.map_err(io_error)?;
+        match self.database_type {
+            DatabaseType::Redis => self.ping_redis(stream).await,
+            // sqlx opens its own connection, by host name so that TLS can
+            // verify it
+            DatabaseType::PostgreSQL => {
+                drop(stream);
+                self.ping_postgres().await
+            }
+            _ => {
+                drop(stream);
+                self.ping_mysql().await
+            }
+        }
+    }
+
+    async fn resolve(&self) -> std::result::Result<SocketAddr, ConnectionTestError> {
+        tokio::net::lookup_host((self.host.as_str(), self.port))
+            .await
+            .map_err(|_| ConnectionTestError::Dns)?
+            .next()
+            .ok_or(ConnectionTestError::Dns)
+    }
+
+    fn tls(&self) -> (bool, bool) {
+        self.ssl_config.as_ref().map_or((false, false), |ssl| (ssl.enabled, ssl.verify_ssl))
+    }
+
+    async fn ping_postgres(&self) -> std::result::Result<(), ConnectionTestError> {
+        let ssl_mode = match self.tls() {
+            (true, true) => PgSslMode::VerifyFull,
+            (true, false) => PgSslMode::Require,
+            _ => PgSslMode::Prefer,
+        };
+        let mut options = PgConnectOptions::new()
+            .host(&self.host)
+            .port(self.port)
+            .username(&self.username)
+            .password(&self.password)
+            .ssl_mode(ssl_mode)
+            .disable_statement_logging();
+        if !self.database.is_empty() {
+            options = options.database(&self.database);
+        }
+        let mut connection = PgConnection::connect_with(&options).await.map_err(sqlx_error)?;
+        connection.ping().await.map_err(sqlx_error)?;
+        connection.close().await.map_err(sqlx_error)
+    }
+
+    async fn ping_mysql(&self) -> std::result::Result<(), ConnectionTestError> {
+        let ssl_mode = match self.tls() {
+            (true, true) => MySqlSslMode::VerifyIdentity,
+            (true, false) => MySqlSslMode::Required,
+            _ => MySqlSslMode::Preferred,
+        };
+        let mut options = MySqlConnectOptions::new()
+            .host(&self.host)
+            .port(self.port)
+            .username(&self.username)
+            .password(&self.password)
+            .ssl_mode(ssl_mode)
+            .disable_statement_logging();
+        if !self.database.is_empty() {
+            options = options.database(&self.database);
+        }
+        let mut connection = MySqlConnection::connect_with(&options).await.map_err(sqlx_error)?;
+        connection.ping().await.map_err(sqlx_error)?;
+        connection.close().await.map_err(sqlx_error)
+    }
+
+    async fn ping_redis(&self, stream: TcpStream) -> std::result::Result<(), ConnectionTestError> {
+        if self.tls().0 {
+            return Err(ConnectionTestError::Other("TLS connections to Redis can't be tested".to_string()));
+        }
+        let mut stream = BufReader::new(stream);
+        if !self.password.is_empty() {
+            let auth = if self.username.is_empty() {
+                redis_command(&["AUTH", &self.password])
+            } else {
+                redis_command(&["AUTH", &self.username, &self.password])
+            };
+            stream.get_mut().write_all(auth.as_bytes()).await.map_err(io_error)?;
+            read_redis_reply(&mut stream, "+OK").await?;
+        }
+        stream.get_mut().write_all(redis_command(&["PING"]).as_bytes()).await.map_err(io_error)?;
+        read_redis_reply(&mut stream, "+PONG").await
+    }
+}
+
+/// A command in the Redis wire protocol (RESP) as an array of bulk strings.
+fn redis_command(args: &[&str]) -> String {
+    let mut command = format!("*{}\r\n", args.len());
+    for arg in args {
+        command.push_str(&format!("${}\r\n{}\r\n", arg.len(), arg));
+    }
+    command
+}
+
+async fn read_redis_reply(
+    stream: &mut BufReader<TcpStream>,
+    expected: &str,
+) -> std::result::Result<(), ConnectionTestError> {
+    let mut line = String::new();
+    if stream.read_line(&mut line).await.map_err(io_error)? == 0 {
+        return Err(ConnectionTestError::Other("Server closed the connection".to_string()));
+    }
+    let reply = line.trim_end();
+    if reply == expected {
+        return Ok(());
+    }
+    let upper = reply.to_ascii_uppercase();
+    if upper.starts_with("-WRONGPASS") || upper.starts_with("-NOAUTH") || upper.contains("INVALID PASSWORD") {
+        Err(ConnectionTestError::Auth)
+    } else if let Some(error) = reply.strip_prefix('-') {
+        Err(ConnectionTestError::Other(error.to_string()))
+    } else {
+        Err(ConnectionTestError::Other("Not a Redis server".to_string()))
+    }
+}
+
+fn io_error(e: std::io::Error) -> ConnectionTestError {
+    match e.kind() {
+        std::io::ErrorKind::ConnectionRefused => ConnectionTestError::Refused,
+        std::io::ErrorKind::TimedOut => ConnectionTestError::Timeout,
+        _ => ConnectionTestError::Other(e.to_string()),
+    }
+}
+
+fn sqlx_error(e: sqlx::Error) -> ConnectionTestError {
+    match e {
+        sqlx::Error::Io(e) => io_error(e),
+        // SQLSTATE class 28 is "invalid authorization specification" in both
+        // PostgreSQL and MySQL
+        sqlx::Error::Database(e) if e.code().is_some_and(|code| code.starts_with("28")) => ConnectionTestError::Auth,
+        sqlx::Error::Database(e) => ConnectionTestError::Other(e.message().to_string()),
+        sqlx::Error::Tls(e) => ConnectionTestError::Other(format!("TLS error: {}", e)),
+        e => ConnectionTestError::Other(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    fn redis_secret(port: u16, password: &str) -> SecretData {
+        SecretData::ConnectionString {
+            database_type: DatabaseType::Redis,
+            host: "127.0.0.1".to_string(),
+            port: Some(port),
+            database: "0".to_string(),
+            username: String::new(),
+            password: password.to_string(),
+            connection_string: String::new(),
+            ssl_config: None,
+        }
+    }
+
+    /// A one-shot server that answers each command with the next reply.
+    async fn fake_redis(replies: &'static [&'static str]) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 256];
+            for reply in replies {
+                if socket.read(&mut buffer).await.unwrap_or(0) == 0 {
+                    return;
+                }
+                socket.write_all(reply.as_bytes()).await.unwrap();
+            }
+            // Keep the connection open so that a missing reply times out
+            while socket.read(&mut buffer).await.unwrap_or(0) > 0 {}
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_redis_ping_and_failure_categories() {
+        let port = fake_redis(&["+OK\r\n", "+PONG\r\n"]).await;
+        let latency = EnvConnectionManager::test_connection(&redis_secret(port, "hunter2")).await;
+        assert!(latency.is_ok(), "{:?}", latency);
+
+        let port = fake_redis(&["-WRONGPASS invalid username-password pair\r\n"]).await;
+        let failed = EnvConnectionManager::test_connection(&redis_secret(port, "hunter2")).await.unwrap_err();
+        assert_eq!(failed, ConnectionTestError::Auth);
+        assert!(!failed.to_string().contains("hunter2"));
+
+        let port = fake_redis(&[]).await;
+        let silent = EnvConnectionManager::test_connection_with_timeout(
+            &redis_secret(port, ""),
+            Duration::from_millis(200),
+        )
+        .await;
+        assert_eq!(silent, Err(ConnectionTestError::Timeout));
+
+        // Bind and drop a listener to find a port nothing listens on
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let refused = EnvConnectionManager::test_connection(&redis_secret(closed, "")).await;
+        assert_eq!(refused, Err(ConnectionTestError::Refused));
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_and_unresolvable_targets() {
+        let mut mongo = redis_secret(27017, "");
+        if let SecretData::ConnectionString { database_type, .. } = &mut mongo {
+            *database_type = DatabaseType::MongoDB;
+        }
+        assert_eq!(
+            EnvConnectionManager::test_connection(&mongo).await,
+            Err(ConnectionTestError::Unsupported(DatabaseType::MongoDB))
+        );
+
+        let mut unknown_host = redis_secret(6379, "");
+        if let SecretData::ConnectionString { host, .. } = &mut unknown_host {
+            *host = "db.nonexistent.invalid".to_string();
+        }
+        assert_eq!(EnvConnectionManager::test_connection(&unknown_host).await, Err(ConnectionTestError::Dns));
+    }
+}
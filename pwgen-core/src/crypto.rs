@@ -4,33 +4,127 @@ use aes_gcm::{
 };
 use argon2::{
     password_hash::{rand_core::RngCore, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
+    Algorithm, Argon2, Params, Version,
 };
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+use rand::Rng;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use crate::{Error, Result};
+use crate::{generator::WORD_LIST, models::KeyFileInfo, Error, Result};
 
 const NONCE_SIZE: usize = 12;
 const SALT_SIZE: usize = 32;
+const KEY_FILE_SIZE: usize = 64;
+const KEY_FILE_INFO: &[u8] = b"pwgen key file v1";
+const AUTHENTICATION_INFO: &[u8] = b"pwgen authentication v1";
+const CACHE_NAMING_INFO: &[u8] = b"pwgen cache names v1";
 
 #[derive(Zeroize, ZeroizeOnDrop)]
 pub struct MasterKey {
     key: Vec<u8>,
 }
 
+/// Argon2id cost settings for deriving a key from a password. A vault keeps
+/// the ones it was created with in the PHC string of its password hash, see
+/// `from_password_hash`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Argon2Params {
+    /// Memory in KiB
+    pub mem_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    /// The argon2 crate's defaults, which every vault used before the
+    /// parameters became configurable.
+    fn default() -> Self {
+        Self {
+            mem_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Argon2Params {
+    /// Upper bound for `benchmark`, so a fast machine doesn't pick settings
+    /// a slower one can't unlock with.
+    const MAX_BENCHMARK_MEM_KIB: u32 = 1024 * 1024;
+    
+    /// The parameters encoded in a PHC password hash.
+    pub fn from_password_hash(password_hash: &str) -> Result<Self> {
+        let params = KdfParams::from_password_hash(password_hash)?;
+        Ok(Self { mem_kib: params.memory_kib, iterations: params.iterations, parallelism: params.parallelism })
+    }
+    
+    /// Whether any cost is below the one in `target`.
+    pub fn is_weaker_than(&self, target: &Argon2Params) -> bool {
+        self.mem_kib < target.mem_kib || self.iterations < target.iterations || self.parallelism < target.parallelism
+    }
+    
+    /// The larger of each cost in `self` and `other`, so that raising one
+    /// setting never lowers another.
+    pub fn max(&self, other: &Argon2Params) -> Self {
+        Self {
+            mem_kib: self.mem_kib.max(other.mem_kib),
+            iterations: self.iterations.max(other.iterations),
+            parallelism: self.parallelism.max(other.parallelism),
+        }
+    }
+    
+    fn argon2(&self) -> Result<Argon2<'static>> {
+        let params = Params::new(self.mem_kib, self.iterations, self.parallelism, None)?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+    
+    /// How long deriving a key with these parameters takes on this machine.
+    pub fn measure(&self) -> Result<Duration> {
+        let salt = MasterKey::generate_salt();
+        let started = Instant::now();
+        MasterKey::derive_with_params("benchmark password", &salt, self)?;
+        Ok(started.elapsed())
+    }
+    
+    /// Parameters that take about `target` to derive a key on this machine,
+    /// never weaker than the defaults. Memory is raised first, up to 1 GiB,
+    /// then iterations.
+    pub fn benchmark(target: Duration) -> Result<Self> {
+        let mut params = Self::default();
+        let elapsed = params.measure()?;
+        let scale = target.as_secs_f64() / elapsed.as_secs_f64().max(0.001);
+        if scale <= 1.0 {
+            return Ok(params);
+        }
+        
+        let mem_kib = (f64::from(params.mem_kib) * scale).min(f64::from(Self::MAX_BENCHMARK_MEM_KIB)) as u32;
+        // Whole MiB, which is what people type in
+        params.mem_kib = (mem_kib / 1024 * 1024).max(params.mem_kib);
+        let remaining = scale * f64::from(Params::DEFAULT_M_COST) / f64::from(params.mem_kib);
+        params.iterations = ((f64::from(params.iterations) * remaining) as u32).max(params.iterations);
+        Ok(params)
+    }
+}
+
 impl MasterKey {
     pub fn derive_from_password(password: &str, salt: &[u8]) -> Result<Self> {
-        let argon2 = Argon2::default();
-        let salt_string = SaltString::encode_b64(salt)
-            .map_err(|e| Error::Encryption(format!("Invalid salt: {}", e)))?;
-        
-        let password_hash = argon2
-            .hash_password(password.as_bytes(), &salt_string)
-            .map_err(|e| Error::Encryption(format!("Failed to hash password: {}", e)))?;
-        
+        Self::derive_with_params(password, salt, &Argon2Params::default())
+    }
+    
+    pub fn derive_with_params(password: &str, salt: &[u8], params: &Argon2Params) -> Result<Self> {
+        let argon2 = params.argon2()?;
+        let salt_string = SaltString::encode_b64(salt)?;
+        let password_hash = argon2.hash_password(password.as_bytes(), &salt_string)?;
         let hash = password_hash.hash.ok_or_else(|| {
-            Error::Encryption("Failed to extract hash from password".to_string())
+            Error::Crypto("Failed to extract hash from password".to_string())
         })?;
         
         let mut key = vec![0u8; 32];
@@ -41,7 +135,7 @@ impl MasterKey {
     
     pub fn verify_password(password: &str, password_hash: &str) -> Result<bool> {
         let parsed_hash = PasswordHash::new(password_hash)
-            .map_err(|e| Error::Decryption(format!("Invalid password hash: {}", e)))?;
+            .map_err(|e| Error::VaultCorrupt(format!("invalid password hash: {}", e)))?;
         
         let argon2 = Argon2::default();
         Ok(argon2.verify_password(password.as_bytes(), &parsed_hash).is_ok())
@@ -53,6 +147,26 @@ impl MasterKey {
         salt
     }
     
+    /// A random key, for data that is handed to someone else rather than
+    /// protected by a password.
+    pub fn generate() -> Self {
+        let mut key = vec![0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        Self { key }
+    }
+    
+    /// A key from the bytes returned by `as_bytes`.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 32 {
+            return Err(Error::Decryption("Invalid key length".to_string()));
+        }
+        Ok(Self { key: bytes.to_vec() })
+    }
+    
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.key
+    }
+    
     pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
         let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
         let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
@@ -83,15 +197,202 @@ impl MasterKey {
             .map_err(|e| Error::Decryption(format!("Decryption failed: {}", e)))
     }
     
+    /// Mix a key file into this key. The result only matches the vault key
+    /// when both the password and the same key file contents are used.
+    pub fn with_key_file(&self, key_file: &KeyFile) -> Result<Self> {
+        let hkdf = Hkdf::<Sha256>::new(Some(&key_file.digest), &self.key);
+        let mut key = vec![0u8; 32];
+        hkdf.expand(KEY_FILE_INFO, &mut key)
+            .map_err(|e| Error::Crypto(format!("key file derivation failed: {}", e)))?;
+        
+        Ok(Self { key })
+    }
+    
+    /// HMAC-SHA256 over `parts` with a subkey of this key, for authenticating
+    /// data stored next to a ciphertext rather than inside it. Each part is
+    /// length-prefixed so the boundaries between them are authenticated too.
+    pub fn authenticate(&self, parts: &[&[u8]]) -> Result<Vec<u8>> {
+        Ok(self.authenticator(parts)?.finalize().into_bytes().to_vec())
+    }
+    
+    /// Check a tag produced by `authenticate`, in constant time.
+    pub fn verify_authentication(&self, parts: &[&[u8]], tag: &[u8]) -> Result<bool> {
+        Ok(self.authenticator(parts)?.verify_slice(tag).is_ok())
+    }
+    
+    /// A subkey for naming files in local caches after what they hold, e.g.
+    /// site icons after their host, without the names giving it away.
+    pub fn cache_naming_key(&self) -> Result<Vec<u8>> {
+        let mut key = vec![0u8; 32];
+        Hkdf::<Sha256>::new(None, &self.key)
+            .expand(CACHE_NAMING_INFO, &mut key)
+            .map_err(|e| Error::Crypto(format!("cache naming key derivation failed: {}", e)))?;
+        Ok(key)
+    }
+    
+    fn authenticator(&self, parts: &[&[u8]]) -> Result<Hmac<Sha256>> {
+        let mut subkey = [0u8; 32];
+        Hkdf::<Sha256>::new(None, &self.key)
+            .expand(AUTHENTICATION_INFO, &mut subkey)
+            .map_err(|e| Error::Crypto(format!("authentication key derivation failed: {}", e)))?;
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&subkey)
+            .map_err(|e| Error::Crypto(format!("invalid authentication key: {}", e)))?;
+        subkey.zeroize();
+        
+        for part in parts {
+            mac.update(&(part.len() as u64).to_le_bytes());
+            mac.update(part);
+        }
+        Ok(mac)
+    }
+    
     pub fn hash_password_for_storage(password: &str) -> Result<String> {
+        Self::hash_password_with_params(password, &Argon2Params::default())
+    }
+    
+    /// PHC string for `password`, which records `params` alongside the hash.
+    pub fn hash_password_with_params(password: &str, params: &Argon2Params) -> Result<String> {
         let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
+        let argon2 = params.argon2()?;
         
-        let password_hash = argon2
-            .hash_password(password.as_bytes(), &salt)
-            .map_err(|e| Error::Encryption(format!("Failed to hash password: {}", e)))?;
+        Ok(argon2.hash_password(password.as_bytes(), &salt)?.to_string())
+    }
+}
+
+/// Key derivation settings of a vault, read from its stored password hash.
+/// The vault key is derived with the same Argon2 settings.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct KdfParams {
+    /// e.g. `argon2id`
+    pub algorithm: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// Parse the parameters out of a PHC string such as
+    /// `$argon2id$v=19$m=19456,t=2,p=1$...`. No password is needed.
+    pub fn from_password_hash(password_hash: &str) -> Result<Self> {
+        let parsed = PasswordHash::new(password_hash)
+            .map_err(|e| Error::VaultCorrupt(format!("invalid password hash: {}", e)))?;
+        let param = |name: &str| {
+            parsed
+                .params
+                .get_decimal(name)
+                .ok_or_else(|| Error::VaultCorrupt(format!("password hash has no '{}' parameter", name)))
+        };
+        Ok(Self {
+            algorithm: parsed.algorithm.as_str().to_string(),
+            memory_kib: param("m")?,
+            iterations: param("t")?,
+            parallelism: param("p")?,
+        })
+    }
+}
+
+/// A file whose contents are required, in addition to the master password,
+/// to unlock a vault. Any file works; only a digest of it is kept in memory.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct KeyFile {
+    digest: Vec<u8>,
+    #[zeroize(skip)]
+    name: Option<String>,
+}
+
+impl KeyFile {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut contents = fs::read(path)
+            .map_err(|e| Error::Other(format!("Cannot read key file {}: {}", path.display(), e)))?;
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned());
+        let key_file = Self::from_bytes(&contents, name);
+        contents.zeroize();
+        key_file
+    }
+    
+    pub fn from_bytes(contents: &[u8], name: Option<String>) -> Result<Self> {
+        if contents.is_empty() {
+            return Err(Error::Other("Key file is empty".to_string()));
+        }
+        Ok(Self { digest: Sha256::digest(contents).to_vec(), name })
+    }
+    
+    /// Write a new key file of random bytes to `path`, which must not exist.
+    pub fn generate<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut contents = vec![0u8; KEY_FILE_SIZE];
+        OsRng.fill_bytes(&mut contents);
         
-        Ok(password_hash.to_string())
+        let mut file = fs::OpenOptions::new().write(true).create_new(true).open(path)?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned());
+        let key_file = Self::from_bytes(&contents, name);
+        contents.zeroize();
+        key_file
+    }
+    
+    /// Identifier stored in the vault header. It is derived from the file
+    /// contents but can't be used to recover them or the vault key.
+    pub fn info(&self) -> KeyFileInfo {
+        let mut hasher = Sha256::new();
+        hasher.update(b"pwgen key file id");
+        hasher.update(&self.digest);
+        KeyFileInfo {
+            id: hex::encode(&hasher.finalize()[..8]),
+            name: self.name.clone(),
+        }
+    }
+}
+
+/// Number of words in a recovery key.
+pub const RECOVERY_KEY_WORDS: usize = 24;
+
+/// A phrase of random words that unlocks a vault in place of its master
+/// password, see `Storage::enable_recovery_key`. The words come from the
+/// built-in list of 160 common words, so 24 of them carry about 175 bits of
+/// entropy.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct RecoveryKey {
+    phrase: String,
+}
+
+impl RecoveryKey {
+    pub fn generate() -> Self {
+        let mut rng = rand::rngs::OsRng;
+        let words: Vec<&str> = (0..RECOVERY_KEY_WORDS)
+            .map(|_| WORD_LIST[rng.gen_range(0..WORD_LIST.len())])
+            .collect();
+        Self { phrase: words.join(" ") }
+    }
+    
+    /// Read a recovery key as typed by the user. Case and the whitespace
+    /// between words don't matter.
+    pub fn parse(input: &str) -> Result<Self> {
+        let words: Vec<String> = input.split_whitespace().map(str::to_lowercase).collect();
+        if words.len() != RECOVERY_KEY_WORDS {
+            return Err(Error::Other(format!(
+                "A recovery key has {} words; {} were given",
+                RECOVERY_KEY_WORDS,
+                words.len()
+            )));
+        }
+        if let Some(unknown) = words.iter().find(|word| !WORD_LIST.contains(&word.as_str())) {
+            return Err(Error::Other(format!("'{}' is not a recovery key word", unknown)));
+        }
+        Ok(Self { phrase: words.join(" ") })
+    }
+    
+    /// The words, separated by single spaces.
+    pub fn phrase(&self) -> &str {
+        &self.phrase
+    }
+    
+    /// The key that wraps the vault key, derived like a master password key.
+    pub(crate) fn wrapping_key(&self, salt: &[u8]) -> Result<MasterKey> {
+        MasterKey::derive_from_password(&self.phrase, salt)
     }
 }
 
@@ -128,4 +429,66 @@ mod tests {
         assert!(MasterKey::verify_password(password, &hash).unwrap());
         assert!(!MasterKey::verify_password("wrong_password", &hash).unwrap());
     }
+    
+    #[test]
+    fn test_kdf_params_from_password_hash() {
+        let hash = MasterKey::hash_password_for_storage("secure_password123!").unwrap();
+        let params = KdfParams::from_password_hash(&hash).unwrap();
+        let defaults = argon2::Params::default();
+        
+        assert_eq!(params.algorithm, "argon2id");
+        assert_eq!(params.memory_kib, defaults.m_cost());
+        assert_eq!(params.iterations, defaults.t_cost());
+        assert_eq!(params.parallelism, defaults.p_cost());
+        assert!(KdfParams::from_password_hash("not a hash").is_err());
+    }
+    
+    #[test]
+    fn test_argon2_params_recorded_in_hash() {
+        let weak = Argon2Params { mem_kib: 1024, iterations: 1, parallelism: 1 };
+        let hash = MasterKey::hash_password_with_params("secure_password123!", &weak).unwrap();
+        
+        assert_eq!(Argon2Params::from_password_hash(&hash).unwrap(), weak);
+        assert!(MasterKey::verify_password("secure_password123!", &hash).unwrap());
+        assert!(weak.is_weaker_than(&Argon2Params::default()));
+        assert!(!Argon2Params::default().is_weaker_than(&weak));
+        
+        let mixed = Argon2Params { mem_kib: 65536, iterations: 1, parallelism: 1 };
+        assert!(mixed.is_weaker_than(&Argon2Params::default()));
+        assert_eq!(mixed.max(&Argon2Params::default()), Argon2Params { mem_kib: 65536, ..Argon2Params::default() });
+        assert!(Argon2Params { mem_kib: 0, ..weak }.measure().is_err());
+    }
+    
+    #[test]
+    fn test_recovery_key_parse() {
+        let key = RecoveryKey::generate();
+        assert_eq!(key.phrase().split(' ').count(), RECOVERY_KEY_WORDS);
+        
+        let typed = format!("  {}\n", key.phrase().to_uppercase().replace(' ', "   "));
+        assert_eq!(RecoveryKey::parse(&typed).unwrap().phrase(), key.phrase());
+        
+        let words: Vec<&str> = key.phrase().split(' ').collect();
+        assert!(RecoveryKey::parse(&words[1..].join(" ")).is_err());
+        let misspelt = format!("{} zzzz", words[1..].join(" "));
+        assert!(RecoveryKey::parse(&misspelt).is_err());
+    }
+    
+    #[test]
+    fn test_key_file_changes_derived_key() {
+        let salt = MasterKey::generate_salt();
+        let master_key = MasterKey::derive_from_password("password", &salt).unwrap();
+        let key_file = KeyFile::from_bytes(b"key file contents", Some("usb.key".to_string())).unwrap();
+        
+        let mixed = master_key.with_key_file(&key_file).unwrap();
+        let encrypted = mixed.encrypt(b"secret").unwrap();
+        assert!(master_key.decrypt(&encrypted).is_err());
+        
+        let other = KeyFile::from_bytes(b"other contents", None).unwrap();
+        assert!(master_key.with_key_file(&other).unwrap().decrypt(&encrypted).is_err());
+        
+        let again = KeyFile::from_bytes(b"key file contents", None).unwrap();
+        assert_eq!(master_key.with_key_file(&again).unwrap().decrypt(&encrypted).unwrap(), b"secret");
+        assert_eq!(key_file.info().id, again.info().id);
+        assert_ne!(key_file.info().id, other.info().id);
+    }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use std::path::Path;
 use std::fs;
+use std::io::Read;
 use sha2::{Sha256, Digest};
 use serde::{Deserialize, Serialize};
 
@@ -67,16 +68,53 @@ pub enum CompressionType {
     Deflate,
 }
 
+/// Largest file, in bytes, imported as a document unless a different limit
+/// is given in `DocumentImportOptions`.
+pub const DEFAULT_MAX_DOCUMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Bytes read from a file at a time while importing it.
+const IMPORT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How `DocumentManager::import_file_with` reads a file.
+#[derive(Debug, Clone)]
+pub struct DocumentImportOptions {
+    /// Gzip the content, keeping it compressed only if that makes it smaller
+    pub compress: bool,
+    /// Files larger than this many bytes are refused
+    pub max_size: u64,
+}
+
+impl Default for DocumentImportOptions {
+    fn default() -> Self {
+        Self { compress: false, max_size: DEFAULT_MAX_DOCUMENT_SIZE }
+    }
+}
+
 /// Document storage manager
 pub struct DocumentManager;
 
 impl DocumentManager {
-    /// Import a file as a secure document
+    /// Import a file as a secure document, refusing files larger than
+    /// `DEFAULT_MAX_DOCUMENT_SIZE`
     pub fn import_file<P: AsRef<Path>>(
         file_path: P,
         document_type: DocumentType,
         _description: Option<String>,
         compress: bool,
+    ) -> Result<(SecretData, DocumentInfo)> {
+        let options = DocumentImportOptions { compress, ..DocumentImportOptions::default() };
+        Self::import_file_with(file_path, document_type, &options, |_, _| {})
+    }
+    
+    /// Import a file as a secure document, reading it in chunks. Each chunk
+    /// is hashed and, when compressing, gzipped as it is read, so the file is
+    /// never held twice. `progress` is called after each chunk with the bytes
+    /// read so far and the file size.
+    pub fn import_file_with<P: AsRef<Path>, F: FnMut(u64, u64)>(
+        file_path: P,
+        document_type: DocumentType,
+        options: &DocumentImportOptions,
+        mut progress: F,
     ) -> Result<(SecretData, DocumentInfo)> {
         let path = file_path.as_ref();
         
@@ -88,8 +126,15 @@ impl DocumentManager {
             return Err(Error::Other(format!("Path is not a file: {}", path.display())));
         }
         
-        // Read file content
-        let content = fs::read(path)
+        // Get file metadata
+        let metadata = fs::metadata(path)
+            .map_err(|e| Error::Other(format!("Failed to read file metadata: {}", e)))?;
+        let file_size = metadata.len();
+        if file_size > options.max_size {
+            return Err(Error::DocumentTooLarge { size: file_size, limit: options.max_size });
+        }
+        
+        let mut file = fs::File::open(path)
             .map_err(|e| Error::Other(format!("Failed to read file: {}", e)))?;
         
         let filename = path.file_name()
@@ -97,34 +142,54 @@ impl DocumentManager {
             .unwrap_or("unknown")
             .to_string();
         
-        // Calculate checksum
-        let checksum = Self::calculate_checksum(&content);
+        // Compress if requested and likely to be worth it
+        let compress = options.compress && file_size > 1024;
+        let mut sink = ContentSink::new(compress, file_size as usize);
+        let mut hasher = Sha256::new();
+        let mut head = Vec::new();
+        let mut chunk = vec![0u8; IMPORT_CHUNK_SIZE];
+        let mut read = 0u64;
+        loop {
+            let n = file.read(&mut chunk)
+                .map_err(|e| Error::Other(format!("Failed to read file: {}", e)))?;
+            if n == 0 {
+                break;
+            }
+            read += n as u64;
+            // The file may have grown since its size was checked
+            if read > options.max_size {
+                return Err(Error::DocumentTooLarge { size: read, limit: options.max_size });
+            }
+            hasher.update(&chunk[..n]);
+            if head.is_empty() {
+                head = chunk[..n].to_vec();
+            }
+            sink.write(&chunk[..n])?;
+            progress(read, file_size.max(read));
+        }
         
-        // Determine content type
-        let content_type = Self::detect_content_type(&content, &filename);
+        let checksum = format!("{:x}", hasher.finalize());
         
-        // Determine encoding
-        let encoding = if Self::is_text_content(&content) {
+        // Content type and encoding come from the start of the file; a
+        // character cut off at the end of it is left out
+        let sample = if (head.len() as u64) < read { Self::text_sample(&head) } else { &head[..] };
+        let content_type = Self::detect_content_type(sample, &filename);
+        let encoding = if Self::is_text_content(sample) {
             DocumentEncoding::Text
         } else {
             DocumentEncoding::Binary
         };
         
-        // Compress if requested and beneficial
-        let (final_content, compression) = if compress && content.len() > 1024 {
-            match Self::compress_content(&content) {
-                Ok(compressed) if compressed.len() < content.len() => {
-                    (compressed, Some(CompressionType::Gzip))
-                }
-                _ => (content, None),
+        let (mut final_content, mut compression) = sink.finish()?;
+        if compression != CompressionType::None && final_content.len() as u64 >= read {
+            // Compression didn't help; store the file as it is
+            final_content = fs::read(path)
+                .map_err(|e| Error::Other(format!("Failed to read file: {}", e)))?;
+            if Self::calculate_checksum(&final_content) != checksum {
+                return Err(Error::Other("The file changed while it was being imported".to_string()));
             }
-        } else {
-            (content, None)
-        };
-        
-        // Get file metadata
-        let metadata = fs::metadata(path)
-            .map_err(|e| Error::Other(format!("Failed to read file metadata: {}", e)))?;
+            compression = CompressionType::None;
+        }
         
         let last_modified = metadata.modified()
             .ok()
@@ -136,11 +201,11 @@ impl DocumentManager {
         let doc_info = DocumentInfo {
             filename: filename.clone(),
             content_type,
-            file_size: metadata.len(),
+            file_size: read,
             checksum_sha256: checksum,
             document_type,
             encoding,
-            compression,
+            compression: (compression != CompressionType::None).then_some(compression.clone()),
             created_at: chrono::Utc::now(),
             last_modified,
         };
@@ -151,6 +216,7 @@ impl DocumentManager {
             content_type: doc_info.content_type.clone(),
             content: final_content,
             checksum: doc_info.checksum_sha256.clone(),
+            compression: Some(compression),
         };
         
         Ok((secret_data, doc_info))
@@ -162,9 +228,8 @@ impl DocumentManager {
         output_path: &Path,
         verify_checksum: bool,
     ) -> Result<()> {
-        if let SecretData::Document { filename: _, content_type: _, content, checksum } = secret_data {
-            // Decompress if the stored bytes are gzip-compressed (see import_file)
-            let data = Self::maybe_decompress(content)?;
+        if let SecretData::Document { content, checksum, compression, .. } = secret_data {
+            let data = Self::decode_content(content, compression.as_ref())?;
 
             // Verify checksum if requested (checksum is over the original content)
             if verify_checksum {
@@ -193,9 +258,12 @@ impl DocumentManager {
     
     /// Get document information from secret data
     pub fn get_document_info(secret_data: &SecretData) -> Result<DocumentInfo> {
-        if let SecretData::Document { filename, content_type, content, checksum } = secret_data {
-            let compressed = Self::is_gzip(content);
-            let data = Self::maybe_decompress(content)?;
+        if let SecretData::Document { filename, content_type, content, checksum, compression } = secret_data {
+            let compressed = match compression {
+                Some(compression) => *compression != CompressionType::None,
+                None => Self::is_gzip(content),
+            };
+            let data = Self::decode_content(content, compression.as_ref())?;
             let file_size = data.len() as u64;
             let encoding = if Self::is_text_content(&data) {
                 DocumentEncoding::Text
@@ -221,8 +289,8 @@ impl DocumentManager {
     
     /// Verify document integrity
     pub fn verify_document(secret_data: &SecretData) -> Result<bool> {
-        if let SecretData::Document { content, checksum, .. } = secret_data {
-            let data = Self::maybe_decompress(content)?;
+        if let SecretData::Document { content, checksum, compression, .. } = secret_data {
+            let data = Self::decode_content(content, compression.as_ref())?;
             let calculated_checksum = Self::calculate_checksum(&data);
             Ok(calculated_checksum == *checksum)
         } else {
@@ -245,14 +313,26 @@ impl DocumentManager {
             content_type,
             content,
             checksum,
+            compression: Some(CompressionType::None),
         })
     }
     
+    /// The document's original bytes, decompressed if they were stored
+    /// compressed
+    pub fn read_content(secret_data: &SecretData) -> Result<Vec<u8>> {
+        if let SecretData::Document { content, compression, .. } = secret_data {
+            Self::decode_content(content, compression.as_ref())
+        } else {
+            Err(Error::Other("Secret is not a document".to_string()))
+        }
+    }
+    
     /// Extract text content from document (if it's text)
     pub fn extract_text_content(secret_data: &SecretData) -> Result<String> {
-        if let SecretData::Document { content, .. } = secret_data {
-            if Self::is_text_content(content) {
-                String::from_utf8(content.clone())
+        if let SecretData::Document { .. } = secret_data {
+            let content = Self::read_content(secret_data)?;
+            if Self::is_text_content(&content) {
+                String::from_utf8(content)
                     .map_err(|e| Error::Other(format!("Failed to decode text: {}", e)))
             } else {
                 Err(Error::Other("Document is not text content".to_string()))
@@ -325,8 +405,9 @@ impl DocumentManager {
         hex::encode(result)
     }
     
-    fn detect_content_type(content: &[u8], filename: &str) -> String {
-        // Simple content type detection based on file extension and magic bytes
+    /// MIME type of a file from its extension, or else its first bytes;
+    /// `application/octet-stream` when neither is recognised.
+    pub fn detect_content_type(content: &[u8], filename: &str) -> String {
         let extension = Path::new(filename)
             .extension()
             .and_then(|ext| ext.to_str())
@@ -412,27 +493,8 @@ impl DocumentManager {
         ratio > 0.7 // If more than 70% are printable, consider it text
     }
     
-    #[cfg(feature = "document-compression")]
-    fn compress_content(content: &[u8]) -> Result<Vec<u8>> {
-        use std::io::Write;
-        
-        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
-        encoder.write_all(content)
-            .map_err(|e| Error::Other(format!("Compression failed: {}", e)))?;
-        
-        encoder.finish()
-            .map_err(|e| Error::Other(format!("Compression failed: {}", e)))
-    }
-    
-    #[cfg(not(feature = "document-compression"))]
-    fn compress_content(content: &[u8]) -> Result<Vec<u8>> {
-        Ok(content.to_vec())
-    }
-    
     #[cfg(feature = "document-compression")]
     fn decompress_content(compressed: &[u8]) -> Result<Vec<u8>> {
-        use std::io::Read;
-        
         let mut decoder = flate2::read::GzDecoder::new(compressed);
         let mut decompressed = Vec::new();
         decoder.read_to_end(&mut decompressed)
@@ -452,8 +514,7 @@ impl DocumentManager {
     }
 
     /// Decompress content if it is gzip-compressed, otherwise return it unchanged.
-    /// This lets document content be self-describing so callers don't need to
-    /// track the compression flag separately.
+    /// Only for documents saved before their compression was recorded.
     fn maybe_decompress(content: &[u8]) -> Result<Vec<u8>> {
         if Self::is_gzip(content) {
             Self::decompress_content(content)
@@ -461,6 +522,67 @@ impl DocumentManager {
             Ok(content.to_vec())
         }
     }
+    
+    /// The original bytes of stored `content`.
+    fn decode_content(content: &[u8], compression: Option<&CompressionType>) -> Result<Vec<u8>> {
+        match compression {
+            Some(CompressionType::None) => Ok(content.to_vec()),
+            Some(CompressionType::Gzip) => Self::decompress_content(content),
+            Some(other) => Err(Error::Other(format!("Unsupported document compression: {:?}", other))),
+            None => Self::maybe_decompress(content),
+        }
+    }
+    
+    /// `head` without a UTF-8 character cut off at its end.
+    fn text_sample(head: &[u8]) -> &[u8] {
+        match std::str::from_utf8(head) {
+            Err(e) if e.error_len().is_none() => &head[..e.valid_up_to()],
+            _ => head,
+        }
+    }
+}
+
+/// Where `import_file_with` puts the bytes it reads.
+enum ContentSink {
+    Plain(Vec<u8>),
+    #[cfg(feature = "document-compression")]
+    Gzip(flate2::write::GzEncoder<Vec<u8>>),
+}
+
+impl ContentSink {
+    #[cfg_attr(not(feature = "document-compression"), allow(unused_variables))]
+    fn new(compress: bool, capacity: usize) -> Self {
+        #[cfg(feature = "document-compression")]
+        if compress {
+            return Self::Gzip(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        }
+        Self::Plain(Vec::with_capacity(capacity))
+    }
+    
+    fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        match self {
+            Self::Plain(content) => content.extend_from_slice(chunk),
+            #[cfg(feature = "document-compression")]
+            Self::Gzip(encoder) => {
+                use std::io::Write;
+                encoder.write_all(chunk)
+                    .map_err(|e| Error::Other(format!("Compression failed: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+    
+    fn finish(self) -> Result<(Vec<u8>, CompressionType)> {
+        match self {
+            Self::Plain(content) => Ok((content, CompressionType::None)),
+            #[cfg(feature = "document-compression")]
+            Self::Gzip(encoder) => {
+                let compressed = encoder.finish()
+                    .map_err(|e| Error::Other(format!("Compression failed: {}", e)))?;
+                Ok((compressed, CompressionType::Gzip))
+            }
+        }
+    }
 }
 
 /// Document attachment utilities
@@ -604,10 +726,59 @@ mod tests {
         // Reported size and checksum reflect the original (decompressed) content.
         assert_eq!(info.file_size, original.len() as u64);
         assert!(DocumentManager::verify_document(&secret_data).unwrap());
+        assert_eq!(DocumentManager::read_content(&secret_data).unwrap(), original);
+        assert_eq!(DocumentManager::extract_text_content(&secret_data).unwrap().len(), original.len());
 
         // Export transparently decompresses back to the exact original bytes.
         let out = dir.path().join("note-exported.txt");
         DocumentManager::export_document(&secret_data, &out, true).unwrap();
         assert_eq!(std::fs::read(&out).unwrap(), original);
     }
+
+    #[test]
+    fn test_import_limits_size_and_reports_progress() {
+        let original: Vec<u8> = (0..200 * 1024).map(|i| (i % 251) as u8).collect();
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("blob.bin");
+        std::fs::write(&src, &original).unwrap();
+
+        let small = DocumentImportOptions { compress: false, max_size: 100 * 1024 };
+        assert!(matches!(
+            DocumentManager::import_file_with(&src, DocumentType::Document, &small, |_, _| {}),
+            Err(Error::DocumentTooLarge { size, limit }) if size == original.len() as u64 && limit == 100 * 1024
+        ));
+
+        for compress in [false, true] {
+            let options = DocumentImportOptions { compress, ..DocumentImportOptions::default() };
+            let mut calls = Vec::new();
+            let (secret_data, info) = DocumentManager::import_file_with(
+                &src,
+                DocumentType::Document,
+                &options,
+                |read, total| calls.push((read, total)),
+            )
+            .unwrap();
+            assert!(calls.len() > 1);
+            assert_eq!(calls.last(), Some(&(original.len() as u64, original.len() as u64)));
+            assert_eq!(info.file_size, original.len() as u64);
+            assert_eq!(DocumentManager::read_content(&secret_data).unwrap(), original);
+            assert!(DocumentManager::verify_document(&secret_data).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_gzip_file_stored_uncompressed_exports_unchanged() {
+        // A .gz file imported without compression must not be unpacked on export
+        let mut original = vec![0x1f, 0x8b, 0x08, 0x00];
+        original.extend_from_slice(b"not really a gzip stream");
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("archive.gz");
+        std::fs::write(&src, &original).unwrap();
+
+        let (secret_data, info) = DocumentManager::import_file(&src, DocumentType::Archive, None, false).unwrap();
+        assert_eq!(info.compression, None);
+        let out = dir.path().join("archive-exported.gz");
+        DocumentManager::export_document(&secret_data, &out, true).unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), original);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,277 @@
+//! Organisation-wide validation rules for password entries.
+//!
+//! Rules live in `pwgen/entry-rules.toml` under the user's config directory,
+//! so an administrator can ship one file with every installation. Without the
+//! file nothing is enforced. Example:
+//!
+//! ```toml
+//! required = ["username", "tags"]
+//! site_must_be_url = true
+//! allowed_tags = ["work", "personal", "finance"]
+//!
+//! [[rules]]
+//! field_name = "username"
+//! rule_type = { Pattern = "^[^@]+@example\\.com$" }
+//! message = "Use your example.com address as the username"
+//! ```
+//!
+//! `rules` are the same `ValidationRule`s that secret templates use, applied
+//! to the `site`, `username`, `password`, `notes` and `tags` fields. Empty
+//! fields count as missing, and `tags` is matched as a comma-separated list.
+//!
+//! Tags can also be rewritten on save, before the rules are checked:
+//!
+//! ```toml
+//! canonicalize_tags = true
+//!
+//! [tag_aliases]
+//! e-mail = "email"
+//! mail = "email"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, fs, path::PathBuf};
+
+use crate::{models::DecryptedPasswordEntry, secret_templates::ValidationRule, Error, Result};
+
+/// An entry field that can be required.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryField {
+    Site,
+    Username,
+    Password,
+    Notes,
+    Tags,
+}
+
+impl EntryField {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntryField::Site => "site",
+            EntryField::Username => "username",
+            EntryField::Password => "password",
+            EntryField::Notes => "notes",
+            EntryField::Tags => "tags",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntryRules {
+    /// Fields that must not be empty.
+    #[serde(default)]
+    pub required: Vec<EntryField>,
+    /// `site` must be an http(s) URL with a host.
+    #[serde(default)]
+    pub site_must_be_url: bool,
+    /// When not empty, every tag must be one of these.
+    #[serde(default)]
+    pub allowed_tags: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<ValidationRule>,
+    /// Lowercase tags on save.
+    #[serde(default)]
+    pub canonicalize_tags: bool,
+    /// Tags replaced on save, e.g. `e-mail = "email"`, matched ignoring case.
+    #[serde(default)]
+    pub tag_aliases: HashMap<String, String>,
+}
+
+/// A rule an entry breaks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleViolation {
+    /// Name of the offending field, e.g. `username`.
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl EntryRules {
+    /// `pwgen/entry-rules.toml` under the user's config directory.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("pwgen").join("entry-rules.toml"))
+    }
+
+    /// Load the configured rules. A missing file means no rules; a file that
+    /// cannot be parsed is an error, so that a broken policy is noticed.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(toml) => Self::from_toml(&toml)
+                .map_err(|e| Error::Other(format!("Invalid entry rules in {}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(|e| Error::Other(e.to_string()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.required.is_empty()
+            && !self.site_must_be_url
+            && self.allowed_tags.is_empty()
+            && self.rules.is_empty()
+            && !self.canonicalize_tags
+            && self.tag_aliases.is_empty()
+    }
+
+    /// `tags` trimmed, lowercased when `canonicalize_tags` is set, with
+    /// aliases replaced, and without empty or duplicate tags.
+    pub fn canonical_tags(&self, tags: &[String]) -> Vec<String> {
+        let mut canonical: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let mut tag = tag.trim().to_string();
+            if self.canonicalize_tags {
+                tag = tag.to_lowercase();
+            }
+            if let Some((_, target)) = self.tag_aliases.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(&tag)) {
+                tag = target.clone();
+            }
+            if !tag.is_empty() && !canonical.contains(&tag) {
+                canonical.push(tag);
+            }
+        }
+        canonical
+    }
+
+    /// Every rule `entry` breaks, in the order the rules are configured.
+    pub fn validate(&self, entry: &DecryptedPasswordEntry) -> Vec<RuleViolation> {
+        let mut violations = Vec::new();
+        let mut violation = |field: &str, message: String| {
+            violations.push(RuleViolation { field: field.to_string(), message });
+        };
+
+        for field in &self.required {
+            if field_value(entry, field.name()).is_none() {
+                violation(field.name(), "This field is required".to_string());
+            }
+        }
+
+        if self.site_must_be_url && !is_web_url(&entry.site) {
+            violation("site", "Must be a full URL such as https://example.com".to_string());
+        }
+
+        if !self.allowed_tags.is_empty() {
+            for tag in entry.tags.iter().filter(|tag| !self.allowed_tags.contains(tag)) {
+                violation(
+                    "tags",
+                    format!("'{}' is not an allowed tag (allowed: {})", tag, self.allowed_tags.join(", ")),
+                );
+            }
+        }
+
+        for rule in &self.rules {
+            let value = field_value(entry, &rule.field_name);
+            if !rule.accepts(value.as_deref(), |field| field_value(entry, field).is_some()) {
+                violation(&rule.field_name, rule.message.clone());
+            }
+        }
+
+        violations
+    }
+}
+
+fn field_value(entry: &DecryptedPasswordEntry, field: &str) -> Option<String> {
+    let value = match field {
+        "site" => entry.site.clone(),
+        "username" => entry.username.clone(),
+        "password" => entry.password.clone(),
+        "notes" => entry.notes.clone().unwrap_or_default(),
+        "tags" => entry.tags.join(", "),
+        _ => String::new(),
+    };
+    (!value.trim().is_empty()).then_some(value)
+}
+
+fn is_web_url(site: &str) -> bool {
+    url::Url::parse(site)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some_and(|h| !h.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn entry(site: &str, username: &str, tags: &[&str]) -> DecryptedPasswordEntry {
+        DecryptedPasswordEntry {
+            id: "entry-1".to_string(),
+            site: site.to_string(),
+            username: username.to_string(),
+            password: "correct horse".to_string(),
+            notes: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_used: None,
+            password_changed_at: Utc::now(),
+            favorite: false,
+            urls: vec![],
+            created_by: None,
+            modified_by: None,
+            totp_secret: None,
+            password_history: Vec::new(),
+            require_reauth: false,
+            password_policy: None,
+        }
+    }
+
+    #[test]
+    fn test_entry_rules_from_toml() {
+        let rules = EntryRules::from_toml(
+            r#"
+            required = ["tags", "notes"]
+            site_must_be_url = true
+            allowed_tags = ["work", "personal"]
+
+            [[rules]]
+            field_name = "username"
+            rule_type = { Pattern = "^[^@]+@example\\.com$" }
+            message = "use your example.com address"
+            "#,
+        )
+        .unwrap();
+
+        let fields = |entry: &DecryptedPasswordEntry| {
+            rules.validate(entry).into_iter().map(|v| v.field).collect::<Vec<_>>()
+        };
+        assert_eq!(fields(&entry("github.com", "me@gmail.com", &[])), ["tags", "notes", "site", "username"]);
+
+        let mut valid = entry("https://github.com/login", "me@example.com", &["work"]);
+        valid.notes = Some("2FA on phone".to_string());
+        assert!(rules.validate(&valid).is_empty());
+
+        valid.tags.push("games".to_string());
+        assert_eq!(rules.validate(&valid)[0].message, "'games' is not an allowed tag (allowed: work, personal)");
+
+        assert!(EntryRules::from_toml("required = [\"email\"]").is_err());
+        assert!(EntryRules::default().validate(&entry("", "", &[])).is_empty());
+    }
+
+    #[test]
+    fn test_canonical_tags() {
+        let rules = EntryRules::from_toml(
+            r#"
+            canonicalize_tags = true
+
+            [tag_aliases]
+            e-mail = "email"
+            "#,
+        )
+        .unwrap();
+        let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(rules.canonical_tags(&tags(&[" Email", "E-Mail", "Work ", ""])), ["email", "work"]);
+        assert_eq!(EntryRules::default().canonical_tags(&tags(&[" Email ", "email"])), ["Email", "email"]);
+    }
+}
@@ -23,9 +23,36 @@ pub enum Error {
     #[error("Invalid master password")]
     InvalidMasterPassword,
     
+    #[error("Invalid recovery key")]
+    InvalidRecoveryKey,
+    
+    #[error("No vault found at {0}")]
+    VaultNotFound(String),
+    
+    #[error("The vault file is damaged or not a PwGen vault: {0}")]
+    VaultCorrupt(String),
+    
+    #[error("The vault is locked")]
+    VaultLocked,
+    
+    #[error("Key derivation failed: {0}")]
+    Crypto(String),
+    
+    #[error("This vault requires its key file: {0}")]
+    KeyFileRequired(String),
+    
+    #[error("Wrong key file; this vault requires {0}")]
+    WrongKeyFile(String),
+    
+    #[error("Backup integrity check failed: {0}")]
+    BackupIntegrity(String),
+    
     #[error("Entry not found: {0}")]
     EntryNotFound(String),
     
+    #[error("The file is {} MB; documents can be at most {} MB", megabytes(.size), megabytes(.limit))]
+    DocumentTooLarge { size: u64, limit: u64 },
+    
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     
@@ -33,4 +60,22 @@ pub enum Error {
     Other(String),
 }
 
+// argon2 is built without its `std` feature, so its errors can't be
+// `#[from]` sources
+impl From<argon2::Error> for Error {
+    fn from(e: argon2::Error) -> Self {
+        Error::Crypto(e.to_string())
+    }
+}
+
+impl From<argon2::password_hash::Error> for Error {
+    fn from(e: argon2::password_hash::Error) -> Self {
+        Error::Crypto(e.to_string())
+    }
+}
+
+fn megabytes(bytes: &u64) -> String {
+    format!("{:.1}", *bytes as f64 / (1024.0 * 1024.0))
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
\ No newline at end of file
@@ -0,0 +1,369 @@
+//! Plaintext exports of the vault, for moving to another password manager or
+//! a browser.
+//!
+//! CSV uses the `name,url,username,password,note` columns Chrome, Edge and
+//! Firefox import. JSON carries every field, including TOTP secrets and
+//! password history. 1Password's 1PIF has one JSON item per line, separated
+//! by a fixed marker line.
+//!
+//! Exports hold every password in the clear. `encrypt_export` wraps one the
+//! way backups are protected: AES-256-GCM under a key derived from a separate
+//! password with Argon2.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+use crate::{
+    backup::entry_value_with_passwords,
+    cards::CardBrand,
+    crypto::MasterKey,
+    models::DecryptedPasswordEntry,
+    secrets::{DecryptedSecretEntry, SecretData},
+    Error, Result,
+};
+
+/// Line that follows every item in a 1PIF file.
+pub const ONE_PIF_SEPARATOR: &str = "***5642bee8-a5ff-11dc-8314-0800200c9a66***";
+
+const ENCRYPTED_EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// Browser-compatible CSV.
+    Csv,
+    Json,
+    /// 1Password Interchange Format.
+    OnePassword,
+}
+
+impl ExportFormat {
+    /// File extension for exports in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::OnePassword => "1pif",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            "1password" | "1pif" => Ok(ExportFormat::OnePassword),
+            _ => Err(Error::Other(format!("Unknown export format: {} (use csv, json or 1password)", s))),
+        }
+    }
+}
+
+/// Write `entries`, and `secrets` if any are given, to `out` in `format`.
+///
+/// CSV has no room for structured secrets, so a secret becomes a row with
+/// its main value (see `SecretData::reveal`) as the password; secrets
+/// without one, such as documents, are left out.
+pub fn write_export<W: Write>(
+    entries: &[DecryptedPasswordEntry],
+    secrets: &[DecryptedSecretEntry],
+    format: ExportFormat,
+    mut out: W,
+) -> Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(["name", "url", "username", "password", "note"])?;
+            for entry in entries {
+                writer.write_record([
+                    entry.site.as_str(),
+                    &entry_url(entry),
+                    &entry.username,
+                    &entry.password,
+                    entry.notes.as_deref().unwrap_or(""),
+                ])?;
+            }
+            for secret in secrets {
+                if let Some(value) = secret.data.reveal() {
+                    writer.write_record([
+                        secret.name.as_str(),
+                        "",
+                        secret_username(&secret.data).unwrap_or(""),
+                        value,
+                        secret.description.as_deref().unwrap_or(""),
+                    ])?;
+                }
+            }
+            writer.flush()?;
+        }
+        ExportFormat::Json => {
+            let entries = entries
+                .iter()
+                .map(entry_value_with_passwords)
+                .collect::<serde_json::Result<Vec<_>>>()?;
+            let mut export = serde_json::json!({
+                "exported_at": Utc::now(),
+                "version": env!("CARGO_PKG_VERSION"),
+                "entries": entries,
+            });
+            if !secrets.is_empty() {
+                export["secrets"] = serde_json::to_value(secrets)?;
+            }
+            serde_json::to_writer_pretty(&mut out, &export)?;
+            out.write_all(b"\n")?;
+        }
+        ExportFormat::OnePassword => {
+            let items = entries.iter().map(entry_1pif).chain(secrets.iter().map(secret_1pif));
+            for item in items {
+                serde_json::to_writer(&mut out, &item)?;
+                writeln!(out, "\n{}", ONE_PIF_SEPARATOR)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The entry's primary URL, or its site when that looks like a domain, as
+/// browsers skip rows without a URL.
+fn entry_url(entry: &DecryptedPasswordEntry) -> String {
+    if let Some(url) = entry.urls.iter().find(|url| url.primary).or(entry.urls.first()) {
+        return url.url.clone();
+    }
+    let site = entry.site.trim();
+    if site.contains("://") {
+        site.to_string()
+    } else if site.contains('.') && !site.contains(char::is_whitespace) {
+        format!("https://{}", site)
+    } else {
+        String::new()
+    }
+}
+
+fn secret_username(data: &SecretData) -> Option<&str> {
+    match data {
+        SecretData::Password { username, .. } | SecretData::ConnectionString { username, .. } => Some(username),
+        SecretData::ApiKey { key_id, .. } => Some(key_id),
+        SecretData::CloudCredentials { access_key, .. } => Some(access_key),
+        SecretData::CreditCard { cardholder, .. } => Some(cardholder),
+        _ => None,
+    }
+}
+
+/// 1PIF identifiers are 32 uppercase hex digits.
+fn pif_uuid(id: &str) -> String {
+    id.replace('-', "").to_uppercase()
+}
+
+fn entry_1pif(entry: &DecryptedPasswordEntry) -> serde_json::Value {
+    let url = entry_url(entry);
+    let mut secure_contents = serde_json::json!({
+        "fields": [
+            { "designation": "username", "name": "username", "type": "T", "value": entry.username },
+            { "designation": "password", "name": "password", "type": "P", "value": entry.password },
+        ],
+        "URLs": entry.urls.iter().map(|url| serde_json::json!({ "url": url.url })).collect::<Vec<_>>(),
+    });
+    if let Some(notes) = &entry.notes {
+        secure_contents["notesPlain"] = notes.as_str().into();
+    }
+    serde_json::json!({
+        "uuid": pif_uuid(&entry.id),
+        "category": "001",
+        "typeName": "webforms.WebForm",
+        "title": entry.site,
+        "location": url,
+        "createdAt": entry.created_at.timestamp(),
+        "updatedAt": entry.updated_at.timestamp(),
+        "openContents": { "tags": entry.tags },
+        "secureContents": secure_contents,
+    })
+}
+
+/// Cards and notes map to 1Password's own categories; everything else
+/// becomes a secure note holding the secret's fields as JSON.
+fn secret_1pif(secret: &DecryptedSecretEntry) -> serde_json::Value {
+    let (category, type_name, secure_contents) = match &secret.data {
+        SecretData::CreditCard { cardholder, number, expiry_month, expiry_year, cvv, issuer, .. } => (
+            "002",
+            "wallet.financial.CreditCard",
+            serde_json::json!({
+                "cardholder": cardholder,
+                "ccnum": number,
+                "cvv": cvv,
+                "expiry_mm": expiry_month,
+                "expiry_yy": expiry_year,
+                "bank": issuer,
+                "type": CardBrand::from_number(number).to_string().to_lowercase(),
+                "notesPlain": secret.description,
+            }),
+        ),
+        SecretData::SecureNote { content, .. } => (
+            "003",
+            "securenotes.SecureNote",
+            serde_json::json!({ "notesPlain": content }),
+        ),
+        data => (
+            "003",
+            "securenotes.SecureNote",
+            serde_json::json!({ "notesPlain": serde_json::to_string_pretty(data).unwrap_or_default() }),
+        ),
+    };
+    serde_json::json!({
+        "uuid": pif_uuid(&secret.id),
+        "category": category,
+        "typeName": type_name,
+        "title": secret.name,
+        "createdAt": secret.created_at.timestamp(),
+        "updatedAt": secret.updated_at.timestamp(),
+        "openContents": { "tags": secret.tags },
+        "secureContents": secure_contents,
+    })
+}
+
+/// An export encrypted with `encrypt_export`.
+#[derive(Serialize, Deserialize)]
+struct EncryptedExport {
+    format_version: u32,
+    salt: Vec<u8>,
+    encrypted_data: Vec<u8>,
+}
+
+/// Encrypt an export with `password`, as backups are encrypted.
+pub fn encrypt_export(plaintext: &[u8], password: &str) -> Result<Vec<u8>> {
+    let salt = MasterKey::generate_salt();
+    let key = MasterKey::derive_from_password(password, &salt)?;
+    let encrypted = EncryptedExport {
+        format_version: ENCRYPTED_EXPORT_VERSION,
+        encrypted_data: key.encrypt(plaintext)?,
+        salt,
+    };
+    Ok(serde_json::to_vec_pretty(&encrypted)?)
+}
+
+/// Decrypt a file written by `encrypt_export`. A wrong password fails with
+/// `Error::Decryption`.
+pub fn decrypt_export(contents: &[u8], password: &str) -> Result<Vec<u8>> {
+    let encrypted: EncryptedExport = serde_json::from_slice(contents)
+        .map_err(|e| Error::Other(format!("Not an encrypted PwGen export: {}", e)))?;
+    if encrypted.format_version > ENCRYPTED_EXPORT_VERSION {
+        return Err(Error::Other(format!(
+            "Encrypted export version {} was written by a newer PwGen",
+            encrypted.format_version
+        )));
+    }
+    let key = MasterKey::derive_from_password(password, &encrypted.salt)?;
+    key.decrypt(&encrypted.encrypted_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browser_import::{BrowserImporter, ImportConfig, ImportFormat};
+    use crate::models::EntryUrl;
+    use crate::secrets::{NoteFormat, SecretMetadata, SecretType};
+
+    fn entry(site: &str, username: &str, password: &str, notes: Option<&str>) -> DecryptedPasswordEntry {
+        DecryptedPasswordEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            site: site.to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            notes: notes.map(str::to_string),
+            tags: vec!["work".to_string()],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_used: None,
+            password_changed_at: Utc::now(),
+            favorite: false,
+            urls: vec![],
+            created_by: None,
+            modified_by: None,
+            totp_secret: None,
+            password_history: Vec::new(),
+            require_reauth: false,
+            password_policy: None,
+        }
+    }
+
+    fn note_secret() -> DecryptedSecretEntry {
+        DecryptedSecretEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: "runbook".to_string(),
+            description: None,
+            secret_type: SecretType::SecureNote,
+            data: SecretData::SecureNote {
+                title: "runbook".to_string(),
+                content: "restart, then pray".to_string(),
+                format: NoteFormat::PlainText,
+            },
+            metadata: SecretMetadata::default(),
+            tags: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_accessed: None,
+            expires_at: None,
+            favorite: false,
+        }
+    }
+
+    fn import(content: &[u8], format: ImportFormat) -> Vec<crate::browser_import::ImportedPassword> {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), content).unwrap();
+        let config = ImportConfig { format, cleanup_urls: false, ..Default::default() };
+        BrowserImporter::import_from_file(file.path(), config).unwrap().0
+    }
+
+    #[test]
+    fn test_csv_export_round_trips_through_browser_import() {
+        let mut github = entry("github.com", "octo", "pa,ss\"word", Some("line one\nline two, with comma"));
+        github.urls = vec![EntryUrl { url: "https://github.com/login".to_string(), primary: true }];
+        let entries = [github, entry("Home router", "admin", "hunter2", None)];
+
+        let mut csv = Vec::new();
+        write_export(&entries, &[note_secret()], ExportFormat::Csv, &mut csv).unwrap();
+        assert!(csv.starts_with(b"name,url,username,password,note\n"));
+
+        let imported = import(&csv, ImportFormat::Csv);
+        // The note secret has a main value, so it is exported as a row too
+        assert_eq!(imported.len(), 3);
+        assert_eq!(imported[0].name, "github.com");
+        assert_eq!(imported[0].url.as_deref(), Some("https://github.com/login"));
+        assert_eq!(imported[0].password.as_deref(), Some("pa,ss\"word"));
+        assert_eq!(imported[0].notes.as_deref(), Some("line one\nline two, with comma"));
+        assert_eq!(imported[1].username.as_deref(), Some("admin"));
+        assert_eq!(imported[1].url, None);
+        assert_eq!(imported[2].password.as_deref(), Some("restart, then pray"));
+    }
+
+    #[test]
+    fn test_1pif_and_json_exports() {
+        let entries = [entry("example.com", "me", "s3cret", Some("note"))];
+
+        let mut pif = Vec::new();
+        write_export(&entries, &[note_secret()], ExportFormat::OnePassword, &mut pif).unwrap();
+        let pif_text = String::from_utf8(pif.clone()).unwrap();
+        assert_eq!(pif_text.matches(ONE_PIF_SEPARATOR).count(), 2);
+        let imported = import(&pif, ImportFormat::OnePasswordPif);
+        // Only logins are imported back
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].username.as_deref(), Some("me"));
+        assert_eq!(imported[0].password.as_deref(), Some("s3cret"));
+        assert_eq!(imported[0].url.as_deref(), Some("https://example.com"));
+
+        let mut json = Vec::new();
+        write_export(&entries, &[], ExportFormat::Json, &mut json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(value["entries"][0]["password"], "s3cret");
+        assert!(value.get("secrets").is_none());
+    }
+
+    #[test]
+    fn test_encrypted_export() {
+        let encrypted = encrypt_export(b"name,url\n", "export password").unwrap();
+        assert!(!encrypted.windows(8).any(|w| w == b"name,url"));
+        assert_eq!(decrypt_export(&encrypted, "export password").unwrap(), b"name,url\n");
+        assert!(matches!(decrypt_export(&encrypted, "wrong password"), Err(Error::Decryption(_))));
+    }
+}
@@ -0,0 +1,167 @@
+//! Editing decrypted note and configuration content in the user's editor.
+//!
+//! The content has to be written to disk in plaintext for the editor to open
+//! it, so the window is kept as small as possible:
+//!
+//! - the file is placed in a fresh directory only the current user can read
+//!   (`0700`, the file itself `0600`), on a memory-backed filesystem where
+//!   one is available (`$XDG_RUNTIME_DIR` or `/dev/shm` on Linux);
+//! - it exists only while the editor runs;
+//! - afterwards every file in the directory, including swap and backup files
+//!   the editor may have left next to it, is overwritten with zeros before
+//!   the directory is removed, even when the editor fails.
+//!
+//! Overwriting is best effort: on copy-on-write or journaling filesystems and
+//! SSDs the old blocks can survive, which is why memory-backed storage is
+//! preferred. Editors that keep swap or undo files elsewhere (e.g. vim's
+//! `directory` and `undodir` settings) are outside PwGen's control.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{secure_delete::shred, Error, Result};
+
+/// Open `content` in the user's editor (`$VISUAL`, then `$EDITOR`, falling
+/// back to `vi`, or `notepad` on Windows) and wait for it to exit.
+/// `extension` (e.g. `"md"`) lets the editor pick syntax highlighting.
+///
+/// Returns the edited content, or `None` if it was left unchanged.
+pub fn edit_in_external_editor(content: &str, extension: &str) -> Result<Option<String>> {
+    edit_with(&editor_command(), content, extension)
+}
+
+/// The editor command line from `$VISUAL` or `$EDITOR`.
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// The directory temporary plaintext files are created under, and whether it
+/// is memory-backed.
+pub fn secure_temp_dir() -> (PathBuf, bool) {
+    #[cfg(target_os = "linux")]
+    {
+        let candidates = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .into_iter()
+            .chain(std::iter::once(PathBuf::from("/dev/shm")));
+        for dir in candidates {
+            if dir.is_dir() {
+                return (dir, true);
+            }
+        }
+    }
+    (std::env::temp_dir(), false)
+}
+
+fn edit_with(editor: &str, content: &str, extension: &str) -> Result<Option<String>> {
+    let workspace = PlaintextDir::create()?;
+    let path = workspace.write_file(&format!("pwgen-edit.{}", extension), content)?;
+
+    let status = editor_process(editor, &path)
+        .status()
+        .map_err(|e| Error::Other(format!("Failed to start editor '{}': {}", editor, e)))?;
+    if !status.success() {
+        return Err(Error::Other(format!("Editor '{}' exited with {}", editor, status)));
+    }
+
+    let edited = fs::read_to_string(&path)?;
+    drop(workspace);
+    Ok((edited != content).then_some(edited))
+}
+
+/// Run the editor command through the shell, as git does, so that values
+/// such as `code --wait` or quoted paths work.
+#[cfg(unix)]
+fn editor_process(editor: &str, path: &Path) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(format!("{} \"$@\"", editor)).arg(editor).arg(path);
+    command
+}
+
+#[cfg(not(unix))]
+fn editor_process(editor: &str, path: &Path) -> Command {
+    let mut parts = editor.split_whitespace();
+    let mut command = Command::new(parts.next().unwrap_or("notepad"));
+    command.args(parts).arg(path);
+    command
+}
+
+/// A private directory for plaintext files, wiped and removed on drop.
+struct PlaintextDir {
+    path: PathBuf,
+}
+
+impl PlaintextDir {
+    fn create() -> Result<Self> {
+        let (base, _) = secure_temp_dir();
+        let path = base.join(format!("pwgen-{}", uuid::Uuid::new_v4()));
+        let mut builder = fs::DirBuilder::new();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(&path)?;
+        Ok(Self { path })
+    }
+
+    fn write_file(&self, name: &str, content: &str) -> Result<PathBuf> {
+        let path = self.path.join(name);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        Ok(path)
+    }
+}
+
+impl Drop for PlaintextDir {
+    fn drop(&mut self) {
+        if let Ok(entries) = fs::read_dir(&self.path) {
+            for entry in entries.flatten() {
+                let _ = shred(&entry.path());
+            }
+        }
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_with_replaces_content_and_cleans_up() {
+        // The "editor" records where the file was, then rewrites it
+        let log = tempfile::NamedTempFile::new().unwrap();
+        let editor = format!(
+            "sh -c 'echo \"$1\" > {log}; printf \"edited\" > \"$1\"' sh",
+            log = log.path().display()
+        );
+
+        let edited = edit_with(&editor, "original secret", "md").unwrap();
+        assert_eq!(edited.as_deref(), Some("edited"));
+
+        let edited_path = PathBuf::from(fs::read_to_string(log.path()).unwrap().trim());
+        assert!(edited_path.extension().is_some_and(|ext| ext == "md"));
+        assert!(!edited_path.exists());
+        assert!(!edited_path.parent().unwrap().exists());
+
+        assert_eq!(edit_with("true", "unchanged", "txt").unwrap(), None);
+        assert!(edit_with("false", "content", "txt").is_err());
+    }
+}
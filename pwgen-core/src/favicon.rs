@@ -0,0 +1,227 @@
+//! Site icons for the entry list, fetched from `https://<host>/favicon.ico`
+//! and cached on disk under a keyed hash of the host from `normalize_site`.
+//!
+//! Fetching an icon tells the site's server that someone is interested in
+//! it, so callers should only fetch when the user has turned icons on. Sites
+//! without an icon are remembered too and asked again after a week.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{models::normalize_site, Error, Result};
+
+/// Icons larger than this are ignored, and not downloaded past this size
+pub const FAVICON_MAX_BYTES: usize = 256 * 1024;
+
+/// How long a site that had no icon is left alone before it is asked again
+const RETRY_MISSING_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Redirects followed before giving up, e.g. from example.com to www.example.com.
+/// Only redirects within the same site are followed, see `same_site`.
+const MAX_REDIRECTS: usize = 3;
+
+/// The host to fetch an icon for `site`, or `None` when it has no public
+/// name to fetch from: an IP address, a name without dots such as
+/// "localhost", one with a port, or a site that isn't an address.
+pub fn favicon_host(site: &str) -> Option<String> {
+    let host = normalize_site(site);
+    let valid = host.contains('.')
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        && host.chars().any(|c| c.is_ascii_alphabetic())
+        && !host.starts_with('.');
+    valid.then_some(host)
+}
+
+/// Icons on disk, one file per host; an empty file marks a site without one.
+///
+/// Files are named by an HMAC of the host under `key`, normally
+/// `Storage::cache_naming_key`, so listing the directory doesn't reveal which
+/// sites are in the vault.
+pub struct FaviconCache {
+    dir: PathBuf,
+    key: Vec<u8>,
+    pub timeout: Duration,
+}
+
+impl FaviconCache {
+    pub fn new(dir: impl Into<PathBuf>, key: Vec<u8>) -> Self {
+        Self { dir: dir.into(), key, timeout: Duration::from_secs(10) }
+    }
+
+    /// `pwgen/favicons` under the user's cache directory.
+    pub fn default_location(key: Vec<u8>) -> Option<Self> {
+        Some(Self::new(dirs::cache_dir()?.join("pwgen").join("favicons"), key))
+    }
+
+    fn path(&self, host: &str) -> PathBuf {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(host.as_bytes());
+        self.dir.join(format!("{}.ico", hex::encode(mac.finalize().into_bytes())))
+    }
+
+    /// Delete icons that earlier versions cached under the plain host name.
+    pub fn remove_unkeyed(&self) -> Result<()> {
+        let Ok(files) = fs::read_dir(&self.dir) else {
+            return Ok(());
+        };
+        for file in files {
+            let path = file?.path();
+            let keyed = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem.len() == 64 && stem.chars().all(|c| c.is_ascii_hexdigit()));
+            if !keyed && path.extension().is_some_and(|extension| extension == "ico") {
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The cached icon for `host`: `Some(None)` for a site known to have
+    /// none, and `None` when it has to be fetched.
+    pub fn cached(&self, host: &str) -> Option<Option<Vec<u8>>> {
+        let path = self.path(host);
+        let metadata = fs::metadata(&path).ok()?;
+        if metadata.len() > FAVICON_MAX_BYTES as u64 {
+            return None;
+        }
+        if metadata.len() > 0 {
+            return fs::read(&path).ok().map(Some);
+        }
+        let age = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or(Duration::MAX);
+        (age < RETRY_MISSING_AFTER).then_some(None)
+    }
+
+    pub fn store(&self, host: &str, icon: Option<&[u8]>) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(host), icon.unwrap_or_default())?;
+        Ok(())
+    }
+
+    /// The icon for `host` from the cache, or else fetched and cached.
+    /// `host` should come from `favicon_host`.
+    pub fn load(&self, host: &str) -> Result<Option<Vec<u8>>> {
+        if favicon_host(host).as_deref() != Some(host) {
+            return Err(Error::Other(format!("No icon can be fetched for {}", host)));
+        }
+        if let Some(icon) = self.cached(host) {
+            return Ok(icon);
+        }
+        let icon = fetch_favicon(host, self.timeout)?;
+        self.store(host, icon.as_deref())?;
+        Ok(icon)
+    }
+}
+
+/// Fetch `/favicon.ico` from `host` over HTTPS, following a few redirects.
+/// `None` when the site doesn't have one; an error when it is larger than
+/// `FAVICON_MAX_BYTES`.
+pub fn fetch_favicon(host: &str, timeout: Duration) -> Result<Option<Vec<u8>>> {
+    let mut url = url::Url::parse(&format!("https://{}/favicon.ico", host))
+        .map_err(|e| Error::Other(format!("Invalid host {}: {}", host, e)))?;
+    for _ in 0..=MAX_REDIRECTS {
+        let Some(current_host) = url.host_str() else {
+            return Ok(None);
+        };
+        let response = crate::https::get(current_host, url.path(), &[], FAVICON_MAX_BYTES, timeout)?;
+        match response.status {
+            200 if !response.body.is_empty() => {
+                return Ok(Some(response.body));
+            }
+            301 | 302 | 303 | 307 | 308 => {
+                let Some(next) = response.header("Location").and_then(|location| url.join(location).ok()) else {
+                    return Ok(None);
+                };
+                let next_host = next.host_str().unwrap_or_default();
+                if next.scheme() != "https" || !same_site(host, next_host) {
+                    return Ok(None);
+                }
+                url = next;
+            }
+            _ => return Ok(None),
+        }
+    }
+    Ok(None)
+}
+
+/// Whether `to` is `from`, a subdomain of it or a domain it is under, such
+/// as www.example.com for example.com.
+fn same_site(from: &str, to: &str) -> bool {
+    let under = |name: &str, domain: &str| name.strip_suffix(domain).is_some_and(|prefix| prefix.ends_with('.'));
+    from.eq_ignore_ascii_case(to) || under(&to.to_ascii_lowercase(), from) || under(from, &to.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_favicon_host() {
+        assert_eq!(favicon_host("https://www.GitHub.com/login").as_deref(), Some("github.com"));
+        assert_eq!(favicon_host("mail.google.com").as_deref(), Some("mail.google.com"));
+        assert_eq!(favicon_host("http://192.168.1.1/"), None);
+        assert_eq!(favicon_host("[::1]"), None);
+        assert_eq!(favicon_host("localhost"), None);
+        assert_eq!(favicon_host("example.com:8443"), None);
+        assert_eq!(favicon_host("Home Router"), None);
+    }
+
+    #[test]
+    fn test_redirects_stay_on_the_site() {
+        assert!(same_site("example.com", "www.example.com"));
+        assert!(same_site("www.example.com", "example.com"));
+        assert!(same_site("example.com", "EXAMPLE.com"));
+        assert!(!same_site("example.com", "evil.com"));
+        assert!(!same_site("example.com", "notexample.com"));
+        assert!(!same_site("example.com", "example.com.evil.net"));
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FaviconCache::new(dir.path().join("favicons"), vec![1; 32]);
+        assert!(cache.cached("example.com").is_none());
+
+        cache.store("example.com", Some(b"icon")).unwrap();
+        assert_eq!(cache.cached("example.com"), Some(Some(b"icon".to_vec())));
+        assert_eq!(cache.load("example.com").unwrap(), Some(b"icon".to_vec()));
+
+        // A site without an icon isn't asked again right away
+        cache.store("example.org", None).unwrap();
+        assert_eq!(cache.cached("example.org"), Some(None));
+
+        // Nor is an oversized file handed out
+        cache.store("example.net", Some(&vec![0; FAVICON_MAX_BYTES + 1])).unwrap();
+        assert!(cache.cached("example.net").is_none());
+
+        assert!(cache.load("../secrets").is_err());
+    }
+
+    #[test]
+    fn test_cache_file_names_hide_the_host() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FaviconCache::new(dir.path(), vec![1; 32]);
+        cache.store("example.com", Some(b"icon")).unwrap();
+        fs::write(dir.path().join("example.org.ico"), b"old icon").unwrap();
+
+        cache.remove_unkeyed().unwrap();
+        let names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|file| file.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names.len(), 1);
+        assert!(!names[0].contains("example"), "{}", names[0]);
+
+        // Another vault's key doesn't find it
+        assert!(FaviconCache::new(dir.path(), vec![2; 32]).cached("example.com").is_none());
+        assert!(cache.cached("example.com").is_some());
+    }
+}
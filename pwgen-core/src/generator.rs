@@ -1,5 +1,9 @@
 use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use crate::{Error, Result};
 
@@ -16,6 +20,11 @@ pub struct PasswordConfig {
     pub min_lowercase: usize,
     pub min_numbers: usize,
     pub min_symbols: usize,
+    /// Restrict symbols to `PasswordGenerator::MOBILE_SYMBOLS` and keep all
+    /// uppercase letters in a single leading run, so the password can be typed
+    /// on a phone keyboard with as few layout switches as possible.
+    #[serde(default)]
+    pub mobile_friendly: bool,
 }
 
 impl Default for PasswordConfig {
@@ -32,76 +41,251 @@ impl Default for PasswordConfig {
             min_lowercase: 1,
             min_numbers: 1,
             min_symbols: 1,
+            mobile_friendly: false,
         }
     }
 }
 
+/// Common English words for recovery keys; also used by the strength
+/// estimator to spot dictionary words. Passphrases use a `Wordlist`.
+pub(crate) const WORD_LIST: &[&str] = &[
+    "ability", "account", "achieve", "across", "action", "activity", "actual", "address",
+    "advance", "advice", "afford", "afraid", "against", "agency", "agenda", "almost",
+    "already", "although", "always", "amazing", "amount", "analysis", "ancient", "animal",
+    "another", "answer", "anxiety", "anyone", "anyway", "appear", "approach", "approve",
+    "archive", "argument", "around", "arrange", "arrival", "article", "artist", "assault",
+    "attempt", "attract", "auction", "audience", "author", "autumn", "average", "awesome",
+    "balance", "balloon", "banana", "banner", "bargain", "barrier", "battery", "beauty",
+    "because", "bedroom", "believe", "benefit", "besides", "between", "bicycle", "billion",
+    "biology", "blanket", "blossom", "bottle", "boulder", "bracket", "brother", "browser",
+    "buffalo", "builder", "burning", "business", "cabinet", "calcium", "calendar", "camera",
+    "campaign", "capable", "capital", "captain", "capture", "carbon", "careful", "carrier",
+    "cartoon", "cascade", "catalog", "category", "ceiling", "cellular", "century", "certain",
+    "chairman", "chamber", "champion", "channel", "chapter", "charity", "chicken", "children",
+    "chimney", "citizen", "clarity", "classic", "climate", "cluster", "coastal", "coconut",
+    "collapse", "collect", "college", "combine", "comfort", "command", "comment", "common",
+    "company", "compare", "compete", "complete", "complex", "concept", "concern", "concert",
+    "conduct", "confirm", "connect", "consider", "console", "contain", "content", "contest",
+    "context", "control", "convert", "cooking", "correct", "costume", "cottage", "council",
+    "counter", "country", "courage", "creative", "cricket", "critical", "crystal", "culture",
+    "current", "curtain", "customer", "cutting", "dancing", "daughter", "daylight", "deadline"
+];
+
+/// The embedded word lists, as downloaded; see `wordlists/README.md` for
+/// their sources and licenses.
+const EFF_LONG_WORDLIST: &str = include_str!("../wordlists/eff_large_wordlist.txt");
+const EFF_SHORT_WORDLIST: &str = include_str!("../wordlists/eff_short_wordlist_1.txt");
+const DICEWARE_WORDLIST: &str = include_str!("../wordlists/diceware.wordlist.asc");
+
 pub struct PasswordGenerator;
 
+/// Where passphrase words come from.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum Wordlist {
+    /// The EFF long list: 7776 words, about 12.9 bits each.
+    #[default]
+    EffLong,
+    /// The first EFF short list: 1296 short words, about 10.3 bits each.
+    EffShort,
+    /// Arnold Reinhold's original Diceware list: 7776 words, about 12.9 bits
+    /// each, some of them abbreviations or symbols.
+    Diceware,
+    /// A word list file, e.g. a Diceware list in another language. See
+    /// `LoadedWordlist::parse` for the accepted format.
+    File(PathBuf),
+}
+
+impl Wordlist {
+    /// The lists built into PwGen.
+    pub const EMBEDDED: [Wordlist; 3] = [Wordlist::EffLong, Wordlist::EffShort, Wordlist::Diceware];
+
+    /// Load the words. Embedded lists are parsed once and then shared.
+    pub fn load(&self) -> Result<Cow<'static, LoadedWordlist>> {
+        static EFF_LONG: OnceLock<LoadedWordlist> = OnceLock::new();
+        static EFF_SHORT: OnceLock<LoadedWordlist> = OnceLock::new();
+        static DICEWARE: OnceLock<LoadedWordlist> = OnceLock::new();
+        let (list, text) = match self {
+            Wordlist::EffLong => (&EFF_LONG, EFF_LONG_WORDLIST),
+            Wordlist::EffShort => (&EFF_SHORT, EFF_SHORT_WORDLIST),
+            Wordlist::Diceware => (&DICEWARE, DICEWARE_WORDLIST),
+            Wordlist::File(path) => return LoadedWordlist::from_file(path).map(Cow::Owned),
+        };
+        Ok(Cow::Borrowed(list.get_or_init(|| {
+            LoadedWordlist::parse(text).expect("embedded word lists are valid")
+        })))
+    }
+}
+
+impl std::fmt::Display for Wordlist {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Wordlist::EffLong => write!(f, "EFF long"),
+            Wordlist::EffShort => write!(f, "EFF short"),
+            Wordlist::Diceware => write!(f, "Diceware"),
+            Wordlist::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl std::str::FromStr for Wordlist {
+    type Err = Error;
+
+    /// `eff-long` (also `default`), `eff-short` or `diceware` select an
+    /// embedded list; anything else is taken as the path of a word list file.
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "eff-long" | "builtin" | "default" => Ok(Wordlist::EffLong),
+            "eff-short" => Ok(Wordlist::EffShort),
+            "diceware" => Ok(Wordlist::Diceware),
+            "" => Err(Error::Other("Empty word list name".to_string())),
+            _ => Ok(Wordlist::File(PathBuf::from(s))),
+        }
+    }
+}
+
+/// A loaded passphrase word list without duplicates.
+#[derive(Debug, Clone)]
+pub struct LoadedWordlist {
+    words: Vec<String>,
+}
+
+impl LoadedWordlist {
+    /// Fewest words a list may have; smaller lists make weak passphrases.
+    pub const MIN_WORDS: usize = 64;
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let text = std::fs::read_to_string(&path)?;
+        Self::parse(&text)
+            .map_err(|e| Error::Other(format!("{}: {}", path.as_ref().display(), e)))
+    }
+
+    /// Parse a word list with one word per line. A leading dice roll number,
+    /// as in `11111<TAB>abacus`, is skipped, so the EFF and Diceware lists can
+    /// be used as downloaded; so are blank lines, `#` comments and the PGP
+    /// armour around the signed Diceware list. Repeated words count once.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut words: Vec<String> = Vec::new();
+        let mut seen = HashSet::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.starts_with("-----BEGIN PGP SIGNATURE") {
+                break;
+            }
+            if line.is_empty() || line.starts_with('#') || line.starts_with("-----") {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let word = match parts.as_slice() {
+                [word] => *word,
+                [roll, word] if roll.bytes().all(|b| b.is_ascii_digit()) => *word,
+                _ => continue,
+            };
+            if seen.insert(word) {
+                words.push(word.to_string());
+            }
+        }
+        if words.len() < Self::MIN_WORDS {
+            return Err(Error::Other(format!(
+                "Word list has {} distinct words; at least {} are needed",
+                words.len(),
+                Self::MIN_WORDS
+            )));
+        }
+        Ok(Self { words })
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Entropy each randomly chosen word adds, in bits.
+    pub fn bits_per_word(&self) -> f64 {
+        (self.words.len() as f64).log2()
+    }
+}
+
+/// One position of a `generate_pattern` format string or template.
+enum PatternToken {
+    Literal(char),
+    Random(&'static str),
+}
+
 impl PasswordGenerator {
     const LOWERCASE: &'static str = "abcdefghijklmnopqrstuvwxyz";
     const UPPERCASE: &'static str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
     const NUMBERS: &'static str = "0123456789";
     const SYMBOLS: &'static str = "!@#$%^&*()-_=+[]{}|;:'\",.<>/?";
     const AMBIGUOUS: &'static str = "0O1lI";
+    const ANY: &'static str = concat!(
+        "abcdefghijklmnopqrstuvwxyz",
+        "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+        "0123456789",
+        "!@#$%^&*()-_=+[]{}|;:'\",.<>/?"
+    );
+    /// Symbols found on the first symbol page of the stock iOS and Android
+    /// keyboards. Quotes are left out because they are often auto-replaced
+    /// with "smart" variants.
+    pub const MOBILE_SYMBOLS: &'static str = "-/:;()$&@.,?!";
+    /// Consonants for pronounceable passwords, leaving out c, q, w, x and y,
+    /// whose sound depends on the letters around them.
+    const CONSONANTS: &'static str = "bdfghjklmnprstvz";
+    const VOWELS: &'static str = "aeiou";
     
     pub fn generate(config: &PasswordConfig) -> Result<String> {
         if config.length < 8 || config.length > 128 {
             return Err(Error::InvalidPasswordLength);
         }
         
-        let mut charset = String::new();
-        let mut password = Vec::new();
         let mut rng = thread_rng();
+        let uppercase_run = Self::mobile_uppercase_run(config);
+        let keep = |c: &char| !config.exclude_ambiguous || !Self::AMBIGUOUS.contains(*c);
         
-        if config.include_lowercase {
-            charset.push_str(Self::LOWERCASE);
-            for _ in 0..config.min_lowercase {
-                let chars: Vec<char> = Self::LOWERCASE.chars().collect();
-                password.push(chars[rng.gen_range(0..chars.len())]);
-            }
-        }
+        // Enabled classes with their minimum counts; the uppercase letters of
+        // a mobile-friendly password are placed separately as a leading run
+        let classes = [
+            (config.include_lowercase, Self::LOWERCASE, config.min_lowercase),
+            (config.include_uppercase && uppercase_run == 0, Self::UPPERCASE, config.min_uppercase),
+            (config.include_numbers, Self::NUMBERS, config.min_numbers),
+            (config.include_symbols, Self::symbol_set(config), config.min_symbols),
+        ];
         
-        if config.include_uppercase {
-            charset.push_str(Self::UPPERCASE);
-            for _ in 0..config.min_uppercase {
-                let chars: Vec<char> = Self::UPPERCASE.chars().collect();
-                password.push(chars[rng.gen_range(0..chars.len())]);
-            }
+        let required = uppercase_run
+            + classes.iter().filter(|(enabled, _, _)| *enabled).map(|(_, _, min)| min).sum::<usize>();
+        if required > config.length {
+            return Err(Error::Other(format!(
+                "Minimum character counts add up to {}, more than the password length of {}",
+                required, config.length
+            )));
         }
         
-        if config.include_numbers {
-            charset.push_str(Self::NUMBERS);
-            for _ in 0..config.min_numbers {
-                let chars: Vec<char> = Self::NUMBERS.chars().collect();
-                password.push(chars[rng.gen_range(0..chars.len())]);
+        // The minimums first, then the rest from every enabled class
+        let mut charset = Vec::new();
+        let mut password = Vec::with_capacity(config.length);
+        for (_, class, min) in classes.iter().filter(|(enabled, _, _)| *enabled) {
+            let chars: Vec<char> = class.chars().filter(keep).collect();
+            if chars.is_empty() && *min > 0 {
+                return Err(Error::Other(format!(
+                    "No characters left to pick from for a required class ('{}')",
+                    class
+                )));
             }
-        }
-        
-        if config.include_symbols {
-            let symbols = config.custom_symbols.as_deref().unwrap_or(Self::SYMBOLS);
-            charset.push_str(symbols);
-            for _ in 0..config.min_symbols {
-                let chars: Vec<char> = symbols.chars().collect();
+            for _ in 0..*min {
                 password.push(chars[rng.gen_range(0..chars.len())]);
             }
+            charset.extend(chars);
         }
         
         if charset.is_empty() {
             return Err(Error::Other("No character set selected".to_string()));
         }
         
-        if config.exclude_ambiguous {
-            charset = charset.chars()
-                .filter(|c| !Self::AMBIGUOUS.contains(*c))
-                .collect();
-        }
-        
-        let chars: Vec<char> = charset.chars().collect();
-        let remaining_length = config.length.saturating_sub(password.len());
-        
+        let remaining_length = config.length - password.len() - uppercase_run;
         for _ in 0..remaining_length {
-            password.push(chars[rng.gen_range(0..chars.len())]);
+            password.push(charset[rng.gen_range(0..charset.len())]);
         }
         
         let mut shuffled = password;
@@ -110,9 +294,196 @@ impl PasswordGenerator {
             shuffled.swap(i, j);
         }
         
+        if uppercase_run > 0 {
+            let uppercase: Vec<char> = Self::UPPERCASE.chars().filter(keep).collect();
+            let run: Vec<char> = (0..uppercase_run)
+                .map(|_| uppercase[rng.gen_range(0..uppercase.len())])
+                .collect();
+            shuffled.splice(0..0, run);
+        }
+        
         Ok(shuffled.into_iter().collect())
     }
     
+    /// Estimate the entropy in bits of a password produced with `config`.
+    ///
+    /// This assumes every character is drawn uniformly from the final character
+    /// set, which is what `generate` does for all but the minimum-count
+    /// characters. Compare against the same config with `mobile_friendly`
+    /// turned off to see how much entropy the mobile restrictions cost.
+    pub fn estimate_entropy(config: &PasswordConfig) -> f64 {
+        let uppercase_run = Self::mobile_uppercase_run(config);
+        let keep = |c: &char| !config.exclude_ambiguous || !Self::AMBIGUOUS.contains(*c);
+        
+        let mut charset = String::new();
+        if config.include_lowercase {
+            charset.push_str(Self::LOWERCASE);
+        }
+        if config.include_uppercase && uppercase_run == 0 {
+            charset.push_str(Self::UPPERCASE);
+        }
+        if config.include_numbers {
+            charset.push_str(Self::NUMBERS);
+        }
+        if config.include_symbols {
+            charset.push_str(Self::symbol_set(config));
+        }
+        
+        let pool_size = charset.chars().filter(keep).count();
+        if pool_size == 0 {
+            return 0.0;
+        }
+        
+        let body_length = config.length.saturating_sub(uppercase_run);
+        let mut bits = body_length as f64 * (pool_size as f64).log2();
+        if uppercase_run > 0 {
+            let uppercase_size = Self::UPPERCASE.chars().filter(keep).count();
+            bits += uppercase_run as f64 * (uppercase_size as f64).log2();
+        }
+        bits
+    }
+    
+    fn symbol_set(config: &PasswordConfig) -> &str {
+        match config.custom_symbols.as_deref() {
+            Some(symbols) => symbols,
+            None if config.mobile_friendly => Self::MOBILE_SYMBOLS,
+            None => Self::SYMBOLS,
+        }
+    }
+    
+    /// Number of uppercase letters to emit as a single leading run in
+    /// mobile-friendly mode, or 0 when uppercase letters are mixed in normally.
+    fn mobile_uppercase_run(config: &PasswordConfig) -> usize {
+        let has_other_classes = config.include_lowercase
+            || config.include_numbers
+            || config.include_symbols;
+        if config.mobile_friendly && config.include_uppercase && has_other_classes {
+            config.min_uppercase.max(1)
+        } else {
+            0
+        }
+    }
+    
+    /// Generate a password from a format string such as `XXX-999-aaa`: `X` is
+    /// an uppercase letter, `x` a lowercase letter, `9` a digit and `*` any
+    /// letter, digit or symbol. Other characters are copied as they are, and
+    /// `\` makes the next character literal (`\X` is a plain `X`).
+    ///
+    /// For the stricter `Llll-dddd-s` template syntax use
+    /// `generate_from_pattern`.
+    pub fn generate_pattern(pattern: &str) -> Result<String> {
+        Ok(Self::fill_pattern(Self::check_pattern(Self::parse_format(pattern)?)?))
+    }
+    
+    /// Generate a password from a template such as `Llll-dddd-s`: `L` is an
+    /// uppercase letter, `l` a lowercase letter, `d` a digit and `s` a symbol.
+    /// Text in braces is copied as it is (`{-}`, `{pw:}`), as are the
+    /// separators `-`, `_`, `.` and space. Any other character is an error,
+    /// so a typo can't silently end up in the password.
+    pub fn generate_from_pattern(pattern: &str) -> Result<String> {
+        Ok(Self::fill_pattern(Self::check_pattern(Self::parse_template(pattern)?)?))
+    }
+    
+    fn fill_pattern(tokens: Vec<PatternToken>) -> String {
+        let mut rng = thread_rng();
+        tokens
+            .into_iter()
+            .map(|token| match token {
+                PatternToken::Literal(c) => c,
+                PatternToken::Random(charset) => {
+                    let chars: Vec<char> = charset.chars().collect();
+                    chars[rng.gen_range(0..chars.len())]
+                }
+            })
+            .collect()
+    }
+    
+    /// Entropy in bits of a password produced by `generate_pattern`. Literal
+    /// characters contribute nothing.
+    pub fn pattern_entropy(pattern: &str) -> Result<f64> {
+        Ok(Self::tokens_entropy(Self::check_pattern(Self::parse_format(pattern)?)?))
+    }
+    
+    /// Entropy in bits of a password produced by `generate_from_pattern`.
+    pub fn template_entropy(pattern: &str) -> Result<f64> {
+        Ok(Self::tokens_entropy(Self::check_pattern(Self::parse_template(pattern)?)?))
+    }
+    
+    fn tokens_entropy(tokens: Vec<PatternToken>) -> f64 {
+        tokens
+            .into_iter()
+            .map(|token| match token {
+                PatternToken::Literal(_) => 0.0,
+                PatternToken::Random(charset) => (charset.chars().count() as f64).log2(),
+            })
+            .sum()
+    }
+    
+    fn parse_template(pattern: &str) -> Result<Vec<PatternToken>> {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars().enumerate();
+        while let Some((position, c)) = chars.next() {
+            match c {
+                'L' => tokens.push(PatternToken::Random(Self::UPPERCASE)),
+                'l' => tokens.push(PatternToken::Random(Self::LOWERCASE)),
+                'd' => tokens.push(PatternToken::Random(Self::NUMBERS)),
+                's' => tokens.push(PatternToken::Random(Self::SYMBOLS)),
+                '-' | '_' | '.' | ' ' => tokens.push(PatternToken::Literal(c)),
+                '{' => loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, literal)) => tokens.push(PatternToken::Literal(literal)),
+                        None => {
+                            return Err(Error::Other(format!(
+                                "Pattern has an unclosed '{{' at position {}",
+                                position + 1
+                            )))
+                        }
+                    }
+                },
+                unknown => {
+                    return Err(Error::Other(format!(
+                        "Unknown pattern token '{}' at position {}: use L, l, d, s or {{literal text}}",
+                        unknown,
+                        position + 1
+                    )))
+                }
+            }
+        }
+        Ok(tokens)
+    }
+    
+    fn parse_format(pattern: &str) -> Result<Vec<PatternToken>> {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            tokens.push(match c {
+                'X' => PatternToken::Random(Self::UPPERCASE),
+                'x' => PatternToken::Random(Self::LOWERCASE),
+                '9' => PatternToken::Random(Self::NUMBERS),
+                '*' => PatternToken::Random(Self::ANY),
+                '\\' => match chars.next() {
+                    Some(escaped) => PatternToken::Literal(escaped),
+                    None => return Err(Error::Other("Pattern ends with an unfinished '\\' escape".to_string())),
+                },
+                literal => PatternToken::Literal(literal),
+            });
+        }
+        Ok(tokens)
+    }
+    
+    fn check_pattern(tokens: Vec<PatternToken>) -> Result<Vec<PatternToken>> {
+        if tokens.is_empty() || tokens.len() > 128 {
+            return Err(Error::InvalidPasswordLength);
+        }
+        if !tokens.iter().any(|t| matches!(t, PatternToken::Random(_))) {
+            return Err(Error::Other(
+                "Pattern has no placeholders (X, x, 9, * or L, l, d, s) and would always produce the same password".to_string(),
+            ));
+        }
+        Ok(tokens)
+    }
+    
     pub fn generate_escaped(config: &PasswordConfig) -> Result<String> {
         let password = Self::generate(config)?;
         Ok(Self::escape_for_shell(&password))
@@ -136,30 +507,25 @@ impl PasswordGenerator {
             .collect()
     }
     
-    pub fn generate_passphrase(word_count: usize, separator: &str, capitalize: bool) -> Result<String> {
-        const WORD_LIST: &[&str] = &[
-            "ability", "account", "achieve", "across", "action", "activity", "actual", "address",
-            "advance", "advice", "afford", "afraid", "against", "agency", "agenda", "almost",
-            "already", "although", "always", "amazing", "amount", "analysis", "ancient", "animal",
-            "another", "answer", "anxiety", "anyone", "anyway", "appear", "approach", "approve",
-            "archive", "argument", "around", "arrange", "arrival", "article", "artist", "assault",
-            "attempt", "attract", "auction", "audience", "author", "autumn", "average", "awesome",
-            "balance", "balloon", "banana", "banner", "bargain", "barrier", "battery", "beauty",
-            "because", "bedroom", "believe", "benefit", "besides", "between", "bicycle", "billion",
-            "biology", "blanket", "blossom", "bottle", "boulder", "bracket", "brother", "browser",
-            "buffalo", "builder", "burning", "business", "cabinet", "calcium", "calendar", "camera",
-            "campaign", "capable", "capital", "captain", "capture", "carbon", "careful", "carrier",
-            "cartoon", "cascade", "catalog", "category", "ceiling", "cellular", "century", "certain",
-            "chairman", "chamber", "champion", "channel", "chapter", "charity", "chicken", "children",
-            "chimney", "citizen", "clarity", "classic", "climate", "cluster", "coastal", "coconut",
-            "collapse", "collect", "college", "combine", "comfort", "command", "comment", "common",
-            "company", "compare", "compete", "complete", "complex", "concept", "concern", "concert",
-            "conduct", "confirm", "connect", "consider", "console", "contain", "content", "contest",
-            "context", "control", "convert", "cooking", "correct", "costume", "cottage", "council",
-            "counter", "country", "courage", "creative", "cricket", "critical", "crystal", "culture",
-            "current", "curtain", "customer", "cutting", "dancing", "daughter", "daylight", "deadline"
-        ];
-        
+    /// Generate a passphrase of `word_count` words from the EFF long list.
+    pub fn generate_passphrase(
+        word_count: usize,
+        separator: &str,
+        capitalize: bool,
+        include_number: bool,
+    ) -> Result<Passphrase> {
+        let wordlist = Wordlist::default().load()?;
+        Self::generate_passphrase_from(&wordlist, word_count, separator, capitalize, include_number)
+    }
+    
+    /// Like `generate_passphrase`, with words from `wordlist`.
+    pub fn generate_passphrase_from(
+        wordlist: &LoadedWordlist,
+        word_count: usize,
+        separator: &str,
+        capitalize: bool,
+        include_number: bool,
+    ) -> Result<Passphrase> {
         if !(3..=20).contains(&word_count) {
             return Err(Error::Other("Word count must be between 3 and 20".to_string()));
         }
@@ -168,7 +534,7 @@ impl PasswordGenerator {
         let mut words = Vec::with_capacity(word_count);
         
         for _ in 0..word_count {
-            let word = WORD_LIST[rng.gen_range(0..WORD_LIST.len())];
+            let word = &wordlist.words[rng.gen_range(0..wordlist.words.len())];
             let word = if capitalize {
                 let mut chars = word.chars();
                 match chars.next() {
@@ -181,10 +547,114 @@ impl PasswordGenerator {
             words.push(word);
         }
         
-        Ok(words.join(separator))
+        // One digit after a randomly chosen word
+        if include_number {
+            let index = rng.gen_range(0..word_count);
+            words[index].push(char::from(b'0' + rng.gen_range(0..10u8)));
+        }
+        
+        Ok(Passphrase {
+            phrase: words.join(separator),
+            entropy_bits: Self::passphrase_entropy(wordlist, word_count, include_number),
+        })
+    }
+    
+    /// Entropy of a passphrase of `word_count` words from `wordlist`, in bits.
+    /// Capitalization and the separator are fixed, so they add nothing.
+    pub fn passphrase_entropy(wordlist: &LoadedWordlist, word_count: usize, include_number: bool) -> f64 {
+        let mut bits = wordlist.bits_per_word() * word_count as f64;
+        if include_number && word_count > 0 {
+            bits += 10f64.log2() + (word_count as f64).log2();
+        }
+        bits
+    }
+    
+    /// Generate a password that can be read aloud: alternating consonants and
+    /// vowels, starting with a capitalized consonant, e.g. `Tovagimu`. With
+    /// `include_symbols` a symbol from `MOBILE_SYMBOLS` follows the letters,
+    /// and with `include_numbers` two digits end the password; both count
+    /// towards `length`.
+    ///
+    /// Far fewer strings are possible than with `generate` at the same length,
+    /// so the returned entropy should be used to judge its strength rather
+    /// than the length or character classes.
+    pub fn generate_pronounceable(
+        length: usize,
+        include_numbers: bool,
+        include_symbols: bool,
+    ) -> Result<Pronounceable> {
+        if !(8..=128).contains(&length) {
+            return Err(Error::InvalidPasswordLength);
+        }
+        
+        let mut rng = thread_rng();
+        let mut pick = |set: &str| {
+            let chars: Vec<char> = set.chars().collect();
+            chars[rng.gen_range(0..chars.len())]
+        };
+        
+        let mut password = String::with_capacity(length);
+        for i in 0..Self::pronounceable_letters(length, include_numbers, include_symbols) {
+            if i % 2 == 0 {
+                let consonant = pick(Self::CONSONANTS);
+                password.push(if i == 0 { consonant.to_ascii_uppercase() } else { consonant });
+            } else {
+                password.push(pick(Self::VOWELS));
+            }
+        }
+        if include_symbols {
+            password.push(pick(Self::MOBILE_SYMBOLS));
+        }
+        if include_numbers {
+            password.push(pick(Self::NUMBERS));
+            password.push(pick(Self::NUMBERS));
+        }
+        
+        Ok(Pronounceable {
+            password,
+            entropy_bits: Self::pronounceable_entropy(length, include_numbers, include_symbols),
+        })
+    }
+    
+    /// Entropy of a password from `generate_pronounceable`, in bits. Only the
+    /// letter, symbol and digit choices count; the consonant-vowel layout, the
+    /// capital and the position of the symbol and digits are fixed.
+    pub fn pronounceable_entropy(length: usize, include_numbers: bool, include_symbols: bool) -> f64 {
+        let letters = Self::pronounceable_letters(length, include_numbers, include_symbols);
+        let consonants = letters.div_ceil(2);
+        let vowels = letters / 2;
+        
+        let mut bits = consonants as f64 * (Self::CONSONANTS.len() as f64).log2()
+            + vowels as f64 * (Self::VOWELS.len() as f64).log2();
+        if include_symbols {
+            bits += (Self::MOBILE_SYMBOLS.len() as f64).log2();
+        }
+        if include_numbers {
+            bits += 2.0 * 10f64.log2();
+        }
+        bits
+    }
+    
+    fn pronounceable_letters(length: usize, include_numbers: bool, include_symbols: bool) -> usize {
+        let suffix = if include_numbers { 2 } else { 0 } + usize::from(include_symbols);
+        length.saturating_sub(suffix)
     }
 }
 
+/// A generated passphrase and its entropy.
+#[derive(Clone)]
+pub struct Passphrase {
+    pub phrase: String,
+    pub entropy_bits: f64,
+}
+
+/// A generated pronounceable password and its entropy.
+#[derive(Clone)]
+pub struct Pronounceable {
+    pub password: String,
+    pub entropy_bits: f64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,8 +668,10 @@ mod tests {
     
     #[test]
     fn test_generate_password_custom_length() {
-        let mut config = PasswordConfig::default();
-        config.length = 32;
+        let config = PasswordConfig {
+            length: 32,
+            ..Default::default()
+        };
         let password = PasswordGenerator::generate(&config).unwrap();
         assert_eq!(password.len(), 32);
     }
@@ -218,11 +690,87 @@ mod tests {
             min_lowercase: 0,
             min_numbers: 0,
             min_symbols: 0,
+            mobile_friendly: false,
         };
         let password = PasswordGenerator::generate(&config).unwrap();
         assert!(password.chars().all(|c| c.is_numeric()));
     }
     
+    #[test]
+    fn test_generate_meets_minimums() {
+        let config = PasswordConfig {
+            length: 8,
+            min_uppercase: 2,
+            min_lowercase: 2,
+            min_numbers: 2,
+            min_symbols: 2,
+            ..Default::default()
+        };
+        for _ in 0..50 {
+            let password = PasswordGenerator::generate(&config).unwrap();
+            assert_eq!(password.len(), 8);
+            assert_eq!(password.chars().filter(char::is_ascii_uppercase).count(), 2, "{}", password);
+            assert_eq!(password.chars().filter(char::is_ascii_lowercase).count(), 2, "{}", password);
+            assert_eq!(password.chars().filter(char::is_ascii_digit).count(), 2, "{}", password);
+            assert_eq!(password.chars().filter(|c| PasswordGenerator::SYMBOLS.contains(*c)).count(), 2, "{}", password);
+            // Ambiguous characters are excluded from the minimums too
+            assert!(!password.chars().any(|c| PasswordGenerator::AMBIGUOUS.contains(c)), "{}", password);
+        }
+    }
+    
+    #[test]
+    fn test_generate_rejects_impossible_minimums() {
+        let config = PasswordConfig {
+            length: 8,
+            min_uppercase: 3,
+            min_lowercase: 3,
+            min_numbers: 3,
+            ..Default::default()
+        };
+        let error = PasswordGenerator::generate(&config).unwrap_err().to_string();
+        assert!(error.contains("add up to 10"), "{}", error);
+        
+        // Minimums of disabled classes don't count
+        let config = PasswordConfig { include_numbers: false, ..config };
+        assert!(PasswordGenerator::generate(&config).is_ok());
+        
+        let config = PasswordConfig { custom_symbols: Some(String::new()), ..Default::default() };
+        assert!(PasswordGenerator::generate(&config).is_err());
+    }
+    
+    #[test]
+    fn test_generate_mobile_friendly() {
+        let config = PasswordConfig {
+            length: 20,
+            min_uppercase: 2,
+            mobile_friendly: true,
+            ..Default::default()
+        };
+        for _ in 0..50 {
+            let password = PasswordGenerator::generate(&config).unwrap();
+            assert_eq!(password.len(), 20);
+            
+            let (head, tail) = password.split_at(2);
+            assert!(head.chars().all(|c| c.is_ascii_uppercase()));
+            assert!(!tail.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(tail.chars().all(|c| c.is_ascii_alphanumeric()
+                || PasswordGenerator::MOBILE_SYMBOLS.contains(c)));
+        }
+    }
+    
+    #[test]
+    fn test_mobile_friendly_entropy_cost() {
+        let standard = PasswordConfig::default();
+        let mobile = PasswordConfig {
+            mobile_friendly: true,
+            ..Default::default()
+        };
+        let standard_bits = PasswordGenerator::estimate_entropy(&standard);
+        let mobile_bits = PasswordGenerator::estimate_entropy(&mobile);
+        assert!(mobile_bits < standard_bits);
+        assert!(mobile_bits > 70.0);
+    }
+    
     #[test]
     fn test_escape_for_shell() {
         let password = "test$password'with\"special`chars!";
@@ -234,13 +782,153 @@ mod tests {
         assert!(escaped.contains("\\!"));
     }
     
+    #[test]
+    fn test_generate_pattern() {
+        let password = PasswordGenerator::generate_pattern("XXX-999-aaa\\9").unwrap();
+        let chars: Vec<char> = password.chars().collect();
+        assert_eq!(chars.len(), 12);
+        assert!(chars[..3].iter().all(|c| c.is_ascii_uppercase()));
+        assert_eq!(chars[3], '-');
+        assert!(chars[4..7].iter().all(|c| c.is_ascii_digit()));
+        assert_eq!(&password[7..], "-aaa9");
+        
+        let bits = PasswordGenerator::pattern_entropy("XXX-999").unwrap();
+        assert!((bits - (3.0 * 26f64.log2() + 3.0 * 10f64.log2())).abs() < 1e-9);
+        
+        assert!(PasswordGenerator::generate_pattern("").is_err());
+        assert!(PasswordGenerator::generate_pattern("abc-").is_err());
+        assert!(PasswordGenerator::generate_pattern("XX\\").is_err());
+    }
+    
+    #[test]
+    fn test_generate_from_pattern() {
+        let password = PasswordGenerator::generate_from_pattern("Llll{-}dddd-s").unwrap();
+        let chars: Vec<char> = password.chars().collect();
+        assert_eq!(chars.len(), 11);
+        assert!(chars[0].is_ascii_uppercase());
+        assert!(chars[1..4].iter().all(|c| c.is_ascii_lowercase()));
+        assert_eq!(chars[4], '-');
+        assert!(chars[5..9].iter().all(|c| c.is_ascii_digit()));
+        assert_eq!(chars[9], '-');
+        assert!(PasswordGenerator::SYMBOLS.contains(chars[10]));
+        
+        // Braces keep letters that would otherwise be tokens
+        let password = PasswordGenerator::generate_from_pattern("{id:Lds}dd").unwrap();
+        assert!(password.starts_with("id:Lds"));
+        
+        let bits = PasswordGenerator::template_entropy("Ll-dd").unwrap();
+        assert!((bits - (2.0 * 26f64.log2() + 2.0 * 10f64.log2())).abs() < 1e-9);
+        
+        // The two syntaxes never stand in for each other: "xxd-99" is two
+        // lowercase letters and literals as a format string, and an error as
+        // a template
+        let password = PasswordGenerator::generate_pattern("xxd-99").unwrap();
+        assert_eq!(&password[2..4], "d-");
+        assert!(PasswordGenerator::generate_from_pattern("xxd-99").is_err());
+        assert!(PasswordGenerator::generate_pattern("Ll-dd").is_err());
+        
+        assert!(PasswordGenerator::generate_from_pattern("").is_err());
+        assert!(PasswordGenerator::generate_from_pattern("{-}").is_err());
+        assert!(PasswordGenerator::generate_from_pattern("Ll{dd").is_err());
+        let error = PasswordGenerator::generate_from_pattern("Llq").unwrap_err().to_string();
+        assert!(error.contains("'q' at position 3"), "{}", error);
+    }
+    
     #[test]
     fn test_generate_passphrase() {
-        let passphrase = PasswordGenerator::generate_passphrase(4, "-", true).unwrap();
-        let parts: Vec<&str> = passphrase.split('-').collect();
+        let passphrase = PasswordGenerator::generate_passphrase(4, "-", true, false).unwrap();
+        let parts: Vec<&str> = passphrase.phrase.split('-').collect();
         assert_eq!(parts.len(), 4);
         for part in parts {
             assert!(part.chars().next().unwrap().is_uppercase());
         }
     }
+    
+    #[test]
+    fn test_generate_passphrase_with_number() {
+        let wordlist = Wordlist::EffLong.load().unwrap();
+        let passphrase = PasswordGenerator::generate_passphrase(5, " ", false, true).unwrap();
+        assert_eq!(passphrase.phrase.split(' ').count(), 5);
+        assert_eq!(passphrase.phrase.chars().filter(char::is_ascii_digit).count(), 1);
+        let expected = wordlist.bits_per_word() * 5.0 + 10f64.log2() + 5f64.log2();
+        assert!((passphrase.entropy_bits - expected).abs() < 1e-9);
+        
+        assert!(PasswordGenerator::generate_passphrase(0, "-", true, false).is_err());
+    }
+    
+    #[test]
+    fn test_eff_long_wordlist() {
+        let wordlist = Wordlist::EffLong.load().unwrap();
+        assert_eq!(wordlist.len(), 7776);
+        assert!((wordlist.bits_per_word() - 7776f64.log2()).abs() < 1e-9);
+        
+        // Six words come to about 77 bits
+        let passphrase = PasswordGenerator::generate_passphrase(6, " ", false, false).unwrap();
+        assert!((passphrase.entropy_bits - 6.0 * 7776f64.log2()).abs() < 1e-9);
+        assert!(passphrase.phrase.split(' ').all(|w| wordlist.words.iter().any(|known| known == w)));
+    }
+    
+    #[test]
+    fn test_embedded_wordlists() {
+        for (name, wordlist, len) in [
+            ("eff-long", Wordlist::EffLong, 7776),
+            ("EFF-Short", Wordlist::EffShort, 1296),
+            ("diceware", Wordlist::Diceware, 7776),
+        ] {
+            assert_eq!(name.parse::<Wordlist>().unwrap(), wordlist);
+            let loaded = wordlist.load().unwrap();
+            assert_eq!(loaded.len(), len, "{}", wordlist);
+            
+            let passphrase = PasswordGenerator::generate_passphrase_from(&loaded, 4, " ", false, false).unwrap();
+            assert!((passphrase.entropy_bits - 4.0 * (len as f64).log2()).abs() < 1e-9);
+        }
+        assert_eq!(
+            "words.txt".parse::<Wordlist>().unwrap(),
+            Wordlist::File(PathBuf::from("words.txt"))
+        );
+        assert!(Wordlist::File(PathBuf::from("/nonexistent/words.txt")).load().is_err());
+    }
+    
+    #[test]
+    fn test_generate_pronounceable() {
+        let plain = PasswordGenerator::generate_pronounceable(10, false, false).unwrap();
+        let chars: Vec<char> = plain.password.chars().collect();
+        assert_eq!(chars.len(), 10);
+        assert!(chars[0].is_ascii_uppercase());
+        for (i, c) in chars.iter().enumerate().skip(1) {
+            let is_vowel = PasswordGenerator::VOWELS.contains(*c);
+            assert_eq!(is_vowel, i % 2 == 1, "{} at {}", c, i);
+        }
+        assert!((plain.entropy_bits - (5.0 * 4.0 + 5.0 * 5f64.log2())).abs() < 1e-9);
+        
+        let full = PasswordGenerator::generate_pronounceable(16, true, true).unwrap();
+        assert_eq!(full.password.len(), 16);
+        assert!(full.password[14..].chars().all(|c| c.is_ascii_digit()));
+        assert!(PasswordGenerator::MOBILE_SYMBOLS.contains(&full.password[13..14]));
+        
+        // Well below a random password of the same length and classes
+        let random = PasswordGenerator::estimate_entropy(&PasswordConfig::default());
+        assert!(full.entropy_bits < random * 0.7);
+        
+        assert!(PasswordGenerator::generate_pronounceable(7, true, true).is_err());
+    }
+    
+    #[test]
+    fn test_wordlist_parse_dice_format() {
+        let words: Vec<String> = (0..100).map(|i| format!("word{}", i)).collect();
+        let mut text = String::from("-----BEGIN PGP SIGNED MESSAGE-----\nHash: SHA256\n\n# comment\n");
+        for (i, word) in words.iter().enumerate() {
+            text.push_str(&format!("{}\t{}\n", 11111 + i, word));
+        }
+        text.push_str("11111\tword0\n-----BEGIN PGP SIGNATURE-----\niQEzBAEBCAAdFiEE\n");
+        
+        let wordlist = LoadedWordlist::parse(&text).unwrap();
+        assert_eq!(wordlist.len(), 100);
+        assert!((wordlist.bits_per_word() - 100f64.log2()).abs() < 1e-9);
+        
+        let passphrase = PasswordGenerator::generate_passphrase_from(&wordlist, 5, " ", false, false).unwrap();
+        assert!(passphrase.phrase.split(' ').all(|w| words.iter().any(|known| known == w)));
+        
+        assert!(LoadedWordlist::parse("one\ntwo\nthree").is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,116 @@
+//! A bare HTTPS GET over rustls, for the few optional features that talk to
+//! a server. Requests are HTTP/1.0 so that bodies are never chunked and end
+//! with the connection.
+
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// Room for the status line and headers on top of the body
+const MAX_HEAD: usize = 16 * 1024;
+
+/// Status, headers and body of a response.
+pub(crate) struct Response {
+    pub status: u16,
+    headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Value of the first header called `name`, compared case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// GET `path` from `host` on port 443, with `headers` added to the request.
+/// Responses with a body over `max_body` bytes are an error, found without
+/// reading more than that. Redirects are returned as they are, not followed.
+pub(crate) fn get(
+    host: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    max_body: usize,
+    timeout: Duration,
+) -> Result<Response> {
+    let network_error = |e: std::io::Error| Error::Other(format!("Could not reach {}: {}", host, e));
+
+    let address = (host, 443)
+        .to_socket_addrs()
+        .map_err(network_error)?
+        .next()
+        .ok_or_else(|| Error::Other(format!("Could not resolve {}", host)))?;
+    let socket = TcpStream::connect_timeout(&address, timeout).map_err(network_error)?;
+    socket.set_read_timeout(Some(timeout)).map_err(network_error)?;
+    socket.set_write_timeout(Some(timeout)).map_err(network_error)?;
+
+    let server_name = host
+        .try_into()
+        .map_err(|e| Error::Other(format!("Invalid server name: {}", e)))?;
+    let connection = rustls::ClientConnection::new(tls_config(), server_name)
+        .map_err(|e| Error::Other(format!("TLS error: {}", e)))?;
+    let mut stream = rustls::StreamOwned::new(connection, socket);
+
+    let mut request = format!("GET {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: PwGen\r\n", path, host);
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).map_err(network_error)?;
+
+    // One byte over the limit tells a response that is too large from one
+    // that fits exactly
+    let limit = MAX_HEAD + max_body;
+    let mut response = Vec::new();
+    match Read::by_ref(&mut stream).take(limit as u64 + 1).read_to_end(&mut response) {
+        Ok(_) => {}
+        // Some servers close without a TLS close_notify
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof && !response.is_empty() => {}
+        Err(e) => return Err(network_error(e)),
+    }
+    let too_large = || Error::Other(format!("Response from {} is larger than {} bytes", host, max_body));
+    if response.len() > limit {
+        return Err(too_large());
+    }
+    let response =
+        parse_response(&response).ok_or_else(|| Error::Other(format!("Malformed response from {}", host)))?;
+    if response.body.len() > max_body {
+        return Err(too_large());
+    }
+    Ok(response)
+}
+
+fn parse_response(response: &[u8]) -> Option<Response> {
+    let split = response.windows(4).position(|window| window == b"\r\n\r\n")?;
+    let head = String::from_utf8_lossy(&response[..split]);
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+    Some(Response { status, headers, body: response[split + 4..].to_vec() })
+}
+
+fn tls_config() -> Arc<rustls::ClientConfig> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    Arc::new(
+        rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    )
+}
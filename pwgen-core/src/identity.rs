@@ -0,0 +1,78 @@
+//! The device or user name recorded as `created_by`/`modified_by` on the
+//! entries and secrets this machine adds or changes.
+//!
+//! It is configured once per machine and kept outside the vault, so that the
+//! devices and people sharing a vault file can be told apart. It is not
+//! secret and is stored in plain text.
+
+use std::{fs, path::PathBuf};
+
+use crate::{Error, Result};
+
+/// Longest accepted device name, in characters.
+pub const MAX_DEVICE_NAME_LEN: usize = 64;
+
+/// `pwgen/device-name` under the user's config directory.
+pub fn device_name_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("pwgen").join("device-name"))
+}
+
+/// The configured device name, if any.
+pub fn load_device_name() -> Option<String> {
+    let name = fs::read_to_string(device_name_path()?).ok()?;
+    normalize_device_name(&name).ok()
+}
+
+/// Store `name` as this machine's device name, or forget it with `None`.
+/// Returns the name as it was saved.
+pub fn save_device_name(name: Option<&str>) -> Result<Option<String>> {
+    let path = device_name_path()
+        .ok_or_else(|| Error::Other("Could not determine config directory".to_string()))?;
+    match name {
+        Some(name) => {
+            let name = normalize_device_name(name)?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&path, format!("{}\n", name))?;
+            Ok(Some(name))
+        }
+        None => {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Trim `name` and check that it is a single, reasonably short line.
+pub fn normalize_device_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::Other("Device name cannot be empty".to_string()));
+    }
+    if name.chars().count() > MAX_DEVICE_NAME_LEN {
+        return Err(Error::Other(format!(
+            "Device name is longer than {} characters",
+            MAX_DEVICE_NAME_LEN
+        )));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(Error::Other("Device name cannot contain control characters".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_device_name() {
+        assert_eq!(normalize_device_name("  alice-laptop \n").unwrap(), "alice-laptop");
+        assert!(normalize_device_name("   ").is_err());
+        assert!(normalize_device_name("work\nhome").is_err());
+        assert!(normalize_device_name(&"x".repeat(MAX_DEVICE_NAME_LEN + 1)).is_err());
+    }
+}
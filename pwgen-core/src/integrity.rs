@@ -0,0 +1,41 @@
+//! Results of checking that every vault item still decrypts and validates.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemKind {
+    PasswordEntry,
+    Secret,
+}
+
+/// One item that failed verification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub kind: ItemKind,
+    pub id: String,
+    pub name: String,
+    pub problem: String,
+}
+
+/// Filled in by `Storage::verify_entries` and `SecretsStorage::verify_secrets`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub entries_checked: usize,
+    pub secrets_checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    pub(crate) fn add_issue(&mut self, kind: ItemKind, id: &str, name: &str, problem: impl ToString) {
+        self.issues.push(IntegrityIssue {
+            kind,
+            id: id.to_string(),
+            name: name.to_string(),
+            problem: problem.to_string(),
+        });
+    }
+}
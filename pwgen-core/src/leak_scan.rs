@@ -0,0 +1,255 @@
+//! Find stored configuration secrets that appear in plaintext in a repository.
+//!
+//! Matching happens entirely in process: secret values are never written to
+//! disk, passed on a command line or included in a `LeakFinding`.
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    secrets::{DecryptedSecretEntry, SecretData},
+    Error, Result,
+};
+
+/// Files larger than this are skipped when scanning the working tree.
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Directories never worth scanning for committed secrets.
+const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules"];
+
+/// A stored value to look for.
+pub struct ScanTarget {
+    pub secret_name: String,
+    pub key: String,
+    value: Zeroizing<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeakLocation {
+    WorkingTree(PathBuf),
+    History { commit: String, path: String },
+}
+
+/// A line containing a stored secret value. Only names are recorded, never the
+/// value itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeakFinding {
+    pub location: LeakLocation,
+    pub line: usize,
+    pub secret_name: String,
+    pub key: String,
+}
+
+impl std::fmt::Display for LeakFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            LeakLocation::WorkingTree(path) => write!(f, "{}:{}", path.display(), self.line)?,
+            LeakLocation::History { commit, path } => write!(f, "{}:{}:{}", commit, path, self.line)?,
+        }
+        write!(f, ": value of {} from secret '{}'", self.key, self.secret_name)
+    }
+}
+
+/// Collect the environment/configuration variable values from `secrets`.
+/// Values shorter than `min_length` are ignored since short values such as
+/// `true` or `5432` match everywhere.
+pub fn scan_targets(secrets: &[DecryptedSecretEntry], min_length: usize) -> Vec<ScanTarget> {
+    let mut targets = Vec::new();
+    for secret in secrets {
+        if let SecretData::Configuration { variables, .. } = &secret.data {
+            for (key, value) in variables {
+                if value.chars().count() >= min_length {
+                    targets.push(ScanTarget {
+                        secret_name: secret.name.clone(),
+                        key: key.clone(),
+                        value: Zeroizing::new(value.clone()),
+                    });
+                }
+            }
+        }
+    }
+    targets
+}
+
+/// Scan every text file under `root`, skipping VCS metadata, build output and
+/// binary files.
+pub fn scan_working_tree(root: &Path, targets: &[ScanTarget]) -> Result<Vec<LeakFinding>> {
+    let mut findings = Vec::new();
+    if !targets.is_empty() {
+        scan_dir(root, root, targets, &mut findings)?;
+    }
+    Ok(findings)
+}
+
+fn scan_dir(root: &Path, dir: &Path, targets: &[ScanTarget], findings: &mut Vec<LeakFinding>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            if !SKIPPED_DIRS.iter().any(|skipped| entry.file_name() == *skipped) {
+                scan_dir(root, &path, targets, findings)?;
+            }
+        } else if file_type.is_file() && entry.metadata()?.len() <= MAX_FILE_SIZE {
+            let mut contents = Zeroizing::new(Vec::new());
+            fs::File::open(&path)?.read_to_end(&mut contents)?;
+            if contents.contains(&0) {
+                continue; // binary
+            }
+            let text = String::from_utf8_lossy(&contents);
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            for (index, line) in text.lines().enumerate() {
+                for target in targets.iter().filter(|t| line.contains(t.value.as_str())) {
+                    findings.push(LeakFinding {
+                        location: LeakLocation::WorkingTree(relative.clone()),
+                        line: index + 1,
+                        secret_name: target.secret_name.clone(),
+                        key: target.key.clone(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Scan the lines added by every commit reachable from any ref in the git
+/// repository at `root`. Line numbers refer to the file as of that commit.
+pub fn scan_git_history(root: &Path, targets: &[ScanTarget]) -> Result<Vec<LeakFinding>> {
+    let mut findings = Vec::new();
+    if targets.is_empty() {
+        return Ok(findings);
+    }
+
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["log", "--all", "-p", "--no-color", "--no-ext-diff", "--format=commit %h"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| Error::Other(format!("Failed to run git: {}", e)))?;
+
+    let mut reader = BufReader::new(child.stdout.take().expect("stdout is piped"));
+    let mut raw = Zeroizing::new(Vec::new());
+    let mut commit = String::new();
+    let mut path = String::new();
+    let mut line_number = 0;
+
+    loop {
+        raw.clear();
+        if reader.read_until(b'\n', &mut raw)? == 0 {
+            break;
+        }
+        let line = String::from_utf8_lossy(&raw);
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if let Some(hash) = line.strip_prefix("commit ") {
+            commit = hash.to_string();
+        } else if let Some(new_path) = line.strip_prefix("+++ ") {
+            path = new_path.strip_prefix("b/").unwrap_or(new_path).to_string();
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            // "@@ -a,b +c,d @@": added lines start at c
+            line_number = hunk
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('+'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|start| start.parse().ok())
+                .unwrap_or(0);
+        } else if let Some(added) = line.strip_prefix('+') {
+            for target in targets.iter().filter(|t| added.contains(t.value.as_str())) {
+                findings.push(LeakFinding {
+                    location: LeakLocation::History { commit: commit.clone(), path: path.clone() },
+                    line: line_number,
+                    secret_name: target.secret_name.clone(),
+                    key: target.key.clone(),
+                });
+            }
+            line_number += 1;
+        } else if line.starts_with(' ') {
+            line_number += 1;
+        }
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(Error::Other(format!("git log failed in {}", root.display())));
+    }
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env_connections::{EnvConnectionManager, EnvVarType, EnvironmentType};
+
+    fn env_secret(var_name: &str, value: &str) -> DecryptedSecretEntry {
+        EnvConnectionManager::create_env_variable(
+            "prod-db".to_string(),
+            var_name.to_string(),
+            value.to_string(),
+            EnvVarType::Secret,
+            EnvironmentType::Production,
+            None,
+            vec![],
+            true,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scan_working_tree_reports_location_without_value() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("config")).unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join("config/app.env"), "HOST=localhost\nDB_PASSWORD=hunter2-but-longer\n").unwrap();
+        fs::write(dir.path().join(".git/leftover"), "hunter2-but-longer").unwrap();
+
+        let secrets = [env_secret("DB_PASSWORD", "hunter2-but-longer"), env_secret("DEBUG", "true")];
+        let targets = scan_targets(&secrets, 8);
+        assert_eq!(targets.len(), 1);
+
+        let findings = scan_working_tree(dir.path(), &targets).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].location, LeakLocation::WorkingTree(PathBuf::from("config/app.env")));
+        assert_eq!(findings[0].line, 2);
+        assert!(!findings[0].to_string().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_scan_git_history_finds_removed_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .stdout(Stdio::null())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        fs::write(dir.path().join(".env"), "# local settings\nAPI_TOKEN=tok_0123456789\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "add env"]);
+        fs::remove_file(dir.path().join(".env")).unwrap();
+        git(&["commit", "-q", "-a", "-m", "remove env"]);
+
+        let targets = scan_targets(&[env_secret("API_TOKEN", "tok_0123456789")], 8);
+        assert!(scan_working_tree(dir.path(), &targets).unwrap().is_empty());
+
+        let findings = scan_git_history(dir.path(), &targets).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert!(matches!(&findings[0].location, LeakLocation::History { path, .. } if path == ".env"));
+        assert_eq!(findings[0].line, 2);
+    }
+}
@@ -1,19 +1,46 @@
 pub mod api_keys;
+pub mod audit_export;
+pub mod backend;
 pub mod backup;
+pub mod breach;
 pub mod browser_import;
+pub mod cards;
+pub mod clipboard_managers;
+#[cfg(feature = "connection-test")]
+pub mod connection_test;
 pub mod crypto;
 pub mod document_storage;
+pub mod entry_rules;
 pub mod env_connections;
 pub mod error;
+pub mod export;
+pub mod external_editor;
+#[cfg(feature = "favicons")]
+pub mod favicon;
+pub mod fs;
 pub mod generator;
+#[cfg(any(feature = "breach-check", feature = "favicons"))]
+mod https;
+pub mod identity;
+pub mod integrity;
+pub mod leak_scan;
 pub mod models;
 pub mod notes_config;
+pub mod recent_vaults;
+pub mod redact;
+pub mod sample_data;
 pub mod secret_templates;
 pub mod secrets;
 pub mod secrets_storage;
+pub mod secure_delete;
+pub mod snippets;
 pub mod ssh_keys;
+pub mod status;
 pub mod storage;
+pub mod strength;
 pub mod team_sharing;
+pub mod totp;
+pub mod vault_audit;
 
 pub use error::{Error, Result};
 
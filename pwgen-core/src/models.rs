@@ -1,7 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use zeroize::Zeroize;
 
+use crate::{
+    generator::PasswordConfig,
+    redact::{redacted, Redacted},
+    totp::{normalize_base32, parse_otpauth_uri, TotpSeed},
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PasswordEntry {
     pub id: String,
@@ -15,9 +22,31 @@ pub struct PasswordEntry {
     pub last_used: Option<DateTime<Utc>>,
     pub password_changed_at: DateTime<Utc>,
     pub favorite: bool,
+    #[serde(default)]
+    pub urls: Vec<EntryUrl>,
+    /// Device or user that added the entry, see `identity`.
+    #[serde(default)]
+    pub created_by: Option<String>,
+    /// Device or user that last changed the entry.
+    #[serde(default)]
+    pub modified_by: Option<String>,
+    /// TOTP secret, encrypted like the password
+    #[serde(default)]
+    pub encrypted_totp_secret: Option<Vec<u8>>,
+    /// Previous passwords as encrypted JSON, see `PasswordHistoryEntry`
+    #[serde(default)]
+    pub encrypted_password_history: Option<Vec<u8>>,
+    /// Ask for the master password again before the password is revealed
+    /// or copied
+    #[serde(default)]
+    pub require_reauth: bool,
+    /// The site's rules for new passwords, when they differ from the
+    /// generator's defaults
+    #[serde(default)]
+    pub password_policy: Option<PasswordPolicy>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DecryptedPasswordEntry {
     pub id: String,
     pub site: String,
@@ -31,6 +60,215 @@ pub struct DecryptedPasswordEntry {
     pub last_used: Option<DateTime<Utc>>,
     pub password_changed_at: DateTime<Utc>,
     pub favorite: bool,
+    #[serde(default)]
+    pub urls: Vec<EntryUrl>,
+    /// Device or user that added the entry, see `identity`.
+    #[serde(default)]
+    pub created_by: Option<String>,
+    /// Device or user that last changed the entry.
+    #[serde(default)]
+    pub modified_by: Option<String>,
+    /// Base32 secret for two-factor codes, see `generate_totp`. Left out
+    /// when serialized, like the password.
+    #[serde(default, skip_serializing)]
+    pub totp_secret: Option<String>,
+    /// Passwords the entry had before, newest first. Left out when
+    /// serialized, like the password.
+    #[serde(default, skip_serializing)]
+    pub password_history: Vec<PasswordHistoryEntry>,
+    /// Ask for the master password again before the password is revealed
+    /// or copied
+    #[serde(default)]
+    pub require_reauth: bool,
+    /// The site's rules for new passwords, when they differ from the
+    /// generator's defaults
+    #[serde(default)]
+    pub password_policy: Option<PasswordPolicy>,
+}
+
+/// A password an entry used to have.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PasswordHistoryEntry {
+    pub password: String,
+    /// When it was replaced
+    pub changed_at: DateTime<Utc>,
+}
+
+impl fmt::Debug for PasswordHistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PasswordHistoryEntry")
+            .field("password", &Redacted)
+            .field("changed_at", &self.changed_at)
+            .finish()
+    }
+}
+
+impl Drop for PasswordHistoryEntry {
+    fn drop(&mut self) {
+        self.password.zeroize();
+    }
+}
+
+/// Length and character classes a site accepts, kept with its entry so a
+/// replacement password can be generated in one go.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasswordPolicy {
+    pub length: usize,
+    pub uppercase: bool,
+    pub lowercase: bool,
+    pub numbers: bool,
+    pub symbols: bool,
+}
+
+impl PasswordPolicy {
+    /// Generator settings for a password that follows the policy, with at
+    /// least one character of every allowed class.
+    pub fn config(&self) -> PasswordConfig {
+        PasswordConfig {
+            length: self.length,
+            include_uppercase: self.uppercase,
+            include_lowercase: self.lowercase,
+            include_numbers: self.numbers,
+            include_symbols: self.symbols,
+            min_uppercase: self.uppercase as usize,
+            min_lowercase: self.lowercase as usize,
+            min_numbers: self.numbers as usize,
+            min_symbols: self.symbols as usize,
+            ..PasswordConfig::default()
+        }
+    }
+}
+
+impl From<&PasswordConfig> for PasswordPolicy {
+    fn from(config: &PasswordConfig) -> Self {
+        Self {
+            length: config.length,
+            uppercase: config.include_uppercase,
+            lowercase: config.include_lowercase,
+            numbers: config.include_numbers,
+            symbols: config.include_symbols,
+        }
+    }
+}
+
+/// One of the addresses an account is used at.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryUrl {
+    pub url: String,
+    pub primary: bool,
+}
+
+impl fmt::Debug for DecryptedPasswordEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecryptedPasswordEntry")
+            .field("id", &self.id)
+            .field("site", &self.site)
+            .field("username", &self.username)
+            .field("password", &Redacted)
+            .field("notes", &redacted(&self.notes))
+            .field("tags", &self.tags)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .field("last_used", &self.last_used)
+            .field("password_changed_at", &self.password_changed_at)
+            .field("favorite", &self.favorite)
+            .field("urls", &self.urls)
+            .field("created_by", &self.created_by)
+            .field("modified_by", &self.modified_by)
+            .field("totp_secret", &redacted(&self.totp_secret))
+            .field("password_history", &self.password_history)
+            .field("require_reauth", &self.require_reauth)
+            .field("password_policy", &self.password_policy)
+            .finish()
+    }
+}
+
+impl DecryptedPasswordEntry {
+    /// The plaintext password. `Debug` output always masks it.
+    pub fn reveal(&self) -> &str {
+        &self.password
+    }
+
+    /// Every URL of the entry, primary first. `site` is always the primary
+    /// address; `urls` is empty for entries that only have one.
+    pub fn all_urls(&self) -> Vec<&str> {
+        let mut all = vec![self.site.as_str()];
+        for entry_url in &self.urls {
+            if !all.contains(&entry_url.url.as_str()) {
+                all.push(&entry_url.url);
+            }
+        }
+        all
+    }
+
+    /// Replace the entry's URLs, making `primary` the `site`.
+    pub fn set_urls(&mut self, primary: String, others: Vec<String>) {
+        let mut urls = vec![EntryUrl { url: primary.clone(), primary: true }];
+        for url in others {
+            if !url.is_empty() && !urls.iter().any(|u| u.url == url) {
+                urls.push(EntryUrl { url, primary: false });
+            }
+        }
+        self.site = primary;
+        self.urls = if urls.len() > 1 { urls } else { Vec::new() };
+    }
+
+    /// The address to open in a browser: `site` when it is an http(s) URL,
+    /// or `site` with `https://` in front when it is a bare domain. `None`
+    /// for sites that are only a name, such as "Home router".
+    pub fn login_url(&self) -> Option<String> {
+        let site = self.site.trim();
+        if let Ok(url) = url::Url::parse(site) {
+            return matches!(url.scheme(), "http" | "https").then(|| site.to_string());
+        }
+        let url = format!("https://{}", site);
+        (site.contains('.') && !site.contains(char::is_whitespace) && url::Url::parse(&url).is_ok()).then_some(url)
+    }
+
+    /// Whether any of the entry's URLs is for the same host as `url`, or a
+    /// parent domain of it ("example.com" matches "login.example.com").
+    pub fn matches_url(&self, url: &str) -> bool {
+        let Some(target) = url_host(url) else {
+            return false;
+        };
+        self.all_urls().into_iter().filter_map(url_host).any(|host| {
+            target == host || target.ends_with(&format!(".{}", host))
+        })
+    }
+}
+
+/// Lower-cased host of `url` without a leading "www.", accepting bare domains.
+pub fn url_host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let parsed = url::Url::parse(url)
+        .ok()
+        .filter(|u| u.host_str().is_some())
+        .or_else(|| url::Url::parse(&format!("https://{}", url)).ok())?;
+    let host = parsed.host_str()?.to_lowercase();
+    Some(host.strip_prefix("www.").map(str::to_string).unwrap_or(host))
+}
+
+/// The site of an entry as compared for duplicates and sorting: the
+/// lower-cased host without the scheme, a leading "www.", the path or the
+/// scheme's default port, so "https://www.Example.com/login" and
+/// "example.com" are the same site. Subdomains and other ports are kept. A
+/// site that isn't an address, like "Home router", is only trimmed and
+/// lower-cased.
+pub fn normalize_site(site: &str) -> String {
+    let site = site.trim();
+    let parsed = url::Url::parse(site)
+        .ok()
+        .filter(|u| u.host_str().is_some())
+        .or_else(|| url::Url::parse(&format!("https://{}", site)).ok());
+    let Some(host) = parsed.as_ref().and_then(|u| u.host_str()) else {
+        return site.to_lowercase();
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    match parsed.and_then(|u| u.port()) {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
 }
 
 impl Drop for DecryptedPasswordEntry {
@@ -39,9 +277,41 @@ impl Drop for DecryptedPasswordEntry {
         if let Some(ref mut notes) = self.notes {
             notes.zeroize();
         }
+        if let Some(ref mut totp_secret) = self.totp_secret {
+            totp_secret.zeroize();
+        }
     }
 }
 
+/// Length of a TOTP time step, in seconds, for seeds that don't set one.
+pub const TOTP_PERIOD: u64 = crate::totp::DEFAULT_PERIOD;
+
+/// The RFC 6238 code for the stored `secret` at `time`: HMAC-SHA1, 6 digits
+/// and 30 second steps for a base32 key, as most authenticator apps
+/// generate it, or whatever an `otpauth://` URI asks for.
+pub fn generate_totp(secret: &str, time: DateTime<Utc>) -> crate::Result<String> {
+    TotpSeed::from_stored(secret)?.code_at(time)
+}
+
+/// Seconds until the code of `secret` for `time` is replaced by the next one.
+pub fn totp_seconds_remaining(secret: &str, time: DateTime<Utc>) -> u64 {
+    TotpSeed::from_stored(secret)
+        .map(|seed| seed.seconds_remaining(time))
+        .unwrap_or_else(|_| TOTP_PERIOD - time.timestamp().max(0) as u64 % TOTP_PERIOD)
+}
+
+/// Tidy a TOTP secret as typed or pasted from a setup page: spaces, dashes,
+/// padding and lower case are accepted, and so is a whole `otpauth://` URI.
+/// Returns what to store, see `TotpSeed::stored_secret`. Errors if the
+/// secret is not valid base32.
+pub fn normalize_totp_secret(input: &str) -> crate::Result<String> {
+    let input = input.trim();
+    if input.to_lowercase().starts_with("otpauth://") {
+        return Ok(parse_otpauth_uri(input)?.stored_secret());
+    }
+    normalize_base32(input)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VaultMetadata {
     pub id: String,
@@ -51,6 +321,80 @@ pub struct VaultMetadata {
     pub version: String,
     pub master_password_hash: String,
     pub salt: Vec<u8>,
+    /// Key file that must be supplied along with the master password
+    #[serde(default)]
+    pub key_file: Option<KeyFileInfo>,
+    /// When a backup of the vault was last written
+    #[serde(default)]
+    pub last_backup_at: Option<DateTime<Utc>>,
+    /// Days deleted entries and secrets stay in the trash before they are
+    /// purged when the vault is opened; 0 keeps them until the trash is
+    /// emptied
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    /// The vault key wrapped with a recovery key, when one has been set up
+    #[serde(default)]
+    pub recovery: Option<RecoveryEnvelope>,
+}
+
+/// The vault key wrapped with a recovery key, so the vault can be unlocked
+/// without the master password. See `Storage::enable_recovery_key`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryEnvelope {
+    /// Salt the wrapping key is derived from the recovery key with
+    pub salt: Vec<u8>,
+    /// The vault key, encrypted with the wrapping key
+    pub wrapped_vault_key: Vec<u8>,
+    /// The wrapping key, encrypted with the vault key, so the envelope can
+    /// be rewrapped when the master password changes
+    pub wrapped_recovery_key: Vec<u8>,
+}
+
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+fn default_trash_retention_days() -> u32 {
+    DEFAULT_TRASH_RETENTION_DAYS
+}
+
+/// A deleted entry or secret waiting in the trash.
+#[derive(Debug, Clone)]
+pub struct Trashed<T> {
+    pub item: T,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Non-secret description of a vault's key file, used to tell the user which
+/// file is needed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyFileInfo {
+    pub id: String,
+    pub name: Option<String>,
+}
+
+impl fmt::Display for KeyFileInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "'{}' (id {})", name, self.id),
+            None => write!(f, "id {}", self.id),
+        }
+    }
+}
+
+/// Tag that always asks for confirmation before an entry is copied or revealed.
+pub const CONFIRM_REVEAL_TAG: &str = "confirm-reveal";
+/// Tag that skips the copy/reveal confirmation even when it is enabled globally.
+pub const NO_CONFIRM_REVEAL_TAG: &str = "no-confirm-reveal";
+
+/// Whether copying or revealing an item carrying `tags` needs confirming,
+/// given the global setting. Per-entry tags override the global default.
+pub fn requires_reveal_confirmation(tags: &[String], confirm_by_default: bool) -> bool {
+    if tags.iter().any(|t| t == CONFIRM_REVEAL_TAG) {
+        true
+    } else if tags.iter().any(|t| t == NO_CONFIRM_REVEAL_TAG) {
+        false
+    } else {
+        confirm_by_default
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,7 +406,7 @@ pub struct SearchFilter {
     pub sort_order: SortOrder,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum SortField {
     Site,
     Username,
@@ -71,12 +415,25 @@ pub enum SortField {
     LastUsed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
 pub enum SortOrder {
     Ascending,
+    #[default]
     Descending,
 }
 
+impl std::str::FromStr for SortOrder {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "asc" | "ascending" => Ok(SortOrder::Ascending),
+            "desc" | "descending" => Ok(SortOrder::Descending),
+            _ => Err(crate::Error::Other(format!("Unknown sort order: {} (expected asc or desc)", s))),
+        }
+    }
+}
+
 impl Default for SearchFilter {
     fn default() -> Self {
         Self {
@@ -108,7 +465,7 @@ pub enum ImportSource {
     Csv,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ImportEntry {
     pub site: String,
     pub username: String,
@@ -116,6 +473,17 @@ pub struct ImportEntry {
     pub notes: Option<String>,
 }
 
+impl fmt::Debug for ImportEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImportEntry")
+            .field("site", &self.site)
+            .field("username", &self.username)
+            .field("password", &Redacted)
+            .field("notes", &redacted(&self.notes))
+            .finish()
+    }
+}
+
 impl Drop for ImportEntry {
     fn drop(&mut self) {
         self.password.zeroize();
@@ -133,4 +501,49 @@ pub struct BackupMetadata {
     pub entry_count: usize,
     pub file_size: u64,
     pub checksum: String,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_normalize_site() {
+        for (site, expected) in [
+            ("https://www.Example.com/login?next=/", "example.com"),
+            ("example.com", "example.com"),
+            ("  EXAMPLE.COM.  ", "example.com"),
+            ("http://mail.google.com/mail/u/0", "mail.google.com"),
+            ("www.accounts.example.co.uk", "accounts.example.co.uk"),
+            ("https://example.com:443/", "example.com"),
+            ("https://example.com:8443/admin", "example.com:8443"),
+            ("localhost:3000", "localhost:3000"),
+            ("http://192.168.1.1/", "192.168.1.1"),
+            ("192.168.1.1:8080/setup", "192.168.1.1:8080"),
+            ("http://[::1]:5000", "[::1]:5000"),
+            ("Home Router", "home router"),
+        ] {
+            assert_eq!(normalize_site(site), expected, "{}", site);
+        }
+    }
+
+    #[test]
+    fn test_generate_totp_rfc6238_vectors() {
+        // The SHA-1 seed from RFC 6238 appendix B, "12345678901234567890"
+        let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+        for (timestamp, code) in [(59, "287082"), (1111111109, "081804"), (2000000000, "279037")] {
+            let time = Utc.timestamp_opt(timestamp, 0).unwrap();
+            assert_eq!(generate_totp(secret, time).unwrap(), code);
+        }
+        assert_eq!(totp_seconds_remaining(secret, Utc.timestamp_opt(59, 0).unwrap()), 1);
+
+        assert_eq!(normalize_totp_secret("gezd gnbv gy3t qojq").unwrap(), "GEZDGNBVGY3TQOJQ");
+        assert_eq!(
+            normalize_totp_secret("otpauth://totp/Example:alice?secret=GEZDGNBVGY3TQOJQ&issuer=Example").unwrap(),
+            "GEZDGNBVGY3TQOJQ"
+        );
+        assert!(normalize_totp_secret("not base32!").is_err());
+        assert!(normalize_totp_secret("").is_err());
+    }
+}
@@ -184,10 +184,13 @@ impl NotesConfigManager {
             format,
             variables,
             template: template_name,
+            raw_variables: HashMap::new(),
         };
 
-        let mut metadata = SecretMetadata::default();
-        metadata.template = Some(format!("{:?}", config_type));
+        let metadata = SecretMetadata {
+            template: Some(format!("{:?}", config_type)),
+            ..Default::default()
+        };
 
         Ok(DecryptedSecretEntry {
             id: Uuid::new_v4().to_string(),
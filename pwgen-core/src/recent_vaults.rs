@@ -0,0 +1,95 @@
+//! The vault files recently opened on this machine, so the GUI can offer
+//! them when unlocking.
+//!
+//! Kept outside any vault, one path per line with the most recent first, in
+//! `pwgen/recent-vaults` under the user's config directory. Only paths are
+//! recorded, never anything from inside a vault.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{Error, Result};
+
+/// Most vaults remembered; older ones drop off the list.
+pub const MAX_RECENT_VAULTS: usize = 8;
+
+/// `pwgen/recent-vaults` under the user's config directory.
+pub fn recent_vaults_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|d| d.join("pwgen").join("recent-vaults"))
+}
+
+/// Recently opened vaults, most recent first. Vaults that no longer exist
+/// are left out.
+pub fn list_recent_vaults() -> Vec<PathBuf> {
+    read_recent_vaults()
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect()
+}
+
+fn read_recent_vaults() -> Vec<PathBuf> {
+    recent_vaults_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .map(|list| {
+            list.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Put `vault` at the top of the recent vaults.
+pub fn remember_vault(vault: &Path) -> Result<()> {
+    write_recent_vaults(&push_recent(read_recent_vaults(), vault.to_path_buf()))
+}
+
+/// Take `vault` off the recent vaults.
+pub fn forget_vault(vault: &Path) -> Result<()> {
+    let mut recent = read_recent_vaults();
+    recent.retain(|path| path != vault);
+    write_recent_vaults(&recent)
+}
+
+fn write_recent_vaults(recent: &[PathBuf]) -> Result<()> {
+    let path = recent_vaults_path()
+        .ok_or_else(|| Error::Other("Could not determine config directory".to_string()))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let list: String = recent.iter().map(|vault| format!("{}\n", vault.display())).collect();
+    fs::write(&path, list)?;
+    Ok(())
+}
+
+/// `recent` with `vault` moved or added to the front, capped at
+/// `MAX_RECENT_VAULTS`.
+fn push_recent(mut recent: Vec<PathBuf>, vault: PathBuf) -> Vec<PathBuf> {
+    recent.retain(|path| *path != vault);
+    recent.insert(0, vault);
+    recent.truncate(MAX_RECENT_VAULTS);
+    recent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_recent() {
+        let recent = vec![PathBuf::from("/a.db"), PathBuf::from("/b.db")];
+        assert_eq!(
+            push_recent(recent.clone(), PathBuf::from("/b.db")),
+            vec![PathBuf::from("/b.db"), PathBuf::from("/a.db")]
+        );
+        assert_eq!(push_recent(recent, PathBuf::from("/c.db"))[0], PathBuf::from("/c.db"));
+
+        let full: Vec<PathBuf> = (0..MAX_RECENT_VAULTS).map(|i| PathBuf::from(format!("/{}.db", i))).collect();
+        let recent = push_recent(full, PathBuf::from("/new.db"));
+        assert_eq!(recent.len(), MAX_RECENT_VAULTS);
+        assert!(!recent.contains(&PathBuf::from(format!("/{}.db", MAX_RECENT_VAULTS - 1))));
+    }
+}
@@ -0,0 +1,73 @@
+//! Masked `Debug` output for types that hold secret values.
+//!
+//! Secret-bearing types implement `Debug` by hand so that logging or
+//! `{:?}`-formatting them never prints a password, key or token. The real
+//! value has to be asked for explicitly with a `reveal()` accessor.
+
+use std::fmt;
+
+/// Shown in place of a secret value.
+pub const MASK: &str = "***";
+
+/// Formats as [`MASK`] whatever it stands in for.
+pub(crate) struct Redacted;
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(MASK)
+    }
+}
+
+/// `Some(***)` or `None`, so the presence of an optional secret stays visible.
+pub(crate) fn redacted<T>(value: &Option<T>) -> Option<Redacted> {
+    value.as_ref().map(|_| Redacted)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::{models::DecryptedPasswordEntry, secrets::SecretData};
+
+    #[test]
+    fn test_debug_output_never_contains_secrets() {
+        let entry = DecryptedPasswordEntry {
+            id: "entry-1".to_string(),
+            site: "example.com".to_string(),
+            username: "alice".to_string(),
+            password: "correct horse battery staple".to_string(),
+            notes: Some("pin is 4321".to_string()),
+            tags: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_used: None,
+            password_changed_at: Utc::now(),
+            favorite: false,
+            urls: vec![],
+            created_by: None,
+            modified_by: None,
+            totp_secret: None,
+            password_history: Vec::new(),
+            require_reauth: false,
+            password_policy: None,
+        };
+        let debug = format!("{:?} {:#?}", entry, entry);
+        assert!(debug.contains("alice"));
+        assert!(!debug.contains("correct horse"));
+        assert!(!debug.contains("4321"));
+        assert_eq!(entry.reveal(), "correct horse battery staple");
+
+        let data = SecretData::CloudCredentials {
+            provider: crate::secrets::CloudProvider::AWS,
+            access_key: "AKIAEXAMPLE".to_string(),
+            secret_key: "wJalrXUtnFEMI".to_string(),
+            region: None,
+            additional_config: [("session_token".to_string(), "FwoGZXIvYXdzE".to_string())].into(),
+        };
+        let debug = format!("{:?}", data);
+        assert!(debug.contains("session_token"));
+        assert!(!debug.contains("wJalrXUtnFEMI"));
+        assert!(!debug.contains("FwoGZXIvYXdzE"));
+        assert_eq!(data.reveal(), Some("wJalrXUtnFEMI"));
+    }
+}
@@ -0,0 +1,96 @@
+//! Realistic-looking fake vault contents for benchmarks and manual testing.
+
+use chrono::{Duration, Utc};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+use crate::{
+    crypto::hash_entry_id,
+    generator::{PasswordConfig, PasswordGenerator},
+    models::DecryptedPasswordEntry,
+};
+
+const SITES: &[&str] = &[
+    "github.com", "gitlab.com", "google.com", "amazon.com", "netflix.com", "spotify.com",
+    "reddit.com", "twitter.com", "linkedin.com", "dropbox.com", "slack.com", "zoom.us",
+    "paypal.com", "stripe.com", "digitalocean.com", "cloudflare.com", "heroku.com",
+    "atlassian.net", "notion.so", "figma.com", "apple.com", "microsoft.com", "ebay.com",
+    "airbnb.com", "booking.com", "steampowered.com", "discord.com", "twitch.tv",
+];
+
+const NAMES: &[&str] = &[
+    "alex", "sam", "jordan", "taylor", "morgan", "casey", "riley", "jamie", "avery",
+    "quinn", "drew", "robin", "kai", "sky", "river", "sage",
+];
+
+const DOMAINS: &[&str] = &["example.com", "mail.test", "work.example", "personal.test"];
+
+const TAGS: &[&str] = &[
+    "work", "personal", "finance", "social", "dev", "shopping", "streaming", "travel",
+    "gaming", "shared", "2fa",
+];
+
+const NOTES: &[&str] = &[
+    "Recovery codes stored offline",
+    "Shared with the team",
+    "Security questions: see paper notes",
+    "Rotate every 90 days",
+];
+
+/// Generate `count` password entries with varied sites, usernames, tags, notes
+/// and timestamps. Entry ids are unique within the returned set.
+pub fn sample_entries(count: usize) -> Vec<DecryptedPasswordEntry> {
+    let mut rng = thread_rng();
+    let config = PasswordConfig::default();
+    let now = Utc::now();
+
+    (0..count)
+        .map(|i| {
+            let base = SITES[i % SITES.len()];
+            // Cycle through subdomains so every entry gets a distinct site
+            let site = match i / SITES.len() {
+                0 => base.to_string(),
+                n => format!("app{}.{}", n, base),
+            };
+            let username = format!(
+                "{}{}@{}",
+                NAMES.choose(&mut rng).unwrap(),
+                rng.gen_range(1..1000),
+                DOMAINS.choose(&mut rng).unwrap()
+            );
+
+            let tag_count = rng.gen_range(0..=3);
+            let tags = TAGS
+                .choose_multiple(&mut rng, tag_count)
+                .map(|t| t.to_string())
+                .collect();
+            let notes = rng
+                .gen_bool(0.25)
+                .then(|| NOTES.choose(&mut rng).unwrap().to_string());
+
+            let created_at = now - Duration::days(rng.gen_range(30..1500));
+            let updated_at = created_at + Duration::days(rng.gen_range(0..30));
+
+            DecryptedPasswordEntry {
+                id: hash_entry_id(&site, &username),
+                password: PasswordGenerator::generate(&config)
+                    .expect("default password config is valid"),
+                site,
+                username,
+                notes,
+                tags,
+                created_at,
+                updated_at,
+                last_used: rng.gen_bool(0.6).then(|| now - Duration::days(rng.gen_range(0..60))),
+                password_changed_at: updated_at,
+                favorite: rng.gen_bool(0.1),
+                urls: Vec::new(),
+                created_by: None,
+                modified_by: None,
+                totp_secret: None,
+                password_history: Vec::new(),
+                require_reauth: false,
+                password_policy: None,
+            }
+        })
+        .collect()
+}
@@ -107,6 +107,24 @@ pub enum ValidationRuleType {
     Custom(String), // Custom validation function name
 }
 
+impl ValidationRule {
+    /// Whether `value`, the value of `field_name` if it has one, passes this
+    /// rule. `has_value` tells whether another field has a value, for
+    /// `RequiredIf`. Custom rules always pass.
+    pub fn accepts(&self, value: Option<&str>, has_value: impl Fn(&str) -> bool) -> bool {
+        match (&self.rule_type, value) {
+            (ValidationRuleType::MinLength(min_len), Some(value)) => value.len() >= *min_len,
+            (ValidationRuleType::MaxLength(max_len), Some(value)) => value.len() <= *max_len,
+            (ValidationRuleType::Pattern(pattern), Some(value)) => {
+                regex::Regex::new(pattern).map_or(true, |regex| regex.is_match(value))
+            }
+            (ValidationRuleType::RequiredIf(required_field), None) => !has_value(required_field),
+            (ValidationRuleType::OneOf(valid_values), Some(value)) => valid_values.iter().any(|v| v == value),
+            _ => true,
+        }
+    }
+}
+
 /// Manager for secret templates
 pub struct SecretTemplateManager;
 
@@ -211,47 +229,9 @@ impl SecretTemplateManager {
         field_values: &HashMap<String, String>,
     ) -> Result<()> {
         for rule in &template.validation_rules {
-            let field_value = field_values.get(&rule.field_name);
-            
-            match &rule.rule_type {
-                ValidationRuleType::MinLength(min_len) => {
-                    if let Some(value) = field_value {
-                        if value.len() < *min_len {
-                            return Err(Error::Other(rule.message.clone()));
-                        }
-                    }
-                }
-                ValidationRuleType::MaxLength(max_len) => {
-                    if let Some(value) = field_value {
-                        if value.len() > *max_len {
-                            return Err(Error::Other(rule.message.clone()));
-                        }
-                    }
-                }
-                ValidationRuleType::Pattern(pattern) => {
-                    if let Some(value) = field_value {
-                        if let Ok(regex) = regex::Regex::new(pattern) {
-                            if !regex.is_match(value) {
-                                return Err(Error::Other(rule.message.clone()));
-                            }
-                        }
-                    }
-                }
-                ValidationRuleType::RequiredIf(required_field) => {
-                    if field_values.contains_key(required_field) && field_value.is_none() {
-                        return Err(Error::Other(rule.message.clone()));
-                    }
-                }
-                ValidationRuleType::OneOf(valid_values) => {
-                    if let Some(value) = field_value {
-                        if !valid_values.contains(value) {
-                            return Err(Error::Other(rule.message.clone()));
-                        }
-                    }
-                }
-                ValidationRuleType::Custom(_) => {
-                    // Custom validation would be implemented based on specific needs
-                }
+            let field_value = field_values.get(&rule.field_name).map(String::as_str);
+            if !rule.accepts(field_value, |field| field_values.contains_key(field)) {
+                return Err(Error::Other(rule.message.clone()));
             }
         }
         Ok(())
@@ -301,6 +281,7 @@ impl SecretTemplateManager {
                     format: crate::secrets::ConfigFormat::EnvFile,
                     variables,
                     template: Some(template.name.clone()),
+                    raw_variables: HashMap::new(),
                 })
             }
             SecretType::SshKey => {
@@ -348,6 +329,7 @@ impl SecretTemplateManager {
                     content_type: field_values.get("content_type").cloned().unwrap_or_else(|| "text/plain".to_string()),
                     content: field_values.get("content").map(|c| c.as_bytes().to_vec()).unwrap_or_default(),
                     checksum: String::new(), // Will be calculated
+                    compression: Some(crate::document_storage::CompressionType::None),
                 })
             }
             SecretType::Token => {
@@ -1,9 +1,15 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 use zeroize::Zeroize;
 
-use crate::{crypto::MasterKey, Result};
+use crate::{
+    crypto::MasterKey,
+    document_storage::CompressionType,
+    models::SortOrder,
+    redact::{redacted, Redacted},
+    Error, Result,
+};
 
 /// Comprehensive secret entry that can store various types of sensitive data
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +28,23 @@ pub struct SecretEntry {
     pub favorite: bool,
 }
 
+/// A secret without its payload, for listing secrets without decrypting
+/// them. `SecretsStorage::get_secrets` fills in the data when it's needed.
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretSummary {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub secret_type: SecretType,
+    pub metadata: SecretMetadata,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_accessed: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub favorite: bool,
+}
+
 /// Decrypted version of a secret entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DecryptedSecretEntry {
@@ -68,12 +91,39 @@ pub enum SecretType {
     ConnectionString,
     /// Cloud service credentials
     CloudCredentials,
+    /// Payment cards
+    CreditCard,
     /// Custom secret type
     Custom(String),
 }
 
+/// Document content as base64 in JSON rather than an array of numbers.
+/// Arrays written by earlier versions are still read.
+mod base64_content {
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(content: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(content))
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Content {
+        Base64(String),
+        Bytes(Vec<u8>),
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        match Content::deserialize(deserializer)? {
+            Content::Base64(encoded) => STANDARD.decode(encoded).map_err(de::Error::custom),
+            Content::Bytes(bytes) => Ok(bytes),
+        }
+    }
+}
+
 /// The actual secret data (encrypted when stored)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum SecretData {
     Password {
         username: String,
@@ -117,13 +167,21 @@ pub enum SecretData {
     Document {
         filename: String,
         content_type: String,
+        #[serde(with = "base64_content")]
         content: Vec<u8>,
         checksum: String,
+        /// How `content` is stored. `None` for documents saved before this
+        /// was recorded, whose content is gzip when it starts like gzip.
+        #[serde(default)]
+        compression: Option<CompressionType>,
     },
     Configuration {
         format: ConfigFormat,
         variables: HashMap<String, String>,
         template: Option<String>,
+        /// Original `${VAR}` form of any variable whose value was interpolated
+        #[serde(default)]
+        raw_variables: HashMap<String, String>,
     },
     SecureNote {
         title: String,
@@ -155,12 +213,249 @@ pub enum SecretData {
         region: Option<String>,
         additional_config: HashMap<String, String>,
     },
+    CreditCard {
+        cardholder: String,
+        number: String,
+        expiry_month: u8,
+        expiry_year: u16,
+        cvv: Option<String>,
+        issuer: Option<String>,
+        billing_zip: Option<String>,
+    },
     Custom {
         schema: String,
         fields: HashMap<String, String>,
     },
 }
 
+/// Names of the entries in `map`, sorted, without their values.
+fn keys_only(map: &HashMap<String, String>) -> Vec<&str> {
+    let mut keys: Vec<&str> = map.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    keys
+}
+
+impl fmt::Debug for SecretData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretData::Password { username, url, notes, .. } => f
+                .debug_struct("Password")
+                .field("username", username)
+                .field("password", &Redacted)
+                .field("url", url)
+                .field("notes", &redacted(notes))
+                .finish(),
+            SecretData::SshKey { key_type, private_key, public_key, passphrase, comment, fingerprint } => f
+                .debug_struct("SshKey")
+                .field("key_type", key_type)
+                .field("private_key", &redacted(private_key))
+                .field("public_key", public_key)
+                .field("passphrase", &redacted(passphrase))
+                .field("comment", comment)
+                .field("fingerprint", fingerprint)
+                .finish(),
+            SecretData::ApiKey {
+                provider,
+                key_id,
+                api_secret,
+                token_type,
+                permissions,
+                environment,
+                endpoint_url,
+                rotation_info,
+                usage_stats,
+                ..
+            } => f
+                .debug_struct("ApiKey")
+                .field("provider", provider)
+                .field("key_id", key_id)
+                .field("api_key", &Redacted)
+                .field("api_secret", &redacted(api_secret))
+                .field("token_type", token_type)
+                .field("permissions", permissions)
+                .field("environment", environment)
+                .field("endpoint_url", endpoint_url)
+                .field("rotation_info", rotation_info)
+                .field("usage_stats", usage_stats)
+                .finish(),
+            SecretData::Token {
+                token_type,
+                refresh_token,
+                token_secret,
+                expires_at,
+                issued_at,
+                issuer,
+                audience,
+                subject,
+                scopes,
+                claims,
+                ..
+            } => f
+                .debug_struct("Token")
+                .field("token_type", token_type)
+                .field("access_token", &Redacted)
+                .field("refresh_token", &redacted(refresh_token))
+                .field("token_secret", &redacted(token_secret))
+                .field("expires_at", expires_at)
+                .field("issued_at", issued_at)
+                .field("issuer", issuer)
+                .field("audience", audience)
+                .field("subject", subject)
+                .field("scopes", scopes)
+                .field("claims", &keys_only(claims))
+                .finish(),
+            SecretData::Document { filename, content_type, content, checksum, compression } => f
+                .debug_struct("Document")
+                .field("filename", filename)
+                .field("content_type", content_type)
+                .field("content", &format_args!("<{} bytes>", content.len()))
+                .field("checksum", checksum)
+                .field("compression", compression)
+                .finish(),
+            SecretData::Configuration { format, variables, template, raw_variables } => f
+                .debug_struct("Configuration")
+                .field("format", format)
+                .field("variables", &keys_only(variables))
+                .field("template", template)
+                .field("raw_variables", &keys_only(raw_variables))
+                .finish(),
+            SecretData::SecureNote { title, format, .. } => f
+                .debug_struct("SecureNote")
+                .field("title", title)
+                .field("content", &Redacted)
+                .field("format", format)
+                .finish(),
+            SecretData::Certificate { cert_type, certificate, private_key, ca_chain, subject, issuer } => f
+                .debug_struct("Certificate")
+                .field("cert_type", cert_type)
+                .field("certificate", certificate)
+                .field("private_key", &redacted(private_key))
+                .field("ca_chain", ca_chain)
+                .field("subject", subject)
+                .field("issuer", issuer)
+                .finish(),
+            SecretData::ConnectionString { database_type, host, port, database, username, ssl_config, .. } => f
+                .debug_struct("ConnectionString")
+                .field("database_type", database_type)
+                .field("host", host)
+                .field("port", port)
+                .field("database", database)
+                .field("username", username)
+                .field("password", &Redacted)
+                .field("connection_string", &Redacted)
+                .field("ssl_config", ssl_config)
+                .finish(),
+            SecretData::CloudCredentials { provider, access_key, region, additional_config, .. } => f
+                .debug_struct("CloudCredentials")
+                .field("provider", provider)
+                .field("access_key", access_key)
+                .field("secret_key", &Redacted)
+                .field("region", region)
+                .field("additional_config", &keys_only(additional_config))
+                .finish(),
+            SecretData::CreditCard { cardholder, expiry_month, expiry_year, cvv, issuer, billing_zip, .. } => f
+                .debug_struct("CreditCard")
+                .field("cardholder", cardholder)
+                .field("number", &Redacted)
+                .field("expiry_month", expiry_month)
+                .field("expiry_year", expiry_year)
+                .field("cvv", &redacted(cvv))
+                .field("issuer", issuer)
+                .field("billing_zip", &redacted(billing_zip))
+                .finish(),
+            SecretData::Custom { schema, fields } => f
+                .debug_struct("Custom")
+                .field("schema", schema)
+                .field("fields", &keys_only(fields))
+                .finish(),
+        }
+    }
+}
+
+impl SecretData {
+    /// The main secret value, e.g. the password of a `Password` or the key of
+    /// an `ApiKey`. `Debug` output always masks it. Returns `None` for types
+    /// without a single textual secret, such as documents and configurations.
+    pub fn reveal(&self) -> Option<&str> {
+        match self {
+            SecretData::Password { password, .. } => Some(password),
+            SecretData::SshKey { private_key, .. } => private_key.as_deref(),
+            SecretData::ApiKey { api_key, .. } => Some(api_key),
+            SecretData::Token { access_token, .. } => Some(access_token),
+            SecretData::SecureNote { content, .. } => Some(content),
+            SecretData::Certificate { private_key, .. } => private_key.as_deref(),
+            SecretData::ConnectionString { connection_string, .. } => Some(connection_string),
+            SecretData::CloudCredentials { secret_key, .. } => Some(secret_key),
+            SecretData::CreditCard { number, .. } => Some(number),
+            SecretData::Document { .. } | SecretData::Configuration { .. } | SecretData::Custom { .. } => None,
+        }
+    }
+
+    /// Check that the data is structurally usable: required values are
+    /// present, API keys match their provider's format and documents match
+    /// their checksum.
+    pub fn validate(&self) -> Result<()> {
+        let require = |value: &str, what: &str| {
+            if value.is_empty() {
+                Err(Error::Other(format!("{} is empty", what)))
+            } else {
+                Ok(())
+            }
+        };
+
+        match self {
+            SecretData::Password { password, .. } => require(password, "Password"),
+            SecretData::SshKey { private_key, public_key, .. } => {
+                if private_key.is_none() && public_key.is_none() {
+                    return Err(Error::Other("SSH key has neither a private nor a public key".to_string()));
+                }
+                Ok(())
+            }
+            SecretData::ApiKey { provider, api_key, .. } => {
+                require(api_key, "API key")?;
+                crate::api_keys::ApiKeyManager::validate_api_key_format(provider, api_key)
+            }
+            SecretData::Token { access_token, .. } => require(access_token, "Access token"),
+            SecretData::Document { .. } => {
+                if !crate::document_storage::DocumentManager::verify_document(self)? {
+                    return Err(Error::Other("Document checksum mismatch".to_string()));
+                }
+                Ok(())
+            }
+            SecretData::Configuration { variables, .. } => {
+                if variables.keys().any(|key| key.is_empty()) {
+                    return Err(Error::Other("Configuration has a variable without a name".to_string()));
+                }
+                Ok(())
+            }
+            SecretData::SecureNote { .. } | SecretData::Custom { .. } => Ok(()),
+            SecretData::Certificate { certificate, .. } => require(certificate, "Certificate"),
+            SecretData::ConnectionString { host, connection_string, .. } => {
+                if host.is_empty() && connection_string.is_empty() {
+                    return Err(Error::Other("Connection has neither a host nor a connection string".to_string()));
+                }
+                Ok(())
+            }
+            SecretData::CloudCredentials { access_key, secret_key, .. } => {
+                require(access_key, "Access key")?;
+                require(secret_key, "Secret key")
+            }
+            SecretData::CreditCard { number, expiry_month, cvv, .. } => {
+                if !crate::cards::luhn_check(number) {
+                    return Err(Error::Other("Card number is not valid".to_string()));
+                }
+                if !(1..=12).contains(expiry_month) {
+                    return Err(Error::Other(format!("Invalid expiry month: {}", expiry_month)));
+                }
+                if cvv.as_ref().is_some_and(|cvv| !(3..=4).contains(&cvv.len()) || !cvv.chars().all(|c| c.is_ascii_digit())) {
+                    return Err(Error::Other("CVV must be 3 or 4 digits".to_string()));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 impl Zeroize for SecretData {
     fn zeroize(&mut self) {
         match self {
@@ -220,17 +515,18 @@ impl Zeroize for SecretData {
                 }
                 claims.clear();
             }
-            SecretData::Document { filename, content_type, content, checksum } => {
+            SecretData::Document { filename, content_type, content, checksum, .. } => {
                 filename.zeroize();
                 content_type.zeroize();
                 content.zeroize();
                 checksum.zeroize();
             }
-            SecretData::Configuration { variables, template, .. } => {
-                for (_, value) in variables.iter_mut() {
+            SecretData::Configuration { variables, template, raw_variables, .. } => {
+                for (_, value) in variables.iter_mut().chain(raw_variables.iter_mut()) {
                     value.zeroize();
                 }
                 variables.clear();
+                raw_variables.clear();
                 if let Some(template) = template {
                     template.zeroize();
                 }
@@ -275,6 +571,13 @@ impl Zeroize for SecretData {
                 }
                 additional_config.clear();
             }
+            SecretData::CreditCard { cardholder, number, cvv, issuer, billing_zip, .. } => {
+                cardholder.zeroize();
+                number.zeroize();
+                for value in [cvv, issuer, billing_zip].into_iter().flatten() {
+                    value.zeroize();
+                }
+            }
             SecretData::Custom { schema, fields } => {
                 schema.zeroize();
                 for (_, value) in fields.iter_mut() {
@@ -347,7 +650,7 @@ pub enum DatabaseType {
 }
 
 /// SSL configuration for connections
-#[derive(Debug, Clone, Serialize, Deserialize, Zeroize)]
+#[derive(Clone, Serialize, Deserialize, Zeroize)]
 pub struct SslConfig {
     pub enabled: bool,
     pub verify_ssl: bool,
@@ -356,6 +659,18 @@ pub struct SslConfig {
     pub client_key: Option<String>,
 }
 
+impl fmt::Debug for SslConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SslConfig")
+            .field("enabled", &self.enabled)
+            .field("verify_ssl", &self.verify_ssl)
+            .field("ca_cert", &self.ca_cert)
+            .field("client_cert", &self.client_cert)
+            .field("client_key", &redacted(&self.client_key))
+            .finish()
+    }
+}
+
 /// Cloud service providers
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum CloudProvider {
@@ -381,6 +696,16 @@ pub struct SecretMetadata {
     pub team: Option<String>,
     pub compliance: Option<ComplianceInfo>,
     pub audit_log: Vec<AuditEntry>,
+    /// Device or user that added the secret, see `identity`.
+    #[serde(default)]
+    pub created_by: Option<String>,
+    /// Device or user that last changed the secret.
+    #[serde(default)]
+    pub modified_by: Option<String>,
+    /// Ask for the master password again before the secret's values are
+    /// revealed or copied
+    #[serde(default)]
+    pub require_reauth: bool,
 }
 
 /// Compliance and regulatory information
@@ -422,6 +747,25 @@ pub enum AuditAction {
     Deleted,
     Shared,
     PermissionsChanged,
+    /// Taken back out of the trash
+    Restored,
+}
+
+impl AuditAction {
+    /// Lower-case name used in exported audit events.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuditAction::Created => "created",
+            AuditAction::Updated => "updated",
+            AuditAction::Accessed => "accessed",
+            AuditAction::Copied => "copied",
+            AuditAction::Exported => "exported",
+            AuditAction::Deleted => "deleted",
+            AuditAction::Shared => "shared",
+            AuditAction::PermissionsChanged => "permissions_changed",
+            AuditAction::Restored => "restored",
+        }
+    }
 }
 
 /// Secret templates for common services
@@ -474,6 +818,40 @@ pub struct SecretFilter {
     pub expires_after: Option<DateTime<Utc>>,
     pub favorite_only: bool,
     pub classification: Option<DataClassification>,
+    #[serde(default)]
+    pub sort_by: SecretSortField,
+    #[serde(default)]
+    pub sort_order: SortOrder,
+}
+
+/// Sort key for `SecretFilter`, the secrets counterpart of `SortField`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum SecretSortField {
+    Name,
+    Type,
+    CreatedAt,
+    #[default]
+    UpdatedAt,
+    /// Secrets without an expiry date sort last in either order
+    ExpiresAt,
+}
+
+impl std::str::FromStr for SecretSortField {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "name" => Ok(SecretSortField::Name),
+            "type" => Ok(SecretSortField::Type),
+            "created" => Ok(SecretSortField::CreatedAt),
+            "updated" => Ok(SecretSortField::UpdatedAt),
+            "expires" => Ok(SecretSortField::ExpiresAt),
+            _ => Err(Error::Other(format!(
+                "Unknown sort field: {} (expected name, type, created, updated or expires)",
+                s
+            ))),
+        }
+    }
 }
 
 
@@ -0,0 +1,84 @@
+//! Overwrite-then-unlink deletion of files holding vault data.
+//!
+//! Overwriting is best effort: on copy-on-write or journaling filesystems and
+//! SSDs the old blocks can survive until the space is reused. The vault
+//! contents are encrypted either way; the overwrite removes the ciphertext
+//! from casual recovery tools.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::Result;
+
+/// Overwrite a regular file with zeros and flush it to disk. Symlinks and
+/// directories are left alone.
+pub fn shred(path: &Path) -> std::io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_file() {
+        return Ok(());
+    }
+    let mut file = fs::OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; 8192];
+    let mut remaining = metadata.len();
+    while remaining > 0 {
+        let chunk = remaining.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()
+}
+
+/// The vault database and the SQLite write-ahead log and shared-memory files
+/// that sit next to it.
+pub fn vault_files(vault_path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![vault_path.to_path_buf()];
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let mut sidecar = vault_path.as_os_str().to_owned();
+        sidecar.push(suffix);
+        files.push(PathBuf::from(sidecar));
+    }
+    files
+}
+
+/// Shred and remove the vault at `vault_path` together with its SQLite
+/// sidecar files. The vault must be closed first. Returns the files that
+/// were deleted.
+pub fn delete_vault(vault_path: &Path) -> Result<Vec<PathBuf>> {
+    let mut deleted = Vec::new();
+    for path in vault_files(vault_path) {
+        if fs::symlink_metadata(&path).is_err() {
+            continue;
+        }
+        shred(&path)?;
+        fs::remove_file(&path)?;
+        deleted.push(path);
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+
+    #[tokio::test]
+    async fn test_delete_vault_removes_database_and_sidecars() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        drop(storage);
+        fs::write(dir.path().join("vault.db-wal"), b"left over").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"unrelated").unwrap();
+
+        let deleted = delete_vault(&vault_path).unwrap();
+        assert!(deleted.contains(&vault_path));
+        assert!(deleted.contains(&dir.path().join("vault.db-wal")));
+        assert!(vault_files(&vault_path).iter().all(|path| !path.exists()));
+        assert!(dir.path().join("notes.txt").exists());
+
+        assert!(delete_vault(&vault_path).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+//! Ready-to-run command snippets for API keys and database connections.
+//!
+//! Each supported secret type has a command template with `{placeholder}`
+//! fields. Secrets are only substituted when the caller asks for them; the
+//! default output uses a visible placeholder such as `<API_KEY>`.
+
+use crate::{
+    api_keys::ApiKeyProvider,
+    env_connections::EnvConnectionManager,
+    secrets::{DatabaseType, SecretData},
+};
+
+const CURL_BEARER: &str = "curl -H {header} {endpoint}";
+const PSQL: &str = "PGPASSWORD={password} psql -h {host} -p {port} -U {username} {database}";
+const MYSQL: &str = "mysql -h {host} -P {port} -u {username} --password={password} {database}";
+const REDIS_CLI: &str = "redis-cli -h {host} -p {port} -a {password}";
+const MONGOSH: &str = "mongosh --host {host} --port {port} -u {username} -p {password} {database}";
+const SQLCMD: &str = "sqlcmd -S {server} -U {username} -P {password} -d {database}";
+const SQLITE3: &str = "sqlite3 {database}";
+
+/// A formatted command and the tool it runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandSnippet {
+    pub tool: &'static str,
+    pub command: String,
+}
+
+/// Build a command that uses `data` directly, e.g. a `curl` call for an API
+/// key or a `psql` invocation for a PostgreSQL connection.
+///
+/// Secrets are replaced by placeholders unless `reveal` is true. Returns
+/// `None` for secret types and databases without a template.
+pub fn command_snippet(data: &SecretData, reveal: bool) -> Option<CommandSnippet> {
+    match data {
+        SecretData::ApiKey { provider, api_key, endpoint_url, .. } => {
+            let secret = if reveal { api_key.as_str() } else { "<API_KEY>" };
+            let header = match provider {
+                ApiKeyProvider::Anthropic => format!("x-api-key: {}", secret),
+                _ => format!("Authorization: Bearer {}", secret),
+            };
+            let endpoint = endpoint_url.as_deref().unwrap_or("<ENDPOINT>");
+            let command = render(CURL_BEARER, &[("header", &header), ("endpoint", endpoint)]);
+            Some(CommandSnippet { tool: "curl", command })
+        }
+        SecretData::ConnectionString { database_type, host, port, database, username, password, .. } => {
+            let (tool, template) = match database_type {
+                DatabaseType::PostgreSQL => ("psql", PSQL),
+                DatabaseType::MySQL => ("mysql", MYSQL),
+                DatabaseType::Redis => ("redis-cli", REDIS_CLI),
+                DatabaseType::MongoDB => ("mongosh", MONGOSH),
+                DatabaseType::SQLServer => ("sqlcmd", SQLCMD),
+                DatabaseType::SQLite => ("sqlite3", SQLITE3),
+                DatabaseType::Oracle | DatabaseType::Custom(_) => return None,
+            };
+            let port = port
+                .unwrap_or_else(|| EnvConnectionManager::get_default_port(database_type))
+                .to_string();
+            let server = format!("{},{}", host, port);
+            let password = if reveal { password.as_str() } else { "<PASSWORD>" };
+            let command = render(
+                template,
+                &[
+                    ("host", host),
+                    ("port", &port),
+                    ("server", &server),
+                    ("username", username),
+                    ("password", password),
+                    ("database", database),
+                ],
+            );
+            Some(CommandSnippet { tool, command })
+        }
+        _ => None,
+    }
+}
+
+/// Substitute each `{name}` in `template` with its shell-quoted value. Done in
+/// a single pass so that values containing braces are never re-expanded.
+fn render(template: &str, fields: &[(&str, &str)]) -> String {
+    let mut command = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        command.push_str(&rest[..start]);
+        let end = start + rest[start..].find('}').expect("unterminated placeholder in template");
+        let name = &rest[start + 1..end];
+        let value = fields
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| *value)
+            .expect("unknown placeholder in template");
+        command.push_str(&shell_quote(value));
+        rest = &rest[end + 1..];
+    }
+    command.push_str(rest);
+    command
+}
+
+/// Quote `value` for a POSIX shell, leaving simple words unquoted.
+fn shell_quote(value: &str) -> String {
+    let is_plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:,@%+=".contains(c));
+    if is_plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_keys::{ApiKeyPermissions, RotationInfo, UsageStats};
+
+    fn api_key(provider: ApiKeyProvider) -> SecretData {
+        SecretData::ApiKey {
+            provider,
+            key_id: "key-1".to_string(),
+            api_key: "sk-live-123".to_string(),
+            api_secret: None,
+            token_type: "Bearer".to_string(),
+            permissions: ApiKeyPermissions::default(),
+            environment: "production".to_string(),
+            endpoint_url: Some("https://api.example.com/v1".to_string()),
+            rotation_info: RotationInfo::default(),
+            usage_stats: UsageStats::default(),
+        }
+    }
+
+    fn connection(database_type: DatabaseType, password: &str) -> SecretData {
+        SecretData::ConnectionString {
+            database_type,
+            host: "db.internal".to_string(),
+            port: None,
+            database: "app".to_string(),
+            username: "admin".to_string(),
+            password: password.to_string(),
+            connection_string: String::new(),
+            ssl_config: None,
+        }
+    }
+
+    #[test]
+    fn test_curl_snippet_hides_key_unless_revealed() {
+        let data = api_key(ApiKeyProvider::Stripe);
+
+        let hidden = command_snippet(&data, false).unwrap();
+        assert_eq!(hidden.tool, "curl");
+        assert_eq!(
+            hidden.command,
+            "curl -H 'Authorization: Bearer <API_KEY>' https://api.example.com/v1"
+        );
+        assert!(!hidden.command.contains("sk-live-123"));
+
+        let revealed = command_snippet(&data, true).unwrap();
+        assert!(revealed.command.contains("'Authorization: Bearer sk-live-123'"));
+    }
+
+    #[test]
+    fn test_database_snippets_use_default_ports_and_quote_passwords() {
+        let psql = command_snippet(&connection(DatabaseType::PostgreSQL, "it's secret"), true).unwrap();
+        assert_eq!(
+            psql.command,
+            r"PGPASSWORD='it'\''s secret' psql -h db.internal -p 5432 -U admin app"
+        );
+
+        let mysql = command_snippet(&connection(DatabaseType::MySQL, "pw"), false).unwrap();
+        assert_eq!(
+            mysql.command,
+            "mysql -h db.internal -P 3306 -u admin --password='<PASSWORD>' app"
+        );
+
+        assert!(command_snippet(&connection(DatabaseType::Oracle, "pw"), true).is_none());
+    }
+}
@@ -0,0 +1,129 @@
+//! A cheap snapshot of vault state for scripts and status bars.
+//!
+//! `VaultStatus::read` only looks at the unencrypted vault header and audit
+//! timestamps, so it needs no master password. Entry and secret counts are
+//! only reported once the caller has unlocked the vault, see
+//! `VaultStatus::unlocked`.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    crypto::KdfParams,
+    secrets_storage::SecretsStorage,
+    storage::{connect_options, Storage},
+    Result,
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    pub vault_path: PathBuf,
+    pub exists: bool,
+    pub locked: bool,
+    pub key_file_required: bool,
+    /// Only known when unlocked
+    pub entry_count: Option<usize>,
+    /// Only known when unlocked
+    pub secret_count: Option<usize>,
+    pub last_backup_at: Option<DateTime<Utc>>,
+    /// Time of the most recent secret audit log event
+    pub last_audit_at: Option<DateTime<Utc>>,
+    pub kdf: Option<KdfParams>,
+}
+
+impl VaultStatus {
+    /// Read the status of the vault at `vault_path` without unlocking it.
+    /// A missing vault is reported with `exists: false`, not as an error.
+    pub async fn read(vault_path: &Path) -> Result<Self> {
+        let mut status = Self {
+            vault_path: vault_path.to_path_buf(),
+            exists: vault_path.exists(),
+            locked: true,
+            key_file_required: false,
+            entry_count: None,
+            secret_count: None,
+            last_backup_at: None,
+            last_audit_at: None,
+            kdf: None,
+        };
+        if !status.exists {
+            return Ok(status);
+        }
+
+        let pool = SqlitePool::connect_with(connect_options(vault_path, false)).await?;
+        let metadata = Storage::load_metadata(&pool).await?;
+        status.key_file_required = metadata.key_file.is_some();
+        status.last_backup_at = metadata.last_backup_at;
+        status.kdf = Some(KdfParams::from_password_hash(&metadata.master_password_hash)?);
+        status.last_audit_at = last_audit_at(&pool).await?;
+        pool.close().await;
+
+        Ok(status)
+    }
+
+    /// Add the counts that are only reported for an unlocked vault.
+    pub async fn unlocked(mut self, storage: &Storage, secrets_storage: &SecretsStorage) -> Result<Self> {
+        self.locked = false;
+        self.entry_count = Some(storage.get_entry_count().await?);
+        self.secret_count = Some(secrets_storage.get_secrets_stats().await?.total_count);
+        Ok(self)
+    }
+}
+
+/// The secret audit log only exists once the vault has been opened for
+/// secrets.
+async fn last_audit_at(pool: &SqlitePool) -> Result<Option<DateTime<Utc>>> {
+    let has_log: bool = sqlx::query_scalar(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'secret_audit_log'"
+    )
+    .fetch_one(pool)
+    .await?;
+    if !has_log {
+        return Ok(None);
+    }
+
+    let latest: Option<String> = sqlx::query_scalar("SELECT MAX(timestamp) FROM secret_audit_log")
+        .fetch_one(pool)
+        .await?;
+    Ok(latest.and_then(|timestamp| timestamp.parse().ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::BackupManager;
+
+    #[tokio::test]
+    async fn test_vault_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+
+        let missing = VaultStatus::read(&vault_path).await.unwrap();
+        assert!(!missing.exists);
+        assert!(missing.kdf.is_none());
+
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        let secrets_storage = SecretsStorage::from_existing_storage(&vault_path, "master_password").await.unwrap();
+
+        let locked = VaultStatus::read(&vault_path).await.unwrap();
+        assert!(locked.exists && locked.locked);
+        assert_eq!(locked.kdf.as_ref().unwrap().algorithm, "argon2id");
+        assert!(locked.entry_count.is_none());
+        assert!(locked.last_backup_at.is_none());
+        assert!(!locked.key_file_required);
+
+        let backup_path = dir.path().join("vault.backup");
+        let backup = BackupManager::create_backup(&storage, None, &backup_path, "backup_password", |_| {})
+            .await
+            .unwrap();
+        let status = VaultStatus::read(&vault_path).await.unwrap();
+        assert_eq!(status.last_backup_at, Some(backup.created_at));
+
+        let unlocked = status.unlocked(&storage, &secrets_storage).await.unwrap();
+        assert!(!unlocked.locked);
+        assert_eq!(unlocked.entry_count, Some(0));
+        assert_eq!(unlocked.secret_count, Some(0));
+    }
+}
@@ -1,29 +1,339 @@
 use chrono::{DateTime, Utc};
-use sqlx::{sqlite::SqlitePool, Row};
-use std::path::Path;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqliteSynchronous},
+    Row,
+};
+use std::{collections::HashMap, path::Path};
+use zeroize::Zeroize;
 
 use crate::{
-    crypto::MasterKey,
-    models::{DecryptedPasswordEntry, PasswordEntry, SearchFilter, SortField, SortOrder, VaultMetadata},
+    backend::{matches_tags, search_terms, VaultBackend},
+    crypto::{hash_entry_id, Argon2Params, KeyFile, MasterKey, RecoveryKey},
+    integrity::{IntegrityReport, ItemKind},
+    models::{
+        normalize_site, DecryptedPasswordEntry, KeyFileInfo, PasswordEntry, PasswordHistoryEntry, RecoveryEnvelope,
+        SearchFilter, SortField, SortOrder, Trashed, VaultMetadata, DEFAULT_TRASH_RETENTION_DAYS,
+    },
+    totp::{site_matches_issuer, TotpImportReport, TotpSeed},
     Error, Result,
 };
 
-pub struct Storage {
-    pool: SqlitePool,
+/// Tag added to entries flagged as reusing another entry's password.
+pub const REUSED_PASSWORD_TAG: &str = "reused-password";
+
+/// Number of previous passwords kept per entry unless changed with
+/// `Storage::set_password_history_limit`.
+pub const DEFAULT_PASSWORD_HISTORY_LIMIT: usize = 10;
+
+/// Encrypted password vault.
+///
+/// Entries are encrypted with the master key before they reach the backend,
+/// which defaults to a SQLite database (`SqliteBackend`). Any other
+/// `VaultBackend` can be plugged in with `create_with_backend` or
+/// `open_with_backend`.
+pub struct Storage<B: VaultBackend = SqliteBackend> {
+    backend: B,
     master_key: MasterKey,
+    device_name: Option<String>,
+    password_history_limit: usize,
+    /// Held shared by writes and exclusively by maintenance (`vacuum`,
+    /// `integrity_check`), so the two never overlap.
+    maintenance: tokio::sync::RwLock<()>,
+}
+
+/// Check `password` and `key_file` against the vault header and derive the
+/// vault key from them.
+pub(crate) fn derive_vault_key(
+    metadata: &VaultMetadata,
+    password: &str,
+    key_file: Option<&KeyFile>,
+) -> Result<MasterKey> {
+    if !MasterKey::verify_password(password, &metadata.master_password_hash)? {
+        return Err(Error::InvalidMasterPassword);
+    }
+    
+    let params = Argon2Params::from_password_hash(&metadata.master_password_hash)?;
+    let master_key = MasterKey::derive_with_params(password, &metadata.salt, &params)?;
+    match (&metadata.key_file, key_file) {
+        (None, None) => Ok(master_key),
+        (Some(required), None) => Err(Error::KeyFileRequired(required.to_string())),
+        (Some(required), Some(key_file)) if key_file.info().id != required.id => {
+            Err(Error::WrongKeyFile(required.to_string()))
+        }
+        (Some(_), Some(key_file)) => master_key.with_key_file(key_file),
+        (None, Some(_)) => Err(Error::Other("This vault does not use a key file".to_string())),
+    }
+}
+
+/// Unwrap the vault key with `recovery_key`.
+fn recover_vault_key(metadata: &VaultMetadata, recovery_key: &RecoveryKey) -> Result<MasterKey> {
+    let envelope = metadata
+        .recovery
+        .as_ref()
+        .ok_or_else(|| Error::Other("This vault has no recovery key".to_string()))?;
+    let wrapping_key = recovery_key.wrapping_key(&envelope.salt)?;
+    let mut key = wrapping_key
+        .decrypt(&envelope.wrapped_vault_key)
+        .map_err(|_| Error::InvalidRecoveryKey)?;
+    let vault_key = MasterKey::from_bytes(&key);
+    key.zeroize();
+    vault_key
+}
+
+/// Wrap `vault_key` with `wrapping_key`, and the other way round so the
+/// envelope can follow the vault key when it changes.
+fn recovery_envelope(vault_key: &MasterKey, wrapping_key: &MasterKey, salt: Vec<u8>) -> Result<RecoveryEnvelope> {
+    Ok(RecoveryEnvelope {
+        salt,
+        wrapped_vault_key: wrapping_key.encrypt(vault_key.as_bytes())?,
+        wrapped_recovery_key: vault_key.encrypt(wrapping_key.as_bytes())?,
+    })
+}
+
+/// Connection options shared by every pool opened on a vault file.
+pub(crate) fn connect_options<P: AsRef<Path>>(path: P, create: bool) -> SqliteConnectOptions {
+    SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(create)
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Full)
 }
 
-impl Storage {
+/// Connect to an existing vault file, telling a missing file or one that is
+/// not a usable database apart from other failures.
+pub(crate) async fn connect_existing<P: AsRef<Path>>(path: P) -> Result<SqlitePool> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Err(Error::VaultNotFound(path.display().to_string()));
+    }
+    SqlitePool::connect_with(connect_options(path, false))
+        .await
+        .map_err(|e| corrupt_vault_error(e.into()))
+}
+
+/// `Error::VaultCorrupt` for SQLite reporting a damaged file, a file that is
+/// not a database or a database without the vault's tables; other errors
+/// unchanged.
+pub(crate) fn corrupt_vault_error(e: Error) -> Error {
+    // Primary result codes SQLITE_CORRUPT and SQLITE_NOTADB
+    const CORRUPT: i32 = 11;
+    const NOT_A_DATABASE: i32 = 26;
+    match e {
+        Error::Database(sqlx::Error::RowNotFound) => Error::VaultCorrupt("the vault header is missing".to_string()),
+        Error::Database(sqlx::Error::Database(db))
+            if db
+                .code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .is_some_and(|code| matches!(code & 0xff, CORRUPT | NOT_A_DATABASE))
+                || db.message().starts_with("no such table") =>
+        {
+            Error::VaultCorrupt(db.message().to_string())
+        }
+        e => e,
+    }
+}
+
+/// `ORDER BY` clause for the sort `filter` asks for.
+fn order_by(filter: &SearchFilter) -> String {
+    format!(
+        " ORDER BY {} {}",
+        match filter.sort_by {
+            SortField::Site => "site_normalized",
+            SortField::Username => "username",
+            SortField::CreatedAt => "created_at",
+            SortField::UpdatedAt => "updated_at",
+            SortField::LastUsed => "COALESCE(last_used, created_at)",
+        },
+        match filter.sort_order {
+            SortOrder::Ascending => "ASC",
+            SortOrder::Descending => "DESC",
+        }
+    )
+}
+
+/// Replace `pool` with a fresh one after a schema change. A connection that
+/// loaded the schema before columns were added keeps reporting the old
+/// column count for `SELECT *` once SQLite re-prepares the statement.
+pub(crate) async fn reconnect<P: AsRef<Path>>(pool: SqlitePool, path: P) -> Result<SqlitePool> {
+    pool.close().await;
+    Ok(SqlitePool::connect_with(connect_options(path, false)).await?)
+}
+
+impl Storage<SqliteBackend> {
     pub async fn create_new<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
-        let salt = MasterKey::generate_salt();
-        let master_key = MasterKey::derive_from_password(password, &salt)?;
-        let password_hash = MasterKey::hash_password_for_storage(password)?;
+        Self::create_new_with_key_file(path, password, None).await
+    }
+    
+    /// Create a vault that can only be unlocked with both `password` and
+    /// the contents of `key_file`, when one is given.
+    pub async fn create_new_with_key_file<P: AsRef<Path>>(
+        path: P,
+        password: &str,
+        key_file: Option<&KeyFile>,
+    ) -> Result<Self> {
+        let backend = SqliteBackend::create(path).await?;
+        Self::create_with_backend(backend, password, key_file).await
+    }
+    
+    pub async fn open<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        Self::open_with_key_file(path, password, None).await
+    }
+    
+    pub async fn open_with_key_file<P: AsRef<Path>>(
+        path: P,
+        password: &str,
+        key_file: Option<&KeyFile>,
+    ) -> Result<Self> {
+        Self::open_with_kdf_target(path, password, key_file, &Argon2Params::default()).await
+    }
+    
+    /// Open the vault, and if its key derivation parameters are weaker than
+    /// `target` in any respect, rehash the master password and re-encrypt
+    /// the vault under stronger ones. Only costs are raised, never lowered.
+    pub async fn open_with_kdf_target<P: AsRef<Path>>(
+        path: P,
+        password: &str,
+        key_file: Option<&KeyFile>,
+        target: &Argon2Params,
+    ) -> Result<Self> {
+        let backend = SqliteBackend::open(path).await?;
+        let mut storage = Self::open_with_backend(backend, password, key_file).await?;
         
-        // Ensure the file can be created with read-write-create mode
-        let db_url = format!("sqlite:{}?mode=rwc", path.as_ref().display());
-        let pool = SqlitePool::connect(&db_url).await?;
+        let metadata = storage.backend.load_metadata().await?;
+        let stored = Argon2Params::from_password_hash(&metadata.master_password_hash)?;
+        if stored.is_weaker_than(target) {
+            storage.rekey(metadata, password, key_file, &stored.max(target)).await?;
+        }
+        Ok(storage)
+    }
+    
+    pub async fn load_metadata(pool: &SqlitePool) -> Result<VaultMetadata> {
+        SqliteBackend::read_metadata(pool).await
+    }
+    
+    /// Check `password` against the stored master password hash, for
+    /// confirming the user before a sensitive action. The session key is
+    /// left as it is.
+    pub async fn verify_master_password(&self, password: &str) -> Result<bool> {
+        let metadata = self.backend.load_metadata().await?;
+        MasterKey::verify_password(password, &metadata.master_password_hash)
+    }
+    
+    /// Replace the master password. Entry passwords, drafts and secrets are
+    /// re-encrypted under the new key in a single transaction, so an
+    /// interrupted change leaves the vault on the old password. A vault with
+    /// a key file still requires it afterwards; `key_file` must be given for
+    /// such vaults.
+    ///
+    /// Other handles on the same vault file, such as a `SecretsStorage`,
+    /// hold the old key and must be reopened.
+    pub async fn change_master_password(
+        &mut self,
+        old_password: &str,
+        new_password: &str,
+        key_file: Option<&KeyFile>,
+    ) -> Result<()> {
+        let metadata = self.backend.load_metadata().await?;
+        derive_vault_key(&metadata, old_password, key_file)?;
+        let params = Argon2Params::from_password_hash(&metadata.master_password_hash)?;
+        self.rekey(metadata, new_password, key_file, &params).await
+    }
+    
+    /// Open the vault with its recovery key instead of the master password.
+    /// A forgotten master password should then be replaced with
+    /// `reset_master_password`.
+    pub async fn open_with_recovery_key<P: AsRef<Path>>(path: P, recovery_key: &RecoveryKey) -> Result<Self> {
+        let backend = SqliteBackend::open(path).await?;
+        Self::open_backend_with_recovery_key(backend, recovery_key).await
+    }
+    
+    /// Set a new master password without knowing the old one, after
+    /// unlocking with `open_with_recovery_key`. From then on the vault
+    /// requires `key_file` alongside the password, or no key file if `None`.
+    /// The recovery key keeps working.
+    pub async fn reset_master_password(&mut self, new_password: &str, key_file: Option<&KeyFile>) -> Result<()> {
+        let mut metadata = self.backend.load_metadata().await?;
+        metadata.key_file = key_file.map(KeyFile::info);
+        let params = Argon2Params::from_password_hash(&metadata.master_password_hash)?;
+        self.rekey(metadata, new_password, key_file, &params).await
+    }
+    
+    /// Derive a new key from `new_password` and `params` under a fresh salt
+    /// and re-encrypt everything with it. The recovery key, if any, is
+    /// rewrapped around the new key.
+    async fn rekey(
+        &mut self,
+        mut metadata: VaultMetadata,
+        new_password: &str,
+        key_file: Option<&KeyFile>,
+        params: &Argon2Params,
+    ) -> Result<()> {
+        let salt = MasterKey::generate_salt();
+        let mut new_key = MasterKey::derive_with_params(new_password, &salt, params)?;
+        if let Some(key_file) = key_file {
+            new_key = new_key.with_key_file(key_file)?;
+        }
+        metadata.master_password_hash = MasterKey::hash_password_with_params(new_password, params)?;
+        metadata.salt = salt;
+        metadata.updated_at = Utc::now();
+        if let Some(envelope) = metadata.recovery.take() {
+            let mut key = self.master_key.decrypt(&envelope.wrapped_recovery_key)?;
+            let wrapping_key = MasterKey::from_bytes(&key);
+            key.zeroize();
+            metadata.recovery = Some(recovery_envelope(&new_key, &wrapping_key?, envelope.salt)?);
+        }
         
-        Self::initialize_database(&pool).await?;
+        {
+            let _guard = self.maintenance.write().await;
+            self.backend.rekey(&metadata, &self.master_key, &new_key).await?;
+        }
+        self.master_key = new_key;
+        Ok(())
+    }
+    
+    /// Rebuild the database file with SQLite `VACUUM`, returning space left
+    /// by deleted rows to the file system. Writes through this storage wait
+    /// until it is done; other connections are kept out by SQLite's own
+    /// locking.
+    pub async fn vacuum(&self) -> Result<VacuumStats> {
+        let _guard = self.maintenance.write().await;
+        let bytes_before = self.backend.database_size().await?;
+        self.backend.vacuum().await?;
+        let bytes_after = self.backend.database_size().await?;
+        Ok(VacuumStats { bytes_before, bytes_after })
+    }
+    
+    /// Run SQLite's `PRAGMA integrity_check` over the whole database file.
+    /// `false` means the file is damaged and should be restored from a
+    /// backup; `verify_entries` checks the entries themselves.
+    pub async fn integrity_check(&self) -> Result<bool> {
+        let _guard = self.maintenance.write().await;
+        self.backend.integrity_check().await
+    }
+}
+
+impl<B: VaultBackend> Storage<B> {
+    /// Initialize a new vault in an empty backend.
+    pub async fn create_with_backend(backend: B, password: &str, key_file: Option<&KeyFile>) -> Result<Self> {
+        Self::create_with_kdf_params(backend, password, key_file, &Argon2Params::default()).await
+    }
+    
+    /// Initialize a new vault whose key is derived with `params`.
+    pub async fn create_with_kdf_params(
+        backend: B,
+        password: &str,
+        key_file: Option<&KeyFile>,
+        params: &Argon2Params,
+    ) -> Result<Self> {
+        let salt = MasterKey::generate_salt();
+        let mut master_key = MasterKey::derive_with_params(password, &salt, params)?;
+        if let Some(key_file) = key_file {
+            master_key = master_key.with_key_file(key_file)?;
+        }
+        let password_hash = MasterKey::hash_password_with_params(password, params)?;
         
         let vault_metadata = VaultMetadata {
             id: uuid::Uuid::new_v4().to_string(),
@@ -33,276 +343,350 @@ impl Storage {
             version: "1.0.0".to_string(),
             master_password_hash: password_hash,
             salt,
+            key_file: key_file.map(KeyFile::info),
+            last_backup_at: None,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            recovery: None,
         };
         
-        Self::save_metadata(&pool, &vault_metadata).await?;
+        backend.save_metadata(&vault_metadata).await?;
         
-        Ok(Self { pool, master_key })
+        Ok(Self {
+            backend,
+            master_key,
+            device_name: None,
+            password_history_limit: DEFAULT_PASSWORD_HISTORY_LIMIT,
+            maintenance: tokio::sync::RwLock::new(()),
+        })
     }
     
-    pub async fn open<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
-        let db_url = format!("sqlite:{}", path.as_ref().display());
-        let pool = SqlitePool::connect(&db_url).await?;
-        
-        let metadata = Self::load_metadata(&pool).await?;
-        
-        if !MasterKey::verify_password(password, &metadata.master_password_hash)? {
-            return Err(Error::InvalidMasterPassword);
+    /// Unlock an existing vault held by `backend`. Entries that have been
+    /// in the trash longer than the vault's retention period are purged.
+    pub async fn open_with_backend(backend: B, password: &str, key_file: Option<&KeyFile>) -> Result<Self> {
+        let metadata = backend.load_metadata().await?;
+        let master_key = derive_vault_key(&metadata, password, key_file)?;
+        Self::unlocked(backend, &metadata, master_key).await
+    }
+    
+    /// Unlock an existing vault held by `backend` with its recovery key. The
+    /// recovery key stands in for both the master password and any key file.
+    pub async fn open_backend_with_recovery_key(backend: B, recovery_key: &RecoveryKey) -> Result<Self> {
+        let metadata = backend.load_metadata().await?;
+        let master_key = recover_vault_key(&metadata, recovery_key)?;
+        Self::unlocked(backend, &metadata, master_key).await
+    }
+    
+    async fn unlocked(backend: B, metadata: &VaultMetadata, master_key: MasterKey) -> Result<Self> {
+        let storage = Self {
+            backend,
+            master_key,
+            device_name: None,
+            password_history_limit: DEFAULT_PASSWORD_HISTORY_LIMIT,
+            maintenance: tokio::sync::RwLock::new(()),
+        };
+        if metadata.trash_retention_days > 0 {
+            storage.purge_trash(chrono::Duration::days(metadata.trash_retention_days.into())).await?;
         }
-        
-        let master_key = MasterKey::derive_from_password(password, &metadata.salt)?;
-        
-        Ok(Self { pool, master_key })
+        Ok(storage)
     }
     
-    async fn initialize_database(pool: &SqlitePool) -> Result<()> {
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS vault_metadata (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                version TEXT NOT NULL,
-                master_password_hash TEXT NOT NULL,
-                salt BLOB NOT NULL
-            )
-            "#,
-        )
-        .execute(pool)
-        .await?;
-        
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS password_entries (
-                id TEXT PRIMARY KEY,
-                site TEXT NOT NULL,
-                username TEXT NOT NULL,
-                encrypted_password BLOB NOT NULL,
-                notes TEXT,
-                tags TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL,
-                last_used TEXT,
-                password_changed_at TEXT NOT NULL,
-                favorite INTEGER NOT NULL DEFAULT 0
-            )
-            "#,
-        )
-        .execute(pool)
-        .await?;
-        
-        sqlx::query(
-            r#"
-            CREATE INDEX IF NOT EXISTS idx_site ON password_entries(site);
-            CREATE INDEX IF NOT EXISTS idx_username ON password_entries(username);
-            CREATE INDEX IF NOT EXISTS idx_favorite ON password_entries(favorite);
-            CREATE INDEX IF NOT EXISTS idx_updated_at ON password_entries(updated_at);
-            "#,
-        )
-        .execute(pool)
-        .await?;
-        
-        Ok(())
+    pub fn backend(&self) -> &B {
+        &self.backend
     }
     
-    async fn save_metadata(pool: &SqlitePool, metadata: &VaultMetadata) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO vault_metadata (
-                id, name, created_at, updated_at, version, master_password_hash, salt
-            ) VALUES (?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&metadata.id)
-        .bind(&metadata.name)
-        .bind(metadata.created_at.to_rfc3339())
-        .bind(metadata.updated_at.to_rfc3339())
-        .bind(&metadata.version)
-        .bind(&metadata.master_password_hash)
-        .bind(&metadata.salt)
-        .execute(pool)
-        .await?;
-        
-        Ok(())
+    /// Generate a recovery key that can unlock the vault if the master
+    /// password is forgotten, replacing any earlier one. It is not stored
+    /// anywhere, so it has to be shown to the user now.
+    pub async fn enable_recovery_key(&self) -> Result<RecoveryKey> {
+        let _guard = self.maintenance.read().await;
+        let recovery_key = RecoveryKey::generate();
+        let salt = MasterKey::generate_salt();
+        let wrapping_key = recovery_key.wrapping_key(&salt)?;
+        let mut metadata = self.backend.load_metadata().await?;
+        metadata.recovery = Some(recovery_envelope(&self.master_key, &wrapping_key, salt)?);
+        self.backend.save_metadata(&metadata).await?;
+        Ok(recovery_key)
     }
     
-    pub async fn load_metadata(pool: &SqlitePool) -> Result<VaultMetadata> {
-        let row = sqlx::query(
-            r#"
-            SELECT id, name, created_at, updated_at, version, master_password_hash, salt
-            FROM vault_metadata
-            LIMIT 1
-            "#,
-        )
-        .fetch_one(pool)
-        .await?;
-        
-        Ok(VaultMetadata {
-            id: row.get("id"),
-            name: row.get("name"),
-            created_at: row.get::<String, _>("created_at").parse().map_err(|_| Error::Other("Invalid date format".to_string()))?,
-            updated_at: row.get::<String, _>("updated_at").parse().map_err(|_| Error::Other("Invalid date format".to_string()))?,
-            version: row.get("version"),
-            master_password_hash: row.get("master_password_hash"),
-            salt: row.get("salt"),
-        })
+    /// Stop the recovery key from unlocking the vault.
+    pub async fn disable_recovery_key(&self) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        let mut metadata = self.backend.load_metadata().await?;
+        metadata.recovery = None;
+        self.backend.save_metadata(&metadata).await
+    }
+    
+    pub async fn has_recovery_key(&self) -> Result<bool> {
+        Ok(self.backend.load_metadata().await?.recovery.is_some())
+    }
+    
+    /// Record `name` as `created_by`/`modified_by` on the entries this
+    /// storage adds or updates from now on. See `identity::load_device_name`.
+    pub fn set_device_name(&mut self, name: Option<String>) {
+        self.device_name = name;
+    }
+    
+    pub fn device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+    
+    /// Keep at most `limit` previous passwords per entry. Longer histories
+    /// are trimmed the next time their entry's password changes; 0 stops
+    /// recording history.
+    pub fn set_password_history_limit(&mut self, limit: usize) {
+        self.password_history_limit = limit;
+    }
+    
+    pub fn password_history_limit(&self) -> usize {
+        self.password_history_limit
+    }
+    
+    /// Key for naming the files of local caches such as `FaviconCache`, so
+    /// they don't list the vault's sites. Changes with the master password.
+    pub fn cache_naming_key(&self) -> Result<Vec<u8>> {
+        self.master_key.cache_naming_key()
     }
     
     pub async fn add_entry(&self, entry: &DecryptedPasswordEntry) -> Result<()> {
-        let encrypted_password = self.master_key.encrypt(entry.password.as_bytes())?;
-        let tags_json = serde_json::to_string(&entry.tags)?;
-        
-        sqlx::query(
-            r#"
-            INSERT INTO password_entries (
-                id, site, username, encrypted_password, notes, tags,
-                created_at, updated_at, last_used, password_changed_at, favorite
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(&entry.id)
-        .bind(&entry.site)
-        .bind(&entry.username)
-        .bind(&encrypted_password)
-        .bind(&entry.notes)
-        .bind(&tags_json)
-        .bind(entry.created_at.to_rfc3339())
-        .bind(entry.updated_at.to_rfc3339())
-        .bind(entry.last_used.map(|dt| dt.to_rfc3339()))
-        .bind(entry.password_changed_at.to_rfc3339())
-        .bind(entry.favorite as i32)
-        .execute(&self.pool)
-        .await?;
-        
-        Ok(())
+        self.add_entries(std::slice::from_ref(entry)).await
+    }
+    
+    /// Add many entries in a single transaction: either all are stored or
+    /// none are, and the cost of syncing to disk is paid once.
+    pub async fn add_entries(&self, entries: &[DecryptedPasswordEntry]) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        let encrypted = entries
+            .iter()
+            .map(|entry| {
+                let mut encrypted = self.encrypt_entry(entry)?;
+                // Entries restored or imported with an author keep it
+                encrypted.created_by = encrypted.created_by.or_else(|| self.device_name.clone());
+                encrypted.modified_by = self.device_name.clone().or(encrypted.modified_by);
+                Ok(encrypted)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.backend.insert_entries(&encrypted).await
     }
     
+    /// Store changes to an existing entry. When the password differs from
+    /// the stored one, the old password is added to the entry's history and
+    /// `password_changed_at` is set.
     pub async fn update_entry(&self, entry: &DecryptedPasswordEntry) -> Result<()> {
-        let encrypted_password = self.master_key.encrypt(entry.password.as_bytes())?;
-        let tags_json = serde_json::to_string(&entry.tags)?;
-        
-        sqlx::query(
-            r#"
-            UPDATE password_entries SET
-                site = ?, username = ?, encrypted_password = ?, notes = ?, tags = ?,
-                updated_at = ?, last_used = ?, password_changed_at = ?, favorite = ?
-            WHERE id = ?
-            "#,
-        )
-        .bind(&entry.site)
-        .bind(&entry.username)
-        .bind(&encrypted_password)
-        .bind(&entry.notes)
-        .bind(&tags_json)
-        .bind(Utc::now().to_rfc3339())
-        .bind(entry.last_used.map(|dt| dt.to_rfc3339()))
-        .bind(entry.password_changed_at.to_rfc3339())
-        .bind(entry.favorite as i32)
-        .bind(&entry.id)
-        .execute(&self.pool)
-        .await?;
+        let _guard = self.maintenance.read().await;
+        let stored = self
+            .backend
+            .get_entry(&entry.id)
+            .await?
+            .ok_or_else(|| Error::EntryNotFound(entry.id.clone()))?;
+        let stored = self.decrypt_entry(&stored)?;
         
-        Ok(())
+        let mut encrypted = if stored.password != entry.password {
+            let now = Utc::now();
+            let mut updated = entry.clone();
+            updated.password_history.insert(
+                0,
+                PasswordHistoryEntry { password: stored.password.clone(), changed_at: now },
+            );
+            // Restoring an old password moves it out of the history
+            updated.password_history.retain(|previous| previous.password != entry.password);
+            updated.password_history.truncate(self.password_history_limit);
+            updated.password_changed_at = now;
+            self.encrypt_entry(&updated)?
+        } else {
+            self.encrypt_entry(entry)?
+        };
+        encrypted.updated_at = Utc::now();
+        encrypted.modified_by = self.device_name.clone().or(encrypted.modified_by);
+        self.backend.update_entry(&encrypted).await
     }
     
     pub async fn get_entry(&self, id: &str) -> Result<DecryptedPasswordEntry> {
-        let entry = self.get_encrypted_entry(id).await?;
+        let entry = self
+            .backend
+            .get_entry(id)
+            .await?
+            .ok_or_else(|| Error::EntryNotFound(id.to_string()))?;
         self.decrypt_entry(&entry)
     }
     
-    async fn get_encrypted_entry(&self, id: &str) -> Result<PasswordEntry> {
-        let row = sqlx::query(
-            r#"
-            SELECT * FROM password_entries WHERE id = ?
-            "#,
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await?
-        .ok_or_else(|| Error::EntryNotFound(id.to_string()))?;
-        
-        self.row_to_entry(row)
+    /// Move the entry to the trash, from where `restore_entry` can bring it
+    /// back until it is purged.
+    pub async fn delete_entry(&self, id: &str) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        self.backend.trash_entry(id, Utc::now()).await
     }
     
-    pub async fn delete_entry(&self, id: &str) -> Result<()> {
-        sqlx::query("DELETE FROM password_entries WHERE id = ?")
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
-        
-        Ok(())
+    /// Add `tag` to every entry in `ids` that doesn't have it yet, in one
+    /// transaction. Returns how many entries changed.
+    pub async fn bulk_add_tag(&self, ids: &[String], tag: &str) -> Result<usize> {
+        let tag = Self::bulk_tag(tag)?;
+        self.bulk_update_tags(ids, |tags| {
+            if tags.iter().any(|existing| existing == tag) {
+                false
+            } else {
+                tags.push(tag.to_string());
+                true
+            }
+        })
+        .await
     }
     
-    pub async fn search_entries(&self, filter: &SearchFilter) -> Result<Vec<DecryptedPasswordEntry>> {
-        let mut query = String::from("SELECT * FROM password_entries WHERE 1=1");
-        let mut bindings = vec![];
-        
-        if let Some(search_query) = &filter.query {
-            query.push_str(" AND (site LIKE ? OR username LIKE ? OR notes LIKE ?)");
-            let like_query = format!("%{}%", search_query);
-            bindings.push(like_query.clone());
-            bindings.push(like_query.clone());
-            bindings.push(like_query);
-        }
-        
-        if filter.favorite_only {
-            query.push_str(" AND favorite = 1");
+    /// Remove `tag` from every entry in `ids`, in one transaction. Returns how
+    /// many entries changed.
+    pub async fn bulk_remove_tag(&self, ids: &[String], tag: &str) -> Result<usize> {
+        let tag = Self::bulk_tag(tag)?;
+        self.bulk_update_tags(ids, |tags| {
+            let before = tags.len();
+            tags.retain(|existing| existing != tag);
+            tags.len() != before
+        })
+        .await
+    }
+    
+    /// Move every entry in `ids` to the trash, in one transaction.
+    pub async fn bulk_delete(&self, ids: &[String]) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        self.backend.trash_entries(ids, Utc::now()).await
+    }
+    
+    fn bulk_tag(tag: &str) -> Result<&str> {
+        match tag.trim() {
+            "" => Err(Error::Other("Tag cannot be empty".to_string())),
+            tag => Ok(tag),
         }
-        
-        query.push_str(&format!(
-            " ORDER BY {} {}",
-            match filter.sort_by {
-                SortField::Site => "site",
-                SortField::Username => "username",
-                SortField::CreatedAt => "created_at",
-                SortField::UpdatedAt => "updated_at",
-                SortField::LastUsed => "COALESCE(last_used, created_at)",
-            },
-            match filter.sort_order {
-                SortOrder::Ascending => "ASC",
-                SortOrder::Descending => "DESC",
+    }
+    
+    /// Apply `update` to the tags of each entry in `ids` and store the ones
+    /// it reports as changed. Tags aren't encrypted, so nothing is decrypted.
+    async fn bulk_update_tags<F>(&self, ids: &[String], mut update: F) -> Result<usize>
+    where
+        F: FnMut(&mut Vec<String>) -> bool,
+    {
+        let _guard = self.maintenance.read().await;
+        let now = Utc::now();
+        let mut changed = Vec::new();
+        for id in ids {
+            let mut entry = self
+                .backend
+                .get_entry(id)
+                .await?
+                .ok_or_else(|| Error::EntryNotFound(id.clone()))?;
+            if update(&mut entry.tags) {
+                entry.updated_at = now;
+                entry.modified_by = self.device_name.clone().or(entry.modified_by);
+                changed.push(entry);
             }
-        ));
-        
-        let mut query_builder = sqlx::query(&query);
-        for binding in bindings {
-            query_builder = query_builder.bind(binding);
         }
-        
-        let rows = query_builder.fetch_all(&self.pool).await?;
-        let mut entries = Vec::new();
-        
-        for row in rows {
-            let entry = self.row_to_entry(row)?;
-            if let Some(tags) = &filter.tags {
-                let entry_tags: Vec<String> = serde_json::from_str(
-                    entry.tags.first().map(|s| s.as_str()).unwrap_or("[]")
-                )?;
-                if !tags.iter().any(|tag| entry_tags.contains(tag)) {
-                    continue;
-                }
-            }
-            entries.push(self.decrypt_entry(&entry)?);
+        self.backend.update_entries(&changed).await?;
+        Ok(changed.len())
+    }
+    
+    /// Entries in the trash, most recently deleted first.
+    pub async fn list_trashed(&self) -> Result<Vec<Trashed<DecryptedPasswordEntry>>> {
+        self.backend
+            .trashed_entries()
+            .await?
+            .iter()
+            .map(|(entry, deleted_at)| {
+                Ok(Trashed { item: self.decrypt_entry(entry)?, deleted_at: *deleted_at })
+            })
+            .collect()
+    }
+    
+    /// Take an entry out of the trash.
+    pub async fn restore_entry(&self, id: &str) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        if self.backend.restore_entry(id).await? {
+            Ok(())
+        } else {
+            Err(Error::EntryNotFound(id.to_string()))
         }
-        
-        Ok(entries)
     }
     
-    fn row_to_entry(&self, row: sqlx::sqlite::SqliteRow) -> Result<PasswordEntry> {
-        Ok(PasswordEntry {
-            id: row.get("id"),
-            site: row.get("site"),
-            username: row.get("username"),
-            encrypted_password: row.get("encrypted_password"),
-            notes: row.get("notes"),
-            tags: serde_json::from_str(row.get::<Option<String>, _>("tags").as_deref().unwrap_or("[]"))?,
-            created_at: row.get::<String, _>("created_at").parse().map_err(|_| Error::Other("Invalid date format".to_string()))?,
-            updated_at: row.get::<String, _>("updated_at").parse().map_err(|_| Error::Other("Invalid date format".to_string()))?,
-            last_used: row.get::<Option<String>, _>("last_used")
-                .and_then(|s| s.parse().ok()),
-            password_changed_at: row.get::<String, _>("password_changed_at").parse().map_err(|_| Error::Other("Invalid date format".to_string()))?,
-            favorite: row.get::<i32, _>("favorite") != 0,
+    /// Permanently remove the entries that have been in the trash for longer
+    /// than `older_than`; a zero duration empties the trash. Returns how many
+    /// were removed.
+    pub async fn purge_trash(&self, older_than: chrono::Duration) -> Result<usize> {
+        let _guard = self.maintenance.read().await;
+        self.backend.purge_trash(Utc::now() - older_than).await
+    }
+    
+    /// Days deleted items stay in the trash, see
+    /// `VaultMetadata::trash_retention_days`.
+    pub async fn trash_retention_days(&self) -> Result<u32> {
+        Ok(self.backend.load_metadata().await?.trash_retention_days)
+    }
+    
+    pub async fn set_trash_retention_days(&self, days: u32) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        let mut metadata = self.backend.load_metadata().await?;
+        metadata.trash_retention_days = days;
+        self.backend.save_metadata(&metadata).await
+    }
+    
+    pub async fn search_entries(&self, filter: &SearchFilter) -> Result<Vec<DecryptedPasswordEntry>> {
+        self.backend
+            .search_entries(filter)
+            .await?
+            .iter()
+            .map(|entry| self.decrypt_entry(entry))
+            .collect()
+    }
+    
+    /// Full-text search over site, username, notes and tags: every word of
+    /// `query` has to start a word in one of them, so `goo mail` finds
+    /// `mail.google.com`. Uses the vault's search index, so only matching
+    /// entries are read and decrypted. `filter.query` is ignored, and a query
+    /// without any words returns what `search_entries` would.
+    pub async fn search_fts(&self, query: &str, filter: &SearchFilter) -> Result<Vec<DecryptedPasswordEntry>> {
+        let terms = search_terms(query);
+        let entries = if terms.is_empty() {
+            self.backend.search_entries(&SearchFilter { query: None, ..filter.clone() }).await?
+        } else {
+            self.backend.full_text_search(&terms, filter).await?
+        };
+        entries.iter().map(|entry| self.decrypt_entry(entry)).collect()
+    }
+    
+    /// Entries with any URL matching `url`'s host, see
+    /// `DecryptedPasswordEntry::matches_url`.
+    pub async fn find_by_url(&self, url: &str) -> Result<Vec<DecryptedPasswordEntry>> {
+        let mut entries = self.search_entries(&SearchFilter::default()).await?;
+        entries.retain(|entry| entry.matches_url(url));
+        Ok(entries)
+    }
+    
+    fn encrypt_entry(&self, entry: &DecryptedPasswordEntry) -> Result<PasswordEntry> {
+        Ok(PasswordEntry {
+            id: entry.id.clone(),
+            site: entry.site.clone(),
+            username: entry.username.clone(),
+            encrypted_password: self.master_key.encrypt(entry.password.as_bytes())?,
+            notes: entry.notes.clone(),
+            tags: entry.tags.clone(),
+            created_at: entry.created_at,
+            updated_at: entry.updated_at,
+            last_used: entry.last_used,
+            password_changed_at: entry.password_changed_at,
+            favorite: entry.favorite,
+            urls: entry.urls.clone(),
+            created_by: entry.created_by.clone(),
+            modified_by: entry.modified_by.clone(),
+            encrypted_totp_secret: entry
+                .totp_secret
+                .as_ref()
+                .map(|secret| self.master_key.encrypt(secret.as_bytes()))
+                .transpose()?,
+            encrypted_password_history: if entry.password_history.is_empty() {
+                None
+            } else {
+                let mut json = serde_json::to_vec(&entry.password_history)?;
+                let encrypted = self.master_key.encrypt(&json);
+                json.zeroize();
+                Some(encrypted?)
+            },
+            require_reauth: entry.require_reauth,
+            password_policy: entry.password_policy.clone(),
         })
     }
     
@@ -310,6 +694,23 @@ impl Storage {
         let decrypted_password = self.master_key.decrypt(&entry.encrypted_password)?;
         let password = String::from_utf8(decrypted_password)
             .map_err(|_| Error::Decryption("Invalid UTF-8 in decrypted password".to_string()))?;
+        let totp_secret = match &entry.encrypted_totp_secret {
+            Some(encrypted) => Some(
+                String::from_utf8(self.master_key.decrypt(encrypted)?)
+                    .map_err(|_| Error::Decryption("Invalid UTF-8 in decrypted TOTP secret".to_string()))?,
+            ),
+            None => None,
+        };
+        let password_history = match &entry.encrypted_password_history {
+            Some(encrypted) => {
+                let mut json = self.master_key.decrypt(encrypted)?;
+                let history = serde_json::from_slice(&json)
+                    .map_err(|_| Error::Decryption("Invalid password history".to_string()));
+                json.zeroize();
+                history?
+            }
+            None => Vec::new(),
+        };
         
         Ok(DecryptedPasswordEntry {
             id: entry.id.clone(),
@@ -323,49 +724,54 @@ impl Storage {
             last_used: entry.last_used,
             password_changed_at: entry.password_changed_at,
             favorite: entry.favorite,
+            urls: entry.urls.clone(),
+            created_by: entry.created_by.clone(),
+            modified_by: entry.modified_by.clone(),
+            totp_secret,
+            password_history,
+            require_reauth: entry.require_reauth,
+            password_policy: entry.password_policy.clone(),
         })
     }
     
-    pub async fn mark_as_used(&self, id: &str) -> Result<()> {
-        sqlx::query(
-            r#"
-            UPDATE password_entries SET last_used = ? WHERE id = ?
-            "#,
-        )
-        .bind(Utc::now().to_rfc3339())
-        .bind(id)
-        .execute(&self.pool)
-        .await?;
-        
-        Ok(())
+    /// Record that the entry was just used (copied, opened or revealed).
+    /// Only `last_used` changes, so the entry doesn't look edited.
+    pub async fn touch_entry(&self, id: &str) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        self.backend.mark_as_used(id, Utc::now()).await
+    }
+    
+    /// Add the entry to or remove it from the favorites.
+    pub async fn set_favorite(&self, id: &str, favorite: bool) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        if self.backend.set_favorite(id, favorite, Utc::now()).await? {
+            Ok(())
+        } else {
+            Err(Error::EntryNotFound(id.to_string()))
+        }
     }
     
     /// Get vault metadata for backup purposes
     pub async fn get_vault_metadata(&self) -> Result<VaultMetadata> {
-        Self::load_metadata(&self.pool).await
+        self.backend.load_metadata().await
+    }
+    
+    /// Remember that a backup was written at `at`.
+    pub async fn record_backup(&self, at: DateTime<Utc>) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        let mut metadata = self.backend.load_metadata().await?;
+        metadata.last_backup_at = Some(at);
+        self.backend.save_metadata(&metadata).await
     }
     
     /// Get entries modified since a specific date (for incremental backups)
     pub async fn get_entries_since(&self, since: DateTime<Utc>) -> Result<Vec<DecryptedPasswordEntry>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT * FROM password_entries 
-            WHERE updated_at > ? OR created_at > ?
-            ORDER BY updated_at DESC
-            "#,
-        )
-        .bind(since.to_rfc3339())
-        .bind(since.to_rfc3339())
-        .fetch_all(&self.pool)
-        .await?;
-        
-        let mut entries = Vec::new();
-        for row in rows {
-            let entry = self.row_to_entry(row)?;
-            entries.push(self.decrypt_entry(&entry)?);
-        }
-        
-        Ok(entries)
+        self.backend
+            .entries_since(since)
+            .await?
+            .iter()
+            .map(|entry| self.decrypt_entry(entry))
+            .collect()
     }
     
     /// Add or update an entry (for restore operations)
@@ -383,39 +789,2088 @@ impl Storage {
         }
     }
     
+    /// Every tag in use with the number of entries carrying it, most used
+    /// first, then by name. Tags are not encrypted, so nothing is decrypted.
+    pub async fn tag_counts(&self) -> Result<Vec<(String, usize)>> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for entry in self.backend.search_entries(&SearchFilter::default()).await? {
+            for tag in entry.tags {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(counts)
+    }
+    
+    /// Entries that share their password with at least one other entry,
+    /// grouped by password: the largest groups first, each sorted by site.
+    ///
+    /// Passwords are compared by an HMAC under a key that only lives for the
+    /// duration of the call, so no comparable digest of them outlives it.
+    pub async fn find_duplicate_passwords(&self) -> Result<Vec<Vec<DecryptedPasswordEntry>>> {
+        group_duplicate_passwords(self.search_entries(&SearchFilter::default()).await?)
+    }
+    
+    /// Entries for the same site and username, grouped: sites are compared
+    /// with `normalize_site` and usernames case-insensitively. Each group is
+    /// sorted by when the entry was created, oldest first.
+    pub async fn find_duplicate_entries(&self) -> Result<Vec<Vec<DecryptedPasswordEntry>>> {
+        let mut groups: HashMap<(String, String), Vec<DecryptedPasswordEntry>> = HashMap::new();
+        for entry in self.search_entries(&SearchFilter::default()).await? {
+            let key = (normalize_site(&entry.site), entry.username.to_lowercase());
+            groups.entry(key).or_default().push(entry);
+        }
+        let mut duplicates: Vec<_> = groups
+            .into_values()
+            .filter(|group| group.len() >= 2)
+            .map(|mut group| {
+                group.sort_by_key(|entry| entry.created_at);
+                group
+            })
+            .collect();
+        duplicates.sort_by_key(|group| normalize_site(&group[0].site));
+        Ok(duplicates)
+    }
+
+    /// The merges `merge_duplicates` would make, without changing anything.
+    pub async fn plan_duplicate_merges(&self, strategy: MergeStrategy) -> Result<MergeReport> {
+        let mut report = MergeReport::default();
+        for group in self.find_duplicate_entries().await? {
+            let sets = match strategy {
+                MergeStrategy::AnyPassword => vec![group],
+                MergeStrategy::SamePassword => {
+                    let mut sets: Vec<Vec<DecryptedPasswordEntry>> = Vec::new();
+                    for entry in group {
+                        match sets.iter_mut().find(|set| set[0].password == entry.password) {
+                            Some(set) => set.push(entry),
+                            None => sets.push(vec![entry]),
+                        }
+                    }
+                    sets
+                }
+            };
+            let mut remaining = Vec::new();
+            for set in sets {
+                if set.len() < 2 {
+                    remaining.extend(set);
+                    continue;
+                }
+                let merge = merge_entries(set);
+                remaining.push(merge.merged.clone());
+                report.merges.push(merge);
+            }
+            if remaining.len() > 1 {
+                report.different_passwords.push(remaining);
+            }
+        }
+        Ok(report)
+    }
+    
+    /// Fold entries for the same site and username (see
+    /// `find_duplicate_entries`) into the most recently updated one, which
+    /// gains the tags, notes, URLs and password history of the others. The
+    /// others go to the trash. All merges are stored in one transaction.
+    pub async fn merge_duplicates(&self, strategy: MergeStrategy) -> Result<MergeReport> {
+        let report = self.plan_duplicate_merges(strategy).await?;
+        let _guard = self.maintenance.read().await;
+        let now = Utc::now();
+        let mut updated = Vec::new();
+        let mut trashed = Vec::new();
+        for merge in &report.merges {
+            let mut encrypted = self.encrypt_entry(&merge.merged)?;
+            encrypted.updated_at = now;
+            encrypted.modified_by = self.device_name.clone().or(encrypted.modified_by);
+            updated.push(encrypted);
+            trashed.extend(merge.removed.iter().map(|entry| entry.id.clone()));
+        }
+        self.backend.update_and_trash_entries(&updated, &trashed, now).await?;
+        Ok(report)
+    }
+    
+    /// Add imported two-factor seeds to the vault. A seed goes to the entry
+    /// for its issuer (see `site_matches_issuer`), narrowed down by username
+    /// when several match; without one, a new entry without a password is
+    /// created. Entries that already have a different seed are left alone.
+    pub async fn import_totp_seeds(&self, seeds: &[TotpSeed]) -> Result<TotpImportReport> {
+        let mut entries = self.search_entries(&SearchFilter::default()).await?;
+        let mut report = TotpImportReport::default();
+        for seed in seeds {
+            let label = format!("{} @ {}", seed.account, seed.site());
+            let stored = seed.stored_secret();
+            let mut candidates: Vec<usize> = (0..entries.len())
+                .filter(|&i| seed.issuer.as_deref().is_some_and(|issuer| site_matches_issuer(&entries[i].site, issuer)))
+                .collect();
+            if candidates.len() > 1 {
+                candidates.retain(|&i| entries[i].username.eq_ignore_ascii_case(&seed.account));
+            }
+
+            match candidates[..] {
+                [i] => {
+                    let entry = &mut entries[i];
+                    match &entry.totp_secret {
+                        Some(existing) if *existing == stored => {
+                            report.skipped.push((label, "already imported".to_string()));
+                        }
+                        Some(_) => {
+                            report.skipped.push((label, format!("{} @ {} already has a 2FA secret", entry.username, entry.site)));
+                        }
+                        None => {
+                            entry.totp_secret = Some(stored);
+                            self.update_entry(entry).await?;
+                            report.attached.push(format!("{} @ {}", entry.username, entry.site));
+                        }
+                    }
+                }
+                [] => {
+                    let now = Utc::now();
+                    let entry = DecryptedPasswordEntry {
+                        id: hash_entry_id(seed.site(), &seed.account),
+                        site: seed.site().to_string(),
+                        username: seed.account.clone(),
+                        password: String::new(),
+                        notes: None,
+                        tags: Vec::new(),
+                        created_at: now,
+                        updated_at: now,
+                        last_used: None,
+                        password_changed_at: now,
+                        favorite: false,
+                        urls: Vec::new(),
+                        created_by: None,
+                        modified_by: None,
+                        totp_secret: Some(stored),
+                        password_history: Vec::new(),
+                        require_reauth: false,
+                        password_policy: None,
+                    };
+                    self.add_entry(&entry).await?;
+                    entries.push(entry);
+                    report.created.push(label);
+                }
+                _ => {
+                    report.skipped.push((label, format!("matches {} entries", candidates.len())));
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Tag the entries in `ids` with `REUSED_PASSWORD_TAG`, for a reused
+    /// password that is kept on one entry and should be changed on these.
+    /// Returns how many entries were newly tagged.
+    pub async fn flag_reused_passwords(&self, ids: &[String]) -> Result<usize> {
+        let mut flagged = 0;
+        for id in ids {
+            let mut entry = self.get_entry(id).await?;
+            if !entry.tags.iter().any(|tag| tag == REUSED_PASSWORD_TAG) {
+                entry.tags.push(REUSED_PASSWORD_TAG.to_string());
+                self.update_entry(&entry).await?;
+                flagged += 1;
+            }
+        }
+        Ok(flagged)
+    }
+    
     /// Get the total number of entries in the vault
     pub async fn get_entry_count(&self) -> Result<usize> {
-        let row = sqlx::query("SELECT COUNT(*) as count FROM password_entries")
-            .fetch_one(&self.pool)
-            .await?;
-        
-        Ok(row.get::<i64, _>("count") as usize)
+        self.backend.entry_count().await
     }
     
     /// Get vault statistics for backup metadata
     pub async fn get_vault_stats(&self) -> Result<VaultStats> {
         let entry_count = self.get_entry_count().await?;
-        
-        let recent_row = sqlx::query(
-            "SELECT MAX(updated_at) as last_modified FROM password_entries"
-        )
-        .fetch_one(&self.pool)
-        .await?;
-        
-        let last_modified: Option<String> = recent_row.get("last_modified");
-        let last_modified = last_modified
-            .and_then(|s| s.parse().ok())
-            .unwrap_or_else(Utc::now);
+        let last_modified = self.backend.last_modified().await?.unwrap_or_else(Utc::now);
         
         Ok(VaultStats {
             entry_count,
             last_modified,
         })
     }
+    /// Decrypt every entry, recording failures in `report` instead of
+    /// stopping at the first one.
+    pub async fn verify_entries(&self, report: &mut IntegrityReport) -> Result<()> {
+        for entry in self.backend.search_entries(&SearchFilter::default()).await? {
+            report.entries_checked += 1;
+            match self.decrypt_entry(&entry) {
+                Ok(decrypted) if decrypted.password.is_empty() => {
+                    report.add_issue(ItemKind::PasswordEntry, &entry.id, &entry.site, "Password is empty");
+                }
+                Ok(_) => {}
+                Err(e) => report.add_issue(ItemKind::PasswordEntry, &entry.id, &entry.site, e),
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Save unsaved form contents under `name`, encrypted with the master key.
+    /// Drafts survive crashes and are meant to be offered back on next unlock.
+    pub async fn save_draft(&self, name: &str, contents: &str) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        let encrypted = self.master_key.encrypt(contents.as_bytes())?;
+        self.backend.save_draft(name, &encrypted).await
+    }
+    
+    pub async fn load_draft(&self, name: &str) -> Result<Option<String>> {
+        match self.backend.load_draft(name).await? {
+            Some(encrypted) => {
+                let decrypted = self.master_key.decrypt(&encrypted)?;
+                let contents = String::from_utf8(decrypted)
+                    .map_err(|_| Error::Decryption("Invalid UTF-8 in decrypted draft".to_string()))?;
+                Ok(Some(contents))
+            }
+            None => Ok(None),
+        }
+    }
+    
+    pub async fn clear_draft(&self, name: &str) -> Result<()> {
+        let _guard = self.maintenance.read().await;
+        self.backend.delete_draft(name).await
+    }
 }
 
 #[derive(Debug)]
 pub struct VaultStats {
     pub entry_count: usize,
     pub last_modified: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+/// Which duplicate entries `Storage::merge_duplicates` folds together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// Only entries with the same password
+    #[default]
+    SamePassword,
+    /// Entries with different passwords too. The merged entry keeps the
+    /// newest password and the others go into its history.
+    AnyPassword,
+}
+
+/// Duplicate entries folded into one.
+#[derive(Debug, Clone)]
+pub struct DuplicateMerge {
+    /// The most recently updated entry, with the tags, notes, URLs and
+    /// password history of the others added
+    pub merged: DecryptedPasswordEntry,
+    /// Entries moved to the trash
+    pub removed: Vec<DecryptedPasswordEntry>,
+}
+
+/// What `Storage::merge_duplicates` did, or would do.
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    pub merges: Vec<DuplicateMerge>,
+    /// Duplicates left apart because their passwords differ, one group per
+    /// site and username
+    pub different_passwords: Vec<Vec<DecryptedPasswordEntry>>,
+}
+
+/// Size of the database file before and after `Storage::vacuum`.
+#[derive(Debug, Clone, Copy)]
+pub struct VacuumStats {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// Default `VaultBackend`, storing the vault in a single SQLite file.
+///
+/// Durability: every connection runs in WAL mode with `synchronous = FULL`, so
+/// the WAL is fsynced before a commit is reported. Once `add_entry`,
+/// `update_entry` or any other write returns `Ok`, the change survives a crash
+/// or power loss. Each write runs in a single transaction, and so is applied
+/// atomically: an interrupted write leaves the previous data intact.
+pub struct SqliteBackend {
+    pool: SqlitePool,
+}
+
+impl SqliteBackend {
+    /// Create the vault file (if needed) and its schema.
+    pub async fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let pool = SqlitePool::connect_with(connect_options(&path, true)).await?;
+        Self::initialize_database(&pool).await?;
+        Ok(Self { pool: reconnect(pool, path).await? })
+    }
+    
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let pool = connect_existing(&path).await?;
+        // The header is read first so that files which aren't vaults are
+        // reported as such rather than failing part way through migrating
+        Self::read_metadata(&pool).await?;
+        Self::migrate(&pool).await.map_err(corrupt_vault_error)?;
+        Ok(Self { pool: reconnect(pool, path).await? })
+    }
+    
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+    
+    /// Size of the database in bytes, from its page count. Pages still in
+    /// the WAL aren't counted until they are checkpointed.
+    pub async fn database_size(&self) -> Result<u64> {
+        let page_count: i64 = sqlx::query_scalar("PRAGMA page_count").fetch_one(&self.pool).await?;
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(&self.pool).await?;
+        Ok((page_count * page_size) as u64)
+    }
+    
+    /// Checkpoint the WAL into the database file, then rebuild the file.
+    pub async fn vacuum(&self) -> Result<()> {
+        let mut connection = self.pool.acquire().await?;
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&mut *connection).await?;
+        sqlx::query("VACUUM").execute(&mut *connection).await?;
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&mut *connection).await?;
+        Ok(())
+    }
+    
+    /// Whether `PRAGMA integrity_check` finds the database file sound.
+    pub async fn integrity_check(&self) -> Result<bool> {
+        let results: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check").fetch_all(&self.pool).await?;
+        Ok(results.len() == 1 && results[0] == "ok")
+    }
+    
+    async fn initialize_database(pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS vault_metadata (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                version TEXT NOT NULL,
+                master_password_hash TEXT NOT NULL,
+                salt BLOB NOT NULL,
+                key_file_id TEXT,
+                key_file_name TEXT,
+                last_backup_at TEXT,
+                trash_retention_days INTEGER,
+                recovery_salt BLOB,
+                recovery_wrapped_vault_key BLOB,
+                recovery_wrapped_key BLOB
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS password_entries (
+                id TEXT PRIMARY KEY,
+                site TEXT NOT NULL,
+                username TEXT NOT NULL,
+                encrypted_password BLOB NOT NULL,
+                notes TEXT,
+                tags TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                last_used TEXT,
+                password_changed_at TEXT NOT NULL,
+                favorite INTEGER NOT NULL DEFAULT 0,
+                urls TEXT,
+                created_by TEXT,
+                modified_by TEXT,
+                encrypted_totp_secret BLOB,
+                encrypted_password_history BLOB,
+                deleted_at TEXT,
+                require_reauth INTEGER NOT NULL DEFAULT 0,
+                password_policy TEXT,
+                site_normalized TEXT NOT NULL DEFAULT ''
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_site ON password_entries(site);
+            CREATE INDEX IF NOT EXISTS idx_username ON password_entries(username);
+            CREATE INDEX IF NOT EXISTS idx_favorite ON password_entries(favorite);
+            CREATE INDEX IF NOT EXISTS idx_updated_at ON password_entries(updated_at);
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        
+        Self::migrate(pool).await
+    }
+    
+    /// Bring a vault created by an older version up to the current schema.
+    async fn migrate(pool: &SqlitePool) -> Result<()> {
+        let has_urls: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('password_entries') WHERE name = 'urls'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_urls {
+            sqlx::query("ALTER TABLE password_entries ADD COLUMN urls TEXT")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_created_by: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('password_entries') WHERE name = 'created_by'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_created_by {
+            sqlx::query("ALTER TABLE password_entries ADD COLUMN created_by TEXT")
+                .execute(pool)
+                .await?;
+            sqlx::query("ALTER TABLE password_entries ADD COLUMN modified_by TEXT")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_totp: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('password_entries') WHERE name = 'encrypted_totp_secret'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_totp {
+            sqlx::query("ALTER TABLE password_entries ADD COLUMN encrypted_totp_secret BLOB")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_password_history: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('password_entries') WHERE name = 'encrypted_password_history'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_password_history {
+            sqlx::query("ALTER TABLE password_entries ADD COLUMN encrypted_password_history BLOB")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_deleted_at: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('password_entries') WHERE name = 'deleted_at'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_deleted_at {
+            sqlx::query("ALTER TABLE password_entries ADD COLUMN deleted_at TEXT")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_require_reauth: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('password_entries') WHERE name = 'require_reauth'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_require_reauth {
+            sqlx::query("ALTER TABLE password_entries ADD COLUMN require_reauth INTEGER NOT NULL DEFAULT 0")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_password_policy: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('password_entries') WHERE name = 'password_policy'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_password_policy {
+            sqlx::query("ALTER TABLE password_entries ADD COLUMN password_policy TEXT")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_site_normalized: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('password_entries') WHERE name = 'site_normalized'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_site_normalized {
+            sqlx::query("ALTER TABLE password_entries ADD COLUMN site_normalized TEXT NOT NULL DEFAULT ''")
+                .execute(pool)
+                .await?;
+            let sites: Vec<(String, String)> = sqlx::query_as("SELECT id, site FROM password_entries")
+                .fetch_all(pool)
+                .await?;
+            for (id, site) in sites {
+                sqlx::query("UPDATE password_entries SET site_normalized = ? WHERE id = ?")
+                    .bind(normalize_site(&site))
+                    .bind(id)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_site_normalized ON password_entries(site_normalized)")
+            .execute(pool)
+            .await?;
+        
+        let has_key_file: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('vault_metadata') WHERE name = 'key_file_id'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_key_file {
+            sqlx::query("ALTER TABLE vault_metadata ADD COLUMN key_file_id TEXT")
+                .execute(pool)
+                .await?;
+            sqlx::query("ALTER TABLE vault_metadata ADD COLUMN key_file_name TEXT")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_last_backup: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('vault_metadata') WHERE name = 'last_backup_at'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_last_backup {
+            sqlx::query("ALTER TABLE vault_metadata ADD COLUMN last_backup_at TEXT")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_trash_retention: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('vault_metadata') WHERE name = 'trash_retention_days'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_trash_retention {
+            sqlx::query("ALTER TABLE vault_metadata ADD COLUMN trash_retention_days INTEGER")
+                .execute(pool)
+                .await?;
+        }
+        
+        let has_recovery: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('vault_metadata') WHERE name = 'recovery_salt'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_recovery {
+            for column in ["recovery_salt", "recovery_wrapped_vault_key", "recovery_wrapped_key"] {
+                sqlx::query(&format!("ALTER TABLE vault_metadata ADD COLUMN {} BLOB", column))
+                    .execute(pool)
+                    .await?;
+            }
+        }
+        
+        // Full-text index over the unencrypted entry columns, kept in sync by
+        // triggers. Vaults from before it existed get it built here.
+        let has_fts: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'password_entries_fts'"
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_fts {
+            sqlx::query(
+                r#"
+                CREATE VIRTUAL TABLE password_entries_fts USING fts5(id UNINDEXED, site, username, notes, tags);
+                INSERT INTO password_entries_fts (id, site, username, notes, tags)
+                    SELECT id, site, username, notes, tags FROM password_entries;
+                "#,
+            )
+            .execute(pool)
+            .await?;
+        }
+        sqlx::query(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS password_entries_fts_insert AFTER INSERT ON password_entries BEGIN
+                INSERT INTO password_entries_fts (id, site, username, notes, tags)
+                    VALUES (new.id, new.site, new.username, new.notes, new.tags);
+            END;
+            CREATE TRIGGER IF NOT EXISTS password_entries_fts_update
+            AFTER UPDATE OF id, site, username, notes, tags ON password_entries BEGIN
+                DELETE FROM password_entries_fts WHERE id = old.id;
+                INSERT INTO password_entries_fts (id, site, username, notes, tags)
+                    VALUES (new.id, new.site, new.username, new.notes, new.tags);
+            END;
+            CREATE TRIGGER IF NOT EXISTS password_entries_fts_delete AFTER DELETE ON password_entries BEGIN
+                DELETE FROM password_entries_fts WHERE id = old.id;
+            END;
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS drafts (
+                name TEXT PRIMARY KEY,
+                encrypted_data BLOB NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Tables holding data encrypted with the master key, with their key and
+    /// ciphertext columns. `secrets` only exists once `SecretsStorage` has
+    /// opened the vault.
+    const ENCRYPTED_COLUMNS: [(&'static str, &'static str, &'static str); 6] = [
+        ("password_entries", "id", "encrypted_password"),
+        ("password_entries", "id", "encrypted_totp_secret"),
+        ("password_entries", "id", "encrypted_password_history"),
+        ("drafts", "name", "encrypted_data"),
+        ("secrets", "id", "encrypted_data"),
+        ("team_identity", "member_id", "encrypted_private_key"),
+    ];
+    
+    /// Re-encrypt everything under `new_key` and store the new password hash,
+    /// salt, key file and recovery key from `metadata`, all in one
+    /// transaction.
+    async fn rekey(&self, metadata: &VaultMetadata, old_key: &MasterKey, new_key: &MasterKey) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for (table, id_column, data_column) in Self::ENCRYPTED_COLUMNS {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?"
+            )
+            .bind(table)
+            .fetch_one(&mut *tx)
+            .await?;
+            if !exists {
+                continue;
+            }
+            
+            let rows: Vec<(String, Vec<u8>)> =
+                sqlx::query_as(&format!(
+                    "SELECT {0}, {1} FROM {2} WHERE {1} IS NOT NULL",
+                    id_column, data_column, table
+                ))
+                    .fetch_all(&mut *tx)
+                    .await?;
+            for (id, ciphertext) in rows {
+                let mut plaintext = old_key.decrypt(&ciphertext)?;
+                let reencrypted = new_key.encrypt(&plaintext);
+                plaintext.zeroize();
+                sqlx::query(&format!("UPDATE {} SET {} = ? WHERE {} = ?", table, data_column, id_column))
+                    .bind(reencrypted?)
+                    .bind(&id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        
+        // Team members are keyed by (team_id, member_id), so they don't fit
+        // the single id column loop above
+        let has_team_members: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'team_members'"
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if has_team_members {
+            let rows: Vec<(String, String, Vec<u8>, Vec<u8>)> = sqlx::query_as(
+                "SELECT team_id, member_id, encrypted_email, encrypted_name FROM team_members"
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            for (team_id, member_id, email, name) in rows {
+                let mut email = old_key.decrypt(&email)?;
+                let mut name = old_key.decrypt(&name)?;
+                let reencrypted_email = new_key.encrypt(&email);
+                let reencrypted_name = new_key.encrypt(&name);
+                email.zeroize();
+                name.zeroize();
+                sqlx::query(
+                    "UPDATE team_members SET encrypted_email = ?, encrypted_name = ? WHERE team_id = ? AND member_id = ?"
+                )
+                .bind(reencrypted_email?)
+                .bind(reencrypted_name?)
+                .bind(&team_id)
+                .bind(&member_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        
+        sqlx::query(
+            r#"
+            UPDATE vault_metadata SET
+                master_password_hash = ?, salt = ?, updated_at = ?, key_file_id = ?, key_file_name = ?,
+                recovery_salt = ?, recovery_wrapped_vault_key = ?, recovery_wrapped_key = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&metadata.master_password_hash)
+        .bind(&metadata.salt)
+        .bind(metadata.updated_at.to_rfc3339())
+        .bind(metadata.key_file.as_ref().map(|k| &k.id))
+        .bind(metadata.key_file.as_ref().and_then(|k| k.name.as_ref()))
+        .bind(metadata.recovery.as_ref().map(|r| &r.salt))
+        .bind(metadata.recovery.as_ref().map(|r| &r.wrapped_vault_key))
+        .bind(metadata.recovery.as_ref().map(|r| &r.wrapped_recovery_key))
+        .bind(&metadata.id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        
+        Ok(())
+    }
+    
+    async fn write_metadata(pool: &SqlitePool, metadata: &VaultMetadata) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO vault_metadata (
+                id, name, created_at, updated_at, version, master_password_hash, salt,
+                key_file_id, key_file_name, last_backup_at, trash_retention_days,
+                recovery_salt, recovery_wrapped_vault_key, recovery_wrapped_key
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&metadata.id)
+        .bind(&metadata.name)
+        .bind(metadata.created_at.to_rfc3339())
+        .bind(metadata.updated_at.to_rfc3339())
+        .bind(&metadata.version)
+        .bind(&metadata.master_password_hash)
+        .bind(&metadata.salt)
+        .bind(metadata.key_file.as_ref().map(|k| &k.id))
+        .bind(metadata.key_file.as_ref().and_then(|k| k.name.as_ref()))
+        .bind(metadata.last_backup_at.map(|dt| dt.to_rfc3339()))
+        .bind(metadata.trash_retention_days)
+        .bind(metadata.recovery.as_ref().map(|r| &r.salt))
+        .bind(metadata.recovery.as_ref().map(|r| &r.wrapped_vault_key))
+        .bind(metadata.recovery.as_ref().map(|r| &r.wrapped_recovery_key))
+        .execute(pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn read_metadata(pool: &SqlitePool) -> Result<VaultMetadata> {
+        let row = sqlx::query(
+            r#"
+            SELECT *
+            FROM vault_metadata
+            LIMIT 1
+            "#,
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| corrupt_vault_error(e.into()))?;
+        
+        let invalid_date = |_| Error::VaultCorrupt("invalid date in the vault header".to_string());
+        Ok(VaultMetadata {
+            id: row.get("id"),
+            name: row.get("name"),
+            created_at: row.get::<String, _>("created_at").parse().map_err(invalid_date)?,
+            updated_at: row.get::<String, _>("updated_at").parse().map_err(invalid_date)?,
+            version: row.get("version"),
+            master_password_hash: row.get("master_password_hash"),
+            salt: row.get("salt"),
+            // Absent in vaults that haven't been migrated yet
+            key_file: row
+                .try_get::<Option<String>, _>("key_file_id")
+                .ok()
+                .flatten()
+                .map(|id| KeyFileInfo {
+                    id,
+                    name: row.try_get("key_file_name").ok().flatten(),
+                }),
+            last_backup_at: row
+                .try_get::<Option<String>, _>("last_backup_at")
+                .ok()
+                .flatten()
+                .and_then(|dt| dt.parse().ok()),
+            trash_retention_days: row
+                .try_get::<Option<u32>, _>("trash_retention_days")
+                .ok()
+                .flatten()
+                .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS),
+            recovery: match (
+                row.try_get::<Option<Vec<u8>>, _>("recovery_salt").ok().flatten(),
+                row.try_get::<Option<Vec<u8>>, _>("recovery_wrapped_vault_key").ok().flatten(),
+                row.try_get::<Option<Vec<u8>>, _>("recovery_wrapped_key").ok().flatten(),
+            ) {
+                (Some(salt), Some(wrapped_vault_key), Some(wrapped_recovery_key)) => {
+                    Some(RecoveryEnvelope { salt, wrapped_vault_key, wrapped_recovery_key })
+                }
+                _ => None,
+            },
+        })
+    }
+    
+    fn row_to_entry(row: sqlx::sqlite::SqliteRow) -> Result<PasswordEntry> {
+        Ok(PasswordEntry {
+            id: row.get("id"),
+            site: row.get("site"),
+            username: row.get("username"),
+            encrypted_password: row.get("encrypted_password"),
+            notes: row.get("notes"),
+            tags: serde_json::from_str(row.get::<Option<String>, _>("tags").as_deref().unwrap_or("[]"))?,
+            created_at: row.get::<String, _>("created_at").parse().map_err(|_| Error::Other("Invalid date format".to_string()))?,
+            updated_at: row.get::<String, _>("updated_at").parse().map_err(|_| Error::Other("Invalid date format".to_string()))?,
+            last_used: row.get::<Option<String>, _>("last_used")
+                .and_then(|s| s.parse().ok()),
+            password_changed_at: row.get::<String, _>("password_changed_at").parse().map_err(|_| Error::Other("Invalid date format".to_string()))?,
+            favorite: row.get::<i32, _>("favorite") != 0,
+            urls: serde_json::from_str(row.get::<Option<String>, _>("urls").as_deref().unwrap_or("[]"))?,
+            created_by: row.get("created_by"),
+            modified_by: row.get("modified_by"),
+            encrypted_totp_secret: row.get("encrypted_totp_secret"),
+            encrypted_password_history: row.get("encrypted_password_history"),
+            require_reauth: row.get::<i32, _>("require_reauth") != 0,
+            password_policy: row
+                .get::<Option<String>, _>("password_policy")
+                .map(|json| serde_json::from_str(&json))
+                .transpose()?,
+        })
+    }
+}
+
+impl VaultBackend for SqliteBackend {
+    async fn load_metadata(&self) -> Result<VaultMetadata> {
+        Self::read_metadata(&self.pool).await
+    }
+    
+    async fn save_metadata(&self, metadata: &VaultMetadata) -> Result<()> {
+        Self::write_metadata(&self.pool, metadata).await
+    }
+    
+    async fn insert_entries(&self, entries: &[PasswordEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for entry in entries {
+            sqlx::query("DELETE FROM password_entries WHERE id = ? AND deleted_at IS NOT NULL")
+                .bind(&entry.id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO password_entries (
+                    id, site, username, encrypted_password, notes, tags,
+                    created_at, updated_at, last_used, password_changed_at, favorite, urls,
+                    created_by, modified_by, encrypted_totp_secret, encrypted_password_history,
+                    require_reauth, password_policy, site_normalized
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(&entry.id)
+            .bind(&entry.site)
+            .bind(&entry.username)
+            .bind(&entry.encrypted_password)
+            .bind(&entry.notes)
+            .bind(serde_json::to_string(&entry.tags)?)
+            .bind(entry.created_at.to_rfc3339())
+            .bind(entry.updated_at.to_rfc3339())
+            .bind(entry.last_used.map(|dt| dt.to_rfc3339()))
+            .bind(entry.password_changed_at.to_rfc3339())
+            .bind(entry.favorite as i32)
+            .bind(serde_json::to_string(&entry.urls)?)
+            .bind(&entry.created_by)
+            .bind(&entry.modified_by)
+            .bind(&entry.encrypted_totp_secret)
+            .bind(&entry.encrypted_password_history)
+            .bind(entry.require_reauth as i32)
+            .bind(entry.password_policy.as_ref().map(serde_json::to_string).transpose()?)
+            .bind(normalize_site(&entry.site))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        
+        Ok(())
+    }
+    
+    async fn update_entry(&self, entry: &PasswordEntry) -> Result<()> {
+        self.update_entries(std::slice::from_ref(entry)).await
+    }
+    
+    async fn update_entries(&self, entries: &[PasswordEntry]) -> Result<()> {
+        self.update_and_trash_entries(entries, &[], Utc::now()).await
+    }
+    
+    async fn update_and_trash_entries(
+        &self,
+        updated: &[PasswordEntry],
+        trashed: &[String],
+        at: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for entry in updated {
+            sqlx::query(
+                r#"
+                UPDATE password_entries SET
+                    site = ?, username = ?, encrypted_password = ?, notes = ?, tags = ?,
+                    updated_at = ?, last_used = ?, password_changed_at = ?, favorite = ?, urls = ?,
+                    modified_by = ?, encrypted_totp_secret = ?, encrypted_password_history = ?,
+                    require_reauth = ?, password_policy = ?, site_normalized = ?
+                WHERE id = ? AND deleted_at IS NULL
+                "#,
+            )
+            .bind(&entry.site)
+            .bind(&entry.username)
+            .bind(&entry.encrypted_password)
+            .bind(&entry.notes)
+            .bind(serde_json::to_string(&entry.tags)?)
+            .bind(entry.updated_at.to_rfc3339())
+            .bind(entry.last_used.map(|dt| dt.to_rfc3339()))
+            .bind(entry.password_changed_at.to_rfc3339())
+            .bind(entry.favorite as i32)
+            .bind(serde_json::to_string(&entry.urls)?)
+            .bind(&entry.modified_by)
+            .bind(&entry.encrypted_totp_secret)
+            .bind(&entry.encrypted_password_history)
+            .bind(entry.require_reauth as i32)
+            .bind(entry.password_policy.as_ref().map(serde_json::to_string).transpose()?)
+            .bind(normalize_site(&entry.site))
+            .bind(&entry.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        for id in trashed {
+            sqlx::query("UPDATE password_entries SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL")
+                .bind(at.to_rfc3339())
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        
+        Ok(())
+    }
+    
+    async fn get_entry(&self, id: &str) -> Result<Option<PasswordEntry>> {
+        sqlx::query(
+            r#"
+            SELECT * FROM password_entries WHERE id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Self::row_to_entry)
+        .transpose()
+    }
+    
+    async fn delete_entry(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM password_entries WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    async fn trash_entry(&self, id: &str, at: DateTime<Utc>) -> Result<()> {
+        self.trash_entries(&[id.to_string()], at).await
+    }
+    
+    async fn trash_entries(&self, ids: &[String], at: DateTime<Utc>) -> Result<()> {
+        self.update_and_trash_entries(&[], ids, at).await
+    }
+    
+    async fn restore_entry(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("UPDATE password_entries SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    async fn trashed_entries(&self) -> Result<Vec<(PasswordEntry, DateTime<Utc>)>> {
+        let rows = sqlx::query("SELECT * FROM password_entries WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC")
+            .fetch_all(&self.pool)
+            .await?;
+        
+        rows.into_iter()
+            .map(|row| {
+                let deleted_at = row
+                    .get::<String, _>("deleted_at")
+                    .parse()
+                    .map_err(|_| Error::Other("Invalid date format".to_string()))?;
+                Ok((Self::row_to_entry(row)?, deleted_at))
+            })
+            .collect()
+    }
+    
+    async fn purge_trash(&self, before: DateTime<Utc>) -> Result<usize> {
+        // Only take the write lock when something expired, so that opening a
+        // vault another process is writing to doesn't wait on it
+        let expired: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM password_entries WHERE deleted_at IS NOT NULL AND deleted_at < ?"
+        )
+        .bind(before.to_rfc3339())
+        .fetch_one(&self.pool)
+        .await?;
+        if !expired {
+            return Ok(0);
+        }
+        
+        let result = sqlx::query("DELETE FROM password_entries WHERE deleted_at IS NOT NULL AND deleted_at < ?")
+            .bind(before.to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() as usize)
+    }
+    
+    async fn search_entries(&self, filter: &SearchFilter) -> Result<Vec<PasswordEntry>> {
+        let mut query = String::from("SELECT * FROM password_entries WHERE deleted_at IS NULL");
+        let mut bindings = vec![];
+        
+        if let Some(search_query) = &filter.query {
+            query.push_str(
+                " AND (site LIKE ? OR username LIKE ? OR notes LIKE ? OR urls LIKE ? OR site_normalized = ?)"
+            );
+            let like_query = format!("%{}%", search_query);
+            bindings.push(like_query.clone());
+            bindings.push(like_query.clone());
+            bindings.push(like_query.clone());
+            bindings.push(like_query);
+            bindings.push(normalize_site(search_query));
+        }
+        
+        if filter.favorite_only {
+            query.push_str(" AND favorite = 1");
+        }
+        
+        query.push_str(&order_by(filter));
+        
+        let mut query_builder = sqlx::query(&query);
+        for binding in bindings {
+            query_builder = query_builder.bind(binding);
+        }
+        
+        let rows = query_builder.fetch_all(&self.pool).await?;
+        let mut entries = Vec::new();
+        
+        for row in rows {
+            let entry = Self::row_to_entry(row)?;
+            if matches_tags(&entry, &filter.tags) {
+                entries.push(entry);
+            }
+        }
+        
+        Ok(entries)
+    }
+    
+    async fn full_text_search(&self, terms: &[String], filter: &SearchFilter) -> Result<Vec<PasswordEntry>> {
+        // Each term as a quoted prefix query, so that words like "or" and
+        // "not" aren't read as FTS5 operators
+        let fts_query = terms
+            .iter()
+            .map(|term| format!("\"{}\"*", term))
+            .collect::<Vec<_>>()
+            .join(" ");
+        
+        let mut query = String::from(
+            "SELECT * FROM password_entries WHERE deleted_at IS NULL \
+             AND id IN (SELECT id FROM password_entries_fts WHERE password_entries_fts MATCH ?)"
+        );
+        if filter.favorite_only {
+            query.push_str(" AND favorite = 1");
+        }
+        query.push_str(&order_by(filter));
+        
+        let rows = sqlx::query(&query).bind(fts_query).fetch_all(&self.pool).await?;
+        let mut entries = Vec::new();
+        for row in rows {
+            let entry = Self::row_to_entry(row)?;
+            if matches_tags(&entry, &filter.tags) {
+                entries.push(entry);
+            }
+        }
+        
+        Ok(entries)
+    }
+    
+    async fn entries_since(&self, since: DateTime<Utc>) -> Result<Vec<PasswordEntry>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM password_entries 
+            WHERE (updated_at > ? OR created_at > ?) AND deleted_at IS NULL
+            ORDER BY updated_at DESC
+            "#,
+        )
+        .bind(since.to_rfc3339())
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.into_iter().map(Self::row_to_entry).collect()
+    }
+    
+    async fn mark_as_used(&self, id: &str, at: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE password_entries SET last_used = ? WHERE id = ?
+            "#,
+        )
+        .bind(at.to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn set_favorite(&self, id: &str, favorite: bool, at: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE password_entries SET favorite = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(favorite as i32)
+        .bind(at.to_rfc3339())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    async fn entry_count(&self) -> Result<usize> {
+        let row = sqlx::query("SELECT COUNT(*) as count FROM password_entries WHERE deleted_at IS NULL")
+            .fetch_one(&self.pool)
+            .await?;
+        
+        Ok(row.get::<i64, _>("count") as usize)
+    }
+    
+    async fn last_modified(&self) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query(
+            "SELECT MAX(updated_at) as last_modified FROM password_entries WHERE deleted_at IS NULL"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        
+        let last_modified: Option<String> = row.get("last_modified");
+        Ok(last_modified.and_then(|s| s.parse().ok()))
+    }
+    
+    async fn load_draft(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let row = sqlx::query("SELECT encrypted_data FROM drafts WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(row.map(|row| row.get("encrypted_data")))
+    }
+    
+    async fn save_draft(&self, name: &str, data: &[u8]) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO drafts (name, encrypted_data, updated_at) VALUES (?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET
+                encrypted_data = excluded.encrypted_data,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(name)
+        .bind(data)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    async fn delete_draft(&self, name: &str) -> Result<()> {
+        sqlx::query("DELETE FROM drafts WHERE name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+}
+
+/// Fold `entries` into the most recently updated one. Tags, URLs and
+/// previous passwords are combined without repeats, distinct notes are
+/// joined, and passwords other than the kept one go into its history.
+fn merge_entries(mut entries: Vec<DecryptedPasswordEntry>) -> DuplicateMerge {
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.updated_at));
+    let removed = entries.split_off(1);
+    let mut merged = entries.remove(0);
+    
+    let mut notes: Vec<String> = merged.notes.iter().map(|notes| notes.trim().to_string()).collect();
+    let mut history = std::mem::take(&mut merged.password_history);
+    let mut other_urls: Vec<String> = merged.all_urls().into_iter().skip(1).map(str::to_string).collect();
+    for entry in &removed {
+        for tag in &entry.tags {
+            if !merged.tags.contains(tag) {
+                merged.tags.push(tag.clone());
+            }
+        }
+        if let Some(text) = entry.notes.as_deref().map(str::trim) {
+            if !text.is_empty() && !notes.iter().any(|existing| existing.contains(text)) {
+                notes.push(text.to_string());
+            }
+        }
+        for url in entry.all_urls() {
+            let site = normalize_site(url);
+            if normalize_site(&merged.site) != site && !other_urls.iter().any(|other| normalize_site(other) == site) {
+                other_urls.push(url.to_string());
+            }
+        }
+        history.extend(entry.password_history.iter().cloned());
+        if entry.password != merged.password {
+            history.push(PasswordHistoryEntry {
+                password: entry.password.clone(),
+                changed_at: entry.updated_at,
+            });
+        }
+        merged.totp_secret = merged.totp_secret.take().or_else(|| entry.totp_secret.clone());
+        merged.password_policy = merged.password_policy.take().or_else(|| entry.password_policy.clone());
+        merged.favorite |= entry.favorite;
+        merged.require_reauth |= entry.require_reauth;
+        merged.created_at = merged.created_at.min(entry.created_at);
+        merged.last_used = merged.last_used.max(entry.last_used);
+    }
+    
+    notes.retain(|text| !text.is_empty());
+    merged.notes = (!notes.is_empty()).then(|| notes.join("\n\n"));
+    history.sort_by_key(|previous| std::cmp::Reverse(previous.changed_at));
+    let mut seen = vec![merged.password.clone()];
+    history.retain(|previous| {
+        let new = !seen.contains(&previous.password);
+        seen.push(previous.password.clone());
+        new
+    });
+    seen.zeroize();
+    merged.password_history = history;
+    let site = merged.site.clone();
+    merged.set_urls(site, other_urls);
+    DuplicateMerge { merged, removed }
+}
+
+/// Group already decrypted `entries` the way `Storage::find_duplicate_passwords`
+/// does, for callers that have loaded them for other checks too.
+pub(crate) fn group_duplicate_passwords(
+    entries: impl IntoIterator<Item = DecryptedPasswordEntry>,
+) -> Result<Vec<Vec<DecryptedPasswordEntry>>> {
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    
+    let mut groups: HashMap<Vec<u8>, Vec<DecryptedPasswordEntry>> = HashMap::new();
+    for entry in entries {
+        if entry.password.is_empty() {
+            continue;
+        }
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&key)
+            .map_err(|e| Error::Other(format!("Failed to hash password: {}", e)))?;
+        mac.update(entry.password.as_bytes());
+        groups.entry(mac.finalize().into_bytes().to_vec()).or_default().push(entry);
+    }
+    key.zeroize();
+    
+    let mut clusters: Vec<Vec<DecryptedPasswordEntry>> = groups
+        .into_values()
+        .filter(|group| group.len() >= 2)
+        .map(|mut group| {
+            group.sort_by(|a, b| a.site.cmp(&b.site).then_with(|| a.username.cmp(&b.username)));
+            group
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].site.cmp(&b[0].site)));
+    Ok(clusters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PasswordPolicy;
+
+    fn test_entry(id: &str, password: &str) -> DecryptedPasswordEntry {
+        DecryptedPasswordEntry {
+            id: id.to_string(),
+            site: "example.com".to_string(),
+            username: "user@example.com".to_string(),
+            password: password.to_string(),
+            notes: None,
+            tags: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_used: None,
+            password_changed_at: Utc::now(),
+            favorite: false,
+            urls: vec![],
+            created_by: None,
+            modified_by: None,
+            totp_secret: None,
+            password_history: Vec::new(),
+            require_reauth: false,
+            password_policy: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_password_history_is_recorded_and_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let mut storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        storage.set_password_history_limit(2);
+        storage.add_entry(&test_entry("entry-1", "first")).await.unwrap();
+
+        // Changing anything but the password records nothing
+        let mut entry = storage.get_entry("entry-1").await.unwrap();
+        entry.notes = Some("note".to_string());
+        storage.update_entry(&entry).await.unwrap();
+        assert!(storage.get_entry("entry-1").await.unwrap().password_history.is_empty());
+
+        for password in ["second", "third", "fourth"] {
+            let mut entry = storage.get_entry("entry-1").await.unwrap();
+            entry.password = password.to_string();
+            storage.update_entry(&entry).await.unwrap();
+        }
+        let entry = storage.get_entry("entry-1").await.unwrap();
+        let history: Vec<_> = entry.password_history.iter().map(|h| h.password.as_str()).collect();
+        assert_eq!(history, ["third", "second"]);
+        assert_eq!(entry.password_changed_at, entry.password_history[0].changed_at);
+
+        let stored = storage.backend().get_entry("entry-1").await.unwrap().unwrap();
+        let ciphertext = stored.encrypted_password_history.unwrap();
+        assert!(!ciphertext.windows(5).any(|window| window == b"third"));
+
+        // Restoring a previous password takes it out of the history
+        let mut entry = storage.get_entry("entry-1").await.unwrap();
+        entry.password = "second".to_string();
+        storage.update_entry(&entry).await.unwrap();
+        let entry = Storage::open(&vault_path, "master_password").await.unwrap().get_entry("entry-1").await.unwrap();
+        let history: Vec<_> = entry.password_history.iter().map(|h| h.password.as_str()).collect();
+        assert_eq!(entry.password, "second");
+        assert_eq!(history, ["fourth", "third"]);
+    }
+
+    #[tokio::test]
+    async fn test_deleted_entries_go_to_the_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        storage.add_entries(&[test_entry("entry-1", "first"), test_entry("entry-2", "second")]).await.unwrap();
+
+        storage.delete_entry("entry-1").await.unwrap();
+        assert!(matches!(storage.get_entry("entry-1").await, Err(Error::EntryNotFound(_))));
+        assert_eq!(storage.search_entries(&SearchFilter::default()).await.unwrap().len(), 1);
+        assert_eq!(storage.get_entry_count().await.unwrap(), 1);
+        let trashed = storage.list_trashed().await.unwrap();
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].item.password, "first");
+
+        storage.restore_entry("entry-1").await.unwrap();
+        assert_eq!(storage.get_entry("entry-1").await.unwrap().password, "first");
+        assert!(storage.restore_entry("entry-1").await.is_err());
+
+        // Adding an entry with the id of a trashed one replaces it
+        storage.delete_entry("entry-1").await.unwrap();
+        storage.add_entry(&test_entry("entry-1", "again")).await.unwrap();
+        assert_eq!(storage.get_entry("entry-1").await.unwrap().password, "again");
+        assert!(storage.list_trashed().await.unwrap().is_empty());
+
+        // Kept for the retention period, then purged on open
+        storage.delete_entry("entry-2").await.unwrap();
+        assert_eq!(storage.purge_trash(chrono::Duration::days(1)).await.unwrap(), 0);
+        assert_eq!(storage.trash_retention_days().await.unwrap(), DEFAULT_TRASH_RETENTION_DAYS);
+        storage.set_trash_retention_days(1).await.unwrap();
+        sqlx::query("UPDATE password_entries SET deleted_at = ? WHERE id = 'entry-2'")
+            .bind((Utc::now() - chrono::Duration::days(2)).to_rfc3339())
+            .execute(storage.backend().pool())
+            .await
+            .unwrap();
+        let reopened = Storage::open(&vault_path, "master_password").await.unwrap();
+        assert!(reopened.list_trashed().await.unwrap().is_empty());
+        assert_eq!(reopened.get_entry_count().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_tag_edits_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::create_new(dir.path().join("vault.db"), "master_password").await.unwrap();
+        let mut tagged = test_entry("entry-1", "first");
+        tagged.tags = vec!["work".to_string()];
+        storage
+            .add_entries(&[tagged, test_entry("entry-2", "second"), test_entry("entry-3", "third")])
+            .await
+            .unwrap();
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        
+        assert_eq!(storage.bulk_add_tag(&ids(&["entry-1", "entry-2"]), " work ").await.unwrap(), 1);
+        assert_eq!(storage.get_entry("entry-2").await.unwrap().tags, ["work"]);
+        assert_eq!(storage.get_entry("entry-1").await.unwrap().tags, ["work"]);
+        let all = SearchFilter::default();
+        assert_eq!(storage.search_fts("work", &all).await.unwrap().len(), 2);
+        
+        assert_eq!(storage.bulk_remove_tag(&ids(&["entry-1", "entry-2", "entry-3"]), "work").await.unwrap(), 2);
+        assert!(storage.get_entry("entry-1").await.unwrap().tags.is_empty());
+        assert!(storage.bulk_add_tag(&ids(&["entry-1"]), "  ").await.is_err());
+        
+        // An unknown id fails the whole batch
+        assert!(storage.bulk_add_tag(&ids(&["entry-1", "missing"]), "home").await.is_err());
+        assert!(storage.get_entry("entry-1").await.unwrap().tags.is_empty());
+        
+        storage.bulk_delete(&ids(&["entry-1", "entry-3"])).await.unwrap();
+        assert_eq!(storage.get_entry_count().await.unwrap(), 1);
+        assert_eq!(storage.list_trashed().await.unwrap().len(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_vacuum_and_integrity_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::create_new(dir.path().join("vault.db"), "master_password").await.unwrap();
+        let mut entries = Vec::new();
+        for i in 0..200 {
+            let mut entry = test_entry(&format!("entry-{}", i), "password");
+            entry.notes = Some("x".repeat(2000));
+            entries.push(entry);
+        }
+        storage.add_entries(&entries).await.unwrap();
+        let ids: Vec<String> = entries.iter().map(|entry| entry.id.clone()).collect();
+        storage.bulk_delete(&ids[1..]).await.unwrap();
+        storage.purge_trash(chrono::Duration::zero()).await.unwrap();
+        
+        assert!(storage.integrity_check().await.unwrap());
+        let stats = storage.vacuum().await.unwrap();
+        assert!(stats.bytes_after < stats.bytes_before, "{:?}", stats);
+        assert!(storage.integrity_check().await.unwrap());
+        assert_eq!(storage.get_entry("entry-0").await.unwrap().password, "password");
+    }
+    
+    #[tokio::test]
+    async fn test_weak_kdf_params_upgraded_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let weak = Argon2Params { mem_kib: 1024, iterations: 1, parallelism: 1 };
+        let backend = SqliteBackend::create(&vault_path).await.unwrap();
+        let storage = Storage::create_with_kdf_params(backend, "master_password", None, &weak).await.unwrap();
+        storage.add_entry(&test_entry("entry-1", "secret")).await.unwrap();
+        drop(storage);
+        
+        let stored_params =
+            |metadata: VaultMetadata| Argon2Params::from_password_hash(&metadata.master_password_hash).unwrap();
+        let reopened = Storage::open(&vault_path, "master_password").await.unwrap();
+        let upgraded = stored_params(reopened.get_vault_metadata().await.unwrap());
+        assert_eq!(upgraded, Argon2Params::default());
+        assert_eq!(reopened.get_entry("entry-1").await.unwrap().password, "secret");
+        drop(reopened);
+        
+        // Only weaker costs are raised: the higher memory setting stays
+        let target = Argon2Params { mem_kib: 8 * 1024, iterations: 3, parallelism: 1 };
+        let reopened = Storage::open_with_kdf_target(&vault_path, "master_password", None, &target).await.unwrap();
+        let upgraded = stored_params(reopened.get_vault_metadata().await.unwrap());
+        assert_eq!(upgraded, Argon2Params { iterations: 3, ..Argon2Params::default() });
+        drop(reopened);
+        
+        let reopened = Storage::open(&vault_path, "master_password").await.unwrap();
+        assert_eq!(reopened.get_entry("entry-1").await.unwrap().password, "secret");
+        assert!(Storage::open(&vault_path, "wrong_password").await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_set_favorite() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        storage.add_entry(&test_entry("entry-1", "secret")).await.unwrap();
+        
+        storage.set_favorite("entry-1", true).await.unwrap();
+        let favorites = SearchFilter { favorite_only: true, ..Default::default() };
+        let starred = storage.search_entries(&favorites).await.unwrap();
+        assert_eq!(starred.len(), 1);
+        assert_eq!(starred[0].password, "secret");
+        
+        storage.set_favorite("entry-1", false).await.unwrap();
+        assert!(!storage.get_entry("entry-1").await.unwrap().favorite);
+        
+        storage.delete_entry("entry-1").await.unwrap();
+        assert!(matches!(
+            storage.set_favorite("entry-1", true).await,
+            Err(Error::EntryNotFound(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_touch_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        storage.add_entry(&test_entry("entry-1", "secret")).await.unwrap();
+        storage.add_entry(&test_entry("entry-2", "other")).await.unwrap();
+        let before = storage.get_entry("entry-1").await.unwrap();
+        assert!(before.last_used.is_none());
+        
+        // What the GUI and CLI do when the password is copied
+        storage.touch_entry("entry-1").await.unwrap();
+        let after = storage.get_entry("entry-1").await.unwrap();
+        assert!(after.last_used.is_some_and(|used| used >= before.updated_at));
+        assert_eq!(after.updated_at, before.updated_at);
+        
+        let recent = SearchFilter { sort_by: SortField::LastUsed, sort_order: SortOrder::Descending, ..Default::default() };
+        let entries = storage.search_entries(&recent).await.unwrap();
+        assert_eq!(entries[0].id, "entry-1");
+    }
+    
+    #[tokio::test]
+    async fn test_require_reauth() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        let mut entry = test_entry("entry-1", "secret");
+        entry.require_reauth = true;
+        storage.add_entry(&entry).await.unwrap();
+        assert!(storage.get_entry("entry-1").await.unwrap().require_reauth);
+        
+        entry.require_reauth = false;
+        storage.update_entry(&entry).await.unwrap();
+        assert!(!storage.get_entry("entry-1").await.unwrap().require_reauth);
+        
+        assert!(storage.verify_master_password("master_password").await.unwrap());
+    }
+    
+    #[tokio::test]
+    async fn test_password_policy_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        let policy = PasswordPolicy { length: 12, uppercase: true, lowercase: true, numbers: true, symbols: false };
+        let mut entry = test_entry("entry-1", "secret");
+        entry.password_policy = Some(policy.clone());
+        storage.add_entry(&entry).await.unwrap();
+        assert_eq!(storage.get_entry("entry-1").await.unwrap().password_policy, Some(policy.clone()));
+        
+        let password = crate::generator::PasswordGenerator::generate(&policy.config()).unwrap();
+        assert_eq!(password.len(), 12);
+        assert!(password.chars().all(|c| c.is_ascii_alphanumeric()));
+        
+        entry.password_policy = None;
+        storage.update_entry(&entry).await.unwrap();
+        assert!(storage.get_entry("entry-1").await.unwrap().password_policy.is_none());
+        assert!(!storage.verify_master_password("wrong_password").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_merge_duplicates_unions_tags_and_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        let mut older = test_entry("older", "secret");
+        older.site = "https://www.example.com/login".to_string();
+        older.tags = vec!["work".to_string(), "email".to_string()];
+        older.notes = Some("Recovery codes in the safe".to_string());
+        older.updated_at = Utc::now() - chrono::Duration::days(10);
+        older.favorite = true;
+        let mut newer = test_entry("newer", "secret");
+        newer.tags = vec!["email".to_string(), "2fa".to_string()];
+        newer.notes = Some("Uses the hardware key".to_string());
+        let mut different = test_entry("different", "other secret");
+        different.username = "USER@example.com".to_string();
+        different.updated_at = Utc::now() - chrono::Duration::days(20);
+        storage.add_entries(&[older, newer, different]).await.unwrap();
+
+        let report = storage.merge_duplicates(MergeStrategy::SamePassword).await.unwrap();
+        assert_eq!(report.merges.len(), 1);
+        assert_eq!(report.merges[0].removed.len(), 1);
+        assert_eq!(report.different_passwords.len(), 1);
+        let merged = storage.get_entry("newer").await.unwrap();
+        assert_eq!(merged.tags, vec!["email", "2fa", "work"]);
+        assert_eq!(merged.notes.as_deref(), Some("Uses the hardware key\n\nRecovery codes in the safe"));
+        assert!(merged.favorite);
+        assert!(merged.password_history.is_empty());
+        assert!(storage.get_entry("older").await.is_err());
+        assert_eq!(storage.list_trashed().await.unwrap().len(), 1);
+        // Entries with a different password are only merged on request
+        assert_eq!(storage.get_entry("different").await.unwrap().password, "other secret");
+
+        let report = storage.merge_duplicates(MergeStrategy::AnyPassword).await.unwrap();
+        assert_eq!(report.merges.len(), 1);
+        let merged = storage.get_entry("newer").await.unwrap();
+        assert_eq!(merged.password, "secret");
+        assert_eq!(merged.password_history.len(), 1);
+        assert_eq!(merged.password_history[0].password, "other secret");
+        assert_eq!(storage.get_entry_count().await.unwrap(), 1);
+        assert!(storage.plan_duplicate_merges(MergeStrategy::AnyPassword).await.unwrap().merges.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_totp_seeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        storage.add_entry(&test_entry("entry-1", "secret")).await.unwrap();
+
+        let import = crate::totp::parse_totp_import(
+            "otpauth://totp/Example:user@example.com?secret=GEZDGNBVGY3TQOJQ\n\
+             otpauth://totp/GitHub:octocat?secret=GEZDGNBVGY3TQOJQ&algorithm=SHA512",
+        )
+        .unwrap();
+        let report = storage.import_totp_seeds(&import.seeds).await.unwrap();
+        assert_eq!(report.attached, vec!["user@example.com @ example.com"]);
+        assert_eq!(report.created, vec!["octocat @ GitHub"]);
+        assert_eq!(storage.get_entry("entry-1").await.unwrap().totp_secret.as_deref(), Some("GEZDGNBVGY3TQOJQ"));
+
+        let created = storage.get_entry(&hash_entry_id("GitHub", "octocat")).await.unwrap();
+        let secret = created.totp_secret.clone().unwrap();
+        assert_eq!(crate::models::generate_totp(&secret, Utc::now()).unwrap().len(), 6);
+        assert!(secret.contains("algorithm=SHA512"));
+
+        // Importing again changes nothing
+        let report = storage.import_totp_seeds(&import.seeds).await.unwrap();
+        assert!(report.attached.is_empty() && report.created.is_empty());
+        assert_eq!(report.skipped.len(), 2);
+        assert_eq!(storage.get_entry_count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        
+        let mut mail = test_entry("entry-1", "secret");
+        mail.site = "mail.google.com".to_string();
+        mail.tags = vec!["personal".to_string()];
+        mail.favorite = true;
+        let mut bank = test_entry("entry-2", "secret");
+        bank.site = "bank.example".to_string();
+        bank.notes = Some("Savings account, PIN on paper".to_string());
+        bank.tags = vec!["finance".to_string()];
+        let other = test_entry("entry-3", "secret");
+        storage.add_entries(&[mail, bank, other]).await.unwrap();
+        
+        let ids = |entries: Vec<DecryptedPasswordEntry>| entries.into_iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+        let all = SearchFilter::default();
+        assert_eq!(ids(storage.search_fts("goo MAIL", &all).await.unwrap()), ["entry-1"]);
+        assert_eq!(ids(storage.search_fts("savings", &all).await.unwrap()), ["entry-2"]);
+        assert_eq!(ids(storage.search_fts("financ", &all).await.unwrap()), ["entry-2"]);
+        assert_eq!(ids(storage.search_fts("\"not\" OR", &all).await.unwrap()), Vec::<String>::new());
+        assert_eq!(storage.search_fts("  ", &all).await.unwrap().len(), 3);
+        
+        let favorites = SearchFilter { favorite_only: true, ..Default::default() };
+        assert_eq!(ids(storage.search_fts("com", &favorites).await.unwrap()), ["entry-1"]);
+        let tagged = SearchFilter { tags: Some(vec!["finance".to_string()]), ..Default::default() };
+        assert_eq!(ids(storage.search_fts("example", &tagged).await.unwrap()), ["entry-2"]);
+        
+        // The index follows updates and deletions
+        let mut renamed = storage.get_entry("entry-3").await.unwrap();
+        renamed.site = "forum.example".to_string();
+        storage.update_entry(&renamed).await.unwrap();
+        assert_eq!(ids(storage.search_fts("forum", &all).await.unwrap()), ["entry-3"]);
+        storage.delete_entry("entry-3").await.unwrap();
+        assert!(storage.search_fts("forum", &all).await.unwrap().is_empty());
+        
+        // A vault from before the index existed gets it built on open
+        sqlx::query("DROP TABLE password_entries_fts")
+            .execute(storage.backend().pool())
+            .await
+            .unwrap();
+        let reopened = Storage::open(&vault_path, "master_password").await.unwrap();
+        assert_eq!(ids(reopened.search_fts("pin paper", &all).await.unwrap()), ["entry-2"]);
+    }
+    
+    #[tokio::test]
+    async fn test_duplicate_entries_by_normalized_site() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        
+        let mut entries = Vec::new();
+        for (id, site, username) in [
+            ("a", "https://www.Example.com/login", "Alice"),
+            ("b", "example.com", "alice"),
+            ("c", "mail.example.com", "alice"),
+            ("d", "example.com", "bob"),
+        ] {
+            let mut entry = test_entry(id, "secret");
+            entry.site = site.to_string();
+            entry.username = username.to_string();
+            entries.push(entry);
+        }
+        storage.add_entries(&entries).await.unwrap();
+        
+        let ids = |entries: &[DecryptedPasswordEntry]| entries.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+        let duplicates = storage.find_duplicate_entries().await.unwrap();
+        assert_eq!(duplicates.len(), 1);
+        let mut group = ids(&duplicates[0]);
+        group.sort();
+        assert_eq!(group, ["a", "b"]);
+        
+        // Searching for an address finds the entries for its site
+        let filter = SearchFilter { query: Some("http://EXAMPLE.com/account".to_string()), ..Default::default() };
+        let mut found = ids(&storage.search_entries(&filter).await.unwrap());
+        found.sort();
+        assert_eq!(found, ["a", "b", "d"]);
+        
+        // Older vaults get the column filled in on open
+        let pool = storage.backend().pool();
+        sqlx::query("DROP INDEX idx_site_normalized").execute(pool).await.unwrap();
+        sqlx::query("ALTER TABLE password_entries DROP COLUMN site_normalized").execute(pool).await.unwrap();
+        let reopened = Storage::open(&vault_path, "master_password").await.unwrap();
+        assert_eq!(reopened.search_entries(&filter).await.unwrap().len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_find_and_flag_duplicate_passwords() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::create_new(dir.path().join("vault.db"), "master_password").await.unwrap();
+
+        let mut entries = vec![
+            test_entry("a", "shared"),
+            test_entry("b", "shared"),
+            test_entry("c", "shared"),
+            test_entry("d", "pair"),
+            test_entry("e", "pair"),
+            test_entry("f", "unique"),
+            test_entry("g", ""),
+            test_entry("h", ""),
+        ];
+        for (entry, site) in entries.iter_mut().zip(["c.com", "a.com", "b.com", "e.com", "d.com", "f.com", "g.com", "h.com"]) {
+            entry.site = site.to_string();
+        }
+        storage.add_entries(&entries).await.unwrap();
+
+        let clusters = storage.find_duplicate_passwords().await.unwrap();
+        let ids: Vec<Vec<&str>> = clusters
+            .iter()
+            .map(|cluster| cluster.iter().map(|entry| entry.id.as_str()).collect())
+            .collect();
+        assert_eq!(ids, vec![vec!["b", "c", "a"], vec!["e", "d"]]);
+
+        let others = vec!["c".to_string(), "a".to_string()];
+        assert_eq!(storage.flag_reused_passwords(&others).await.unwrap(), 2);
+        assert_eq!(storage.flag_reused_passwords(&others).await.unwrap(), 0);
+        assert!(storage.get_entry("a").await.unwrap().tags.contains(&REUSED_PASSWORD_TAG.to_string()));
+        assert!(storage.get_entry("b").await.unwrap().tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_is_visible_to_fresh_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+
+        let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous")
+            .fetch_one(storage.backend().pool())
+            .await
+            .unwrap();
+        assert_eq!(synchronous, 2, "vault connections must use synchronous = FULL");
+
+        storage.add_entry(&test_entry("entry-1", "first")).await.unwrap();
+        storage.update_entry(&test_entry("entry-1", "second")).await.unwrap();
+
+        // The original pool stays open: the fresh connection must see the
+        // committed data without relying on a close-time checkpoint.
+        let reopened = Storage::open(&vault_path, "master_password").await.unwrap();
+        let entry = reopened.get_entry("entry-1").await.unwrap();
+        assert_eq!(entry.password, "second");
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_transaction_is_not_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        storage.add_entry(&test_entry("entry-1", "original")).await.unwrap();
+
+        // Simulate a writer dying mid-transaction: the transaction is never
+        // committed or rolled back, its connection is simply abandoned.
+        let writer = SqlitePool::connect_with(connect_options(&vault_path, false)).await.unwrap();
+        let mut tx = writer.begin().await.unwrap();
+        sqlx::query("UPDATE password_entries SET site = 'tampered.com' WHERE id = 'entry-1'")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM vault_metadata")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        std::mem::forget(tx);
+
+        let reopened = Storage::open(&vault_path, "master_password").await.unwrap();
+        let entry = reopened.get_entry("entry-1").await.unwrap();
+        assert_eq!(entry.site, "example.com");
+        assert_eq!(entry.password, "original");
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend_round_trip() {
+        use crate::backend::MemoryBackend;
+
+        let storage = Storage::create_with_backend(MemoryBackend::new(), "master_password", None)
+            .await
+            .unwrap();
+        let mut tagged = test_entry("entry-1", "secret");
+        tagged.tags = vec!["work".to_string(), "finance".to_string()];
+        storage.add_entries(&[tagged, test_entry("entry-2", "other")]).await.unwrap();
+
+        let stored = storage.backend().get_entry("entry-1").await.unwrap().unwrap();
+        assert_ne!(stored.encrypted_password, b"secret".to_vec());
+
+        let filter = SearchFilter { tags: Some(vec!["finance".to_string()]), ..Default::default() };
+        let found = storage.search_entries(&filter).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].password, "secret");
+        assert_eq!(storage.search_fts("fin exam", &SearchFilter::default()).await.unwrap().len(), 1);
+        assert_eq!(storage.get_entry_count().await.unwrap(), 2);
+        assert_eq!(
+            storage.tag_counts().await.unwrap(),
+            [("finance".to_string(), 1), ("work".to_string(), 1)]
+        );
+
+        assert!(matches!(
+            Storage::open_with_backend(storage.backend, "wrong_password", None).await,
+            Err(Error::InvalidMasterPassword)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_key_file_is_required_to_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let key_file = KeyFile::generate(dir.path().join("vault.key")).unwrap();
+        let storage = Storage::create_new_with_key_file(&vault_path, "master_password", Some(&key_file))
+            .await
+            .unwrap();
+        storage.add_entry(&test_entry("entry-1", "secret")).await.unwrap();
+        
+        match Storage::open(&vault_path, "master_password").await {
+            Err(Error::KeyFileRequired(required)) => assert!(required.contains("vault.key")),
+            other => panic!("expected KeyFileRequired, got {:?}", other.err()),
+        }
+        let other_key = KeyFile::from_bytes(b"not the key file", None).unwrap();
+        assert!(matches!(
+            Storage::open_with_key_file(&vault_path, "master_password", Some(&other_key)).await,
+            Err(Error::WrongKeyFile(_))
+        ));
+        
+        let key_file = KeyFile::read(dir.path().join("vault.key")).unwrap();
+        let reopened = Storage::open_with_key_file(&vault_path, "master_password", Some(&key_file))
+            .await
+            .unwrap();
+        assert_eq!(reopened.get_entry("entry-1").await.unwrap().password, "secret");
+    }
+    
+    #[tokio::test]
+    async fn test_open_distinguishes_missing_and_corrupt_vaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        assert!(matches!(
+            Storage::open(&vault_path, "master_password").await,
+            Err(Error::VaultNotFound(_))
+        ));
+        
+        std::fs::write(&vault_path, vec![0x5a; 4096]).unwrap();
+        assert!(matches!(
+            Storage::open(&vault_path, "master_password").await,
+            Err(Error::VaultCorrupt(_))
+        ));
+        
+        let other_db = dir.path().join("other.db");
+        let pool = SqlitePool::connect_with(connect_options(&other_db, true)).await.unwrap();
+        sqlx::query("CREATE TABLE notes (text TEXT)").execute(&pool).await.unwrap();
+        pool.close().await;
+        assert!(matches!(
+            Storage::open(&other_db, "master_password").await,
+            Err(Error::VaultCorrupt(_))
+        ));
+        
+        std::fs::remove_file(&vault_path).unwrap();
+        Storage::create_new(&vault_path, "master_password").await.unwrap();
+        assert!(matches!(
+            Storage::open(&vault_path, "wrong_password").await,
+            Err(Error::InvalidMasterPassword)
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_change_master_password_reencrypts_vault() {
+        use crate::{secrets::SecretData, secrets_storage::SecretsStorage};
+        
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let mut storage = Storage::create_new(&vault_path, "old_password").await.unwrap();
+        let secrets_storage = SecretsStorage::from_existing_storage(&vault_path, "old_password")
+            .await
+            .unwrap();
+        let secret = crate::secrets::DecryptedSecretEntry {
+            id: "secret-1".to_string(),
+            name: "API".to_string(),
+            description: None,
+            secret_type: crate::secrets::SecretType::Password,
+            data: SecretData::Password {
+                username: "user".to_string(),
+                password: "secret value".to_string(),
+                url: None,
+                notes: None,
+            },
+            metadata: Default::default(),
+            tags: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_accessed: None,
+            expires_at: None,
+            favorite: false,
+        };
+        secrets_storage.add_secret(&secret).await.unwrap();
+        let mut entry = test_entry("entry-1", "secret");
+        entry.totp_secret = Some("GEZDGNBVGY3TQOJQ".to_string());
+        storage.add_entry(&entry).await.unwrap();
+        storage.save_draft("entry", "draft note").await.unwrap();
+        
+        assert!(matches!(
+            storage.change_master_password("wrong_password", "new_password", None).await,
+            Err(Error::InvalidMasterPassword)
+        ));
+        storage.change_master_password("old_password", "new_password", None).await.unwrap();
+        assert_eq!(storage.get_entry("entry-1").await.unwrap().password, "secret");
+        
+        assert!(matches!(
+            Storage::open(&vault_path, "old_password").await,
+            Err(Error::InvalidMasterPassword)
+        ));
+        let reopened = Storage::open(&vault_path, "new_password").await.unwrap();
+        let entry = reopened.get_entry("entry-1").await.unwrap();
+        assert_eq!(entry.password, "secret");
+        assert_eq!(entry.totp_secret.as_deref(), Some("GEZDGNBVGY3TQOJQ"));
+        assert_eq!(reopened.load_draft("entry").await.unwrap().as_deref(), Some("draft note"));
+        let secrets_storage = SecretsStorage::from_existing_storage(&vault_path, "new_password")
+            .await
+            .unwrap();
+        let restored = secrets_storage.get_secret("secret-1").await.unwrap();
+        assert!(matches!(&restored.data, SecretData::Password { password, .. } if password == "secret value"));
+    }
+    
+    #[tokio::test]
+    async fn test_change_master_password_keeps_teams_readable() {
+        use crate::secrets_storage::SecretsStorage;
+        use crate::team_sharing::{MemberKeyPair, Permission, TeamSharingManager};
+        
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let mut storage = Storage::create_new(&vault_path, "old_password").await.unwrap();
+        let (owner_id, owner_key) = {
+            let secrets = SecretsStorage::from_existing_storage(&vault_path, "old_password").await.unwrap();
+            let (owner_id, owner_key) = secrets.team_identity().await.unwrap();
+            let mut team = TeamSharingManager::create_team(
+                "Ops".to_string(), None, owner_id.clone(),
+                "owner@example.com".to_string(), "Owner".to_string(), owner_key.public_key(),
+            ).unwrap();
+            TeamSharingManager::add_team_member(
+                &mut team, "member".to_string(), "member@example.com".to_string(), "Member".to_string(),
+                MemberKeyPair::generate().public_key(), Permission::Read, &owner_id,
+            ).unwrap();
+            secrets.save_team(&team).await.unwrap();
+            (owner_id, owner_key)
+        };
+        
+        storage.change_master_password("old_password", "new_password", None).await.unwrap();
+        drop(storage);
+        
+        let secrets = SecretsStorage::from_existing_storage(&vault_path, "new_password").await.unwrap();
+        let teams = secrets.teams().await.unwrap();
+        assert_eq!(teams.len(), 1);
+        assert_eq!(teams[0].members.len(), 2);
+        assert_eq!(teams[0].members[1].email, "member@example.com");
+        assert_eq!(teams[0].members[1].name, "Member");
+        let (reopened_id, reopened_key) = secrets.team_identity().await.unwrap();
+        assert_eq!(reopened_id, owner_id);
+        assert_eq!(reopened_key.public_key(), owner_key.public_key());
+    }
+    
+    #[tokio::test]
+    async fn test_recovery_key_unlocks_after_password_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let mut storage = Storage::create_new(&vault_path, "old_password").await.unwrap();
+        storage.add_entry(&test_entry("entry-1", "secret")).await.unwrap();
+        assert!(!storage.has_recovery_key().await.unwrap());
+        assert!(matches!(
+            Storage::open_with_recovery_key(&vault_path, &RecoveryKey::generate()).await,
+            Err(Error::Other(_))
+        ));
+        
+        let recovery_key = storage.enable_recovery_key().await.unwrap();
+        assert!(storage.has_recovery_key().await.unwrap());
+        storage.change_master_password("old_password", "new_password", None).await.unwrap();
+        drop(storage);
+        
+        assert!(matches!(
+            Storage::open_with_recovery_key(&vault_path, &RecoveryKey::generate()).await,
+            Err(Error::InvalidRecoveryKey)
+        ));
+        let typed = RecoveryKey::parse(&recovery_key.phrase().to_uppercase()).unwrap();
+        let mut recovered = Storage::open_with_recovery_key(&vault_path, &typed).await.unwrap();
+        assert_eq!(recovered.get_entry("entry-1").await.unwrap().password, "secret");
+        
+        recovered.reset_master_password("reset_password", None).await.unwrap();
+        drop(recovered);
+        assert!(matches!(
+            Storage::open(&vault_path, "new_password").await,
+            Err(Error::InvalidMasterPassword)
+        ));
+        let reopened = Storage::open(&vault_path, "reset_password").await.unwrap();
+        assert_eq!(reopened.get_entry("entry-1").await.unwrap().password, "secret");
+        let recovered = Storage::open_with_recovery_key(&vault_path, &recovery_key).await.unwrap();
+        assert_eq!(recovered.get_entry("entry-1").await.unwrap().password, "secret");
+        
+        reopened.disable_recovery_key().await.unwrap();
+        assert!(Storage::open_with_recovery_key(&vault_path, &recovery_key).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_drafts_are_encrypted_and_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        storage.save_draft("entry", "half-written secret note").await.unwrap();
+        
+        let raw = storage.backend().load_draft("entry").await.unwrap().unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("secret note"));
+        
+        let reopened = Storage::open(&vault_path, "master_password").await.unwrap();
+        assert_eq!(
+            reopened.load_draft("entry").await.unwrap().as_deref(),
+            Some("half-written secret note")
+        );
+        
+        reopened.clear_draft("entry").await.unwrap();
+        assert!(reopened.load_draft("entry").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_find_by_url_matches_any_entry_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let storage = Storage::create_new(&vault_path, "master_password").await.unwrap();
+
+        let mut entry = test_entry("entry-1", "secret");
+        entry.set_urls(
+            "https://example.com".to_string(),
+            vec!["https://sso.example.org/login".to_string(), "https://example.com".to_string()],
+        );
+        assert_eq!(entry.all_urls(), vec!["https://example.com", "https://sso.example.org/login"]);
+        storage.add_entry(&entry).await.unwrap();
+
+        let reopened = Storage::open(&vault_path, "master_password").await.unwrap();
+        let found = reopened.find_by_url("https://auth.sso.example.org/").await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].site, "https://example.com");
+        assert!(found[0].urls.iter().any(|u| u.primary && u.url == "https://example.com"));
+        assert!(reopened.find_by_url("https://example.net").await.unwrap().is_empty());
+
+        let filter = SearchFilter { query: Some("sso.example".to_string()), ..Default::default() };
+        assert_eq!(reopened.search_entries(&filter).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_device_name_is_recorded_on_add_and_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.db");
+        let mut laptop = Storage::create_new(&vault_path, "master_password").await.unwrap();
+        laptop.set_device_name(Some("laptop".to_string()));
+        laptop.add_entry(&test_entry("entry-1", "first")).await.unwrap();
+
+        let mut desktop = Storage::open(&vault_path, "master_password").await.unwrap();
+        desktop.set_device_name(Some("desktop".to_string()));
+        let mut entry = desktop.get_entry("entry-1").await.unwrap();
+        assert_eq!(entry.created_by.as_deref(), Some("laptop"));
+        assert_eq!(entry.modified_by.as_deref(), Some("laptop"));
+
+        entry.password = "second".to_string();
+        desktop.update_entry(&entry).await.unwrap();
+
+        // A device without a name leaves the recorded authors alone
+        let unnamed = Storage::open(&vault_path, "master_password").await.unwrap();
+        let entry = unnamed.get_entry("entry-1").await.unwrap();
+        assert_eq!(entry.created_by.as_deref(), Some("laptop"));
+        assert_eq!(entry.modified_by.as_deref(), Some("desktop"));
+        unnamed.update_entry(&entry).await.unwrap();
+        assert_eq!(unnamed.get_entry("entry-1").await.unwrap().modified_by.as_deref(), Some("desktop"));
+    }
+}
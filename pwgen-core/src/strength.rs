@@ -0,0 +1,503 @@
+use serde::{Deserialize, Serialize};
+#[cfg(not(feature = "zxcvbn"))]
+use std::collections::HashMap;
+#[cfg(not(feature = "zxcvbn"))]
+use std::sync::OnceLock;
+
+#[cfg(not(feature = "zxcvbn"))]
+use crate::generator::WORD_LIST;
+
+#[cfg(not(feature = "zxcvbn"))]
+/// Passwords that show up at the top of every public breach corpus.
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "123456789", "12345678", "1234567890", "111111", "000000", "password",
+    "password1", "passw0rd", "qwerty", "qwertyuiop", "abc123", "letmein", "welcome",
+    "admin", "login", "iloveyou", "monkey", "dragon", "football", "baseball", "sunshine",
+    "princess", "master", "shadow", "trustno1", "starwars", "superman", "whatever",
+];
+
+#[cfg(not(feature = "zxcvbn"))]
+/// Keyboard rows checked for "qwerty"-style walks.
+const KEYBOARD_ROWS: &[&str] = &["qwertyuiop", "asdfghjkl", "zxcvbnm", "1234567890"];
+
+#[cfg(not(feature = "zxcvbn"))]
+/// Dictionary words are only held against passwords shorter than this; longer
+/// ones are typically passphrases where words are expected.
+const PASSPHRASE_LENGTH: usize = 20;
+
+/// Guess rate of an offline attack on a slow password hash (bcrypt, Argon2),
+/// the scenario crack times are reported for. This is zxcvbn's
+/// `offline_slow_hashing_1e4_per_second` estimate.
+pub const GUESSES_PER_SECOND: f64 = 1e4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum StrengthLevel {
+    VeryWeak,
+    Weak,
+    Fair,
+    Good,
+    Strong,
+}
+
+impl StrengthLevel {
+    /// The level as a 0 (very weak) to 4 (strong) score.
+    pub fn score(&self) -> u8 {
+        *self as u8
+    }
+
+    /// The level for the number of guesses an attacker needs, using the
+    /// zxcvbn score thresholds of 10^3, 10^6, 10^8 and 10^10 guesses.
+    fn from_guesses_log10(guesses_log10: f64) -> Self {
+        match guesses_log10 {
+            g if g < 3.0 => StrengthLevel::VeryWeak,
+            g if g < 6.0 => StrengthLevel::Weak,
+            g if g < 8.0 => StrengthLevel::Fair,
+            g if g < 10.0 => StrengthLevel::Good,
+            _ => StrengthLevel::Strong,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StrengthLevel::VeryWeak => "Very Weak",
+            StrengthLevel::Weak => "Weak",
+            StrengthLevel::Fair => "Fair",
+            StrengthLevel::Good => "Good",
+            StrengthLevel::Strong => "Strong",
+        }
+    }
+
+    #[cfg(not(feature = "zxcvbn"))]
+    fn downgrade(self) -> Self {
+        match self {
+            StrengthLevel::Strong => StrengthLevel::Good,
+            StrengthLevel::Good => StrengthLevel::Fair,
+            StrengthLevel::Fair => StrengthLevel::Weak,
+            StrengthLevel::Weak | StrengthLevel::VeryWeak => StrengthLevel::VeryWeak,
+        }
+    }
+}
+
+/// Result of `estimate_strength`: an overall level plus the specific reasons
+/// that held the password back, in a form suitable for showing to the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrengthReport {
+    pub level: StrengthLevel,
+    pub reasons: Vec<String>,
+    /// Base-10 logarithm of the estimated number of guesses needed
+    pub guesses_log10: f64,
+    /// The change that would help most, e.g. "Add length", for showing
+    /// while a password is typed. `None` for strong passwords.
+    #[serde(default)]
+    pub tip: Option<String>,
+}
+
+impl StrengthReport {
+    /// The level as a 0 (very weak) to 4 (strong) score.
+    pub fn score(&self) -> u8 {
+        self.level.score()
+    }
+
+    /// Limit the estimate to a keyspace of `entropy_bits`, for generated
+    /// passwords whose real entropy is known. A pronounceable password looks
+    /// random to `estimate_strength` but comes from far fewer possibilities.
+    pub fn capped_at_entropy(mut self, entropy_bits: f64) -> Self {
+        self.guesses_log10 = self.guesses_log10.min(entropy_bits * 2f64.log10());
+        self.level = self.level.min(StrengthLevel::from_guesses_log10(self.guesses_log10));
+        self
+    }
+
+    /// Estimated time to guess the password at `GUESSES_PER_SECOND`.
+    pub fn crack_time_seconds(&self) -> f64 {
+        10f64.powf(self.guesses_log10) / GUESSES_PER_SECOND
+    }
+
+    /// `crack_time_seconds` in words, e.g. "3 hours" or "centuries".
+    pub fn crack_time_display(&self) -> String {
+        const UNITS: &[(f64, &str)] = &[
+            (60.0, "second"),
+            (60.0, "minute"),
+            (24.0, "hour"),
+            (30.0, "day"),
+            (12.0, "month"),
+            (100.0, "year"),
+        ];
+        let mut amount = self.crack_time_seconds();
+        if amount < 1.0 {
+            return "less than a second".to_string();
+        }
+        for (size, unit) in UNITS {
+            if amount < *size {
+                let amount = amount.round() as u64;
+                return format!("{} {}{}", amount, unit, if amount == 1 { "" } else { "s" });
+            }
+            amount /= size;
+        }
+        "centuries".to_string()
+    }
+}
+
+/// Estimate how strong `password` is and explain what weakens it.
+///
+/// The level is zxcvbn's 0-4 score and the guess count is its estimate, so
+/// `crack_time_seconds` is its offline slow-hashing crack time. zxcvbn's
+/// warning becomes the reason and its first suggestion the tip.
+#[cfg(feature = "zxcvbn")]
+pub fn estimate_strength(password: &str) -> StrengthReport {
+    let entropy = zxcvbn::zxcvbn(password, &[]);
+    let level = match u8::from(entropy.score()) {
+        0 => StrengthLevel::VeryWeak,
+        1 => StrengthLevel::Weak,
+        2 => StrengthLevel::Fair,
+        3 => StrengthLevel::Good,
+        _ => StrengthLevel::Strong,
+    };
+    let sentence = |text: String| text.trim_end_matches('.').to_string();
+    let feedback = entropy.feedback();
+
+    let reasons = feedback
+        .and_then(|feedback| feedback.warning())
+        .map(|warning| sentence(warning.to_string()))
+        .into_iter()
+        .collect();
+    // zxcvbn only gives feedback up to a score of 2
+    let tip = match feedback.and_then(|feedback| feedback.suggestions().first()) {
+        _ if level == StrengthLevel::Strong => None,
+        Some(suggestion) => Some(sentence(suggestion.to_string())),
+        None => Some("Add another word or two".to_string()),
+    };
+
+    StrengthReport {
+        level,
+        reasons,
+        // The empty password needs no guesses, which zxcvbn reports as -inf
+        guesses_log10: entropy.guesses_log10().max(0.0),
+        tip,
+    }
+}
+
+/// Estimate how strong `password` is and explain what weakens it.
+///
+/// The level is the lower of two ratings: one from length and character
+/// variety, and one from the number of guesses an attacker trying common
+/// passwords, dictionary words, sequences and repeats before brute force
+/// would need, in the style of zxcvbn. Used when pwgen-core is built
+/// without the `zxcvbn` feature.
+#[cfg(not(feature = "zxcvbn"))]
+pub fn estimate_strength(password: &str) -> StrengthReport {
+    let length = password.chars().count();
+    let lower = password.to_lowercase();
+    let mut reasons = Vec::new();
+    // Most important first: whatever the attacker would try earliest
+    let mut tips = Vec::new();
+
+    let classes = [
+        (password.chars().any(|c| c.is_uppercase()), "uppercase letters"),
+        (password.chars().any(|c| c.is_lowercase()), "lowercase letters"),
+        (password.chars().any(|c| c.is_numeric()), "numbers"),
+        (password.chars().any(|c| !c.is_alphanumeric()), "symbols"),
+    ];
+    let variety = classes.iter().filter(|(present, _)| *present).count();
+
+    let mut level = match (length, variety) {
+        (0..=7, _) => StrengthLevel::VeryWeak,
+        (8..=11, 1..=2) => StrengthLevel::Weak,
+        (8..=11, 3..=4) => StrengthLevel::Fair,
+        (12..=15, 1..=2) => StrengthLevel::Fair,
+        (12..=15, 3..=4) => StrengthLevel::Good,
+        (16.., 1..=2) => StrengthLevel::Good,
+        (16.., 3..=4) => StrengthLevel::Strong,
+        _ => StrengthLevel::Weak,
+    };
+
+    if length < 8 {
+        reasons.push(format!("Too short: {} characters (use at least 12)", length));
+        tips.push((1, "Add length"));
+    } else if length < 12 {
+        reasons.push(format!("Shorter than the recommended 12 characters ({})", length));
+        tips.push((4, "Add length"));
+    }
+
+    if variety < 3 && length > 0 {
+        let missing: Vec<&str> = classes
+            .iter()
+            .filter(|(present, _)| !*present)
+            .map(|(_, name)| *name)
+            .collect();
+        reasons.push(format!("Only {} of 4 character types (no {})", variety, missing.join(", ")));
+        tips.push((5, "Mix in other character types"));
+    }
+
+    if COMMON_PASSWORDS.contains(&lower.as_str()) {
+        reasons.push("Matches a password found in public breach lists".to_string());
+        tips.push((0, "Avoid common passwords"));
+        level = StrengthLevel::VeryWeak;
+    } else if let Some(word) = find_dictionary_word(&lower) {
+        reasons.push(format!("Contains the dictionary word \"{}\"", word));
+        if length < PASSPHRASE_LENGTH {
+            tips.push((2, "Avoid common words"));
+            level = level.downgrade();
+        }
+    }
+
+    if let Some(run) = find_sequence(&lower) {
+        reasons.push(format!("Contains the sequence \"{}\"", run));
+        tips.push((3, "Avoid sequences like abc or qwerty"));
+        level = level.downgrade();
+    }
+
+    if let Some(c) = find_repeat(password) {
+        reasons.push(format!("Repeats the character '{}' three or more times in a row", c));
+        tips.push((3, "Avoid repeated characters"));
+        level = level.downgrade();
+    }
+
+    let guesses_log10 = estimate_guesses_log10(password);
+    level = level.min(StrengthLevel::from_guesses_log10(guesses_log10));
+
+    let tip = match tips.into_iter().min_by_key(|(priority, _)| *priority) {
+        _ if level == StrengthLevel::Strong => None,
+        Some((_, tip)) => Some(tip.to_string()),
+        None => Some("Add length".to_string()),
+    };
+
+    StrengthReport { level, reasons, guesses_log10, tip }
+}
+
+#[cfg(not(feature = "zxcvbn"))]
+/// Rank of each common password and passphrase word in guessing order.
+fn dictionary_ranks() -> &'static HashMap<&'static str, usize> {
+    static RANKS: OnceLock<HashMap<&'static str, usize>> = OnceLock::new();
+    RANKS.get_or_init(|| {
+        let mut ranks = HashMap::new();
+        for (rank, word) in COMMON_PASSWORDS.iter().chain(WORD_LIST.iter()).enumerate() {
+            ranks.entry(*word).or_insert(rank + 1);
+        }
+        ranks
+    })
+}
+
+#[cfg(not(feature = "zxcvbn"))]
+/// Split the password into the cheapest sequence of guessable pieces and add
+/// up their cost: dictionary words by rank (doubled for capitals), runs and
+/// keyboard walks by start and direction, repeats by character and count, and
+/// anything else by brute force over the character classes used.
+fn estimate_guesses_log10(password: &str) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    if chars.is_empty() {
+        return 0.0;
+    }
+    let lower: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    if lower.len() != chars.len() {
+        // Case mapping changed the length; only brute force lines up
+        return chars.len() as f64 * brute_force_cardinality(&chars).log10();
+    }
+
+    let brute_force = brute_force_cardinality(&chars).log10();
+    let ranks = dictionary_ranks();
+    let mut best = vec![f64::INFINITY; chars.len() + 1];
+    best[0] = 0.0;
+
+    for start in 0..chars.len() {
+        let base = best[start];
+        if base.is_infinite() {
+            continue;
+        }
+        let mut relax = |end: usize, cost: f64| {
+            if base + cost < best[end] {
+                best[end] = base + cost;
+            }
+        };
+
+        relax(start + 1, brute_force);
+
+        for end in start + 1..=chars.len() {
+            let piece = &lower[start..end];
+            let word: String = piece.iter().collect();
+            if let Some(rank) = ranks.get(word.as_str()) {
+                let uppercase = chars[start..end].iter().filter(|c| c.is_uppercase()).count();
+                let case_cost = match uppercase {
+                    0 => 0.0,
+                    1 if chars[start].is_uppercase() => 2f64.log10(),
+                    n => n as f64 * 2f64.log10(),
+                };
+                relax(end, (*rank as f64).log10() + case_cost);
+            }
+
+            let length = end - start;
+            if length >= 3 {
+                if is_sequence(piece) {
+                    relax(end, (36.0 * 2.0 * length as f64).log10());
+                }
+                if piece.iter().all(|c| *c == piece[0]) {
+                    relax(end, (brute_force_cardinality(&chars[start..start + 1]) * length as f64).log10());
+                }
+            }
+        }
+    }
+
+    best[chars.len()]
+}
+
+#[cfg(not(feature = "zxcvbn"))]
+/// Size of the character set a brute-force attack on `chars` has to cover.
+fn brute_force_cardinality(chars: &[char]) -> f64 {
+    let mut cardinality = 0.0;
+    if chars.iter().any(|c| c.is_lowercase()) {
+        cardinality += 26.0;
+    }
+    if chars.iter().any(|c| c.is_uppercase()) {
+        cardinality += 26.0;
+    }
+    if chars.iter().any(|c| c.is_numeric()) {
+        cardinality += 10.0;
+    }
+    if chars.iter().any(|c| !c.is_alphanumeric()) {
+        cardinality += 33.0;
+    }
+    f64::max(cardinality, 10.0)
+}
+
+#[cfg(not(feature = "zxcvbn"))]
+/// Whether the lower-cased `piece` is an alphabetical/numerical run or lies
+/// on a keyboard row, in either direction.
+fn is_sequence(piece: &[char]) -> bool {
+    let step = piece[1] as i32 - piece[0] as i32;
+    let stepped = piece.iter().all(|c| c.is_ascii_alphanumeric())
+        && step.abs() == 1
+        && piece.windows(2).all(|w| w[1] as i32 - w[0] as i32 == step);
+
+    let run: String = piece.iter().collect();
+    let reversed: String = piece.iter().rev().collect();
+    stepped || KEYBOARD_ROWS.iter().any(|row| row.contains(&run) || row.contains(&reversed))
+}
+
+#[cfg(not(feature = "zxcvbn"))]
+fn find_dictionary_word(lower: &str) -> Option<&'static str> {
+    COMMON_PASSWORDS
+        .iter()
+        .chain(WORD_LIST.iter())
+        .filter(|word| word.len() >= 4 && word.chars().all(char::is_alphabetic))
+        .find(|word| lower.contains(*word))
+        .copied()
+}
+
+#[cfg(not(feature = "zxcvbn"))]
+/// Find a run of at least three consecutive characters that are either
+/// alphabetical/numerical steps ("abc", "321") or a keyboard walk ("qwe").
+fn find_sequence(lower: &str) -> Option<String> {
+    let chars: Vec<char> = lower.chars().collect();
+    for window in chars.windows(3) {
+        let (a, b, c) = (window[0] as i32, window[1] as i32, window[2] as i32);
+        let step = b - a;
+        if window.iter().all(|c| c.is_ascii_alphanumeric()) && step.abs() == 1 && c - b == step {
+            return Some(window.iter().collect());
+        }
+
+        let run: String = window.iter().collect();
+        let reversed: String = window.iter().rev().collect();
+        if KEYBOARD_ROWS.iter().any(|row| row.contains(&run) || row.contains(&reversed)) {
+            return Some(run);
+        }
+    }
+    None
+}
+
+#[cfg(not(feature = "zxcvbn"))]
+fn find_repeat(password: &str) -> Option<char> {
+    let chars: Vec<char> = password.chars().collect();
+    chars
+        .windows(3)
+        .find(|w| w[0] == w[1] && w[1] == w[2])
+        .map(|w| w[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "zxcvbn"))]
+    fn test_common_password_is_very_weak() {
+        let report = estimate_strength("password");
+        assert_eq!(report.level, StrengthLevel::VeryWeak);
+        assert!(report.reasons.iter().any(|r| r.contains("breach")));
+    }
+
+    #[test]
+    #[cfg(not(feature = "zxcvbn"))]
+    fn test_reasons_for_short_sequential_password() {
+        let report = estimate_strength("abc123");
+        assert_eq!(report.level, StrengthLevel::VeryWeak);
+        assert!(report.reasons.iter().any(|r| r.starts_with("Too short")));
+        assert!(report.reasons.iter().any(|r| r.contains("sequence")));
+    }
+
+    #[test]
+    #[cfg(not(feature = "zxcvbn"))]
+    fn test_dictionary_word_and_repeat_downgrade() {
+        let report = estimate_strength("Balloon!!!2024x");
+        assert!(report.reasons.iter().any(|r| r.contains("\"balloon\"")));
+        assert!(report.reasons.iter().any(|r| r.contains("'!'")));
+        assert!(report.level < StrengthLevel::Good);
+    }
+
+    #[test]
+    #[cfg(not(feature = "zxcvbn"))]
+    fn test_tip_names_the_biggest_weakness() {
+        let tip = |password| estimate_strength(password).tip;
+        assert_eq!(tip("password").as_deref(), Some("Avoid common passwords"));
+        assert_eq!(tip("x9#K").as_deref(), Some("Add length"));
+        assert_eq!(tip("Balloon!!!2024x").as_deref(), Some("Avoid common words"));
+        assert_eq!(tip("T7#mK9$vQ2@xW4&z"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "zxcvbn")]
+    fn test_zxcvbn_catches_names_and_common_passwords() {
+        // None of these are in the passphrase word list
+        for password in ["jennifer", "Michael1985", "charlie2019"] {
+            let report = estimate_strength(password);
+            assert!(report.level <= StrengthLevel::Weak, "{}: {:?}", password, report);
+            assert!(report.tip.is_some());
+        }
+
+        let report = estimate_strength("password");
+        assert_eq!(report.level, StrengthLevel::VeryWeak);
+        assert!(report.reasons.iter().any(|r| r.contains("common password")), "{:?}", report);
+    }
+
+    #[test]
+    fn test_random_password_has_no_reasons() {
+        let report = estimate_strength("T7#mK9$vQ2@xW4&z");
+        assert_eq!(report.level, StrengthLevel::Strong);
+        assert!(report.reasons.is_empty());
+    }
+
+    #[test]
+    fn test_guess_estimate_catches_predictable_patterns() {
+        // Long with all four character types, but built from a keyboard walk
+        // and a common password
+        #[cfg(not(feature = "zxcvbn"))]
+        {
+            let report = estimate_strength("Qwertyuiop!Password1");
+            assert!(report.level <= StrengthLevel::Weak, "{:?}", report);
+            assert!(report.crack_time_seconds() < 60.0, "{}", report.crack_time_display());
+        }
+
+        let passphrase = estimate_strength("correcthorsebatterystaple");
+        assert!(passphrase.level >= StrengthLevel::Good, "{:?}", passphrase);
+        assert_eq!(passphrase.crack_time_display(), "centuries");
+        assert_eq!(estimate_strength("").score(), 0);
+    }
+
+    #[test]
+    fn test_capped_at_entropy() {
+        let report = estimate_strength("T7#mK9$vQ2@xW4&z");
+        let capped = report.clone().capped_at_entropy(20.0);
+        assert_eq!(capped.level, StrengthLevel::Fair);
+        assert!((capped.guesses_log10 - 20.0 * 2f64.log10()).abs() < 1e-9);
+        // A cap above the estimate changes nothing
+        assert_eq!(report.clone().capped_at_entropy(500.0).level, report.level);
+    }
+}
@@ -1,11 +1,43 @@
+//! Teams, permissions and sharing secrets with team members.
+//!
+//! # Threat model
+//!
+//! A secret shared with a team is encrypted with a random content key, and
+//! that key is sealed to the X25519 public key of every active member
+//! (`crypto_box` sealed boxes: an ephemeral key pair per copy, so the
+//! sender cannot be identified from the blob). A `SharedSecret` holds only
+//! ciphertext, so it can be stored or synced through untrusted places; only
+//! a member holding their private key can read it.
+//!
+//! This protects against anyone who gets hold of the stored share without a
+//! member's private key, including people who were never members. It does
+//! not protect against:
+//! - a member who has already decrypted the secret. Revoking their access
+//!   drops their sealed copy of the key, but what they have seen stays seen;
+//!   change the secret itself after removing someone.
+//! - substituted public keys. Keys are trusted as they are entered in the
+//!   team, so check them with the member out-of-band.
+//! - a compromised device of the sharer or of a member.
+//!
+//! Permissions (`Permission`) are enforced by this code, not by the
+//! cryptography: every member who can decrypt a share can read it.
+
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use crypto_box::{aead::OsRng, PublicKey, SecretKey};
 use uuid::Uuid;
 use zeroize::Zeroize;
 
 use crate::{Result, Error};
 use crate::secrets::DecryptedSecretEntry;
 use crate::crypto::MasterKey;
+use crate::generator::PasswordGenerator;
+
+/// Wrong passphrases accepted before a one-time share is destroyed.
+pub const MAX_SHARE_REDEEM_ATTEMPTS: u32 = 5;
+
+/// Words in a generated one-time share passphrase.
+const SHARE_PASSPHRASE_WORDS: usize = 5;
 
 /// Permission levels for team members
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -20,12 +52,49 @@ pub enum Permission {
     Admin,
 }
 
+/// A team member's X25519 key pair for receiving shared secrets. The public
+/// half goes in `TeamMember::public_key`; the private half never leaves the
+/// member's own vault. The private key is wiped from memory on drop.
+pub struct MemberKeyPair {
+    secret_key: SecretKey,
+}
+
+impl MemberKeyPair {
+    pub fn generate() -> Self {
+        Self { secret_key: SecretKey::generate(&mut OsRng) }
+    }
+
+    /// The key pair for a 32 byte private key saved with `private_key`.
+    pub fn from_private_key(bytes: &[u8]) -> Result<Self> {
+        let secret_key = SecretKey::from_slice(bytes)
+            .map_err(|_| Error::Decryption("Invalid member private key".to_string()))?;
+        Ok(Self { secret_key })
+    }
+
+    pub fn public_key(&self) -> Vec<u8> {
+        self.secret_key.public_key().as_bytes().to_vec()
+    }
+
+    pub fn private_key(&self) -> [u8; 32] {
+        self.secret_key.to_bytes()
+    }
+}
+
+impl std::fmt::Debug for MemberKeyPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemberKeyPair")
+            .field("public_key", &hex::encode(self.public_key()))
+            .finish_non_exhaustive()
+    }
+}
+
 /// Team member information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TeamMember {
     pub id: String,
     pub email: String,
     pub name: String,
+    /// X25519 public key, see `MemberKeyPair`
     pub public_key: Vec<u8>,
     pub role: Permission,
     pub added_at: DateTime<Utc>,
@@ -55,13 +124,25 @@ pub struct SharedSecret {
     pub shared_by: String,
     pub shared_at: DateTime<Utc>,
     pub permissions: Permission,
-    pub encrypted_secret_key: Vec<u8>, // Secret key encrypted with team's public key
+    /// The secret, encrypted with a random content key
+    pub encrypted_payload: Vec<u8>,
+    /// The content key, sealed to each member the secret is shared with
+    pub wrapped_keys: Vec<WrappedKey>,
     pub expiration: Option<DateTime<Utc>>,
     pub access_count: u64,
     pub last_accessed: Option<DateTime<Utc>>,
     pub is_active: bool,
 }
 
+/// One member's copy of a shared secret's content key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrappedKey {
+    pub member_id: String,
+    /// The public key the content key was sealed to
+    pub public_key: Vec<u8>,
+    pub sealed_key: Vec<u8>,
+}
+
 /// Share request for sharing secrets with team members
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShareRequest {
@@ -85,6 +166,62 @@ pub enum ShareRequestStatus {
     Expired,
 }
 
+/// A secret handed out once, without adding the recipient to a team.
+///
+/// The secret is encrypted with a key derived from a passphrase that is
+/// delivered out-of-band. The ciphertext is wiped when the share is redeemed,
+/// expires, is revoked or sees too many wrong passphrases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OneTimeShare {
+    pub id: String,
+    pub secret_id: String,
+    pub secret_name: String,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub status: OneTimeShareStatus,
+    pub redeemed_at: Option<DateTime<Utc>>,
+    pub failed_attempts: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OneTimeShareStatus {
+    Active,
+    Redeemed,
+    Expired,
+    Revoked,
+    /// Wiped after `MAX_SHARE_REDEEM_ATTEMPTS` wrong passphrases.
+    Destroyed,
+}
+
+impl OneTimeShareStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OneTimeShareStatus::Active => "active",
+            OneTimeShareStatus::Redeemed => "redeemed",
+            OneTimeShareStatus::Expired => "expired",
+            OneTimeShareStatus::Revoked => "revoked",
+            OneTimeShareStatus::Destroyed => "destroyed",
+        }
+    }
+}
+
+impl std::str::FromStr for OneTimeShareStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "active" => Ok(OneTimeShareStatus::Active),
+            "redeemed" => Ok(OneTimeShareStatus::Redeemed),
+            "expired" => Ok(OneTimeShareStatus::Expired),
+            "revoked" => Ok(OneTimeShareStatus::Revoked),
+            "destroyed" => Ok(OneTimeShareStatus::Destroyed),
+            _ => Err(Error::Other(format!("Unknown share status: {}", s))),
+        }
+    }
+}
+
 /// Access log entry for audit purposes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccessLog {
@@ -248,14 +385,15 @@ impl TeamSharingManager {
         Ok(())
     }
 
-    /// Share a secret with a team
-    pub fn share_secret_with_team(
+    /// Share a secret with every active member of `team`. The secret is
+    /// encrypted with a fresh content key, which is sealed to each member's
+    /// public key; see the module documentation for what this protects.
+    pub fn share_secret(
         secret: &DecryptedSecretEntry,
         team: &Team,
         shared_by: &str,
         permissions: Permission,
         expiration: Option<DateTime<Utc>>,
-        secret_key: &[u8],
     ) -> Result<SharedSecret> {
         // Check if the person sharing has the right to share
         let sharer = team.members.iter()
@@ -266,11 +404,24 @@ impl TeamSharingManager {
             return Err(Error::Other("Insufficient permissions to share secrets".to_string()));
         }
 
-        // For now, we'll use a simple approach - in a real implementation,
-        // you'd use proper public key cryptography to encrypt the secret key
-        // Using a dummy encryption for demonstration purposes
-        let master_key = MasterKey::derive_from_password("dummy", &[0u8; 32])?;
-        let encrypted_secret_key = master_key.encrypt(secret_key)?;
+        let content_key = MasterKey::generate();
+        let mut plaintext = serde_json::to_vec(secret)?;
+        let encrypted_payload = content_key.encrypt(&plaintext);
+        plaintext.zeroize();
+
+        let wrapped_keys = team.members.iter()
+            .filter(|m| m.is_active)
+            .map(|member| {
+                let public_key = Self::member_public_key(member)?;
+                let sealed_key = public_key.seal(&mut OsRng, content_key.as_bytes())
+                    .map_err(|_| Error::Encryption(format!("Could not seal the key for {}", member.email)))?;
+                Ok(WrappedKey {
+                    member_id: member.id.clone(),
+                    public_key: member.public_key.clone(),
+                    sealed_key,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
 
         Ok(SharedSecret {
             id: Uuid::new_v4().to_string(),
@@ -279,7 +430,8 @@ impl TeamSharingManager {
             shared_by: shared_by.to_string(),
             shared_at: Utc::now(),
             permissions,
-            encrypted_secret_key,
+            encrypted_payload: encrypted_payload?,
+            wrapped_keys,
             expiration,
             access_count: 0,
             last_accessed: None,
@@ -287,6 +439,42 @@ impl TeamSharingManager {
         })
     }
 
+    /// Decrypt a secret shared with the member holding `member_private_key`.
+    pub fn decrypt_shared_secret(
+        shared_secret: &SharedSecret,
+        member_private_key: &MemberKeyPair,
+    ) -> Result<DecryptedSecretEntry> {
+        if !shared_secret.is_active {
+            return Err(Error::Other("This share was revoked".to_string()));
+        }
+        if shared_secret.expiration.is_some_and(|expiration| Utc::now() > expiration) {
+            return Err(Error::Other("This share has expired".to_string()));
+        }
+
+        let public_key = member_private_key.public_key();
+        let wrapped = shared_secret.wrapped_keys.iter()
+            .find(|k| k.public_key == public_key)
+            .ok_or_else(|| Error::Decryption("This secret is not shared with your key".to_string()))?;
+        let mut key_bytes = member_private_key.secret_key.unseal(&wrapped.sealed_key)
+            .map_err(|_| Error::Decryption("Could not unseal the shared key".to_string()))?;
+        let content_key = MasterKey::from_bytes(&key_bytes);
+        key_bytes.zeroize();
+
+        let mut plaintext = content_key?.decrypt(&shared_secret.encrypted_payload)?;
+        let secret = serde_json::from_slice(&plaintext);
+        plaintext.zeroize();
+        Ok(secret?)
+    }
+
+    fn member_public_key(member: &TeamMember) -> Result<PublicKey> {
+        // An all-zero key would seal to a key anyone can compute
+        if member.public_key.iter().all(|&b| b == 0) {
+            return Err(Error::Other(format!("{} has no public key", member.email)));
+        }
+        PublicKey::from_slice(&member.public_key)
+            .map_err(|_| Error::Other(format!("{} has an invalid public key", member.email)))
+    }
+
     /// Create a share request
     pub fn create_share_request(
         secret_id: String,
@@ -385,19 +573,20 @@ impl TeamSharingManager {
 
     /// Check if a permission level allows a required permission
     fn permission_allows(granted: &Permission, required: &Permission) -> bool {
-        match (granted, required) {
-            (Permission::Admin, _) => true,
-            (Permission::Share, Permission::Share) => true,
-            (Permission::Share, Permission::Write) => true,
-            (Permission::Share, Permission::Read) => true,
-            (Permission::Write, Permission::Write) => true,
-            (Permission::Write, Permission::Read) => true,
-            (Permission::Read, Permission::Read) => true,
-            _ => false,
-        }
+        matches!(
+            (granted, required),
+            (Permission::Admin, _)
+                | (Permission::Share, Permission::Share)
+                | (Permission::Share, Permission::Write)
+                | (Permission::Share, Permission::Read)
+                | (Permission::Write, Permission::Write)
+                | (Permission::Write, Permission::Read)
+                | (Permission::Read, Permission::Read)
+        )
     }
 
     /// Log access to a secret
+    #[allow(clippy::too_many_arguments)]
     pub fn log_access(
         secret_id: String,
         user_id: String,
@@ -470,6 +659,39 @@ impl TeamSharingManager {
         Ok(user_secrets)
     }
 
+    /// A random passphrase for a one-time share, e.g. `mirror-answer-ocean-...`.
+    pub fn generate_share_passphrase() -> Result<String> {
+        PasswordGenerator::generate_passphrase(SHARE_PASSPHRASE_WORDS, "-", false, false)
+            .map(|passphrase| passphrase.phrase)
+    }
+
+    /// Encrypt `secret` for a one-time share. Returns the salt and the
+    /// ciphertext; neither reveals anything without the passphrase.
+    pub fn seal_one_time_share(
+        secret: &DecryptedSecretEntry,
+        passphrase: &str,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let salt = MasterKey::generate_salt();
+        let key = MasterKey::derive_from_password(passphrase, &salt)?;
+        let mut plaintext = serde_json::to_vec(secret)?;
+        let ciphertext = key.encrypt(&plaintext);
+        plaintext.zeroize();
+        Ok((salt, ciphertext?))
+    }
+
+    /// Decrypt a one-time share sealed by `seal_one_time_share`.
+    pub fn open_one_time_share(
+        salt: &[u8],
+        ciphertext: &[u8],
+        passphrase: &str,
+    ) -> Result<DecryptedSecretEntry> {
+        let key = MasterKey::derive_from_password(passphrase.trim(), salt)?;
+        let mut plaintext = key.decrypt(ciphertext)?;
+        let secret = serde_json::from_slice(&plaintext);
+        plaintext.zeroize();
+        Ok(secret?)
+    }
+
     /// Revoke access to a shared secret
     pub fn revoke_shared_secret(
         shared_secret: &mut SharedSecret,
@@ -491,6 +713,38 @@ impl TeamSharingManager {
         }
 
         shared_secret.is_active = false;
+        shared_secret.wrapped_keys.clear();
+        shared_secret.encrypted_payload.zeroize();
+        shared_secret.encrypted_payload.clear();
+        Ok(())
+    }
+
+    /// Take one member's access to a shared secret away by dropping their
+    /// sealed copy of the content key. Call this for each share when
+    /// removing someone from a team.
+    pub fn revoke_member_access(
+        shared_secret: &mut SharedSecret,
+        member_id: &str,
+        revoked_by: &str,
+        teams: &[Team],
+    ) -> Result<()> {
+        let team = teams.iter()
+            .find(|t| t.id == shared_secret.team_id)
+            .ok_or_else(|| Error::Other("Team not found".to_string()))?;
+
+        let revoker = team.members.iter()
+            .find(|m| m.id == revoked_by)
+            .ok_or_else(|| Error::Other("User not found in team".to_string()))?;
+
+        if revoker.role != Permission::Admin && shared_secret.shared_by != revoked_by {
+            return Err(Error::Other("Insufficient permissions to revoke access".to_string()));
+        }
+
+        let before = shared_secret.wrapped_keys.len();
+        shared_secret.wrapped_keys.retain(|k| k.member_id != member_id);
+        if shared_secret.wrapped_keys.len() == before {
+            return Err(Error::Other("Secret is not shared with this member".to_string()));
+        }
         Ok(())
     }
 }
@@ -504,6 +758,105 @@ impl Drop for TeamMember {
 
 impl Drop for SharedSecret {
     fn drop(&mut self) {
-        self.encrypted_secret_key.zeroize();
+        self.encrypted_payload.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets::{SecretData, SecretMetadata, SecretType};
+
+    fn deploy_token() -> DecryptedSecretEntry {
+        DecryptedSecretEntry {
+            id: Uuid::new_v4().to_string(),
+            name: "Deploy token".to_string(),
+            description: None,
+            secret_type: SecretType::Password,
+            data: SecretData::Password {
+                username: "deploy".to_string(),
+                password: "s3cr3t-t0ken".to_string(),
+                url: None,
+                notes: None,
+            },
+            metadata: SecretMetadata::default(),
+            tags: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            last_accessed: None,
+            expires_at: None,
+            favorite: false,
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_shared_secret_is_sealed_to_members() {
+        let owner_key = MemberKeyPair::generate();
+        let member_key = MemberKeyPair::generate();
+        let outsider_key = MemberKeyPair::generate();
+
+        let mut team = TeamSharingManager::create_team(
+            "Ops".to_string(),
+            None,
+            "owner".to_string(),
+            "owner@example.com".to_string(),
+            "Owner".to_string(),
+            owner_key.public_key(),
+        )
+        .unwrap();
+        TeamSharingManager::add_team_member(
+            &mut team,
+            "member".to_string(),
+            "member@example.com".to_string(),
+            "Member".to_string(),
+            member_key.public_key(),
+            Permission::Read,
+            "owner",
+        )
+        .unwrap();
+
+        let secret = deploy_token();
+        let mut shared = TeamSharingManager::share_secret(&secret, &team, "owner", Permission::Read, None).unwrap();
+        assert_eq!(shared.wrapped_keys.len(), 2);
+        // Neither the payload nor the sealed keys contain the secret
+        let stored = serde_json::to_string(&shared).unwrap();
+        assert!(!stored.contains("s3cr3t-t0ken"));
+
+        let opened = TeamSharingManager::decrypt_shared_secret(&shared, &member_key).unwrap();
+        assert_eq!(opened.id, secret.id);
+        assert!(matches!(opened.data, SecretData::Password { ref password, .. } if password == "s3cr3t-t0ken"));
+
+        // A non-member cannot decrypt, even by borrowing a member's sealed copy
+        assert!(TeamSharingManager::decrypt_shared_secret(&shared, &outsider_key).is_err());
+        let mut forged = shared.clone();
+        forged.wrapped_keys[1].public_key = outsider_key.public_key();
+        assert!(TeamSharingManager::decrypt_shared_secret(&forged, &outsider_key).is_err());
+
+        let restored = MemberKeyPair::from_private_key(&member_key.private_key()).unwrap();
+        assert_eq!(restored.public_key(), member_key.public_key());
+
+        let teams = vec![team];
+        assert!(TeamSharingManager::revoke_member_access(&mut shared, "member", "member", &teams).is_err());
+        TeamSharingManager::revoke_member_access(&mut shared, "member", "owner", &teams).unwrap();
+        assert!(TeamSharingManager::decrypt_shared_secret(&shared, &member_key).is_err());
+        assert!(TeamSharingManager::decrypt_shared_secret(&shared, &owner_key).is_ok());
+
+        TeamSharingManager::revoke_shared_secret(&mut shared, "owner", &teams).unwrap();
+        assert!(shared.wrapped_keys.is_empty());
+        assert!(TeamSharingManager::decrypt_shared_secret(&shared, &owner_key).is_err());
+    }
+
+    #[test]
+    fn test_share_requires_member_keys() {
+        let team = TeamSharingManager::create_team(
+            "Ops".to_string(),
+            None,
+            "owner".to_string(),
+            "owner@example.com".to_string(),
+            "Owner".to_string(),
+            vec![0u8; 32],
+        )
+        .unwrap();
+        assert!(TeamSharingManager::share_secret(&deploy_token(), &team, "owner", Permission::Read, None).is_err());
+    }
+}
@@ -0,0 +1,605 @@
+//! Two-factor seeds as authenticator apps hand them out: `otpauth://totp/`
+//! URIs and Google Authenticator's `otpauth-migration://offline?data=`
+//! export, which packs many seeds into one protobuf message.
+//!
+//! Entries keep a seed in `totp_secret`. Seeds with the usual parameters
+//! (SHA-1, 6 digits, 30 seconds) are stored as the bare base32 key; any
+//! other seed is stored as an `otpauth://` URI carrying its parameters, so
+//! codes for it are still computed correctly.
+
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use hmac::{digest::KeyInit, Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use std::fmt;
+use zeroize::Zeroize;
+
+use crate::{redact::Redacted, Error, Result};
+
+/// Length of a TOTP time step, in seconds, unless the seed says otherwise.
+pub const DEFAULT_PERIOD: u64 = 30;
+
+/// Code length unless the seed says otherwise.
+pub const DEFAULT_DIGITS: u32 = 6;
+
+/// The HMAC a seed's codes are computed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TotpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl TotpAlgorithm {
+    fn from_name(name: &str) -> Result<Self> {
+        match name.to_uppercase().replace('-', "").as_str() {
+            "SHA1" => Ok(Self::Sha1),
+            "SHA256" => Ok(Self::Sha256),
+            "SHA512" => Ok(Self::Sha512),
+            _ => Err(Error::Other(format!("Unsupported TOTP algorithm: {}", name))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA1",
+            Self::Sha256 => "SHA256",
+            Self::Sha512 => "SHA512",
+        }
+    }
+}
+
+/// A TOTP seed with the account it belongs to.
+#[derive(Clone)]
+pub struct TotpSeed {
+    /// Service the seed is for, e.g. "GitHub"
+    pub issuer: Option<String>,
+    /// Account name, usually the username or email address
+    pub account: String,
+    /// Base32 key, upper case without padding
+    secret: String,
+    pub algorithm: TotpAlgorithm,
+    pub digits: u32,
+    /// Seconds each code is valid for
+    pub period: u64,
+}
+
+impl fmt::Debug for TotpSeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TotpSeed")
+            .field("issuer", &self.issuer)
+            .field("account", &self.account)
+            .field("secret", &Redacted)
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .finish()
+    }
+}
+
+impl Drop for TotpSeed {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl TotpSeed {
+    /// A seed with the usual parameters for a base32 key.
+    fn with_secret(secret: String) -> Self {
+        Self {
+            issuer: None,
+            account: String::new(),
+            secret,
+            algorithm: TotpAlgorithm::Sha1,
+            digits: DEFAULT_DIGITS,
+            period: DEFAULT_PERIOD,
+        }
+    }
+
+    /// The seed kept in an entry's `totp_secret`: a base32 key or an
+    /// `otpauth://` URI.
+    pub fn from_stored(stored: &str) -> Result<Self> {
+        if is_otpauth_uri(stored) {
+            parse_otpauth_uri(stored)
+        } else {
+            Ok(Self::with_secret(stored.to_string()))
+        }
+    }
+
+    /// What to keep in an entry's `totp_secret`: the base32 key when the
+    /// parameters are the usual ones, otherwise an `otpauth://` URI without
+    /// a label, since the entry already names the account.
+    pub fn stored_secret(&self) -> String {
+        if self.algorithm == TotpAlgorithm::Sha1 && self.digits == DEFAULT_DIGITS && self.period == DEFAULT_PERIOD {
+            return self.secret.clone();
+        }
+        let mut url = url::Url::parse("otpauth://totp/").expect("static URI is valid");
+        url.query_pairs_mut()
+            .append_pair("secret", &self.secret)
+            .append_pair("algorithm", self.algorithm.name())
+            .append_pair("digits", &self.digits.to_string())
+            .append_pair("period", &self.period.to_string());
+        url.into()
+    }
+
+    /// The site for an entry created from the seed: its issuer, or the
+    /// account name when there is none.
+    pub fn site(&self) -> &str {
+        self.issuer.as_deref().unwrap_or(&self.account)
+    }
+
+    /// The RFC 6238 code at `time`.
+    pub fn code_at(&self, time: DateTime<Utc>) -> Result<String> {
+        let mut key = decode_base32(&self.secret)?;
+        let counter = (time.timestamp().max(0) as u64 / self.period).to_be_bytes();
+        let digest = match self.algorithm {
+            TotpAlgorithm::Sha1 => sign::<Hmac<Sha1>>(&key, &counter),
+            TotpAlgorithm::Sha256 => sign::<Hmac<Sha256>>(&key, &counter),
+            TotpAlgorithm::Sha512 => sign::<Hmac<Sha512>>(&key, &counter),
+        };
+        key.zeroize();
+        let digest = digest?;
+
+        // Dynamic truncation, RFC 4226 section 5.3
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let code = u32::from_be_bytes([
+            digest[offset] & 0x7f,
+            digest[offset + 1],
+            digest[offset + 2],
+            digest[offset + 3],
+        ]) % 10u32.pow(self.digits);
+        Ok(format!("{:0width$}", code, width = self.digits as usize))
+    }
+
+    /// Seconds until the code for `time` is replaced by the next one.
+    pub fn seconds_remaining(&self, time: DateTime<Utc>) -> u64 {
+        self.period - time.timestamp().max(0) as u64 % self.period
+    }
+}
+
+fn sign<M: Mac + KeyInit>(key: &[u8], message: &[u8]) -> Result<Vec<u8>> {
+    let mut mac = <M as Mac>::new_from_slice(key)
+        .map_err(|e| Error::Other(format!("Invalid TOTP secret: {}", e)))?;
+    mac.update(message);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn is_otpauth_uri(input: &str) -> bool {
+    input.trim().to_lowercase().starts_with("otpauth://")
+}
+
+/// Seeds read for import, with the ones that can't be used.
+#[derive(Debug, Default)]
+pub struct TotpImport {
+    pub seeds: Vec<TotpSeed>,
+    /// Account and reason for each seed left out, e.g. counter-based codes
+    pub unsupported: Vec<(String, String)>,
+}
+
+/// Every seed in `text`: one `otpauth://totp/` or `otpauth-migration://`
+/// URI per line. Blank lines and lines starting with `#` are ignored.
+pub fn parse_totp_import(text: &str) -> Result<TotpImport> {
+    let mut import = TotpImport::default();
+    for (number, line) in text.lines().map(str::trim).enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.to_lowercase().starts_with("otpauth-migration://") {
+            let migrated = parse_migration_uri(line)?;
+            import.seeds.extend(migrated.seeds);
+            import.unsupported.extend(migrated.unsupported);
+        } else if is_otpauth_uri(line) {
+            import.seeds.push(parse_otpauth_uri(line)?);
+        } else {
+            // The line itself is left out, since it may be a bare secret
+            return Err(Error::Other(format!("Line {} is not an otpauth:// URI", number + 1)));
+        }
+    }
+    Ok(import)
+}
+
+/// Parse an `otpauth://totp/Issuer:account?secret=...` URI. The issuer is
+/// taken from the `issuer` parameter, or else the label's prefix.
+pub fn parse_otpauth_uri(uri: &str) -> Result<TotpSeed> {
+    let url = url::Url::parse(uri.trim()).map_err(|e| Error::Other(format!("Invalid otpauth URI: {}", e)))?;
+    if !url.scheme().eq_ignore_ascii_case("otpauth") {
+        return Err(Error::Other("Not an otpauth:// URI".to_string()));
+    }
+    match url.host_str().map(str::to_lowercase).as_deref() {
+        Some("totp") => {}
+        Some("hotp") => return Err(Error::Other("Counter-based (HOTP) codes are not supported".to_string())),
+        _ => return Err(Error::Other("otpauth URI is not for TOTP".to_string())),
+    }
+
+    let label = percent_decode(url.path().trim_start_matches('/'));
+    let (label_issuer, account) = match label.split_once(':') {
+        Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim().to_string()),
+        None => (None, label.trim().to_string()),
+    };
+    let mut seed = TotpSeed::with_secret(String::new());
+    seed.account = account;
+    seed.issuer = label_issuer.filter(|issuer| !issuer.is_empty());
+
+    for (key, value) in url.query_pairs() {
+        match key.to_lowercase().as_str() {
+            "secret" => seed.secret = normalize_base32(&value)?,
+            "issuer" if !value.trim().is_empty() => seed.issuer = Some(value.trim().to_string()),
+            "algorithm" => seed.algorithm = TotpAlgorithm::from_name(&value)?,
+            "digits" => {
+                seed.digits = value
+                    .parse()
+                    .ok()
+                    .filter(|digits| (6..=8).contains(digits))
+                    .ok_or_else(|| Error::Other(format!("Unsupported number of TOTP digits: {}", value)))?;
+            }
+            "period" => {
+                seed.period = value
+                    .parse()
+                    .ok()
+                    .filter(|period| *period > 0)
+                    .ok_or_else(|| Error::Other(format!("Invalid TOTP period: {}", value)))?;
+            }
+            _ => {}
+        }
+    }
+    if seed.secret.is_empty() {
+        return Err(Error::Other("otpauth URI has no secret".to_string()));
+    }
+    Ok(seed)
+}
+
+/// Parse a Google Authenticator `otpauth-migration://offline?data=...`
+/// export. Counter-based seeds and MD5 ones are listed as unsupported.
+pub fn parse_migration_uri(uri: &str) -> Result<TotpImport> {
+    let url = url::Url::parse(uri.trim()).map_err(|e| Error::Other(format!("Invalid migration URI: {}", e)))?;
+    let data = url
+        .query_pairs()
+        .find(|(key, _)| key == "data")
+        .map(|(_, value)| value.into_owned())
+        .ok_or_else(|| Error::Other("Migration URI has no data".to_string()))?;
+    // An unescaped '+' in the base64 reads back as a space
+    let data = data.replace(' ', "+");
+    let mut payload = base64::engine::general_purpose::STANDARD
+        .decode(&data)
+        .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(data.trim_end_matches('=')))
+        .map_err(|e| Error::Other(format!("Invalid migration data: {}", e)))?;
+
+    let mut import = TotpImport::default();
+    let result = (|| -> Result<()> {
+        let mut message = ProtoReader::new(&payload);
+        while let Some((field, value)) = message.next_field()? {
+            // MigrationPayload.otp_parameters
+            if let (1, ProtoValue::Bytes(parameters)) = (field, value) {
+                match migrated_seed(parameters)? {
+                    Ok(seed) => import.seeds.push(seed),
+                    Err(unsupported) => import.unsupported.push(unsupported),
+                }
+            }
+        }
+        Ok(())
+    })();
+    payload.zeroize();
+    result?;
+    Ok(import)
+}
+
+/// One `OtpParameters` message of a migration payload, or the account and
+/// why it can't be imported.
+fn migrated_seed(message: &[u8]) -> Result<std::result::Result<TotpSeed, (String, String)>> {
+    let mut seed = TotpSeed::with_secret(String::new());
+    let mut name = String::new();
+    let mut otp_type = 2;
+    let mut algorithm = 1;
+    let mut reader = ProtoReader::new(message);
+    while let Some((field, value)) = reader.next_field()? {
+        match (field, value) {
+            (1, ProtoValue::Bytes(secret)) => seed.secret = encode_base32(secret),
+            (2, ProtoValue::Bytes(bytes)) => name = String::from_utf8_lossy(bytes).into_owned(),
+            (3, ProtoValue::Bytes(bytes)) => {
+                let issuer = String::from_utf8_lossy(bytes).trim().to_string();
+                seed.issuer = (!issuer.is_empty()).then_some(issuer);
+            }
+            (4, ProtoValue::Varint(value)) => algorithm = value,
+            // DigitCount: 1 = six, 2 = eight
+            (5, ProtoValue::Varint(2)) => seed.digits = 8,
+            (6, ProtoValue::Varint(value)) => otp_type = value,
+            _ => {}
+        }
+    }
+
+    // The name repeats the issuer as a prefix, as in an otpauth label
+    seed.account = match (name.split_once(':'), &seed.issuer) {
+        (Some((prefix, account)), Some(issuer)) if prefix.trim() == issuer => account.trim().to_string(),
+        (Some((prefix, account)), None) => {
+            seed.issuer = Some(prefix.trim().to_string());
+            account.trim().to_string()
+        }
+        _ => name.trim().to_string(),
+    };
+
+    let unsupported = match (otp_type, algorithm) {
+        (1, _) => Some("counter-based (HOTP) codes are not supported"),
+        (_, 4) => Some("MD5 codes are not supported"),
+        _ if seed.secret.is_empty() => Some("no secret"),
+        _ => None,
+    };
+    if let Some(reason) = unsupported {
+        return Ok(Err((format!("{} @ {}", seed.account, seed.site()), reason.to_string())));
+    }
+    seed.algorithm = match algorithm {
+        2 => TotpAlgorithm::Sha256,
+        3 => TotpAlgorithm::Sha512,
+        _ => TotpAlgorithm::Sha1,
+    };
+    Ok(Ok(seed))
+}
+
+enum ProtoValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Just enough of the protobuf wire format to read a migration payload.
+struct ProtoReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ProtoReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.data.get(self.position).ok_or_else(truncated)?;
+            self.position += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(truncated())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.position.checked_add(len).filter(|end| *end <= self.data.len()).ok_or_else(truncated)?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn next_field(&mut self) -> Result<Option<(u64, ProtoValue<'a>)>> {
+        if self.position >= self.data.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let value = match key & 0x07 {
+            0 => ProtoValue::Varint(self.varint()?),
+            1 => {
+                self.take(8)?;
+                ProtoValue::Fixed
+            }
+            2 => {
+                let len = self.varint()? as usize;
+                ProtoValue::Bytes(self.take(len)?)
+            }
+            5 => {
+                self.take(4)?;
+                ProtoValue::Fixed
+            }
+            wire_type => return Err(Error::Other(format!("Invalid migration data: wire type {}", wire_type))),
+        };
+        Ok(Some((key >> 3, value)))
+    }
+}
+
+fn truncated() -> Error {
+    Error::Other("Invalid migration data: message is truncated".to_string())
+}
+
+/// Decode `%XX` escapes in an otpauth label.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| input.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Tidy a base32 key as typed or pasted from a setup page: spaces, dashes,
+/// padding and lower case are accepted. Errors if the result is not valid
+/// base32.
+pub(crate) fn normalize_base32(input: &str) -> Result<String> {
+    let secret: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '=')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    decode_base32(&secret)?.zeroize();
+    Ok(secret)
+}
+
+/// Decode RFC 4648 base32, ignoring padding.
+fn decode_base32(input: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for c in input.trim_end_matches('=').chars() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a == c.to_ascii_uppercase() as u8)
+            .ok_or_else(|| Error::Other(format!("Invalid character '{}' in TOTP secret", c)))?;
+        buffer = (buffer << 5) | value as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    if bytes.is_empty() {
+        return Err(Error::Other("TOTP secret is empty".to_string()));
+    }
+    Ok(bytes)
+}
+
+/// Encode RFC 4648 base32 without padding.
+fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer = 0u64;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u64;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    encoded
+}
+
+/// What `Storage::import_totp_seeds` did with each seed, as "account @ site".
+#[derive(Debug, Default)]
+pub struct TotpImportReport {
+    /// Seeds added to an existing entry
+    pub attached: Vec<String>,
+    /// Seeds that got a new entry of their own
+    pub created: Vec<String>,
+    /// Seeds left alone, with the reason
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Whether an entry for `site` is for the service named `issuer`: the same
+/// site, or a domain with the issuer as one of its labels, so "GitHub"
+/// matches "github.com" and "Google" matches "accounts.google.com".
+pub(crate) fn site_matches_issuer(site: &str, issuer: &str) -> bool {
+    let issuer: String = issuer.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_lowercase();
+    if issuer.is_empty() {
+        return false;
+    }
+    let site = crate::models::normalize_site(site);
+    if site.replace(' ', "") == issuer {
+        return true;
+    }
+    let host = site.split(':').next().unwrap_or_default();
+    let labels: Vec<&str> = host.split('.').collect();
+    labels.len() > 1 && labels[..labels.len() - 1].contains(&issuer.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_otpauth_uri_parameters() {
+        let seed = parse_otpauth_uri(
+            "otpauth://totp/ACME%20Co:john.doe%40email.com?secret=HXDMVJECJJWSRB3HWIZR4IFUGFTMXBOZ&issuer=ACME%20Co&algorithm=SHA256&digits=8&period=60",
+        )
+        .unwrap();
+        assert_eq!(seed.issuer.as_deref(), Some("ACME Co"));
+        assert_eq!(seed.account, "john.doe@email.com");
+        assert_eq!(seed.algorithm, TotpAlgorithm::Sha256);
+        assert_eq!((seed.digits, seed.period), (8, 60));
+
+        // Parameters other than the defaults survive being stored
+        let stored = TotpSeed::from_stored(&seed.stored_secret()).unwrap();
+        assert_eq!(stored.algorithm, TotpAlgorithm::Sha256);
+        assert_eq!((stored.digits, stored.period), (8, 60));
+
+        let plain = parse_otpauth_uri("otpauth://totp/alice?secret=gezd-gnbv-gy3t-qojq").unwrap();
+        assert_eq!(plain.issuer, None);
+        assert_eq!(plain.stored_secret(), "GEZDGNBVGY3TQOJQ");
+
+        assert!(parse_otpauth_uri("otpauth://hotp/alice?secret=GEZDGNBVGY3TQOJQ&counter=1").is_err());
+        assert!(parse_otpauth_uri("otpauth://totp/alice?secret=GEZDGNBVGY3TQOJQ&algorithm=MD5").is_err());
+        assert!(parse_otpauth_uri("otpauth://totp/alice?issuer=Example").is_err());
+    }
+
+    #[test]
+    fn test_sha256_and_sha512_rfc6238_vectors() {
+        let time = Utc.timestamp_opt(59, 0).unwrap();
+        for (algorithm, key, code) in [
+            ("SHA256", &b"12345678901234567890123456789012"[..], "46119246"),
+            ("SHA512", &b"1234567890123456789012345678901234567890123456789012345678901234"[..], "90693936"),
+        ] {
+            let uri = format!("otpauth://totp/test?secret={}&algorithm={}&digits=8", encode_base32(key), algorithm);
+            assert_eq!(parse_otpauth_uri(&uri).unwrap().code_at(time).unwrap(), code, "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn test_migration_payload() {
+        // Two seeds: an 8-digit SHA-256 one for "Example", and an HOTP one
+        let mut first = vec![0x0a, 0x0a];
+        first.extend(b"1234567890");
+        first.extend([0x12, 0x0d]);
+        first.extend(b"Example:alice");
+        first.extend([0x1a, 0x07]);
+        first.extend(b"Example");
+        first.extend([0x20, 0x02, 0x28, 0x02, 0x30, 0x02]);
+        let mut second = vec![0x0a, 0x02, 0x01, 0x02, 0x12, 0x03];
+        second.extend(b"bob");
+        second.extend([0x30, 0x01]);
+        let mut payload = vec![0x0a, first.len() as u8];
+        payload.extend(&first);
+        payload.extend([0x0a, second.len() as u8]);
+        payload.extend(&second);
+        payload.extend([0x10, 0x01]);
+        let data = base64::engine::general_purpose::STANDARD.encode(&payload);
+        let uri = format!("otpauth-migration://offline?data={}", url::form_urlencoded::byte_serialize(data.as_bytes()).collect::<String>());
+
+        let import = parse_totp_import(&format!("# exported\n\n{}\n", uri)).unwrap();
+        assert_eq!(import.seeds.len(), 1);
+        let seed = &import.seeds[0];
+        assert_eq!(seed.issuer.as_deref(), Some("Example"));
+        assert_eq!(seed.account, "alice");
+        assert_eq!(seed.algorithm, TotpAlgorithm::Sha256);
+        assert_eq!(seed.digits, 8);
+        assert_eq!(seed.stored_secret(), format!("otpauth://totp/?secret={}&algorithm=SHA256&digits=8&period=30", encode_base32(b"1234567890")));
+        assert_eq!(import.unsupported.len(), 1);
+        assert_eq!(import.unsupported[0].0, "bob @ bob");
+
+        assert!(parse_migration_uri("otpauth-migration://offline?data=CgI").is_err());
+        assert!(parse_totp_import("GEZDGNBVGY3TQOJQ").is_err());
+    }
+
+    #[test]
+    fn test_base32_round_trip_and_issuer_matching() {
+        for bytes in [&b"f"[..], b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            assert_eq!(decode_base32(&encode_base32(bytes)).unwrap(), bytes);
+        }
+        assert_eq!(encode_base32(b"foobar"), "MZXW6YTBOI");
+
+        assert!(site_matches_issuer("https://github.com/login", "GitHub"));
+        assert!(site_matches_issuer("accounts.google.com", "Google"));
+        assert!(site_matches_issuer("Home Router", "home router"));
+        assert!(!site_matches_issuer("github.io.example.com", "com"));
+        assert!(!site_matches_issuer("gitlab.com", "GitHub"));
+    }
+}
@@ -0,0 +1,271 @@
+//! One-shot security review of a vault: weak, reused and old passwords,
+//! entries without two-factor codes on sites that offer them, and expiring
+//! secrets, summed up as a 0-100 score.
+//!
+//! Entries are decrypted once and every password check runs over that list.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{
+    models::{url_host, DecryptedPasswordEntry, SearchFilter},
+    secrets_storage::{ExpiryNotice, SecretsStorage},
+    storage::{group_duplicate_passwords, Storage},
+    strength::{estimate_strength, StrengthLevel},
+    Result,
+};
+
+/// Sites known to offer two-factor codes from an authenticator app. Entries
+/// for them without a TOTP secret are reported by the `missing_totp` check.
+pub const TOTP_CAPABLE_SITES: &[&str] = &[
+    "amazon.com",
+    "apple.com",
+    "atlassian.com",
+    "bitbucket.org",
+    "cloudflare.com",
+    "coinbase.com",
+    "digitalocean.com",
+    "discord.com",
+    "dropbox.com",
+    "facebook.com",
+    "github.com",
+    "gitlab.com",
+    "google.com",
+    "instagram.com",
+    "linkedin.com",
+    "live.com",
+    "microsoft.com",
+    "npmjs.com",
+    "paypal.com",
+    "proton.me",
+    "reddit.com",
+    "slack.com",
+    "stripe.com",
+    "twitter.com",
+    "x.com",
+];
+
+/// Which checks to run. Checks that are off are left out of the report and
+/// the score.
+#[derive(Debug, Clone)]
+pub struct AuditChecks {
+    pub weak: bool,
+    pub reused: bool,
+    /// Report passwords not changed for this many days
+    pub max_age_days: Option<i64>,
+    pub missing_totp: bool,
+    /// Report secrets expiring, and API keys due for rotation, within this
+    /// many days. Needs the secrets storage.
+    pub expiring_within_days: Option<i64>,
+}
+
+impl Default for AuditChecks {
+    fn default() -> Self {
+        Self {
+            weak: true,
+            reused: true,
+            max_age_days: Some(365),
+            missing_totp: false,
+            expiring_within_days: Some(30),
+        }
+    }
+}
+
+/// An entry flagged by a check. Never includes the password.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditedEntry {
+    pub id: String,
+    pub site: String,
+    pub username: String,
+}
+
+impl From<&DecryptedPasswordEntry> for AuditedEntry {
+    fn from(entry: &DecryptedPasswordEntry) -> Self {
+        Self {
+            id: entry.id.clone(),
+            site: entry.site.clone(),
+            username: entry.username.clone(),
+        }
+    }
+}
+
+/// Findings of the checks that ran; `None` for checks that were off.
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultAudit {
+    /// 100 when nothing was found
+    pub score: u8,
+    pub entries_checked: usize,
+    pub weak: Option<Vec<AuditedEntry>>,
+    /// Groups of entries sharing one password
+    pub reused: Option<Vec<Vec<AuditedEntry>>>,
+    pub old: Option<Vec<AuditedEntry>>,
+    pub missing_totp: Option<Vec<AuditedEntry>>,
+    pub expiring: Option<Vec<ExpiryNotice>>,
+}
+
+impl VaultAudit {
+    /// Run `checks` over the vault. Expiring secrets are only checked when
+    /// `secrets` is given.
+    pub async fn run(storage: &Storage, secrets: Option<&SecretsStorage>, checks: &AuditChecks) -> Result<Self> {
+        let entries = storage.search_entries(&SearchFilter::default()).await?;
+        let expiring = match (secrets, checks.expiring_within_days) {
+            (Some(secrets), Some(days)) => Some(secrets.get_expiry_notices(days).await?),
+            _ => None,
+        };
+        Self::from_entries(entries, expiring, checks, Utc::now())
+    }
+
+    /// The audit of already decrypted `entries` and expiry notices.
+    pub fn from_entries(
+        entries: Vec<DecryptedPasswordEntry>,
+        expiring: Option<Vec<ExpiryNotice>>,
+        checks: &AuditChecks,
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        let weak = checks.weak.then(|| {
+            entries
+                .iter()
+                .filter(|entry| !entry.password.is_empty())
+                .filter(|entry| estimate_strength(&entry.password).level <= StrengthLevel::Weak)
+                .map(AuditedEntry::from)
+                .collect()
+        });
+        let old = checks.max_age_days.map(|days| {
+            let cutoff = now - Duration::days(days);
+            entries
+                .iter()
+                .filter(|entry| entry.password_changed_at < cutoff)
+                .map(AuditedEntry::from)
+                .collect()
+        });
+        let missing_totp = checks.missing_totp.then(|| {
+            entries
+                .iter()
+                .filter(|entry| entry.totp_secret.is_none() && offers_totp(entry))
+                .map(AuditedEntry::from)
+                .collect()
+        });
+
+        let entries_checked = entries.len();
+        let reused = if checks.reused {
+            let groups = group_duplicate_passwords(entries)?;
+            Some(groups.iter().map(|group| group.iter().map(AuditedEntry::from).collect()).collect())
+        } else {
+            None
+        };
+
+        let mut audit = Self { score: 100, entries_checked, weak, reused, old, missing_totp, expiring };
+        audit.score = audit.compute_score();
+        Ok(audit)
+    }
+
+    /// Each password check takes off up to its weight, in proportion to the
+    /// share of entries it flags; every expiring secret takes off 2 more, up
+    /// to 10.
+    fn compute_score(&self) -> u8 {
+        let total = self.entries_checked.max(1) as f64;
+        let mut penalty = 0.0;
+        if let Some(weak) = &self.weak {
+            penalty += 40.0 * weak.len() as f64 / total;
+        }
+        if let Some(reused) = &self.reused {
+            penalty += 30.0 * reused.iter().map(Vec::len).sum::<usize>() as f64 / total;
+        }
+        if let Some(old) = &self.old {
+            penalty += 10.0 * old.len() as f64 / total;
+        }
+        if let Some(missing_totp) = &self.missing_totp {
+            penalty += 10.0 * missing_totp.len() as f64 / total;
+        }
+        if let Some(expiring) = &self.expiring {
+            penalty += (2 * expiring.len()).min(10) as f64;
+        }
+        (100.0 - penalty).round().clamp(0.0, 100.0) as u8
+    }
+}
+
+/// Whether any of the entry's addresses is a site in `TOTP_CAPABLE_SITES`,
+/// or a subdomain of one.
+fn offers_totp(entry: &DecryptedPasswordEntry) -> bool {
+    entry.all_urls().into_iter().filter_map(url_host).any(|host| {
+        TOTP_CAPABLE_SITES
+            .iter()
+            .any(|site| host == *site || host.ends_with(&format!(".{}", site)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secrets_storage::ExpiryNoticeKind;
+
+    fn entry(id: &str, site: &str, password: &str, changed_days_ago: i64) -> DecryptedPasswordEntry {
+        let now = Utc::now();
+        DecryptedPasswordEntry {
+            id: id.to_string(),
+            site: site.to_string(),
+            username: "alice".to_string(),
+            password: password.to_string(),
+            notes: None,
+            tags: Vec::new(),
+            created_at: now - Duration::days(changed_days_ago),
+            updated_at: now,
+            last_used: None,
+            password_changed_at: now - Duration::days(changed_days_ago),
+            favorite: false,
+            urls: Vec::new(),
+            created_by: None,
+            modified_by: None,
+            totp_secret: None,
+            password_history: Vec::new(),
+            require_reauth: false,
+            password_policy: None,
+        }
+    }
+
+    #[test]
+    fn test_audit_checks_and_score() {
+        let entries = vec![
+            entry("1", "github.com", "correct-horse-battery-staple-42", 10),
+            entry("2", "mail.google.com", "password", 10),
+            entry("3", "router", "Q7#vLx9!mP2$wZ4k", 400),
+            entry("4", "intranet", "Q7#vLx9!mP2$wZ4k", 10),
+        ];
+        let notice = ExpiryNotice {
+            secret_id: "s1".to_string(),
+            name: "Deploy token".to_string(),
+            kind: ExpiryNoticeKind::ExpiresSoon,
+            due: Utc::now() + Duration::days(3),
+        };
+        let checks = AuditChecks { missing_totp: true, ..Default::default() };
+        let audit = VaultAudit::from_entries(entries.clone(), Some(vec![notice]), &checks, Utc::now()).unwrap();
+
+        assert_eq!(audit.entries_checked, 4);
+        let ids = |found: &Option<Vec<AuditedEntry>>| -> Vec<String> {
+            found.as_ref().unwrap().iter().map(|e| e.id.clone()).collect()
+        };
+        assert_eq!(ids(&audit.weak), ["2"]);
+        assert_eq!(ids(&audit.old), ["3"]);
+        assert_eq!(ids(&audit.missing_totp), ["1", "2"]);
+        let reused = audit.reused.as_ref().unwrap();
+        assert_eq!(reused.len(), 1);
+        assert_eq!(reused[0].len(), 2);
+        assert_eq!(audit.expiring.as_ref().unwrap().len(), 1);
+        // 100 - (40 * 1/4 + 30 * 2/4 + 10 * 1/4 + 10 * 2/4 + 2), rounded
+        assert_eq!(audit.score, 66);
+
+        let only_weak = AuditChecks {
+            weak: true,
+            reused: false,
+            max_age_days: None,
+            missing_totp: false,
+            expiring_within_days: None,
+        };
+        let audit = VaultAudit::from_entries(entries, None, &only_weak, Utc::now()).unwrap();
+        assert!(audit.reused.is_none() && audit.old.is_none() && audit.missing_totp.is_none());
+        assert_eq!(audit.score, 90);
+
+        let empty = VaultAudit::from_entries(Vec::new(), None, &AuditChecks::default(), Utc::now()).unwrap();
+        assert_eq!(empty.score, 100);
+    }
+}
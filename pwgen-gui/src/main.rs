@@ -1,21 +1,69 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+// Storage handles are std mutexes driven through `runtime.block_on`, so guards
+// held across `.await` never cross threads.
+#![allow(clippy::await_holding_lock)]
 
 #[cfg(feature = "clipboard")]
 use arboard::Clipboard;
 use chrono::Utc;
 use eframe::egui;
 use pwgen_core::{
-    generator::{PasswordConfig, PasswordGenerator},
-    models::{DecryptedPasswordEntry, SearchFilter, SortField, SortOrder},
-    storage::Storage,
-    secrets::{DecryptedSecretEntry, SecretType, SecretData, SecretFilter},
-    secrets_storage::SecretsStorage,
+    api_keys::{ApiKeyManager, ApiKeyProvider},
+    backend::search_terms,
+    backup::{BackupManager, BackupProgress, BackupStage, ConflictResolution, RestoreConflict, RestoreOptions},
+    browser_import::{
+        BrowserImporter, BrowserType, CsvColumn, CsvColumnMapping, ImportConfig, ImportFormat, ImportPreview,
+        UrlCleanupRules,
+    },
+    cards::{card_expires_at, luhn_check, mask_card_number, normalize_card_number, parse_expiry, CardBrand},
+    clipboard_managers::detect_clipboard_managers,
+    crypto::{KeyFile, RecoveryKey},
+    document_storage::{
+        CompressionType, DocumentAttachment, DocumentImportOptions, DocumentManager, DEFAULT_MAX_DOCUMENT_SIZE,
+    },
+    entry_rules::{EntryRules, RuleViolation},
+    env_connections::{EnvConnectionManager, EnvDiffKind, EnvFileOptions},
+    external_editor::{edit_in_external_editor, editor_command},
+    fs::write_private_file,
+    generator::{PasswordConfig, PasswordGenerator, Wordlist},
+    identity::{load_device_name, save_device_name},
+    integrity::{IntegrityReport, ItemKind},
+    models::{
+        generate_totp, normalize_totp_secret, requires_reveal_confirmation, totp_seconds_remaining,
+        DecryptedPasswordEntry, PasswordPolicy, SearchFilter, SortField, SortOrder, Trashed,
+    },
+    notes_config::NotesConfigManager,
+    storage::{
+        MergeReport, MergeStrategy, Storage, VacuumStats, DEFAULT_PASSWORD_HISTORY_LIMIT, REUSED_PASSWORD_TAG,
+    },
+    strength::{estimate_strength, StrengthLevel, StrengthReport},
+    secrets::{ConfigFormat, DatabaseType, DecryptedSecretEntry, NoteFormat, SecretType, SecretData, SecretFilter, SecretSortField, SecretSummary, SshKeyType},
+    secrets_storage::{ExpiryNotice, SecretsBreakdown, SecretsStats, SecretsStorage},
+    recent_vaults::{forget_vault, list_recent_vaults, remember_vault},
+    secure_delete::{delete_vault, vault_files},
+    ssh_keys::{SshKeyGenParams, SshKeyManager},
+    snippets::command_snippet,
+    totp::parse_totp_import,
 };
+#[cfg(feature = "breach-check")]
+use pwgen_core::breach::{BreachChecker, BreachedEntry};
+#[cfg(feature = "connection-test")]
+use pwgen_core::connection_test::ConnectionTestError;
+#[cfg(feature = "favicons")]
+use pwgen_core::favicon::{favicon_host, FaviconCache};
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
     path::PathBuf,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
-// System tray functionality disabled - will be re-enabled once dependencies are resolved
+#[cfg(feature = "tray")]
+use tray_icon::menu::MenuEvent;
 
 struct PwGenApp {
     // State
@@ -23,14 +71,30 @@ struct PwGenApp {
     secrets_storage: Arc<Mutex<Option<SecretsStorage>>>,
     entries: Vec<DecryptedPasswordEntry>,
     filtered_entries: Vec<DecryptedPasswordEntry>,
-    secrets: Vec<DecryptedSecretEntry>,
-    filtered_secrets: Vec<DecryptedSecretEntry>,
+    // Every secret without its payload; only `secret_page` is decrypted
+    secrets: Vec<SecretSummary>,
+    // API keys due for rotation, counted when the secrets are loaded
+    rotation_due: usize,
+    // The current tab's page of secrets matching the search and filters
+    secret_page: Vec<DecryptedSecretEntry>,
+    secret_page_type: Option<SecretType>,
+    secret_current_page: usize,
+    secrets_per_page: usize,
+    secret_total_pages: usize,
+    // Secrets tab search and filters; the query is debounced like `search_query`
+    secret_search_query: String,
+    secret_search_due: Option<Instant>,
+    secret_filter_tags: String,
+    secret_filter_environment: Option<String>,
+    secret_filter_favorites: bool,
     
     // UI State
     screen: Screen,
     current_tab: MainTab,
     master_password: String,
     master_password_confirm: String,
+    /// Generate a recovery key when creating the vault
+    create_recovery_key: bool,
     error_message: String,
     success_message: String,
     
@@ -38,14 +102,31 @@ struct PwGenApp {
     show_add_dialog: bool,
     edit_entry: Option<DecryptedPasswordEntry>,
     entry_site: String,
+    // Additional URLs; `entry_site` is the primary one
+    entry_urls: Vec<String>,
     entry_username: String,
     entry_password: String,
+    // Strength of `entry_password`, keyed by a hash of the text it was
+    // estimated for
+    entry_strength: Option<(u64, StrengthReport)>,
+    // Base32 or otpauth:// secret for two-factor codes
+    entry_totp: String,
     entry_notes: String,
     entry_tags: String,
+    // Ask for the master password before revealing or copying the password
+    entry_require_reauth: bool,
+    // The site's password rules; `None` generates from the generator settings
+    entry_policy: Option<PasswordPolicy>,
     show_password: bool,
     
     // Search and pagination
     search_query: String,
+    // Full-text search results for `search_query`, refreshed once typing
+    // pauses for `SEARCH_DEBOUNCE`
+    search_results: Vec<DecryptedPasswordEntry>,
+    search_due: Option<Instant>,
+    // Entries matching the search and filters, across all pages
+    total_filtered: usize,
     search_field: SearchField,
     filter_favorites: bool,
     filter_tags: String,
@@ -66,32 +147,83 @@ struct PwGenApp {
     gen_numbers: bool,
     gen_symbols: bool,
     gen_exclude_ambiguous: bool,
+    gen_mobile_friendly: bool,
+    gen_mode: GeneratorMode,
+    gen_pattern: String,
+    /// Read `gen_pattern` as an `Llll-dddd-s` template rather than a format string
+    gen_pattern_template: bool,
+    gen_words: usize,
+    gen_separator: String,
+    gen_capitalize: bool,
+    gen_with_number: bool,
+    gen_wordlist: Wordlist,
     generated_password: String,
+    // Entropy of the last memorable password, which the strength meter
+    // can't tell from the text alone
+    generated_entropy: Option<(String, f64)>,
     
     // Dialog states
     show_settings: bool,
     show_about: bool,
     show_import: bool,
+    import_strip_query: bool,
+    import_strip_www: bool,
+    import_preview: Option<ImportPreviewState>,
+    import_job: Option<ImportJob>,
+    csv_mapping: Option<CsvMappingState>,
+    // otpauth:// URIs pasted into the import dialog
+    totp_import_text: String,
     show_backup: bool,
     show_statistics: bool,
+    // Loaded when the statistics dialog opens
+    secret_statistics: Option<(SecretsStats, SecretsBreakdown)>,
+    show_recently_used: bool,
+    backup_password: String,
+    backup_password_confirm: String,
+    backup_job: Option<BackupJob>,
+    restore_conflicts: Option<RestoreConflictDialog>,
+    /// Latest connection test per database secret id
+    #[cfg(feature = "connection-test")]
+    connection_tests: HashMap<String, ConnectionTest>,
+    /// Expired and expiring secrets found at unlock, until dismissed
+    expiry_notices: Vec<ExpiryNotice>,
     
     // Secrets management
     show_secrets_view: bool,
     show_add_secret_dialog: bool,
+    // Note or config content open in the external editor
+    external_edit: Option<ExternalEdit>,
+    env_diff: Option<EnvDiffView>,
+    env_export: Option<EnvExport>,
+    document_view: Option<DocumentView>,
+    vault_reset: Option<VaultReset>,
+    password_change: Option<PasswordChange>,
+    recovery_unlock: Option<RecoveryUnlock>,
+    // Recovery key just generated, shown until the user has noted it down
+    new_recovery_key: Option<RecoveryKey>,
     selected_secret_type: SecretType,
+    // Secret loaded into the add-secret dialog for editing
+    edit_secret: Option<DecryptedSecretEntry>,
     current_secret_tab: SecretType,
     
     // Selected entry
     selected_entry_id: Option<String>,
+    // Entries ticked for bulk tag edits and deletion
+    checked_entry_ids: HashSet<String>,
+    bulk_tag: String,
+    confirm_bulk_delete: bool,
     
     // Inline tag editing
     editing_tags_for_entry: Option<String>,
     temp_tags: String,
+    // Tags in use, most used first, for suggestions
+    tag_counts: Vec<(String, usize)>,
     
     // Secret creation form
     secret_name: String,
     secret_description: String,
     secret_tags: String,
+    secret_require_reauth: bool,
     
     // API Key fields
     api_provider: String,
@@ -107,10 +239,15 @@ struct PwGenApp {
     ssh_public_key: String,
     ssh_passphrase: String,
     ssh_comment: String,
+    ssh_key_bits: u32,
+    // Key pair being generated on a background thread
+    ssh_keygen_job: Option<mpsc::Receiver<pwgen_core::Result<(String, String)>>>,
     
     // Document fields
     document_filename: String,
     document_content: Vec<u8>,
+    // File being read on a background thread
+    document_import: Option<DocumentImportJob>,
     
     // Configuration fields
     config_variables: String,
@@ -118,6 +255,21 @@ struct PwGenApp {
     // Secure note fields
     note_title: String,
     note_content: String,
+    note_format: NoteFormat,
+    // Show Markdown notes rendered rather than in the editor
+    note_preview: bool,
+    
+    // Credit card fields
+    card_cardholder: String,
+    card_number: String,
+    /// `MM/YY` as printed on the card
+    card_expiry: String,
+    card_cvv: String,
+    card_issuer: String,
+    card_billing_zip: String,
+    show_card_number: bool,
+    /// Card secret whose full number is shown in the secrets list
+    revealed_card: Option<String>,
     
     // Database connection fields
     db_type: String,
@@ -137,12 +289,764 @@ struct PwGenApp {
     logo_square: Option<egui::TextureHandle>,
     
     // Settings
+    // Vault file to unlock, chosen on the login screen or with "Open Vault..."
+    vault_path: Option<PathBuf>,
+    recent_vaults: Vec<PathBuf>,
+    // Key file required along with the master password, chosen on the login screen
+    key_file_path: Option<PathBuf>,
     minimize_to_tray: bool,
     auto_lock_minutes: u32,
+    was_focused: bool,
+    // The generated password is masked until shown again after the window
+    // lost focus
+    generated_masked: bool,
     show_system_tray: bool,
+    #[cfg(feature = "favicons")]
+    site_icons: SiteIcons,
+    clipboard_sensitive_hint: bool,
+    // Recorded as created/modified by on items changed here; empty for none
+    device_name: String,
+    // Previous passwords kept per entry; 0 keeps none
+    password_history_limit: usize,
+    // Entry rules the entry form broke on the last save attempt
+    entry_violations: Vec<RuleViolation>,
+    
+    // Preferences kept across sessions
+    gui_config: GuiConfig,
+    
+    // Clipboard managers found on the first copy of the session, until the
+    // notice about them is dismissed
+    clipboard_checked: bool,
+    clipboard_notice: Option<Vec<&'static str>>,
+    clipboard_notice_dont_show: bool,
+    
+    // Copy/reveal waiting for confirmation
+    pending_reveal: Option<PendingReveal>,
+    // Master password typed into the confirmation of a protected item
+    reauth_password: String,
+    reauth_failed: bool,
+    
+    // Password shown in the large reveal overlay
+    large_reveal: Option<LargeReveal>,
+    
+    // Result of the last "Verify Integrity" run
+    integrity_report: Option<IntegrityReport>,
+    
+    // Entries sharing a password
+    duplicate_audit: Option<DuplicateAudit>,
+    duplicate_merge: Option<DuplicateMergeView>,
+    database_maintenance: Option<DatabaseMaintenance>,
+    
+    // Deleted entries and secrets, while the trash dialog is open
+    trash: Option<TrashView>,
+    
+    // Have I Been Pwned check of the stored passwords
+    #[cfg(feature = "breach-check")]
+    breach_audit: Option<BreachAudit>,
+    
+    // Last pointer or keyboard input, for auto-lock
+    last_activity: Instant,
+    
+    // Draft autosave
+    last_draft_save: Instant,
+    saved_drafts: HashMap<&'static str, String>,
+    pending_drafts: Vec<FormDraft>,
+    
+    // Tray icon, when the `tray` feature is built and the icon could be created
+    #[cfg(feature = "tray")]
+    tray: Option<Tray>,
+}
+
+/// How long the search box waits for typing to pause before querying the vault.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(250);
+/// Entries listed in the "Recently Used" window
+const RECENTLY_USED_LIMIT: usize = 15;
+/// Text documents longer than this are previewed only up to it
+const DOCUMENT_TEXT_PREVIEW_LIMIT: usize = 256 * 1024;
+/// Image previews are scaled down to fit this many pixels a side
+const DOCUMENT_PREVIEW_MAX_SIDE: u32 = 1024;
+
+const SSH_KEYGEN_HINT: &str =
+    "Create a new key pair, protected with the passphrase and labelled with the comment above. Replaces the keys in this form";
+
+const REVEAL_TAGS_HINT: &str =
+    "Tag an item \"confirm-reveal\" to always confirm, or \"no-confirm-reveal\" to never confirm";
+
+const AUTO_LOCK_HINT: &str =
+    "Lock the vault when there has been no mouse or keyboard input in PwGen for this long";
+
+const STRENGTH_CRACK_TIME_HINT: &str =
+    "Estimated for an offline attack on a slow password hash at 10,000 guesses per second, trying common passwords, words and patterns first";
+
+const DRAFT_AUTOSAVE_HINT: &str =
+    "Encrypted drafts of open entry and secret forms are kept in the vault until you save or cancel";
+
+const GEN_FOR_NEW_ENTRIES_HINT: &str =
+    "Prefill the password of new entries using the generator settings; editing an entry never changes its password";
+
+const DEVICE_NAME_HINT: &str =
+    "Shown as \"created by\"/\"modified by\" on entries and secrets changed on this machine";
+
+const PASSWORD_HISTORY_HINT: &str =
+    "How many replaced passwords each entry keeps, so that one can be restored after a failed change";
+
+const EXTERNAL_EDITOR_HINT: &str =
+    "The content is written to a private temporary file, in memory where possible, only while the editor is open, then overwritten and deleted. Use a graphical editor in $VISUAL or $EDITOR that waits until the file is closed, e.g. \"code --wait\".";
+
+const COPY_AND_OPEN_HINT: &str =
+    "What \"Copy and open\" puts on the clipboard before opening the entry's site in your browser";
+
+const NO_WEB_ADDRESS_HINT: &str =
+    "The site is a name rather than a web address; edit the entry and add its URL to open it";
+
+const DOUBLE_CLICK_HINT: &str =
+    "What double-clicking an entry in the password list does; copies are cleared from the clipboard like any other";
+
+const CLIPBOARD_CLEAR_HINT: &str =
+    "Copied passwords and secrets are removed from the clipboard after this long, unless something else was copied since; 0 never clears";
+
+const SENSITIVE_COPY_HINT: &str =
+    "Marks copied values so that clipboard managers which honour the hint leave them out of their history";
+
+const MAX_DOCUMENT_SIZE_HINT: &str =
+    "Larger files are refused when uploading a document. Documents are kept in memory while the vault is open";
+
+const EXPIRY_WARNING_HINT: &str =
+    "After unlocking, secrets expiring within this many days are listed along with expired ones and API keys due for rotation; 0 shows only what is already overdue";
+
+const TOTP_SECRET_HINT: &str =
+    "The setup key shown next to the QR code when enabling two-factor login; the current code appears when the entry is selected";
+
+const REQUIRE_REAUTH_HINT: &str =
+    "For high-value items: even while the vault is unlocked, revealing or copying needs the master password";
+#[cfg(feature = "favicons")]
+const SITE_ICONS_HINT: &str = "Fetches each site's favicon.ico from the site itself, which lets it know \
+    the vault has an entry for it. Icons are cached, so each site is asked once";
+const HIDE_WHEN_UNFOCUSED_HINT: &str = "When PwGen loses focus, mask revealed passwords and card numbers \
+    and cover the entry dialog, without locking the vault";
+const MERGE_ANY_PASSWORD_HINT: &str = "The most recently updated entry keeps its password; \
+    the other passwords are kept in its password history";
+const TOTP_IMPORT_HINT: &str = "Each seed goes to the entry for its issuer, e.g. GitHub to github.com. \
+    Seeds without an entry get a new one without a password";
+const PASSWORD_POLICY_HINT: &str =
+    "Saved with the entry, so 🎲 makes passwords the site accepts, for example without symbols";
+
+/// Which field "Copy and open" puts on the clipboard.
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum CopyAndOpenField {
+    Username,
+    #[default]
+    Password,
+    /// Only open the site
+    Nothing,
+}
+
+/// What double-clicking an entry in the password list does.
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum DoubleClickAction {
+    #[default]
+    CopyPassword,
+    CopyUsername,
+    /// Open the entry for editing
+    Open,
+}
+
+/// How the passwords tab lays out entries in wide windows; narrow windows
+/// always get the compact list.
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum PasswordView {
+    #[default]
+    List,
+    Table,
+}
+
+/// Order of the password list, chosen with the table's column headers.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct EntrySort {
+    field: SortField,
+    order: SortOrder,
+}
+
+impl Default for EntrySort {
+    fn default() -> Self {
+        Self { field: SortField::Site, order: SortOrder::Ascending }
+    }
+}
+
+/// Color scheme of the window.
+#[derive(Serialize, Deserialize, Default, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum ThemeMode {
+    /// Follow the operating system's light or dark setting where it is
+    /// known, dark otherwise
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemeMode {
+    fn label(self) -> &'static str {
+        match self {
+            ThemeMode::System => "System (auto)",
+            ThemeMode::Light => "Light",
+            ThemeMode::Dark => "Dark",
+        }
+    }
+    
+    fn preference(self) -> egui::ThemePreference {
+        match self {
+            ThemeMode::System => egui::ThemePreference::System,
+            ThemeMode::Light => egui::ThemePreference::Light,
+            ThemeMode::Dark => egui::ThemePreference::Dark,
+        }
+    }
+}
+
+/// GUI preferences stored in `gui.json` under the user's config directory.
+#[derive(Serialize, Deserialize)]
+struct GuiConfig {
+    #[serde(default)]
+    hide_clipboard_manager_notice: bool,
+    #[serde(default)]
+    copy_and_open: CopyAndOpenField,
+    #[serde(default)]
+    double_click: DoubleClickAction,
+    #[serde(default)]
+    password_view: PasswordView,
+    #[serde(default)]
+    entry_sort: EntrySort,
+    #[serde(default)]
+    theme: ThemeMode,
+    #[serde(default = "default_expiry_warning_days")]
+    expiry_warning_days: u32,
+    /// List favorites above other entries, whatever the sort field
+    #[serde(default)]
+    favorites_first: bool,
+    /// Largest file, in megabytes, that can be uploaded as a document
+    #[serde(default = "default_max_document_size_mb")]
+    max_document_size_mb: u64,
+    /// Mask revealed secrets and cover the entry dialog while the window
+    /// doesn't have focus
+    #[serde(default = "default_hide_when_unfocused")]
+    hide_when_unfocused: bool,
+    #[serde(default)]
+    lock_on_minimize: bool,
+    /// Copied values are cleared from the clipboard after this long; 0 keeps them
+    #[serde(default = "default_clipboard_clear_secs")]
+    clipboard_clear_secs: u32,
+    /// Prefill new entries with a password from the generator settings
+    #[serde(default)]
+    gen_for_new_entries: bool,
+    /// Confirm every copy or reveal, not only those of items with a reveal tag
+    #[serde(default)]
+    confirm_copy_reveal: bool,
+    /// Keep encrypted drafts of open forms, saved every `draft_autosave_secs`
+    #[serde(default)]
+    autosave_drafts: bool,
+    #[serde(default = "default_draft_autosave_secs")]
+    draft_autosave_secs: u32,
+    #[serde(default = "default_large_reveal_secs")]
+    large_reveal_secs: u32,
+    #[serde(default = "default_large_reveal_chunked")]
+    large_reveal_chunked: bool,
+    /// Site icons in the entry list; off by default, since fetching them
+    /// contacts every site in the vault
+    #[serde(default)]
+    show_site_icons: bool,
+}
+
+fn default_expiry_warning_days() -> u32 {
+    30
+}
+
+fn default_max_document_size_mb() -> u64 {
+    DEFAULT_MAX_DOCUMENT_SIZE / (1024 * 1024)
+}
+
+fn default_hide_when_unfocused() -> bool {
+    true
+}
+
+fn default_clipboard_clear_secs() -> u32 {
+    30
+}
+
+fn default_draft_autosave_secs() -> u32 {
+    10
+}
+
+fn default_large_reveal_secs() -> u32 {
+    20
+}
+
+fn default_large_reveal_chunked() -> bool {
+    true
+}
+
+impl Default for GuiConfig {
+    fn default() -> Self {
+        Self {
+            hide_clipboard_manager_notice: false,
+            copy_and_open: CopyAndOpenField::default(),
+            double_click: DoubleClickAction::default(),
+            password_view: PasswordView::default(),
+            entry_sort: EntrySort::default(),
+            theme: ThemeMode::default(),
+            expiry_warning_days: default_expiry_warning_days(),
+            favorites_first: false,
+            max_document_size_mb: default_max_document_size_mb(),
+            hide_when_unfocused: default_hide_when_unfocused(),
+            lock_on_minimize: false,
+            clipboard_clear_secs: default_clipboard_clear_secs(),
+            gen_for_new_entries: false,
+            confirm_copy_reveal: false,
+            autosave_drafts: false,
+            draft_autosave_secs: default_draft_autosave_secs(),
+            large_reveal_secs: default_large_reveal_secs(),
+            large_reveal_chunked: default_large_reveal_chunked(),
+            show_site_icons: false,
+        }
+    }
+}
+
+impl GuiConfig {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("pwgen").join("gui.json"))
+    }
+    
+    /// Load the saved preferences, falling back to defaults if there are none
+    /// or they cannot be read.
+    fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+    
+    fn save(&self) -> std::result::Result<(), String> {
+        let path = Self::path().ok_or("Could not determine config directory")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, json).map_err(|e| e.to_string())
+    }
+}
+
+struct PendingReveal {
+    what: String,
+    action: RevealAction,
+    /// The item requires the master password, not just a confirmation
+    reauth: bool,
+}
+
+enum RevealAction {
+    Copy(String),
+    /// Copy the value, then open the URL
+    CopyAndOpen(String, String),
+    ShowPassword,
+    /// Show the full number of the card secret with this ID
+    ShowCardNumber(String),
+    LargeReveal(String),
+    EnvDiffValues,
+    /// Open the document secret with this ID in the preview window
+    ViewDocument(String),
+}
+
+/// Two configuration secrets compared side by side, by ID.
+struct EnvDiffView {
+    // Decrypted when the window opens, sorted by name
+    configs: Vec<DecryptedSecretEntry>,
+    left_id: String,
+    right_id: String,
+    reveal: bool,
+}
+
+/// A configuration secret being saved to a file or copied as `.env` lines.
+struct EnvExport {
+    secret: DecryptedSecretEntry,
+    // Save as KEY=VALUE lines rather than the secret's own format
+    as_env: bool,
+    options: EnvFileOptions,
+}
+
+/// A document secret open in the preview window.
+struct DocumentView {
+    secret: DecryptedSecretEntry,
+    // Decompression, checksum and decoding run on a worker thread
+    job: Option<mpsc::Receiver<Result<PreparedDocument, String>>>,
+    intact: Option<bool>,
+    preview: DocumentPreview,
+}
+
+/// A document read and decoded off the UI thread.
+struct PreparedDocument {
+    intact: bool,
+    content_type: String,
+    size: usize,
+    content: PreparedContent,
+}
+
+enum PreparedContent {
+    Text { text: String, truncated: bool },
+    /// Scaled to fit `DOCUMENT_PREVIEW_MAX_SIDE`; `size` is the original
+    Image { image: egui::ColorImage, size: [u32; 2] },
+    Other,
+}
+
+enum DocumentPreview {
+    Loading,
+    Text { text: String, truncated: bool },
+    Image { texture: egui::TextureHandle, size: [u32; 2] },
+    /// Content type that can't be previewed
+    Unsupported(String),
+    Failed(String),
+}
+
+/// Progress through the reset vault confirmation steps.
+#[derive(PartialEq, Clone, Copy)]
+enum ResetStep {
+    Backup,
+    TypeDelete,
+    Confirm,
+}
+
+struct VaultReset {
+    step: ResetStep,
+    typed_confirmation: String,
+    no_recovery_need: bool,
+    // Where the encrypted copy was saved, if one was made
+    backup_path: Option<PathBuf>,
+}
+
+/// Fields of the "Change Master Password" dialog.
+#[derive(Default)]
+struct PasswordChange {
+    current: String,
+    new: String,
+    confirm: String,
+}
+
+/// Fields of the "Unlock with Recovery Key" dialog.
+#[derive(Default)]
+struct RecoveryUnlock {
+    phrase: String,
+    new: String,
+    confirm: String,
+}
+
+struct LargeReveal {
+    what: String,
+    text: String,
+    expires_at: Instant,
+}
+
+/// Browser export picked for import, with the names and URLs it would get.
+struct ImportPreviewState {
+    browser: String,
+    path: PathBuf,
+    entries: Vec<ImportPreview>,
+    column_mapping: Option<CsvColumnMapping>,
+}
+
+/// A file being read into the document form on a worker thread.
+struct DocumentImportJob {
+    filename: String,
+    events: mpsc::Receiver<DocumentImportEvent>,
+    /// Bytes read so far and the file size
+    progress: (u64, u64),
+}
+
+enum DocumentImportEvent {
+    Progress { read: u64, total: u64 },
+    Done(pwgen_core::Result<Vec<u8>>),
+}
+
+/// A browser import adding entries on a worker thread.
+struct ImportJob {
+    browser: String,
+    events: mpsc::Receiver<ImportEvent>,
+    /// Entries processed so far and in total, once the export has been read
+    progress: Option<(usize, usize)>,
+    cancel: Arc<AtomicBool>,
+}
+
+enum ImportEvent {
+    Progress { processed: usize, total: usize },
+    Done(std::result::Result<ImportOutcome, String>),
+}
+
+struct ImportOutcome {
+    imported: usize,
+    total: usize,
+    cancelled: bool,
+}
+
+/// A CSV file of unknown layout whose columns the user is assigning to
+/// fields. Each field holds a position in `columns`.
+struct CsvMappingState {
+    path: PathBuf,
+    columns: Vec<String>,
+    site: Option<usize>,
+    username: Option<usize>,
+    password: Option<usize>,
+    notes: Option<usize>,
+    tags: Option<usize>,
+}
+
+impl CsvMappingState {
+    fn new(path: PathBuf, columns: Vec<String>) -> Self {
+        // Preselect columns whose names suggest the field
+        let guess = |names: &[&str]| {
+            columns
+                .iter()
+                .position(|column| names.iter().any(|name| column.to_lowercase().contains(name)))
+        };
+        Self {
+            site: guess(&["url", "site", "website", "title", "name"]),
+            username: guess(&["user", "login", "email"]),
+            password: guess(&["pass", "secret", "pwd"]),
+            notes: guess(&["note", "comment", "extra"]),
+            tags: guess(&["tag", "label", "folder", "group", "category"]),
+            path,
+            columns,
+        }
+    }
+    
+    fn mapping(&self) -> CsvColumnMapping {
+        let column = |index: Option<usize>| index.map(CsvColumn::Index);
+        CsvColumnMapping {
+            site: column(self.site),
+            username: column(self.username),
+            password: column(self.password),
+            notes: column(self.notes),
+            tags: column(self.tags),
+        }
+    }
+}
+
+/// A backup or restore running on a worker thread.
+struct BackupJob {
+    title: &'static str,
+    events: mpsc::Receiver<BackupEvent>,
+    progress: Option<BackupProgress>,
+    reloads_entries: bool,
+}
+
+enum BackupRequest {
+    Create(PathBuf),
+    Restore(PathBuf, RestoreOptions),
+}
+
+/// Backup entries that match existing ones, shown before a restore so the
+/// user can choose what happens to each.
+struct RestoreConflictDialog {
+    path: PathBuf,
+    conflicts: Vec<RestoreConflict>,
+    resolution: ConflictResolution,
+    /// Choice per conflict; `None` follows `resolution`
+    choices: Vec<Option<ConflictResolution>>,
+}
+
+const CONFLICT_RESOLUTIONS: [ConflictResolution; 4] = [
+    ConflictResolution::Merge,
+    ConflictResolution::Skip,
+    ConflictResolution::Overwrite,
+    ConflictResolution::KeepBoth,
+];
+
+fn conflict_resolution_label(resolution: ConflictResolution) -> &'static str {
+    match resolution {
+        ConflictResolution::Merge => "Keep newer",
+        ConflictResolution::Skip => "Skip",
+        ConflictResolution::Overwrite => "Overwrite",
+        ConflictResolution::KeepBoth => "Keep both",
+    }
+}
+
+enum BackupEvent {
+    Progress(BackupProgress),
+    /// Finished; `Ok` carries the success message.
+    Done(std::result::Result<String, String>),
+}
+
+/// The reused password dialog: the search runs on a worker thread, then
+/// each group of entries sharing a password is listed.
+enum DuplicateAudit {
+    Running(mpsc::Receiver<pwgen_core::Result<Vec<Vec<DecryptedPasswordEntry>>>>),
+    Done(Vec<DuplicateGroup>),
+    Failed(String),
+}
+
+/// Site icons by host from `favicon_host`, loaded by a worker thread from
+/// the favicon cache or the sites themselves.
+#[cfg(feature = "favicons")]
+#[derive(Default)]
+struct SiteIcons {
+    // `None` for sites without a usable icon
+    textures: HashMap<String, Option<egui::TextureHandle>>,
+    requested: HashSet<String>,
+    worker: Option<(mpsc::Sender<String>, mpsc::Receiver<SiteIconResult>)>,
+}
+
+#[cfg(feature = "favicons")]
+type SiteIconResult = (String, Option<egui::ColorImage>);
+
+#[cfg(feature = "favicons")]
+impl SiteIcons {
+    /// Ask the worker for the icon of `host`, starting it if needed with the
+    /// cache naming key from `cache_key`. Hosts are fetched one at a time,
+    /// so a large vault doesn't open dozens of connections at once.
+    fn request(&mut self, host: String, cache_key: impl FnOnce() -> Option<Vec<u8>>) {
+        if !self.requested.insert(host.clone()) {
+            return;
+        }
+        let (requests, _) = self.worker.get_or_insert_with(|| {
+            let (request_sender, request_receiver) = mpsc::channel::<String>();
+            let (result_sender, result_receiver) = mpsc::channel();
+            let cache_key = cache_key();
+            std::thread::spawn(move || {
+                let cache = cache_key.and_then(FaviconCache::default_location);
+                if let Some(cache) = &cache {
+                    let _ = cache.remove_unkeyed();
+                }
+                for host in request_receiver {
+                    let icon = cache
+                        .as_ref()
+                        .and_then(|cache| cache.load(&host).ok().flatten())
+                        .and_then(|bytes| decode_site_icon(&bytes));
+                    if result_sender.send((host, icon)).is_err() {
+                        break;
+                    }
+                }
+            });
+            (request_sender, result_receiver)
+        });
+        let _ = requests.send(host);
+    }
+}
+
+/// A connection test for a database secret, run on a worker thread.
+#[cfg(feature = "connection-test")]
+enum ConnectionTest {
+    Running(mpsc::Receiver<Result<Duration, ConnectionTestError>>),
+    Done(Result<Duration, ConnectionTestError>),
+}
+
+/// The database maintenance dialog: the integrity check and vacuum run on
+/// a worker thread, then their results are shown.
+enum DatabaseMaintenance {
+    Running(mpsc::Receiver<pwgen_core::Result<MaintenanceResult>>),
+    Done(MaintenanceResult),
+    Failed(String),
+}
+
+struct MaintenanceResult {
+    integrity_ok: bool,
+    /// Not run when the integrity check fails
+    vacuum: Option<VacuumStats>,
+}
+
+/// Entries sharing one password, and which of them keeps it.
+struct DuplicateGroup {
+    entries: Vec<DecryptedPasswordEntry>,
+    keep: usize,
+    flagged: bool,
+}
+
+/// The merge duplicates dialog: what would be merged, listed before
+/// anything changes.
+struct DuplicateMergeView {
+    strategy: MergeStrategy,
+    plan: std::result::Result<MergeReport, String>,
+}
+
+/// Contents of the trash dialog.
+struct TrashView {
+    entries: Vec<Trashed<DecryptedPasswordEntry>>,
+    secrets: Vec<Trashed<DecryptedSecretEntry>>,
+    retention_days: u32,
+    confirm_empty: bool,
+}
+
+/// The breach check dialog: waiting for the user to opt in, running on the
+/// tokio runtime, or showing the result.
+#[cfg(feature = "breach-check")]
+enum BreachAudit {
+    Confirm,
+    Running {
+        events: mpsc::Receiver<BreachEvent>,
+        checked: usize,
+        total: usize,
+    },
+    Done(std::result::Result<Vec<BreachedEntry>, String>),
+}
+
+#[cfg(feature = "breach-check")]
+enum BreachEvent {
+    Progress(usize, usize),
+    Done(std::result::Result<Vec<BreachedEntry>, String>),
+}
+
+/// Add-secret form text being edited in the external editor.
+#[derive(Clone, Copy, PartialEq)]
+enum ExternalEditTarget {
+    NoteContent,
+    ConfigVariables,
+}
+
+/// An external editor running on a worker thread.
+struct ExternalEdit {
+    target: ExternalEditTarget,
+    result: mpsc::Receiver<pwgen_core::Result<Option<String>>>,
+}
+
+const ENTRY_DRAFT: &str = "gui-entry-form";
+const SECRET_DRAFT: &str = "gui-secret-form";
+
+/// Contents of an unsaved entry or secret dialog, stored encrypted in the
+/// vault by draft autosave and offered back on the next unlock.
+#[derive(Serialize, Deserialize)]
+enum FormDraft {
+    Entry {
+        edit_id: Option<String>,
+        site: String,
+        #[serde(default)]
+        urls: Vec<String>,
+        username: String,
+        password: String,
+        #[serde(default)]
+        totp: String,
+        notes: String,
+        tags: String,
+    },
+    Secret {
+        #[serde(default)]
+        edit_id: Option<String>,
+        secret_type: SecretType,
+        fields: Vec<(String, String)>,
+    },
+}
+
+impl FormDraft {
+    fn name(&self) -> &'static str {
+        match self {
+            FormDraft::Entry { .. } => ENTRY_DRAFT,
+            FormDraft::Secret { .. } => SECRET_DRAFT,
+        }
+    }
     
-    // Tray - disabled for now
-    // _tray_icon: Option<TrayIcon>,
+    fn describe(&self) -> String {
+        match self {
+            FormDraft::Entry { site, .. } if !site.is_empty() => format!("password entry for {}", site),
+            FormDraft::Entry { .. } => "password entry".to_string(),
+            FormDraft::Secret { secret_type, .. } => format!("{:?} secret", secret_type),
+        }
+    }
 }
 
 #[derive(PartialEq, Default)]
@@ -162,6 +1066,16 @@ enum SearchField {
     Tags,
 }
 
+/// What the password generator produces.
+#[derive(PartialEq, Default, Clone, Copy)]
+enum GeneratorMode {
+    #[default]
+    Characters,
+    Pattern,
+    Passphrase,
+    Memorable,
+}
+
 #[derive(PartialEq, Default, Clone, Copy)]
 enum MainTab {
     #[default]
@@ -189,9 +1103,6 @@ impl PwGenApp {
                 .unwrap()
         );
         
-        // Create system tray
-        let _tray_icon = create_tray_icon();
-        
         let mut app = Self {
             runtime,
             storage: Arc::new(Mutex::new(None)),
@@ -202,25 +1113,53 @@ impl PwGenApp {
             gen_numbers: true,
             gen_symbols: true,
             gen_exclude_ambiguous: true,
+            gen_mobile_friendly: false,
+            gen_mode: GeneratorMode::Characters,
+            gen_pattern: "XXX-999-xxx".to_string(),
+            gen_pattern_template: false,
+            gen_words: 4,
+            gen_separator: "-".to_string(),
+            gen_capitalize: true,
+            gen_with_number: false,
+            gen_wordlist: Wordlist::default(),
             entries: Vec::new(),
             filtered_entries: Vec::new(),
             secrets: Vec::new(),
-            filtered_secrets: Vec::new(),
+            rotation_due: 0,
+            secret_page: Vec::new(),
+            secret_page_type: None,
+            secret_current_page: 0,
+            secrets_per_page: 50,
+            secret_total_pages: 0,
+            secret_search_query: String::new(),
+            secret_search_due: None,
+            secret_filter_tags: String::new(),
+            secret_filter_environment: None,
+            secret_filter_favorites: false,
             screen: Screen::Login,
             current_tab: MainTab::Passwords,
             master_password: String::new(),
             master_password_confirm: String::new(),
+            create_recovery_key: false,
             error_message: String::new(),
             success_message: String::new(),
             show_add_dialog: false,
             edit_entry: None,
             entry_site: String::new(),
+            entry_urls: Vec::new(),
             entry_username: String::new(),
             entry_password: String::new(),
+            entry_strength: None,
+            entry_totp: String::new(),
             entry_notes: String::new(),
             entry_tags: String::new(),
+            entry_require_reauth: false,
+            entry_policy: None,
             show_password: false,
             search_query: String::new(),
+            search_results: Vec::new(),
+            search_due: None,
+            total_filtered: 0,
             search_field: SearchField::All,
             filter_favorites: false,
             filter_tags: String::new(),
@@ -232,21 +1171,51 @@ impl PwGenApp {
             is_compact_mode: false,
             show_generator: false,
             generated_password: String::new(),
+            generated_entropy: None,
             show_settings: false,
             show_about: false,
             show_import: false,
+            import_strip_query: true,
+            import_strip_www: false,
+            import_preview: None,
+            import_job: None,
+            csv_mapping: None,
+            totp_import_text: String::new(),
             show_backup: false,
             show_statistics: false,
+            secret_statistics: None,
+            show_recently_used: false,
+            backup_password: String::new(),
+            backup_password_confirm: String::new(),
+            backup_job: None,
+            restore_conflicts: None,
+            #[cfg(feature = "connection-test")]
+            connection_tests: HashMap::new(),
+            expiry_notices: Vec::new(),
             show_secrets_view: false,
             show_add_secret_dialog: false,
+            external_edit: None,
+            env_diff: None,
+            env_export: None,
+            document_view: None,
+            vault_reset: None,
+            password_change: None,
+            recovery_unlock: None,
+            new_recovery_key: None,
             selected_secret_type: SecretType::Password,
+            edit_secret: None,
             current_secret_tab: SecretType::Password,
             selected_entry_id: None,
+            checked_entry_ids: HashSet::new(),
+            bulk_tag: String::new(),
+            confirm_bulk_delete: false,
             editing_tags_for_entry: None,
             temp_tags: String::new(),
+            tag_counts: Vec::new(),
             secret_name: String::new(),
             secret_description: String::new(),
             secret_tags: String::new(),
+            secret_require_reauth: false,
             api_provider: String::new(),
             api_key_id: String::new(),
             api_key: String::new(),
@@ -258,11 +1227,24 @@ impl PwGenApp {
             ssh_public_key: String::new(),
             ssh_passphrase: String::new(),
             ssh_comment: String::new(),
+            ssh_key_bits: 4096,
+            ssh_keygen_job: None,
             document_filename: String::new(),
             document_content: Vec::new(),
+            document_import: None,
             config_variables: String::new(),
             note_title: String::new(),
             note_content: String::new(),
+            note_format: NoteFormat::PlainText,
+            note_preview: false,
+            card_cardholder: String::new(),
+            card_number: String::new(),
+            card_expiry: String::new(),
+            card_cvv: String::new(),
+            card_issuer: String::new(),
+            card_billing_zip: String::new(),
+            show_card_number: false,
+            revealed_card: None,
             db_type: "PostgreSQL".to_string(),
             db_host: String::new(),
             db_port: "5432".to_string(),
@@ -271,10 +1253,41 @@ impl PwGenApp {
             db_password: String::new(),
             logo_wide,
             logo_square,
+            vault_path: list_recent_vaults().into_iter().next().or_else(default_vault_path),
+            recent_vaults: list_recent_vaults(),
+            key_file_path: None,
             minimize_to_tray: true,
             auto_lock_minutes: 10,
+            was_focused: true,
+            generated_masked: false,
             show_system_tray: true,
-            // _tray_icon: tray_icon,
+            #[cfg(feature = "favicons")]
+            site_icons: SiteIcons::default(),
+            clipboard_sensitive_hint: true,
+            device_name: load_device_name().unwrap_or_default(),
+            password_history_limit: DEFAULT_PASSWORD_HISTORY_LIMIT,
+            entry_violations: Vec::new(),
+            gui_config: GuiConfig::load(),
+            clipboard_checked: false,
+            clipboard_notice: None,
+            clipboard_notice_dont_show: false,
+            pending_reveal: None,
+            reauth_password: String::new(),
+            reauth_failed: false,
+            large_reveal: None,
+            integrity_report: None,
+            duplicate_audit: None,
+            duplicate_merge: None,
+            database_maintenance: None,
+            trash: None,
+            #[cfg(feature = "breach-check")]
+            breach_audit: None,
+            last_activity: Instant::now(),
+            last_draft_save: Instant::now(),
+            saved_drafts: HashMap::new(),
+            pending_drafts: Vec::new(),
+            #[cfg(feature = "tray")]
+            tray: create_tray_icon(&cc.egui_ctx),
         };
         
         // Check if vault exists
@@ -288,8 +1301,45 @@ impl PwGenApp {
     }
     
     fn get_vault_path(&self) -> Option<PathBuf> {
-        dirs::data_dir()
-            .map(|d| d.join("pwgen").join("vault.db"))
+        self.vault_path.clone()
+    }
+    
+    /// Lock the open vault, if any, and go to the login screen for `path`,
+    /// which shows "Create Your Vault" when the file doesn't exist yet.
+    fn switch_vault(&mut self, path: PathBuf) {
+        if self.screen == Screen::Main {
+            self.lock_vault();
+        }
+        // The key file belongs to the previous vault
+        self.key_file_path = None;
+        self.master_password.clear();
+        self.master_password_confirm.clear();
+        self.error_message.clear();
+        self.success_message.clear();
+        self.vault_path = Some(path);
+    }
+    
+    /// Ask for a vault file to open, or a new one to create with `create`.
+    fn choose_vault_file(&mut self, create: bool) {
+        let dialog = rfd::FileDialog::new().add_filter("PwGen vault", &["db"]);
+        let path = if create {
+            dialog.set_file_name("vault.db").save_file()
+        } else {
+            dialog.pick_file()
+        };
+        if let Some(path) = path {
+            self.switch_vault(path);
+        }
+    }
+    
+    /// Put the current vault at the top of the recent vaults.
+    fn remember_current_vault(&mut self) {
+        if let Some(path) = &self.vault_path {
+            if let Err(e) = remember_vault(path) {
+                eprintln!("Failed to save the recent vaults: {}", e);
+            }
+        }
+        self.recent_vaults = list_recent_vaults();
     }
     
     fn init_vault(&mut self) {
@@ -311,6 +1361,14 @@ impl PwGenApp {
             }
         };
         
+        let key_file = match self.load_key_file() {
+            Ok(key_file) => key_file,
+            Err(e) => {
+                self.error_message = e.to_string();
+                return;
+            }
+        };
+        
         // Create parent directory if needed
         if let Some(parent) = vault_path.parent() {
             std::fs::create_dir_all(parent).ok();
@@ -326,12 +1384,12 @@ impl PwGenApp {
         
         // Run async operation
         runtime.block_on(async {
-            match Storage::create_new(&vault_path, &password).await {
+            match Storage::create_new_with_key_file(&vault_path, &password, key_file.as_ref()).await {
                 Ok(storage) => {
                     *storage_mutex.lock().unwrap() = Some(storage);
                     
                     // Initialize secrets storage
-                    match SecretsStorage::create_new(&vault_path_clone, &password).await {
+                    match SecretsStorage::create_new_with_key_file(&vault_path_clone, &password, key_file.as_ref()).await {
                         Ok(secrets_storage) => {
                             *secrets_storage_mutex.lock().unwrap() = Some(secrets_storage);
                         }
@@ -351,140 +1409,790 @@ impl PwGenApp {
         
         if self.error_message.is_empty() {
             self.screen = Screen::Main;
+            self.last_activity = Instant::now();
             self.master_password.clear();
             self.master_password_confirm.clear();
+            self.remember_current_vault();
+            self.apply_device_name();
+            self.apply_password_history_limit();
             self.load_entries();
             self.load_secrets();
+            if self.create_recovery_key {
+                self.enable_recovery_key();
+            }
         }
     }
     
-    fn unlock_vault(&mut self) {
-        let vault_path = match self.get_vault_path() {
-            Some(p) => p,
-            None => {
-                self.error_message = "Could not determine vault path".to_string();
-                return;
+    /// Generate a recovery key for the open vault, replacing any earlier
+    /// one, and show it once.
+    fn enable_recovery_key(&mut self) {
+        let storage = self.storage.clone();
+        let result = self.runtime.block_on(async {
+            match storage.lock().unwrap().as_ref() {
+                Some(storage) => storage.enable_recovery_key().await,
+                None => Err(pwgen_core::Error::VaultLocked),
             }
+        });
+        match result {
+            Ok(recovery_key) => self.new_recovery_key = Some(recovery_key),
+            Err(e) => self.error_message = format!("Failed to create recovery key: {}", e),
+        }
+    }
+    
+    fn show_new_recovery_key(&mut self, ctx: &egui::Context) {
+        let Some(recovery_key) = &self.new_recovery_key else {
+            return;
         };
+        let mut done = false;
         
-        let password = self.master_password.clone();
-        let storage_mutex = self.storage.clone();
-        let secrets_storage_mutex = self.secrets_storage.clone();
-        let runtime = self.runtime.clone();
-        let vault_path_clone = vault_path.clone();
-        
-        self.error_message.clear();
-        
-        runtime.block_on(async {
-            match Storage::open(&vault_path, &password).await {
-                Ok(storage) => {
-                    *storage_mutex.lock().unwrap() = Some(storage);
-                    
-                    // Initialize secrets storage
-                    match SecretsStorage::from_existing_storage(&vault_path_clone, &password).await {
-                        Ok(secrets_storage) => {
-                            *secrets_storage_mutex.lock().unwrap() = Some(secrets_storage);
-                        }
-                        Err(e) => {
-                            return Err(format!("Failed to initialize secrets storage: {}", e));
+        egui::Window::new("🛟 Recovery Key")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    "These words unlock the vault if you forget the master password, even without its key file. \
+                     Write them down in order and keep them somewhere safe: they will not be shown again.",
+                );
+                ui.add_space(10.0);
+                egui::Grid::new("recovery_key_words").num_columns(4).spacing([20.0, 6.0]).show(ui, |ui| {
+                    for (i, word) in recovery_key.phrase().split(' ').enumerate() {
+                        ui.monospace(format!("{:>2}. {}", i + 1, word));
+                        if i % 4 == 3 {
+                            ui.end_row();
                         }
                     }
+                });
+                ui.add_space(10.0);
+                if ui.button("✅ I have written it down").clicked() {
+                    done = true;
                 }
-                Err(e) => {
-                    return Err(e.to_string());
-                }
-            }
-            Ok(())
-        }).unwrap_or_else(|e| {
-            self.error_message = format!("Error unlocking vault: {}", e);
-        });
+            });
+        
+        if done {
+            self.new_recovery_key = None;
+        }
+    }
+    
+    fn show_recovery_unlock_dialog(&mut self, ctx: &egui::Context) {
+        let Some(recovery) = &mut self.recovery_unlock else {
+            return;
+        };
+        let mut open = true;
+        let mut submit = false;
+        
+        egui::Window::new("Unlock with Recovery Key")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.label("Enter the recovery key's words, then choose a new master password.");
+                ui.add(
+                    egui::TextEdit::multiline(&mut recovery.phrase)
+                        .desired_rows(3)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("24 words separated by spaces"),
+                );
+                ui.add_space(5.0);
+                egui::Grid::new("recovery_unlock").num_columns(2).show(ui, |ui| {
+                    ui.label("New password:");
+                    ui.add(egui::TextEdit::singleline(&mut recovery.new).password(true));
+                    ui.end_row();
+                    
+                    ui.label("Confirm new password:");
+                    ui.add(egui::TextEdit::singleline(&mut recovery.confirm).password(true));
+                    ui.end_row();
+                });
+                
+                if !recovery.new.is_empty() {
+                    Self::show_password_strength(ui, &recovery.new);
+                }
+                if !recovery.confirm.is_empty() && recovery.new != recovery.confirm {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), "Passwords do not match");
+                }
+                ui.small(
+                    "The vault will then need the key file chosen on the unlock screen, or no key file if none is chosen. \
+                     The recovery key keeps working.",
+                );
+                ui.add_space(10.0);
+                
+                let ready = !recovery.phrase.trim().is_empty()
+                    && !recovery.new.is_empty()
+                    && recovery.new == recovery.confirm;
+                if ui.add_enabled(ready, egui::Button::new("🔓 Unlock and Set Password")).clicked() {
+                    submit = true;
+                }
+            });
+        
+        if submit {
+            self.recover_vault();
+        } else if !open {
+            self.recovery_unlock = None;
+        }
+    }
+    
+    /// Replace the forgotten master password using the recovery key, then
+    /// unlock the vault with the new one.
+    fn recover_vault(&mut self) {
+        let Some(recovery) = self.recovery_unlock.take() else {
+            return;
+        };
+        let Some(vault_path) = self.get_vault_path() else {
+            self.error_message = "Could not determine vault path".to_string();
+            return;
+        };
+        let key_file = match self.load_key_file() {
+            Ok(key_file) => key_file,
+            Err(e) => {
+                self.error_message = e.to_string();
+                return;
+            }
+        };
+        
+        let result = RecoveryKey::parse(&recovery.phrase).and_then(|recovery_key| {
+            self.runtime.block_on(async {
+                let mut storage = Storage::open_with_recovery_key(&vault_path, &recovery_key).await?;
+                storage.reset_master_password(&recovery.new, key_file.as_ref()).await
+            })
+        });
+        if let Err(e) = result {
+            self.error_message = match e {
+                pwgen_core::Error::InvalidRecoveryKey => "Incorrect recovery key".to_string(),
+                e => format!("Failed to unlock with the recovery key: {}", e),
+            };
+            // Keep the dialog open so a mistyped word can be corrected
+            self.recovery_unlock = Some(recovery);
+            return;
+        }
+        
+        self.master_password = recovery.new;
+        self.unlock_vault();
+        if self.error_message.is_empty() {
+            self.success_message = "Master password reset. The recovery key still works.".to_string();
+        }
+    }
+    
+    fn load_key_file(&self) -> pwgen_core::Result<Option<KeyFile>> {
+        self.key_file_path.as_ref().map(KeyFile::read).transpose()
+    }
+    
+    fn unlock_vault(&mut self) {
+        let vault_path = match self.get_vault_path() {
+            Some(p) => p,
+            None => {
+                self.error_message = "Could not determine vault path".to_string();
+                return;
+            }
+        };
+        
+        let key_file = match self.load_key_file() {
+            Ok(key_file) => key_file,
+            Err(e) => {
+                self.error_message = e.to_string();
+                return;
+            }
+        };
+        
+        let password = self.master_password.clone();
+        let storage_mutex = self.storage.clone();
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        let runtime = self.runtime.clone();
+        let vault_path_clone = vault_path.clone();
+        
+        self.error_message.clear();
+        
+        let result = runtime.block_on(async {
+            let storage = Storage::open_with_key_file(&vault_path, &password, key_file.as_ref()).await?;
+            let secrets_storage =
+                SecretsStorage::from_existing_storage_with_key_file(&vault_path_clone, &password, key_file.as_ref())
+                    .await?;
+            *storage_mutex.lock().unwrap() = Some(storage);
+            *secrets_storage_mutex.lock().unwrap() = Some(secrets_storage);
+            Ok::<_, pwgen_core::Error>(())
+        });
+        if let Err(e) = result {
+            self.error_message = match e {
+                pwgen_core::Error::InvalidMasterPassword => "Incorrect master password".to_string(),
+                pwgen_core::Error::VaultNotFound(_) => {
+                    format!("{}. Choose another vault file or create a new vault", e)
+                }
+                pwgen_core::Error::VaultCorrupt(_) => {
+                    format!("{}. Restore it from a backup, or choose another vault file", e)
+                }
+                e => format!("Error unlocking vault: {}", e),
+            };
+        }
         
         if self.error_message.is_empty() {
             self.screen = Screen::Main;
+            self.last_activity = Instant::now();
             self.master_password.clear();
+            self.remember_current_vault();
+            self.apply_device_name();
+            self.apply_password_history_limit();
             self.load_entries();
             self.load_secrets();
+            self.load_drafts();
+            self.check_expiring_secrets();
             self.success_message = "Vault unlocked successfully".to_string();
         }
     }
     
+    fn check_expiring_secrets(&mut self) {
+        let secrets_storage = self.secrets_storage.clone();
+        let within_days = i64::from(self.gui_config.expiry_warning_days);
+        let notices = self.runtime.block_on(async {
+            match secrets_storage.lock().unwrap().as_ref() {
+                Some(secrets_storage) => secrets_storage.get_expiry_notices(within_days).await,
+                None => Ok(Vec::new()),
+            }
+        });
+        match notices {
+            Ok(notices) => self.expiry_notices = notices,
+            Err(e) => self.error_message = format!("Failed to check for expiring secrets: {}", e),
+        }
+    }
+    
+    fn show_password_change_dialog(&mut self, ctx: &egui::Context) {
+        let Some(change) = &mut self.password_change else {
+            return;
+        };
+        let mut open = true;
+        let mut submit = false;
+        
+        egui::Window::new("Change Master Password")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("password_change").num_columns(2).show(ui, |ui| {
+                    ui.label("Current password:");
+                    ui.add(egui::TextEdit::singleline(&mut change.current).password(true));
+                    ui.end_row();
+                    
+                    ui.label("New password:");
+                    ui.add(egui::TextEdit::singleline(&mut change.new).password(true));
+                    ui.end_row();
+                    
+                    ui.label("Confirm new password:");
+                    ui.add(egui::TextEdit::singleline(&mut change.confirm).password(true));
+                    ui.end_row();
+                });
+                
+                if !change.new.is_empty() {
+                    Self::show_password_strength(ui, &change.new);
+                }
+                if !change.confirm.is_empty() && change.new != change.confirm {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), "Passwords do not match");
+                }
+                ui.small("Every password and secret is re-encrypted. Existing backups keep their own backup password.");
+                ui.add_space(10.0);
+                
+                let ready = !change.current.is_empty() && !change.new.is_empty() && change.new == change.confirm;
+                if ui.add_enabled(ready, egui::Button::new("🔑 Change Password")).clicked() {
+                    submit = true;
+                }
+            });
+        
+        if submit {
+            self.change_master_password();
+        } else if !open {
+            self.password_change = None;
+        }
+    }
+    
+    /// Re-encrypt the vault under the new master password, then reopen the
+    /// secrets storage, which still holds the old key.
+    fn change_master_password(&mut self) {
+        let Some(change) = self.password_change.take() else {
+            return;
+        };
+        let Some(vault_path) = self.get_vault_path() else {
+            self.error_message = "Could not determine vault path".to_string();
+            return;
+        };
+        let key_file = match self.load_key_file() {
+            Ok(key_file) => key_file,
+            Err(e) => {
+                self.error_message = e.to_string();
+                return;
+            }
+        };
+        
+        let storage_mutex = self.storage.clone();
+        let changed = self.runtime.block_on(async {
+            let mut storage_guard = storage_mutex.lock().unwrap();
+            let Some(storage) = storage_guard.as_mut() else {
+                return Err(pwgen_core::Error::VaultLocked);
+            };
+            storage.change_master_password(&change.current, &change.new, key_file.as_ref()).await
+        });
+        if let Err(e) = changed {
+            self.error_message = format!("Failed to change master password: {}", e);
+            // Keep the dialog open so a mistyped password can be corrected
+            self.password_change = Some(change);
+            return;
+        }
+        
+        let reopened = self.runtime.block_on(
+            SecretsStorage::from_existing_storage_with_key_file(&vault_path, &change.new, key_file.as_ref())
+        );
+        match reopened {
+            Ok(secrets_storage) => {
+                *self.secrets_storage.lock().unwrap() = Some(secrets_storage);
+                self.apply_device_name();
+                self.success_message = "Master password changed".to_string();
+            }
+            Err(e) => {
+                *self.secrets_storage.lock().unwrap() = None;
+                self.error_message = format!(
+                    "Master password changed, but secrets could not be reopened ({}). Lock and unlock the vault.",
+                    e
+                );
+            }
+        }
+    }
+    
+    /// Make the open storages record the configured device name.
+    fn apply_device_name(&mut self) {
+        let name = (!self.device_name.is_empty()).then(|| self.device_name.clone());
+        if let Some(storage) = self.storage.lock().unwrap().as_mut() {
+            storage.set_device_name(name.clone());
+        }
+        if let Some(secrets_storage) = self.secrets_storage.lock().unwrap().as_mut() {
+            secrets_storage.set_device_name(name);
+        }
+    }
+    
+    fn apply_password_history_limit(&mut self) {
+        if let Some(storage) = self.storage.lock().unwrap().as_mut() {
+            storage.set_password_history_limit(self.password_history_limit);
+        }
+    }
+    
+    fn save_device_name(&mut self) {
+        let name = self.device_name.trim();
+        match save_device_name((!name.is_empty()).then_some(name)) {
+            Ok(saved) => {
+                self.device_name = saved.unwrap_or_default();
+                self.apply_device_name();
+                self.success_message = if self.device_name.is_empty() {
+                    "Device name cleared".to_string()
+                } else {
+                    format!("Changes will be recorded as '{}'", self.device_name)
+                };
+            }
+            Err(e) => self.error_message = format!("Failed to save device name: {}", e),
+        }
+    }
+    
     fn lock_vault(&mut self) {
         *self.storage.lock().unwrap() = None;
         *self.secrets_storage.lock().unwrap() = None;
         self.entries.clear();
         self.filtered_entries.clear();
+        self.search_results.clear();
+        self.search_due = None;
+        self.tag_counts.clear();
         self.secrets.clear();
-        self.filtered_secrets.clear();
+        self.rotation_due = 0;
+        self.secret_statistics = None;
+        self.secret_page.clear();
+        self.secret_page_type = None;
+        self.secret_current_page = 0;
+        self.secret_search_query.clear();
+        self.secret_search_due = None;
+        self.secret_filter_tags.clear();
+        self.secret_filter_environment = None;
+        self.secret_filter_favorites = false;
+        self.revealed_card = None;
         self.selected_entry_id = None;
+        self.checked_entry_ids.clear();
+        self.confirm_bulk_delete = false;
+        self.pending_drafts.clear();
+        self.saved_drafts.clear();
+        self.large_reveal = None;
+        self.new_recovery_key = None;
+        self.pending_reveal = None;
+        self.reauth_password.clear();
+        self.reauth_failed = false;
+        self.integrity_report = None;
+        self.duplicate_audit = None;
+        self.duplicate_merge = None;
+        self.database_maintenance = None;
+        self.restore_conflicts = None;
+        if let Some(job) = self.import_job.take() {
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+        self.env_export = None;
+        self.document_view = None;
+        self.entry_strength = None;
+        self.expiry_notices.clear();
+        self.trash = None;
+        #[cfg(feature = "breach-check")]
+        {
+            self.breach_audit = None;
+        }
+        #[cfg(feature = "connection-test")]
+        self.connection_tests.clear();
+        // Drops the worker too, whose cache is named with this vault's key
+        #[cfg(feature = "favicons")]
+        {
+            self.site_icons = SiteIcons::default();
+        }
         self.screen = Screen::Login;
         self.success_message = "Vault locked".to_string();
     }
     
+    /// Lock the vault once it has been idle for `auto_lock_minutes`, or when
+    /// the window is minimized with "lock on minimize" set. Any pointer or
+    /// keyboard input restarts the countdown.
+    fn check_auto_lock(&mut self, ctx: &egui::Context) {
+        if self.screen != Screen::Main {
+            return;
+        }
+        
+        if ctx.input(|i| !i.events.is_empty() || i.pointer.any_down()) {
+            self.last_activity = Instant::now();
+        }
+        
+        if self.gui_config.lock_on_minimize && ctx.input(|i| i.viewport().minimized.unwrap_or(false)) {
+            self.lock_vault();
+            self.success_message = "Vault locked when the window was minimized".to_string();
+            return;
+        }
+        
+        let timeout = Duration::from_secs(u64::from(self.auto_lock_minutes) * 60);
+        let idle = self.last_activity.elapsed();
+        if idle >= timeout {
+            self.lock_vault();
+            self.success_message = format!(
+                "Vault locked after {} minute{} of inactivity",
+                self.auto_lock_minutes,
+                if self.auto_lock_minutes == 1 { "" } else { "s" }
+            );
+        } else {
+            // Wake up in time even if nothing else repaints the window
+            ctx.request_repaint_after(timeout - idle);
+        }
+    }
+    
+    /// Mask everything revealed when the window loses focus, so it doesn't
+    /// show up behind other windows or in a shared screen.
+    fn check_focus(&mut self, ctx: &egui::Context) {
+        let focused = ctx.input(|i| i.focused);
+        if self.was_focused && !focused && self.gui_config.hide_when_unfocused {
+            self.show_password = false;
+            self.revealed_card = None;
+            self.large_reveal = None;
+            if let Some(view) = &mut self.env_diff {
+                view.reveal = false;
+            }
+            self.generated_masked = true;
+        }
+        self.was_focused = focused;
+    }
+    
+    /// Filter for listing entries in the chosen sort order.
+    fn entry_filter(&self) -> SearchFilter {
+        SearchFilter {
+            query: None,
+            tags: None,
+            favorite_only: false,
+            sort_by: self.gui_config.entry_sort.field,
+            sort_order: self.gui_config.entry_sort.order,
+        }
+    }
+    
     fn load_entries(&mut self) {
         let storage_mutex = self.storage.clone();
         let runtime = self.runtime.clone();
+        let filter = self.entry_filter();
         
-        self.entries = runtime.block_on(async {
+        (self.entries, self.tag_counts) = runtime.block_on(async {
             let storage_guard = storage_mutex.lock().unwrap();
             if let Some(storage) = storage_guard.as_ref() {
-                let filter = SearchFilter {
-                    query: None,
-                    tags: None,
-                    favorite_only: false,
-                    sort_by: SortField::Site,
-                    sort_order: SortOrder::Ascending,
-                };
-                storage.search_entries(&filter).await.unwrap_or_default()
+                (
+                    storage.search_entries(&filter).await.unwrap_or_default(),
+                    storage.tag_counts().await.unwrap_or_default(),
+                )
             } else {
-                vec![]
+                (vec![], vec![])
             }
         });
         
+        // Forget ticked entries that were deleted
+        let ids: HashSet<&String> = self.entries.iter().map(|e| &e.id).collect();
+        self.checked_entry_ids.retain(|id| ids.contains(id));
+        
         self.current_page = 0; // Reset to first page when loading entries
+        if self.search_query.is_empty() {
+            self.filter_entries();
+        } else {
+            self.run_search();
+        }
+    }
+    
+    /// Search again once typing pauses. Clearing the search applies at once.
+    fn schedule_search(&mut self) {
+        if self.search_query.trim().is_empty() {
+            self.search_due = None;
+            self.current_page = 0;
+            self.filter_entries();
+        } else {
+            self.search_due = Some(Instant::now() + SEARCH_DEBOUNCE);
+        }
+    }
+    
+    fn poll_search(&mut self, ctx: &egui::Context) {
+        if let Some(due) = self.search_due {
+            let now = Instant::now();
+            if now >= due {
+                self.run_search();
+            } else {
+                ctx.request_repaint_after(due - now);
+            }
+        }
+        if let Some(due) = self.secret_search_due {
+            let now = Instant::now();
+            if now >= due {
+                self.filter_secrets();
+            } else {
+                ctx.request_repaint_after(due - now);
+            }
+        }
+    }
+    
+    /// Query the vault's full-text index for `search_query`; only matching
+    /// entries are decrypted.
+    fn run_search(&mut self) {
+        self.search_due = None;
+        let storage_mutex = self.storage.clone();
+        let runtime = self.runtime.clone();
+        let query = self.search_query.clone();
+        let filter = self.entry_filter();
+        
+        let result = runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            match storage_guard.as_ref() {
+                Some(storage) => storage.search_fts(&query, &filter).await,
+                None => Ok(vec![]),
+            }
+        });
+        match result {
+            Ok(results) => self.search_results = results,
+            Err(e) => {
+                self.search_results.clear();
+                self.error_message = format!("Search failed: {}", e);
+            }
+        }
+        
+        self.current_page = 0;
         self.filter_entries();
     }
     
+    /// Load the list of secrets without decrypting them, then the current
+    /// page. Large documents are only decrypted when their page is shown.
     fn load_secrets(&mut self) {
         let secrets_storage_mutex = self.secrets_storage.clone();
         let runtime = self.runtime.clone();
         
-        self.secrets = runtime.block_on(async {
+        (self.secrets, self.rotation_due) = runtime.block_on(async {
             let secrets_storage_guard = secrets_storage_mutex.lock().unwrap();
             if let Some(secrets_storage) = secrets_storage_guard.as_ref() {
                 let filter = SecretFilter::default();
-                secrets_storage.search_secrets(&filter).await.unwrap_or_default()
+                let summaries = secrets_storage
+                    .search_secrets_paged(&filter, 0, usize::MAX)
+                    .await
+                    .map(|page| page.secrets)
+                    .unwrap_or_default();
+                let rotation_due = secrets_storage
+                    .api_keys_needing_rotation()
+                    .await
+                    .map(|keys| keys.len())
+                    .unwrap_or_default();
+                (summaries, rotation_due)
             } else {
-                vec![]
+                (vec![], 0)
+            }
+        });
+        
+        self.load_secret_page();
+    }
+    
+    /// Decrypt the current tab's page of secrets matching the search and
+    /// filters.
+    fn load_secret_page(&mut self) {
+        let mut filter = self.secret_filter().unwrap_or_default();
+        filter.secret_types = Some(vec![self.current_secret_tab.clone()]);
+        let offset = self.secret_current_page * self.secrets_per_page;
+        let limit = self.secrets_per_page;
+        
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        let runtime = self.runtime.clone();
+        let result = runtime.block_on(async {
+            let secrets_storage_guard = secrets_storage_mutex.lock().unwrap();
+            let Some(secrets_storage) = secrets_storage_guard.as_ref() else {
+                return Ok((vec![], 0));
+            };
+            let mut page = secrets_storage.search_secrets_paged(&filter, offset, limit).await?;
+            if page.secrets.is_empty() && page.total > 0 {
+                // The last page emptied out; show the new last page
+                let last = (page.total - 1) / limit * limit;
+                page = secrets_storage.search_secrets_paged(&filter, last, limit).await?;
             }
+            let ids: Vec<String> = page.secrets.iter().map(|secret| secret.id.clone()).collect();
+            Ok::<_, pwgen_core::Error>((secrets_storage.get_secrets(&ids).await?, page.total))
         });
         
-        self.filter_secrets();
+        self.secret_page_type = Some(self.current_secret_tab.clone());
+        match result {
+            Ok((secrets, total)) => {
+                self.secret_page = secrets;
+                self.secret_total_pages = total.div_ceil(self.secrets_per_page).max(1);
+                self.secret_current_page = self.secret_current_page.min(self.secret_total_pages - 1);
+            }
+            Err(e) => {
+                self.secret_page.clear();
+                self.secret_total_pages = 1;
+                self.error_message = format!("Search failed: {}", e);
+            }
+        }
+    }
+    
+    /// A secret decrypted from the current page, or else from storage.
+    fn find_secret(&self, id: &str) -> Option<DecryptedSecretEntry> {
+        if let Some(secret) = self.secret_page.iter().find(|secret| secret.id == id) {
+            return Some(secret.clone());
+        }
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        self.runtime.block_on(async {
+            let secrets_storage_guard = secrets_storage_mutex.lock().unwrap();
+            let secrets_storage = secrets_storage_guard.as_ref()?;
+            secrets_storage.get_secrets(&[id.to_string()]).await.ok()?.pop()
+        })
+    }
+    
+    /// The Secrets tab's search box and filters as a `SecretFilter`, or `None`
+    /// when nothing narrows the list down.
+    fn secret_filter(&self) -> Option<SecretFilter> {
+        let query = self.secret_search_query.trim();
+        let tags: Vec<String> = self.secret_filter_tags
+            .split(',')
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        let filter = SecretFilter {
+            query: (!query.is_empty()).then(|| query.to_string()),
+            tags: (!tags.is_empty()).then_some(tags),
+            environment: self.secret_filter_environment.clone(),
+            favorite_only: self.secret_filter_favorites,
+            ..Default::default()
+        };
+        let narrows = filter.query.is_some()
+            || filter.tags.is_some()
+            || filter.environment.is_some()
+            || filter.favorite_only;
+        narrows.then_some(filter)
+    }
+    
+    /// Filter again once typing pauses. Clearing the search applies at once.
+    fn schedule_secret_search(&mut self) {
+        if self.secret_search_query.trim().is_empty() {
+            self.filter_secrets();
+        } else {
+            self.secret_search_due = Some(Instant::now() + SEARCH_DEBOUNCE);
+        }
     }
     
     fn filter_secrets(&mut self) {
-        // Simple filtering for now - can be enhanced later
-        self.filtered_secrets = self.secrets.clone();
+        self.secret_search_due = None;
+        self.secret_current_page = 0;
+        self.load_secret_page();
+    }
+    
+    fn show_secret_filters(&mut self, ui: &mut egui::Ui) {
+        let mut environments: Vec<String> = self.secrets.iter()
+            .filter_map(|secret| secret.metadata.environment.clone())
+            .collect();
+        environments.sort();
+        environments.dedup();
+        
+        ui.horizontal_wrapped(|ui| {
+            ui.label("🔍");
+            let search_response = ui.add(
+                egui::TextEdit::singleline(&mut self.secret_search_query)
+                    .desired_width(220.0)
+                    .hint_text("Search secrets...")
+            );
+            if search_response.changed() {
+                self.schedule_secret_search();
+            }
+            
+            ui.label("Tags:");
+            let tag_response = ui.add(
+                egui::TextEdit::singleline(&mut self.secret_filter_tags)
+                    .desired_width(150.0)
+                    .hint_text("Comma-separated")
+            );
+            if tag_response.changed() {
+                self.schedule_secret_search();
+            }
+            
+            if !environments.is_empty() || self.secret_filter_environment.is_some() {
+                ui.label("Environment:");
+                let mut changed = false;
+                egui::ComboBox::from_id_salt("secret_filter_environment")
+                    .selected_text(self.secret_filter_environment.as_deref().unwrap_or("Any"))
+                    .show_ui(ui, |ui| {
+                        changed |= ui.selectable_value(&mut self.secret_filter_environment, None, "Any").changed();
+                        for environment in environments {
+                            let label = environment.clone();
+                            changed |= ui
+                                .selectable_value(&mut self.secret_filter_environment, Some(environment), label)
+                                .changed();
+                        }
+                    });
+                if changed {
+                    self.filter_secrets();
+                }
+            }
+            
+            if ui.checkbox(&mut self.secret_filter_favorites, "⭐ Favorites only").changed() {
+                self.filter_secrets();
+            }
+            
+            if self.secret_filter().is_some() && ui.small_button("Clear All").clicked() {
+                self.secret_search_query.clear();
+                self.secret_filter_tags.clear();
+                self.secret_filter_environment = None;
+                self.secret_filter_favorites = false;
+                self.filter_secrets();
+            }
+        });
     }
     
     fn filter_entries(&mut self) {
-        // First apply search filter
-        let mut filtered = if self.search_query.is_empty() {
+        // First apply search filter. `search_results` matched the words in
+        // any field; narrow them down to the selected one.
+        let mut filtered = if self.search_query.trim().is_empty() {
             self.entries.clone()
         } else {
-            let query = self.search_query.to_lowercase();
-            self.entries.iter()
+            let terms = search_terms(&self.search_query);
+            let in_field = |text: &str| {
+                let words = search_terms(text);
+                terms.iter().all(|term| words.iter().any(|word| word.starts_with(term.as_str())))
+            };
+            self.search_results.iter()
                 .filter(|e| {
                     match self.search_field {
-                        SearchField::All => {
-                            e.site.to_lowercase().contains(&query) ||
-                            e.username.to_lowercase().contains(&query) ||
-                            e.notes.as_ref().map(|n| n.to_lowercase().contains(&query)).unwrap_or(false) ||
-                            e.tags.iter().any(|t| t.to_lowercase().contains(&query))
-                        }
-                        SearchField::Site => e.site.to_lowercase().contains(&query),
-                        SearchField::Username => e.username.to_lowercase().contains(&query),
-                        SearchField::Notes => e.notes.as_ref().map(|n| n.to_lowercase().contains(&query)).unwrap_or(false),
-                        SearchField::Tags => e.tags.iter().any(|t| t.to_lowercase().contains(&query)),
+                        SearchField::All => true,
+                        SearchField::Site => in_field(&e.site),
+                        SearchField::Username => in_field(&e.username),
+                        SearchField::Notes => e.notes.as_deref().is_some_and(in_field),
+                        SearchField::Tags => in_field(&e.tags.join(" ")),
                     }
                 })
                 .cloned()
@@ -513,7 +2221,13 @@ impl PwGenApp {
             }
         }
         
+        // The sort is stable, so favorites keep the active order among themselves
+        if self.gui_config.favorites_first {
+            filtered.sort_by_key(|e| !e.favorite);
+        }
+        
         // Calculate pagination
+        self.total_filtered = filtered.len();
         self.total_pages = filtered.len().div_ceil(self.entries_per_page);
         if self.total_pages == 0 {
             self.total_pages = 1;
@@ -545,49 +2259,95 @@ impl PwGenApp {
         let runtime = self.runtime.clone();
         
         let site = self.entry_site.clone();
+        let urls = self.entry_urls.clone();
         let username = self.entry_username.clone();
         let password = self.entry_password.clone();
         let notes = if self.entry_notes.is_empty() { None } else { Some(self.entry_notes.clone()) };
+        let totp_secret = if self.entry_totp.trim().is_empty() {
+            None
+        } else {
+            match normalize_totp_secret(&self.entry_totp) {
+                Ok(secret) => Some(secret),
+                Err(e) => {
+                    self.error_message = e.to_string();
+                    return;
+                }
+            }
+        };
         let tags: Vec<String> = self.entry_tags.split(',')
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect();
+        let require_reauth = self.entry_require_reauth;
+        let password_policy = self.entry_policy.clone();
+        
+        let mut entry = if let Some(existing) = &self.edit_entry {
+            let mut updated = existing.clone();
+            updated.set_urls(site, urls);
+            updated.username = username;
+            updated.password = password;
+            updated.notes = notes;
+            updated.totp_secret = totp_secret;
+            updated.tags = tags;
+            updated.require_reauth = require_reauth;
+            updated.password_policy = password_policy;
+            updated.updated_at = Utc::now();
+            updated
+        } else {
+            let mut new_entry = DecryptedPasswordEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                site: site.clone(),
+                username,
+                password,
+                notes,
+                tags,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                last_used: None,
+                password_changed_at: Utc::now(),
+                favorite: false,
+                urls: Vec::new(),
+                created_by: None,
+                modified_by: None,
+                totp_secret,
+                password_history: Vec::new(),
+                require_reauth,
+                password_policy,
+            };
+            new_entry.set_urls(site, urls);
+            new_entry
+        };
+        
+        // Read the rules on every save so edits to the file apply immediately
+        match EntryRules::load() {
+            Ok(rules) => {
+                entry.tags = rules.canonical_tags(&entry.tags);
+                self.entry_tags = entry.tags.join(", ");
+                self.entry_violations = rules.validate(&entry);
+            }
+            Err(e) => {
+                self.error_message = format!("Failed to load entry rules: {}", e);
+                return;
+            }
+        }
+        if !self.entry_violations.is_empty() {
+            self.error_message = "Entry does not satisfy the entry rules".to_string();
+            return;
+        }
+        
+        self.error_message.clear();
+        let is_update = self.edit_entry.is_some();
         
-        self.error_message.clear();
-        
         runtime.block_on(async {
             let storage_guard = storage_mutex.lock().unwrap();
             if let Some(storage) = storage_guard.as_ref() {
-                if let Some(existing) = &self.edit_entry {
-                    // Update existing entry
-                    let mut updated = existing.clone();
-                    updated.site = site;
-                    updated.username = username;
-                    updated.password = password;
-                    updated.notes = notes;
-                    updated.tags = tags;
-                    updated.updated_at = Utc::now();
-                    
-                    storage.update_entry(&updated).await
+                if is_update {
+                    storage.update_entry(&entry).await
                 } else {
-                    // Add new entry
-                    let new_entry = DecryptedPasswordEntry {
-                        id: uuid::Uuid::new_v4().to_string(),
-                        site: site.clone(),
-                        username: username.clone(),
-                        password: password.clone(),
-                        notes: notes.clone(),
-                        tags: tags.clone(),
-                        created_at: Utc::now(),
-                        updated_at: Utc::now(),
-                        last_used: None,
-                        password_changed_at: Utc::now(),
-                        favorite: false,
-                    };
-                    storage.add_entry(&new_entry).await
+                    storage.add_entry(&entry).await
                 }
             } else {
-                Err(pwgen_core::Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "Storage not initialized")))
+                Err(pwgen_core::Error::VaultLocked)
             }
         }).unwrap_or_else(|e| {
             self.error_message = format!("Failed to save entry: {}", e);
@@ -597,6 +2357,7 @@ impl PwGenApp {
             self.success_message = if self.edit_entry.is_some() { "Entry updated successfully" } else { "Entry added successfully" }.to_string();
             self.show_add_dialog = false;
             self.clear_entry_form();
+            self.clear_draft(ENTRY_DRAFT);
             self.load_entries();
         }
     }
@@ -611,31 +2372,219 @@ impl PwGenApp {
             if let Some(storage) = storage_guard.as_ref() {
                 storage.delete_entry(&id_string).await
             } else {
-                Err(pwgen_core::Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "Storage not initialized")))
+                Err(pwgen_core::Error::VaultLocked)
             }
         }).unwrap_or_else(|e| {
             self.error_message = format!("Failed to delete entry: {}", e);
         });
         
         if self.error_message.is_empty() {
-            self.success_message = "Entry deleted successfully".to_string();
+            self.success_message = "Entry moved to the trash".to_string();
             self.selected_entry_id = None;
             self.load_entries();
         }
     }
     
+    /// Star or unstar an entry. The list updates in place, staying on the
+    /// same page.
+    fn toggle_favorite(&mut self, entry: &DecryptedPasswordEntry) {
+        let storage_mutex = self.storage.clone();
+        let runtime = self.runtime.clone();
+        let favorite = !entry.favorite;
+        
+        let result = runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            if let Some(storage) = storage_guard.as_ref() {
+                storage.set_favorite(&entry.id, favorite).await
+            } else {
+                Err(pwgen_core::Error::VaultLocked)
+            }
+        });
+        if let Err(e) = result {
+            self.error_message = format!("Failed to update favorites: {}", e);
+            return;
+        }
+        
+        for stored in self.entries.iter_mut().chain(self.search_results.iter_mut()) {
+            if stored.id == entry.id {
+                stored.favorite = favorite;
+            }
+        }
+        self.filter_entries();
+    }
+    
+    /// Star button of an entry row, lit for favorites.
+    fn show_favorite_button(&mut self, ui: &mut egui::Ui, entry: &DecryptedPasswordEntry) {
+        let (color, hint) = if entry.favorite {
+            (egui::Color32::from_rgb(255, 200, 80), "Remove from favorites")
+        } else {
+            (ui.visuals().weak_text_color(), "Add to favorites")
+        };
+        let star = egui::Button::new(egui::RichText::new("⭐").color(color)).frame(false);
+        if ui.add(star).on_hover_text(hint).clicked() {
+            self.toggle_favorite(entry);
+        }
+    }
+    
+    /// Save `gui_config` after a preference changed, reporting any failure.
+    fn save_gui_config(&mut self) {
+        if let Err(e) = self.gui_config.save() {
+            self.error_message = format!("Failed to save preferences: {}", e);
+        }
+    }
+    
+    fn show_favorites_first_checkbox(&mut self, ui: &mut egui::Ui) {
+        if ui
+            .checkbox(&mut self.gui_config.favorites_first, "Favorites first")
+            .on_hover_text("List favorites at the top, whatever the sort order")
+            .changed()
+        {
+            if let Err(e) = self.gui_config.save() {
+                self.error_message = format!("Failed to save preferences: {}", e);
+            }
+            self.filter_entries();
+        }
+    }
+    
+    /// Add or remove `bulk_tag` on every ticked entry.
+    fn bulk_edit_tag(&mut self, add: bool) {
+        let tag = match EntryRules::load() {
+            Ok(rules) => rules.canonical_tags(std::slice::from_ref(&self.bulk_tag)),
+            Err(e) => {
+                self.error_message = format!("Failed to load entry rules: {}", e);
+                return;
+            }
+        };
+        let Some(tag) = tag.into_iter().next() else {
+            return;
+        };
+        
+        let storage_mutex = self.storage.clone();
+        let runtime = self.runtime.clone();
+        let ids: Vec<String> = self.checked_entry_ids.iter().cloned().collect();
+        let result = runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            match storage_guard.as_ref() {
+                Some(storage) if add => storage.bulk_add_tag(&ids, &tag).await,
+                Some(storage) => storage.bulk_remove_tag(&ids, &tag).await,
+                None => Err(pwgen_core::Error::VaultLocked),
+            }
+        });
+        
+        match result {
+            Ok(changed) => {
+                self.success_message = format!(
+                    "{} tag '{}' {} {} entr{}",
+                    if add { "Added" } else { "Removed" },
+                    tag,
+                    if add { "to" } else { "from" },
+                    changed,
+                    if changed == 1 { "y" } else { "ies" }
+                );
+                self.bulk_tag.clear();
+                self.load_entries();
+            }
+            Err(e) => self.error_message = format!("Failed to update tags: {}", e),
+        }
+    }
+    
+    /// Move every ticked entry to the trash.
+    fn bulk_delete_entries(&mut self) {
+        let storage_mutex = self.storage.clone();
+        let runtime = self.runtime.clone();
+        let ids: Vec<String> = self.checked_entry_ids.iter().cloned().collect();
+        let result = runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            match storage_guard.as_ref() {
+                Some(storage) => storage.bulk_delete(&ids).await,
+                None => Err(pwgen_core::Error::VaultLocked),
+            }
+        });
+        
+        match result {
+            Ok(()) => {
+                self.success_message = format!(
+                    "{} entr{} moved to the trash",
+                    ids.len(),
+                    if ids.len() == 1 { "y" } else { "ies" }
+                );
+                if self.selected_entry_id.as_ref().is_some_and(|id| self.checked_entry_ids.contains(id)) {
+                    self.selected_entry_id = None;
+                }
+                self.checked_entry_ids.clear();
+                self.load_entries();
+            }
+            Err(e) => self.error_message = format!("Failed to delete entries: {}", e),
+        }
+    }
+    
     fn clear_entry_form(&mut self) {
         self.entry_site.clear();
+        self.entry_urls.clear();
         self.entry_username.clear();
         self.entry_password.clear();
+        self.entry_strength = None;
+        self.entry_totp.clear();
         self.entry_notes.clear();
         self.entry_tags.clear();
+        self.entry_require_reauth = false;
+        self.entry_policy = None;
         self.show_password = false;
         self.edit_entry = None;
+        self.entry_violations.clear();
     }
     
-    fn generate_password(&mut self) {
-        let config = PasswordConfig {
+    /// Show an empty add-entry dialog, with a generated password when
+    /// `gen_for_new_entries` is on.
+    fn open_new_entry_form(&mut self) {
+        self.clear_entry_form();
+        self.show_add_dialog = true;
+        if self.gui_config.gen_for_new_entries {
+            self.fill_entry_password();
+        }
+    }
+    
+    /// Replace the entry form's password with a new one that follows the
+    /// site's password rules, or else the generator settings.
+    fn fill_entry_password(&mut self) {
+        let generated = match &self.entry_policy {
+            Some(policy) => PasswordGenerator::generate(&policy.config()),
+            None => self.generate_from_settings(),
+        };
+        match generated {
+            Ok(password) => self.entry_password = password,
+            Err(e) => self.error_message = format!("Failed to generate password: {}", e),
+        }
+    }
+    
+    /// The entry form's password rules: off to use the generator settings,
+    /// or a length and the character classes the site accepts.
+    fn show_entry_policy(&mut self, ui: &mut egui::Ui) {
+        ui.vertical(|ui| {
+            let mut remember = self.entry_policy.is_some();
+            if ui
+                .checkbox(&mut remember, "Site has password rules")
+                .on_hover_text(PASSWORD_POLICY_HINT)
+                .changed()
+            {
+                self.entry_policy = remember.then(|| PasswordPolicy::from(&self.generator_config()));
+            }
+            if let Some(policy) = &mut self.entry_policy {
+                ui.horizontal(|ui| {
+                    ui.add(egui::Slider::new(&mut policy.length, 8..=128).text("characters"));
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut policy.uppercase, "A-Z");
+                    ui.checkbox(&mut policy.lowercase, "a-z");
+                    ui.checkbox(&mut policy.numbers, "0-9");
+                    ui.checkbox(&mut policy.symbols, "!@#$");
+                });
+            }
+        });
+    }
+    
+    fn generator_config(&self) -> PasswordConfig {
+        PasswordConfig {
             length: self.gen_length as usize,
             include_uppercase: self.gen_uppercase,
             include_lowercase: self.gen_lowercase,
@@ -647,11 +2596,150 @@ impl PwGenApp {
             min_lowercase: if self.gen_lowercase { 1 } else { 0 },
             min_numbers: if self.gen_numbers { 1 } else { 0 },
             min_symbols: if self.gen_symbols { 1 } else { 0 },
+            mobile_friendly: self.gen_mobile_friendly,
+        }
+    }
+    
+    fn mobile_entropy_summary(&self) -> String {
+        let config = self.generator_config();
+        let standard = PasswordConfig { mobile_friendly: false, ..config.clone() };
+        let bits = PasswordGenerator::estimate_entropy(&config);
+        let cost = PasswordGenerator::estimate_entropy(&standard) - bits;
+        format!("~{:.0} bits of entropy ({:.0} bits less than standard)", bits, cost)
+    }
+    
+    /// Entropy of the current pattern, or why it is not a valid pattern.
+    fn pattern_summary(&self) -> String {
+        let entropy = if self.gen_pattern_template {
+            PasswordGenerator::template_entropy(&self.gen_pattern)
+        } else {
+            PasswordGenerator::pattern_entropy(&self.gen_pattern)
         };
-        
-        match PasswordGenerator::generate(&config) {
+        match entropy {
+            Ok(bits) => format!("~{:.0} bits of entropy", bits),
+            Err(e) => e.to_string(),
+        }
+    }
+    
+    fn passphrase_summary(&self) -> String {
+        match self.gen_wordlist.load() {
+            Ok(wordlist) => format!(
+                "{:.1} bits per word, ~{:.0} bits of entropy",
+                wordlist.bits_per_word(),
+                PasswordGenerator::passphrase_entropy(&wordlist, self.gen_words, self.gen_with_number)
+            ),
+            Err(e) => e.to_string(),
+        }
+    }
+    
+    fn memorable_entropy(&self) -> f64 {
+        PasswordGenerator::pronounceable_entropy(self.gen_length as usize, self.gen_numbers, self.gen_symbols)
+    }
+    
+    fn generate_from_settings(&self) -> pwgen_core::Result<String> {
+        match self.gen_mode {
+            GeneratorMode::Characters => PasswordGenerator::generate(&self.generator_config()),
+            GeneratorMode::Pattern if self.gen_pattern_template => {
+                PasswordGenerator::generate_from_pattern(&self.gen_pattern)
+            }
+            GeneratorMode::Pattern => PasswordGenerator::generate_pattern(&self.gen_pattern),
+            GeneratorMode::Passphrase => {
+                let wordlist = self.gen_wordlist.load()?;
+                PasswordGenerator::generate_passphrase_from(
+                    &wordlist,
+                    self.gen_words,
+                    &self.gen_separator,
+                    self.gen_capitalize,
+                    self.gen_with_number,
+                )
+                .map(|passphrase| passphrase.phrase)
+            }
+            GeneratorMode::Memorable => {
+                PasswordGenerator::generate_pronounceable(self.gen_length as usize, self.gen_numbers, self.gen_symbols)
+                    .map(|pronounceable| pronounceable.password)
+            }
+        }
+    }
+    
+    /// Mode selector plus the pattern or passphrase settings, shared by the
+    /// generator tab and dialog.
+    fn show_generator_mode(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.gen_mode, GeneratorMode::Characters, "Characters");
+            ui.selectable_value(&mut self.gen_mode, GeneratorMode::Pattern, "Pattern")
+                .on_hover_text("X = uppercase, x = lowercase, 9 = digit, * = any; other characters are kept (\\ escapes)");
+            ui.selectable_value(&mut self.gen_mode, GeneratorMode::Passphrase, "Passphrase")
+                .on_hover_text("Random words from the EFF or Diceware word lists");
+            ui.selectable_value(&mut self.gen_mode, GeneratorMode::Memorable, "Memorable")
+                .on_hover_text("Alternating consonants and vowels, easy to read and type but weaker per character");
+        });
+        match self.gen_mode {
+            GeneratorMode::Characters => {}
+            GeneratorMode::Pattern => {
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.gen_pattern);
+                    ui.checkbox(&mut self.gen_pattern_template, "Template")
+                        .on_hover_text("L = uppercase, l = lowercase, d = digit, s = symbol, {text} = literal, e.g. Llll-dddd-s");
+                });
+                ui.small(self.pattern_summary());
+            }
+            GeneratorMode::Passphrase => {
+                egui::Grid::new("passphrase_settings").num_columns(2).show(ui, |ui| {
+                    ui.label("Word list:");
+                    egui::ComboBox::from_id_salt("passphrase_wordlist")
+                        .selected_text(self.gen_wordlist.to_string())
+                        .show_ui(ui, |ui| {
+                            for wordlist in Wordlist::EMBEDDED {
+                                let label = wordlist.to_string();
+                                ui.selectable_value(&mut self.gen_wordlist, wordlist, label);
+                            }
+                        });
+                    ui.end_row();
+                    
+                    ui.label("Words:");
+                    ui.add(egui::Slider::new(&mut self.gen_words, 3..=20));
+                    ui.end_row();
+                    
+                    ui.label("Separator:");
+                    ui.add(egui::TextEdit::singleline(&mut self.gen_separator).desired_width(40.0));
+                    ui.end_row();
+                    
+                    ui.label("Capitalize:");
+                    ui.checkbox(&mut self.gen_capitalize, "Word");
+                    ui.end_row();
+                    
+                    ui.label("Number:");
+                    ui.checkbox(&mut self.gen_with_number, "Add a digit");
+                    ui.end_row();
+                });
+                ui.small(self.passphrase_summary());
+            }
+            GeneratorMode::Memorable => {
+                egui::Grid::new("memorable_settings").num_columns(2).show(ui, |ui| {
+                    ui.label("Length:");
+                    ui.add(egui::Slider::new(&mut self.gen_length, 8..=128));
+                    ui.end_row();
+                    
+                    ui.label("Number:");
+                    ui.checkbox(&mut self.gen_numbers, "End with two digits");
+                    ui.end_row();
+                    
+                    ui.label("Symbol:");
+                    ui.checkbox(&mut self.gen_symbols, "Add a symbol");
+                    ui.end_row();
+                });
+                ui.small(format!("~{:.0} bits of entropy", self.memorable_entropy()));
+            }
+        }
+    }
+    
+    fn generate_password(&mut self) {
+        match self.generate_from_settings() {
             Ok(password) => {
+                self.generated_entropy = (self.gen_mode == GeneratorMode::Memorable)
+                    .then(|| (password.clone(), self.memorable_entropy()));
                 self.generated_password = password;
+                self.generated_masked = false;
             }
             Err(e) => {
                 self.error_message = format!("Failed to generate password: {}", e);
@@ -660,2330 +2748,5997 @@ impl PwGenApp {
     }
     
     #[cfg(feature = "clipboard")]
-    fn copy_to_clipboard(&self, text: &str) {
+    fn copy_to_clipboard(&mut self, text: &str) {
         if let Ok(mut clipboard) = Clipboard::new() {
-            if clipboard.set_text(text).is_ok() {
+            let set = clipboard.set();
+            let set = if self.clipboard_sensitive_hint { exclude_from_history(set) } else { set };
+            if set.text(text).is_ok() {
                 // Don't set success message here as it's called frequently
+                self.check_clipboard_managers();
+                self.schedule_clipboard_clear(text);
             }
         }
     }
     
+    /// Clear the clipboard after `clipboard_clear_secs`, but only if it still
+    /// holds `text`, so that anything copied in the meantime is left alone.
+    #[cfg(feature = "clipboard")]
+    fn schedule_clipboard_clear(&self, text: &str) {
+        if self.gui_config.clipboard_clear_secs == 0 {
+            return;
+        }
+        let delay = Duration::from_secs(u64::from(self.gui_config.clipboard_clear_secs));
+        let copied = text.to_string();
+        self.runtime.spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = tokio::task::spawn_blocking(move || {
+                let Ok(mut clipboard) = Clipboard::new() else {
+                    return;
+                };
+                // An error means the clipboard is empty or holds something
+                // other than text, which is not ours to clear
+                if clipboard.get_text().is_ok_and(|current| current == copied) {
+                    let _ = clipboard.clear();
+                }
+            })
+            .await;
+        });
+    }
+    
+    /// Success message for a copy, mentioning when the clipboard is cleared.
+    fn copied_message(&self, what: &str) -> String {
+        if cfg!(feature = "clipboard") && self.gui_config.clipboard_clear_secs > 0 {
+            format!("{} copied (clears in {}s)", what, self.gui_config.clipboard_clear_secs)
+        } else {
+            format!("{} copied", what)
+        }
+    }
+    
     #[cfg(not(feature = "clipboard"))]
-    fn copy_to_clipboard(&self, _text: &str) {
+    fn copy_to_clipboard(&mut self, _text: &str) {
         // Clipboard functionality disabled
     }
     
-    fn quick_copy_entry(&mut self, entry: &DecryptedPasswordEntry, field: &str) {
-        match field {
-            "username" => {
-                self.copy_to_clipboard(&entry.username);
-                self.success_message = format!("Username copied for {}", entry.site);
-            }
-            "password" => {
-                self.copy_to_clipboard(&entry.password);
-                self.success_message = format!("Password copied for {}", entry.site);
-            }
-            _ => {}
+    /// On the first copy of the session, look for clipboard history tools and
+    /// queue a notice about them unless it was turned off.
+    #[cfg_attr(not(feature = "clipboard"), allow(dead_code))]
+    fn check_clipboard_managers(&mut self) {
+        if self.clipboard_checked || self.gui_config.hide_clipboard_manager_notice {
+            return;
+        }
+        self.clipboard_checked = true;
+        let managers = detect_clipboard_managers();
+        if !managers.is_empty() {
+            self.clipboard_notice = Some(managers);
         }
     }
     
-    fn copy_secret_data(&mut self, secret: &DecryptedSecretEntry) {
-        use pwgen_core::secrets::SecretData;
+    fn show_clipboard_notice(&mut self, ctx: &egui::Context) {
+        let Some(managers) = &self.clipboard_notice else {
+            return;
+        };
+        let hint = if self.clipboard_sensitive_hint {
+            "PwGen marks copied values as sensitive, but not every tool honours that hint."
+        } else {
+            "Turn on \"Mark copies as sensitive\" in Settings so that tools which honour the hint skip PwGen."
+        };
         
-        match &secret.data {
-            SecretData::ApiKey { api_key, .. } => {
-                self.copy_to_clipboard(api_key);
-                self.success_message = format!("API key copied for {}", secret.name);
-            }
-            SecretData::Token { access_token, .. } => {
-                self.copy_to_clipboard(access_token);
-                self.success_message = format!("Access token copied for {}", secret.name);
-            }
-            SecretData::SshKey { private_key: Some(private_key), .. } => {
-                self.copy_to_clipboard(private_key);
-                self.success_message = format!("SSH private key copied for {}", secret.name);
+        let mut dismissed = false;
+        egui::Window::new("ℹ Clipboard history detected")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -40.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} may keep a copy of everything copied from PwGen.",
+                    managers.join(", ")
+                ));
+                ui.label(hint);
+                ui.label("Consider excluding PwGen in its settings or clearing its history after copying secrets.");
+                ui.add_space(4.0);
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.clipboard_notice_dont_show, "Don't show this again");
+                    dismissed = ui.button("OK").clicked();
+                });
+            });
+        
+        if dismissed {
+            self.clipboard_notice = None;
+            if self.clipboard_notice_dont_show {
+                self.gui_config.hide_clipboard_manager_notice = true;
+                if let Err(e) = self.gui_config.save() {
+                    self.error_message = format!("Failed to save preferences: {}", e);
+                }
             }
-            SecretData::SshKey { public_key: Some(public_key), .. } => {
-                self.copy_to_clipboard(public_key);
-                self.success_message = format!("SSH public key copied for {}", secret.name);
+        }
+    }
+    
+    fn show_expiry_banner(&mut self, ctx: &egui::Context) {
+        let mut dismissed = false;
+        let mut open_secrets = false;
+        egui::TopBottomPanel::top("expiry_banner").show(ctx, |ui| {
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                let count = self.expiry_notices.len();
+                let plural = if count == 1 { "" } else { "s" };
+                let overdue = self.expiry_notices.iter().filter(|n| n.is_overdue()).count();
+                let heading = if overdue > 0 {
+                    format!("⚠ {} secret{} need attention, {} overdue", count, plural, overdue)
+                } else {
+                    format!(
+                        "⏰ {} secret{} expiring or due for rotation within {} days",
+                        count, plural, self.gui_config.expiry_warning_days
+                    )
+                };
+                ui.strong(heading);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    dismissed = ui.small_button("✖").on_hover_text("Dismiss").clicked();
+                    open_secrets = ui.small_button("🔐 Open Secrets").clicked();
+                });
+            });
+            for notice in self.expiry_notices.iter().take(5) {
+                if notice.is_overdue() {
+                    ui.colored_label(error_color(ui), notice.to_string());
+                } else {
+                    ui.label(notice.to_string());
+                }
             }
-            SecretData::SecureNote { content, .. } => {
-                self.copy_to_clipboard(content);
-                self.success_message = format!("Note content copied for {}", secret.name);
+            if self.expiry_notices.len() > 5 {
+                ui.small(format!("…and {} more", self.expiry_notices.len() - 5));
             }
-            SecretData::ConnectionString { connection_string, .. } => {
-                self.copy_to_clipboard(connection_string);
-                self.success_message = format!("Connection string copied for {}", secret.name);
+            ui.add_space(4.0);
+        });
+        
+        if open_secrets {
+            self.show_secrets_view = true;
+        }
+        if dismissed {
+            self.expiry_notices.clear();
+        }
+    }
+    
+    /// Copy a sensitive value, first asking for confirmation when the global
+    /// setting or the item's tags require it, or for the master password when
+    /// `reauth` is set. `what` names the value for the prompt and the success
+    /// message, e.g. "Password for github.com".
+    fn copy_sensitive(&mut self, text: &str, what: String, tags: &[String], reauth: bool) {
+        if reauth || requires_reveal_confirmation(tags, self.gui_config.confirm_copy_reveal) {
+            self.pending_reveal = Some(PendingReveal {
+                what,
+                action: RevealAction::Copy(text.to_string()),
+                reauth,
+            });
+        } else {
+            self.copy_to_clipboard(text);
+            self.success_message = self.copied_message(&what);
+        }
+    }
+    
+    /// The entry's current two-factor code with the seconds it stays valid
+    /// and a copy button. Repaints every second while shown.
+    fn show_totp_code(&mut self, ui: &mut egui::Ui, entry: &DecryptedPasswordEntry) {
+        let Some(secret) = &entry.totp_secret else {
+            return;
+        };
+        let now = Utc::now();
+        match generate_totp(secret, now) {
+            Ok(code) => {
+                ui.horizontal(|ui| {
+                    let (first, second) = code.split_at(code.len() / 2);
+                    ui.monospace(format!("🔢 {} {}", first, second));
+                    ui.small(format!("{}s", totp_seconds_remaining(secret, now)));
+                    if ui.small_button("📋").on_hover_text("Copy 2FA code").clicked() {
+                        self.copy_to_clipboard(&code);
+                        self.success_message = self.copied_message("2FA code");
+                    }
+                });
+                ui.ctx().request_repaint_after(Duration::from_secs(1));
             }
-            SecretData::Configuration { variables, .. } => {
-                // Copy the first environment variable value, or a formatted string of all variables
-                if let Some((key, value)) = variables.iter().next() {
-                    self.copy_to_clipboard(value);
-                    self.success_message = format!("Variable '{}' copied for {}", key, secret.name);
-                } else {
-                    self.success_message = format!("No variables to copy for {}", secret.name);
-                }
+            Err(e) => {
+                ui.small(format!("2FA: {}", e));
             }
-            _ => {
-                self.success_message = "Copy not supported for this secret type".to_string();
+        }
+    }
+    
+    /// The entry's previous passwords with when they were replaced, each
+    /// with copy and restore buttons.
+    fn show_password_history(&mut self, ui: &mut egui::Ui, entry: &DecryptedPasswordEntry) {
+        if entry.password_history.is_empty() {
+            return;
+        }
+        let mut restore = None;
+        ui.collapsing(format!("🕘 History ({})", entry.password_history.len()), |ui| {
+            for (index, previous) in entry.password_history.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.small(format!(
+                        "Replaced {}",
+                        previous.changed_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+                    ));
+                    if ui.small_button("📋").on_hover_text("Copy this password").clicked() {
+                        self.copy_sensitive(
+                            &previous.password,
+                            format!("Previous password for {}", entry.site),
+                            &entry.tags,
+                            entry.require_reauth,
+                        );
+                    }
+                    if ui.small_button("↺").on_hover_text("Make this the current password again").clicked() {
+                        restore = Some(index);
+                    }
+                });
             }
+        });
+        if let Some(index) = restore {
+            self.restore_previous_password(entry, index);
         }
     }
     
-    fn delete_secret(&mut self, secret_id: &str) {
-        let secrets_storage_mutex = self.secrets_storage.clone();
-        let runtime = self.runtime.clone();
-        let id_string = secret_id.to_string();
+    /// Make a password from the entry's history current again. The replaced
+    /// password goes into the history in its place.
+    fn restore_previous_password(&mut self, entry: &DecryptedPasswordEntry, index: usize) {
+        let Some(previous) = entry.password_history.get(index) else {
+            return;
+        };
+        let mut restored = entry.clone();
+        restored.password = previous.password.clone();
         
-        let result = runtime.block_on(async {
-            let secrets_storage_guard = secrets_storage_mutex.lock().unwrap();
-            if let Some(secrets_storage) = secrets_storage_guard.as_ref() {
-                secrets_storage.delete_secret(&id_string).await
+        let storage_mutex = self.storage.clone();
+        let result = self.runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            if let Some(storage) = storage_guard.as_ref() {
+                storage.update_entry(&restored).await
             } else {
-                Err(pwgen_core::Error::Other("Secrets storage not available".to_string()))
+                Err(pwgen_core::Error::VaultLocked)
             }
         });
-        
         match result {
-            Ok(_) => {
-                self.success_message = "Secret deleted successfully".to_string();
-                self.load_secrets(); // Reload secrets list
-            }
-            Err(e) => {
-                self.error_message = format!("Failed to delete secret: {}", e);
+            Ok(()) => {
+                self.success_message = format!("Restored the previous password for {}", entry.site);
+                self.load_entries();
             }
+            Err(e) => self.error_message = format!("Failed to restore password: {}", e),
         }
     }
-}
-
-impl eframe::App for PwGenApp {
-    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        // Update window dimensions for responsive layout
-        let screen_rect = ctx.screen_rect();
-        self.window_width = screen_rect.width();
-        self.is_compact_mode = self.window_width < 1000.0;
+    
+    fn quick_copy_entry(&mut self, entry: &DecryptedPasswordEntry, field: &str) {
+        self.touch_entry(&entry.id);
+        match field {
+            "username" => {
+                self.copy_sensitive(&entry.username, format!("Username for {}", entry.site), &entry.tags, false);
+            }
+            "password" => {
+                self.copy_sensitive(
+                    &entry.password,
+                    format!("Password for {}", entry.site),
+                    &entry.tags,
+                    entry.require_reauth,
+                );
+            }
+            "totp" => {
+                let Some(secret) = &entry.totp_secret else {
+                    return;
+                };
+                match generate_totp(secret, Utc::now()) {
+                    Ok(code) => {
+                        self.copy_to_clipboard(&code);
+                        self.success_message = self.copied_message("2FA code");
+                    }
+                    Err(e) => self.error_message = format!("Could not generate a 2FA code for {}: {}", entry.site, e),
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// Record that the entry was used just now, in the vault and in the lists
+    /// shown, without reloading them.
+    fn touch_entry(&mut self, id: &str) {
+        let storage_mutex = self.storage.clone();
+        let result = self.runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            match storage_guard.as_ref() {
+                Some(storage) => storage.touch_entry(id).await,
+                None => Ok(()),
+            }
+        });
+        if let Err(e) = result {
+            self.error_message = format!("Failed to record use of the entry: {}", e);
+            return;
+        }
         
-        // TODO: Handle tray menu events when system tray is re-enabled
+        let now = Utc::now();
+        for entry in self.entries.iter_mut()
+            .chain(self.filtered_entries.iter_mut())
+            .chain(self.search_results.iter_mut())
+            .filter(|entry| entry.id == id)
+        {
+            entry.last_used = Some(now);
+        }
+    }
+    
+    /// Copy the username or password, as configured, and open the entry's
+    /// site in the default browser: the usual way of logging in to a site.
+    fn copy_and_open(&mut self, entry: &DecryptedPasswordEntry) {
+        let Some(url) = entry.login_url() else {
+            self.error_message = format!("{} has no web address to open", entry.site);
+            return;
+        };
+        self.touch_entry(&entry.id);
+        let (text, what, reauth) = match self.gui_config.copy_and_open {
+            CopyAndOpenField::Username => (&entry.username, format!("Username for {}", entry.site), false),
+            CopyAndOpenField::Password => {
+                (&entry.password, format!("Password for {}", entry.site), entry.require_reauth)
+            }
+            CopyAndOpenField::Nothing => {
+                self.open_url(&url);
+                return;
+            }
+        };
         
-        match self.screen {
-            Screen::Login => self.show_login_screen(ctx),
-            Screen::Main => self.show_main_screen(ctx, frame),
+        if reauth || requires_reveal_confirmation(&entry.tags, self.gui_config.confirm_copy_reveal) {
+            self.pending_reveal = Some(PendingReveal {
+                what,
+                action: RevealAction::CopyAndOpen(text.to_string(), url),
+                reauth,
+            });
+        } else {
+            let text = text.clone();
+            self.copy_and_open_now(&text, &url, &what);
         }
     }
-}
-
-impl PwGenApp {
-    fn show_login_screen(&mut self, ctx: &egui::Context) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical_centered(|ui| {
-                ui.add_space(40.0);
-                
-                // Display wide logo prominently
-                if let Some(logo) = &self.logo_wide {
-                    ui.add(
-                        egui::Image::from_texture(logo)
-                            .fit_to_exact_size(egui::vec2(200.0, 67.0))
-                            .rounding(egui::Rounding::same(8.0))
-                    );
-                    ui.add_space(10.0);
-                    ui.label(
-                        egui::RichText::new("Password Manager")
-                            .size(16.0)
-                            .color(ui.visuals().weak_text_color())
-                    );
+    
+    /// Open the entry's site in the default browser without copying anything.
+    fn open_in_browser(&mut self, entry: &DecryptedPasswordEntry) {
+        match entry.login_url() {
+            Some(url) => self.open_url(&url),
+            None => self.error_message = format!("{} has no web address to open", entry.site),
+        }
+    }
+    
+    fn open_url(&mut self, url: &str) {
+        match open::that(url) {
+            Ok(()) => self.success_message = format!("Opened {} in your browser", url),
+            Err(e) => self.error_message = format!("Failed to open {}: {}", url, e),
+        }
+    }
+    
+    fn copy_and_open_now(&mut self, text: &str, url: &str, what: &str) {
+        self.copy_to_clipboard(text);
+        match open::that(url) {
+            Ok(()) => self.success_message = format!("{}, opened {} in your browser", self.copied_message(what), url),
+            Err(e) => {
+                self.success_message = self.copied_message(what);
+                self.error_message = format!("Failed to open {}: {}", url, e);
+            }
+        }
+    }
+    
+    fn double_click_setting(&mut self, ui: &mut egui::Ui) {
+        let before = self.gui_config.double_click;
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.gui_config.double_click, DoubleClickAction::CopyPassword, "Copy password");
+            ui.radio_value(&mut self.gui_config.double_click, DoubleClickAction::CopyUsername, "Copy username");
+            ui.radio_value(&mut self.gui_config.double_click, DoubleClickAction::Open, "Open");
+        })
+        .response
+        .on_hover_text(DOUBLE_CLICK_HINT);
+        if self.gui_config.double_click != before {
+            if let Err(e) = self.gui_config.save() {
+                self.error_message = format!("Failed to save preferences: {}", e);
+            }
+        }
+    }
+    
+    fn copy_and_open_setting(&mut self, ui: &mut egui::Ui) {
+        let before = self.gui_config.copy_and_open;
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.gui_config.copy_and_open, CopyAndOpenField::Password, "Password");
+            ui.radio_value(&mut self.gui_config.copy_and_open, CopyAndOpenField::Username, "Username");
+            ui.radio_value(&mut self.gui_config.copy_and_open, CopyAndOpenField::Nothing, "Nothing");
+        })
+        .response
+        .on_hover_text(COPY_AND_OPEN_HINT);
+        if self.gui_config.copy_and_open != before {
+            if let Err(e) = self.gui_config.save() {
+                self.error_message = format!("Failed to save preferences: {}", e);
+            }
+        }
+    }
+    
+    fn copy_secret_data(&mut self, secret: &DecryptedSecretEntry) {
+        use pwgen_core::secrets::SecretData;
+        
+        let (value, what) = match &secret.data {
+            SecretData::ApiKey { api_key, .. } => (api_key, "API key".to_string()),
+            SecretData::Token { access_token, .. } => (access_token, "Access token".to_string()),
+            SecretData::SshKey { private_key: Some(private_key), .. } => (private_key, "SSH private key".to_string()),
+            SecretData::SshKey { public_key: Some(public_key), .. } => (public_key, "SSH public key".to_string()),
+            SecretData::SecureNote { content, .. } => (content, "Note content".to_string()),
+            SecretData::CreditCard { number, .. } => (number, "Card number".to_string()),
+            SecretData::ConnectionString { connection_string, .. } => (connection_string, "Connection string".to_string()),
+            SecretData::Configuration { variables, .. } => {
+                // Copy the first environment variable value, or a formatted string of all variables
+                if let Some((key, value)) = variables.iter().next() {
+                    (value, format!("Variable '{}'", key))
                 } else {
-                    // Fallback if logo doesn't load
-                    ui.heading(
-                        egui::RichText::new("🦀 PwGen")
-                            .size(32.0)
-                            .color(ui.visuals().strong_text_color())
-                    );
-                    ui.label(
-                        egui::RichText::new("Password Manager")
-                            .size(16.0)
-                            .color(ui.visuals().weak_text_color())
-                    );
-                }
-                
-                ui.add_space(40.0);
-                
-                let vault_exists = self.get_vault_path()
-                    .map(|p| p.exists())
-                    .unwrap_or(false);
-                
-                ui.group(|ui| {
-                    ui.set_min_width(350.0);
-                    ui.vertical_centered(|ui| {
-                        if vault_exists {
-                            ui.heading("Welcome Back");
-                            ui.label("Enter your master password to unlock the vault");
-                        } else {
-                            ui.heading("Create Your Vault");
-                            ui.label("Set a strong master password to protect your data");
-                        }
-                        
-                        ui.add_space(20.0);
-                        
-                        ui.horizontal(|ui| {
-                            ui.label("Master Password:");
-                            let response = ui.add(
-                                egui::TextEdit::singleline(&mut self.master_password)
-                                    .password(true)
-                                    .desired_width(200.0)
-                                    .hint_text("Enter password...")
-                                    .font(egui::TextStyle::Monospace)
-                            );
-                            
-                            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                                if vault_exists {
-                                    self.unlock_vault();
-                                } else if !self.master_password_confirm.is_empty() {
-                                    self.init_vault();
-                                }
-                            }
-                        });
-                        
-                        if !vault_exists {
-                            ui.horizontal(|ui| {
-                                ui.label("Confirm Password:");
-                                let response = ui.add(
-                                    egui::TextEdit::singleline(&mut self.master_password_confirm)
-                                        .password(true)
-                                        .desired_width(200.0)
-                                        .hint_text("Confirm password...")
-                                        .font(egui::TextStyle::Monospace)
-                                );
-                                
-                                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                                    self.init_vault();
-                                }
-                            });
-                        }
-                        
-                        ui.add_space(20.0);
-                        
-                        if vault_exists {
-                            if ui.button("🔓 Unlock Vault").clicked() {
-                                self.unlock_vault();
-                            }
-                        } else {
-                            if ui.button("🔐 Create Vault").clicked() {
-                                self.init_vault();
-                            }
-                            
-                            ui.add_space(10.0);
-                            ui.label("⚠ Remember: Your master password cannot be recovered!");
-                        }
-                    });
-                });
-                
-                if !self.error_message.is_empty() {
-                    ui.add_space(10.0);
-                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), &self.error_message);
-                }
-                
-                if !self.success_message.is_empty() {
-                    ui.add_space(10.0);
-                    ui.colored_label(egui::Color32::from_rgb(100, 255, 100), &self.success_message);
+                    self.success_message = format!("No variables to copy for {}", secret.name);
+                    return;
                 }
+            }
+            _ => {
+                self.success_message = "Copy not supported for this secret type".to_string();
+                return;
+            }
+        };
+        
+        self.copy_sensitive(
+            value,
+            format!("{} for {}", what, secret.name),
+            &secret.tags,
+            secret.metadata.require_reauth,
+        );
+    }
+    
+    fn copy_secret_command(&mut self, secret: &DecryptedSecretEntry) {
+        if let Some(snippet) = command_snippet(&secret.data, true) {
+            self.copy_sensitive(
+                &snippet.command,
+                format!("{} command for {}", snippet.tool, secret.name),
+                &secret.tags,
+                secret.metadata.require_reauth,
+            );
+        }
+    }
+    
+    fn add_ssh_key_to_agent(&mut self, secret: &DecryptedSecretEntry) {
+        if let SecretData::SshKey { private_key: Some(private_key), passphrase, .. } = &secret.data {
+            match SshKeyManager::add_to_agent(private_key, passphrase.as_deref(), None) {
+                Ok(()) => self.success_message = format!("Added {} to the SSH agent", secret.name),
+                Err(e) => self.error_message = format!("Could not add {} to the SSH agent: {}", secret.name, e),
+            }
+        }
+    }
+    
+    /// Show `text` in the large reveal overlay, asking first when the tags or
+    /// settings require confirmation, or for the master password when
+    /// `reauth` is set.
+    fn large_reveal_sensitive(&mut self, text: &str, what: String, tags: &[String], reauth: bool) {
+        if reauth || requires_reveal_confirmation(tags, self.gui_config.confirm_copy_reveal) {
+            self.pending_reveal = Some(PendingReveal {
+                what,
+                action: RevealAction::LargeReveal(text.to_string()),
+                reauth,
             });
+        } else {
+            self.open_large_reveal(text.to_string(), what);
+        }
+    }
+    
+    fn open_large_reveal(&mut self, text: String, what: String) {
+        self.large_reveal = Some(LargeReveal {
+            what,
+            text,
+            expires_at: Instant::now() + Duration::from_secs(self.gui_config.large_reveal_secs as u64),
         });
     }
     
-    fn show_main_screen(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Menu bar
-        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
-            egui::menu::bar(ui, |ui| {
-                ui.menu_button("File", |ui| {
-                    if ui.button("➕ New Entry").clicked() {
-                        self.show_add_dialog = true;
-                        self.clear_entry_form();
-                        ui.close_menu();
-                    }
-                    if ui.button("🔒 Lock Vault").clicked() {
-                        self.lock_vault();
-                        ui.close_menu();
+    fn show_large_reveal_overlay(&mut self, ctx: &egui::Context) {
+        let Some(reveal) = &self.large_reveal else {
+            return;
+        };
+        let remaining = reveal.expires_at.saturating_duration_since(Instant::now());
+        let job = large_reveal_job(&reveal.text, self.gui_config.large_reveal_chunked);
+        
+        let mut close = ctx.input(|i| {
+            i.events.iter().any(|event| matches!(event, egui::Event::Key { pressed: true, .. }))
+        });
+        egui::Window::new("Large Reveal")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.heading(&reveal.what);
+                    ui.add_space(10.0);
+                    ui.label(job);
+                    ui.add_space(10.0);
+                    ui.small(format!("Closes in {}s or on any key", remaining.as_secs() + 1));
+                    if ui.button("✖ Close").clicked() {
+                        close = true;
                     }
-                    ui.separator();
-                    if ui.button("⚙ Settings").clicked() {
-                        self.show_settings = true;
-                        ui.close_menu();
+                });
+            });
+        
+        if close {
+            self.large_reveal = None;
+        }
+    }
+    
+    fn show_reveal_confirm_dialog(&mut self, ctx: &egui::Context) {
+        let Some(pending) = &self.pending_reveal else {
+            return;
+        };
+        let prompt = match pending.action {
+            RevealAction::Copy(_) | RevealAction::CopyAndOpen(..) => {
+                format!("Copy the {} to the clipboard?", pending.what.to_lowercase())
+            }
+            RevealAction::ShowPassword | RevealAction::ShowCardNumber(_) => {
+                format!("Show the {} on screen?", pending.what.to_lowercase())
+            }
+            RevealAction::LargeReveal(_) => format!("Show the {} in large text?", pending.what.to_lowercase()),
+            RevealAction::EnvDiffValues | RevealAction::ViewDocument(_) => {
+                format!("Show the {} on screen?", pending.what.to_lowercase())
+            }
+        };
+        let reauth = pending.reauth;
+        
+        let mut confirmed = false;
+        let mut cancelled = false;
+        egui::Window::new("Confirm")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(prompt);
+                if reauth {
+                    ui.label("Enter the master password to continue:");
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.reauth_password).password(true));
+                    response.request_focus();
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        confirmed = true;
                     }
-                    ui.separator();
-                    if ui.button("❌ Exit").clicked() {
-                        self.minimize_to_tray = false; // Force exit
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    if self.reauth_failed {
+                        ui.colored_label(error_color(ui), "Wrong master password");
                     }
+                }
+                ui.horizontal(|ui| {
+                    confirmed |= ui.button("✅ Confirm").clicked();
+                    cancelled = ui.button("❌ Cancel").clicked();
                 });
-                
-                ui.menu_button("Edit", |ui| {
-                    if ui.button("🔍 Search").clicked() {
-                        // Focus search field
-                        ui.close_menu();
-                    }
-                    ui.separator();
-                    if ui.button("📋 Copy Username").clicked() {
-                        if let Some(id) = &self.selected_entry_id {
-                            if let Some(entry) = self.entries.iter().find(|e| e.id == *id).cloned() {
-                                self.quick_copy_entry(&entry, "username");
-                            }
-                        }
-                        ui.close_menu();
-                    }
-                    if ui.button("🔑 Copy Password").clicked() {
-                        if let Some(id) = &self.selected_entry_id {
-                            if let Some(entry) = self.entries.iter().find(|e| e.id == *id).cloned() {
-                                self.quick_copy_entry(&entry, "password");
-                            }
-                        }
-                        ui.close_menu();
-                    }
-                });
-                
-                ui.menu_button("Tools", |ui| {
-                    if ui.button("🔐 Secrets Manager").clicked() {
-                        self.show_secrets_view = true;
-                        ui.close_menu();
-                    }
-                    ui.separator();
-                    if ui.button("🎲 Password Generator").clicked() {
-                        self.show_generator = true;
-                        self.generate_password();
-                        ui.close_menu();
-                    }
-                    if ui.button("📊 Vault Statistics").clicked() {
-                        self.show_statistics = true;
-                        ui.close_menu();
-                    }
-                    if ui.button("📥 Import from Browser").clicked() {
-                        self.show_import = true;
-                        ui.close_menu();
-                    }
-                    if ui.button("💾 Backup Vault").clicked() {
-                        self.show_backup = true;
-                        ui.close_menu();
+            });
+        
+        if confirmed && reauth {
+            let storage_mutex = self.storage.clone();
+            let password = std::mem::take(&mut self.reauth_password);
+            let verified = self.runtime.block_on(async {
+                let storage_guard = storage_mutex.lock().unwrap();
+                match storage_guard.as_ref() {
+                    Some(storage) => storage.verify_master_password(&password).await,
+                    None => Ok(false),
+                }
+            });
+            match verified {
+                Ok(true) => self.reauth_failed = false,
+                Ok(false) => {
+                    self.reauth_failed = true;
+                    return;
+                }
+                Err(e) => {
+                    self.error_message = format!("Failed to check the master password: {}", e);
+                    return;
+                }
+            }
+        }
+        
+        if confirmed {
+            if let Some(pending) = self.pending_reveal.take() {
+                match pending.action {
+                    RevealAction::Copy(text) => {
+                        self.copy_to_clipboard(&text);
+                        self.success_message = self.copied_message(&pending.what);
                     }
-                });
-                
-                ui.menu_button("Help", |ui| {
-                    if ui.button("📚 Documentation").clicked() {
-                        if let Err(e) = open::that("https://github.com/hxhippy/pwgen/blob/main/README.md") {
-                            eprintln!("Failed to open documentation: {}", e);
+                    RevealAction::CopyAndOpen(text, url) => self.copy_and_open_now(&text, &url, &pending.what),
+                    RevealAction::ShowPassword => self.show_password = true,
+                    RevealAction::ShowCardNumber(id) => self.revealed_card = Some(id),
+                    RevealAction::LargeReveal(text) => self.open_large_reveal(text, pending.what),
+                    RevealAction::EnvDiffValues => {
+                        if let Some(view) = &mut self.env_diff {
+                            view.reveal = true;
                         }
-                        ui.close_menu();
                     }
-                    if ui.button("🌐 Kief Studio").clicked() {
-                        if let Err(e) = open::that("https://kief.studio") {
-                            eprintln!("Failed to open Kief Studio: {}", e);
+                    RevealAction::ViewDocument(id) => {
+                        if let Some(secret) = self.find_secret(&id) {
+                            self.open_document_view(secret);
                         }
-                        ui.close_menu();
                     }
-                    ui.separator();
-                    if ui.button("ℹ About PwGen").clicked() {
-                        self.show_about = true;
-                        ui.close_menu();
+                }
+            }
+        } else if cancelled {
+            self.pending_reveal = None;
+            self.reauth_password.clear();
+            self.reauth_failed = false;
+        }
+    }
+    
+    fn verify_integrity(&mut self) {
+        let storage_mutex = self.storage.clone();
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        
+        let result = self.runtime.block_on(async {
+            let mut report = IntegrityReport::default();
+            if let Some(storage) = storage_mutex.lock().unwrap().as_ref() {
+                storage.verify_entries(&mut report).await?;
+            }
+            if let Some(secrets_storage) = secrets_storage_mutex.lock().unwrap().as_ref() {
+                secrets_storage.verify_secrets(&mut report).await?;
+            }
+            Ok::<_, pwgen_core::Error>(report)
+        });
+        
+        match result {
+            Ok(report) => self.integrity_report = Some(report),
+            Err(e) => self.error_message = format!("Integrity check failed to run: {}", e),
+        }
+    }
+    
+    /// Group entries by password on a worker thread, so that decrypting a
+    /// large vault doesn't stall the window.
+    fn start_duplicate_audit(&mut self) {
+        if matches!(self.duplicate_audit, Some(DuplicateAudit::Running(_))) {
+            return;
+        }
+        let (sender, result) = mpsc::channel();
+        let storage_mutex = self.storage.clone();
+        let runtime = self.runtime.clone();
+        std::thread::spawn(move || {
+            let clusters = runtime.block_on(async {
+                let storage_guard = storage_mutex.lock().unwrap();
+                match storage_guard.as_ref() {
+                    Some(storage) => storage.find_duplicate_passwords().await,
+                    None => Err(pwgen_core::Error::VaultLocked),
+                }
+            });
+            let _ = sender.send(clusters);
+        });
+        self.duplicate_audit = Some(DuplicateAudit::Running(result));
+    }
+    
+    /// Check the database file and, if it is sound, vacuum it on a worker
+    /// thread. The storage lock is held throughout, so nothing else touches
+    /// the vault meanwhile.
+    #[cfg(feature = "connection-test")]
+    fn start_connection_test(&mut self, secret: DecryptedSecretEntry) {
+        let (sender, result) = mpsc::channel();
+        let runtime = self.runtime.clone();
+        let id = secret.id.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(runtime.block_on(EnvConnectionManager::test_connection(&secret.data)));
+        });
+        self.connection_tests.insert(id, ConnectionTest::Running(result));
+    }
+    
+    #[cfg(feature = "connection-test")]
+    fn poll_connection_tests(&mut self, ctx: &egui::Context) {
+        for test in self.connection_tests.values_mut() {
+            if let ConnectionTest::Running(result) = test {
+                match result.try_recv() {
+                    Ok(done) => *test = ConnectionTest::Done(done),
+                    Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(100)),
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        *test = ConnectionTest::Done(Err(ConnectionTestError::Other("Test stopped".to_string())));
                     }
+                }
+            }
+        }
+    }
+    
+    /// The site's icon next to its name in the entry list, when icons are
+    /// on and it has been loaded.
+    #[cfg(feature = "favicons")]
+    fn show_site_icon(&mut self, ui: &mut egui::Ui, entry: &DecryptedPasswordEntry) {
+        if !self.gui_config.show_site_icons {
+            return;
+        }
+        let Some(host) = favicon_host(&entry.site) else {
+            return;
+        };
+        match self.site_icons.textures.get(&host) {
+            Some(Some(texture)) => {
+                ui.image((texture.id(), egui::vec2(16.0, 16.0)));
+            }
+            Some(None) => {}
+            None => {
+                let storage = self.storage.clone();
+                self.site_icons.request(host, move || {
+                    storage.lock().unwrap().as_ref()?.cache_naming_key().ok()
+                });
+            }
+        }
+    }
+    
+    #[cfg(feature = "favicons")]
+    fn poll_site_icons(&mut self, ctx: &egui::Context) {
+        let icons = &mut self.site_icons;
+        let Some((_, results)) = &icons.worker else {
+            return;
+        };
+        loop {
+            match results.try_recv() {
+                Ok((host, image)) => {
+                    let texture = image.map(|image| {
+                        ctx.load_texture(format!("favicon-{}", host), image, egui::TextureOptions::LINEAR)
+                    });
+                    icons.textures.insert(host, texture);
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    icons.worker = None;
+                    break;
+                }
+            }
+        }
+        if icons.requested.len() > icons.textures.len() {
+            ctx.request_repaint_after(Duration::from_millis(200));
+        }
+    }
+    
+    /// Green latency or red reason for the last test of a database secret.
+    #[cfg(feature = "connection-test")]
+    fn show_connection_test_status(&self, ui: &mut egui::Ui, secret_id: &str) {
+        match self.connection_tests.get(secret_id) {
+            Some(ConnectionTest::Running(_)) => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.small("Connecting...");
                 });
+            }
+            Some(ConnectionTest::Done(Ok(latency))) => {
+                ui.colored_label(success_color(ui), format!("● Connected in {} ms", latency.as_millis()));
+            }
+            Some(ConnectionTest::Done(Err(e))) => {
+                ui.colored_label(error_color(ui), format!("● {}", e));
+            }
+            None => {}
+        }
+    }
+    
+    fn start_database_maintenance(&mut self) {
+        if matches!(self.database_maintenance, Some(DatabaseMaintenance::Running(_))) {
+            return;
+        }
+        let (sender, result) = mpsc::channel();
+        let storage_mutex = self.storage.clone();
+        let runtime = self.runtime.clone();
+        std::thread::spawn(move || {
+            let result = runtime.block_on(async {
+                let storage_guard = storage_mutex.lock().unwrap();
+                let Some(storage) = storage_guard.as_ref() else {
+                    return Err(pwgen_core::Error::VaultLocked);
+                };
+                let integrity_ok = storage.integrity_check().await?;
+                let vacuum = if integrity_ok { Some(storage.vacuum().await?) } else { None };
+                Ok(MaintenanceResult { integrity_ok, vacuum })
             });
+            let _ = sender.send(result);
         });
+        self.database_maintenance = Some(DatabaseMaintenance::Running(result));
+    }
+    
+    fn show_database_maintenance_dialog(&mut self, ctx: &egui::Context) {
+        let Some(maintenance) = &mut self.database_maintenance else {
+            return;
+        };
         
-        // Header with logo and search - responsive layout
-        egui::TopBottomPanel::top("header").show(ctx, |ui| {
-            ui.add_space(5.0);
-            
-            if self.is_compact_mode {
-                // Compact mode: stack elements vertically
-                ui.vertical(|ui| {
-                    // Top row: Logo and main action buttons
+        if let DatabaseMaintenance::Running(result) = maintenance {
+            match result.try_recv() {
+                Ok(Ok(done)) => *maintenance = DatabaseMaintenance::Done(done),
+                Ok(Err(e)) => *maintenance = DatabaseMaintenance::Failed(e.to_string()),
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(100)),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    *maintenance = DatabaseMaintenance::Failed("Maintenance stopped".to_string());
+                }
+            }
+        }
+        
+        let mut open = true;
+        egui::Window::new("🔧 Database Maintenance")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(400.0)
+            .show(ctx, |ui| match maintenance {
+                DatabaseMaintenance::Running(_) => {
                     ui.horizontal(|ui| {
-                        // Display logo if available
-                        if let Some(logo) = &self.logo_wide {
-                            ui.add(
-                                egui::Image::from_texture(logo)
-                                    .fit_to_exact_size(egui::vec2(100.0, 33.0))
-                                    .rounding(egui::Rounding::same(4.0))
-                            );
-                        } else {
-                            ui.heading(
-                                egui::RichText::new("🦀 PwGen")
-                                    .size(20.0)
-                                    .color(ui.visuals().strong_text_color())
-                            );
-                        }
-                        
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            if ui.button("🔒 Lock").clicked() {
-                                self.lock_vault();
-                            }
-                            if ui.button("🎲 Generate").clicked() {
-                                self.show_generator = true;
-                                self.generate_password();
+                        ui.spinner();
+                        ui.label("Checking and optimizing the database...");
+                    });
+                }
+                DatabaseMaintenance::Failed(e) => {
+                    ui.colored_label(error_color(ui), format!("Maintenance failed: {}", e));
+                }
+                DatabaseMaintenance::Done(result) => {
+                    if result.integrity_ok {
+                        ui.colored_label(success_color(ui), "✅ Integrity check passed");
+                    } else {
+                        ui.colored_label(
+                            error_color(ui),
+                            "❌ Integrity check failed: the database file is damaged. Restore a backup; \
+                             the database was not optimized.",
+                        );
+                    }
+                    if let Some(stats) = &result.vacuum {
+                        let kib = |bytes: u64| bytes as f64 / 1024.0;
+                        ui.label(format!(
+                            "Size: {:.1} KiB before, {:.1} KiB after ({:.1} KiB reclaimed)",
+                            kib(stats.bytes_before),
+                            kib(stats.bytes_after),
+                            kib(stats.bytes_before.saturating_sub(stats.bytes_after)),
+                        ));
+                    }
+                }
+            });
+        
+        if !open && !matches!(self.database_maintenance, Some(DatabaseMaintenance::Running(_))) {
+            self.database_maintenance = None;
+        }
+    }
+    
+    fn open_trash(&mut self) {
+        let storage_mutex = self.storage.clone();
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        let result = self.runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            let Some(storage) = storage_guard.as_ref() else {
+                return Err(pwgen_core::Error::VaultLocked);
+            };
+            let entries = storage.list_trashed().await?;
+            let retention_days = storage.trash_retention_days().await?;
+            let secrets = match secrets_storage_mutex.lock().unwrap().as_ref() {
+                Some(secrets_storage) => secrets_storage.list_trashed_secrets().await?,
+                None => Vec::new(),
+            };
+            Ok(TrashView { entries, secrets, retention_days, confirm_empty: false })
+        });
+        match result {
+            Ok(view) => self.trash = Some(view),
+            Err(e) => self.error_message = format!("Failed to open the trash: {}", e),
+        }
+    }
+    
+    fn show_trash_dialog(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.trash else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut restore_entry = None;
+        let mut restore_secret = None;
+        let mut empty = false;
+        let mut retention_changed = false;
+        egui::Window::new("♻ Trash")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Purge deleted items after");
+                    retention_changed = ui
+                        .add(egui::DragValue::new(&mut view.retention_days).range(0..=3650).suffix(" days"))
+                        .on_hover_text("Checked each time the vault is unlocked; 0 keeps items until the trash is emptied")
+                        .changed();
+                });
+                ui.separator();
+                
+                if view.entries.is_empty() && view.secrets.is_empty() {
+                    ui.label("The trash is empty");
+                    return;
+                }
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for trashed in &view.entries {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("🔑 {} ({})", trashed.item.site, trashed.item.username));
+                            ui.small(format!("deleted {}", trashed.deleted_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")));
+                            if ui.small_button("Restore").clicked() {
+                                restore_entry = Some(trashed.item.id.clone());
                             }
-                            if ui.button("➕ Add Entry").clicked() {
-                                self.show_add_dialog = true;
-                                self.clear_entry_form();
+                        });
+                    }
+                    for trashed in &view.secrets {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("🔐 {}", trashed.item.name));
+                            ui.small(format!("deleted {}", trashed.deleted_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")));
+                            if ui.small_button("Restore").clicked() {
+                                restore_secret = Some(trashed.item.id.clone());
                             }
                         });
-                    });
-                    
-                    ui.add_space(3.0);
-                    
-                    // Second row: Search and advanced search toggle
+                    }
+                });
+                ui.separator();
+                if view.confirm_empty {
                     ui.horizontal(|ui| {
-                        ui.label("🔍");
-                        let search_response = ui.add(
-                            egui::TextEdit::singleline(&mut self.search_query)
-                                .desired_width(ui.available_width() - 60.0)
-                                .hint_text("Search passwords...")
-                        );
-                        if search_response.changed() {
-                            self.filter_entries();
+                        ui.colored_label(egui::Color32::from_rgb(255, 100, 100), "Permanently delete everything in the trash?");
+                        if ui.button("Delete").clicked() {
+                            empty = true;
                         }
-                        
-                        if ui.small_button("🔧").on_hover_text("Advanced Search").clicked() {
-                            self.show_advanced_search = !self.show_advanced_search;
+                        if ui.button("Cancel").clicked() {
+                            view.confirm_empty = false;
                         }
                     });
-                    
-                    // Third row: Pagination controls (if needed)
-                    if self.total_pages > 1 {
-                        ui.add_space(2.0);
-                        ui.horizontal(|ui| {
-                            ui.label("📄");
-                            
-                            if ui.add_enabled(self.current_page > 0, egui::Button::new("◀")).clicked() {
-                                self.current_page -= 1;
-                                self.filter_entries();
-                            }
-                            
-                            ui.label(format!("{}/{}", self.current_page + 1, self.total_pages));
-                            
-                            if ui.add_enabled(self.current_page < self.total_pages - 1, egui::Button::new("▶")).clicked() {
-                                self.current_page += 1;
-                                self.filter_entries();
-                            }
-                            
-                            ui.separator();
-                            ui.label("Per page:");
-                            egui::ComboBox::from_id_salt("entries_per_page")
-                                .selected_text(format!("{}", self.entries_per_page))
-                                .width(80.0)
-                                .show_ui(ui, |ui| {
-                                    for &size in &[25, 50, 100, 200] {
-                                        if ui.selectable_value(&mut self.entries_per_page, size, format!("{}", size)).clicked() {
-                                            self.current_page = 0;
-                                            self.filter_entries();
-                                        }
-                                    }
-                                });
+                } else if ui.button("Empty Trash").clicked() {
+                    view.confirm_empty = true;
+                }
+            });
+        
+        let retention_days = view.retention_days;
+        if !open {
+            self.trash = None;
+        }
+        if !(retention_changed || empty || restore_entry.is_some() || restore_secret.is_some()) {
+            return;
+        }
+        
+        let storage_mutex = self.storage.clone();
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        let result = self.runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            let secrets_guard = secrets_storage_mutex.lock().unwrap();
+            let (Some(storage), Some(secrets_storage)) = (storage_guard.as_ref(), secrets_guard.as_ref()) else {
+                return Err(pwgen_core::Error::VaultLocked);
+            };
+            if retention_changed {
+                storage.set_trash_retention_days(retention_days).await?;
+            }
+            if let Some(id) = &restore_entry {
+                storage.restore_entry(id).await?;
+            }
+            if let Some(id) = &restore_secret {
+                secrets_storage.restore_secret(id).await?;
+            }
+            if empty {
+                storage.purge_trash(chrono::Duration::zero()).await?;
+                secrets_storage.purge_trashed_secrets(chrono::Duration::zero()).await?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            self.error_message = format!("Trash: {}", e);
+            return;
+        }
+        
+        if restore_entry.is_some() || restore_secret.is_some() {
+            self.success_message = "Restored from the trash".to_string();
+            self.load_entries();
+            self.load_secrets();
+        } else if empty {
+            self.success_message = "Trash emptied".to_string();
+        }
+        if self.trash.is_some() && (empty || restore_entry.is_some() || restore_secret.is_some()) {
+            self.open_trash();
+        }
+    }
+    
+    /// Open the merge duplicates dialog with what `strategy` would merge.
+    fn plan_duplicate_merge(&mut self, strategy: MergeStrategy) {
+        let storage_mutex = self.storage.clone();
+        let plan = self.runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            match storage_guard.as_ref() {
+                Some(storage) => storage.plan_duplicate_merges(strategy).await,
+                None => Err(pwgen_core::Error::VaultLocked),
+            }
+        });
+        self.duplicate_merge = Some(DuplicateMergeView { strategy, plan: plan.map_err(|e| e.to_string()) });
+    }
+    
+    fn show_duplicate_merge_dialog(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.duplicate_merge else {
+            return;
+        };
+        let mut open = true;
+        let mut replan = None;
+        let mut merge = false;
+        let mut cancel = false;
+        
+        egui::Window::new("🔗 Remove Duplicates")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                let mut any_password = view.strategy == MergeStrategy::AnyPassword;
+                if ui.checkbox(&mut any_password, "Also merge entries with different passwords")
+                    .on_hover_text(MERGE_ANY_PASSWORD_HINT)
+                    .changed()
+                {
+                    replan = Some(if any_password { MergeStrategy::AnyPassword } else { MergeStrategy::SamePassword });
+                }
+                ui.separator();
+                
+                let plan = match &view.plan {
+                    Ok(plan) => plan,
+                    Err(e) => {
+                        ui.colored_label(error_color(ui), format!("Search failed: {}", e));
+                        return;
+                    }
+                };
+                if plan.merges.is_empty() {
+                    ui.colored_label(success_color(ui), "✅ Nothing to merge");
+                }
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for merge in &plan.merges {
+                        ui.group(|ui| {
+                            let merged = &merge.merged;
+                            ui.strong(format!("{} ({})", merged.site, merged.username));
+                            ui.label(format!(
+                                "Keeps the entry updated {}; moves {} to the trash:",
+                                merged.updated_at.format("%Y-%m-%d"),
+                                merge.removed.len(),
+                            ));
+                            for removed in &merge.removed {
+                                ui.small(format!(
+                                    "🗑 {} ({}), updated {}",
+                                    removed.site,
+                                    removed.username,
+                                    removed.updated_at.format("%Y-%m-%d")
+                                ));
+                            }
+                            if !merged.tags.is_empty() {
+                                ui.small(format!("Tags: {}", merged.tags.join(", ")));
+                            }
+                            if merged.notes.is_some() {
+                                ui.small("Notes of all entries are kept");
+                            }
+                            if !merged.password_history.is_empty() {
+                                ui.small(format!("{} previous password(s) kept in the history", merged.password_history.len()));
+                            }
                         });
                     }
+                    if !plan.different_passwords.is_empty() {
+                        ui.add_space(5.0);
+                        ui.label(format!(
+                            "Not merged, since their passwords differ ({} site(s)):",
+                            plan.different_passwords.len()
+                        ));
+                        for group in &plan.different_passwords {
+                            ui.small(format!("• {} ({}), {} entries", group[0].site, group[0].username, group.len()));
+                        }
+                    }
                 });
-            } else {
-                // Wide mode: horizontal layout
+                
+                ui.separator();
                 ui.horizontal(|ui| {
-                    // Display logo if available
-                    if let Some(logo) = &self.logo_wide {
-                        ui.add(
-                            egui::Image::from_texture(logo)
-                                .fit_to_exact_size(egui::vec2(120.0, 40.0))
-                                .rounding(egui::Rounding::same(4.0))
-                        );
-                    } else {
-                        ui.heading(
-                            egui::RichText::new("🦀 PwGen")
-                                .size(24.0)
-                                .color(ui.visuals().strong_text_color())
-                        );
-                    }
-                    
-                    ui.separator();
-                    
-                    // Search
-                    ui.add_space(5.0);
-                    ui.label("🔍");
-                    let search_width = if self.window_width > 1400.0 { 400.0 } else { 300.0 };
-                    let search_response = ui.add(
-                        egui::TextEdit::singleline(&mut self.search_query)
-                            .desired_width(search_width)
-                            .hint_text("Search passwords...")
-                    );
-                    if search_response.changed() {
-                        self.filter_entries();
+                    let label = format!("🔗 Merge {} group(s)", plan.merges.len());
+                    if ui.add_enabled(!plan.merges.is_empty(), egui::Button::new(label)).clicked() {
+                        merge = true;
                     }
-                    
-                    // Advanced search toggle
-                    if ui.small_button("🔧").on_hover_text("Advanced Search").clicked() {
-                        self.show_advanced_search = !self.show_advanced_search;
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
                     }
-                    
-                    // Pagination controls
-                    if self.total_pages > 1 {
-                        ui.separator();
-                        ui.label("📄");
-                        
-                        if ui.add_enabled(self.current_page > 0, egui::Button::new("◀")).clicked() {
-                            self.current_page -= 1;
-                            self.filter_entries();
-                        }
-                        
-                        ui.label(format!("{}/{}", self.current_page + 1, self.total_pages));
-                        
-                        if ui.add_enabled(self.current_page < self.total_pages - 1, egui::Button::new("▶")).clicked() {
-                            self.current_page += 1;
-                            self.filter_entries();
-                        }
-                        
-                        ui.separator();
-                        ui.label("Per page:");
-                        egui::ComboBox::from_id_salt("entries_per_page")
-                            .selected_text(format!("{}", self.entries_per_page))
-                            .width(80.0)
-                            .show_ui(ui, |ui| {
-                                for &size in &[25, 50, 100, 200] {
-                                    if ui.selectable_value(&mut self.entries_per_page, size, format!("{}", size)).clicked() {
-                                        self.current_page = 0;
-                                        self.filter_entries();
+                });
+            });
+        
+        if let Some(strategy) = replan {
+            self.plan_duplicate_merge(strategy);
+            return;
+        }
+        if merge {
+            let strategy = view.strategy;
+            let storage_mutex = self.storage.clone();
+            let result = self.runtime.block_on(async {
+                let storage_guard = storage_mutex.lock().unwrap();
+                match storage_guard.as_ref() {
+                    Some(storage) => storage.merge_duplicates(strategy).await,
+                    None => Err(pwgen_core::Error::VaultLocked),
+                }
+            });
+            match result {
+                Ok(report) => {
+                    let removed: usize = report.merges.iter().map(|merge| merge.removed.len()).sum();
+                    self.success_message = format!(
+                        "Merged {} duplicate group(s); {} entr{} moved to the trash",
+                        report.merges.len(),
+                        removed,
+                        if removed == 1 { "y" } else { "ies" }
+                    );
+                    self.load_entries();
+                }
+                Err(e) => self.error_message = format!("Failed to merge duplicates: {}", e),
+            }
+            self.duplicate_merge = None;
+        } else if !open || cancel {
+            self.duplicate_merge = None;
+        }
+    }
+    
+    fn show_duplicate_audit_dialog(&mut self, ctx: &egui::Context) {
+        let Some(audit) = &mut self.duplicate_audit else {
+            return;
+        };
+        
+        if let DuplicateAudit::Running(result) = audit {
+            match result.try_recv() {
+                Ok(Ok(clusters)) => {
+                    let groups = clusters
+                        .into_iter()
+                        .map(|entries| DuplicateGroup { entries, keep: 0, flagged: false })
+                        .collect();
+                    *audit = DuplicateAudit::Done(groups);
+                }
+                Ok(Err(e)) => *audit = DuplicateAudit::Failed(e.to_string()),
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(100)),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    *audit = DuplicateAudit::Failed("Duplicate search stopped".to_string());
+                }
+            }
+        }
+        
+        let mut open = true;
+        let mut flag = None;
+        egui::Window::new("🔍 Reused Passwords")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(450.0)
+            .show(ctx, |ui| match audit {
+                DuplicateAudit::Running(_) => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Comparing passwords...");
+                    });
+                }
+                DuplicateAudit::Failed(e) => {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("Search failed: {}", e));
+                }
+                DuplicateAudit::Done(groups) if groups.is_empty() => {
+                    ui.colored_label(egui::Color32::from_rgb(100, 255, 100), "✅ Every entry has its own password");
+                }
+                DuplicateAudit::Done(groups) => {
+                    ui.label(format!("{} password(s) are used by more than one entry.", groups.len()));
+                    ui.small(format!(
+                        "Pick the entry that keeps each password; the others are tagged \"{}\" so you can find and change them.",
+                        REUSED_PASSWORD_TAG
+                    ));
+                    ui.separator();
+                    egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                        for (index, group) in groups.iter_mut().enumerate() {
+                            ui.group(|ui| {
+                                ui.strong(format!("Shared by {} entries", group.entries.len()));
+                                ui.add_enabled_ui(!group.flagged, |ui| {
+                                    for (position, entry) in group.entries.iter().enumerate() {
+                                        ui.radio_value(
+                                            &mut group.keep,
+                                            position,
+                                            format!("{} ({})", entry.site, entry.username),
+                                        );
                                     }
+                                });
+                                if group.flagged {
+                                    ui.small("✅ Others flagged");
+                                } else if ui.button("Keep selected, flag others").clicked() {
+                                    flag = Some(index);
                                 }
                             });
-                    }
-                    
-                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        if ui.button("🔒 Lock").clicked() {
-                            self.lock_vault();
-                        }
-                        
-                        if ui.button("🎲 Generate").clicked() {
-                            self.show_generator = true;
-                            self.generate_password();
-                        }
-                        
-                        if ui.button("➕ Add Entry").clicked() {
-                            self.show_add_dialog = true;
-                            self.clear_entry_form();
                         }
                     });
-                });
+                }
+            });
+        
+        if let (Some(index), Some(DuplicateAudit::Done(groups))) = (flag, &mut self.duplicate_audit) {
+            let group = &groups[index];
+            let others: Vec<String> = group
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(position, _)| *position != group.keep)
+                .map(|(_, entry)| entry.id.clone())
+                .collect();
+            let storage_mutex = self.storage.clone();
+            let result = self.runtime.block_on(async {
+                let storage_guard = storage_mutex.lock().unwrap();
+                match storage_guard.as_ref() {
+                    Some(storage) => storage.flag_reused_passwords(&others).await,
+                    None => Err(pwgen_core::Error::VaultLocked),
+                }
+            });
+            match result {
+                Ok(_) => {
+                    groups[index].flagged = true;
+                    self.success_message =
+                        format!("Tagged {} entries \"{}\"", others.len(), REUSED_PASSWORD_TAG);
+                    self.load_entries();
+                }
+                Err(e) => self.error_message = format!("Failed to flag entries: {}", e),
             }
-            
-            ui.add_space(5.0);
+        }
+        if !open {
+            self.duplicate_audit = None;
+        }
+    }
+    
+    /// Look up every stored password with the Have I Been Pwned range API on
+    /// the tokio runtime. Only the first 5 characters of each SHA-1 hash are
+    /// sent.
+    #[cfg(feature = "breach-check")]
+    fn start_breach_audit(&mut self) {
+        let (sender, events) = mpsc::channel();
+        let entries = self.entries.clone();
+        self.breach_audit = Some(BreachAudit::Running { events, checked: 0, total: entries.len() });
+        
+        self.runtime.spawn(async move {
+            let progress_sender = sender.clone();
+            let result = BreachChecker::new()
+                .check_entries(&entries, |checked, total| {
+                    let _ = progress_sender.send(BreachEvent::Progress(checked, total));
+                })
+                .await
+                .map_err(|e| e.to_string());
+            let _ = sender.send(BreachEvent::Done(result));
         });
+    }
+    
+    #[cfg(feature = "breach-check")]
+    fn show_breach_audit_dialog(&mut self, ctx: &egui::Context) {
+        let Some(audit) = &mut self.breach_audit else {
+            return;
+        };
         
-        // Advanced search panel
-        if self.show_advanced_search {
-            egui::TopBottomPanel::top("advanced_search").show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    ui.label("🔍 Advanced Search & Filters");
+        if let BreachAudit::Running { events, checked, total } = audit {
+            let mut finished = None;
+            for event in events.try_iter() {
+                match event {
+                    BreachEvent::Progress(done, of) => (*checked, *total) = (done, of),
+                    BreachEvent::Done(result) => finished = Some(result),
+                }
+            }
+            match finished {
+                Some(result) => *audit = BreachAudit::Done(result),
+                None => ctx.request_repaint_after(Duration::from_millis(100)),
+            }
+        }
+        
+        let mut open = true;
+        let mut start = false;
+        let mut show_entry = None;
+        egui::Window::new("🛡 Breach Check")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(450.0)
+            .show(ctx, |ui| match &*audit {
+                BreachAudit::Confirm => {
+                    ui.label(format!(
+                        "Check the {} stored passwords against the Have I Been Pwned breach database?",
+                        self.entries.len()
+                    ));
+                    ui.small(
+                        "Only the first 5 characters of each password's SHA-1 hash are sent; \
+                         the match is made on this computer.",
+                    );
+                    ui.add_space(6.0);
+                    start = ui.button("🌐 Check Now").clicked();
+                }
+                BreachAudit::Running { checked, total, .. } => {
+                    ui.label(format!("Checked {} of {} passwords", checked, total));
+                    let fraction = if *total == 0 { 1.0 } else { *checked as f32 / *total as f32 };
+                    ui.add(egui::ProgressBar::new(fraction).show_percentage());
+                }
+                BreachAudit::Done(Err(e)) => {
+                    ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("Breach check failed: {}", e));
+                }
+                BreachAudit::Done(Ok(breached)) if breached.is_empty() => {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(100, 255, 100),
+                        "✅ None of your passwords appear in known breaches",
+                    );
+                }
+                BreachAudit::Done(Ok(breached)) => {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 100, 100),
+                        format!("⚠ {} password(s) appear in known breaches; change them", breached.len()),
+                    );
                     ui.separator();
-                    
-                    if self.is_compact_mode {
-                        // Compact mode: stack filter controls vertically
-                        ui.horizontal(|ui| {
-                            ui.label("Search in:");
-                            egui::ComboBox::from_id_salt("search_field")
-                                .selected_text(match self.search_field {
-                                    SearchField::All => "All Fields",
-                                    SearchField::Site => "Website",
-                                    SearchField::Username => "Username",
-                                    SearchField::Notes => "Notes",
-                                    SearchField::Tags => "Tags",
-                                })
-                                .width(140.0)
-                                .show_ui(ui, |ui| {
-                                    let mut changed = false;
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::All, "All Fields").changed();
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Site, "Website").changed();
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Username, "Username").changed();
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Notes, "Notes").changed();
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Tags, "Tags").changed();
-                                    if changed {
-                                        self.filter_entries();
-                                    }
-                                });
-                            
-                            if ui.checkbox(&mut self.filter_favorites, "⭐ Favorites").changed() {
-                                self.filter_entries();
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        egui::Grid::new("breached_entries").num_columns(3).striped(true).show(ui, |ui| {
+                            for entry in breached {
+                                ui.label(format!("{} ({})", entry.site, entry.username));
+                                ui.label(format!("seen {} times", entry.count));
+                                if ui.small_button("Show").clicked() {
+                                    show_entry = Some(entry.entry_id.clone());
+                                }
+                                ui.end_row();
                             }
-                            
-                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                if ui.small_button("Clear All").clicked() {
-                                    self.search_query.clear();
-                                    self.filter_tags.clear();
-                                    self.filter_favorites = false;
-                                    self.search_field = SearchField::All;
-                                    self.filter_entries();
-                                }
-                            });
-                        });
-                        
-                        ui.add_space(2.0);
-                        
-                        ui.horizontal(|ui| {
-                            ui.label("Tags:");
-                            let tag_response = ui.add(
-                                egui::TextEdit::singleline(&mut self.filter_tags)
-                                    .desired_width(ui.available_width() - 50.0)
-                                    .hint_text("Filter by tags (comma-separated)")
-                            );
-                            if tag_response.changed() {
-                                self.filter_entries();
-                            }
-                        });
-                    } else {
-                        // Wide mode: horizontal layout
-                        ui.horizontal(|ui| {
-                            // Search field selector
-                            ui.label("Search in:");
-                            egui::ComboBox::from_id_salt("search_field")
-                                .selected_text(match self.search_field {
-                                    SearchField::All => "All Fields",
-                                    SearchField::Site => "Website",
-                                    SearchField::Username => "Username",
-                                    SearchField::Notes => "Notes",
-                                    SearchField::Tags => "Tags",
-                                })
-                                .width(140.0)
-                                .show_ui(ui, |ui| {
-                                    let mut changed = false;
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::All, "All Fields").changed();
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Site, "Website").changed();
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Username, "Username").changed();
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Notes, "Notes").changed();
-                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Tags, "Tags").changed();
-                                    if changed {
-                                        self.filter_entries();
-                                    }
-                                });
-                            
-                            ui.separator();
-                            
-                            // Favorites filter
-                            if ui.checkbox(&mut self.filter_favorites, "⭐ Favorites only").changed() {
-                                self.filter_entries();
-                            }
-                            
-                            ui.separator();
-                            
-                            // Tag filter
-                            ui.label("Tags:");
-                            let tag_width = if self.window_width > 1400.0 { 200.0 } else { 150.0 };
-                            let tag_response = ui.add(
-                                egui::TextEdit::singleline(&mut self.filter_tags)
-                                    .desired_width(tag_width)
-                                    .hint_text("Filter by tags (comma-separated)")
-                            );
-                            if tag_response.changed() {
-                                self.filter_entries();
-                            }
-                            
-                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                if ui.small_button("Clear All").clicked() {
-                                    self.search_query.clear();
-                                    self.filter_tags.clear();
-                                    self.filter_favorites = false;
-                                    self.search_field = SearchField::All;
-                                    self.filter_entries();
-                                }
-                            });
                         });
-                    }
-                });
+                    });
+                }
+            });
+        
+        if start {
+            self.start_breach_audit();
+        }
+        if let Some(id) = show_entry {
+            self.current_tab = MainTab::Passwords;
+            self.selected_entry_id = Some(id);
+        }
+        // A running check finishes in the background and is dropped
+        if !open {
+            self.breach_audit = None;
+        }
+    }
+    
+    fn open_env_diff(&mut self) {
+        let filter = SecretFilter {
+            secret_types: Some(vec![SecretType::Configuration]),
+            sort_by: SecretSortField::Name,
+            sort_order: SortOrder::Ascending,
+            ..Default::default()
+        };
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        let result = self.runtime.block_on(async {
+            let secrets_storage_guard = secrets_storage_mutex.lock().unwrap();
+            match secrets_storage_guard.as_ref() {
+                Some(secrets_storage) => secrets_storage.search_secrets(&filter).await,
+                None => Ok(vec![]),
+            }
+        });
+        let configs = match result {
+            Ok(configs) => configs,
+            Err(e) => {
+                self.error_message = format!("Failed to load configurations: {}", e);
+                return;
+            }
+        };
+        if let [left, right, ..] = configs.as_slice() {
+            self.env_diff = Some(EnvDiffView {
+                left_id: left.id.clone(),
+                right_id: right.id.clone(),
+                configs,
+                reveal: false,
             });
         }
+    }
+    
+    fn show_env_diff_dialog(&mut self, ctx: &egui::Context) {
+        let Some(view) = &self.env_diff else {
+            return;
+        };
+        let configs = view.configs.clone();
+        let mut left_id = view.left_id.clone();
+        let mut right_id = view.right_id.clone();
+        let mut reveal = view.reveal;
+        let mut request_reveal = false;
+        let mut open = true;
         
-        // Status bar
-        if !self.error_message.is_empty() || !self.success_message.is_empty() {
-            egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+        let name_of = |id: &str| {
+            configs.iter().find(|c| c.id == id).map(|c| c.name.clone()).unwrap_or_default()
+        };
+        
+        egui::Window::new("Compare Environments")
+            .open(&mut open)
+            .resizable(true)
+            .default_width(650.0)
+            .show(ctx, |ui| {
                 ui.horizontal(|ui| {
-                    if !self.error_message.is_empty() {
-                        ui.colored_label(egui::Color32::from_rgb(255, 100, 100), &self.error_message);
-                        if ui.small_button("✖").clicked() {
-                            self.error_message.clear();
+                    for (salt, id) in [("env_diff_left", &mut left_id), ("env_diff_right", &mut right_id)] {
+                        egui::ComboBox::from_id_salt(salt)
+                            .selected_text(name_of(id))
+                            .show_ui(ui, |ui| {
+                                for config in &configs {
+                                    ui.selectable_value(id, config.id.clone(), &config.name);
+                                }
+                            });
+                        if salt == "env_diff_left" {
+                            ui.label("→");
                         }
                     }
-                    if !self.success_message.is_empty() {
-                        ui.colored_label(egui::Color32::from_rgb(100, 255, 100), &self.success_message);
-                        if ui.small_button("✖").clicked() {
-                            self.success_message.clear();
+                    let mut show = reveal;
+                    if ui.checkbox(&mut show, "Show values").changed() {
+                        if show {
+                            request_reveal = true;
+                        } else {
+                            reveal = false;
                         }
                     }
                 });
-            });
-        }
-        
-        // Footer with stats
-        egui::TopBottomPanel::bottom("footer").show(ctx, |ui| {
-            ui.horizontal(|ui| {
-                ui.label(format!("📊 {} total entries", self.entries.len()));
                 ui.separator();
                 
-                // Calculate total filtered entries before pagination
-                let total_filtered = if self.search_query.is_empty() {
-                    self.entries.len()
-                } else {
-                    let query = self.search_query.to_lowercase();
-                    self.entries.iter()
-                        .filter(|e| {
-                            e.site.to_lowercase().contains(&query) ||
-                            e.username.to_lowercase().contains(&query) ||
-                            e.notes.as_ref().map(|n| n.to_lowercase().contains(&query)).unwrap_or(false) ||
-                            e.tags.iter().any(|t| t.to_lowercase().contains(&query))
-                        })
-                        .count()
+                let left = configs.iter().find(|c| c.id == left_id);
+                let right = configs.iter().find(|c| c.id == right_id);
+                let (Some(left), Some(right)) = (left, right) else {
+                    ui.label("Choose two configurations to compare");
+                    return;
                 };
-                
-                if self.total_pages > 1 {
-                    let start_entry = self.current_page * self.entries_per_page + 1;
-                    let end_entry = std::cmp::min((self.current_page + 1) * self.entries_per_page, total_filtered);
-                    ui.label(format!("📄 Showing {}-{} of {} entries", start_entry, end_entry, total_filtered));
-                } else {
-                    ui.label(format!("🔍 {} shown", self.filtered_entries.len()));
-                }
-                
-                // Show active filters
-                let mut active_filters = Vec::new();
-                if !self.search_query.is_empty() {
-                    let field_name = match self.search_field {
-                        SearchField::All => "all",
-                        SearchField::Site => "site",
-                        SearchField::Username => "username", 
-                        SearchField::Notes => "notes",
-                        SearchField::Tags => "tags",
-                    };
-                    active_filters.push(format!("🔍 {} '{}'", field_name, self.search_query));
-                }
-                if self.filter_favorites {
-                    active_filters.push("⭐ favorites".to_string());
-                }
-                if !self.filter_tags.is_empty() {
-                    active_filters.push(format!("🏷 tags '{}'", self.filter_tags));
-                }
-                
-                if !active_filters.is_empty() && !self.is_compact_mode {
-                    ui.separator();
-                    ui.label("Filters:");
-                    for filter in active_filters {
-                        ui.small(&filter);
-                    }
-                } else if !active_filters.is_empty() && self.is_compact_mode {
-                    // In compact mode, only show filter count
-                    ui.separator();
-                    ui.small(format!("🎯 {} filters active", active_filters.len()));
-                }
-                
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    // Show security status with color-coded shield
-                    let vault_secure = self.storage.lock().unwrap().is_some();
-                    if vault_secure {
-                        ui.colored_label(
-                            egui::Color32::from_rgb(50, 200, 50), 
-                            "🛡 Vault Secure"
-                        );
-                    } else {
-                        ui.colored_label(
-                            egui::Color32::from_rgb(200, 50, 50), 
-                            "🛡 Vault Locked"
-                        );
-                    }
-                });
-            });
-        });
-        
-        // Tab navigation
-        egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
-            ui.add_space(5.0);
-            ui.horizontal(|ui| {
-                ui.spacing_mut().item_spacing.x = 8.0;
-                
-                // Tab buttons with icons and styling
-                let mut tab_button = |ui: &mut egui::Ui, tab: MainTab, icon: &str, text: &str| {
-                    let is_selected = self.current_tab == tab;
-                    let text_color = if is_selected {
-                        ui.visuals().strong_text_color()
-                    } else {
-                        ui.visuals().text_color()
-                    };
-                    
-                    let label = egui::RichText::new(format!("{} {}", icon, text)).color(text_color);
-                    let button_style = if is_selected {
-                        egui::Button::new(label)
-                            .fill(ui.visuals().selection.bg_fill)
-                            .stroke(egui::Stroke::new(1.0, ui.visuals().selection.stroke.color))
-                    } else {
-                        egui::Button::new(label)
-                            .fill(egui::Color32::TRANSPARENT)
-                    };
-                    
-                    if ui.add(button_style).clicked() {
-                        self.current_tab = tab;
+                let diff = match EnvConnectionManager::diff_environment_sets(left, right) {
+                    Ok(diff) => diff,
+                    Err(e) => {
+                        ui.colored_label(egui::Color32::from_rgb(255, 100, 100), e.to_string());
+                        return;
                     }
                 };
                 
-                tab_button(ui, MainTab::Passwords, "🔑", "Passwords");
-                tab_button(ui, MainTab::Secrets, "🔐", "Secrets");
-                tab_button(ui, MainTab::Generator, "🎲", "Generator");
-                tab_button(ui, MainTab::Tools, "🛠", "Tools");
-                tab_button(ui, MainTab::Settings, "⚙", "Settings");
+                let count = |kind| diff.iter().filter(|d| d.kind == kind).count();
+                ui.label(format!(
+                    "{} only in {}, {} missing from {}, {} changed, {} the same",
+                    count(EnvDiffKind::Added),
+                    right.name,
+                    count(EnvDiffKind::Removed),
+                    right.name,
+                    count(EnvDiffKind::Changed),
+                    count(EnvDiffKind::Unchanged)
+                ));
                 
-                // Add entry count badges
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    match self.current_tab {
-                        MainTab::Passwords => {
-                            ui.label(
-                                egui::RichText::new(format!("({} entries)", self.entries.len()))
-                                    .size(11.0)
-                                    .color(ui.visuals().weak_text_color())
-                            );
-                        }
-                        MainTab::Secrets => {
-                            ui.label(
-                                egui::RichText::new(format!("({} secrets)", self.secrets.len()))
-                                    .size(11.0)
-                                    .color(ui.visuals().weak_text_color())
-                            );
+                let cell = |value: &Option<String>| match value {
+                    Some(value) if reveal => value.clone(),
+                    Some(_) => "••••••••".to_string(),
+                    None => "—".to_string(),
+                };
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("env_diff_grid").num_columns(3).striped(true).show(ui, |ui| {
+                        ui.strong("Variable");
+                        ui.strong(&left.name);
+                        ui.strong(&right.name);
+                        ui.end_row();
+                        
+                        for var in &diff {
+                            let (marker, color) = match var.kind {
+                                EnvDiffKind::Added => ("+", egui::Color32::from_rgb(100, 255, 100)),
+                                EnvDiffKind::Removed => ("-", egui::Color32::from_rgb(255, 100, 100)),
+                                EnvDiffKind::Changed => ("~", egui::Color32::from_rgb(255, 200, 80)),
+                                EnvDiffKind::Unchanged => (" ", ui.visuals().weak_text_color()),
+                            };
+                            ui.colored_label(color, egui::RichText::new(format!("{} {}", marker, var.name)).monospace());
+                            ui.colored_label(color, egui::RichText::new(cell(&var.left)).monospace());
+                            ui.colored_label(color, egui::RichText::new(cell(&var.right)).monospace());
+                            ui.end_row();
                         }
-                        _ => {}
-                    }
+                    });
                 });
             });
-            ui.add_space(5.0);
-        });
         
-        // Main content area
-        egui::CentralPanel::default().show(ctx, |ui| {
-            match self.current_tab {
-                MainTab::Passwords => self.show_passwords_tab(ui),
-                MainTab::Secrets => self.show_secrets_tab(ui),
-                MainTab::Generator => self.show_generator_tab(ui),
-                MainTab::Tools => self.show_tools_tab(ui),
-                MainTab::Settings => self.show_settings_tab(ui),
+        if !open {
+            self.env_diff = None;
+            return;
+        }
+        if request_reveal {
+            let compared: Vec<_> = configs.iter().filter(|c| c.id == left_id || c.id == right_id).collect();
+            let tags: Vec<String> = compared.iter().flat_map(|c| c.tags.clone()).collect();
+            let reauth = compared.iter().any(|c| c.metadata.require_reauth);
+            if reauth || requires_reveal_confirmation(&tags, self.gui_config.confirm_copy_reveal) {
+                self.pending_reveal = Some(PendingReveal {
+                    what: "Environment values".to_string(),
+                    action: RevealAction::EnvDiffValues,
+                    reauth,
+                });
+            } else {
+                reveal = true;
             }
+        }
+        self.env_diff = Some(EnvDiffView { configs, left_id, right_id, reveal });
+    }
+    
+    fn open_env_export(&mut self, secret: DecryptedSecretEntry) {
+        let as_env = config_format_extension(&secret.data).is_none();
+        self.env_export = Some(EnvExport {
+            secret,
+            as_env,
+            options: EnvFileOptions::default(),
         });
-        
-        // Show dialogs
-        self.show_entry_dialog(ctx);
-        self.show_generator_dialog(ctx);
-        self.show_settings_dialog(ctx);
-        self.show_about_dialog(ctx);
-        self.show_import_dialog(ctx);
-        self.show_backup_dialog(ctx);
-        self.show_statistics_dialog(ctx);
-        self.show_tag_edit_dialog(ctx);
-        self.show_secrets_view(ctx);
-        self.show_add_secret_dialog(ctx);
     }
     
-    fn show_passwords_tab(&mut self, ui: &mut egui::Ui) {
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            ui.spacing_mut().item_spacing.y = 2.0;
-            
-            let entries_to_display = self.filtered_entries.clone();
-            for entry in entries_to_display {
-                let is_selected = self.selected_entry_id.as_ref() == Some(&entry.id);
-                
-                ui.push_id(&entry.id, |ui| {
-                    let response = ui.add(
-                        egui::SelectableLabel::new(is_selected, "")
-                    );
-                    
-                    if response.clicked() {
-                        self.selected_entry_id = Some(entry.id.clone());
-                    }
-                    
-                    // Clone values for context menu
-                    let entry_id = entry.id.clone();
-                    let entry_username = entry.username.clone();
-                    let entry_password = entry.password.clone();
-                    let entry_for_edit = entry.clone();
-                    
-                    response.context_menu(|ui| {
-                        ui.set_min_width(150.0);
-                        if ui.button("📋 Copy Username").clicked() {
-                            self.copy_to_clipboard(&entry_username);
-                            self.success_message = "Username copied!".to_string();
-                            ui.close_menu();
-                        }
-                        if ui.button("🔑 Copy Password").clicked() {
-                            self.copy_to_clipboard(&entry_password);
-                            self.success_message = "Password copied!".to_string();
-                            ui.close_menu();
-                        }
-                        ui.separator();
-                        if ui.button("✏ Edit").clicked() {
-                            self.edit_entry = Some(entry_for_edit.clone());
-                            self.entry_site = entry_for_edit.site.clone();
-                            self.entry_username = entry_for_edit.username.clone();
-                            self.entry_password = entry_for_edit.password.clone();
-                            self.entry_notes = entry_for_edit.notes.clone().unwrap_or_default();
-                            self.entry_tags = entry_for_edit.tags.join(", ");
-                            self.show_add_dialog = true;
-                            ui.close_menu();
-                        }
-                        if ui.button("🗑 Delete").clicked() {
-                            self.delete_entry(&entry_id);
-                            ui.close_menu();
-                        }
+    fn show_env_export_dialog(&mut self, ctx: &egui::Context) {
+        let Some(export) = &mut self.env_export else {
+            return;
+        };
+        let SecretData::Configuration { variables, .. } = &export.secret.data else {
+            self.env_export = None;
+            return;
+        };
+        let original_extension = config_format_extension(&export.secret.data);
+        let mut open = true;
+        let mut save = false;
+        let mut copy = false;
+        
+        egui::Window::new(format!("Export {}", export.secret.name))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("{} variables", variables.len()));
+                if let Some(extension) = original_extension {
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut export.as_env, false, format!("Original format (.{})", extension));
+                        ui.radio_value(&mut export.as_env, true, ".env");
                     });
-                    
-                    // Entry row layout - responsive
-                    if self.is_compact_mode {
-                        // Compact mode: stack info vertically with buttons on right
-                        ui.horizontal(|ui| {
-                            ui.group(|ui| {
-                                ui.set_min_width(ui.available_width() - 80.0);
-                                ui.vertical(|ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.strong(&entry.site);
-                                        if entry.favorite {
-                                            ui.label("⭐");
-                                        }
-                                    });
-                                    ui.small(&entry.username);
-                                    if !entry.tags.is_empty() && entry.tags.len() <= 2 {
-                                        ui.horizontal(|ui| {
-                                            for tag in entry.tags.iter().take(2) {
-                                                ui.small(format!("🏷{}", tag));
-                                            }
-                                            if entry.tags.len() > 2 {
-                                                ui.small("...");
-                                            }
-                                            if ui.small_button("✏").on_hover_text("Edit tags").clicked() {
-                                                self.editing_tags_for_entry = Some(entry.id.clone());
-                                                self.temp_tags = entry.tags.join(", ");
-                                            }
-                                        });
-                                    } else if entry.tags.is_empty() {
-                                        ui.horizontal(|ui| {
-                                            ui.small("🏷No tags");
-                                            if ui.small_button("✏").on_hover_text("Add tags").clicked() {
-                                                self.editing_tags_for_entry = Some(entry.id.clone());
-                                                self.temp_tags = String::new();
-                                            }
-                                        });
-                                    }
-                                });
-                            });
-                            
-                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                if ui.small_button("🔑").on_hover_text("Copy password").clicked() {
-                                    self.copy_to_clipboard(&entry.password);
-                                    self.success_message = "Password copied!".to_string();
-                                }
-                                if ui.small_button("👤").on_hover_text("Copy username").clicked() {
-                                    self.copy_to_clipboard(&entry.username);
-                                    self.success_message = "Username copied!".to_string();
-                                }
-                            });
-                        });
-                    } else {
-                        // Wide mode: full horizontal layout
-                        ui.horizontal(|ui| {
-                            ui.group(|ui| {
-                                ui.set_min_width(ui.available_width() - 120.0);
-                                ui.vertical(|ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.strong(&entry.site);
-                                        if entry.favorite {
-                                            ui.label("⭐");
-                                        }
-                                    });
-                                    ui.label(&entry.username);
-                                    if !entry.tags.is_empty() {
-                                        ui.horizontal(|ui| {
-                                            for tag in &entry.tags {
-                                                ui.small(format!("🏷{}", tag));
-                                            }
-                                            if ui.small_button("✏").on_hover_text("Edit tags").clicked() {
-                                                self.editing_tags_for_entry = Some(entry.id.clone());
-                                                self.temp_tags = entry.tags.join(", ");
-                                            }
-                                        });
-                                    } else {
-                                        ui.horizontal(|ui| {
-                                            ui.small("🏷No tags");
-                                            if ui.small_button("✏").on_hover_text("Add tags").clicked() {
-                                                self.editing_tags_for_entry = Some(entry.id.clone());
-                                                self.temp_tags = String::new();
-                                            }
-                                        });
-                                    }
-                                });
-                            });
-                            
-                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                if ui.small_button("🔑").on_hover_text("Copy password").clicked() {
-                                    self.copy_to_clipboard(&entry.password);
-                                    self.success_message = "Password copied!".to_string();
-                                }
-                                if ui.small_button("👤").on_hover_text("Copy username").clicked() {
-                                    self.copy_to_clipboard(&entry.username);
-                                    self.success_message = "Username copied!".to_string();
-                                }
-                            });
-                        });
+                }
+                ui.add_enabled_ui(export.as_env, |ui| {
+                    ui.checkbox(&mut export.options.quote, "Quote values containing spaces or special characters");
+                    ui.checkbox(&mut export.options.export, "Prefix lines with export")
+                        .on_hover_text("For sourcing the file from a shell");
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("💾 Save As...").clicked() {
+                        save = true;
+                    }
+                    if ui.button("📋 Copy all as .env").clicked() {
+                        copy = true;
                     }
                 });
+            });
+        
+        let env_contents = EnvConnectionManager::env_file_contents(variables, export.options);
+        if copy {
+            let secret = export.secret.clone();
+            self.copy_sensitive(
+                &env_contents,
+                format!("{} as .env", secret.name),
+                &secret.tags,
+                secret.metadata.require_reauth,
+            );
+        }
+        if save {
+            self.save_env_export(env_contents);
+        }
+        if !open {
+            self.env_export = None;
+        }
+    }
+    
+    /// Ask where to save the configuration being exported and write it
+    /// there, readable only by the owner.
+    fn save_env_export(&mut self, env_contents: String) {
+        let Some(export) = &self.env_export else {
+            return;
+        };
+        let (file_name, contents) = match config_format_extension(&export.secret.data) {
+            Some(extension) if !export.as_env => {
+                match NotesConfigManager::export_config_to_string(&export.secret, None) {
+                    Ok(contents) => (format!("{}.{}", export.secret.name, extension), contents),
+                    Err(e) => {
+                        self.error_message = format!("Failed to export configuration: {}", e);
+                        return;
+                    }
+                }
+            }
+            _ => (".env".to_string(), env_contents),
+        };
+        let Some(path) = rfd::FileDialog::new().set_file_name(&file_name).save_file() else {
+            return;
+        };
+        match write_private_file(&path, contents.as_bytes()) {
+            Ok(()) => {
+                self.error_message.clear();
+                self.success_message = format!("Exported {} to {}", export.secret.name, path.display());
             }
+            Err(e) => self.error_message = format!("Failed to export configuration: {}", e),
+        }
+    }
+    
+    /// Open a document in the preview window, asking first when the tags,
+    /// settings or the secret require it.
+    fn view_document(&mut self, secret: DecryptedSecretEntry) {
+        let reauth = secret.metadata.require_reauth;
+        if reauth || requires_reveal_confirmation(&secret.tags, self.gui_config.confirm_copy_reveal) {
+            self.pending_reveal = Some(PendingReveal {
+                what: format!("Document {}", secret.name),
+                action: RevealAction::ViewDocument(secret.id.clone()),
+                reauth,
+            });
+        } else {
+            self.open_document_view(secret);
+        }
+    }
+    
+    /// Show the preview window and prepare the preview on a worker thread, so
+    /// large documents don't stall the UI. Only the scaled preview of an
+    /// image is uploaded as a texture.
+    fn open_document_view(&mut self, secret: DecryptedSecretEntry) {
+        let data = secret.data.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(prepare_document(&data));
+        });
+        self.document_view = Some(DocumentView {
+            secret,
+            job: Some(receiver),
+            intact: None,
+            preview: DocumentPreview::Loading,
         });
     }
     
-    fn show_secrets_tab(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.heading("🔐 Secrets Manager");
-            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                if ui.button("➕ Add Secret").clicked() {
-                    self.show_add_secret_dialog = true;
-                    self.selected_secret_type = SecretType::ApiKey;
+    fn show_document_view(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.document_view else {
+            return;
+        };
+        if let Some(job) = &view.job {
+            match job.try_recv() {
+                Ok(Ok(prepared)) => {
+                    view.intact = Some(prepared.intact);
+                    view.preview = match prepared.content {
+                        PreparedContent::Text { text, truncated } => DocumentPreview::Text { text, truncated },
+                        PreparedContent::Image { image, size } => DocumentPreview::Image {
+                            texture: ctx.load_texture(
+                                format!("document-{}", view.secret.id),
+                                image,
+                                egui::TextureOptions::LINEAR,
+                            ),
+                            size,
+                        },
+                        PreparedContent::Other => DocumentPreview::Unsupported(format!(
+                            "{}, {} bytes",
+                            prepared.content_type, prepared.size
+                        )),
+                    };
+                    view.job = None;
+                }
+                Ok(Err(e)) => {
+                    view.preview = DocumentPreview::Failed(e);
+                    view.job = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(100)),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    view.preview = DocumentPreview::Failed("The preview could not be prepared".to_string());
+                    view.job = None;
+                }
+            }
+        }
+        
+        let filename = match &view.secret.data {
+            SecretData::Document { filename, .. } => filename.clone(),
+            _ => String::new(),
+        };
+        let mut open = true;
+        let mut save = false;
+        egui::Window::new(format!("📄 {}", view.secret.name))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(&filename);
+                    if ui.button("💾 Save to disk").clicked() {
+                        save = true;
+                    }
+                });
+                if view.intact == Some(false) {
+                    ui.colored_label(
+                        error_color(ui),
+                        "⚠ Checksum mismatch: the content no longer matches what was stored and may be damaged",
+                    );
+                }
+                ui.separator();
+                match &view.preview {
+                    DocumentPreview::Loading => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Preparing preview...");
+                        });
+                    }
+                    DocumentPreview::Text { text, truncated } => {
+                        if *truncated {
+                            ui.small(format!(
+                                "Showing the first {} KiB; save the document to see all of it",
+                                DOCUMENT_TEXT_PREVIEW_LIMIT / 1024
+                            ));
+                        }
+                        egui::ScrollArea::both().show(ui, |ui| {
+                            ui.add(egui::Label::new(egui::RichText::new(text).monospace()).selectable(true));
+                        });
+                    }
+                    DocumentPreview::Image { texture, size } => {
+                        ui.small(format!("{} × {} pixels", size[0], size[1]));
+                        egui::ScrollArea::both().show(ui, |ui| {
+                            let available = ui.available_size();
+                            let texture_size = texture.size_vec2();
+                            let scale = (available.x / texture_size.x).clamp(0.1, 1.0);
+                            ui.image((texture.id(), texture_size * scale));
+                        });
+                    }
+                    DocumentPreview::Unsupported(description) => {
+                        ui.label(format!("No preview for this type of document ({})", description));
+                    }
+                    DocumentPreview::Failed(e) => {
+                        ui.colored_label(error_color(ui), format!("Could not preview the document: {}", e));
+                    }
                 }
             });
-        });
         
-        ui.separator();
+        if save {
+            self.save_document(&filename);
+        }
+        if !open {
+            self.document_view = None;
+        }
+    }
+    
+    fn save_document(&mut self, filename: &str) {
+        let Some(view) = &self.document_view else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new().set_file_name(filename).save_file() else {
+            return;
+        };
+        let result = DocumentManager::read_content(&view.secret.data)
+            .map_err(|e| e.to_string())
+            .and_then(|content| write_private_file(&path, &content).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => {
+                self.error_message.clear();
+                self.success_message = format!("Saved {} to {}", view.secret.name, path.display());
+            }
+            Err(e) => self.error_message = format!("Failed to save document: {}", e),
+        }
+    }
+    
+    fn show_integrity_dialog(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.integrity_report else {
+            return;
+        };
         
-        // Secrets filter tabs
-        ui.horizontal(|ui| {
-            let mut secret_tab_button = |ui: &mut egui::Ui, secret_type: SecretType, icon: &str, text: &str| {
-                let is_selected = self.current_secret_tab == secret_type;
-                let button_style = if is_selected {
-                    egui::Button::new(format!("{} {}", icon, text))
-                        .fill(ui.visuals().selection.bg_fill)
+        let mut open = true;
+        egui::Window::new("🩺 Vault Integrity")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Checked {} password entries and {} secrets",
+                    report.entries_checked, report.secrets_checked
+                ));
+                ui.separator();
+                if report.is_ok() {
+                    ui.colored_label(egui::Color32::from_rgb(100, 255, 100), "✅ Every item decrypts and validates");
                 } else {
-                    egui::Button::new(format!("{} {}", icon, text))
-                        .fill(egui::Color32::TRANSPARENT)
-                };
-                
-                if ui.add(button_style).clicked() {
-                    self.current_secret_tab = secret_type;
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 100, 100),
+                        format!("❌ {} item(s) failed verification", report.issues.len()),
+                    );
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for issue in &report.issues {
+                            let kind = match issue.kind {
+                                ItemKind::PasswordEntry => "Entry",
+                                ItemKind::Secret => "Secret",
+                            };
+                            ui.label(format!("{} \"{}\": {}", kind, issue.name, issue.problem))
+                                .on_hover_text(format!("ID: {}", issue.id));
+                        }
+                    });
                 }
-            };
-            
-            secret_tab_button(ui, SecretType::ApiKey, "🔑", "API Keys");
-            secret_tab_button(ui, SecretType::SshKey, "🔐", "SSH Keys");
-            secret_tab_button(ui, SecretType::Document, "📄", "Documents");
-            secret_tab_button(ui, SecretType::Configuration, "⚙", "Config");
-            secret_tab_button(ui, SecretType::SecureNote, "📝", "Notes");
-            secret_tab_button(ui, SecretType::ConnectionString, "🗄", "Database");
-        });
+            });
         
-        ui.separator();
+        if !open {
+            self.integrity_report = None;
+        }
+    }
+    
+    fn delete_secret(&mut self, secret_id: &str) {
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        let runtime = self.runtime.clone();
+        let id_string = secret_id.to_string();
         
-        // Secrets list
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            ui.spacing_mut().item_spacing.y = 4.0;
-            
-            let filtered_secrets: Vec<_> = self.filtered_secrets.iter()
-                .filter(|secret| secret.secret_type == self.current_secret_tab)
-                .cloned()
-                .collect();
-            
-            if filtered_secrets.is_empty() {
-                ui.vertical_centered(|ui| {
-                    ui.add_space(50.0);
-                    ui.label(format!("📭 No {} found", match self.current_secret_tab {
-                        SecretType::ApiKey => "API keys",
-                        SecretType::SshKey => "SSH keys", 
-                        SecretType::Document => "documents",
-                        SecretType::Configuration => "configurations",
-                        SecretType::SecureNote => "secure notes",
-                        SecretType::ConnectionString => "database connections",
-                        _ => "secrets",
-                    }));
-                    ui.small("Click 'Add Secret' to create your first secret");
-                });
+        let result = runtime.block_on(async {
+            let secrets_storage_guard = secrets_storage_mutex.lock().unwrap();
+            if let Some(secrets_storage) = secrets_storage_guard.as_ref() {
+                secrets_storage.delete_secret(&id_string).await
             } else {
-                let mut action = None;
+                Err(pwgen_core::Error::Other("Secrets storage not available".to_string()))
+            }
+        });
+        
+        match result {
+            Ok(_) => {
+                self.success_message = "Secret moved to the trash".to_string();
+                self.load_secrets(); // Reload secrets list
+            }
+            Err(e) => {
+                self.error_message = format!("Failed to delete secret: {}", e);
+            }
+        }
+    }
+}
+
+impl eframe::App for PwGenApp {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Update window dimensions for responsive layout
+        let screen_rect = ctx.screen_rect();
+        self.window_width = screen_rect.width();
+        self.is_compact_mode = self.window_width < 1000.0;
+        
+        let theme = self.gui_config.theme.preference();
+        if ctx.options(|options| options.theme_preference) != theme {
+            ctx.set_theme(theme);
+        }
+        
+        // Expire the large reveal overlay, and wake up in time to do so
+        if let Some(reveal) = &self.large_reveal {
+            let now = Instant::now();
+            if now >= reveal.expires_at {
+                self.large_reveal = None;
+            } else {
+                ctx.request_repaint_after((reveal.expires_at - now).min(Duration::from_secs(1)));
+            }
+        }
+        
+        self.check_auto_lock(ctx);
+        self.check_focus(ctx);
+        // After the auto-lock check, so "lock on minimize" still applies
+        // when minimizing hides the window to the tray
+        #[cfg(feature = "tray")]
+        handle_tray_event(self, ctx);
+        self.poll_search(ctx);
+        
+        match self.screen {
+            Screen::Login => self.show_login_screen(ctx),
+            Screen::Main => self.show_main_screen(ctx, frame),
+        }
+    }
+}
+
+impl PwGenApp {
+    fn show_login_screen(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(40.0);
                 
-                for secret in &filtered_secrets {
-                    ui.group(|ui| {
+                // Display wide logo prominently
+                if let Some(logo) = &self.logo_wide {
+                    ui.add(
+                        egui::Image::from_texture(logo)
+                            .fit_to_exact_size(egui::vec2(200.0, 67.0))
+                            .bg_fill(logo_background(ui))
+                            .rounding(egui::Rounding::same(8.0))
+                    );
+                    ui.add_space(10.0);
+                    ui.label(
+                        egui::RichText::new("Password Manager")
+                            .size(16.0)
+                            .color(ui.visuals().weak_text_color())
+                    );
+                } else {
+                    // Fallback if logo doesn't load
+                    ui.heading(
+                        egui::RichText::new("🦀 PwGen")
+                            .size(32.0)
+                            .color(ui.visuals().strong_text_color())
+                    );
+                    ui.label(
+                        egui::RichText::new("Password Manager")
+                            .size(16.0)
+                            .color(ui.visuals().weak_text_color())
+                    );
+                }
+                
+                ui.add_space(40.0);
+                
+                let vault_exists = self.get_vault_path()
+                    .map(|p| p.exists())
+                    .unwrap_or(false);
+                
+                self.show_vault_selector(ui);
+                ui.add_space(10.0);
+                
+                ui.group(|ui| {
+                    ui.set_min_width(350.0);
+                    ui.vertical_centered(|ui| {
+                        if vault_exists {
+                            ui.heading("Welcome Back");
+                            ui.label("Enter your master password to unlock the vault");
+                        } else {
+                            ui.heading("Create Your Vault");
+                            ui.label("Set a strong master password to protect your data");
+                        }
+                        
+                        ui.add_space(20.0);
+                        
                         ui.horizontal(|ui| {
-                            ui.vertical(|ui| {
-                                ui.strong(&secret.name);
-                                if let Some(desc) = &secret.description {
-                                    ui.small(desc);
+                            ui.label("Master Password:");
+                            let response = ui.add(
+                                egui::TextEdit::singleline(&mut self.master_password)
+                                    .password(true)
+                                    .desired_width(200.0)
+                                    .hint_text("Enter password...")
+                                    .font(egui::TextStyle::Monospace)
+                            );
+                            
+                            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                if vault_exists {
+                                    self.unlock_vault();
+                                } else if !self.master_password_confirm.is_empty() {
+                                    self.init_vault();
                                 }
-                                if !secret.tags.is_empty() {
-                                    ui.horizontal(|ui| {
-                                        for tag in secret.tags.iter().take(3) {
-                                            ui.small(format!("🏷{}", tag));
-                                        }
-                                        if secret.tags.len() > 3 {
-                                            ui.small("...");
-                                        }
-                                    });
+                            }
+                        });
+                        
+                        if !vault_exists {
+                            ui.horizontal(|ui| {
+                                ui.label("Confirm Password:");
+                                let response = ui.add(
+                                    egui::TextEdit::singleline(&mut self.master_password_confirm)
+                                        .password(true)
+                                        .desired_width(200.0)
+                                        .hint_text("Confirm password...")
+                                        .font(egui::TextStyle::Monospace)
+                                );
+                                
+                                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                    self.init_vault();
                                 }
                             });
-                            
-                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                if ui.small_button("📋").on_hover_text("Copy").clicked() {
-                                    action = Some(("copy", secret.clone()));
+                        }
+                        
+                        ui.horizontal(|ui| {
+                            ui.label("Key File:");
+                            match &self.key_file_path {
+                                Some(path) => {
+                                    ui.label(path.file_name().unwrap_or_default().to_string_lossy())
+                                        .on_hover_text(path.display().to_string());
                                 }
-                                if ui.small_button("✏").on_hover_text("Edit").clicked() {
-                                    action = Some(("edit", secret.clone()));
+                                None => {
+                                    ui.weak("None (optional)");
                                 }
-                                if ui.small_button("🗑").on_hover_text("Delete").clicked() {
-                                    action = Some(("delete", secret.clone()));
+                            }
+                            if ui.button("📁 Choose...").clicked() {
+                                if let Some(path) = rfd::FileDialog::new().pick_file() {
+                                    self.key_file_path = Some(path);
                                 }
-                            });
+                            }
+                            if !vault_exists && ui.button("🎲 New...").on_hover_text("Generate a random key file").clicked() {
+                                if let Some(path) = rfd::FileDialog::new().set_file_name("pwgen.key").save_file() {
+                                    match KeyFile::generate(&path) {
+                                        Ok(_) => self.key_file_path = Some(path),
+                                        Err(e) => self.error_message = format!("Failed to create key file: {}", e),
+                                    }
+                                }
+                            }
+                            if self.key_file_path.is_some() && ui.button("✖").on_hover_text("Don't use a key file").clicked() {
+                                self.key_file_path = None;
+                            }
                         });
+                        
+                        if !vault_exists {
+                            ui.checkbox(&mut self.create_recovery_key, "Create a recovery key")
+                                .on_hover_text("24 words, shown once, that unlock the vault if the master password is forgotten");
+                        }
+                        
+                        ui.add_space(20.0);
+                        
+                        if vault_exists {
+                            if ui.button("🔓 Unlock Vault").clicked() {
+                                self.unlock_vault();
+                            }
+                        } else {
+                            if ui.button("🔐 Create Vault").clicked() {
+                                self.init_vault();
+                            }
+                            
+                            ui.add_space(10.0);
+                            if self.create_recovery_key {
+                                ui.label("⚠ Keep the recovery key safe: anyone with it can open the vault.");
+                            } else {
+                                ui.label("⚠ Remember: Your master password cannot be recovered!");
+                            }
+                            if self.key_file_path.is_some() {
+                                ui.label("⚠ Keep the key file safe: the vault can't be opened without it.");
+                            }
+                        }
                     });
-                    ui.add_space(2.0);
+                });
+                
+                if !self.error_message.is_empty() {
+                    ui.add_space(10.0);
+                    ui.colored_label(error_color(ui), &self.error_message);
                 }
                 
-                // Handle actions outside the loop to avoid borrowing issues
-                if let Some((action_type, secret)) = action {
-                    match action_type {
-                        "copy" => self.copy_secret_data(&secret),
-                        "edit" => self.success_message = "Edit not yet implemented".to_string(),
-                        "delete" => self.delete_secret(&secret.id),
-                        _ => {}
+                if !self.success_message.is_empty() {
+                    ui.add_space(10.0);
+                    ui.colored_label(success_color(ui), &self.success_message);
+                }
+                
+                if vault_exists {
+                    ui.add_space(30.0);
+                    if ui.small_button("🛟 Unlock with recovery key...").clicked() {
+                        self.recovery_unlock = Some(RecoveryUnlock::default());
+                    }
+                    if ui.small_button("Forgot your master password? Reset vault...").clicked() {
+                        self.vault_reset = Some(VaultReset {
+                            step: ResetStep::Backup,
+                            typed_confirmation: String::new(),
+                            no_recovery_need: false,
+                            backup_path: None,
+                        });
                     }
                 }
-            }
+            });
         });
+        
+        self.show_vault_reset_dialog(ctx);
+        self.show_recovery_unlock_dialog(ctx);
     }
     
-    fn show_generator_tab(&mut self, ui: &mut egui::Ui) {
-        ui.heading("🎲 Password Generator");
-        ui.separator();
+    /// Which vault the login screen unlocks: the recent vaults, plus buttons
+    /// to open another file or create a new one.
+    fn show_vault_selector(&mut self, ui: &mut egui::Ui) {
+        let mut choices = self.recent_vaults.clone();
+        if let Some(default) = default_vault_path() {
+            if !choices.contains(&default) {
+                choices.push(default);
+            }
+        }
+        if let Some(current) = &self.vault_path {
+            if !choices.contains(current) {
+                choices.insert(0, current.clone());
+            }
+        }
         
+        let mut chosen = None;
         ui.horizontal(|ui| {
-            ui.vertical(|ui| {
-                ui.set_width(250.0);
-                
-                ui.group(|ui| {
-                    ui.label("🔧 Settings");
-                    
-                    egui::Grid::new("generator_settings").num_columns(2).show(ui, |ui| {
-                        ui.label("Length:");
-                        ui.add(egui::Slider::new(&mut self.gen_length, 4..=128));
-                        ui.end_row();
-                        
-                        ui.label("Uppercase:");
-                        ui.checkbox(&mut self.gen_uppercase, "A-Z");
-                        ui.end_row();
-                        
-                        ui.label("Lowercase:");
-                        ui.checkbox(&mut self.gen_lowercase, "a-z");
-                        ui.end_row();
-                        
-                        ui.label("Numbers:");
-                        ui.checkbox(&mut self.gen_numbers, "0-9");
-                        ui.end_row();
-                        
-                        ui.label("Symbols:");
-                        ui.checkbox(&mut self.gen_symbols, "!@#$");
-                        ui.end_row();
-                        
-                        ui.label("Exclude ambiguous:");
-                        ui.checkbox(&mut self.gen_exclude_ambiguous, "0O1lI");
-                        ui.end_row();
-                    });
-                    
-                    ui.separator();
-                    
-                    if ui.button("🎲 Generate New Password").clicked() {
-                        self.generate_password();
+            ui.label("Vault:");
+            let selected = self.vault_path.as_deref().map(vault_label).unwrap_or_default();
+            egui::ComboBox::from_id_salt("vault_selector")
+                .selected_text(selected)
+                .width(220.0)
+                .show_ui(ui, |ui| {
+                    for path in &choices {
+                        let current = Some(path) == self.vault_path.as_ref();
+                        if ui.selectable_label(current, vault_label(path))
+                            .on_hover_text(path.display().to_string())
+                            .clicked()
+                            && !current
+                        {
+                            chosen = Some(path.clone());
+                        }
                     }
                 });
-            });
-            
-            ui.separator();
-            
-            ui.vertical(|ui| {
-                ui.label("🔑 Generated Password:");
-                ui.add_space(5.0);
+            if ui.button("📂").on_hover_text("Open another vault file").clicked() {
+                self.choose_vault_file(false);
+            }
+            if ui.button("🆕").on_hover_text("Create a new vault file").clicked() {
+                self.choose_vault_file(true);
+            }
+        });
+        if let Some(path) = chosen {
+            self.switch_vault(path);
+        }
+    }
+    
+    fn show_vault_reset_dialog(&mut self, ctx: &egui::Context) {
+        let Some(reset) = &mut self.vault_reset else {
+            return;
+        };
+        let mut open = true;
+        let mut save_copy = false;
+        let mut delete = false;
+        
+        egui::Window::new("Reset Vault")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 100, 100),
+                    "⚠ This permanently deletes every password and secret in the vault.",
+                );
+                ui.label("Without the master password the contents cannot be recovered by anyone, including PwGen.");
+                ui.add_space(10.0);
                 
-                if !self.generated_password.is_empty() {
-                    ui.group(|ui| {
+                match reset.step {
+                    ResetStep::Backup => {
+                        ui.strong("Step 1 of 3: Keep an encrypted copy");
+                        ui.label("Save a copy of the encrypted vault first in case you remember the password later. \
+                                  The copy can only be opened with the current master password.");
+                        ui.add_space(5.0);
+                        if ui.button("💾 Save encrypted copy...").clicked() {
+                            save_copy = true;
+                        }
+                        if let Some(path) = &reset.backup_path {
+                            ui.colored_label(egui::Color32::from_rgb(100, 255, 100), format!("Saved to {}", path.display()));
+                        }
+                        ui.add_space(10.0);
+                        let next = if reset.backup_path.is_some() { "Next →" } else { "Skip, don't keep a copy →" };
+                        if ui.button(next).clicked() {
+                            reset.step = ResetStep::TypeDelete;
+                        }
+                    }
+                    ResetStep::TypeDelete => {
+                        ui.strong("Step 2 of 3: Type DELETE to continue");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut reset.typed_confirmation)
+                                .hint_text("DELETE")
+                                .desired_width(150.0)
+                        );
+                        ui.add_space(10.0);
                         ui.horizontal(|ui| {
-                            ui.add(
-                                egui::TextEdit::singleline(&mut self.generated_password)
-                                    .desired_width(300.0)
-                                    .font(egui::TextStyle::Monospace)
+                            if ui.button("← Back").clicked() {
+                                reset.step = ResetStep::Backup;
+                            }
+                            if ui.add_enabled(reset.typed_confirmation == "DELETE", egui::Button::new("Next →")).clicked() {
+                                reset.step = ResetStep::Confirm;
+                            }
+                        });
+                    }
+                    ResetStep::Confirm => {
+                        ui.strong("Step 3 of 3: Confirm");
+                        ui.checkbox(
+                            &mut reset.no_recovery_need,
+                            "I don't need to recover anything from this vault, and I understand this cannot be undone",
+                        );
+                        if reset.backup_path.is_none() {
+                            ui.label("No encrypted copy was saved.");
+                        }
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            if ui.button("← Back").clicked() {
+                                reset.step = ResetStep::TypeDelete;
+                            }
+                            let button = egui::Button::new(
+                                egui::RichText::new("🗑 Delete vault").color(egui::Color32::from_rgb(255, 100, 100))
                             );
-                            if ui.button("📋 Copy").clicked() {
-                                self.copy_to_clipboard(&self.generated_password);
-                                self.success_message = "Password copied!".to_string();
+                            if ui.add_enabled(reset.no_recovery_need, button).clicked() {
+                                delete = true;
                             }
                         });
-                    });
-                    
-                    ui.add_space(10.0);
-                    
-                    // Password strength indicator
-                    let strength = self.calculate_password_strength(&self.generated_password);
-                    ui.label(format!("💪 Strength: {}", strength));
-                } else {
-                    ui.label("Generate a password to see it here");
+                    }
                 }
             });
-        });
+        
+        if save_copy {
+            self.save_encrypted_vault_copy();
+        }
+        if delete {
+            self.reset_vault();
+        } else if !open {
+            self.vault_reset = None;
+        }
     }
     
-    fn show_tools_tab(&mut self, ui: &mut egui::Ui) {
-        ui.heading("🛠 Tools & Utilities");
-        ui.separator();
-        ui.add_space(10.0);
+    /// Copy the vault file, still encrypted, to a location the user picks.
+    /// Needs no master password.
+    fn save_encrypted_vault_copy(&mut self) {
+        let Some(vault_path) = self.get_vault_path() else {
+            return;
+        };
+        let file_name = format!("pwgen-vault-{}.db", Utc::now().format("%Y%m%d-%H%M%S"));
+        let Some(destination) = rfd::FileDialog::new().set_file_name(&file_name).save_file() else {
+            return;
+        };
+        if destination == vault_path {
+            self.error_message = "Choose a location other than the vault itself".to_string();
+            return;
+        }
         
-        // Main Tools Grid - 2x3 layout for better space usage
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            egui::Grid::new("main_tools_grid")
-                .num_columns(2)
-                .spacing([30.0, 20.0])
-                .min_col_width(350.0)
-                .show(ui, |ui| {
-                    
-                    // Data Management Card
-                    ui.group(|ui| {
-                        ui.set_min_size(egui::vec2(350.0, 200.0));
-                        ui.vertical(|ui| {
-                            ui.heading("📦 Data Management");
-                            ui.separator();
-                            ui.add_space(10.0);
-                            
-                            // Import section
-                            ui.horizontal(|ui| {
-                                ui.label("📥");
-                                ui.vertical(|ui| {
-                                    if ui.button("Import from Browser").clicked() {
-                                        self.show_import = true;
-                                    }
-                                    ui.small("Chrome, Firefox, Safari, Edge...");
-                                });
-                            });
-                            
-                            ui.add_space(8.0);
-                            
-                            // Export section
-                            ui.horizontal(|ui| {
-                                ui.label("📤");
-                                ui.vertical(|ui| {
-                                    if ui.button("Create Backup").clicked() {
-                                        self.show_backup = true;
-                                    }
-                                    ui.small("Encrypted vault backup");
-                                });
-                            });
-                            
-                            ui.add_space(8.0);
-                            
-                            // Sync section
-                            ui.horizontal(|ui| {
-                                ui.label("🔄");
-                                ui.vertical(|ui| {
-                                    ui.add_enabled(false, egui::Button::new("Cloud Sync"));
-                                    ui.small("Coming soon - sync across devices");
-                                });
-                            });
-                        });
-                    });
-                    
-                    // Analysis & Security Card
-                    ui.group(|ui| {
-                        ui.set_min_size(egui::vec2(350.0, 200.0));
-                        ui.vertical(|ui| {
-                            ui.heading("🔍 Analysis & Security");
-                            ui.separator();
-                            ui.add_space(10.0);
-                            
-                            // Statistics section
-                            ui.horizontal(|ui| {
-                                ui.label("📊");
-                                ui.vertical(|ui| {
-                                    if ui.button("Vault Statistics").clicked() {
-                                        self.show_statistics = true;
-                                    }
-                                    ui.small(format!("{} passwords • {} secrets", self.entries.len(), self.secrets.len()));
-                                });
-                            });
-                            
-                            ui.add_space(8.0);
-                            
-                            // Security audit section
-                            ui.horizontal(|ui| {
-                                ui.label("🛡");
-                                ui.vertical(|ui| {
-                                    ui.add_enabled(false, egui::Button::new("Security Audit"));
-                                    ui.small("Password strength & breach check");
-                                });
-                            });
-                            
-                            ui.add_space(8.0);
-                            
-                            // Duplicate detection
-                            ui.horizontal(|ui| {
-                                ui.label("🔍");
-                                ui.vertical(|ui| {
-                                    ui.add_enabled(false, egui::Button::new("Find Duplicates"));
-                                    ui.small("Identify duplicate passwords");
-                                });
-                            });
-                        });
-                    });
-                    
-                    ui.end_row();
-                    
-                    // Vault Management Card
-                    ui.group(|ui| {
-                        ui.set_min_size(egui::vec2(350.0, 200.0));
-                        ui.vertical(|ui| {
-                            ui.heading("🔐 Vault Management");
-                            ui.separator();
-                            ui.add_space(10.0);
-                            
-                            // Reload data
-                            ui.horizontal(|ui| {
-                                ui.label("🔄");
-                                ui.vertical(|ui| {
-                                    if ui.button("Reload Data").clicked() {
-                                        self.load_entries();
-                                        self.success_message = "Data reloaded successfully".to_string();
-                                    }
-                                    ui.small("Refresh entries and secrets");
-                                });
-                            });
-                            
-                            ui.add_space(8.0);
-                            
-                            // Lock vault
-                            ui.horizontal(|ui| {
-                                ui.label("🔒");
-                                ui.vertical(|ui| {
-                                    if ui.button("Lock Vault").clicked() {
-                                        self.lock_vault();
-                                    }
-                                    ui.small("Secure and close vault");
-                                });
-                            });
-                            
-                            ui.add_space(8.0);
-                            
-                            // Database maintenance
-                            ui.horizontal(|ui| {
-                                ui.label("🔧");
-                                ui.vertical(|ui| {
-                                    ui.add_enabled(false, egui::Button::new("Database Repair"));
-                                    ui.small("Optimize and repair database");
-                                });
-                            });
-                        });
-                    });
-                    
-                    // Utilities & Cleanup Card
-                    ui.group(|ui| {
-                        ui.set_min_size(egui::vec2(350.0, 200.0));
-                        ui.vertical(|ui| {
-                            ui.heading("🧹 Utilities & Cleanup");
-                            ui.separator();
-                            ui.add_space(10.0);
-                            
-                            // Clean duplicates
-                            ui.horizontal(|ui| {
-                                ui.label("🗑");
-                                ui.vertical(|ui| {
-                                    ui.add_enabled(false, egui::Button::new("Remove Duplicates"));
-                                    ui.small("Clean up duplicate entries");
-                                });
-                            });
-                            
-                            ui.add_space(8.0);
-                            
-                            // Fix tags
-                            ui.horizontal(|ui| {
-                                ui.label("🏷");
-                                ui.vertical(|ui| {
-                                    ui.add_enabled(false, egui::Button::new("Organize Tags"));
-                                    ui.small("Clean and organize tags");
-                                });
-                            });
-                            
-                            ui.add_space(8.0);
-                            
-                            // Export reports
-                            ui.horizontal(|ui| {
-                                ui.label("📋");
-                                ui.vertical(|ui| {
-                                    ui.add_enabled(false, egui::Button::new("Export Report"));
-                                    ui.small("Generate security report");
-                                });
-                            });
+        // Copy any write-ahead log alongside so the copy is complete
+        for (source, target) in vault_files(&vault_path).into_iter().zip(vault_files(&destination)) {
+            if !source.exists() {
+                continue;
+            }
+            if let Err(e) = std::fs::copy(&source, &target) {
+                self.error_message = format!("Failed to save a copy of the vault: {}", e);
+                return;
+            }
+        }
+        self.error_message.clear();
+        if let Some(reset) = &mut self.vault_reset {
+            reset.backup_path = Some(destination);
+        }
+    }
+    
+    /// Overwrite and delete the vault, leaving the login screen on "Create Your Vault".
+    fn reset_vault(&mut self) {
+        let Some(vault_path) = self.get_vault_path() else {
+            return;
+        };
+        *self.storage.lock().unwrap() = None;
+        *self.secrets_storage.lock().unwrap() = None;
+        
+        match delete_vault(&vault_path) {
+            Ok(_) => {
+                let _ = forget_vault(&vault_path);
+                self.recent_vaults = list_recent_vaults();
+                self.vault_reset = None;
+                self.master_password.clear();
+                self.master_password_confirm.clear();
+                self.key_file_path = None;
+                self.error_message.clear();
+                self.success_message = "Vault deleted. Create a new vault to start over.".to_string();
+            }
+            Err(e) => {
+                self.error_message = format!("Failed to delete the vault: {}", e);
+            }
+        }
+    }
+    
+    fn show_main_screen(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Menu bar
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                ui.menu_button("File", |ui| {
+                    if ui.button("➕ New Entry").clicked() {
+                        self.open_new_entry_form();
+                        ui.close_menu();
+                    }
+                    if ui.button("🔒 Lock Vault").clicked() {
+                        self.lock_vault();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("📂 Open Vault...").clicked() {
+                        self.choose_vault_file(false);
+                        ui.close_menu();
+                    }
+                    if ui.button("🆕 New Vault...").clicked() {
+                        self.choose_vault_file(true);
+                        ui.close_menu();
+                    }
+                    let others: Vec<PathBuf> = self
+                        .recent_vaults
+                        .iter()
+                        .filter(|path| Some(*path) != self.vault_path.as_ref())
+                        .cloned()
+                        .collect();
+                    ui.add_enabled_ui(!others.is_empty(), |ui| {
+                        ui.menu_button("🕘 Recent Vaults", |ui| {
+                            for path in others {
+                                if ui.button(vault_label(&path)).on_hover_text(path.display().to_string()).clicked() {
+                                    self.switch_vault(path);
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                    ui.separator();
+                    if ui.button("⚙ Settings").clicked() {
+                        self.show_settings = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("❌ Exit").clicked() {
+                        self.minimize_to_tray = false; // Force exit
+                        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    }
+                });
+                
+                ui.menu_button("Edit", |ui| {
+                    if ui.button("🔍 Search").clicked() {
+                        // Focus search field
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("📋 Copy Username").clicked() {
+                        if let Some(id) = &self.selected_entry_id {
+                            if let Some(entry) = self.entries.iter().find(|e| e.id == *id).cloned() {
+                                self.quick_copy_entry(&entry, "username");
+                            }
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("🔑 Copy Password").clicked() {
+                        if let Some(id) = &self.selected_entry_id {
+                            if let Some(entry) = self.entries.iter().find(|e| e.id == *id).cloned() {
+                                self.quick_copy_entry(&entry, "password");
+                            }
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("🌐 Copy and Open Site").clicked() {
+                        if let Some(id) = &self.selected_entry_id {
+                            if let Some(entry) = self.entries.iter().find(|e| e.id == *id).cloned() {
+                                self.copy_and_open(&entry);
+                            }
+                        }
+                        ui.close_menu();
+                    }
+                });
+                
+                ui.menu_button("Tools", |ui| {
+                    if ui.button("🔐 Secrets Manager").clicked() {
+                        self.show_secrets_view = true;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("🎲 Password Generator").clicked() {
+                        self.show_generator = true;
+                        self.generate_password();
+                        ui.close_menu();
+                    }
+                    if ui.button("📊 Vault Statistics").clicked() {
+                        self.open_statistics();
+                        ui.close_menu();
+                    }
+                    if ui.button("🕘 Recently Used").clicked() {
+                        self.show_recently_used = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("📥 Import from Browser").clicked() {
+                        self.show_import = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("💾 Backup Vault").clicked() {
+                        self.show_backup = true;
+                        ui.close_menu();
+                    }
+                });
+                
+                ui.menu_button("Help", |ui| {
+                    if ui.button("📚 Documentation").clicked() {
+                        if let Err(e) = open::that("https://github.com/hxhippy/pwgen/blob/main/README.md") {
+                            eprintln!("Failed to open documentation: {}", e);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("🌐 Kief Studio").clicked() {
+                        if let Err(e) = open::that("https://kief.studio") {
+                            eprintln!("Failed to open Kief Studio: {}", e);
+                        }
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button("ℹ About PwGen").clicked() {
+                        self.show_about = true;
+                        ui.close_menu();
+                    }
+                });
+            });
+        });
+        
+        // Header with logo and search - responsive layout
+        egui::TopBottomPanel::top("header").show(ctx, |ui| {
+            ui.add_space(5.0);
+            
+            if self.is_compact_mode {
+                // Compact mode: stack elements vertically
+                ui.vertical(|ui| {
+                    // Top row: Logo and main action buttons
+                    ui.horizontal(|ui| {
+                        // Display logo if available
+                        if let Some(logo) = &self.logo_wide {
+                            ui.add(
+                                egui::Image::from_texture(logo)
+                                    .fit_to_exact_size(egui::vec2(100.0, 33.0))
+                                    .bg_fill(logo_background(ui))
+                                    .rounding(egui::Rounding::same(4.0))
+                            );
+                        } else {
+                            ui.heading(
+                                egui::RichText::new("🦀 PwGen")
+                                    .size(20.0)
+                                    .color(ui.visuals().strong_text_color())
+                            );
+                        }
+                        
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("🔒 Lock").clicked() {
+                                self.lock_vault();
+                            }
+                            if ui.button("🎲 Generate").clicked() {
+                                self.show_generator = true;
+                                self.generate_password();
+                            }
+                            if ui.button("➕ Add Entry").clicked() {
+                                self.open_new_entry_form();
+                            }
                         });
                     });
                     
-                    ui.end_row();
+                    ui.add_space(3.0);
                     
-                    // System Information Card
-                    ui.group(|ui| {
-                        ui.set_min_size(egui::vec2(350.0, 200.0));
-                        ui.vertical(|ui| {
-                            ui.heading("ℹ System Information");
-                            ui.separator();
-                            ui.add_space(10.0);
-                            
-                            egui::Grid::new("system_info_grid").num_columns(2).spacing([20.0, 5.0]).show(ui, |ui| {
-                                ui.label("Version:");
-                                ui.label("PwGen v0.1.0");
-                                ui.end_row();
-                                
-                                ui.label("Engine:");
-                                ui.label("🦀 Rust + egui");
-                                ui.end_row();
-                                
-                                ui.label("Encryption:");
-                                ui.label("🔒 AES-256-GCM");
-                                ui.end_row();
-                                
-                                ui.label("Storage:");
-                                ui.label("💾 SQLite");
-                                ui.end_row();
-                                
-                                ui.label("Layout:");
-                                ui.label(if self.is_compact_mode { "📱 Compact" } else { "🖥 Wide" });
-                                ui.end_row();
-                                
-                                ui.label("Window:");
-                                ui.label(format!("{:.0}px wide", self.window_width));
-                                ui.end_row();
-                            });
-                        });
+                    // Second row: Search and advanced search toggle
+                    ui.horizontal(|ui| {
+                        ui.label("🔍");
+                        let search_response = ui.add(
+                            egui::TextEdit::singleline(&mut self.search_query)
+                                .desired_width(ui.available_width() - 60.0)
+                                .hint_text("Search passwords...")
+                        );
+                        if search_response.changed() {
+                            self.schedule_search();
+                        }
+                        
+                        if ui.small_button("🔧").on_hover_text("Advanced Search").clicked() {
+                            self.show_advanced_search = !self.show_advanced_search;
+                        }
                     });
                     
-                    // Resources & Support Card
-                    ui.group(|ui| {
-                        ui.set_min_size(egui::vec2(350.0, 200.0));
-                        ui.vertical(|ui| {
-                            ui.heading("🌐 Resources & Support");
-                            ui.separator();
-                            ui.add_space(10.0);
-                            
-                            // Documentation
-                            ui.horizontal(|ui| {
-                                ui.label("📚");
-                                ui.vertical(|ui| {
-                                    if ui.button("Documentation").clicked() {
-                                        let _ = open::that("https://github.com/hxhippy/pwgen/blob/main/README.md");
-                                    }
-                                    ui.small("User guide and API docs");
-                                });
-                            });
+                    // Third row: Pagination controls (if needed)
+                    if self.total_pages > 1 {
+                        ui.add_space(2.0);
+                        ui.horizontal(|ui| {
+                            ui.label("📄");
                             
-                            ui.add_space(8.0);
+                            if ui.add_enabled(self.current_page > 0, egui::Button::new("◀")).clicked() {
+                                self.current_page -= 1;
+                                self.filter_entries();
+                            }
                             
-                            // About
-                            ui.horizontal(|ui| {
-                                ui.label("ℹ");
-                                ui.vertical(|ui| {
-                                    if ui.button("About PwGen").clicked() {
-                                        self.show_about = true;
-                                    }
-                                    ui.small("Version info and credits");
-                                });
-                            });
+                            ui.label(format!("{}/{}", self.current_page + 1, self.total_pages));
                             
-                            ui.add_space(8.0);
+                            if ui.add_enabled(self.current_page < self.total_pages - 1, egui::Button::new("▶")).clicked() {
+                                self.current_page += 1;
+                                self.filter_entries();
+                            }
                             
-                            // External links
-                            ui.horizontal(|ui| {
-                                ui.label("🔗");
-                                ui.vertical(|ui| {
-                                    ui.horizontal(|ui| {
-                                        if ui.small_button("Kief Studio").clicked() {
-                                            let _ = open::that("https://kief.studio");
+                            ui.separator();
+                            ui.label("Per page:");
+                            egui::ComboBox::from_id_salt("entries_per_page")
+                                .selected_text(format!("{}", self.entries_per_page))
+                                .width(80.0)
+                                .show_ui(ui, |ui| {
+                                    for &size in &[25, 50, 100, 200] {
+                                        if ui.selectable_value(&mut self.entries_per_page, size, format!("{}", size)).clicked() {
+                                            self.current_page = 0;
+                                            self.filter_entries();
                                         }
-                                    });
-                                    ui.small("Developer websites");
+                                    }
                                 });
-                            });
                         });
-                    });
+                    }
                 });
-        });
-    }
-    
-    fn show_settings_tab(&mut self, ui: &mut egui::Ui) {
-        ui.heading("⚙ Settings");
-        ui.separator();
-        
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            ui.group(|ui| {
-                ui.label("🔒 Security");
-                ui.separator();
-                
-                egui::Grid::new("security_settings").num_columns(2).show(ui, |ui| {
-                    ui.label("Auto-lock after (minutes):");
-                    ui.add(egui::DragValue::new(&mut self.auto_lock_minutes).range(1..=60));
-                    ui.end_row();
+            } else {
+                // Wide mode: horizontal layout
+                ui.horizontal(|ui| {
+                    // Display logo if available
+                    if let Some(logo) = &self.logo_wide {
+                        ui.add(
+                            egui::Image::from_texture(logo)
+                                .fit_to_exact_size(egui::vec2(120.0, 40.0))
+                                .bg_fill(logo_background(ui))
+                                .rounding(egui::Rounding::same(4.0))
+                        );
+                    } else {
+                        ui.heading(
+                            egui::RichText::new("🦀 PwGen")
+                                .size(24.0)
+                                .color(ui.visuals().strong_text_color())
+                        );
+                    }
                     
-                    ui.label("Minimize to tray:");
-                    ui.checkbox(&mut self.minimize_to_tray, "");
-                    ui.end_row();
+                    ui.separator();
                     
-                    ui.label("Show system tray:");
-                    ui.checkbox(&mut self.show_system_tray, "");
-                    ui.end_row();
-                });
-            });
-            
-            ui.add_space(10.0);
-            
-            ui.group(|ui| {
-                ui.label("🎨 Appearance");
-                ui.separator();
-                
-                egui::Grid::new("appearance_settings").num_columns(2).show(ui, |ui| {
-                    ui.label("Entries per page:");
-                    ui.add(egui::DragValue::new(&mut self.entries_per_page).range(10..=500));
-                    ui.end_row();
+                    // Search
+                    ui.add_space(5.0);
+                    ui.label("🔍");
+                    let search_width = if self.window_width > 1400.0 { 400.0 } else { 300.0 };
+                    let search_response = ui.add(
+                        egui::TextEdit::singleline(&mut self.search_query)
+                            .desired_width(search_width)
+                            .hint_text("Search passwords...")
+                    );
+                    if search_response.changed() {
+                        self.schedule_search();
+                    }
                     
-                    ui.label("Theme:");
-                    ui.label("System (auto)");
-                    ui.end_row();
+                    // Advanced search toggle
+                    if ui.small_button("🔧").on_hover_text("Advanced Search").clicked() {
+                        self.show_advanced_search = !self.show_advanced_search;
+                    }
                     
-                    ui.label("Font:");
-                    ui.label("Fira Code");
-                    ui.end_row();
-                });
-            });
-            
-            ui.add_space(10.0);
-            
-            ui.group(|ui| {
-                ui.label("💾 Data");
-                ui.separator();
-                
-                if ui.button("🔄 Reload Entries").clicked() {
-                    self.load_entries();
-                    self.success_message = "Entries reloaded".to_string();
-                }
-                
-                ui.add_space(5.0);
-                
-                if ui.button("💾 Backup Now").clicked() {
-                    self.show_backup = true;
-                }
-                
-                if ui.button("📥 Import Data").clicked() {
-                    self.show_import = true;
-                }
-            });
-            
-            ui.add_space(10.0);
-            
-            ui.group(|ui| {
-                ui.label("ℹ Information");
-                ui.separator();
-                
-                ui.label("PwGen v0.1.0");
-                ui.label("🦀 Built with Rust + egui");
-                ui.label("🔒 AES-256-GCM encryption");
-                ui.label("💾 SQLite storage");
-                
-                ui.add_space(5.0);
-                
-                if ui.button("ℹ About").clicked() {
-                    self.show_about = true;
-                }
-            });
-        });
-    }
-    
-    fn calculate_password_strength(&self, password: &str) -> &'static str {
-        let length = password.len();
-        let has_upper = password.chars().any(|c| c.is_uppercase());
-        let has_lower = password.chars().any(|c| c.is_lowercase());
-        let has_digit = password.chars().any(|c| c.is_numeric());
-        let has_symbol = password.chars().any(|c| !c.is_alphanumeric());
-        
-        let variety = [has_upper, has_lower, has_digit, has_symbol].iter().filter(|&&x| x).count();
-        
-        match (length, variety) {
-            (0..=7, _) => "Very Weak",
-            (8..=11, 1..=2) => "Weak", 
-            (8..=11, 3..=4) => "Fair",
-            (12..=15, 1..=2) => "Fair",
-            (12..=15, 3..=4) => "Good",
-            (16.., 1..=2) => "Good",
-            (16.., 3..=4) => "Strong",
-            _ => "Weak",
-        }
-    }
-    
-    fn show_entry_dialog(&mut self, ctx: &egui::Context) {
-        if !self.show_add_dialog {
-            return;
-        }
-        
-        egui::Window::new(if self.edit_entry.is_some() { "Edit Entry" } else { "Add New Entry" })
-            .collapsible(false)
-            .resizable(false)
-            .default_width(400.0)
-            .show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    egui::Grid::new("entry_form").num_columns(2).show(ui, |ui| {
-                        ui.label("Website:");
-                        ui.text_edit_singleline(&mut self.entry_site);
-                        ui.end_row();
+                    // Pagination controls
+                    if self.total_pages > 1 {
+                        ui.separator();
+                        ui.label("📄");
                         
-                        ui.label("Username:");
-                        ui.text_edit_singleline(&mut self.entry_username);
-                        ui.end_row();
+                        if ui.add_enabled(self.current_page > 0, egui::Button::new("◀")).clicked() {
+                            self.current_page -= 1;
+                            self.filter_entries();
+                        }
                         
-                        ui.label("Password:");
-                        ui.horizontal(|ui| {
-                            if self.show_password {
-                                ui.add(
-                                    egui::TextEdit::singleline(&mut self.entry_password)
-                                        .font(egui::TextStyle::Monospace)
-                                );
-                            } else {
-                                ui.add(
-                                    egui::TextEdit::singleline(&mut self.entry_password)
-                                        .password(true)
-                                        .font(egui::TextStyle::Monospace)
-                                );
-                            }
-                            if ui.button(if self.show_password { "🙈" } else { "👁" }).clicked() {
-                                self.show_password = !self.show_password;
-                            }
-                            if ui.button("🎲").on_hover_text("Generate").clicked() {
-                                self.show_generator = true;
-                                self.generate_password();
-                            }
-                        });
-                        ui.end_row();
+                        ui.label(format!("{}/{}", self.current_page + 1, self.total_pages));
                         
-                        ui.label("Notes:");
-                        ui.add(egui::TextEdit::multiline(&mut self.entry_notes).desired_rows(3));
-                        ui.end_row();
+                        if ui.add_enabled(self.current_page < self.total_pages - 1, egui::Button::new("▶")).clicked() {
+                            self.current_page += 1;
+                            self.filter_entries();
+                        }
                         
-                        ui.label("Tags:");
-                        ui.add(
-                            egui::TextEdit::singleline(&mut self.entry_tags)
-                                .hint_text("Comma separated tags...")
-                        );
-                        ui.end_row();
-                    });
-                    
-                    ui.separator();
+                        ui.separator();
+                        ui.label("Per page:");
+                        egui::ComboBox::from_id_salt("entries_per_page")
+                            .selected_text(format!("{}", self.entries_per_page))
+                            .width(80.0)
+                            .show_ui(ui, |ui| {
+                                for &size in &[25, 50, 100, 200] {
+                                    if ui.selectable_value(&mut self.entries_per_page, size, format!("{}", size)).clicked() {
+                                        self.current_page = 0;
+                                        self.filter_entries();
+                                    }
+                                }
+                            });
+                    }
                     
-                    ui.horizontal(|ui| {
-                        if ui.button("💾 Save").clicked() {
-                            self.save_entry();
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("🔒 Lock").clicked() {
+                            self.lock_vault();
                         }
-                        if ui.button("❌ Cancel").clicked() {
-                            self.show_add_dialog = false;
-                            self.clear_entry_form();
+                        
+                        if ui.button("🎲 Generate").clicked() {
+                            self.show_generator = true;
+                            self.generate_password();
+                        }
+                        
+                        if ui.button("➕ Add Entry").clicked() {
+                            self.open_new_entry_form();
                         }
                     });
                 });
-            });
-    }
-    
-    fn show_generator_dialog(&mut self, ctx: &egui::Context) {
-        if !self.show_generator {
-            return;
+            }
+            
+            ui.add_space(5.0);
+        });
+        
+        if !self.expiry_notices.is_empty() {
+            self.show_expiry_banner(ctx);
         }
         
-        egui::Window::new("Password Generator")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(350.0)
-            .show(ctx, |ui| {
+        // Advanced search panel
+        if self.show_advanced_search {
+            egui::TopBottomPanel::top("advanced_search").show(ctx, |ui| {
                 ui.vertical(|ui| {
-                    ui.group(|ui| {
-                        ui.horizontal(|ui| {
-                            ui.add(
-                                egui::TextEdit::singleline(&mut self.generated_password)
-                                    .desired_width(250.0)
-                                    .font(egui::TextStyle::Monospace)
-                                    .text_color(ui.visuals().strong_text_color())
-                            );
-                            if ui.button("📋").on_hover_text("Copy").clicked() {
-                                self.copy_to_clipboard(&self.generated_password);
-                                self.success_message = "Password copied!".to_string();
-                            }
-                            if ui.button("🔄").on_hover_text("Regenerate").clicked() {
-                                self.generate_password();
-                            }
-                        });
-                    });
-                    
-                    ui.separator();
-                    
-                    ui.horizontal(|ui| {
-                        ui.label("Length:");
-                        ui.add(egui::Slider::new(&mut self.gen_length, 8..=128));
-                    });
-                    
-                    ui.checkbox(&mut self.gen_uppercase, "Uppercase (A-Z)");
-                    ui.checkbox(&mut self.gen_lowercase, "Lowercase (a-z)");
-                    ui.checkbox(&mut self.gen_numbers, "Numbers (0-9)");
-                    ui.checkbox(&mut self.gen_symbols, "Symbols (!@#$%)");
-                    ui.checkbox(&mut self.gen_exclude_ambiguous, "Exclude ambiguous (0O1lI)");
-                    
+                    ui.label("🔍 Advanced Search & Filters");
                     ui.separator();
                     
-                    ui.horizontal(|ui| {
-                        if ui.button("✅ Use This Password").clicked() {
-                            self.entry_password = self.generated_password.clone();
-                            self.show_generator = false;
-                        }
-                        if ui.button("❌ Close").clicked() {
-                            self.show_generator = false;
-                        }
-                    });
-                });
-            });
-    }
-    
-    fn show_settings_dialog(&mut self, ctx: &egui::Context) {
-        if !self.show_settings {
-            return;
-        }
-        
-        egui::Window::new("⚙ Settings")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(400.0)
-            .show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    ui.group(|ui| {
-                        ui.label("Security Settings");
+                    if self.is_compact_mode {
+                        // Compact mode: stack filter controls vertically
                         ui.horizontal(|ui| {
-                            ui.label("Auto-lock after:");
-                            ui.add(egui::Slider::new(&mut self.auto_lock_minutes, 1..=60).suffix(" min"));
+                            ui.label("Search in:");
+                            egui::ComboBox::from_id_salt("search_field")
+                                .selected_text(match self.search_field {
+                                    SearchField::All => "All Fields",
+                                    SearchField::Site => "Website",
+                                    SearchField::Username => "Username",
+                                    SearchField::Notes => "Notes",
+                                    SearchField::Tags => "Tags",
+                                })
+                                .width(140.0)
+                                .show_ui(ui, |ui| {
+                                    let mut changed = false;
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::All, "All Fields").changed();
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Site, "Website").changed();
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Username, "Username").changed();
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Notes, "Notes").changed();
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Tags, "Tags").changed();
+                                    if changed {
+                                        self.filter_entries();
+                                    }
+                                });
+                            
+                            if ui.checkbox(&mut self.filter_favorites, "⭐ Favorites").changed() {
+                                self.filter_entries();
+                            }
+                            self.show_favorites_first_checkbox(ui);
+                            
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button("Clear All").clicked() {
+                                    self.search_query.clear();
+                                    self.filter_tags.clear();
+                                    self.filter_favorites = false;
+                                    self.search_field = SearchField::All;
+                                    self.schedule_search();
+                                }
+                            });
                         });
-                        ui.checkbox(&mut self.minimize_to_tray, "Minimize to system tray");
-                    });
-                    
-                    ui.add_space(10.0);
-                    
-                    ui.group(|ui| {
-                        ui.label("Generator Defaults");
+                        
+                        ui.add_space(2.0);
+                        
                         ui.horizontal(|ui| {
-                            ui.label("Default length:");
-                            ui.add(egui::Slider::new(&mut self.gen_length, 8..=128));
+                            ui.label("Tags:");
+                            let tag_response = ui.add(
+                                egui::TextEdit::singleline(&mut self.filter_tags)
+                                    .desired_width(ui.available_width() - 50.0)
+                                    .hint_text("Filter by tags (comma-separated)")
+                            );
+                            if tag_response.changed() {
+                                self.filter_entries();
+                            }
                         });
-                        ui.checkbox(&mut self.gen_uppercase, "Include uppercase by default");
-                        ui.checkbox(&mut self.gen_numbers, "Include numbers by default");
-                        ui.checkbox(&mut self.gen_symbols, "Include symbols by default");
-                        ui.checkbox(&mut self.gen_exclude_ambiguous, "Exclude ambiguous characters");
-                    });
-                    
-                    ui.separator();
-                    
-                    ui.horizontal(|ui| {
-                        if ui.button("💾 Save").clicked() {
-                            // TODO: Save settings to config file
-                            self.success_message = "Settings saved!".to_string();
-                            self.show_settings = false;
-                        }
-                        if ui.button("❌ Cancel").clicked() {
-                            self.show_settings = false;
-                        }
-                    });
+                    } else {
+                        // Wide mode: horizontal layout
+                        ui.horizontal(|ui| {
+                            // Search field selector
+                            ui.label("Search in:");
+                            egui::ComboBox::from_id_salt("search_field")
+                                .selected_text(match self.search_field {
+                                    SearchField::All => "All Fields",
+                                    SearchField::Site => "Website",
+                                    SearchField::Username => "Username",
+                                    SearchField::Notes => "Notes",
+                                    SearchField::Tags => "Tags",
+                                })
+                                .width(140.0)
+                                .show_ui(ui, |ui| {
+                                    let mut changed = false;
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::All, "All Fields").changed();
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Site, "Website").changed();
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Username, "Username").changed();
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Notes, "Notes").changed();
+                                    changed |= ui.selectable_value(&mut self.search_field, SearchField::Tags, "Tags").changed();
+                                    if changed {
+                                        self.filter_entries();
+                                    }
+                                });
+                            
+                            ui.separator();
+                            
+                            // Favorites filter
+                            if ui.checkbox(&mut self.filter_favorites, "⭐ Favorites only").changed() {
+                                self.filter_entries();
+                            }
+                            self.show_favorites_first_checkbox(ui);
+                            
+                            ui.separator();
+                            
+                            // Tag filter
+                            ui.label("Tags:");
+                            let tag_width = if self.window_width > 1400.0 { 200.0 } else { 150.0 };
+                            let tag_response = ui.add(
+                                egui::TextEdit::singleline(&mut self.filter_tags)
+                                    .desired_width(tag_width)
+                                    .hint_text("Filter by tags (comma-separated)")
+                            );
+                            if tag_response.changed() {
+                                self.filter_entries();
+                            }
+                            
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button("Clear All").clicked() {
+                                    self.search_query.clear();
+                                    self.filter_tags.clear();
+                                    self.filter_favorites = false;
+                                    self.search_field = SearchField::All;
+                                    self.schedule_search();
+                                }
+                            });
+                        });
+                    }
                 });
             });
-    }
-    
-    fn show_about_dialog(&mut self, ctx: &egui::Context) {
-        if !self.show_about {
-            return;
         }
         
-        egui::Window::new("ℹ About PwGen")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(450.0)
-            .show(ctx, |ui| {
-                ui.vertical_centered(|ui| {
-                    if let Some(logo) = &self.logo_wide {
-                        ui.add(
-                            egui::Image::from_texture(logo)
-                                .fit_to_exact_size(egui::vec2(150.0, 50.0))
-                        );
-                        ui.add_space(10.0);
-                    }
-                    
-                    ui.heading("PwGen Password Manager");
-                    ui.label("Version 1.0.0");
-                    ui.add_space(10.0);
-                    
-                    ui.label("A secure, cross-platform password manager built in Rust");
-                    ui.label("with military-grade encryption and modern UI.");
-                    
-                    ui.add_space(15.0);
-                    ui.separator();
-                    ui.add_space(15.0);
-                    
-                    ui.strong("Developed by HxHippy");
-                    if ui.link("🌐 hxhippy.com").clicked() {
-                        let _ = open::that("https://hxhippy.com");
+        // Status bar
+        if !self.error_message.is_empty() || !self.success_message.is_empty() {
+            egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if !self.error_message.is_empty() {
+                        ui.colored_label(error_color(ui), &self.error_message);
+                        if ui.small_button("✖").clicked() {
+                            self.error_message.clear();
+                        }
                     }
-                    
-                    ui.add_space(10.0);
-                    
-                    ui.horizontal(|ui| {
-                        ui.label("🤖 AI Consulting:");
-                        if ui.link("Kief Studio").clicked() {
-                            let _ = open::that("https://kief.studio");
+                    if !self.success_message.is_empty() {
+                        ui.colored_label(success_color(ui), &self.success_message);
+                        if ui.small_button("✖").clicked() {
+                            self.success_message.clear();
                         }
-                    });
-
-                    ui.add_space(15.0);
-                    ui.separator();
-                    ui.add_space(10.0);
-                    
-                    ui.horizontal(|ui| {
-                        ui.label("🛡 Security:");
-                        ui.label("AES-256-GCM, Argon2, Zero-Knowledge");
-                    });
-                    
-                    ui.horizontal(|ui| {
-                        ui.label("🦀 Built with:");
-                        ui.label("Rust, egui, SQLite");
-                    });
-                    
-                    ui.add_space(15.0);
-                    
-                    if ui.button("✅ Close").clicked() {
-                        self.show_about = false;
                     }
                 });
             });
-    }
-    
-    fn show_statistics_dialog(&mut self, ctx: &egui::Context) {
-        if !self.show_statistics {
-            return;
         }
         
-        egui::Window::new("📊 Vault Statistics")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(350.0)
-            .show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    ui.group(|ui| {
-                        ui.label("📈 Entry Statistics");
-                        ui.horizontal(|ui| {
-                            ui.label("Total entries:");
-                            ui.strong(format!("{}", self.entries.len()));
-                        });
-                        ui.horizontal(|ui| {
-                            ui.label("Entries shown:");
-                            ui.strong(format!("{}", self.filtered_entries.len()));
-                        });
-                        
-                        // Calculate some stats
-                        let unique_sites: std::collections::HashSet<_> = self.entries.iter().map(|e| &e.site).collect();
-                        ui.horizontal(|ui| {
-                            ui.label("Unique sites:");
-                            ui.strong(format!("{}", unique_sites.len()));
-                        });
-                        
-                        let total_tags: std::collections::HashSet<_> = self.entries.iter().flat_map(|e| &e.tags).collect();
-                        ui.horizontal(|ui| {
-                            ui.label("Total tags:");
-                            ui.strong(format!("{}", total_tags.len()));
-                        });
-                    });
-                    
-                    ui.add_space(10.0);
-                    
-                    ui.group(|ui| {
-                        ui.label("🔒 Security Analysis");
-                        
-                        // Analyze password security
-                        let weak_passwords = self.entries.iter()
-                            .filter(|e| e.password.len() < 12)
-                            .count();
-                        let strong_passwords = self.entries.iter()
-                            .filter(|e| e.password.len() >= 16)
-                            .count();
-                        
-                        ui.horizontal(|ui| {
-                            ui.label("Strong passwords (16+ chars):");
-                            ui.colored_label(egui::Color32::from_rgb(50, 200, 50), format!("{}", strong_passwords));
-                        });
-                        ui.horizontal(|ui| {
-                            ui.label("Weak passwords (<12 chars):");
-                            ui.colored_label(egui::Color32::from_rgb(200, 50, 50), format!("{}", weak_passwords));
-                        });
-                        
-                        // Password age analysis
-                        let old_passwords = self.entries.iter()
-                            .filter(|e| {
-                                let age = chrono::Utc::now().signed_duration_since(e.password_changed_at);
-                                age.num_days() > 90
-                            })
-                            .count();
-                        
-                        ui.horizontal(|ui| {
-                            ui.label("Passwords >90 days old:");
-                            if old_passwords > 0 {
-                                ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("{}", old_passwords));
-                            } else {
-                                ui.colored_label(egui::Color32::from_rgb(50, 200, 50), format!("{}", old_passwords));
-                            }
-                        });
-                    });
-                    
-                    ui.add_space(15.0);
-                    
-                    if ui.button("✅ Close").clicked() {
-                        self.show_statistics = false;
+        // Footer with stats
+        egui::TopBottomPanel::bottom("footer").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("📊 {} total entries", self.entries.len()));
+                ui.separator();
+                
+                let total_filtered = self.total_filtered;
+                
+                if self.total_pages > 1 {
+                    let start_entry = self.current_page * self.entries_per_page + 1;
+                    let end_entry = std::cmp::min((self.current_page + 1) * self.entries_per_page, total_filtered);
+                    ui.label(format!("📄 Showing {}-{} of {} entries", start_entry, end_entry, total_filtered));
+                } else {
+                    ui.label(format!("🔍 {} shown", self.filtered_entries.len()));
+                }
+                
+                // Show active filters
+                let mut active_filters = Vec::new();
+                if !self.search_query.is_empty() {
+                    let field_name = match self.search_field {
+                        SearchField::All => "all",
+                        SearchField::Site => "site",
+                        SearchField::Username => "username", 
+                        SearchField::Notes => "notes",
+                        SearchField::Tags => "tags",
+                    };
+                    active_filters.push(format!("🔍 {} '{}'", field_name, self.search_query));
+                }
+                if self.filter_favorites {
+                    active_filters.push("⭐ favorites".to_string());
+                }
+                if !self.filter_tags.is_empty() {
+                    active_filters.push(format!("🏷 tags '{}'", self.filter_tags));
+                }
+                
+                if !active_filters.is_empty() && !self.is_compact_mode {
+                    ui.separator();
+                    ui.label("Filters:");
+                    for filter in active_filters {
+                        ui.small(&filter);
+                    }
+                } else if !active_filters.is_empty() && self.is_compact_mode {
+                    // In compact mode, only show filter count
+                    ui.separator();
+                    ui.small(format!("🎯 {} filters active", active_filters.len()));
+                }
+                
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    // Show security status with color-coded shield
+                    // A running backup job holds the storage lock, and only runs on an open vault
+                    let vault_secure = self.backup_job.is_some() || self.storage.lock().unwrap().is_some();
+                    if vault_secure {
+                        ui.colored_label(success_color(ui), "🛡 Vault Secure");
+                    } else {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(200, 50, 50), 
+                            "🛡 Vault Locked"
+                        );
                     }
                 });
             });
-    }
-    
-    fn show_import_dialog(&mut self, ctx: &egui::Context) {
-        if !self.show_import {
-            return;
-        }
+        });
         
-        egui::Window::new("📥 Import from Browser")
-            .collapsible(false)
-            .resizable(false)
-            .default_width(400.0)
-            .show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    ui.label("Import passwords from your web browser:");
-                    ui.add_space(10.0);
+        // Tab navigation
+        egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 8.0;
+                
+                // Tab buttons with icons and styling
+                let mut tab_button = |ui: &mut egui::Ui, tab: MainTab, icon: &str, text: &str| {
+                    let is_selected = self.current_tab == tab;
+                    let text_color = if is_selected {
+                        ui.visuals().strong_text_color()
+                    } else {
+                        ui.visuals().text_color()
+                    };
                     
-                    ui.group(|ui| {
-                        ui.label("🌐 Supported Browsers");
-                        
-                        if ui.button("🟦 Google Chrome").clicked() {
-                            self.import_from_browser("chrome");
-                        }
-                        if ui.button("🟧 Mozilla Firefox").clicked() {
-                            self.import_from_browser("firefox");
-                        }
-                        if ui.button("🟦 Microsoft Edge").clicked() {
-                            self.import_from_browser("edge");
-                        }
-                        if ui.button("🟣 Opera").clicked() {
-                            self.import_from_browser("opera");
-                        }
-                        if ui.button("🟠 Brave").clicked() {
-                            self.import_from_browser("brave");
+                    let label = egui::RichText::new(format!("{} {}", icon, text)).color(text_color);
+                    let button_style = if is_selected {
+                        egui::Button::new(label)
+                            .fill(ui.visuals().selection.bg_fill)
+                            .stroke(egui::Stroke::new(1.0, ui.visuals().selection.stroke.color))
+                    } else {
+                        egui::Button::new(label)
+                            .fill(egui::Color32::TRANSPARENT)
+                    };
+                    
+                    if ui.add(button_style).clicked() {
+                        self.current_tab = tab;
+                    }
+                };
+                
+                tab_button(ui, MainTab::Passwords, "🔑", "Passwords");
+                tab_button(ui, MainTab::Secrets, "🔐", "Secrets");
+                tab_button(ui, MainTab::Generator, "🎲", "Generator");
+                tab_button(ui, MainTab::Tools, "🛠", "Tools");
+                tab_button(ui, MainTab::Settings, "⚙", "Settings");
+                
+                // Add entry count badges
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    match self.current_tab {
+                        MainTab::Passwords => {
+                            ui.label(
+                                egui::RichText::new(format!("({} entries)", self.entries.len()))
+                                    .size(11.0)
+                                    .color(ui.visuals().weak_text_color())
+                            );
                         }
-                        if ui.button("🔵 Safari").clicked() {
-                            self.import_from_browser("safari");
+                        MainTab::Secrets => {
+                            ui.label(
+                                egui::RichText::new(format!("({} secrets)", self.secrets.len()))
+                                    .size(11.0)
+                                    .color(ui.visuals().weak_text_color())
+                            );
                         }
-                    });
-                    
-                    ui.add_space(10.0);
-                    ui.separator();
-                    
-                    ui.label("⚠ Note: Browser must be closed during import");
-                    ui.label("🔒 All imported passwords are encrypted with your master password");
-                    
-                    ui.add_space(15.0);
-                    
-                    if ui.button("❌ Cancel").clicked() {
-                        self.show_import = false;
+                        _ => {}
                     }
                 });
             });
+            ui.add_space(5.0);
+        });
+        
+        // Main content area
+        egui::CentralPanel::default().show(ctx, |ui| {
+            match self.current_tab {
+                MainTab::Passwords => self.show_passwords_tab(ui),
+                MainTab::Secrets => self.show_secrets_tab(ui),
+                MainTab::Generator => self.show_generator_tab(ui),
+                MainTab::Tools => self.show_tools_tab(ui),
+                MainTab::Settings => self.show_settings_tab(ui),
+            }
+        });
+        
+        // Show dialogs
+        self.show_entry_dialog(ctx);
+        self.show_generator_dialog(ctx);
+        self.show_settings_dialog(ctx);
+        self.show_about_dialog(ctx);
+        self.poll_import_job(ctx);
+        self.show_import_dialog(ctx);
+        self.show_backup_dialog(ctx);
+        self.show_backup_progress(ctx);
+        self.show_restore_conflicts_dialog(ctx);
+        self.show_statistics_dialog(ctx);
+        self.show_recently_used_dialog(ctx);
+        self.show_tag_edit_dialog(ctx);
+        self.show_bulk_delete_dialog(ctx);
+        self.show_reveal_confirm_dialog(ctx);
+        self.show_secrets_view(ctx);
+        self.poll_external_edit(ctx);
+        #[cfg(feature = "favicons")]
+        self.poll_site_icons(ctx);
+        self.show_add_secret_dialog(ctx);
+        self.show_draft_restore_dialog(ctx);
+        self.show_large_reveal_overlay(ctx);
+        self.show_integrity_dialog(ctx);
+        self.show_database_maintenance_dialog(ctx);
+        self.show_duplicate_audit_dialog(ctx);
+        self.show_duplicate_merge_dialog(ctx);
+        self.show_trash_dialog(ctx);
+        #[cfg(feature = "breach-check")]
+        self.show_breach_audit_dialog(ctx);
+        self.show_env_diff_dialog(ctx);
+        self.show_env_export_dialog(ctx);
+        self.show_document_view(ctx);
+        self.show_clipboard_notice(ctx);
+        self.show_password_change_dialog(ctx);
+        self.show_new_recovery_key(ctx);
+        self.autosave_drafts(ctx);
     }
     
-    fn show_backup_dialog(&mut self, ctx: &egui::Context) {
-        if !self.show_backup {
+    /// Right-click menu of a password entry, in the list and table views.
+    fn show_entry_context_menu(&mut self, ui: &mut egui::Ui, entry: &DecryptedPasswordEntry) {
+        ui.set_min_width(150.0);
+        if ui.button("📋 Copy Username").clicked() {
+            self.quick_copy_entry(entry, "username");
+            ui.close_menu();
+        }
+        if ui.button("🔑 Copy Password").clicked() {
+            self.quick_copy_entry(entry, "password");
+            ui.close_menu();
+        }
+        if entry.totp_secret.is_some() && ui.button("🔢 Copy 2FA Code").clicked() {
+            self.quick_copy_entry(entry, "totp");
+            ui.close_menu();
+        }
+        let has_url = entry.login_url().is_some();
+        if ui
+            .add_enabled(has_url, egui::Button::new("🌐 Open in Browser"))
+            .on_disabled_hover_text(NO_WEB_ADDRESS_HINT)
+            .clicked()
+        {
+            self.open_in_browser(entry);
+            ui.close_menu();
+        }
+        if ui
+            .add_enabled(has_url, egui::Button::new("🌐 Copy and Open Site"))
+            .on_disabled_hover_text(NO_WEB_ADDRESS_HINT)
+            .clicked()
+        {
+            self.copy_and_open(entry);
+            ui.close_menu();
+        }
+        if ui.button("🔍 Large Reveal").clicked() {
+            self.touch_entry(&entry.id);
+            self.large_reveal_sensitive(
+                &entry.password,
+                format!("Password for {}", entry.site),
+                &entry.tags,
+                entry.require_reauth,
+            );
+            ui.close_menu();
+        }
+        let favorite_label = if entry.favorite { "⭐ Remove from Favorites" } else { "⭐ Add to Favorites" };
+        if ui.button(favorite_label).clicked() {
+            self.toggle_favorite(entry);
+            ui.close_menu();
+        }
+        ui.separator();
+        if ui.button("✏ Edit").clicked() {
+            self.open_entry_editor(entry);
+            ui.close_menu();
+        }
+        if ui.button("🗑 Delete").clicked() {
+            self.delete_entry(&entry.id);
+            ui.close_menu();
+        }
+    }
+    
+    /// Fill the entry dialog with `entry` for editing.
+    fn open_entry_editor(&mut self, entry: &DecryptedPasswordEntry) {
+        self.edit_entry = Some(entry.clone());
+        self.entry_site = entry.site.clone();
+        self.entry_urls = entry.all_urls()[1..]
+            .iter()
+            .map(|url| url.to_string())
+            .collect();
+        self.entry_username = entry.username.clone();
+        self.entry_password = entry.password.clone();
+        self.entry_totp = entry.totp_secret.clone().unwrap_or_default();
+        self.entry_notes = entry.notes.clone().unwrap_or_default();
+        self.entry_tags = entry.tags.join(", ");
+        self.entry_require_reauth = entry.require_reauth;
+        self.entry_policy = entry.password_policy.clone();
+        self.show_add_dialog = true;
+    }
+    
+    /// Selection, double-click action, username tooltip and context menu of
+    /// the parts of an entry's row that select it.
+    fn handle_entry_row(&mut self, response: egui::Response, entry: &DecryptedPasswordEntry) {
+        let response = response.on_hover_text(format!("👤 {}", entry.username));
+        if response.clicked() {
+            self.selected_entry_id = Some(entry.id.clone());
+        }
+        if response.double_clicked() {
+            match self.gui_config.double_click {
+                DoubleClickAction::CopyPassword => self.quick_copy_entry(entry, "password"),
+                DoubleClickAction::CopyUsername => self.quick_copy_entry(entry, "username"),
+                DoubleClickAction::Open => self.open_entry_editor(entry),
+            }
+        }
+        response.context_menu(|ui| self.show_entry_context_menu(ui, entry));
+    }
+    
+    /// Tick box of an entry for the bulk actions.
+    fn show_entry_checkbox(&mut self, ui: &mut egui::Ui, id: &str) {
+        let mut checked = self.checked_entry_ids.contains(id);
+        if ui.checkbox(&mut checked, "").on_hover_text("Select for bulk actions").changed() {
+            if checked {
+                self.checked_entry_ids.insert(id.to_string());
+            } else {
+                self.checked_entry_ids.remove(id);
+            }
+        }
+    }
+    
+    /// Tag and delete actions for the ticked entries.
+    fn show_bulk_actions(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.strong(format!("{} selected", self.checked_entry_ids.len()));
+            ui.add(egui::TextEdit::singleline(&mut self.bulk_tag).hint_text("tag").desired_width(120.0));
+            let has_tag = !self.bulk_tag.trim().is_empty();
+            if ui.add_enabled(has_tag, egui::Button::new("🏷 Add tag")).clicked() {
+                self.bulk_edit_tag(true);
+            }
+            if ui.add_enabled(has_tag, egui::Button::new("Remove tag")).clicked() {
+                self.bulk_edit_tag(false);
+            }
+            if ui.button("🗑 Delete selected").clicked() {
+                self.confirm_bulk_delete = true;
+            }
+            if ui.button("Clear selection").clicked() {
+                self.checked_entry_ids.clear();
+            }
+        });
+        ui.separator();
+    }
+    
+    fn show_bulk_delete_dialog(&mut self, ctx: &egui::Context) {
+        if !self.confirm_bulk_delete {
             return;
         }
-        
-        egui::Window::new("💾 Backup & Restore")
+        let count = self.checked_entry_ids.len();
+        egui::Window::new("Delete Entries")
             .collapsible(false)
             .resizable(false)
-            .default_width(400.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
             .show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    ui.group(|ui| {
-                        ui.label("📤 Create Backup");
-                        ui.label("Export your encrypted vault to a backup file");
-                        ui.add_space(5.0);
-                        
-                        if ui.button("💾 Create Backup File").clicked() {
-                            self.create_backup();
-                        }
-                    });
-                    
-                    ui.add_space(10.0);
-                    
-                    ui.group(|ui| {
-                        ui.label("📥 Restore from Backup");
-                        ui.label("Import entries from a previously created backup");
-                        ui.add_space(5.0);
-                        
-                        if ui.button("📂 Select Backup File").clicked() {
-                            self.restore_backup();
-                        }
-                    });
-                    
-                    ui.add_space(10.0);
-                    ui.separator();
-                    
-                    ui.label("🔒 Backups are encrypted with your master password");
-                    ui.label("📁 Keep backups in a secure location");
-                    
-                    ui.add_space(15.0);
-                    
-                    if ui.button("❌ Close").clicked() {
-                        self.show_backup = false;
+                ui.label(format!(
+                    "Move {} selected entr{} to the trash?",
+                    count,
+                    if count == 1 { "y" } else { "ies" }
+                ));
+                ui.small("They can be restored from Tools → Trash.");
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button(format!("🗑 Move {} to Trash", count)).clicked() {
+                        self.confirm_bulk_delete = false;
+                        self.bulk_delete_entries();
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.confirm_bulk_delete = false;
                     }
                 });
             });
     }
     
-    fn import_from_browser(&mut self, browser: &str) {
-        // For now, show a file dialog to select CSV file from browser export
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("CSV Files", &["csv"])
-            .set_title(format!("Select {} password export file", browser))
-            .pick_file()
-        {
-            let storage_mutex = self.storage.clone();
-            let runtime = self.runtime.clone();
-            
-            self.error_message.clear();
-            
-            let result = runtime.block_on(async {
-                let storage_guard = storage_mutex.lock().unwrap();
-                if let Some(storage) = storage_guard.as_ref() {
-                    // Use the browser import functionality
-                    let config = pwgen_core::browser_import::ImportConfig {
-                        browser_type: match browser {
-                            "chrome" => pwgen_core::browser_import::BrowserType::Chrome,
-                            "firefox" => pwgen_core::browser_import::BrowserType::Firefox,
-                            "edge" => pwgen_core::browser_import::BrowserType::Edge,
-                            "opera" => pwgen_core::browser_import::BrowserType::Opera,
-                            "brave" => pwgen_core::browser_import::BrowserType::Brave,
-                            "safari" => pwgen_core::browser_import::BrowserType::Safari,
-                            _ => pwgen_core::browser_import::BrowserType::Chrome,
-                        },
-                        format: pwgen_core::browser_import::ImportFormat::Csv,
-                        skip_duplicates: true,
-                        merge_duplicates: false,
-                        import_folders_as_tags: true,
-                        default_tags: vec![format!("imported-{}", browser)],
-                        password_strength_check: false,
-                        cleanup_urls: true,
-                    };
-                    
-                    match pwgen_core::browser_import::BrowserImporter::import_from_file(path, config) {
-                        Ok((imported_passwords, _result)) => {
-                            // Create a simple config for conversion
-                            let convert_config = pwgen_core::browser_import::ImportConfig::default();
-                            match pwgen_core::browser_import::BrowserImporter::convert_to_entries(imported_passwords, &convert_config) {
-                                Ok(entries) => {
-                                    let mut imported_count = 0;
-                                    for entry in entries {
-                                        if storage.add_entry(&entry).await.is_ok() {
-                                            imported_count += 1;
-                                        }
-                                    }
-                                    Ok(imported_count)
-                                }
-                                Err(e) => Err(e.to_string())
-                            }
-                        }
-                        Err(e) => Err(e.to_string())
+    fn show_passwords_tab(&mut self, ui: &mut egui::Ui) {
+        if !self.checked_entry_ids.is_empty() {
+            self.show_bulk_actions(ui);
+        }
+        
+        if !self.is_compact_mode {
+            ui.horizontal(|ui| {
+                ui.label("View:");
+                let before = self.gui_config.password_view;
+                ui.selectable_value(&mut self.gui_config.password_view, PasswordView::List, "☰ List");
+                ui.selectable_value(&mut self.gui_config.password_view, PasswordView::Table, "▦ Table");
+                if self.gui_config.password_view != before {
+                    if let Err(e) = self.gui_config.save() {
+                        self.error_message = format!("Failed to save preferences: {}", e);
                     }
-                } else {
-                    Err("Storage not initialized".to_string())
+                }
+                
+                ui.separator();
+                ui.label("Sort:");
+                let mut field = self.gui_config.entry_sort.field;
+                egui::ComboBox::from_id_salt("entry_sort")
+                    .selected_text(sort_field_label(field))
+                    .show_ui(ui, |ui| {
+                        for option in [
+                            SortField::Site,
+                            SortField::Username,
+                            SortField::LastUsed,
+                            SortField::UpdatedAt,
+                            SortField::CreatedAt,
+                        ] {
+                            ui.selectable_value(&mut field, option, sort_field_label(option));
+                        }
+                    });
+                if field != self.gui_config.entry_sort.field {
+                    self.sort_entries_by(field);
                 }
             });
             
-            match result {
-                Ok(count) => {
-                    self.success_message = format!("Successfully imported {} passwords from {}", count, browser);
-                    self.load_entries();
-                    self.show_import = false;
-                }
-                Err(e) => {
-                    self.error_message = format!("Import failed: {}", e);
-                }
+            if self.gui_config.password_view == PasswordView::Table {
+                self.show_password_table(ui);
+                return;
             }
         }
-    }
-    
-    fn create_backup(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("JSON Backup", &["json"])
-            .set_file_name(format!("pwgen_backup_{}.json", chrono::Utc::now().format("%Y%m%d_%H%M%S")))
-            .save_file()
-        {
-            let storage_mutex = self.storage.clone();
-            let runtime = self.runtime.clone();
+        
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.spacing_mut().item_spacing.y = 2.0;
             
-            let result = runtime.block_on(async {
-                let storage_guard = storage_mutex.lock().unwrap();
-                if let Some(storage) = storage_guard.as_ref() {
-                    // Get all entries and serialize to JSON
-                    let filter = pwgen_core::models::SearchFilter {
-                        query: None,
-                        tags: None,
-                        favorite_only: false,
-                        sort_by: pwgen_core::models::SortField::Site,
-                        sort_order: pwgen_core::models::SortOrder::Ascending,
-                    };
+            let entries_to_display = self.filtered_entries.clone();
+            for entry in entries_to_display {
+                let is_selected = self.selected_entry_id.as_ref() == Some(&entry.id);
+                
+                ui.push_id(&entry.id, |ui| {
+                    let response = ui.add(
+                        egui::SelectableLabel::new(is_selected, "")
+                    );
+                    self.handle_entry_row(response, &entry);
                     
-                    match storage.search_entries(&filter).await {
-                        Ok(entries) => {
-                            let backup_data = serde_json::to_string_pretty(&entries)
-                                .map_err(|e| pwgen_core::Error::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+                    // Entry row layout - responsive
+                    if self.is_compact_mode {
+                        // Compact mode: stack info vertically with buttons on right
+                        ui.horizontal(|ui| {
+                            self.show_entry_checkbox(ui, &entry.id);
+                            ui.group(|ui| {
+                                ui.set_min_width(ui.available_width() - 80.0);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        self.show_favorite_button(ui, &entry);
+                                        #[cfg(feature = "favicons")]
+                                        self.show_site_icon(ui, &entry);
+                                        let site = ui.add(
+                                            egui::Label::new(egui::RichText::new(&entry.site).strong())
+                                                .sense(egui::Sense::click()),
+                                        );
+                                        self.handle_entry_row(site, &entry);
+                                    });
+                                    ui.small(&entry.username);
+                                    if is_selected {
+                                        if let Some(attribution) = attribution_text(&entry.created_by, &entry.modified_by) {
+                                            ui.small(egui::RichText::new(attribution).weak());
+                                        }
+                                        self.show_totp_code(ui, &entry);
+                                        self.show_password_history(ui, &entry);
+                                    }
+                                    if !entry.tags.is_empty() && entry.tags.len() <= 2 {
+                                        ui.horizontal(|ui| {
+                                            for tag in entry.tags.iter().take(2) {
+                                                ui.small(format!("🏷{}", tag));
+                                            }
+                                            if entry.tags.len() > 2 {
+                                                ui.small("...");
+                                            }
+                                            if ui.small_button("✏").on_hover_text("Edit tags").clicked() {
+                                                self.editing_tags_for_entry = Some(entry.id.clone());
+                                                self.temp_tags = entry.tags.join(", ");
+                                            }
+                                        });
+                                    } else if entry.tags.is_empty() {
+                                        ui.horizontal(|ui| {
+                                            ui.small("🏷No tags");
+                                            if ui.small_button("✏").on_hover_text("Add tags").clicked() {
+                                                self.editing_tags_for_entry = Some(entry.id.clone());
+                                                self.temp_tags = String::new();
+                                            }
+                                        });
+                                    }
+                                });
+                            });
                             
-                            std::fs::write(&path, backup_data)
-                                .map_err(pwgen_core::Error::Io)?;
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui
+                                    .add_enabled(entry.login_url().is_some(), egui::Button::new("🌐").small())
+                                    .on_hover_text("Open site in browser (copies as set in Settings)")
+                                    .on_disabled_hover_text(NO_WEB_ADDRESS_HINT)
+                                    .clicked()
+                                {
+                                    self.copy_and_open(&entry);
+                                }
+                                if ui.small_button("🔑").on_hover_text("Copy password").clicked() {
+                                    self.quick_copy_entry(&entry, "password");
+                                }
+                                if ui.small_button("👤").on_hover_text("Copy username").clicked() {
+                                    self.quick_copy_entry(&entry, "username");
+                                }
+                            });
+                        });
+                    } else {
+                        // Wide mode: full horizontal layout
+                        ui.horizontal(|ui| {
+                            self.show_entry_checkbox(ui, &entry.id);
+                            ui.group(|ui| {
+                                ui.set_min_width(ui.available_width() - 120.0);
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        self.show_favorite_button(ui, &entry);
+                                        #[cfg(feature = "favicons")]
+                                        self.show_site_icon(ui, &entry);
+                                        let site = ui.add(
+                                            egui::Label::new(egui::RichText::new(&entry.site).strong())
+                                                .sense(egui::Sense::click()),
+                                        );
+                                        self.handle_entry_row(site, &entry);
+                                    });
+                                    ui.label(&entry.username);
+                                    if is_selected {
+                                        if let Some(attribution) = attribution_text(&entry.created_by, &entry.modified_by) {
+                                            ui.small(egui::RichText::new(attribution).weak());
+                                        }
+                                        self.show_totp_code(ui, &entry);
+                                        self.show_password_history(ui, &entry);
+                                    }
+                                    if !entry.tags.is_empty() {
+                                        ui.horizontal(|ui| {
+                                            for tag in &entry.tags {
+                                                ui.small(format!("🏷{}", tag));
+                                            }
+                                            if ui.small_button("✏").on_hover_text("Edit tags").clicked() {
+                                                self.editing_tags_for_entry = Some(entry.id.clone());
+                                                self.temp_tags = entry.tags.join(", ");
+                                            }
+                                        });
+                                    } else {
+                                        ui.horizontal(|ui| {
+                                            ui.small("🏷No tags");
+                                            if ui.small_button("✏").on_hover_text("Add tags").clicked() {
+                                                self.editing_tags_for_entry = Some(entry.id.clone());
+                                                self.temp_tags = String::new();
+                                            }
+                                        });
+                                    }
+                                });
+                            });
                             
-                            Ok(())
-                        }
-                        Err(e) => Err(e)
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui
+                                    .add_enabled(entry.login_url().is_some(), egui::Button::new("🌐").small())
+                                    .on_hover_text("Open site in browser (copies as set in Settings)")
+                                    .on_disabled_hover_text(NO_WEB_ADDRESS_HINT)
+                                    .clicked()
+                                {
+                                    self.copy_and_open(&entry);
+                                }
+                                if ui.small_button("🔑").on_hover_text("Copy password").clicked() {
+                                    self.quick_copy_entry(&entry, "password");
+                                }
+                                if ui.small_button("👤").on_hover_text("Copy username").clicked() {
+                                    self.quick_copy_entry(&entry, "username");
+                                }
+                            });
+                        });
                     }
-                } else {
-                    Err(pwgen_core::Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "Storage not initialized")))
-                }
-            });
-            
-            match result {
-                Ok(_) => {
-                    self.success_message = "Backup created successfully!".to_string();
-                    self.show_backup = false;
-                }
-                Err(e) => {
-                    self.error_message = format!("Backup failed: {}", e);
-                }
+                });
             }
-        }
+        });
     }
     
-    fn restore_backup(&mut self) {
-        if let Some(path) = rfd::FileDialog::new()
-            .add_filter("JSON Backup", &["json"])
-            .pick_file()
-        {
-            let storage_mutex = self.storage.clone();
-            let runtime = self.runtime.clone();
-            
-            let result = runtime.block_on(async {
-                let storage_guard = storage_mutex.lock().unwrap();
-                if let Some(storage) = storage_guard.as_ref() {
-                    // Read and deserialize the backup file
-                    let backup_data = std::fs::read_to_string(&path)
-                        .map_err(pwgen_core::Error::Io)?;
-                    
-                    let entries: Vec<pwgen_core::models::DecryptedPasswordEntry> = serde_json::from_str(&backup_data)
-                        .map_err(|e| pwgen_core::Error::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())))?;
-                    
-                    let mut imported_count = 0;
-                    for entry in entries {
-                        if storage.add_entry(&entry).await.is_ok() {
-                            imported_count += 1;
+    /// The current page of entries as a table; the Site, Username and Last
+    /// Used headers sort the whole list.
+    fn show_password_table(&mut self, ui: &mut egui::Ui) {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("password_table")
+                .num_columns(6)
+                .striped(true)
+                .spacing([16.0, 4.0])
+                .show(ui, |ui| {
+                    // Ticks or clears the whole page
+                    let page_ids: Vec<String> = self.filtered_entries.iter().map(|e| e.id.clone()).collect();
+                    let mut all_checked = !page_ids.is_empty() && page_ids.iter().all(|id| self.checked_entry_ids.contains(id));
+                    if ui.checkbox(&mut all_checked, "").on_hover_text("Select all on this page").changed() {
+                        if all_checked {
+                            self.checked_entry_ids.extend(page_ids);
+                        } else {
+                            for id in &page_ids {
+                                self.checked_entry_ids.remove(id);
+                            }
                         }
                     }
+                    self.sort_header(ui, "Site", SortField::Site);
+                    self.sort_header(ui, "Username", SortField::Username);
+                    ui.strong("Tags");
+                    self.sort_header(ui, "Last Used", SortField::LastUsed);
+                    ui.strong("Strength");
+                    ui.end_row();
                     
-                    Ok(imported_count)
-                } else {
-                    Err(pwgen_core::Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "Storage not initialized")))
-                }
-            });
-            
-            match result {
-                Ok(count) => {
-                    self.success_message = format!("Successfully restored {} entries from backup!", count);
-                    self.load_entries();
-                    self.show_backup = false;
-                }
-                Err(e) => {
-                    self.error_message = format!("Restore failed: {}", e);
-                }
-            }
+                    for entry in self.filtered_entries.clone() {
+                        let is_selected = self.selected_entry_id.as_ref() == Some(&entry.id);
+                        self.show_entry_checkbox(ui, &entry.id);
+                        let response = ui
+                            .push_id(&entry.id, |ui| {
+                                ui.horizontal(|ui| {
+                                    self.show_favorite_button(ui, &entry);
+                                    #[cfg(feature = "favicons")]
+                                    self.show_site_icon(ui, &entry);
+                                    ui.selectable_label(is_selected, &entry.site)
+                                })
+                                .inner
+                            })
+                            .inner;
+                        self.handle_entry_row(response, &entry);
+                        
+                        ui.label(&entry.username);
+                        ui.label(entry.tags.join(", "));
+                        match entry.last_used {
+                            Some(last_used) => ui.label(last_used.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string()),
+                            None => ui.weak("Never"),
+                        };
+                        let level = estimate_strength(&entry.password).level;
+                        ui.colored_label(Self::strength_color(level, ui.visuals().dark_mode), level.label());
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+    
+    /// A column header that sorts by `field`, or reverses the order when it
+    /// already does.
+    fn sort_header(&mut self, ui: &mut egui::Ui, label: &str, field: SortField) {
+        let sort = self.gui_config.entry_sort;
+        let text = match (sort.field == field, sort.order) {
+            (true, SortOrder::Ascending) => format!("{} ⬆", label),
+            (true, SortOrder::Descending) => format!("{} ⬇", label),
+            (false, _) => label.to_string(),
+        };
+        if ui.add(egui::Button::new(egui::RichText::new(text).strong()).frame(false)).clicked() {
+            self.sort_entries_by(field);
         }
     }
     
-    fn show_tag_edit_dialog(&mut self, ctx: &egui::Context) {
-        if let Some(entry_id) = &self.editing_tags_for_entry.clone() {
-            egui::Window::new("Edit Tags")
-                .collapsible(false)
-                .resizable(false)
-                .default_width(300.0)
-                .show(ctx, |ui| {
-                    ui.vertical(|ui| {
-                        ui.label("Tags (comma-separated):");
-                        let response = ui.add(
-                            egui::TextEdit::singleline(&mut self.temp_tags)
-                                .desired_width(ui.available_width() - 20.0)
-                                .hint_text("tag1, tag2, tag3...")
-                        );
-                        
-                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                            self.save_tags(entry_id.clone());
-                        }
-                        
-                        ui.add_space(10.0);
-                        
-                        ui.horizontal(|ui| {
-                            if ui.button("Save").clicked() {
-                                self.save_tags(entry_id.clone());
-                            }
-                            if ui.button("Cancel").clicked() {
-                                self.editing_tags_for_entry = None;
-                                self.temp_tags.clear();
-                            }
-                        });
-                    });
-                });
+    fn sort_entries_by(&mut self, field: SortField) {
+        let sort = &mut self.gui_config.entry_sort;
+        sort.order = match (sort.field == field, sort.order) {
+            (true, SortOrder::Ascending) => SortOrder::Descending,
+            (true, SortOrder::Descending) => SortOrder::Ascending,
+            // Most recent first
+            (false, _) if matches!(field, SortField::LastUsed | SortField::UpdatedAt | SortField::CreatedAt) => {
+                SortOrder::Descending
+            }
+            (false, _) => SortOrder::Ascending,
+        };
+        sort.field = field;
+        if let Err(e) = self.gui_config.save() {
+            self.error_message = format!("Failed to save preferences: {}", e);
         }
+        self.load_entries();
     }
     
-    fn save_tags(&mut self, entry_id: String) {
-        let storage_mutex = self.storage.clone();
-        let runtime = self.runtime.clone();
-        
-        // Parse tags from temp_tags
-        let new_tags: Vec<String> = self.temp_tags
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
+    fn show_secrets_tab(&mut self, ui: &mut egui::Ui) {
+        #[cfg(feature = "connection-test")]
+        self.poll_connection_tests(ui.ctx());
         
-        // Find and update the entry
-        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == entry_id) {
-            entry.tags = new_tags.clone();
-            entry.updated_at = chrono::Utc::now();
-            
-            // Save to storage
-            let entry_to_save = entry.clone();
-            runtime.block_on(async {
-                let storage_guard = storage_mutex.lock().unwrap();
-                if let Some(storage) = storage_guard.as_ref() {
-                    let _ = storage.update_entry(&entry_to_save).await;
+        ui.horizontal(|ui| {
+            ui.heading("🔐 Secrets Manager");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("➕ Add Secret").clicked() {
+                    if self.edit_secret.is_some() {
+                        self.clear_secret_form();
+                    }
+                    self.show_add_secret_dialog = true;
+                    self.selected_secret_type = SecretType::ApiKey;
                 }
             });
-            
-            self.success_message = "Tags updated successfully!".to_string();
-            self.filter_entries(); // Refresh the display
-        }
+        });
         
-        // Clear editing state
-        self.editing_tags_for_entry = None;
-        self.temp_tags.clear();
-    }
-    
-    fn show_secrets_view(&mut self, ctx: &egui::Context) {
-        if !self.show_secrets_view {
-            return;
+        ui.separator();
+        
+        let api_keys_label = if self.rotation_due > 0 {
+            format!("API Keys ⚠{}", self.rotation_due)
+        } else {
+            "API Keys".to_string()
+        };
+        
+        // Secrets filter tabs
+        ui.horizontal(|ui| {
+            let mut secret_tab_button = |ui: &mut egui::Ui, secret_type: SecretType, icon: &str, text: &str| {
+                let is_selected = self.current_secret_tab == secret_type;
+                let button_style = if is_selected {
+                    egui::Button::new(format!("{} {}", icon, text))
+                        .fill(ui.visuals().selection.bg_fill)
+                } else {
+                    egui::Button::new(format!("{} {}", icon, text))
+                        .fill(egui::Color32::TRANSPARENT)
+                };
+                
+                if ui.add(button_style).clicked() {
+                    self.current_secret_tab = secret_type;
+                }
+            };
+            
+            secret_tab_button(ui, SecretType::ApiKey, "🔑", &api_keys_label);
+            secret_tab_button(ui, SecretType::SshKey, "🔐", "SSH Keys");
+            secret_tab_button(ui, SecretType::Document, "📄", "Documents");
+            secret_tab_button(ui, SecretType::Configuration, "⚙", "Config");
+            secret_tab_button(ui, SecretType::SecureNote, "📝", "Notes");
+            secret_tab_button(ui, SecretType::CreditCard, "💳", "Cards");
+            secret_tab_button(ui, SecretType::ConnectionString, "🗄", "Database");
+        });
+        
+        if self.current_secret_tab == SecretType::Configuration
+            && self.secrets.iter().filter(|s| s.secret_type == SecretType::Configuration).count() >= 2
+            && ui
+                .button("🔀 Compare environments")
+                .on_hover_text("Show variables that are added, missing or different between two sets")
+                .clicked()
+        {
+            self.open_env_diff();
         }
         
-        egui::Window::new("🔐 Secrets Manager")
-            .collapsible(false)
-            .resizable(true)
-            .default_size([800.0, 600.0])
-            .show(ctx, |ui| {
-                ui.vertical(|ui| {
-                    // Header with close button
-                    ui.horizontal(|ui| {
-                        ui.heading("Secrets Manager");
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            if ui.button("✖ Close").clicked() {
-                                self.show_secrets_view = false;
+        self.show_secret_filters(ui);
+        
+        if self.secret_page_type.as_ref() != Some(&self.current_secret_tab) {
+            self.secret_current_page = 0;
+            self.load_secret_page();
+        }
+        
+        if self.secret_total_pages > 1 {
+            ui.horizontal(|ui| {
+                ui.label("📄");
+                
+                if ui.add_enabled(self.secret_current_page > 0, egui::Button::new("◀")).clicked() {
+                    self.secret_current_page -= 1;
+                    self.load_secret_page();
+                }
+                
+                ui.label(format!("{}/{}", self.secret_current_page + 1, self.secret_total_pages));
+                
+                if ui.add_enabled(self.secret_current_page < self.secret_total_pages - 1, egui::Button::new("▶")).clicked() {
+                    self.secret_current_page += 1;
+                    self.load_secret_page();
+                }
+                
+                ui.separator();
+                ui.label("Per page:");
+                egui::ComboBox::from_id_salt("secrets_per_page")
+                    .selected_text(format!("{}", self.secrets_per_page))
+                    .width(80.0)
+                    .show_ui(ui, |ui| {
+                        for &size in &[25, 50, 100, 200] {
+                            if ui.selectable_value(&mut self.secrets_per_page, size, format!("{}", size)).clicked() {
+                                self.secret_current_page = 0;
+                                self.load_secret_page();
                             }
+                        }
+                    });
+            });
+        }
+        
+        ui.separator();
+        
+        // Secrets list
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.spacing_mut().item_spacing.y = 4.0;
+            
+            let filtered_secrets = self.secret_page.clone();
+            
+            if filtered_secrets.is_empty() {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(50.0);
+                    ui.label(format!("📭 No {} found", match self.current_secret_tab {
+                        SecretType::ApiKey => "API keys",
+                        SecretType::SshKey => "SSH keys", 
+                        SecretType::Document => "documents",
+                        SecretType::Configuration => "configurations",
+                        SecretType::SecureNote => "secure notes",
+                        SecretType::CreditCard => "cards",
+                        SecretType::ConnectionString => "database connections",
+                        _ => "secrets",
+                    }));
+                    if self.secret_filter().is_some() {
+                        ui.small("Nothing matches the search and filters");
+                    } else {
+                        ui.small("Click 'Add Secret' to create your first secret");
+                    }
+                });
+            } else {
+                let mut action = None;
+                let mut field_copy = None;
+                
+                for secret in &filtered_secrets {
+                    let response = ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.vertical(|ui| {
+                                ui.strong(&secret.name);
+                                if let Some(desc) = &secret.description {
+                                    ui.small(desc);
+                                }
+                                if let Some(attribution) =
+                                    attribution_text(&secret.metadata.created_by, &secret.metadata.modified_by)
+                                {
+                                    ui.small(egui::RichText::new(attribution).weak());
+                                }
+                                if let SecretData::CreditCard { number, expiry_month, expiry_year, .. } = &secret.data {
+                                    ui.horizontal(|ui| {
+                                        let revealed = self.revealed_card.as_deref() == Some(secret.id.as_str());
+                                        let shown = if revealed { number.clone() } else { mask_card_number(number) };
+                                        ui.small(format!(
+                                            "{} {} · exp {:02}/{}",
+                                            CardBrand::from_number(number),
+                                            shown,
+                                            expiry_month,
+                                            expiry_year % 100
+                                        ));
+                                        if ui.small_button(if revealed { "🙈" } else { "👁" }).clicked() {
+                                            action = Some(("reveal_card", secret.clone()));
+                                        }
+                                    });
+                                }
+                                #[cfg(feature = "connection-test")]
+                                if secret.secret_type == SecretType::ConnectionString {
+                                    self.show_connection_test_status(ui, &secret.id);
+                                }
+                                if let Some(rotation) = ApiKeyManager::rotation_status(secret) {
+                                    let due = rotation.due.format("%Y-%m-%d");
+                                    if rotation.overdue {
+                                        ui.colored_label(error_color(ui), format!("🔄 Rotation overdue since {}", due));
+                                    } else if rotation.reminder {
+                                        ui.small(format!("🔄 Rotate by {} ({} days left)", due, rotation.days_remaining));
+                                    }
+                                }
+                                if !secret.tags.is_empty() {
+                                    ui.horizontal(|ui| {
+                                        for tag in secret.tags.iter().take(3) {
+                                            ui.small(format!("🏷{}", tag));
+                                        }
+                                        if secret.tags.len() > 3 {
+                                            ui.small("...");
+                                        }
+                                    });
+                                }
+                            });
+                            
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.small_button("📋").on_hover_text("Copy (right-click for other fields)").clicked() {
+                                    action = Some(("copy", secret.clone()));
+                                }
+                                if let Some(snippet) = command_snippet(&secret.data, false) {
+                                    if ui
+                                        .small_button("⌨")
+                                        .on_hover_text(format!("Copy as {} command:\n{}", snippet.tool, snippet.command))
+                                        .clicked()
+                                    {
+                                        action = Some(("copy_command", secret.clone()));
+                                    }
+                                }
+                                if secret.secret_type == SecretType::Configuration
+                                    && ui.small_button("📤").on_hover_text("Export .env").clicked()
+                                {
+                                    action = Some(("export_env", secret.clone()));
+                                }
+                                if secret.secret_type == SecretType::Document
+                                    && ui.small_button("👁").on_hover_text("View").clicked()
+                                {
+                                    action = Some(("view_document", secret.clone()));
+                                }
+                                if matches!(secret.data, SecretData::SshKey { private_key: Some(_), .. })
+                                    && ui.small_button("🔑").on_hover_text("Add to agent").clicked()
+                                {
+                                    action = Some(("add_to_agent", secret.clone()));
+                                }
+                                #[cfg(feature = "connection-test")]
+                                if secret.secret_type == SecretType::ConnectionString
+                                    && ui
+                                        .add_enabled(
+                                            !matches!(
+                                                self.connection_tests.get(&secret.id),
+                                                Some(ConnectionTest::Running(_))
+                                            ),
+                                            egui::Button::new("🔌").small(),
+                                        )
+                                        .on_hover_text("Test the connection")
+                                        .clicked()
+                                {
+                                    action = Some(("test_connection", secret.clone()));
+                                }
+                                if ui.small_button("✏").on_hover_text("Edit").clicked() {
+                                    action = Some(("edit", secret.clone()));
+                                }
+                                if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                                    action = Some(("delete", secret.clone()));
+                                }
+                            });
                         });
+                    }).response;
+                    response.context_menu(|ui| {
+                        ui.set_min_width(150.0);
+                        let fields = secret_copy_fields(&secret.data);
+                        if fields.is_empty() {
+                            ui.weak("Nothing to copy");
+                        }
+                        for (label, value) in fields {
+                            if ui.button(format!("📋 Copy {}", label)).clicked() {
+                                field_copy = Some((secret.clone(), label, value));
+                                ui.close_menu();
+                            }
+                        }
                     });
+                    ui.add_space(2.0);
+                }
+                
+                if let Some((secret, label, value)) = field_copy {
+                    self.copy_sensitive(
+                        &value,
+                        format!("{} for {}", label, secret.name),
+                        &secret.tags,
+                        secret.metadata.require_reauth,
+                    );
+                }
+                
+                // Handle actions outside the loop to avoid borrowing issues
+                if let Some((action_type, secret)) = action {
+                    match action_type {
+                        "copy" => self.copy_secret_data(&secret),
+                        "copy_command" => self.copy_secret_command(&secret),
+                        "reveal_card" => self.toggle_card_reveal(&secret),
+                        #[cfg(feature = "connection-test")]
+                        "test_connection" => self.start_connection_test(secret),
+                        "export_env" => self.open_env_export(secret),
+                        "view_document" => self.view_document(secret),
+                        "add_to_agent" => self.add_ssh_key_to_agent(&secret),
+                        "edit" => self.start_secret_edit(secret),
+                        "delete" => self.delete_secret(&secret.id),
+                        _ => {}
+                    }
+                }
+            }
+        });
+    }
+    
+    fn show_generator_tab(&mut self, ui: &mut egui::Ui) {
+        ui.heading("🎲 Password Generator");
+        ui.separator();
+        
+        ui.horizontal(|ui| {
+            ui.vertical(|ui| {
+                ui.set_width(250.0);
+                
+                ui.group(|ui| {
+                    ui.label("🔧 Settings");
                     
-                    ui.separator();
+                    self.show_generator_mode(ui);
                     
-                    // Tab bar for different secret types
-                    ui.horizontal(|ui| {
-                        ui.label("Type:");
-                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::ApiKey), "🔑 API Keys").clicked() {
-                            self.current_secret_tab = SecretType::ApiKey;
-                        }
-                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::SshKey), "🔐 SSH Keys").clicked() {
-                            self.current_secret_tab = SecretType::SshKey;
-                        }
-                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::Document), "📄 Documents").clicked() {
-                            self.current_secret_tab = SecretType::Document;
-                        }
-                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::Configuration), "⚙ Config/Env").clicked() {
-                            self.current_secret_tab = SecretType::Configuration;
-                        }
-                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::SecureNote), "📝 Secure Notes").clicked() {
-                            self.current_secret_tab = SecretType::SecureNote;
-                        }
-                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::ConnectionString), "🔗 Database").clicked() {
-                            self.current_secret_tab = SecretType::ConnectionString;
+                    ui.add_enabled_ui(self.gen_mode == GeneratorMode::Characters, |ui| {
+                        egui::Grid::new("generator_settings").num_columns(2).show(ui, |ui| {
+                            ui.label("Length:");
+                            ui.add(egui::Slider::new(&mut self.gen_length, 4..=128));
+                            ui.end_row();
+                            
+                            ui.label("Uppercase:");
+                            ui.checkbox(&mut self.gen_uppercase, "A-Z");
+                            ui.end_row();
+                            
+                            ui.label("Lowercase:");
+                            ui.checkbox(&mut self.gen_lowercase, "a-z");
+                            ui.end_row();
+                            
+                            ui.label("Numbers:");
+                            ui.checkbox(&mut self.gen_numbers, "0-9");
+                            ui.end_row();
+                            
+                            ui.label("Symbols:");
+                            ui.checkbox(&mut self.gen_symbols, "!@#$");
+                            ui.end_row();
+                            
+                            ui.label("Exclude ambiguous:");
+                            ui.checkbox(&mut self.gen_exclude_ambiguous, "0O1lI");
+                            ui.end_row();
+                            
+                            ui.label("Mobile-friendly:");
+                            ui.checkbox(&mut self.gen_mobile_friendly, PasswordGenerator::MOBILE_SYMBOLS)
+                                .on_hover_text("Easy-to-type symbols, capitals grouped at the start");
+                            ui.end_row();
+                        });
+                    
+                        if self.gen_mobile_friendly {
+                            ui.small(self.mobile_entropy_summary());
                         }
                     });
                     
                     ui.separator();
                     
-                    // Add new secret button
-                    if ui.button(format!("➕ Add New {}", match self.current_secret_tab {
-                        SecretType::ApiKey => "API Key",
-                        SecretType::SshKey => "SSH Key", 
-                        SecretType::Document => "Document",
-                        SecretType::Configuration => "Configuration",
-                        SecretType::SecureNote => "Secure Note",
-                        SecretType::ConnectionString => "Database Connection",
-                        _ => "Secret",
-                    })).clicked() {
-                        self.selected_secret_type = self.current_secret_tab.clone();
-                        self.show_add_secret_dialog = true;
+                    if ui.button("🎲 Generate New Password").clicked() {
+                        self.generate_password();
                     }
-                    
-                    ui.add_space(10.0);
-                    
-                    // Secrets list for current tab
-                    egui::ScrollArea::vertical().show(ui, |ui| {
-                        ui.label("Secrets list will be implemented next...");
-                        
-                        // Placeholder for now - we'll implement the actual list next
-                        match self.current_secret_tab {
-                            SecretType::ApiKey => {
-                                ui.label("API Keys will be listed here");
-                            }
-                            SecretType::SshKey => {
-                                ui.label("SSH Keys will be listed here");
-                            }
-                            SecretType::Document => {
-                                ui.label("Documents will be listed here");
-                            }
-                            SecretType::Configuration => {
-                                ui.label("Environment variables and config will be listed here");
-                            }
-                            SecretType::SecureNote => {
-                                ui.label("Secure notes will be listed here");
-                            }
-                            SecretType::ConnectionString => {
-                                ui.label("Database connections will be listed here");
+                    if ui.checkbox(&mut self.gui_config.gen_for_new_entries, "Use for new entries")
+                        .on_hover_text(GEN_FOR_NEW_ENTRIES_HINT)
+                        .changed()
+                    {
+                        self.save_gui_config();
+                    }
+                });
+            });
+            
+            ui.separator();
+            
+            ui.vertical(|ui| {
+                ui.label("🔑 Generated Password:");
+                ui.add_space(5.0);
+                
+                if !self.generated_password.is_empty() {
+                    ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.generated_password)
+                                    .desired_width(300.0)
+                                    .password(self.generated_masked)
+                                    .font(egui::TextStyle::Monospace)
+                            );
+                            if self.generated_masked && ui.button("👁").on_hover_text("Show password").clicked() {
+                                self.generated_masked = false;
                             }
-                            _ => {
-                                ui.label("Other secrets will be listed here");
+                            if ui.button("📋 Copy").clicked() {
+                                let password = self.generated_password.clone();
+                                self.copy_to_clipboard(&password);
+                                self.success_message = self.copied_message("Password");
                             }
-                        }
+                        });
                     });
-                });
-            });
-    }
+                    
+                    ui.add_space(10.0);
+                    
+                    // Password strength indicator, held to the generator's
+                    // entropy for memorable passwords
+                    let report = estimate_strength(&self.generated_password);
+                    let report = match &self.generated_entropy {
+                        Some((password, bits)) if *password == self.generated_password => report.capped_at_entropy(*bits),
+                        _ => report,
+                    };
+                    Self::show_strength_report(ui, &report);
+                } else {
+                    ui.label("Generate a password to see it here");
+                }
+            });
+        });
+    }
+    
+    fn show_tools_tab(&mut self, ui: &mut egui::Ui) {
+        ui.heading("🛠 Tools & Utilities");
+        ui.separator();
+        ui.add_space(10.0);
+        
+        // Main Tools Grid - 2x3 layout for better space usage
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("main_tools_grid")
+                .num_columns(2)
+                .spacing([30.0, 20.0])
+                .min_col_width(350.0)
+                .show(ui, |ui| {
+                    
+                    // Data Management Card
+                    ui.group(|ui| {
+                        ui.set_min_size(egui::vec2(350.0, 200.0));
+                        ui.vertical(|ui| {
+                            ui.heading("📦 Data Management");
+                            ui.separator();
+                            ui.add_space(10.0);
+                            
+                            // Import section
+                            ui.horizontal(|ui| {
+                                ui.label("📥");
+                                ui.vertical(|ui| {
+                                    if ui.button("Import from Browser").clicked() {
+                                        self.show_import = true;
+                                    }
+                                    ui.small("Chrome, Firefox, Safari, Edge...");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Export section
+                            ui.horizontal(|ui| {
+                                ui.label("📤");
+                                ui.vertical(|ui| {
+                                    if ui.button("Create Backup").clicked() {
+                                        self.show_backup = true;
+                                    }
+                                    ui.small("Encrypted vault backup");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Sync section
+                            ui.horizontal(|ui| {
+                                ui.label("🔄");
+                                ui.vertical(|ui| {
+                                    ui.add_enabled(false, egui::Button::new("Cloud Sync"));
+                                    ui.small("Coming soon - sync across devices");
+                                });
+                            });
+                        });
+                    });
+                    
+                    // Analysis & Security Card
+                    ui.group(|ui| {
+                        ui.set_min_size(egui::vec2(350.0, 200.0));
+                        ui.vertical(|ui| {
+                            ui.heading("🔍 Analysis & Security");
+                            ui.separator();
+                            ui.add_space(10.0);
+                            
+                            // Statistics section
+                            ui.horizontal(|ui| {
+                                ui.label("📊");
+                                ui.vertical(|ui| {
+                                    if ui.button("Vault Statistics").clicked() {
+                                        self.open_statistics();
+                                    }
+                                    ui.small(format!("{} passwords • {} secrets", self.entries.len(), self.secrets.len()));
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            ui.horizontal(|ui| {
+                                ui.label("🕘");
+                                ui.vertical(|ui| {
+                                    if ui.button("Recently Used").clicked() {
+                                        self.show_recently_used = true;
+                                    }
+                                    ui.small("Entries you copied or opened last");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Security audit section
+                            ui.horizontal(|ui| {
+                                ui.label("🛡");
+                                ui.vertical(|ui| {
+                                    #[cfg(feature = "breach-check")]
+                                    if ui.button("Breach Check").clicked() && self.breach_audit.is_none() {
+                                        self.breach_audit = Some(BreachAudit::Confirm);
+                                    }
+                                    #[cfg(not(feature = "breach-check"))]
+                                    ui.add_enabled(false, egui::Button::new("Breach Check"))
+                                        .on_disabled_hover_text("This build was made without the breach-check feature");
+                                    ui.small("Find passwords exposed in known breaches");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Duplicate detection
+                            ui.horizontal(|ui| {
+                                ui.label("🔍");
+                                ui.vertical(|ui| {
+                                    if ui.button("Find Duplicates").clicked() {
+                                        self.start_duplicate_audit();
+                                    }
+                                    ui.small("Identify duplicate passwords");
+                                });
+                            });
+                        });
+                    });
+                    
+                    ui.end_row();
+                    
+                    // Vault Management Card
+                    ui.group(|ui| {
+                        ui.set_min_size(egui::vec2(350.0, 200.0));
+                        ui.vertical(|ui| {
+                            ui.heading("🔐 Vault Management");
+                            ui.separator();
+                            ui.add_space(10.0);
+                            
+                            // Reload data
+                            ui.horizontal(|ui| {
+                                ui.label("🔄");
+                                ui.vertical(|ui| {
+                                    if ui.button("Reload Data").clicked() {
+                                        self.load_entries();
+                                        self.success_message = "Data reloaded successfully".to_string();
+                                    }
+                                    ui.small("Refresh entries and secrets");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Lock vault
+                            ui.horizontal(|ui| {
+                                ui.label("🔒");
+                                ui.vertical(|ui| {
+                                    if ui.button("Lock Vault").clicked() {
+                                        self.lock_vault();
+                                    }
+                                    ui.small("Secure and close vault");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Verify integrity
+                            ui.horizontal(|ui| {
+                                ui.label("🩺");
+                                ui.vertical(|ui| {
+                                    if ui.button("Verify Integrity").clicked() {
+                                        self.verify_integrity();
+                                    }
+                                    ui.small("Check every item decrypts and validates");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Database maintenance
+                            ui.horizontal(|ui| {
+                                ui.label("🔧");
+                                ui.vertical(|ui| {
+                                    if ui.button("Database Repair").clicked() {
+                                        self.start_database_maintenance();
+                                    }
+                                    ui.small("Check the database file and reclaim unused space");
+                                });
+                            });
+                        });
+                    });
+                    
+                    // Utilities & Cleanup Card
+                    ui.group(|ui| {
+                        ui.set_min_size(egui::vec2(350.0, 200.0));
+                        ui.vertical(|ui| {
+                            ui.heading("🧹 Utilities & Cleanup");
+                            ui.separator();
+                            ui.add_space(10.0);
+                            
+                            // Clean duplicates
+                            ui.horizontal(|ui| {
+                                ui.label("🗑");
+                                ui.vertical(|ui| {
+                                    if ui.button("Flag Reused Passwords").clicked() {
+                                        self.start_duplicate_audit();
+                                    }
+                                    ui.small("Keep one entry per password, tag the others");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            ui.horizontal(|ui| {
+                                ui.label("🔗");
+                                ui.vertical(|ui| {
+                                    if ui.button("Remove Duplicates").clicked() {
+                                        self.plan_duplicate_merge(MergeStrategy::SamePassword);
+                                    }
+                                    ui.small("Merge entries for the same site and username");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Deleted items
+                            ui.horizontal(|ui| {
+                                ui.label("♻");
+                                ui.vertical(|ui| {
+                                    if ui.button("Trash").clicked() {
+                                        self.open_trash();
+                                    }
+                                    ui.small("Restore deleted entries and secrets");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Fix tags
+                            ui.horizontal(|ui| {
+                                ui.label("🏷");
+                                ui.vertical(|ui| {
+                                    if ui.button("Organize Tags").clicked() {
+                                        self.current_tab = MainTab::Passwords;
+                                        self.success_message =
+                                            "Tick entries in the list to add or remove a tag on all of them at once".to_string();
+                                    }
+                                    ui.small("Add or remove tags on many entries");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // Export reports
+                            ui.horizontal(|ui| {
+                                ui.label("📋");
+                                ui.vertical(|ui| {
+                                    ui.add_enabled(false, egui::Button::new("Export Report"));
+                                    ui.small("Generate security report");
+                                });
+                            });
+                        });
+                    });
+                    
+                    ui.end_row();
+                    
+                    // System Information Card
+                    ui.group(|ui| {
+                        ui.set_min_size(egui::vec2(350.0, 200.0));
+                        ui.vertical(|ui| {
+                            ui.heading("ℹ System Information");
+                            ui.separator();
+                            ui.add_space(10.0);
+                            
+                            egui::Grid::new("system_info_grid").num_columns(2).spacing([20.0, 5.0]).show(ui, |ui| {
+                                ui.label("Version:");
+                                ui.label("PwGen v0.1.0");
+                                ui.end_row();
+                                
+                                ui.label("Engine:");
+                                ui.label("🦀 Rust + egui");
+                                ui.end_row();
+                                
+                                ui.label("Encryption:");
+                                ui.label("🔒 AES-256-GCM");
+                                ui.end_row();
+                                
+                                ui.label("Storage:");
+                                ui.label("💾 SQLite");
+                                ui.end_row();
+                                
+                                ui.label("Layout:");
+                                ui.label(if self.is_compact_mode { "📱 Compact" } else { "🖥 Wide" });
+                                ui.end_row();
+                                
+                                ui.label("Window:");
+                                ui.label(format!("{:.0}px wide", self.window_width));
+                                ui.end_row();
+                            });
+                        });
+                    });
+                    
+                    // Resources & Support Card
+                    ui.group(|ui| {
+                        ui.set_min_size(egui::vec2(350.0, 200.0));
+                        ui.vertical(|ui| {
+                            ui.heading("🌐 Resources & Support");
+                            ui.separator();
+                            ui.add_space(10.0);
+                            
+                            // Documentation
+                            ui.horizontal(|ui| {
+                                ui.label("📚");
+                                ui.vertical(|ui| {
+                                    if ui.button("Documentation").clicked() {
+                                        let _ = open::that("https://github.com/hxhippy/pwgen/blob/main/README.md");
+                                    }
+                                    ui.small("User guide and API docs");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // About
+                            ui.horizontal(|ui| {
+                                ui.label("ℹ");
+                                ui.vertical(|ui| {
+                                    if ui.button("About PwGen").clicked() {
+                                        self.show_about = true;
+                                    }
+                                    ui.small("Version info and credits");
+                                });
+                            });
+                            
+                            ui.add_space(8.0);
+                            
+                            // External links
+                            ui.horizontal(|ui| {
+                                ui.label("🔗");
+                                ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        if ui.small_button("Kief Studio").clicked() {
+                                            let _ = open::that("https://kief.studio");
+                                        }
+                                    });
+                                    ui.small("Developer websites");
+                                });
+                            });
+                        });
+                    });
+                });
+        });
+    }
+    
+    fn show_settings_tab(&mut self, ui: &mut egui::Ui) {
+        ui.heading("⚙ Settings");
+        ui.separator();
+        
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.group(|ui| {
+                ui.label("🔒 Security");
+                ui.separator();
+                
+                egui::Grid::new("security_settings").num_columns(2).show(ui, |ui| {
+                    ui.label("Auto-lock after (minutes):");
+                    ui.add(egui::DragValue::new(&mut self.auto_lock_minutes).range(1..=60))
+                        .on_hover_text(AUTO_LOCK_HINT);
+                    ui.end_row();
+                    
+                    ui.label("Lock on minimize:");
+                    if ui.checkbox(&mut self.gui_config.lock_on_minimize, "").changed() {
+                        self.save_gui_config();
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Hide secrets when unfocused:");
+                    if ui.checkbox(&mut self.gui_config.hide_when_unfocused, "")
+                        .on_hover_text(HIDE_WHEN_UNFOCUSED_HINT)
+                        .changed()
+                    {
+                        self.save_gui_config();
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Minimize to tray:");
+                    ui.checkbox(&mut self.minimize_to_tray, "");
+                    ui.end_row();
+                    
+                    ui.label("Show system tray:");
+                    ui.checkbox(&mut self.show_system_tray, "");
+                    ui.end_row();
+                    
+                    ui.label("Confirm before copy/reveal:");
+                    if ui.checkbox(&mut self.gui_config.confirm_copy_reveal, "")
+                        .on_hover_text(REVEAL_TAGS_HINT)
+                        .changed()
+                    {
+                        self.save_gui_config();
+                    }
+                    ui.end_row();
+                    
+                    #[cfg(feature = "favicons")]
+                    {
+                        ui.label("Show site icons:");
+                        if ui.checkbox(&mut self.gui_config.show_site_icons, "")
+                            .on_hover_text(SITE_ICONS_HINT)
+                            .changed()
+                        {
+                            self.save_gui_config();
+                        }
+                        ui.end_row();
+                    }
+                    
+                    ui.label("Autosave drafts every (seconds):");
+                    ui.horizontal(|ui| {
+                        let toggled = ui.checkbox(&mut self.gui_config.autosave_drafts, "")
+                            .on_hover_text(DRAFT_AUTOSAVE_HINT)
+                            .changed();
+                        let interval = ui.add_enabled(
+                            self.gui_config.autosave_drafts,
+                            egui::DragValue::new(&mut self.gui_config.draft_autosave_secs).range(2..=300),
+                        );
+                        if toggled || interval.changed() {
+                            self.save_gui_config();
+                        }
+                    });
+                    ui.end_row();
+                    
+                    ui.label("Large reveal closes after (seconds):");
+                    if ui.add(egui::DragValue::new(&mut self.gui_config.large_reveal_secs).range(3..=120)).changed() {
+                        self.save_gui_config();
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Group large reveal in chunks:");
+                    if ui.checkbox(&mut self.gui_config.large_reveal_chunked, "").changed() {
+                        self.save_gui_config();
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Mark copies as sensitive:");
+                    ui.checkbox(&mut self.clipboard_sensitive_hint, "")
+                        .on_hover_text(SENSITIVE_COPY_HINT);
+                    ui.end_row();
+                    
+                    if cfg!(feature = "clipboard") {
+                        ui.label("Clear clipboard after (seconds):");
+                        if ui.add(egui::DragValue::new(&mut self.gui_config.clipboard_clear_secs).range(0..=600))
+                            .on_hover_text(CLIPBOARD_CLEAR_HINT)
+                            .changed()
+                        {
+                            self.save_gui_config();
+                        }
+                        ui.end_row();
+                    }
+                    
+                    ui.label("Copy and open copies:");
+                    self.copy_and_open_setting(ui);
+                    ui.end_row();
+                    
+                    ui.label("Double-clicking an entry:");
+                    self.double_click_setting(ui);
+                    ui.end_row();
+                    
+                    ui.label("Password history per entry:");
+                    if ui.add(egui::DragValue::new(&mut self.password_history_limit).range(0..=100))
+                        .on_hover_text(PASSWORD_HISTORY_HINT)
+                        .changed()
+                    {
+                        self.apply_password_history_limit();
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Device name:");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.device_name)
+                                .desired_width(160.0)
+                                .hint_text("e.g. work-laptop"),
+                        )
+                        .on_hover_text(DEVICE_NAME_HINT);
+                        if ui.button("Save").clicked() {
+                            self.save_device_name();
+                        }
+                    });
+                    ui.end_row();
+                    
+                    ui.label("Warn about expiry within (days):");
+                    if ui.add(egui::DragValue::new(&mut self.gui_config.expiry_warning_days).range(0..=365))
+                        .on_hover_text(EXPIRY_WARNING_HINT)
+                        .changed()
+                    {
+                        if let Err(e) = self.gui_config.save() {
+                            self.error_message = format!("Failed to save preferences: {}", e);
+                        }
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Largest document (MB):");
+                    if ui.add(egui::DragValue::new(&mut self.gui_config.max_document_size_mb).range(1..=4096))
+                        .on_hover_text(MAX_DOCUMENT_SIZE_HINT)
+                        .changed()
+                    {
+                        if let Err(e) = self.gui_config.save() {
+                            self.error_message = format!("Failed to save preferences: {}", e);
+                        }
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Clipboard history notice:");
+                    if ui.checkbox(&mut self.gui_config.hide_clipboard_manager_notice, "Don't show").changed() {
+                        if let Err(e) = self.gui_config.save() {
+                            self.error_message = format!("Failed to save preferences: {}", e);
+                        }
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Master password:");
+                    if ui.button("🔑 Change Master Password...").clicked() {
+                        self.password_change = Some(PasswordChange::default());
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Recovery key:");
+                    if ui.button("🛟 New Recovery Key...")
+                        .on_hover_text("Unlocks the vault if the master password is forgotten. Replaces any earlier recovery key.")
+                        .clicked()
+                    {
+                        self.enable_recovery_key();
+                    }
+                    ui.end_row();
+                });
+            });
+            
+            ui.add_space(10.0);
+            
+            ui.group(|ui| {
+                ui.label("🎨 Appearance");
+                ui.separator();
+                
+                egui::Grid::new("appearance_settings").num_columns(2).show(ui, |ui| {
+                    ui.label("Entries per page:");
+                    ui.add(egui::DragValue::new(&mut self.entries_per_page).range(10..=500));
+                    ui.end_row();
+                    
+                    ui.label("Theme:");
+                    let before = self.gui_config.theme;
+                    egui::ComboBox::from_id_salt("theme")
+                        .selected_text(self.gui_config.theme.label())
+                        .show_ui(ui, |ui| {
+                            for theme in [ThemeMode::System, ThemeMode::Light, ThemeMode::Dark] {
+                                ui.selectable_value(&mut self.gui_config.theme, theme, theme.label());
+                            }
+                        });
+                    if self.gui_config.theme != before {
+                        if let Err(e) = self.gui_config.save() {
+                            self.error_message = format!("Failed to save preferences: {}", e);
+                        }
+                    }
+                    ui.end_row();
+                    
+                    ui.label("Font:");
+                    ui.label("Fira Code");
+                    ui.end_row();
+                });
+            });
+            
+            ui.add_space(10.0);
+            
+            ui.group(|ui| {
+                ui.label("💾 Data");
+                ui.separator();
+                
+                if ui.button("🔄 Reload Entries").clicked() {
+                    self.load_entries();
+                    self.success_message = "Entries reloaded".to_string();
+                }
+                
+                ui.add_space(5.0);
+                
+                if ui.button("💾 Backup Now").clicked() {
+                    self.show_backup = true;
+                }
+                
+                if ui.button("📥 Import Data").clicked() {
+                    self.show_import = true;
+                }
+            });
+            
+            ui.add_space(10.0);
+            
+            ui.group(|ui| {
+                ui.label("ℹ Information");
+                ui.separator();
+                
+                ui.label("PwGen v0.1.0");
+                ui.label("🦀 Built with Rust + egui");
+                ui.label("🔒 AES-256-GCM encryption");
+                ui.label("💾 SQLite storage");
+                
+                ui.add_space(5.0);
+                
+                if ui.button("ℹ About").clicked() {
+                    self.show_about = true;
+                }
+            });
+        });
+    }
+    
+    fn show_password_strength(ui: &mut egui::Ui, password: &str) {
+        if password.is_empty() {
+            return;
+        }
+        Self::show_strength_report(ui, &estimate_strength(password));
+    }
+    
+    /// Strength of the password typed in the entry dialog. Estimated again
+    /// only when the text changes, so typing doesn't stutter.
+    fn entry_password_strength(&mut self) -> StrengthReport {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.entry_password.hash(&mut hasher);
+        let key = hasher.finish();
+        match &self.entry_strength {
+            Some((cached, report)) if *cached == key => report.clone(),
+            _ => {
+                let report = estimate_strength(&self.entry_password);
+                self.entry_strength = Some((key, report.clone()));
+                report
+            }
+        }
+    }
+    
+    fn strength_color(level: StrengthLevel, dark_mode: bool) -> egui::Color32 {
+        match (level, dark_mode) {
+            (StrengthLevel::VeryWeak, true) => egui::Color32::from_rgb(255, 100, 100),
+            (StrengthLevel::Weak, true) => egui::Color32::from_rgb(255, 150, 80),
+            (StrengthLevel::Fair, true) => egui::Color32::from_rgb(255, 200, 80),
+            (StrengthLevel::Good, true) => egui::Color32::from_rgb(160, 230, 100),
+            (StrengthLevel::Strong, true) => egui::Color32::from_rgb(100, 255, 100),
+            (StrengthLevel::VeryWeak, false) => egui::Color32::from_rgb(190, 30, 30),
+            (StrengthLevel::Weak, false) => egui::Color32::from_rgb(200, 90, 0),
+            (StrengthLevel::Fair, false) => egui::Color32::from_rgb(160, 120, 0),
+            (StrengthLevel::Good, false) => egui::Color32::from_rgb(80, 140, 0),
+            (StrengthLevel::Strong, false) => egui::Color32::from_rgb(20, 130, 20),
+        }
+    }
+    
+    fn show_strength_report(ui: &mut egui::Ui, report: &StrengthReport) {
+        let color = Self::strength_color(report.level, ui.visuals().dark_mode);
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::ProgressBar::new((report.score() + 1) as f32 / 5.0)
+                    .fill(color)
+                    .desired_width(120.0),
+            );
+            ui.colored_label(color, format!("💪 {}", report.level.label()));
+            ui.weak(format!("cracked in {}", report.crack_time_display()))
+                .on_hover_text(STRENGTH_CRACK_TIME_HINT);
+            if !report.reasons.is_empty() {
+                ui.label("ℹ").on_hover_ui(|ui| {
+                    ui.label("Why this rating:");
+                    for reason in &report.reasons {
+                        ui.label(format!("• {}", reason));
+                    }
+                });
+            }
+        });
+    }
+    
+    fn show_entry_dialog(&mut self, ctx: &egui::Context) {
+        if !self.show_add_dialog {
+            return;
+        }
+        
+        let window = egui::Window::new(if self.edit_entry.is_some() { "Edit Entry" } else { "Add New Entry" })
+            .collapsible(false)
+            .resizable(false)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    egui::Grid::new("entry_form").num_columns(2).show(ui, |ui| {
+                        ui.label("Website:");
+                        ui.horizontal(|ui| {
+                            ui.text_edit_singleline(&mut self.entry_site);
+                            if ui.button("➕").on_hover_text("Add another URL").clicked() {
+                                self.entry_urls.push(String::new());
+                            }
+                        });
+                        ui.end_row();
+                        show_violations(ui, &self.entry_violations, "site");
+                        
+                        let mut make_primary = None;
+                        let mut remove = None;
+                        for (index, url) in self.entry_urls.iter_mut().enumerate() {
+                            ui.label("");
+                            ui.horizontal(|ui| {
+                                ui.text_edit_singleline(url);
+                                if ui.button("⭐").on_hover_text("Make primary").clicked() {
+                                    make_primary = Some(index);
+                                }
+                                if ui.button("🗑").on_hover_text("Remove URL").clicked() {
+                                    remove = Some(index);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                        if let Some(index) = make_primary {
+                            std::mem::swap(&mut self.entry_site, &mut self.entry_urls[index]);
+                        }
+                        if let Some(index) = remove {
+                            self.entry_urls.remove(index);
+                        }
+                        
+                        ui.label("Username:");
+                        ui.text_edit_singleline(&mut self.entry_username);
+                        ui.end_row();
+                        show_violations(ui, &self.entry_violations, "username");
+                        
+                        ui.label("Password:");
+                        ui.horizontal(|ui| {
+                            if self.show_password {
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.entry_password)
+                                        .font(egui::TextStyle::Monospace)
+                                );
+                            } else {
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.entry_password)
+                                        .password(true)
+                                        .font(egui::TextStyle::Monospace)
+                                );
+                            }
+                            if ui.button(if self.show_password { "🙈" } else { "👁" }).clicked() {
+                                let protection = self.edit_entry.as_ref().map(|e| (e.tags.clone(), e.require_reauth));
+                                match protection {
+                                    Some((tags, reauth)) if !self.show_password
+                                        && (reauth || requires_reveal_confirmation(&tags, self.gui_config.confirm_copy_reveal)) =>
+                                    {
+                                        self.pending_reveal = Some(PendingReveal {
+                                            what: format!("Password for {}", self.entry_site),
+                                            action: RevealAction::ShowPassword,
+                                            reauth,
+                                        });
+                                    }
+                                    _ => self.show_password = !self.show_password,
+                                }
+                            }
+                            if ui.button("🎲").on_hover_text("Generate a password here, following the site's password rules if it has them").clicked() {
+                                self.fill_entry_password();
+                            }
+                            if ui.small_button("⚙").on_hover_text("Open the generator").clicked() {
+                                self.show_generator = true;
+                                self.generate_password();
+                            }
+                            if ui.small_button("✖").on_hover_text("Clear password").clicked() {
+                                self.entry_password.clear();
+                            }
+                        });
+                        ui.end_row();
+                        show_violations(ui, &self.entry_violations, "password");
+                        
+                        ui.label("");
+                        self.show_entry_policy(ui);
+                        ui.end_row();
+                        
+                        if !self.entry_password.is_empty() {
+                            ui.label("");
+                            let report = self.entry_password_strength();
+                            ui.vertical(|ui| {
+                                Self::show_strength_report(ui, &report);
+                                if let Some(tip) = &report.tip {
+                                    ui.small(format!("💡 {}", tip));
+                                }
+                            });
+                            ui.end_row();
+                        }
+                        
+                        ui.label("2FA secret:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.entry_totp)
+                                .password(!self.show_password)
+                                .font(egui::TextStyle::Monospace)
+                                .hint_text("Base32 key or otpauth:// link")
+                        )
+                        .on_hover_text(TOTP_SECRET_HINT);
+                        ui.end_row();
+                        
+                        ui.label("Notes:");
+                        ui.add(egui::TextEdit::multiline(&mut self.entry_notes).desired_rows(3));
+                        ui.end_row();
+                        show_violations(ui, &self.entry_violations, "notes");
+                        
+                        ui.label("Tags:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.entry_tags)
+                                .hint_text("Comma separated tags...")
+                        );
+                        ui.end_row();
+                        if !tag_suggestions(&self.tag_counts, &self.entry_tags).is_empty() {
+                            ui.label("");
+                            show_tag_suggestions(ui, &self.tag_counts, &mut self.entry_tags);
+                            ui.end_row();
+                        }
+                        show_violations(ui, &self.entry_violations, "tags");
+                        
+                        ui.label("");
+                        ui.checkbox(&mut self.entry_require_reauth, "🔒 Ask for the master password before revealing or copying")
+                            .on_hover_text(REQUIRE_REAUTH_HINT);
+                        ui.end_row();
+                    });
+                    
+                    if let Some(attribution) = self
+                        .edit_entry
+                        .as_ref()
+                        .and_then(|entry| attribution_text(&entry.created_by, &entry.modified_by))
+                    {
+                        ui.small(attribution);
+                    }
+                    
+                    ui.separator();
+                    
+                    ui.horizontal(|ui| {
+                        if ui.button("💾 Save").clicked() {
+                            self.save_entry();
+                        }
+                        if ui.button("❌ Cancel").clicked() {
+                            self.show_add_dialog = false;
+                            self.clear_entry_form();
+                            self.clear_draft(ENTRY_DRAFT);
+                        }
+                    });
+                });
+            });
+        
+        if let Some(window) = window {
+            if self.gui_config.hide_when_unfocused && !self.was_focused {
+                cover_window(ctx, window.response.rect);
+            }
+        }
+    }
+    
+    fn show_generator_dialog(&mut self, ctx: &egui::Context) {
+        if !self.show_generator {
+            return;
+        }
+        
+        egui::Window::new("Password Generator")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(350.0)
+            .show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.generated_password)
+                                    .desired_width(250.0)
+                                    .password(self.generated_masked)
+                                    .font(egui::TextStyle::Monospace)
+                                    .text_color(ui.visuals().strong_text_color())
+                            );
+                            if self.generated_masked && ui.button("👁").on_hover_text("Show password").clicked() {
+                                self.generated_masked = false;
+                            }
+                            if ui.button("📋").on_hover_text("Copy").clicked() {
+                                let password = self.generated_password.clone();
+                                self.copy_to_clipboard(&password);
+                                self.success_message = self.copied_message("Password");
+                            }
+                            if ui.button("🔄").on_hover_text("Regenerate").clicked() {
+                                self.generate_password();
+                            }
+                        });
+                    });
+                    
+                    ui.separator();
+                    
+                    self.show_generator_mode(ui);
+                    if self.gen_mode == GeneratorMode::Characters {
+                        ui.horizontal(|ui| {
+                            ui.label("Length:");
+                            ui.add(egui::Slider::new(&mut self.gen_length, 8..=128));
+                        });
+                        
+                        ui.checkbox(&mut self.gen_uppercase, "Uppercase (A-Z)");
+                        ui.checkbox(&mut self.gen_lowercase, "Lowercase (a-z)");
+                        ui.checkbox(&mut self.gen_numbers, "Numbers (0-9)");
+                        ui.checkbox(&mut self.gen_symbols, "Symbols (!@#$%)");
+                        ui.checkbox(&mut self.gen_exclude_ambiguous, "Exclude ambiguous (0O1lI)");
+                        ui.checkbox(&mut self.gen_mobile_friendly, "Mobile-friendly");
+                        if self.gen_mobile_friendly {
+                            ui.small(self.mobile_entropy_summary());
+                        }
+                    }
+                    
+                    ui.separator();
+                    
+                    ui.horizontal(|ui| {
+                        if ui.button("✅ Use This Password").clicked() {
+                            self.entry_password = self.generated_password.clone();
+                            self.show_generator = false;
+                        }
+                        if ui.button("❌ Close").clicked() {
+                            self.show_generator = false;
+                        }
+                    });
+                });
+            });
+    }
+    
+    fn show_settings_dialog(&mut self, ctx: &egui::Context) {
+        if !self.show_settings {
+            return;
+        }
+        
+        egui::Window::new("⚙ Settings")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    ui.group(|ui| {
+                        ui.label("Security Settings");
+                        ui.horizontal(|ui| {
+                            ui.label("Auto-lock after:");
+                            ui.add(egui::Slider::new(&mut self.auto_lock_minutes, 1..=60).suffix(" min"))
+                                .on_hover_text(AUTO_LOCK_HINT);
+                        });
+                        if ui.checkbox(&mut self.gui_config.lock_on_minimize, "Lock when the window is minimized").changed() {
+                            self.save_gui_config();
+                        }
+                        if ui.checkbox(&mut self.gui_config.hide_when_unfocused, "Hide secrets when unfocused")
+                            .on_hover_text(HIDE_WHEN_UNFOCUSED_HINT)
+                            .changed()
+                        {
+                            self.save_gui_config();
+                        }
+                        ui.checkbox(&mut self.minimize_to_tray, "Minimize to system tray");
+                        if ui.checkbox(&mut self.gui_config.confirm_copy_reveal, "Confirm before copying or revealing secrets")
+                            .on_hover_text(REVEAL_TAGS_HINT)
+                            .changed()
+                        {
+                            self.save_gui_config();
+                        }
+                        #[cfg(feature = "favicons")]
+                        if ui.checkbox(&mut self.gui_config.show_site_icons, "Show site icons in the entry list")
+                            .on_hover_text(SITE_ICONS_HINT)
+                            .changed()
+                        {
+                            self.save_gui_config();
+                        }
+                        ui.horizontal(|ui| {
+                            let toggled = ui.checkbox(&mut self.gui_config.autosave_drafts, "Autosave unsaved forms every")
+                                .on_hover_text(DRAFT_AUTOSAVE_HINT)
+                                .changed();
+                            let interval = ui.add_enabled(
+                                self.gui_config.autosave_drafts,
+                                egui::Slider::new(&mut self.gui_config.draft_autosave_secs, 2..=300).suffix(" s"),
+                            );
+                            if toggled || interval.changed() {
+                                self.save_gui_config();
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Large reveal closes after:");
+                            if ui.add(egui::Slider::new(&mut self.gui_config.large_reveal_secs, 3..=120).suffix(" s")).changed() {
+                                self.save_gui_config();
+                            }
+                        });
+                        if ui.checkbox(&mut self.gui_config.large_reveal_chunked, "Group large reveal into chunks of 4").changed() {
+                            self.save_gui_config();
+                        }
+                        ui.checkbox(&mut self.clipboard_sensitive_hint, "Mark copies as sensitive")
+                            .on_hover_text(SENSITIVE_COPY_HINT);
+                        if cfg!(feature = "clipboard") {
+                            ui.horizontal(|ui| {
+                                ui.label("Clear clipboard after:");
+                                if ui.add(egui::Slider::new(&mut self.gui_config.clipboard_clear_secs, 0..=600).suffix(" s"))
+                                    .on_hover_text(CLIPBOARD_CLEAR_HINT)
+                                    .changed()
+                                {
+                                    self.save_gui_config();
+                                }
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Copy and open copies:");
+                            self.copy_and_open_setting(ui);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Double-clicking an entry:");
+                            self.double_click_setting(ui);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Password history per entry:");
+                            if ui.add(egui::Slider::new(&mut self.password_history_limit, 0..=100))
+                                .on_hover_text(PASSWORD_HISTORY_HINT)
+                                .changed()
+                            {
+                                self.apply_password_history_limit();
+                            }
+                        });
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.group(|ui| {
+                        ui.label("Generator Defaults");
+                        ui.horizontal(|ui| {
+                            ui.label("Default length:");
+                            ui.add(egui::Slider::new(&mut self.gen_length, 8..=128));
+                        });
+                        ui.checkbox(&mut self.gen_uppercase, "Include uppercase by default");
+                        ui.checkbox(&mut self.gen_numbers, "Include numbers by default");
+                        ui.checkbox(&mut self.gen_symbols, "Include symbols by default");
+                        ui.checkbox(&mut self.gen_exclude_ambiguous, "Exclude ambiguous characters");
+                        if ui.checkbox(&mut self.gui_config.gen_for_new_entries, "Auto-generate password for new entries")
+                            .on_hover_text(GEN_FOR_NEW_ENTRIES_HINT)
+                            .changed()
+                        {
+                            self.save_gui_config();
+                        }
+                    });
+                    
+                    ui.separator();
+                    
+                    ui.horizontal(|ui| {
+                        if ui.button("💾 Save").clicked() {
+                            // TODO: Save settings to config file
+                            self.success_message = "Settings saved!".to_string();
+                            self.show_settings = false;
+                        }
+                        if ui.button("❌ Cancel").clicked() {
+                            self.show_settings = false;
+                        }
+                    });
+                });
+            });
+    }
+    
+    fn show_about_dialog(&mut self, ctx: &egui::Context) {
+        if !self.show_about {
+            return;
+        }
+        
+        egui::Window::new("ℹ About PwGen")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(450.0)
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    if let Some(logo) = &self.logo_wide {
+                        ui.add(
+                            egui::Image::from_texture(logo)
+                                .fit_to_exact_size(egui::vec2(150.0, 50.0))
+                                .bg_fill(logo_background(ui))
+                        );
+                        ui.add_space(10.0);
+                    }
+                    
+                    ui.heading("PwGen Password Manager");
+                    ui.label("Version 1.0.0");
+                    ui.add_space(10.0);
+                    
+                    ui.label("A secure, cross-platform password manager built in Rust");
+                    ui.label("with military-grade encryption and modern UI.");
+                    
+                    ui.add_space(15.0);
+                    ui.separator();
+                    ui.add_space(15.0);
+                    
+                    ui.strong("Developed by HxHippy");
+                    if ui.link("🌐 hxhippy.com").clicked() {
+                        let _ = open::that("https://hxhippy.com");
+                    }
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.horizontal(|ui| {
+                        ui.label("🤖 AI Consulting:");
+                        if ui.link("Kief Studio").clicked() {
+                            let _ = open::that("https://kief.studio");
+                        }
+                    });
+
+                    ui.add_space(15.0);
+                    ui.separator();
+                    ui.add_space(10.0);
+                    
+                    ui.horizontal(|ui| {
+                        ui.label("🛡 Security:");
+                        ui.label("AES-256-GCM, Argon2, Zero-Knowledge");
+                    });
+                    
+                    ui.horizontal(|ui| {
+                        ui.label("🦀 Built with:");
+                        ui.label("Rust, egui, SQLite");
+                    });
+                    
+                    ui.add_space(15.0);
+                    
+                    if ui.button("✅ Close").clicked() {
+                        self.show_about = false;
+                    }
+                });
+            });
+    }
+    
+    fn show_statistics_dialog(&mut self, ctx: &egui::Context) {
+        if !self.show_statistics {
+            return;
+        }
+        
+        egui::Window::new("📊 Vault Statistics")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(700.0)
+            .show(ctx, |ui| {
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| {
+                        ui.group(|ui| {
+                            ui.label("📈 Entry Statistics");
+                            ui.horizontal(|ui| {
+                                ui.label("Total entries:");
+                                ui.strong(format!("{}", self.entries.len()));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Entries shown:");
+                                ui.strong(format!("{}", self.filtered_entries.len()));
+                            });
+                            
+                            // Calculate some stats
+                            let unique_sites: std::collections::HashSet<_> = self.entries.iter().map(|e| &e.site).collect();
+                            ui.horizontal(|ui| {
+                                ui.label("Unique sites:");
+                                ui.strong(format!("{}", unique_sites.len()));
+                            });
+                            
+                            let total_tags: std::collections::HashSet<_> = self.entries.iter().flat_map(|e| &e.tags).collect();
+                            ui.horizontal(|ui| {
+                                ui.label("Total tags:");
+                                ui.strong(format!("{}", total_tags.len()));
+                            });
+                        });
+                        
+                        ui.add_space(10.0);
+                        
+                        ui.group(|ui| {
+                            ui.label("🔒 Security Analysis");
+                            
+                            // Analyze password security
+                            let weak_passwords = self.entries.iter()
+                                .filter(|e| e.password.len() < 12)
+                                .count();
+                            let strong_passwords = self.entries.iter()
+                                .filter(|e| e.password.len() >= 16)
+                                .count();
+                            
+                            ui.horizontal(|ui| {
+                                ui.label("Strong passwords (16+ chars):");
+                                ui.colored_label(egui::Color32::from_rgb(50, 200, 50), format!("{}", strong_passwords));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Weak passwords (<12 chars):");
+                                ui.colored_label(egui::Color32::from_rgb(200, 50, 50), format!("{}", weak_passwords));
+                            });
+                            
+                            // Password age analysis
+                            let old_passwords = self.entries.iter()
+                                .filter(|e| {
+                                    let age = chrono::Utc::now().signed_duration_since(e.password_changed_at);
+                                    age.num_days() > 90
+                                })
+                                .count();
+                            
+                            ui.horizontal(|ui| {
+                                ui.label("Passwords >90 days old:");
+                                if old_passwords > 0 {
+                                    ui.colored_label(egui::Color32::from_rgb(200, 150, 50), format!("{}", old_passwords));
+                                } else {
+                                    ui.colored_label(egui::Color32::from_rgb(50, 200, 50), format!("{}", old_passwords));
+                                }
+                            });
+                        });
+                    });
+                    
+                    ui.vertical(|ui| self.show_secret_statistics(ui));
+                });
+                
+                ui.add_space(15.0);
+                
+                if ui.button("✅ Close").clicked() {
+                    self.show_statistics = false;
+                }
+            });
+    }
+    
+    fn open_statistics(&mut self) {
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        let result = self.runtime.block_on(async {
+            let secrets_storage_guard = secrets_storage_mutex.lock().unwrap();
+            let Some(secrets_storage) = secrets_storage_guard.as_ref() else {
+                return Ok(None);
+            };
+            let stats = secrets_storage.get_secrets_stats().await?;
+            let breakdown = secrets_storage.get_secrets_breakdown().await?;
+            Ok::<_, pwgen_core::Error>(Some((stats, breakdown)))
+        });
+        match result {
+            Ok(statistics) => self.secret_statistics = statistics,
+            Err(e) => {
+                self.secret_statistics = None;
+                self.error_message = format!("Failed to load secret statistics: {}", e);
+            }
+        }
+        self.show_statistics = true;
+    }
+    
+    /// Second column of the statistics dialog: secrets by type and what
+    /// needs attention.
+    fn show_secret_statistics(&self, ui: &mut egui::Ui) {
+        let Some((stats, breakdown)) = &self.secret_statistics else {
+            ui.label("Secret statistics are unavailable");
+            return;
+        };
+        let warn = |ui: &mut egui::Ui, count: usize| {
+            if count > 0 {
+                ui.colored_label(egui::Color32::from_rgb(200, 150, 50), count.to_string());
+            } else {
+                ui.colored_label(egui::Color32::from_rgb(50, 200, 50), count.to_string());
+            }
+        };
+        
+        ui.group(|ui| {
+            ui.label("🔐 Secret Statistics");
+            ui.horizontal(|ui| {
+                ui.label("Total secrets:");
+                ui.strong(stats.total_count.to_string());
+            });
+            for (secret_type, count) in &breakdown.by_type {
+                ui.horizontal(|ui| {
+                    ui.label(format!("  {}:", secret_type_name(secret_type)));
+                    ui.label(count.to_string());
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label("Document storage:");
+                ui.strong(format!("{:.1} KiB", breakdown.document_bytes as f64 / 1024.0));
+            });
+        });
+        
+        ui.add_space(10.0);
+        
+        ui.group(|ui| {
+            ui.label("⚠ Needs Attention");
+            ui.horizontal(|ui| {
+                ui.label("Expired:");
+                warn(ui, stats.expired_count);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Expiring within 30 days:");
+                warn(ui, stats.expiring_soon_count);
+            });
+            ui.horizontal(|ui| {
+                ui.label("API keys overdue for rotation:");
+                warn(ui, breakdown.rotation_overdue);
+            });
+            ui.horizontal(|ui| {
+                ui.label("SSH keys without a passphrase:");
+                warn(ui, breakdown.ssh_keys_without_passphrase);
+            });
+        });
+    }
+    
+    fn show_recently_used_dialog(&mut self, ctx: &egui::Context) {
+        if !self.show_recently_used {
+            return;
+        }
+        
+        let mut recent: Vec<DecryptedPasswordEntry> = self.entries.iter()
+            .filter(|entry| entry.last_used.is_some())
+            .cloned()
+            .collect();
+        recent.sort_by_key(|entry| std::cmp::Reverse(entry.last_used));
+        recent.truncate(RECENTLY_USED_LIMIT);
+        
+        let mut open = true;
+        let mut show_entry = None;
+        let mut copy = None;
+        egui::Window::new("🕘 Recently Used")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                if recent.is_empty() {
+                    ui.label("No entry has been copied or opened yet.");
+                    return;
+                }
+                egui::Grid::new("recently_used").num_columns(3).striped(true).show(ui, |ui| {
+                    for entry in &recent {
+                        if ui.link(&entry.site).on_hover_text(&entry.username).clicked() {
+                            show_entry = Some(entry.id.clone());
+                        }
+                        if let Some(last_used) = entry.last_used {
+                            ui.label(last_used.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string());
+                        }
+                        if ui.small_button("🔑").on_hover_text("Copy password").clicked() {
+                            copy = Some(entry.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        
+        if let Some(entry) = copy {
+            self.quick_copy_entry(&entry, "password");
+        }
+        if let Some(id) = show_entry {
+            self.current_tab = MainTab::Passwords;
+            self.selected_entry_id = Some(id);
+        }
+        self.show_recently_used = open;
+    }
+    
+    fn show_import_dialog(&mut self, ctx: &egui::Context) {
+        if !self.show_import {
+            return;
+        }
+        
+        egui::Window::new("📥 Import from Browser")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    if self.import_job.is_some() {
+                        self.show_import_progress(ui);
+                        return;
+                    }
+                    if self.import_preview.is_some() {
+                        self.show_import_preview(ui);
+                        return;
+                    }
+                    if self.csv_mapping.is_some() {
+                        self.show_csv_mapping(ui);
+                        return;
+                    }
+                    
+                    ui.label("Import passwords from your web browser:");
+                    ui.add_space(10.0);
+                    
+                    ui.group(|ui| {
+                        ui.label("🔗 URL Cleanup");
+                        ui.checkbox(&mut self.import_strip_query, "Strip query strings from URLs");
+                        ui.checkbox(&mut self.import_strip_www, "Drop \"www.\" from hosts");
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.group(|ui| {
+                        ui.label("🌐 Supported Browsers");
+                        
+                        if ui.button("🟦 Google Chrome").clicked() {
+                            self.preview_browser_import("chrome");
+                        }
+                        if ui.button("🟧 Mozilla Firefox").clicked() {
+                            self.preview_browser_import("firefox");
+                        }
+                        if ui.button("🟦 Microsoft Edge").clicked() {
+                            self.preview_browser_import("edge");
+                        }
+                        if ui.button("🟣 Opera").clicked() {
+                            self.preview_browser_import("opera");
+                        }
+                        if ui.button("🟠 Brave").clicked() {
+                            self.preview_browser_import("brave");
+                        }
+                        if ui.button("🔵 Safari").clicked() {
+                            self.preview_browser_import("safari");
+                        }
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.group(|ui| {
+                        ui.label("📄 Other CSV File");
+                        if ui.button("Choose CSV and map columns...")
+                            .on_hover_text("For exports from other tools: pick which column holds the site, username, password, notes and tags")
+                            .clicked()
+                        {
+                            self.choose_csv_for_mapping();
+                        }
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.group(|ui| {
+                        ui.label("🔢 Authenticator Codes");
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.totp_import_text)
+                                .hint_text("otpauth://totp/... or otpauth-migration://offline?data=..., one per line")
+                                .desired_rows(3)
+                                .desired_width(f32::INFINITY)
+                                .font(egui::TextStyle::Monospace),
+                        );
+                        if ui.add_enabled(!self.totp_import_text.trim().is_empty(), egui::Button::new("Import 2FA seeds"))
+                            .on_hover_text(TOTP_IMPORT_HINT)
+                            .clicked()
+                        {
+                            self.import_totp_seeds();
+                        }
+                    });
+                    
+                    ui.add_space(10.0);
+                    ui.separator();
+                    
+                    ui.label("⚠ Note: Browser must be closed during import");
+                    ui.label("🔒 All imported passwords are encrypted with your master password");
+                    
+                    ui.add_space(15.0);
+                    
+                    if ui.button("❌ Cancel").clicked() {
+                        self.show_import = false;
+                    }
+                });
+            });
+    }
+    
+    /// Add the seeds pasted into the import dialog to their entries.
+    fn import_totp_seeds(&mut self) {
+        let import = parse_totp_import(&self.totp_import_text);
+        let import = match import {
+            Ok(import) => import,
+            Err(e) => {
+                self.error_message = format!("Could not read the 2FA seeds: {}", e);
+                return;
+            }
+        };
+        let storage_mutex = self.storage.clone();
+        let result = self.runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            match storage_guard.as_ref() {
+                Some(storage) => storage.import_totp_seeds(&import.seeds).await,
+                None => Err(pwgen_core::Error::VaultLocked),
+            }
+        });
+        
+        match result {
+            Ok(report) => {
+                self.totp_import_text.clear();
+                let skipped: Vec<String> = report
+                    .skipped
+                    .iter()
+                    .chain(&import.unsupported)
+                    .map(|(label, reason)| format!("{} ({})", label, reason))
+                    .collect();
+                self.success_message = format!(
+                    "2FA seeds imported: {} added to existing entries, {} new entries",
+                    report.attached.len(),
+                    report.created.len()
+                );
+                if !skipped.is_empty() {
+                    self.success_message.push_str(&format!("; skipped {}", skipped.join(", ")));
+                }
+                self.load_entries();
+            }
+            Err(e) => self.error_message = format!("Failed to import 2FA seeds: {}", e),
+        }
+    }
+    
+    fn show_import_preview(&mut self, ui: &mut egui::Ui) {
+        let Some(preview) = &self.import_preview else {
+            return;
+        };
+        
+        ui.label(format!("{} entries will be imported:", preview.entries.len()));
+        ui.add_space(5.0);
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            egui::Grid::new("import_preview_grid").striped(true).show(ui, |ui| {
+                ui.strong("Site");
+                ui.strong("Username");
+                ui.end_row();
+                for entry in &preview.entries {
+                    // Entries are stored under their URL, or their name when they have none
+                    let site = ui.label(entry.url.as_deref().unwrap_or(&entry.name));
+                    if entry.url != entry.original_url {
+                        site.on_hover_text(format!("In export: {}", entry.original_url.as_deref().unwrap_or("-")));
+                    }
+                    ui.label(entry.username.as_deref().unwrap_or("-"));
+                    ui.end_row();
+                }
+            });
+        });
+        
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            if ui.button("📥 Import").clicked() {
+                if let Some(preview) = self.import_preview.take() {
+                    self.import_from_browser(&preview.browser, preview.path, preview.column_mapping);
+                }
+            }
+            if ui.button("⬅ Back").clicked() {
+                self.import_preview = None;
+            }
+        });
+    }
+    
+    fn show_csv_mapping(&mut self, ui: &mut egui::Ui) {
+        let Some(state) = &mut self.csv_mapping else {
+            return;
+        };
+        let mut preview = false;
+        let mut back = false;
+        
+        ui.label(format!(
+            "Which columns of {} hold each field?",
+            state.path.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        ui.add_space(5.0);
+        
+        let columns = state.columns.clone();
+        egui::Grid::new("csv_mapping_grid").num_columns(2).show(ui, |ui| {
+            for (label, field) in [
+                ("Site / URL:", &mut state.site),
+                ("Username:", &mut state.username),
+                ("Password:", &mut state.password),
+                ("Notes:", &mut state.notes),
+                ("Tags:", &mut state.tags),
+            ] {
+                ui.label(label);
+                let selected = field
+                    .and_then(|i| columns.get(i))
+                    .map(|name| name.as_str())
+                    .unwrap_or("(none)");
+                egui::ComboBox::from_id_salt(label)
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(field, None, "(none)");
+                        for (i, column) in columns.iter().enumerate() {
+                            ui.selectable_value(field, Some(i), format!("{}. {}", i + 1, column));
+                        }
+                    });
+                ui.end_row();
+            }
+        });
+        
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            if ui.add_enabled(state.password.is_some(), egui::Button::new("🔍 Preview")).clicked() {
+                preview = true;
+            }
+            if ui.button("⬅ Back").clicked() {
+                back = true;
+            }
+        });
+        
+        if back {
+            self.csv_mapping = None;
+        } else if preview {
+            self.preview_mapped_csv_import();
+        }
+    }
+    
+    fn choose_csv_for_mapping(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV Files", &["csv"])
+            .set_title("Select CSV file to import")
+            .pick_file()
+        else {
+            return;
+        };
+        self.error_message.clear();
+        match BrowserImporter::csv_columns(&path) {
+            Ok(columns) if columns.is_empty() => self.error_message = "The file has no columns".to_string(),
+            Ok(columns) => self.csv_mapping = Some(CsvMappingState::new(path, columns)),
+            Err(e) => self.error_message = format!("Failed to read CSV file: {}", e),
+        }
+    }
+    
+    fn preview_mapped_csv_import(&mut self) {
+        let Some(state) = &self.csv_mapping else {
+            return;
+        };
+        let column_mapping = state.mapping();
+        let config = ImportConfig {
+            column_mapping: Some(column_mapping.clone()),
+            ..self.import_config("csv")
+        };
+        match BrowserImporter::preview_import(&state.path, &config) {
+            Ok(entries) => {
+                self.error_message.clear();
+                self.import_preview = Some(ImportPreviewState {
+                    browser: "csv".to_string(),
+                    path: state.path.clone(),
+                    entries,
+                    column_mapping: Some(column_mapping),
+                });
+                self.csv_mapping = None;
+            }
+            Err(e) => self.error_message = format!("Import failed: {}", e),
+        }
+    }
+    
+    fn show_backup_dialog(&mut self, ctx: &egui::Context) {
+        if !self.show_backup {
+            return;
+        }
+        
+        let busy = self.backup_job.is_some();
+        egui::Window::new("💾 Backup & Restore")
+            .collapsible(false)
+            .resizable(false)
+            .default_width(400.0)
+            .show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Backup password:");
+                        ui.add(egui::TextEdit::singleline(&mut self.backup_password).password(true));
+                    });
+                    ui.add_space(10.0);
+                    
+                    ui.group(|ui| {
+                        ui.label("📤 Create Backup");
+                        ui.label("Export your vault to an encrypted backup file");
+                        ui.horizontal(|ui| {
+                            ui.label("Confirm password:");
+                            ui.add(egui::TextEdit::singleline(&mut self.backup_password_confirm).password(true));
+                        });
+                        ui.add_space(5.0);
+                        
+                        if ui.add_enabled(!busy, egui::Button::new("💾 Create Backup File")).clicked() {
+                            self.create_backup();
+                        }
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.group(|ui| {
+                        ui.label("📥 Restore from Backup");
+                        ui.label("Merge entries from a previously created backup; you choose what happens to entries that already exist");
+                        ui.add_space(5.0);
+                        
+                        if ui.add_enabled(!busy, egui::Button::new("📂 Select Backup File")).clicked() {
+                            self.restore_backup();
+                        }
+                    });
+                    
+                    ui.add_space(10.0);
+                    ui.separator();
+                    
+                    ui.label("🔒 Backups are encrypted with the backup password");
+                    ui.label("🛡 Backups are verified before anything is restored");
+                    ui.label("📁 Keep backups in a secure location");
+                    
+                    ui.add_space(15.0);
+                    
+                    if ui.button("❌ Close").clicked() {
+                        self.show_backup = false;
+                        self.backup_password.clear();
+                        self.backup_password_confirm.clear();
+                    }
+                });
+            });
+    }
+    
+    fn show_backup_progress(&mut self, ctx: &egui::Context) {
+        let Some(job) = &mut self.backup_job else {
+            return;
+        };
+        
+        let mut finished = None;
+        for event in job.events.try_iter() {
+            match event {
+                BackupEvent::Progress(progress) => job.progress = Some(progress),
+                BackupEvent::Done(result) => finished = Some(result),
+            }
+        }
+        
+        egui::Window::new(job.title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                match &job.progress {
+                    Some(progress) => {
+                        let detail = match progress.stage {
+                            BackupStage::Writing => format!(
+                                "{}: {} of {} bytes",
+                                progress.stage, progress.bytes_written, progress.total_bytes
+                            ),
+                            _ => format!(
+                                "{}: {} of {} entries",
+                                progress.stage, progress.entries_processed, progress.total_entries
+                            ),
+                        };
+                        ui.label(detail);
+                        ui.add(egui::ProgressBar::new(progress.fraction()).show_percentage());
+                    }
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Starting...");
+                        });
+                    }
+                }
+            });
+        ctx.request_repaint_after(Duration::from_millis(100));
+        
+        let Some(result) = finished else {
+            return;
+        };
+        let restored = job.reloads_entries;
+        self.backup_job = None;
+        match result {
+            Ok(message) => {
+                self.success_message = message;
+                self.show_backup = false;
+                if restored {
+                    self.load_entries();
+                    self.load_secrets();
+                }
+            }
+            Err(e) => self.error_message = e,
+        }
+    }
+    
+    fn import_config(&self, browser: &str) -> ImportConfig {
+        ImportConfig {
+            browser_type: match browser {
+                "chrome" => BrowserType::Chrome,
+                "firefox" => BrowserType::Firefox,
+                "edge" => BrowserType::Edge,
+                "opera" => BrowserType::Opera,
+                "brave" => BrowserType::Brave,
+                "safari" => BrowserType::Safari,
+                _ => BrowserType::Chrome,
+            },
+            format: ImportFormat::Csv,
+            skip_duplicates: true,
+            merge_duplicates: false,
+            import_folders_as_tags: true,
+            default_tags: vec![format!("imported-{}", browser)],
+            password_strength_check: false,
+            cleanup_urls: true,
+            url_rules: UrlCleanupRules {
+                strip_query: self.import_strip_query,
+                strip_www: self.import_strip_www,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+    
+    fn preview_browser_import(&mut self, browser: &str) {
+        // For now, show a file dialog to select CSV file from browser export
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV Files", &["csv"])
+            .set_title(format!("Select {} password export file", browser))
+            .pick_file()
+        {
+            self.error_message.clear();
+            match BrowserImporter::preview_import(&path, &self.import_config(browser)) {
+                Ok(entries) => {
+                    self.import_preview = Some(ImportPreviewState {
+                        browser: browser.to_string(),
+                        path,
+                        entries,
+                        column_mapping: None,
+                    });
+                }
+                Err(e) => {
+                    self.error_message = format!("Import failed: {}", e);
+                }
+            }
+        }
+    }
+    
+    /// Read the export and add its entries on a worker thread, reporting
+    /// each one added so the import dialog can show progress. The storage
+    /// lock is taken per entry, so the rest of the window stays usable.
+    fn import_from_browser(&mut self, browser: &str, path: PathBuf, column_mapping: Option<CsvColumnMapping>) {
+        let storage_mutex = self.storage.clone();
+        let runtime = self.runtime.clone();
+        let config = ImportConfig { column_mapping, ..self.import_config(browser) };
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancelled = cancel.clone();
+        let (sender, events) = mpsc::channel();
+        
+        self.error_message.clear();
+        
+        std::thread::spawn(move || {
+            let result = (|| {
+                let (imported_passwords, _result) =
+                    BrowserImporter::import_from_file(path, config).map_err(|e| e.to_string())?;
+                // Create a simple config for conversion
+                let convert_config = ImportConfig::default();
+                let entries = BrowserImporter::convert_to_entries(imported_passwords, &convert_config)
+                    .map_err(|e| e.to_string())?;
+                
+                let total = entries.len();
+                let mut imported = 0;
+                let _ = sender.send(ImportEvent::Progress { processed: 0, total });
+                for (processed, entry) in entries.into_iter().enumerate() {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return Ok(ImportOutcome { imported, total, cancelled: true });
+                    }
+                    let added = runtime.block_on(async {
+                        let storage_guard = storage_mutex.lock().unwrap();
+                        match storage_guard.as_ref() {
+                            Some(storage) => Ok(storage.add_entry(&entry).await.is_ok()),
+                            None => Err("Storage not initialized".to_string()),
+                        }
+                    })?;
+                    if added {
+                        imported += 1;
+                    }
+                    let _ = sender.send(ImportEvent::Progress { processed: processed + 1, total });
+                }
+                Ok(ImportOutcome { imported, total, cancelled: false })
+            })();
+            let _ = sender.send(ImportEvent::Done(result));
+        });
+        
+        self.import_job = Some(ImportJob {
+            browser: browser.to_string(),
+            events,
+            progress: None,
+            cancel,
+        });
+    }
+    
+    fn show_import_progress(&mut self, ui: &mut egui::Ui) {
+        let Some(job) = &self.import_job else {
+            return;
+        };
+        let cancelling = job.cancel.load(Ordering::Relaxed);
+        
+        match job.progress {
+            Some((processed, total)) => {
+                ui.label(format!("Processed {} of {} entries", processed, total));
+                let fraction = if total == 0 { 1.0 } else { processed as f32 / total as f32 };
+                ui.add(egui::ProgressBar::new(fraction).show_percentage());
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Reading the export...");
+                });
+            }
+        }
+        ui.add_space(10.0);
+        let label = if cancelling { "Cancelling..." } else { "❌ Cancel Import" };
+        if ui.add_enabled(!cancelling, egui::Button::new(label)).clicked() {
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+    
+    /// Take progress from the running import, and once it finishes report
+    /// the outcome and reload the entries.
+    fn poll_import_job(&mut self, ctx: &egui::Context) {
+        let Some(job) = &mut self.import_job else {
+            return;
+        };
+        
+        let mut finished = None;
+        for event in job.events.try_iter() {
+            match event {
+                ImportEvent::Progress { processed, total } => job.progress = Some((processed, total)),
+                ImportEvent::Done(result) => finished = Some(result),
+            }
+        }
+        ctx.request_repaint_after(Duration::from_millis(100));
+        
+        let Some(result) = finished else {
+            return;
+        };
+        let browser = std::mem::take(&mut job.browser);
+        self.import_job = None;
+        match result {
+            Ok(outcome) if outcome.cancelled => {
+                self.success_message = format!(
+                    "Import cancelled: {} of {} passwords from {} were imported",
+                    outcome.imported, outcome.total, browser
+                );
+                self.load_entries();
+                self.show_import = false;
+            }
+            Ok(outcome) => {
+                self.success_message = format!("Successfully imported {} passwords from {}", outcome.imported, browser);
+                self.load_entries();
+                self.show_import = false;
+            }
+            Err(e) => {
+                self.error_message = format!("Import failed: {}", e);
+            }
+        }
+    }
+    
+    fn create_backup(&mut self) {
+        if self.backup_password.is_empty() {
+            self.error_message = "Enter a backup password".to_string();
+            return;
+        }
+        if self.backup_password != self.backup_password_confirm {
+            self.error_message = "Backup passwords do not match".to_string();
+            return;
+        }
+        
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("PwGen Backup", &["pwgenbak"])
+            .set_file_name(format!("pwgen_backup_{}.pwgenbak", chrono::Utc::now().format("%Y%m%d_%H%M%S")))
+            .save_file()
+        {
+            self.start_backup_job(BackupRequest::Create(path));
+        }
+    }
+    
+    fn restore_backup(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("PwGen Backup", &["pwgenbak"])
+            .add_filter("Legacy JSON Backup", &["json"])
+            .pick_file()
+        {
+            // Plaintext backups from older versions have no password
+            let legacy_plaintext = self
+                .runtime
+                .block_on(BackupManager::is_legacy_plaintext_backup(&path))
+                .unwrap_or(false);
+            if self.backup_password.is_empty() && !legacy_plaintext {
+                self.error_message = "Enter the password the backup was created with".to_string();
+                return;
+            }
+            
+            let storage_mutex = self.storage.clone();
+            let conflicts = self.runtime.block_on(async {
+                let storage_guard = storage_mutex.lock().unwrap();
+                let Some(storage) = storage_guard.as_ref() else {
+                    return Err(pwgen_core::Error::VaultLocked);
+                };
+                BackupManager::find_restore_conflicts(&path, &self.backup_password, storage).await
+            });
+            match conflicts {
+                Ok(conflicts) if conflicts.is_empty() => {
+                    self.start_backup_job(BackupRequest::Restore(path, RestoreOptions::default()));
+                }
+                Ok(conflicts) => {
+                    self.restore_conflicts = Some(RestoreConflictDialog {
+                        path,
+                        choices: vec![None; conflicts.len()],
+                        conflicts,
+                        resolution: ConflictResolution::Merge,
+                    });
+                }
+                Err(e) => self.error_message = format!("Restore failed: {}", e),
+            }
+        }
+    }
+    
+    fn show_restore_conflicts_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.restore_conflicts else {
+            return;
+        };
+        
+        let mut open = true;
+        let mut restore = false;
+        let mut cancel = false;
+        egui::Window::new("📥 Restore Conflicts")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(550.0)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} backup entries match entries already in the vault, by ID or by site and username.",
+                    dialog.conflicts.len()
+                ));
+                ui.horizontal(|ui| {
+                    ui.label("For all conflicts:");
+                    for resolution in CONFLICT_RESOLUTIONS {
+                        ui.radio_value(&mut dialog.resolution, resolution, conflict_resolution_label(resolution));
+                    }
+                });
+                ui.small(
+                    "Keep newer keeps whichever was changed last; Keep both adds the backup entry as a new entry.",
+                );
+                ui.separator();
+                
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("restore_conflicts").num_columns(4).striped(true).show(ui, |ui| {
+                        ui.strong("Entry");
+                        ui.strong("Backup changed");
+                        ui.strong("Vault changed");
+                        ui.strong("Action");
+                        ui.end_row();
+                        
+                        for (index, conflict) in dialog.conflicts.iter().enumerate() {
+                            ui.label(format!("{} ({})", conflict.backup.site, conflict.backup.username));
+                            ui.label(conflict.backup.updated_at.format("%Y-%m-%d %H:%M").to_string());
+                            ui.label(conflict.existing.updated_at.format("%Y-%m-%d %H:%M").to_string());
+                            let choice = &mut dialog.choices[index];
+                            let selected = match choice {
+                                Some(resolution) => conflict_resolution_label(*resolution).to_string(),
+                                None => format!("Default ({})", conflict_resolution_label(dialog.resolution)),
+                            };
+                            egui::ComboBox::from_id_salt(("restore_conflict", index))
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(choice, None, "Default");
+                                    for resolution in CONFLICT_RESOLUTIONS {
+                                        ui.selectable_value(
+                                            choice,
+                                            Some(resolution),
+                                            conflict_resolution_label(resolution),
+                                        );
+                                    }
+                                });
+                            ui.end_row();
+                        }
+                    });
+                });
+                
+                ui.separator();
+                ui.horizontal(|ui| {
+                    restore = ui.button("📥 Restore").clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        
+        if restore {
+            if let Some(dialog) = self.restore_conflicts.take() {
+                let per_conflict = dialog
+                    .conflicts
+                    .iter()
+                    .zip(dialog.choices)
+                    .filter_map(|(conflict, choice)| Some((conflict.backup.id.clone(), choice?)))
+                    .collect();
+                let options = RestoreOptions { conflict_resolution: dialog.resolution, per_conflict };
+                self.start_backup_job(BackupRequest::Restore(dialog.path, options));
+            }
+        } else if cancel || !open {
+            self.restore_conflicts = None;
+        }
+    }
+    
+    /// Run a backup or restore on a worker thread so the progress window keeps
+    /// repainting. The storage lock is held until the job finishes.
+    fn start_backup_job(&mut self, request: BackupRequest) {
+        let password = std::mem::take(&mut self.backup_password);
+        self.backup_password_confirm.clear();
+        self.error_message.clear();
+        
+        let (sender, events) = mpsc::channel();
+        let title = match request {
+            BackupRequest::Create(_) => "💾 Creating Backup",
+            BackupRequest::Restore(..) => "📥 Restoring Backup",
+        };
+        let reloads_entries = matches!(request, BackupRequest::Restore(..));
+        let storage_mutex = self.storage.clone();
+        let secrets_storage_mutex = self.secrets_storage.clone();
+        let runtime = self.runtime.clone();
+        
+        std::thread::spawn(move || {
+            let progress_sender = sender.clone();
+            let progress = move |progress| {
+                let _ = progress_sender.send(BackupEvent::Progress(progress));
+            };
+            
+            let result = runtime.block_on(async {
+                let mut storage_guard = storage_mutex.lock().unwrap();
+                let Some(storage) = storage_guard.as_mut() else {
+                    return Err("Storage not initialized".to_string());
+                };
+                let secrets_guard = secrets_storage_mutex.lock().unwrap();
+                let secrets_storage = secrets_guard.as_ref();
+                
+                match request {
+                    BackupRequest::Create(path) => {
+                        let metadata = BackupManager::create_backup(storage, secrets_storage, &path, &password, progress)
+                            .await
+                            .map_err(|e| format!("Backup failed: {}", e))?;
+                        Ok(format!("Backup of {} entries created successfully!", metadata.entry_count))
+                    }
+                    BackupRequest::Restore(path, options) => {
+                        let result =
+                            BackupManager::restore_backup(&path, &password, storage, secrets_storage, options, progress)
+                            .await
+                            .map_err(|e| format!("Restore failed: {}", e))?;
+                        let mut message = format!(
+                            "Restored backup of {} entries: {} imported, {} updated, {} skipped, {} errors",
+                            result.total_entries,
+                            result.restored_count - result.updated_count,
+                            result.updated_count,
+                            result.skipped_count,
+                            result.error_count
+                        );
+                        for warning in &result.warnings {
+                            message.push_str(&format!("\n⚠ {}", warning));
+                        }
+                        Ok(message)
+                    }
+                }
+            });
+            let _ = sender.send(BackupEvent::Done(result));
+        });
+        
+        self.backup_job = Some(BackupJob { title, events, progress: None, reloads_entries });
+    }
+    
+    fn show_tag_edit_dialog(&mut self, ctx: &egui::Context) {
+        if let Some(entry_id) = &self.editing_tags_for_entry.clone() {
+            egui::Window::new("Edit Tags")
+                .collapsible(false)
+                .resizable(false)
+                .default_width(300.0)
+                .show(ctx, |ui| {
+                    ui.vertical(|ui| {
+                        ui.label("Tags (comma-separated):");
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.temp_tags)
+                                .desired_width(ui.available_width() - 20.0)
+                                .hint_text("tag1, tag2, tag3...")
+                        );
+                        
+                        show_tag_suggestions(ui, &self.tag_counts, &mut self.temp_tags);
+                        
+                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            self.save_tags(entry_id.clone());
+                        }
+                        
+                        ui.add_space(10.0);
+                        
+                        ui.horizontal(|ui| {
+                            if ui.button("Save").clicked() {
+                                self.save_tags(entry_id.clone());
+                            }
+                            if ui.button("Cancel").clicked() {
+                                self.editing_tags_for_entry = None;
+                                self.temp_tags.clear();
+                            }
+                        });
+                    });
+                });
+        }
+    }
+    
+    fn save_tags(&mut self, entry_id: String) {
+        let storage_mutex = self.storage.clone();
+        let runtime = self.runtime.clone();
+        
+        // Parse tags from temp_tags
+        let new_tags: Vec<String> = self.temp_tags
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let new_tags = match EntryRules::load() {
+            Ok(rules) => rules.canonical_tags(&new_tags),
+            Err(e) => {
+                self.error_message = format!("Failed to load entry rules: {}", e);
+                return;
+            }
+        };
+        
+        // Find and update the entry
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == entry_id) {
+            entry.tags = new_tags.clone();
+            entry.updated_at = chrono::Utc::now();
+            
+            // Save to storage
+            let entry_to_save = entry.clone();
+            if let Some(tag_counts) = runtime.block_on(async {
+                let storage_guard = storage_mutex.lock().unwrap();
+                if let Some(storage) = storage_guard.as_ref() {
+                    let _ = storage.update_entry(&entry_to_save).await;
+                    storage.tag_counts().await.ok()
+                } else {
+                    None
+                }
+            }) {
+                self.tag_counts = tag_counts;
+            }
+            
+            if let Some(result) = self.search_results.iter_mut().find(|e| e.id == entry_id) {
+                result.tags = new_tags;
+            }
+            
+            self.success_message = "Tags updated successfully!".to_string();
+            self.filter_entries(); // Refresh the display
+        }
+        
+        // Clear editing state
+        self.editing_tags_for_entry = None;
+        self.temp_tags.clear();
+    }
+    
+    fn show_secrets_view(&mut self, ctx: &egui::Context) {
+        if !self.show_secrets_view {
+            return;
+        }
+        
+        egui::Window::new("🔐 Secrets Manager")
+            .collapsible(false)
+            .resizable(true)
+            .default_size([800.0, 600.0])
+            .show(ctx, |ui| {
+                ui.vertical(|ui| {
+                    // Header with close button
+                    ui.horizontal(|ui| {
+                        ui.heading("Secrets Manager");
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("✖ Close").clicked() {
+                                self.show_secrets_view = false;
+                            }
+                        });
+                    });
+                    
+                    ui.separator();
+                    
+                    // Tab bar for different secret types
+                    ui.horizontal(|ui| {
+                        ui.label("Type:");
+                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::ApiKey), "🔑 API Keys").clicked() {
+                            self.current_secret_tab = SecretType::ApiKey;
+                        }
+                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::SshKey), "🔐 SSH Keys").clicked() {
+                            self.current_secret_tab = SecretType::SshKey;
+                        }
+                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::Document), "📄 Documents").clicked() {
+                            self.current_secret_tab = SecretType::Document;
+                        }
+                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::Configuration), "⚙ Config/Env").clicked() {
+                            self.current_secret_tab = SecretType::Configuration;
+                        }
+                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::SecureNote), "📝 Secure Notes").clicked() {
+                            self.current_secret_tab = SecretType::SecureNote;
+                        }
+                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::CreditCard), "💳 Cards").clicked() {
+                            self.current_secret_tab = SecretType::CreditCard;
+                        }
+                        if ui.selectable_label(matches!(self.current_secret_tab, SecretType::ConnectionString), "🔗 Database").clicked() {
+                            self.current_secret_tab = SecretType::ConnectionString;
+                        }
+                    });
+                    
+                    ui.separator();
+                    
+                    // Add new secret button
+                    if ui.button(format!("➕ Add New {}", match self.current_secret_tab {
+                        SecretType::ApiKey => "API Key",
+                        SecretType::SshKey => "SSH Key", 
+                        SecretType::Document => "Document",
+                        SecretType::Configuration => "Configuration",
+                        SecretType::SecureNote => "Secure Note",
+                        SecretType::CreditCard => "Card",
+                        SecretType::ConnectionString => "Database Connection",
+                        _ => "Secret",
+                    })).clicked() {
+                        if self.edit_secret.is_some() {
+                            self.clear_secret_form();
+                        }
+                        self.selected_secret_type = self.current_secret_tab.clone();
+                        self.show_add_secret_dialog = true;
+                    }
+                    
+                    ui.add_space(10.0);
+                    
+                    // Secrets list for current tab
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        ui.label("Secrets list will be implemented next...");
+                        
+                        // Placeholder for now - we'll implement the actual list next
+                        match self.current_secret_tab {
+                            SecretType::ApiKey => {
+                                ui.label("API Keys will be listed here");
+                            }
+                            SecretType::SshKey => {
+                                ui.label("SSH Keys will be listed here");
+                            }
+                            SecretType::Document => {
+                                let documents: Vec<_> = self
+                                    .secrets
+                                    .iter()
+                                    .filter(|secret| secret.secret_type == SecretType::Document)
+                                    .cloned()
+                                    .collect();
+                                if documents.is_empty() {
+                                    ui.label("No documents yet");
+                                }
+                                for document in documents {
+                                    ui.horizontal(|ui| {
+                                        if ui.small_button("👁").on_hover_text("View").clicked() {
+                                            if let Some(secret) = self.find_secret(&document.id) {
+                                                self.view_document(secret);
+                                            }
+                                        }
+                                        ui.label(&document.name);
+                                        if let Some(description) = &document.description {
+                                            ui.small(description);
+                                        }
+                                    });
+                                }
+                            }
+                            SecretType::Configuration => {
+                                ui.label("Environment variables and config will be listed here");
+                            }
+                            SecretType::SecureNote => {
+                                ui.label("Secure notes will be listed here");
+                            }
+                            SecretType::ConnectionString => {
+                                ui.label("Database connections will be listed here");
+                            }
+                            _ => {
+                                ui.label("Other secrets will be listed here");
+                            }
+                        }
+                    });
+                });
+            });
+    }
     
     fn show_add_secret_dialog(&mut self, ctx: &egui::Context) {
         if !self.show_add_secret_dialog {
             return;
         }
         
-        let dialog_title = match self.selected_secret_type {
-            SecretType::ApiKey => "Add API Key",
-            SecretType::SshKey => "Add SSH Key",
-            SecretType::Document => "Add Document",
-            SecretType::Configuration => "Add Configuration",
-            SecretType::SecureNote => "Add Secure Note",
-            SecretType::ConnectionString => "Add Database Connection",
-            _ => "Add Secret",
+        let kind = match self.selected_secret_type {
+            SecretType::ApiKey => "API Key",
+            SecretType::SshKey => "SSH Key",
+            SecretType::Document => "Document",
+            SecretType::Configuration => "Configuration",
+            SecretType::SecureNote => "Secure Note",
+            SecretType::CreditCard => "Card",
+            SecretType::ConnectionString => "Database Connection",
+            _ => "Secret",
+        };
+        let dialog_title = if self.edit_secret.is_some() {
+            format!("Edit {}", kind)
+        } else {
+            format!("Add {}", kind)
         };
         
         egui::Window::new(dialog_title)
@@ -3008,6 +8763,11 @@ impl PwGenApp {
                                 .hint_text("Comma-separated tags...")
                         );
                         ui.end_row();
+                        
+                        ui.label("");
+                        ui.checkbox(&mut self.secret_require_reauth, "🔒 Ask for the master password before revealing or copying")
+                            .on_hover_text(REQUIRE_REAUTH_HINT);
+                        ui.end_row();
                     });
                     
                     ui.separator();
@@ -3019,6 +8779,7 @@ impl PwGenApp {
                         SecretType::Document => self.show_document_fields(ui),
                         SecretType::Configuration => self.show_config_fields(ui),
                         SecretType::SecureNote => self.show_note_fields(ui),
+                        SecretType::CreditCard => self.show_credit_card_fields(ui),
                         SecretType::ConnectionString => self.show_database_fields(ui),
                         _ => {
                             ui.label("Secret type not yet implemented");
@@ -3108,370 +8869,1745 @@ impl PwGenApp {
             );
             ui.end_row();
             
-            ui.label("Public Key:");
-            ui.add(
-                egui::TextEdit::multiline(&mut self.ssh_public_key)
-                    .desired_rows(3)
-                    .font(egui::TextStyle::Monospace)
-                    .hint_text("ssh-rsa AAAAB3NzaC1yc2E...")
-            );
+            ui.label("Public Key:");
+            ui.add(
+                egui::TextEdit::multiline(&mut self.ssh_public_key)
+                    .desired_rows(3)
+                    .font(egui::TextStyle::Monospace)
+                    .hint_text("ssh-rsa AAAAB3NzaC1yc2E...")
+            );
+            ui.end_row();
+            
+            ui.label("Passphrase:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.ssh_passphrase)
+                    .password(true)
+            );
+            ui.end_row();
+            
+            ui.label("Comment:");
+            ui.text_edit_singleline(&mut self.ssh_comment);
+            ui.end_row();
+            
+            if self.ssh_key_type == "RSA" {
+                ui.label("Key Size:");
+                egui::ComboBox::from_id_salt("ssh_key_bits")
+                    .selected_text(format!("{} bits", self.ssh_key_bits))
+                    .show_ui(ui, |ui| {
+                        for bits in [2048, 3072, 4096] {
+                            ui.selectable_value(&mut self.ssh_key_bits, bits, format!("{} bits", bits));
+                        }
+                    });
+                ui.end_row();
+            }
+            
+            ui.label("");
+            if self.ssh_keygen_job.is_some() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Generating key pair...");
+                });
+            } else if ui
+                .button("🎲 Generate Key Pair")
+                .on_hover_text(SSH_KEYGEN_HINT)
+                .clicked()
+            {
+                self.start_ssh_keygen();
+            }
+            ui.end_row();
+        });
+        
+        self.poll_ssh_keygen(ui.ctx());
+    }
+    
+    fn selected_ssh_key_type(&self) -> SshKeyType {
+        match self.ssh_key_type.as_str() {
+            "RSA" => SshKeyType::Rsa,
+            "Ed25519" => SshKeyType::Ed25519,
+            "ECDSA" => SshKeyType::Ecdsa,
+            "DSA" => SshKeyType::Dsa,
+            _ => SshKeyType::Rsa,
+        }
+    }
+    
+    /// Generate a key pair for the SSH key form on a background thread; RSA
+    /// keys can take a few seconds.
+    fn start_ssh_keygen(&mut self) {
+        let params = SshKeyGenParams {
+            key_type: self.selected_ssh_key_type(),
+            bits: Some(self.ssh_key_bits),
+            comment: Some(self.ssh_comment.clone()).filter(|c| !c.is_empty()),
+            passphrase: Some(self.ssh_passphrase.clone()).filter(|p| !p.is_empty()),
+        };
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(SshKeyManager::generate_key_pair(&params));
+        });
+        self.ssh_keygen_job = Some(receiver);
+    }
+    
+    fn poll_ssh_keygen(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.ssh_keygen_job else {
+            return;
+        };
+        match job.try_recv() {
+            Ok(Ok((private_key, public_key))) => {
+                self.ssh_private_key = private_key;
+                self.ssh_public_key = public_key;
+                self.success_message = "SSH key pair generated".to_string();
+            }
+            Ok(Err(e)) => self.error_message = format!("Failed to generate SSH key: {}", e),
+            Err(mpsc::TryRecvError::Empty) => {
+                ctx.request_repaint_after(Duration::from_millis(100));
+                return;
+            }
+            Err(mpsc::TryRecvError::Disconnected) => {}
+        }
+        self.ssh_keygen_job = None;
+    }
+    
+    fn show_document_fields(&mut self, ui: &mut egui::Ui) {
+        ui.heading("📄 Document Details");
+        egui::Grid::new("document_fields").num_columns(2).show(ui, |ui| {
+            ui.label("Filename:");
+            ui.text_edit_singleline(&mut self.document_filename);
+            ui.end_row();
+            
+            ui.label("Upload File:");
+            match &self.document_import {
+                Some(job) => {
+                    let (read, total) = job.progress;
+                    ui.add(
+                        egui::ProgressBar::new(read as f32 / total.max(1) as f32)
+                            .text(format!("Reading {}...", job.filename))
+                            .desired_width(250.0),
+                    );
+                }
+                None => {
+                    if ui.button("📁 Choose File")
+                        .on_hover_text(format!(
+                            "Files up to {} MB; the limit can be changed in Settings",
+                            self.gui_config.max_document_size_mb
+                        ))
+                        .clicked()
+                    {
+                        if let Some(path) = rfd::FileDialog::new().pick_file() {
+                            self.start_document_import(path);
+                        }
+                    }
+                }
+            }
+            ui.end_row();
+            
+            if !self.document_content.is_empty() {
+                ui.label("File Size:");
+                ui.label(format!("{} bytes", self.document_content.len()));
+                ui.end_row();
+            }
+        });
+        
+        self.poll_document_import(ui.ctx());
+    }
+    
+    /// Read `path` into the document form on a worker thread, so large files
+    /// don't freeze the window.
+    fn start_document_import(&mut self, path: PathBuf) {
+        let options = DocumentImportOptions {
+            compress: false,
+            max_size: self.gui_config.max_document_size_mb.saturating_mul(1024 * 1024),
+        };
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let document_type = DocumentAttachment::guess_document_type(&path);
+            let progress_sender = sender.clone();
+            let imported = DocumentManager::import_file_with(&path, document_type, &options, |read, total| {
+                let _ = progress_sender.send(DocumentImportEvent::Progress { read, total });
+            });
+            let _ = sender.send(DocumentImportEvent::Done(
+                imported.and_then(|(data, _)| DocumentManager::read_content(&data)),
+            ));
+        });
+        self.document_import = Some(DocumentImportJob { filename, events: receiver, progress: (0, 1) });
+    }
+    
+    fn poll_document_import(&mut self, ctx: &egui::Context) {
+        let Some(job) = &mut self.document_import else {
+            return;
+        };
+        loop {
+            match job.events.try_recv() {
+                Ok(DocumentImportEvent::Progress { read, total }) => job.progress = (read, total),
+                Ok(DocumentImportEvent::Done(result)) => {
+                    match result {
+                        Ok(content) => {
+                            self.document_content = content;
+                            self.document_filename = job.filename.clone();
+                        }
+                        Err(e @ pwgen_core::Error::DocumentTooLarge { .. }) => {
+                            self.error_message = format!("{}. The limit can be raised in Settings.", e);
+                        }
+                        Err(e) => self.error_message = format!("Failed to read file: {}", e),
+                    }
+                    break;
+                }
+                Err(mpsc::TryRecvError::Empty) => {
+                    ctx.request_repaint_after(Duration::from_millis(100));
+                    return;
+                }
+                Err(mpsc::TryRecvError::Disconnected) => break,
+            }
+        }
+        self.document_import = None;
+    }
+    
+    fn show_config_fields(&mut self, ui: &mut egui::Ui) {
+        ui.heading("⚙ Configuration Variables");
+        ui.horizontal(|ui| {
+            ui.label("Environment Variables (KEY=VALUE format, one per line):");
+            self.external_edit_button(ui, ExternalEditTarget::ConfigVariables);
+        });
+        let editable = self.external_edit.is_none();
+        ui.add_enabled(
+            editable,
+            egui::TextEdit::multiline(&mut self.config_variables)
+                .desired_rows(10)
+                .font(egui::TextStyle::Monospace)
+                .hint_text("DATABASE_URL=postgresql://...\nAPI_KEY=your_key_here\nDEBUG=true")
+        );
+    }
+    
+    fn show_note_fields(&mut self, ui: &mut egui::Ui) {
+        ui.heading("📝 Secure Note");
+        egui::Grid::new("note_fields").num_columns(2).show(ui, |ui| {
+            ui.label("Title:");
+            ui.text_edit_singleline(&mut self.note_title);
+            ui.end_row();
+            
+            // HTML and rich text notes made elsewhere keep their format
+            if matches!(self.note_format, NoteFormat::PlainText | NoteFormat::Markdown) {
+                ui.label("Format:");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.note_format, NoteFormat::PlainText, "Plain text");
+                    ui.radio_value(&mut self.note_format, NoteFormat::Markdown, "Markdown");
+                });
+                ui.end_row();
+            }
+        });
+        
+        ui.add_space(5.0);
+        let markdown = self.note_format == NoteFormat::Markdown;
+        ui.horizontal(|ui| {
+            ui.label("Content:");
+            if markdown {
+                ui.selectable_value(&mut self.note_preview, false, "✏ Edit");
+                ui.selectable_value(&mut self.note_preview, true, "👁 Preview");
+            }
+            self.external_edit_button(ui, ExternalEditTarget::NoteContent);
+        });
+        if markdown && self.note_preview {
+            let mut clicked = None;
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    clicked = show_markdown(ui, &self.note_content);
+                });
+            });
+            if let Some(url) = clicked {
+                if let Err(e) = open::that(&url) {
+                    self.error_message = format!("Failed to open {}: {}", url, e);
+                }
+            }
+            return;
+        }
+        let editable = self.external_edit.is_none();
+        ui.add_enabled(
+            editable,
+            egui::TextEdit::multiline(&mut self.note_content)
+                .desired_rows(12)
+                .hint_text("Enter your secure note content here...")
+        );
+    }
+    
+    fn show_credit_card_fields(&mut self, ui: &mut egui::Ui) {
+        ui.heading("💳 Card Details");
+        egui::Grid::new("credit_card_fields").num_columns(2).show(ui, |ui| {
+            ui.label("Cardholder:");
+            ui.text_edit_singleline(&mut self.card_cardholder);
+            ui.end_row();
+            
+            ui.label("Card Number:");
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.card_number)
+                        .password(!self.show_card_number)
+                        .font(egui::TextStyle::Monospace)
+                );
+                if ui.button(if self.show_card_number { "🙈" } else { "👁" }).clicked() {
+                    self.show_card_number = !self.show_card_number;
+                }
+            });
+            ui.end_row();
+            
+            if !self.card_number.trim().is_empty() {
+                ui.label("");
+                if luhn_check(&self.card_number) {
+                    ui.small(format!("✅ {}", CardBrand::from_number(&self.card_number)));
+                } else {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), "⚠ Not a valid card number");
+                }
+                ui.end_row();
+            }
+            
+            ui.label("Expiry:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.card_expiry)
+                    .hint_text("MM/YY")
+                    .desired_width(60.0)
+            );
+            ui.end_row();
+            
+            ui.label("CVV:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.card_cvv)
+                    .password(true)
+                    .desired_width(60.0)
+            );
+            ui.end_row();
+            
+            ui.label("Issuer:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.card_issuer)
+                    .hint_text("Issuing bank")
+            );
+            ui.end_row();
+            
+            ui.label("Billing ZIP:");
+            ui.text_edit_singleline(&mut self.card_billing_zip);
+            ui.end_row();
+        });
+    }
+    
+    /// Show or hide the full number of a card in the secrets list, asking
+    /// first for sensitive cards.
+    fn toggle_card_reveal(&mut self, secret: &DecryptedSecretEntry) {
+        if self.revealed_card.as_deref() == Some(secret.id.as_str()) {
+            self.revealed_card = None;
+        } else if secret.metadata.require_reauth || requires_reveal_confirmation(&secret.tags, self.gui_config.confirm_copy_reveal) {
+            self.pending_reveal = Some(PendingReveal {
+                what: format!("Card number for {}", secret.name),
+                action: RevealAction::ShowCardNumber(secret.id.clone()),
+                reauth: secret.metadata.require_reauth,
+            });
+        } else {
+            self.revealed_card = Some(secret.id.clone());
+        }
+    }
+    
+    /// "Edit in $EDITOR" button, or a spinner while the editor is open.
+    fn external_edit_button(&mut self, ui: &mut egui::Ui, target: ExternalEditTarget) {
+        if self.external_edit.as_ref().is_some_and(|edit| edit.target == target) {
+            ui.spinner();
+            ui.label("Waiting for the editor to close...");
+            return;
+        }
+        let button = ui
+            .add_enabled(self.external_edit.is_none(), egui::Button::new("✏ Edit in $EDITOR"))
+            .on_hover_text(EXTERNAL_EDITOR_HINT);
+        if button.clicked() {
+            self.start_external_edit(target);
+        }
+    }
+    
+    fn start_external_edit(&mut self, target: ExternalEditTarget) {
+        let (content, extension) = match target {
+            ExternalEditTarget::NoteContent => {
+                let extension = if self.note_format == NoteFormat::Markdown { "md" } else { "txt" };
+                (self.note_content.clone(), extension)
+            }
+            ExternalEditTarget::ConfigVariables => (self.config_variables.clone(), "env"),
+        };
+        let (sender, result) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(edit_in_external_editor(&content, extension));
+        });
+        self.external_edit = Some(ExternalEdit { target, result });
+    }
+    
+    /// Take the edited text back once the external editor has exited.
+    fn poll_external_edit(&mut self, ctx: &egui::Context) {
+        let Some(edit) = &self.external_edit else {
+            return;
+        };
+        let outcome = match edit.result.try_recv() {
+            Ok(outcome) => outcome,
+            Err(mpsc::TryRecvError::Empty) => {
+                ctx.request_repaint_after(Duration::from_millis(250));
+                return;
+            }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Err(pwgen_core::Error::Other("Editor thread stopped".to_string()))
+            }
+        };
+        let target = edit.target;
+        self.external_edit = None;
+        match outcome {
+            Ok(Some(edited)) => match target {
+                ExternalEditTarget::NoteContent => self.note_content = edited,
+                ExternalEditTarget::ConfigVariables => self.config_variables = edited,
+            },
+            Ok(None) => {}
+            Err(e) => {
+                self.error_message = format!("Failed to edit in {}: {}", editor_command(), e);
+            }
+        }
+    }
+    
+    fn show_database_fields(&mut self, ui: &mut egui::Ui) {
+        ui.heading("🔗 Database Connection");
+        egui::Grid::new("database_fields").num_columns(2).show(ui, |ui| {
+            ui.label("Database Type:");
+            egui::ComboBox::from_id_salt("db_type")
+                .selected_text(&self.db_type)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.db_type, "PostgreSQL".to_string(), "PostgreSQL");
+                    ui.selectable_value(&mut self.db_type, "MySQL".to_string(), "MySQL");
+                    ui.selectable_value(&mut self.db_type, "SQLite".to_string(), "SQLite");
+                    ui.selectable_value(&mut self.db_type, "MongoDB".to_string(), "MongoDB");
+                    ui.selectable_value(&mut self.db_type, "Redis".to_string(), "Redis");
+                });
+            ui.end_row();
+            
+            ui.label("Host:");
+            ui.text_edit_singleline(&mut self.db_host);
+            ui.end_row();
+            
+            ui.label("Port:");
+            ui.text_edit_singleline(&mut self.db_port);
+            ui.end_row();
+            
+            ui.label("Database Name:");
+            ui.text_edit_singleline(&mut self.db_name);
             ui.end_row();
             
-            ui.label("Passphrase:");
+            ui.label("Username:");
+            ui.text_edit_singleline(&mut self.db_username);
+            ui.end_row();
+            
+            ui.label("Password:");
             ui.add(
-                egui::TextEdit::singleline(&mut self.ssh_passphrase)
+                egui::TextEdit::singleline(&mut self.db_password)
                     .password(true)
             );
             ui.end_row();
-            
-            ui.label("Comment:");
-            ui.text_edit_singleline(&mut self.ssh_comment);
-            ui.end_row();
         });
     }
     
-    fn show_document_fields(&mut self, ui: &mut egui::Ui) {
-        ui.heading("📄 Document Details");
-        egui::Grid::new("document_fields").num_columns(2).show(ui, |ui| {
-            ui.label("Filename:");
-            ui.text_edit_singleline(&mut self.document_filename);
-            ui.end_row();
-            
-            ui.label("Upload File:");
-            if ui.button("📁 Choose File").clicked() {
-                if let Some(path) = rfd::FileDialog::new().pick_file() {
-                    match std::fs::read(&path) {
-                        Ok(content) => {
-                            self.document_content = content;
-                            self.document_filename = path.file_name()
-                                .unwrap_or_default()
-                                .to_string_lossy()
-                                .to_string();
-                        }
-                        Err(e) => {
-                            self.error_message = format!("Failed to read file: {}", e);
-                        }
-                    }
+    fn save_secret(&mut self) {
+        if self.external_edit.is_some() {
+            self.error_message = "Close the external editor before saving".to_string();
+            return;
+        }
+        if self.secret_name.trim().is_empty() {
+            self.error_message = "Secret name is required".to_string();
+            return;
+        }
+
+        let secrets_storage = self.secrets_storage.clone();
+        let current_secret_type = self.selected_secret_type.clone();
+        
+        // Create the secret data based on the selected type
+        let secret_data = match current_secret_type {
+            SecretType::ApiKey => {
+                use pwgen_core::api_keys::{ApiKeyProvider, ApiKeyPermissions, RotationInfo, UsageStats};
+                
+                let provider = match self.api_provider.as_str() {
+                    "AWS" => ApiKeyProvider::AWS,
+                    "Google Cloud" => ApiKeyProvider::GCP,
+                    "Azure" => ApiKeyProvider::Azure,
+                    "GitHub" => ApiKeyProvider::GitHub,
+                    "GitLab" => ApiKeyProvider::GitLab,
+                    "Docker Hub" => ApiKeyProvider::DockerHub,
+                    "Stripe" => ApiKeyProvider::Stripe,
+                    "Twilio" => ApiKeyProvider::Twilio,
+                    "SendGrid" => ApiKeyProvider::SendGrid,
+                    "Slack" => ApiKeyProvider::Slack,
+                    "Discord" => ApiKeyProvider::Discord,
+                    "OpenAI" => ApiKeyProvider::OpenAI,
+                    "Anthropic" => ApiKeyProvider::Anthropic,
+                    "Generic" => ApiKeyProvider::Generic,
+                    _ => ApiKeyProvider::Custom(self.api_provider.clone()),
+                };
+                
+                SecretData::ApiKey {
+                    provider,
+                    key_id: self.api_key_id.clone(),
+                    api_key: self.api_key.clone(),
+                    api_secret: if self.api_secret.is_empty() { None } else { Some(self.api_secret.clone()) },
+                    token_type: "Bearer".to_string(),
+                    permissions: ApiKeyPermissions {
+                        read: true,
+                        write: false,
+                        admin: false,
+                        scopes: vec![],
+                        resource_access: std::collections::HashMap::new(),
+                    },
+                    environment: self.api_environment.clone(),
+                    endpoint_url: if self.api_endpoint.is_empty() { None } else { Some(self.api_endpoint.clone()) },
+                    rotation_info: RotationInfo {
+                        auto_rotate: false,
+                        rotation_period_days: None,
+                        last_rotated: None,
+                        next_rotation: None,
+                        rotation_reminder_days: None,
+                    },
+                    usage_stats: UsageStats {
+                        last_used: None,
+                        usage_count: 0,
+                        rate_limit_info: None,
+                        error_count: 0,
+                        last_error: None,
+                    },
+                }
+            },
+            SecretType::SshKey => {
+                SecretData::SshKey {
+                    key_type: self.selected_ssh_key_type(),
+                    private_key: if self.ssh_private_key.is_empty() { None } else { Some(self.ssh_private_key.clone()) },
+                    public_key: if self.ssh_public_key.is_empty() { None } else { Some(self.ssh_public_key.clone()) },
+                    passphrase: if self.ssh_passphrase.is_empty() { None } else { Some(self.ssh_passphrase.clone()) },
+                    comment: if self.ssh_comment.is_empty() { None } else { Some(self.ssh_comment.clone()) },
+                    fingerprint: None, // Could be calculated from the key
+                }
+            },
+            SecretType::Document => {
+                use sha2::{Sha256, Digest};
+                
+                let mut hasher = Sha256::new();
+                hasher.update(&self.document_content);
+                let checksum = format!("{:x}", hasher.finalize());
+                
+                SecretData::Document {
+                    filename: self.document_filename.clone(),
+                    content_type: DocumentManager::detect_content_type(&self.document_content, &self.document_filename),
+                    content: self.document_content.clone(),
+                    checksum,
+                    compression: Some(CompressionType::None),
+                }
+            },
+            SecretType::Configuration => {
+                // Parse environment variables from KEY=VALUE format
+                let mut variables = std::collections::HashMap::new();
+                for line in self.config_variables.lines() {
+                    if let Some((key, value)) = line.split_once('=') {
+                        variables.insert(key.trim().to_string(), value.trim().to_string());
+                    }
+                }
+                
+                SecretData::Configuration {
+                    format: ConfigFormat::EnvFile,
+                    variables,
+                    template: None,
+                    raw_variables: std::collections::HashMap::new(),
+                }
+            },
+            SecretType::SecureNote => {
+                SecretData::SecureNote {
+                    title: self.note_title.clone(),
+                    content: self.note_content.clone(),
+                    format: self.note_format.clone(),
+                }
+            },
+            SecretType::CreditCard => {
+                if !luhn_check(&self.card_number) {
+                    self.error_message = "Card number is not valid, please check it for typos".to_string();
+                    return;
+                }
+                let Some((expiry_month, expiry_year)) = parse_expiry(&self.card_expiry) else {
+                    self.error_message = "Expiry must be MM/YY".to_string();
+                    return;
+                };
+                let optional = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+                
+                let card = SecretData::CreditCard {
+                    cardholder: self.card_cardholder.trim().to_string(),
+                    number: normalize_card_number(&self.card_number),
+                    expiry_month,
+                    expiry_year,
+                    cvv: optional(&self.card_cvv),
+                    issuer: optional(&self.card_issuer),
+                    billing_zip: optional(&self.card_billing_zip),
+                };
+                if let Err(e) = card.validate() {
+                    self.error_message = e.to_string();
+                    return;
+                }
+                card
+            },
+            SecretType::ConnectionString => {
+                use pwgen_core::secrets::DatabaseType;
+                
+                let database_type = match self.db_type.as_str() {
+                    "PostgreSQL" => DatabaseType::PostgreSQL,
+                    "MySQL" => DatabaseType::MySQL,
+                    "SQLite" => DatabaseType::SQLite,
+                    "MongoDB" => DatabaseType::MongoDB,
+                    "Redis" => DatabaseType::Redis,
+                    "Oracle" => DatabaseType::Oracle,
+                    "SQL Server" => DatabaseType::SQLServer,
+                    _ => DatabaseType::Custom(self.db_type.clone()),
+                };
+                
+                let port = self.db_port.parse::<u16>().unwrap_or(5432);
+                let connection_string = format!(
+                    "{}://{}:{}@{}:{}/{}",
+                    self.db_type.to_lowercase(),
+                    self.db_username,
+                    self.db_password,
+                    self.db_host,
+                    port,
+                    self.db_name
+                );
+                
+                SecretData::ConnectionString {
+                    database_type,
+                    host: self.db_host.clone(),
+                    port: Some(port),
+                    database: self.db_name.clone(),
+                    username: self.db_username.clone(),
+                    password: self.db_password.clone(),
+                    connection_string,
+                    ssl_config: None,
+                }
+            },
+            _ => {
+                self.error_message = "Unsupported secret type".to_string();
+                return;
+            }
+        };
+
+        // Parse tags
+        let tags: Vec<String> = self.secret_tags
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let description = if self.secret_description.is_empty() { None } else { Some(self.secret_description.clone()) };
+        // Cards expire with the card, so they show up in the expiry notices
+        let card_expires_at = match &secret_data {
+            SecretData::CreditCard { expiry_month, expiry_year, .. } => card_expires_at(*expiry_month, *expiry_year),
+            _ => None,
+        };
+        let secret_entry = if let Some(existing) = &self.edit_secret {
+            // Keep the id, creation time and metadata; storage records the update
+            let mut updated = existing.clone();
+            updated.name = self.secret_name.clone();
+            updated.description = description;
+            updated.data = secret_data;
+            preserve_unedited_secret_data(&existing.data, &mut updated.data);
+            if card_expires_at.is_some() {
+                updated.expires_at = card_expires_at;
+            }
+            updated.tags = tags;
+            updated.metadata.require_reauth = self.secret_require_reauth;
+            updated.updated_at = Utc::now();
+            updated
+        } else {
+            DecryptedSecretEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: self.secret_name.clone(),
+                description,
+                secret_type: current_secret_type,
+                data: secret_data,
+                metadata: pwgen_core::secrets::SecretMetadata {
+                    require_reauth: self.secret_require_reauth,
+                    ..Default::default()
+                },
+                tags,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                last_accessed: None,
+                expires_at: card_expires_at,
+                favorite: false,
+            }
+        };
+        let is_update = self.edit_secret.is_some();
+
+        // Save the secret to storage
+        let runtime = self.runtime.clone();
+        let result = runtime.block_on(async {
+            let secrets_storage_guard = secrets_storage.lock().unwrap();
+            match secrets_storage_guard.as_ref() {
+                Some(storage) if is_update => storage.update_secret(&secret_entry).await,
+                Some(storage) => storage.add_secret(&secret_entry).await,
+                None => Err(pwgen_core::Error::Other("Secrets storage not initialized".to_string())),
+            }
+        });
+        if let Err(e) = result {
+            self.error_message = format!("Failed to save secret: {}", e);
+            return;
+        }
+
+        // Reload secrets from database
+        self.load_secrets();
+
+        self.success_message = if is_update { "Secret updated successfully!" } else { "Secret saved successfully!" }.to_string();
+        self.error_message.clear();
+        self.cancel_secret_creation();
+    }
+    
+    /// Open the add-secret dialog with `secret` loaded into the fields for
+    /// its type.
+    fn start_secret_edit(&mut self, secret: DecryptedSecretEntry) {
+        self.clear_secret_form();
+        self.secret_name = secret.name.clone();
+        self.secret_description = secret.description.clone().unwrap_or_default();
+        self.secret_tags = secret.tags.join(", ");
+        self.secret_require_reauth = secret.metadata.require_reauth;
+        
+        match &secret.data {
+            SecretData::ApiKey { provider, key_id, api_key, api_secret, environment, endpoint_url, .. } => {
+                self.api_provider = api_provider_label(provider);
+                self.api_key_id = key_id.clone();
+                self.api_key = api_key.clone();
+                self.api_secret = api_secret.clone().unwrap_or_default();
+                self.api_environment = environment.clone();
+                self.api_endpoint = endpoint_url.clone().unwrap_or_default();
+            }
+            SecretData::SshKey { key_type, private_key, public_key, passphrase, comment, .. } => {
+                self.ssh_key_type = match key_type {
+                    SshKeyType::Rsa => "RSA",
+                    SshKeyType::Ed25519 => "Ed25519",
+                    SshKeyType::Ecdsa => "ECDSA",
+                    SshKeyType::Dsa => "DSA",
+                }
+                .to_string();
+                self.ssh_private_key = private_key.clone().unwrap_or_default();
+                self.ssh_public_key = public_key.clone().unwrap_or_default();
+                self.ssh_passphrase = passphrase.clone().unwrap_or_default();
+                self.ssh_comment = comment.clone().unwrap_or_default();
+            }
+            SecretData::Document { filename, .. } => {
+                self.document_filename = filename.clone();
+                // Edited and saved again uncompressed
+                match DocumentManager::read_content(&secret.data) {
+                    Ok(content) => self.document_content = content,
+                    Err(e) => self.error_message = format!("Failed to read document: {}", e),
+                }
+            }
+            SecretData::Configuration { variables, .. } => {
+                let mut lines: Vec<String> = variables
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect();
+                lines.sort();
+                self.config_variables = lines.join("\n");
+            }
+            SecretData::SecureNote { title, content, format } => {
+                self.note_title = title.clone();
+                self.note_content = content.clone();
+                self.note_format = format.clone();
+                self.note_preview = *format == NoteFormat::Markdown && !content.is_empty();
+            }
+            SecretData::CreditCard { cardholder, number, expiry_month, expiry_year, cvv, issuer, billing_zip } => {
+                self.card_cardholder = cardholder.clone();
+                self.card_number = number.clone();
+                self.card_expiry = format!("{:02}/{:02}", expiry_month, expiry_year % 100);
+                self.card_cvv = cvv.clone().unwrap_or_default();
+                self.card_issuer = issuer.clone().unwrap_or_default();
+                self.card_billing_zip = billing_zip.clone().unwrap_or_default();
+            }
+            SecretData::ConnectionString { database_type, host, port, database, username, password, .. } => {
+                self.db_type = match database_type {
+                    DatabaseType::PostgreSQL => "PostgreSQL".to_string(),
+                    DatabaseType::MySQL => "MySQL".to_string(),
+                    DatabaseType::SQLite => "SQLite".to_string(),
+                    DatabaseType::MongoDB => "MongoDB".to_string(),
+                    DatabaseType::Redis => "Redis".to_string(),
+                    DatabaseType::Oracle => "Oracle".to_string(),
+                    DatabaseType::SQLServer => "SQL Server".to_string(),
+                    DatabaseType::Custom(name) => name.clone(),
+                };
+                self.db_host = host.clone();
+                self.db_port = port.map(|port| port.to_string()).unwrap_or_default();
+                self.db_name = database.clone();
+                self.db_username = username.clone();
+                self.db_password = password.clone();
+            }
+            _ => {
+                self.error_message = "This secret type cannot be edited here".to_string();
+                return;
+            }
+        }
+        
+        self.selected_secret_type = secret.secret_type.clone();
+        self.edit_secret = Some(secret);
+        self.show_add_secret_dialog = true;
+    }
+    
+    fn cancel_secret_creation(&mut self) {
+        self.show_add_secret_dialog = false;
+        self.clear_secret_form();
+        self.clear_draft(SECRET_DRAFT);
+    }
+    
+    fn clear_secret_form(&mut self) {
+        self.edit_secret = None;
+        // A still-open editor's result is discarded; its file is wiped when it exits
+        self.external_edit = None;
+        // Clear all form fields
+        self.secret_name.clear();
+        self.secret_description.clear();
+        self.secret_tags.clear();
+        self.secret_require_reauth = false;
+        self.api_provider.clear();
+        self.api_key_id.clear();
+        self.api_key.clear();
+        self.api_secret.clear();
+        self.api_environment = "production".to_string();
+        self.api_endpoint.clear();
+        self.ssh_key_type = "RSA".to_string();
+        self.ssh_private_key.clear();
+        self.ssh_public_key.clear();
+        self.ssh_passphrase.clear();
+        self.ssh_comment.clear();
+        self.ssh_key_bits = 4096;
+        self.ssh_keygen_job = None;
+        self.document_filename.clear();
+        self.document_content.clear();
+        self.document_import = None;
+        self.config_variables.clear();
+        self.note_title.clear();
+        self.note_content.clear();
+        self.note_format = NoteFormat::PlainText;
+        self.note_preview = false;
+        self.card_cardholder.clear();
+        self.card_number.clear();
+        self.card_expiry.clear();
+        self.card_cvv.clear();
+        self.card_issuer.clear();
+        self.card_billing_zip.clear();
+        self.show_card_number = false;
+        self.db_type = "PostgreSQL".to_string();
+        self.db_host.clear();
+        self.db_port = "5432".to_string();
+        self.db_name.clear();
+        self.db_username.clear();
+        self.db_password.clear();
+    }
+    
+    /// Text fields of the add-secret dialog, by name, for draft autosave.
+    fn secret_form_fields(&mut self) -> Vec<(&'static str, &mut String)> {
+        vec![
+            ("secret_name", &mut self.secret_name),
+            ("secret_description", &mut self.secret_description),
+            ("secret_tags", &mut self.secret_tags),
+            ("api_provider", &mut self.api_provider),
+            ("api_key_id", &mut self.api_key_id),
+            ("api_key", &mut self.api_key),
+            ("api_secret", &mut self.api_secret),
+            ("api_environment", &mut self.api_environment),
+            ("api_endpoint", &mut self.api_endpoint),
+            ("ssh_key_type", &mut self.ssh_key_type),
+            ("ssh_private_key", &mut self.ssh_private_key),
+            ("ssh_public_key", &mut self.ssh_public_key),
+            ("ssh_passphrase", &mut self.ssh_passphrase),
+            ("ssh_comment", &mut self.ssh_comment),
+            ("document_filename", &mut self.document_filename),
+            ("config_variables", &mut self.config_variables),
+            ("note_title", &mut self.note_title),
+            ("note_content", &mut self.note_content),
+            ("card_cardholder", &mut self.card_cardholder),
+            ("card_number", &mut self.card_number),
+            ("card_expiry", &mut self.card_expiry),
+            ("card_cvv", &mut self.card_cvv),
+            ("card_issuer", &mut self.card_issuer),
+            ("card_billing_zip", &mut self.card_billing_zip),
+            ("db_type", &mut self.db_type),
+            ("db_host", &mut self.db_host),
+            ("db_port", &mut self.db_port),
+            ("db_name", &mut self.db_name),
+            ("db_username", &mut self.db_username),
+            ("db_password", &mut self.db_password),
+        ]
+    }
+    
+    fn open_drafts(&mut self) -> Vec<FormDraft> {
+        let mut drafts = Vec::new();
+        if self.show_add_dialog {
+            drafts.push(FormDraft::Entry {
+                edit_id: self.edit_entry.as_ref().map(|e| e.id.clone()),
+                site: self.entry_site.clone(),
+                urls: self.entry_urls.clone(),
+                username: self.entry_username.clone(),
+                password: self.entry_password.clone(),
+                totp: self.entry_totp.clone(),
+                notes: self.entry_notes.clone(),
+                tags: self.entry_tags.clone(),
+            });
+        }
+        if self.show_add_secret_dialog {
+            let secret_type = self.selected_secret_type.clone();
+            let fields = self
+                .secret_form_fields()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect();
+            let edit_id = self.edit_secret.as_ref().map(|s| s.id.clone());
+            drafts.push(FormDraft::Secret { edit_id, secret_type, fields });
+        }
+        drafts
+    }
+    
+    /// Save open dialogs as encrypted drafts every `draft_autosave_secs`,
+    /// skipping drafts that haven't changed since the last save.
+    fn autosave_drafts(&mut self, ctx: &egui::Context) {
+        if !self.gui_config.autosave_drafts || (!self.show_add_dialog && !self.show_add_secret_dialog) {
+            return;
+        }
+        
+        let interval = Duration::from_secs(self.gui_config.draft_autosave_secs as u64);
+        ctx.request_repaint_after(interval);
+        if self.last_draft_save.elapsed() < interval {
+            return;
+        }
+        self.last_draft_save = Instant::now();
+        
+        for draft in self.open_drafts() {
+            let json = match serde_json::to_string(&draft) {
+                Ok(json) => json,
+                Err(e) => {
+                    self.error_message = format!("Failed to autosave draft: {}", e);
+                    continue;
                 }
+            };
+            if self.saved_drafts.get(draft.name()) == Some(&json) {
+                continue;
             }
-            ui.end_row();
             
-            if !self.document_content.is_empty() {
-                ui.label("File Size:");
-                ui.label(format!("{} bytes", self.document_content.len()));
-                ui.end_row();
+            let storage_mutex = self.storage.clone();
+            let result = self.runtime.block_on(async {
+                let storage_guard = storage_mutex.lock().unwrap();
+                match storage_guard.as_ref() {
+                    Some(storage) => storage.save_draft(draft.name(), &json).await,
+                    None => Ok(()),
+                }
+            });
+            match result {
+                Ok(()) => {
+                    self.saved_drafts.insert(draft.name(), json);
+                }
+                Err(e) => self.error_message = format!("Failed to autosave draft: {}", e),
+            }
+        }
+    }
+    
+    fn clear_draft(&mut self, name: &'static str) {
+        self.saved_drafts.remove(name);
+        self.pending_drafts.retain(|draft| draft.name() != name);
+        
+        let storage_mutex = self.storage.clone();
+        let result = self.runtime.block_on(async {
+            let storage_guard = storage_mutex.lock().unwrap();
+            match storage_guard.as_ref() {
+                Some(storage) => storage.clear_draft(name).await,
+                None => Ok(()),
             }
         });
+        if let Err(e) = result {
+            self.error_message = format!("Failed to clear draft: {}", e);
+        }
     }
     
-    fn show_config_fields(&mut self, ui: &mut egui::Ui) {
-        ui.heading("⚙ Configuration Variables");
-        ui.label("Environment Variables (KEY=VALUE format, one per line):");
-        ui.add(
-            egui::TextEdit::multiline(&mut self.config_variables)
-                .desired_rows(10)
-                .font(egui::TextStyle::Monospace)
-                .hint_text("DATABASE_URL=postgresql://...\nAPI_KEY=your_key_here\nDEBUG=true")
-        );
+    /// Queue drafts left over from a previous session so the user can resume
+    /// or discard them.
+    fn load_drafts(&mut self) {
+        let storage_mutex = self.storage.clone();
+        let mut found = Vec::new();
+        for (name, dialog_open) in [
+            (ENTRY_DRAFT, self.show_add_dialog),
+            (SECRET_DRAFT, self.show_add_secret_dialog),
+        ] {
+            if dialog_open {
+                continue;
+            }
+            let result = self.runtime.block_on(async {
+                let storage_guard = storage_mutex.lock().unwrap();
+                match storage_guard.as_ref() {
+                    Some(storage) => storage.load_draft(name).await,
+                    None => Ok(None),
+                }
+            });
+            match result {
+                Ok(Some(json)) => match serde_json::from_str::<FormDraft>(&json) {
+                    Ok(draft) => found.push(draft),
+                    // Written by an incompatible version; nothing to offer
+                    Err(_) => self.clear_draft(name),
+                },
+                Ok(None) => {}
+                Err(e) => self.error_message = format!("Failed to load draft: {}", e),
+            }
+        }
+        self.pending_drafts = found;
     }
     
-    fn show_note_fields(&mut self, ui: &mut egui::Ui) {
-        ui.heading("📝 Secure Note");
-        egui::Grid::new("note_fields").num_columns(2).show(ui, |ui| {
-            ui.label("Title:");
-            ui.text_edit_singleline(&mut self.note_title);
-            ui.end_row();
-        });
-        
-        ui.add_space(5.0);
-        ui.label("Content:");
-        ui.add(
-            egui::TextEdit::multiline(&mut self.note_content)
-                .desired_rows(12)
-                .hint_text("Enter your secure note content here...")
-        );
+    fn restore_draft(&mut self, draft: FormDraft) {
+        match draft {
+            FormDraft::Entry { edit_id, site, urls, username, password, totp, notes, tags } => {
+                self.edit_entry = edit_id.and_then(|id| self.entries.iter().find(|e| e.id == id).cloned());
+                self.entry_site = site;
+                self.entry_urls = urls;
+                self.entry_username = username;
+                self.entry_password = password;
+                self.entry_totp = totp;
+                self.entry_notes = notes;
+                self.entry_tags = tags;
+                self.entry_require_reauth = self.edit_entry.as_ref().is_some_and(|e| e.require_reauth);
+                self.entry_policy = self.edit_entry.as_ref().and_then(|e| e.password_policy.clone());
+                self.show_password = false;
+                self.show_add_dialog = true;
+            }
+            FormDraft::Secret { edit_id, secret_type, fields } => {
+                // Document bytes are not part of the draft; start from the saved secret
+                if let Some(secret) = edit_id.and_then(|id| self.find_secret(&id)) {
+                    self.start_secret_edit(secret);
+                }
+                self.selected_secret_type = secret_type;
+                let saved: HashMap<String, String> = fields.into_iter().collect();
+                for (name, value) in self.secret_form_fields() {
+                    if let Some(saved_value) = saved.get(name) {
+                        *value = saved_value.clone();
+                    }
+                }
+                self.show_add_secret_dialog = true;
+            }
+        }
     }
     
-    fn show_database_fields(&mut self, ui: &mut egui::Ui) {
-        ui.heading("🔗 Database Connection");
-        egui::Grid::new("database_fields").num_columns(2).show(ui, |ui| {
-            ui.label("Database Type:");
-            egui::ComboBox::from_id_salt("db_type")
-                .selected_text(&self.db_type)
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut self.db_type, "PostgreSQL".to_string(), "PostgreSQL");
-                    ui.selectable_value(&mut self.db_type, "MySQL".to_string(), "MySQL");
-                    ui.selectable_value(&mut self.db_type, "SQLite".to_string(), "SQLite");
-                    ui.selectable_value(&mut self.db_type, "MongoDB".to_string(), "MongoDB");
-                    ui.selectable_value(&mut self.db_type, "Redis".to_string(), "Redis");
+    fn show_draft_restore_dialog(&mut self, ctx: &egui::Context) {
+        let Some(draft) = self.pending_drafts.first() else {
+            return;
+        };
+        let description = draft.describe();
+        
+        let mut resume = false;
+        let mut discard = false;
+        egui::Window::new("Unsaved Draft")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!("You have an unsaved {} from a previous session.", description));
+                ui.horizontal(|ui| {
+                    resume = ui.button("↩ Resume").clicked();
+                    discard = ui.button("🗑 Discard").clicked();
                 });
-            ui.end_row();
-            
-            ui.label("Host:");
-            ui.text_edit_singleline(&mut self.db_host);
-            ui.end_row();
-            
-            ui.label("Port:");
-            ui.text_edit_singleline(&mut self.db_port);
-            ui.end_row();
-            
-            ui.label("Database Name:");
-            ui.text_edit_singleline(&mut self.db_name);
-            ui.end_row();
-            
-            ui.label("Username:");
-            ui.text_edit_singleline(&mut self.db_username);
-            ui.end_row();
-            
-            ui.label("Password:");
-            ui.add(
-                egui::TextEdit::singleline(&mut self.db_password)
-                    .password(true)
-            );
-            ui.end_row();
-        });
+            });
+        
+        if resume {
+            let draft = self.pending_drafts.remove(0);
+            self.restore_draft(draft);
+        } else if discard {
+            let name = self.pending_drafts.remove(0).name();
+            self.clear_draft(name);
+        }
+    }
+}
+
+/// Name of a password list sort order in the "Sort" menu.
+fn sort_field_label(field: SortField) -> &'static str {
+    match field {
+        SortField::Site => "Site",
+        SortField::Username => "Username",
+        SortField::LastUsed => "Recently used",
+        SortField::UpdatedAt => "Recently changed",
+        SortField::CreatedAt => "Recently added",
+    }
+}
+
+/// The file extension for a configuration's own format, when it is one
+/// that can be written out and isn't already `.env`.
+fn config_format_extension(data: &SecretData) -> Option<&'static str> {
+    match data {
+        SecretData::Configuration { format, .. } => match format {
+            ConfigFormat::Json => Some("json"),
+            ConfigFormat::Yaml => Some("yaml"),
+            ConfigFormat::Toml => Some("toml"),
+            ConfigFormat::Properties => Some("properties"),
+            ConfigFormat::EnvFile | ConfigFormat::Xml | ConfigFormat::Custom(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// The fields of a secret that can be copied one by one, as (label, value)
+/// pairs in display order. Empty values are left out.
+fn secret_copy_fields(data: &SecretData) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    let mut add = |label: &str, value: Option<&String>| {
+        if let Some(value) = value.filter(|value| !value.is_empty()) {
+            fields.push((label.to_string(), value.clone()));
+        }
+    };
+    match data {
+        SecretData::Password { username, password, url, .. } => {
+            add("Username", Some(username));
+            add("Password", Some(password));
+            add("URL", url.as_ref());
+        }
+        SecretData::SshKey { private_key, public_key, passphrase, fingerprint, .. } => {
+            add("Private Key", private_key.as_ref());
+            add("Public Key", public_key.as_ref());
+            add("Passphrase", passphrase.as_ref());
+            add("Fingerprint", fingerprint.as_ref());
+        }
+        SecretData::ApiKey { key_id, api_key, api_secret, endpoint_url, .. } => {
+            add("Key ID", Some(key_id));
+            add("API Key", Some(api_key));
+            add("Secret", api_secret.as_ref());
+            add("Endpoint", endpoint_url.as_ref());
+        }
+        SecretData::Token { access_token, refresh_token, token_secret, .. } => {
+            add("Access Token", Some(access_token));
+            add("Refresh Token", refresh_token.as_ref());
+            add("Token Secret", token_secret.as_ref());
+        }
+        SecretData::SecureNote { content, .. } => add("Content", Some(content)),
+        SecretData::Certificate { certificate, private_key, .. } => {
+            add("Certificate", Some(certificate));
+            add("Private Key", private_key.as_ref());
+        }
+        SecretData::ConnectionString { host, port, database, username, password, connection_string, .. } => {
+            let host = match port {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.clone(),
+            };
+            add("Host", Some(&host));
+            add("Database", Some(database));
+            add("Username", Some(username));
+            add("Password", Some(password));
+            add("Connection String", Some(connection_string));
+        }
+        SecretData::CloudCredentials { access_key, secret_key, region, .. } => {
+            add("Access Key", Some(access_key));
+            add("Secret Key", Some(secret_key));
+            add("Region", region.as_ref());
+        }
+        SecretData::CreditCard { cardholder, number, expiry_month, expiry_year, cvv, .. } => {
+            add("Cardholder", Some(cardholder));
+            add("Card Number", Some(number));
+            add("Expiry", Some(&format!("{:02}/{:02}", expiry_month, expiry_year % 100)));
+            add("CVV", cvv.as_ref());
+        }
+        SecretData::Configuration { variables, .. } => {
+            let mut names: Vec<&String> = variables.keys().collect();
+            names.sort();
+            for name in names {
+                add(name, variables.get(name));
+            }
+        }
+        SecretData::Custom { fields: custom, .. } => {
+            let mut names: Vec<&String> = custom.keys().collect();
+            names.sort();
+            for name in names {
+                add(name, custom.get(name));
+            }
+        }
+        SecretData::Document { .. } => {}
     }
+    fields
+}
+
+/// Write `contents` to `path`, readable only by the owner on Unix.
+/// Read, verify and decode a document for the preview window: text as a
+/// string, cut at `DOCUMENT_TEXT_PREVIEW_LIMIT`, and images scaled down to
+/// `DOCUMENT_PREVIEW_MAX_SIDE`. Documents saved without a specific content
+/// type are recognised by name and content.
+fn prepare_document(data: &SecretData) -> Result<PreparedDocument, String> {
+    let SecretData::Document { filename, content_type, .. } = data else {
+        return Err("Not a document".to_string());
+    };
+    let intact = DocumentManager::verify_document(data).map_err(|e| e.to_string())?;
+    let bytes = DocumentManager::read_content(data).map_err(|e| e.to_string())?;
+    let content_type = if content_type == "application/octet-stream" {
+        DocumentManager::detect_content_type(&bytes, filename)
+    } else {
+        content_type.clone()
+    };
     
-    fn save_secret(&mut self) {
-        if self.secret_name.trim().is_empty() {
-            self.error_message = "Secret name is required".to_string();
-            return;
+    let content = if content_type.starts_with("image/") {
+        let image = image::load_from_memory(&bytes).map_err(|e| format!("Unreadable image: {}", e))?;
+        let size = [image.width(), image.height()];
+        let preview = if size[0] > DOCUMENT_PREVIEW_MAX_SIDE || size[1] > DOCUMENT_PREVIEW_MAX_SIDE {
+            image.thumbnail(DOCUMENT_PREVIEW_MAX_SIDE, DOCUMENT_PREVIEW_MAX_SIDE)
+        } else {
+            image
+        };
+        let rgba = preview.to_rgba8();
+        let image = egui::ColorImage::from_rgba_unmultiplied(
+            [rgba.width() as usize, rgba.height() as usize],
+            rgba.as_raw(),
+        );
+        PreparedContent::Image { image, size }
+    } else if is_text_content_type(&content_type) {
+        let mut end = bytes.len().min(DOCUMENT_TEXT_PREVIEW_LIMIT);
+        // Don't cut a multi-byte character in half
+        while end < bytes.len() && end > 0 && (bytes[end] & 0xC0) == 0x80 {
+            end -= 1;
+        }
+        PreparedContent::Text {
+            text: String::from_utf8_lossy(&bytes[..end]).into_owned(),
+            truncated: end < bytes.len(),
         }
+    } else {
+        PreparedContent::Other
+    };
+    Ok(PreparedDocument { intact, content_type, size: bytes.len(), content })
+}
 
-        let secrets_storage = self.secrets_storage.clone();
-        let current_secret_type = self.selected_secret_type.clone();
-        
-        // Create the secret data based on the selected type
-        let secret_data = match current_secret_type {
-            SecretType::ApiKey => {
-                use pwgen_core::api_keys::{ApiKeyProvider, ApiKeyPermissions, RotationInfo, UsageStats};
-                
-                let provider = match self.api_provider.as_str() {
-                    "AWS" => ApiKeyProvider::AWS,
-                    "Google Cloud" => ApiKeyProvider::GCP,
-                    "Azure" => ApiKeyProvider::Azure,
-                    "GitHub" => ApiKeyProvider::GitHub,
-                    "Stripe" => ApiKeyProvider::Stripe,
-                    "Twilio" => ApiKeyProvider::Twilio,
-                    "SendGrid" => ApiKeyProvider::SendGrid,
-                    "Slack" => ApiKeyProvider::Slack,
-                    "Discord" => ApiKeyProvider::Discord,
-                    _ => ApiKeyProvider::Custom(self.api_provider.clone()),
-                };
-                
-                SecretData::ApiKey {
-                    provider,
-                    key_id: self.api_key_id.clone(),
-                    api_key: self.api_key.clone(),
-                    api_secret: if self.api_secret.is_empty() { None } else { Some(self.api_secret.clone()) },
-                    token_type: "Bearer".to_string(),
-                    permissions: ApiKeyPermissions {
-                        read: true,
-                        write: false,
-                        admin: false,
-                        scopes: vec![],
-                        resource_access: std::collections::HashMap::new(),
-                    },
-                    environment: self.api_environment.clone(),
-                    endpoint_url: if self.api_endpoint.is_empty() { None } else { Some(self.api_endpoint.clone()) },
-                    rotation_info: RotationInfo {
-                        auto_rotate: false,
-                        rotation_period_days: None,
-                        last_rotated: None,
-                        next_rotation: None,
-                        rotation_reminder_days: None,
-                    },
-                    usage_stats: UsageStats {
-                        last_used: None,
-                        usage_count: 0,
-                        rate_limit_info: None,
-                        error_count: 0,
-                        last_error: None,
-                    },
-                }
-            },
-            SecretType::SshKey => {
-                use pwgen_core::secrets::SshKeyType;
-                
-                let key_type = match self.ssh_key_type.as_str() {
-                    "RSA" => SshKeyType::Rsa,
-                    "Ed25519" => SshKeyType::Ed25519,
-                    "ECDSA" => SshKeyType::Ecdsa,
-                    "DSA" => SshKeyType::Dsa,
-                    _ => SshKeyType::Rsa,
-                };
-                
-                SecretData::SshKey {
-                    key_type,
-                    private_key: if self.ssh_private_key.is_empty() { None } else { Some(self.ssh_private_key.clone()) },
-                    public_key: if self.ssh_public_key.is_empty() { None } else { Some(self.ssh_public_key.clone()) },
-                    passphrase: if self.ssh_passphrase.is_empty() { None } else { Some(self.ssh_passphrase.clone()) },
-                    comment: if self.ssh_comment.is_empty() { None } else { Some(self.ssh_comment.clone()) },
-                    fingerprint: None, // Could be calculated from the key
-                }
+/// A site icon as a texture-sized image, or `None` if it can't be decoded.
+#[cfg(feature = "favicons")]
+fn decode_site_icon(bytes: &[u8]) -> Option<egui::ColorImage> {
+    let icon = image::load_from_memory(bytes).ok()?.thumbnail(32, 32).to_rgba8();
+    Some(egui::ColorImage::from_rgba_unmultiplied(
+        [icon.width() as usize, icon.height() as usize],
+        icon.as_raw(),
+    ))
+}
+
+/// Where the vault lives unless another one is opened.
+fn default_vault_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("pwgen").join("vault.db"))
+}
+
+/// A vault as listed for choosing: its file name and the folder it is in.
+fn vault_label(path: &std::path::Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    match path.parent().and_then(|dir| dir.file_name()) {
+        Some(dir) => format!("{} ({})", name, dir.to_string_lossy()),
+        None => name.into_owned(),
+    }
+}
+
+/// Paint over a window so its fields can't be read, e.g. while the app is in
+/// the background.
+fn cover_window(ctx: &egui::Context, rect: egui::Rect) {
+    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("privacy_cover")));
+    let visuals = ctx.style().visuals.clone();
+    painter.rect_filled(rect, visuals.window_rounding, visuals.window_fill);
+    painter.text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        "🔒 Hidden while PwGen is in the background",
+        egui::FontId::proportional(14.0),
+        visuals.weak_text_color(),
+    );
+}
+
+/// What a secret type is called in lists and statistics.
+fn secret_type_name(secret_type: &SecretType) -> &str {
+    match secret_type {
+        SecretType::Password => "Passwords",
+        SecretType::SshKey => "SSH keys",
+        SecretType::ApiKey => "API keys",
+        SecretType::Token => "Tokens",
+        SecretType::Document => "Documents",
+        SecretType::Configuration => "Configurations",
+        SecretType::SecureNote => "Secure notes",
+        SecretType::Certificate => "Certificates",
+        SecretType::ConnectionString => "Database connections",
+        SecretType::CloudCredentials => "Cloud credentials",
+        SecretType::CreditCard => "Cards",
+        SecretType::Custom(name) => name,
+    }
+}
+
+/// Whether documents of this MIME type are shown as text.
+fn is_text_content_type(content_type: &str) -> bool {
+    content_type.starts_with("text/")
+        || matches!(
+            content_type,
+            "application/json" | "application/xml" | "application/x-yaml" | "application/toml" | "application/x-pem-file"
+        )
+}
+
+/// Grid rows with the entry rule violations for `field`, under its input.
+fn show_violations(ui: &mut egui::Ui, violations: &[RuleViolation], field: &str) {
+    for violation in violations.iter().filter(|v| v.field == field) {
+        ui.label("");
+        ui.colored_label(egui::Color32::from_rgb(255, 100, 100), format!("⚠ {}", violation.message));
+        ui.end_row();
+    }
+}
+
+/// Existing tags completing the tag being typed, the last one in the comma
+/// separated `text`, most used first. Tags already in `text` are left out.
+fn tag_suggestions<'a>(tag_counts: &'a [(String, usize)], text: &str) -> Vec<&'a (String, usize)> {
+    let (entered, typing) = match text.rsplit_once(',') {
+        Some((entered, typing)) => (entered, typing.trim().to_lowercase()),
+        None => ("", text.trim().to_lowercase()),
+    };
+    if typing.is_empty() {
+        return Vec::new();
+    }
+    let entered: Vec<&str> = entered.split(',').map(str::trim).collect();
+    
+    let mut suggestions: Vec<&(String, usize)> = tag_counts
+        .iter()
+        .filter(|(tag, _)| !entered.contains(&tag.as_str()) && tag.to_lowercase() != typing)
+        .filter(|(tag, _)| tag.to_lowercase().contains(&typing))
+        .collect();
+    // Prefix matches first; the sort is stable, so usage order is kept
+    suggestions.sort_by_key(|(tag, _)| !tag.to_lowercase().starts_with(&typing));
+    suggestions.truncate(6);
+    suggestions
+}
+
+/// Red for error messages, dark enough to read on the light theme.
+fn error_color(ui: &egui::Ui) -> egui::Color32 {
+    if ui.visuals().dark_mode {
+        egui::Color32::from_rgb(255, 100, 100)
+    } else {
+        egui::Color32::from_rgb(190, 30, 30)
+    }
+}
+
+/// Green for success messages, dark enough to read on the light theme.
+fn success_color(ui: &egui::Ui) -> egui::Color32 {
+    if ui.visuals().dark_mode {
+        egui::Color32::from_rgb(100, 255, 100)
+    } else {
+        egui::Color32::from_rgb(20, 130, 20)
+    }
+}
+
+/// Backdrop for the wide logo, whose white lettering would vanish on the
+/// light theme.
+fn logo_background(ui: &egui::Ui) -> egui::Color32 {
+    if ui.visuals().dark_mode {
+        egui::Color32::TRANSPARENT
+    } else {
+        egui::Color32::from_gray(40)
+    }
+}
+
+/// Clickable suggestions that replace the tag being typed in `text`.
+fn show_tag_suggestions(ui: &mut egui::Ui, tag_counts: &[(String, usize)], text: &mut String) {
+    let mut chosen = None;
+    ui.horizontal_wrapped(|ui| {
+        for (tag, count) in tag_suggestions(tag_counts, text) {
+            if ui.small_button(tag).on_hover_text(format!("Used by {} entries", count)).clicked() {
+                chosen = Some(tag.clone());
+            }
+        }
+    });
+    if let Some(tag) = chosen {
+        let entered = text.rsplit_once(',').map(|(entered, _)| format!("{}, ", entered.trim_end()));
+        *text = format!("{}{}, ", entered.unwrap_or_default(), tag);
+    }
+}
+
+/// Who added and last changed an item, or `None` if neither was recorded.
+fn attribution_text(created_by: &Option<String>, modified_by: &Option<String>) -> Option<String> {
+    match (created_by, modified_by) {
+        (Some(created), Some(modified)) if created == modified => {
+            Some(format!("Created and last modified by {}", created))
+        }
+        (Some(created), Some(modified)) => Some(format!("Created by {} · modified by {}", created, modified)),
+        (Some(created), None) => Some(format!("Created by {}", created)),
+        (None, Some(modified)) => Some(format!("Modified by {}", modified)),
+        (None, None) => None,
+    }
+}
+
+/// Provider name as typed in the API key form; `save_secret` maps it back.
+fn api_provider_label(provider: &ApiKeyProvider) -> String {
+    match provider {
+        ApiKeyProvider::AWS => "AWS",
+        ApiKeyProvider::GCP => "Google Cloud",
+        ApiKeyProvider::Azure => "Azure",
+        ApiKeyProvider::GitHub => "GitHub",
+        ApiKeyProvider::GitLab => "GitLab",
+        ApiKeyProvider::DockerHub => "Docker Hub",
+        ApiKeyProvider::Stripe => "Stripe",
+        ApiKeyProvider::Twilio => "Twilio",
+        ApiKeyProvider::SendGrid => "SendGrid",
+        ApiKeyProvider::Slack => "Slack",
+        ApiKeyProvider::Discord => "Discord",
+        ApiKeyProvider::OpenAI => "OpenAI",
+        ApiKeyProvider::Anthropic => "Anthropic",
+        ApiKeyProvider::Generic => "Generic",
+        ApiKeyProvider::Custom(name) => name,
+    }
+    .to_string()
+}
+
+/// Carry over the parts of an edited secret that the form doesn't show:
+/// API key permissions, rotation and usage, note and config formats, SSL
+/// settings, and the document or connection string when nothing they are
+/// derived from changed.
+fn preserve_unedited_secret_data(original: &SecretData, edited: &mut SecretData) {
+    match (original, edited) {
+        (
+            SecretData::ApiKey { token_type, permissions, rotation_info, usage_stats, .. },
+            SecretData::ApiKey {
+                token_type: edited_token_type,
+                permissions: edited_permissions,
+                rotation_info: edited_rotation_info,
+                usage_stats: edited_usage_stats,
+                ..
             },
-            SecretType::Document => {
-                use sha2::{Sha256, Digest};
-                
-                let mut hasher = Sha256::new();
-                hasher.update(&self.document_content);
-                let checksum = format!("{:x}", hasher.finalize());
-                
-                SecretData::Document {
-                    filename: self.document_filename.clone(),
-                    content_type: "application/octet-stream".to_string(),
-                    content: self.document_content.clone(),
-                    checksum,
-                }
+        ) => {
+            *edited_token_type = token_type.clone();
+            *edited_permissions = permissions.clone();
+            *edited_rotation_info = rotation_info.clone();
+            *edited_usage_stats = usage_stats.clone();
+        }
+        (
+            SecretData::SshKey { public_key: original_public_key, fingerprint, .. },
+            SecretData::SshKey { public_key, fingerprint: edited_fingerprint, .. },
+        ) if public_key == original_public_key => {
+            *edited_fingerprint = fingerprint.clone();
+        }
+        (
+            SecretData::Document {
+                content_type,
+                content: original_content,
+                checksum: original_checksum,
+                compression: original_compression,
+                ..
             },
-            SecretType::Configuration => {
-                use pwgen_core::secrets::ConfigFormat;
-                
-                // Parse environment variables from KEY=VALUE format
-                let mut variables = std::collections::HashMap::new();
-                for line in self.config_variables.lines() {
-                    if let Some((key, value)) = line.split_once('=') {
-                        variables.insert(key.trim().to_string(), value.trim().to_string());
-                    }
-                }
-                
-                SecretData::Configuration {
-                    format: ConfigFormat::EnvFile,
-                    variables,
-                    template: None,
-                }
+            SecretData::Document { content_type: edited_content_type, content, checksum, compression, .. },
+        ) => {
+            *edited_content_type = content_type.clone();
+            // A draft restored without the file keeps the stored content
+            if content.is_empty() {
+                *content = original_content.clone();
+                *checksum = original_checksum.clone();
+                *compression = original_compression.clone();
+            }
+        }
+        (
+            SecretData::Configuration { format, template, variables: original_variables, raw_variables },
+            SecretData::Configuration {
+                format: edited_format,
+                template: edited_template,
+                variables,
+                raw_variables: edited_raw_variables,
             },
-            SecretType::SecureNote => {
-                use pwgen_core::secrets::NoteFormat;
-                
-                SecretData::SecureNote {
-                    title: self.note_title.clone(),
-                    content: self.note_content.clone(),
-                    format: NoteFormat::PlainText,
-                }
+        ) => {
+            *edited_format = format.clone();
+            *edited_template = template.clone();
+            // Uninterpolated values only still apply to unchanged variables
+            *edited_raw_variables = raw_variables
+                .iter()
+                .filter(|(name, _)| variables.get(*name) == original_variables.get(*name))
+                .map(|(name, raw)| (name.clone(), raw.clone()))
+                .collect();
+        }
+        (
+            SecretData::ConnectionString {
+                database_type: original_type,
+                host: original_host,
+                port: original_port,
+                database: original_database,
+                username: original_username,
+                password: original_password,
+                connection_string: original_connection_string,
+                ssl_config,
             },
-            SecretType::ConnectionString => {
-                use pwgen_core::secrets::DatabaseType;
-                
-                let database_type = match self.db_type.as_str() {
-                    "PostgreSQL" => DatabaseType::PostgreSQL,
-                    "MySQL" => DatabaseType::MySQL,
-                    "SQLite" => DatabaseType::SQLite,
-                    "MongoDB" => DatabaseType::MongoDB,
-                    "Redis" => DatabaseType::Redis,
-                    "Oracle" => DatabaseType::Oracle,
-                    "SQL Server" => DatabaseType::SQLServer,
-                    _ => DatabaseType::Custom(self.db_type.clone()),
-                };
-                
-                let port = self.db_port.parse::<u16>().unwrap_or(5432);
-                let connection_string = format!(
-                    "{}://{}:{}@{}:{}/{}",
-                    self.db_type.to_lowercase(),
-                    self.db_username,
-                    self.db_password,
-                    self.db_host,
-                    port,
-                    self.db_name
-                );
-                
-                SecretData::ConnectionString {
-                    database_type,
-                    host: self.db_host.clone(),
-                    port: Some(port),
-                    database: self.db_name.clone(),
-                    username: self.db_username.clone(),
-                    password: self.db_password.clone(),
-                    connection_string,
-                    ssl_config: None,
-                }
+            SecretData::ConnectionString {
+                database_type,
+                host,
+                port,
+                database,
+                username,
+                password,
+                connection_string,
+                ssl_config: edited_ssl_config,
             },
-            _ => {
-                self.error_message = "Unsupported secret type".to_string();
-                return;
+        ) => {
+            *edited_ssl_config = ssl_config.clone();
+            // An empty port field is saved as the default port; a stored
+            // connection without one hasn't changed because of that
+            let unchanged = (&*database_type, &*host, &*database, &*username, &*password)
+                == (original_type, original_host, original_database, original_username, original_password)
+                && (port == original_port || original_port.is_none());
+            if unchanged {
+                *port = *original_port;
+                *connection_string = original_connection_string.clone();
             }
-        };
+        }
+        _ => {}
+    }
+}
 
-        // Parse tags
-        let tags: Vec<String> = self.secret_tags
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
+/// Ask clipboard managers not to record what is being copied, using each
+/// platform's convention for password managers.
+#[cfg(feature = "clipboard")]
+fn exclude_from_history(set: arboard::Set<'_>) -> arboard::Set<'_> {
+    #[cfg(target_os = "macos")]
+    use arboard::SetExtApple as _;
+    #[cfg(all(unix, not(target_os = "macos")))]
+    use arboard::SetExtLinux as _;
+    #[cfg(windows)]
+    use arboard::SetExtWindows as _;
+    set.exclude_from_history()
+}
 
-        // Create the DecryptedSecretEntry
-        let secret_entry = DecryptedSecretEntry {
-            id: uuid::Uuid::new_v4().to_string(),
-            name: self.secret_name.clone(),
-            description: if self.secret_description.is_empty() { None } else { Some(self.secret_description.clone()) },
-            secret_type: current_secret_type,
-            data: secret_data,
-            metadata: pwgen_core::secrets::SecretMetadata::default(),
-            tags,
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-            last_accessed: None,
-            expires_at: None,
-            favorite: false,
-        };
+/// A run of Markdown text in one style.
+#[derive(Default, Clone)]
+struct MarkdownSpan {
+    text: String,
+    strong: bool,
+    emphasis: bool,
+    strikethrough: bool,
+    code: bool,
+    link: Option<String>,
+}
 
-        // Save the secret to storage
-        let runtime = self.runtime.clone();
-        let secret_entry_clone = secret_entry.clone();
+/// One block of a Markdown note, as `show_markdown` draws it.
+enum MarkdownBlock {
+    Heading(usize, Vec<MarkdownSpan>),
+    /// A paragraph or list item, indented by the lists and quotes it's in
+    Text {
+        indent: usize,
+        marker: Option<String>,
+        spans: Vec<MarkdownSpan>,
+    },
+    Code(String),
+    Rule,
+}
+
+/// Collects `MarkdownBlock`s from pulldown-cmark events.
+#[derive(Default)]
+struct MarkdownBlocks {
+    blocks: Vec<MarkdownBlock>,
+    spans: Vec<MarkdownSpan>,
+    style: MarkdownSpan,
+    heading: Option<usize>,
+    marker: Option<String>,
+    // Next number of each open list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    quotes: usize,
+    code: Option<String>,
+}
+
+impl MarkdownBlocks {
+    fn parse(markdown: &str) -> Vec<MarkdownBlock> {
+        use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
         
-        let _ = runtime.block_on(async {
-            let secrets_storage_guard = secrets_storage.lock().unwrap();
-            if let Some(storage) = secrets_storage_guard.as_ref() {
-                if let Err(e) = storage.add_secret(&secret_entry_clone).await {
-                    eprintln!("Failed to save secret: {}", e);
-                    return Err(e);
+        let mut builder = Self::default();
+        for event in Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH) {
+            match event {
+                Event::Start(Tag::Heading { level, .. }) => {
+                    builder.finish_block();
+                    builder.heading = Some(level as usize);
+                }
+                // A list item's first paragraph goes next to its marker
+                Event::Start(Tag::Paragraph) if !builder.spans.is_empty() => builder.finish_block(),
+                Event::Start(Tag::List(first)) => {
+                    builder.finish_block();
+                    builder.lists.push(first);
+                }
+                Event::Start(Tag::Item) => {
+                    builder.finish_block();
+                    builder.marker = Some(match builder.lists.last_mut() {
+                        Some(Some(number)) => {
+                            *number += 1;
+                            format!("{}.", *number - 1)
+                        }
+                        _ => "•".to_string(),
+                    });
+                }
+                Event::Start(Tag::BlockQuote(_)) => {
+                    builder.finish_block();
+                    builder.quotes += 1;
+                }
+                Event::Start(Tag::CodeBlock(_)) => {
+                    builder.finish_block();
+                    builder.code = Some(String::new());
+                }
+                Event::Start(Tag::Emphasis) => builder.style.emphasis = true,
+                Event::Start(Tag::Strong) => builder.style.strong = true,
+                Event::Start(Tag::Strikethrough) => builder.style.strikethrough = true,
+                Event::Start(Tag::Link { dest_url, .. }) => builder.style.link = Some(dest_url.to_string()),
+                Event::End(TagEnd::Paragraph | TagEnd::Heading(_) | TagEnd::Item | TagEnd::HtmlBlock) => {
+                    builder.finish_block();
+                }
+                Event::End(TagEnd::List(_)) => {
+                    builder.finish_block();
+                    builder.lists.pop();
+                }
+                Event::End(TagEnd::BlockQuote(_)) => {
+                    builder.finish_block();
+                    builder.quotes = builder.quotes.saturating_sub(1);
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some(code) = builder.code.take() {
+                        builder.blocks.push(MarkdownBlock::Code(code));
+                    }
                 }
+                Event::End(TagEnd::Emphasis) => builder.style.emphasis = false,
+                Event::End(TagEnd::Strong) => builder.style.strong = false,
+                Event::End(TagEnd::Strikethrough) => builder.style.strikethrough = false,
+                Event::End(TagEnd::Link) => builder.style.link = None,
+                Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => builder.push_text(&text, false),
+                Event::Code(text) => builder.push_text(&text, true),
+                Event::SoftBreak => builder.push_text(" ", false),
+                Event::HardBreak => builder.push_text("\n", false),
+                Event::Rule => {
+                    builder.finish_block();
+                    builder.blocks.push(MarkdownBlock::Rule);
+                }
+                _ => {}
             }
-            Ok(())
-        });
-
-        // Reload secrets from database
-        self.load_secrets();
-
-        self.success_message = "Secret saved successfully!".to_string();
-        self.error_message.clear();
-        self.cancel_secret_creation();
+        }
+        builder.finish_block();
+        builder.blocks
     }
     
-    #[allow(dead_code)]
-    fn refresh_secrets_list(&mut self) {
-        self.filtered_secrets = self.secrets.clone();
+    fn push_text(&mut self, text: &str, code: bool) {
+        if let Some(block) = &mut self.code {
+            block.push_str(text);
+            return;
+        }
+        self.spans.push(MarkdownSpan {
+            text: text.to_string(),
+            code,
+            ..self.style.clone()
+        });
     }
     
-    fn cancel_secret_creation(&mut self) {
-        self.show_add_secret_dialog = false;
-        // Clear all form fields
-        self.secret_name.clear();
-        self.secret_description.clear();
-        self.secret_tags.clear();
-        self.api_provider.clear();
-        self.api_key_id.clear();
-        self.api_key.clear();
-        self.api_secret.clear();
-        self.api_environment = "production".to_string();
-        self.api_endpoint.clear();
-        self.ssh_key_type = "RSA".to_string();
-        self.ssh_private_key.clear();
-        self.ssh_public_key.clear();
-        self.ssh_passphrase.clear();
-        self.ssh_comment.clear();
-        self.document_filename.clear();
-        self.document_content.clear();
-        self.config_variables.clear();
-        self.note_title.clear();
-        self.note_content.clear();
-        self.db_type = "PostgreSQL".to_string();
-        self.db_host.clear();
-        self.db_port = "5432".to_string();
-        self.db_name.clear();
-        self.db_username.clear();
-        self.db_password.clear();
+    fn finish_block(&mut self) {
+        let spans = std::mem::take(&mut self.spans);
+        let marker = self.marker.take();
+        if let Some(level) = self.heading.take() {
+            self.blocks.push(MarkdownBlock::Heading(level, spans));
+        } else if !spans.is_empty() || marker.is_some() {
+            self.blocks.push(MarkdownBlock::Text {
+                indent: self.lists.len() + self.quotes,
+                marker,
+                spans,
+            });
+        }
+    }
+}
+
+/// Draw a Markdown note: headings, paragraphs, lists, quotes, code blocks
+/// and links. Returns the link clicked, if any; only web and mail links
+/// can be clicked.
+fn show_markdown(ui: &mut egui::Ui, markdown: &str) -> Option<String> {
+    let mut clicked = None;
+    for block in MarkdownBlocks::parse(markdown) {
+        match block {
+            MarkdownBlock::Heading(level, spans) => {
+                let size = match level {
+                    1 => 22.0,
+                    2 => 19.0,
+                    3 => 16.0,
+                    _ => 14.0,
+                };
+                ui.add_space(4.0);
+                show_markdown_line(ui, 0, None, &spans, Some(size), &mut clicked);
+            }
+            MarkdownBlock::Text { indent, marker, spans } => {
+                show_markdown_line(ui, indent, marker, &spans, None, &mut clicked);
+            }
+            MarkdownBlock::Code(code) => {
+                egui::Frame::none()
+                    .fill(ui.visuals().extreme_bg_color)
+                    .rounding(4.0)
+                    .inner_margin(6.0)
+                    .show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        ui.label(egui::RichText::new(code.trim_end_matches('\n')).monospace());
+                    });
+            }
+            MarkdownBlock::Rule => {
+                ui.separator();
+            }
+        }
+        ui.add_space(2.0);
+    }
+    clicked
+}
+
+fn show_markdown_line(
+    ui: &mut egui::Ui,
+    indent: usize,
+    marker: Option<String>,
+    spans: &[MarkdownSpan],
+    heading_size: Option<f32>,
+    clicked: &mut Option<String>,
+) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        ui.add_space(indent as f32 * 16.0);
+        if let Some(marker) = marker {
+            ui.label(format!("{} ", marker));
+        }
+        for span in spans {
+            let mut text = egui::RichText::new(&span.text);
+            if let Some(size) = heading_size {
+                text = text.size(size).strong();
+            }
+            if span.strong {
+                text = text.strong();
+            }
+            if span.emphasis {
+                text = text.italics();
+            }
+            if span.strikethrough {
+                text = text.strikethrough();
+            }
+            if span.code {
+                text = text.code();
+            }
+            match &span.link {
+                Some(url) if is_web_link(url) => {
+                    if ui.link(text).on_hover_text(url).clicked() {
+                        *clicked = Some(url.clone());
+                    }
+                }
+                Some(url) => {
+                    ui.label(text.underline()).on_hover_text(url);
+                }
+                None => {
+                    ui.label(text);
+                }
+            }
+        }
+    });
+}
+
+fn is_web_link(url: &str) -> bool {
+    let url = url.to_ascii_lowercase();
+    ["http://", "https://", "mailto:"].iter().any(|scheme| url.starts_with(scheme))
+}
+
+/// Big monospace rendering of a password for reading aloud. Digits and symbols
+/// get their own colors; `chunked` adds a gap after every 4 characters.
+fn large_reveal_job(text: &str, chunked: bool) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    let font = egui::FontId::monospace(40.0);
+    for (i, c) in text.chars().enumerate() {
+        if chunked && i > 0 && i % 4 == 0 {
+            job.append("  ", 0.0, egui::TextFormat::simple(font.clone(), egui::Color32::TRANSPARENT));
+        }
+        let color = if c.is_ascii_digit() {
+            egui::Color32::from_rgb(100, 180, 255)
+        } else if !c.is_alphanumeric() {
+            egui::Color32::from_rgb(255, 170, 60)
+        } else if c.is_uppercase() {
+            egui::Color32::from_rgb(120, 230, 120)
+        } else {
+            egui::Color32::from_gray(230)
+        };
+        job.append(&c.to_string(), 0.0, egui::TextFormat::simple(font.clone(), color));
     }
+    job
 }
 
 fn setup_custom_fonts(ctx: &egui::Context) {
@@ -3609,16 +10745,192 @@ fn load_logo_square(ctx: &egui::Context) -> Option<egui::TextureHandle> {
     }
 }
 
-fn create_tray_icon() -> Option<()> {
-    // TODO: Implement system tray - currently disabled due to missing system dependencies
-    // For now, return None to disable tray functionality
-    None
+// Ids of the tray menu items
+#[cfg(feature = "tray")]
+const TRAY_SHOW_HIDE: &str = "show-hide";
+#[cfg(feature = "tray")]
+const TRAY_LOCK: &str = "lock";
+#[cfg(feature = "tray")]
+const TRAY_GENERATE: &str = "generate";
+#[cfg(feature = "tray")]
+const TRAY_QUIT: &str = "quit";
+
+/// The system tray icon and the menu events it sends to the UI thread.
+#[cfg(feature = "tray")]
+struct Tray {
+    events: mpsc::Receiver<MenuEvent>,
+    // On Linux the icon lives on its GTK thread, which polls this flag
+    #[cfg(target_os = "linux")]
+    visible: Arc<AtomicBool>,
+    #[cfg(not(target_os = "linux"))]
+    icon: tray_icon::TrayIcon,
+    window_hidden: bool,
+}
+
+#[cfg(feature = "tray")]
+impl Tray {
+    fn set_visible(&self, visible: bool) {
+        #[cfg(target_os = "linux")]
+        self.visible.store(visible, Ordering::Relaxed);
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = self.icon.set_visible(visible);
+        }
+    }
+    
+    fn hide_window(&mut self, ctx: &egui::Context) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        self.window_hidden = true;
+    }
+    
+    fn show_window(&mut self, ctx: &egui::Context) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        self.window_hidden = false;
+    }
+}
+
+#[cfg(feature = "tray")]
+fn build_tray_icon() -> Result<tray_icon::TrayIcon, String> {
+    use tray_icon::menu::{Menu, MenuItem, PredefinedMenuItem};
+    
+    let menu = Menu::new();
+    menu.append_items(&[
+        &MenuItem::with_id(TRAY_SHOW_HIDE, "Show/Hide", true, None),
+        &MenuItem::with_id(TRAY_LOCK, "Lock Vault", true, None),
+        &MenuItem::with_id(TRAY_GENERATE, "Generate Password", true, None),
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id(TRAY_QUIT, "Quit", true, None),
+    ])
+    .map_err(|e| e.to_string())?;
+    
+    let icon = eframe::icon_data::from_png_bytes(include_bytes!("../icons/PWGenLogo.png"))
+        .map_err(|e| e.to_string())?;
+    let icon = tray_icon::Icon::from_rgba(icon.rgba, icon.width, icon.height).map_err(|e| e.to_string())?;
+    
+    tray_icon::TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip("PwGen")
+        .with_icon(icon)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Create the tray icon. Menu events are forwarded to the returned `Tray`
+/// and wake the UI, which may be hidden. `None` if the icon couldn't be
+/// created, e.g. without a tray on the desktop.
+#[cfg(feature = "tray")]
+fn create_tray_icon(ctx: &egui::Context) -> Option<Tray> {
+    let (sender, events) = mpsc::channel();
+    let repaint_ctx = ctx.clone();
+    MenuEvent::set_event_handler(Some(move |event| {
+        let _ = sender.send(event);
+        repaint_ctx.request_repaint();
+    }));
+    
+    // tray-icon needs a GTK main loop on Linux, and the icon must be created
+    // on the thread that runs it
+    #[cfg(target_os = "linux")]
+    {
+        let visible = Arc::new(AtomicBool::new(true));
+        let icon_visible = visible.clone();
+        let (created, result) = mpsc::channel();
+        std::thread::spawn(move || {
+            if let Err(e) = gtk::init() {
+                let _ = created.send(Err(e.to_string()));
+                return;
+            }
+            let icon = match build_tray_icon() {
+                Ok(icon) => icon,
+                Err(e) => {
+                    let _ = created.send(Err(e));
+                    return;
+                }
+            };
+            let _ = created.send(Ok(()));
+            
+            let mut shown = true;
+            gtk::glib::timeout_add_local(Duration::from_millis(250), move || {
+                let wanted = icon_visible.load(Ordering::Relaxed);
+                if wanted != shown {
+                    let _ = icon.set_visible(wanted);
+                    shown = wanted;
+                }
+                gtk::glib::ControlFlow::Continue
+            });
+            gtk::main();
+        });
+        
+        match result.recv() {
+            Ok(Ok(())) => Some(Tray { events, visible, window_hidden: false }),
+            Ok(Err(e)) => {
+                eprintln!("Failed to create tray icon: {}", e);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+    
+    #[cfg(not(target_os = "linux"))]
+    match build_tray_icon() {
+        Ok(icon) => Some(Tray { events, icon, window_hidden: false }),
+        Err(e) => {
+            eprintln!("Failed to create tray icon: {}", e);
+            None
+        }
+    }
 }
 
-// Reserved for when the system tray feature is enabled (see create_tray_icon).
-#[allow(dead_code)]
-fn handle_tray_event(_app: &mut PwGenApp, _ctx: &egui::Context) {
-    // TODO: Implement tray event handling when system tray is enabled
+/// Apply tray menu clicks, and hide the window to the tray when it is
+/// minimized or closed with "minimize to tray" on. File > Exit and the
+/// tray's Quit turn that off first, so they really close.
+#[cfg(feature = "tray")]
+fn handle_tray_event(app: &mut PwGenApp, ctx: &egui::Context) {
+    let Some(mut tray) = app.tray.take() else {
+        return;
+    };
+    tray.set_visible(app.show_system_tray);
+    
+    let events: Vec<MenuEvent> = tray.events.try_iter().collect();
+    for event in events {
+        match event.id.0.as_str() {
+            TRAY_SHOW_HIDE => {
+                if tray.window_hidden {
+                    tray.show_window(ctx);
+                } else {
+                    tray.hide_window(ctx);
+                }
+            }
+            TRAY_LOCK if app.screen == Screen::Main => app.lock_vault(),
+            TRAY_GENERATE => {
+                let previous = app.generated_password.clone();
+                app.generate_password();
+                if app.generated_password != previous {
+                    let password = app.generated_password.clone();
+                    app.copy_to_clipboard(&password);
+                    app.success_message = app.copied_message("Generated password");
+                }
+            }
+            TRAY_QUIT => {
+                app.minimize_to_tray = false;
+                tray.show_window(ctx);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            _ => {}
+        }
+    }
+    
+    if app.show_system_tray && app.minimize_to_tray && !tray.window_hidden {
+        if ctx.input(|i| i.viewport().close_requested()) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            tray.hide_window(ctx);
+        } else if ctx.input(|i| i.viewport().minimized.unwrap_or(false)) {
+            tray.hide_window(ctx);
+        }
+    }
+    
+    app.tray = Some(tray);
 }
 
 fn main() -> eframe::Result<()> {